@@ -0,0 +1,11 @@
+-- Migration: Tamper-evident hash chain for audit logs
+-- Each entry stores the hash of the previous entry and a hash over its own
+-- fields plus that previous hash. Rows written before this migration have
+-- NULL hashes and are not part of the chain.
+
+-- Strict insertion order for the chain; timestamps alone can tie.
+ALTER TABLE audit_logs ADD COLUMN chain_seq BIGSERIAL;
+ALTER TABLE audit_logs ADD COLUMN prev_hash CHAR(64);
+ALTER TABLE audit_logs ADD COLUMN entry_hash CHAR(64);
+
+CREATE UNIQUE INDEX idx_audit_logs_chain_seq ON audit_logs(chain_seq);
@@ -0,0 +1,13 @@
+-- Migration: Allow audit log retention purges
+-- Audit logs stay immutable, but the retention job may delete old rows by
+-- setting `audit.allow_purge = 'on'` for its own transaction (SET LOCAL).
+
+CREATE OR REPLACE FUNCTION prevent_audit_log_modification()
+RETURNS TRIGGER AS $$
+BEGIN
+    IF TG_OP = 'DELETE' AND current_setting('audit.allow_purge', true) = 'on' THEN
+        RETURN OLD;
+    END IF;
+    RAISE EXCEPTION 'Audit logs are immutable and cannot be modified or deleted';
+END;
+$$ LANGUAGE plpgsql;
@@ -0,0 +1,11 @@
+-- Migration: Idempotency keys for withdrawal creation
+-- A client retry carrying the same `Idempotency-Key` must resolve to the
+-- original withdrawal instead of opening a second anchor transaction.
+-- Keys are scoped per user.
+
+ALTER TABLE withdrawals
+    ADD COLUMN IF NOT EXISTS idempotency_key VARCHAR(255);
+
+CREATE UNIQUE INDEX IF NOT EXISTS idx_withdrawals_user_idempotency_key
+    ON withdrawals(user_id, idempotency_key)
+    WHERE idempotency_key IS NOT NULL;
@@ -0,0 +1,6 @@
+-- Migration: Keyset pagination index for withdrawal history
+-- `GET /withdrawals` pages a user's withdrawals newest-first on
+-- (created_at, id); this index serves both the ordering and the cursor seek.
+
+CREATE INDEX IF NOT EXISTS idx_withdrawals_user_created_id
+    ON withdrawals(user_id, created_at DESC, id DESC);
@@ -0,0 +1,12 @@
+-- Migration: Scheduled reconciliation of withdrawal status with the anchor
+-- The reconciler polls non-terminal withdrawals and backs off on ones whose
+-- anchor status hasn't moved, tracked per row.
+
+ALTER TABLE withdrawals
+    ADD COLUMN IF NOT EXISTS reconcile_attempts INTEGER NOT NULL DEFAULT 0,
+    ADD COLUMN IF NOT EXISTS next_reconcile_at TIMESTAMP WITH TIME ZONE;
+
+CREATE INDEX IF NOT EXISTS idx_withdrawals_reconcile_due
+    ON withdrawals(next_reconcile_at NULLS FIRST, created_at)
+    WHERE anchor_tx_id IS NOT NULL
+      AND status NOT IN ('completed', 'failed', 'refunded');
@@ -0,0 +1,22 @@
+-- Migration: Ledger of received anchor webhooks
+-- Every verified webhook is recorded before it is processed so that one we
+-- failed to apply can be replayed later instead of relying on the anchor to
+-- retry. A webhook is identified by the anchor transaction and the status it
+-- reports, which also makes redelivery of the same event a no-op.
+
+CREATE TABLE IF NOT EXISTS anchor_webhooks (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    transaction_id VARCHAR(255) NOT NULL,
+    status VARCHAR(50) NOT NULL,
+    message TEXT,
+    processing_status VARCHAR(20) NOT NULL DEFAULT 'pending',
+    attempts INTEGER NOT NULL DEFAULT 0,
+    last_error TEXT,
+    received_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+    processed_at TIMESTAMP WITH TIME ZONE,
+    UNIQUE (transaction_id, status)
+);
+
+CREATE INDEX IF NOT EXISTS idx_anchor_webhooks_unprocessed
+    ON anchor_webhooks(received_at)
+    WHERE processing_status IN ('pending', 'failed');
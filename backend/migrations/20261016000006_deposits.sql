@@ -0,0 +1,19 @@
+-- Migration: Deposits tracked against anchor transactions
+-- Anchor webhooks carry only the anchor's transaction ID, so it is indexed on
+-- both deposits and withdrawals to route each callback to the right table.
+
+CREATE TABLE IF NOT EXISTS deposits (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    user_id VARCHAR(255) NOT NULL,
+    amount BIGINT NOT NULL,
+    asset VARCHAR(56) NOT NULL,
+    status VARCHAR(50) NOT NULL DEFAULT 'pending',
+    anchor_tx_id VARCHAR(255),
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
+    updated_at TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
+    FOREIGN KEY (user_id) REFERENCES users(user_id)
+);
+
+CREATE INDEX IF NOT EXISTS idx_deposits_user_id ON deposits(user_id);
+CREATE INDEX IF NOT EXISTS idx_deposits_anchor_tx_id ON deposits(anchor_tx_id);
+CREATE INDEX IF NOT EXISTS idx_withdrawals_anchor_tx_id ON withdrawals(anchor_tx_id);
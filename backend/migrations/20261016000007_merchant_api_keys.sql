@@ -0,0 +1,17 @@
+-- Migration: Merchant-scoped API keys
+-- Server-to-server merchant integrations authenticate with an `X-API-Key`
+-- header instead of a user JWT. Only a SHA-256 hash of each key is stored;
+-- the plaintext is returned once when the key is issued.
+
+CREATE TABLE IF NOT EXISTS merchant_api_keys (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    merchant_id VARCHAR(255) NOT NULL,
+    key_prefix VARCHAR(16) NOT NULL,
+    key_hash CHAR(64) UNIQUE NOT NULL,
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
+    last_used_at TIMESTAMP WITH TIME ZONE,
+    revoked_at TIMESTAMP WITH TIME ZONE,
+    FOREIGN KEY (merchant_id) REFERENCES merchants(merchant_id)
+);
+
+CREATE INDEX IF NOT EXISTS idx_merchant_api_keys_merchant_id ON merchant_api_keys(merchant_id);
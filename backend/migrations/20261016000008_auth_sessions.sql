@@ -0,0 +1,20 @@
+-- Migration: Refresh-token sessions
+-- Each login starts a session (a refresh-token family). Refresh tokens carry
+-- the session id and a per-rotation token id; only the latest token id is
+-- accepted, so revoking the session, or replaying a rotated-out token,
+-- invalidates the whole family.
+
+CREATE TABLE IF NOT EXISTS auth_sessions (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    user_id VARCHAR(255) NOT NULL,
+    device VARCHAR(255),
+    current_token_id UUID NOT NULL DEFAULT gen_random_uuid(),
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+    last_used_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+    expires_at TIMESTAMP WITH TIME ZONE NOT NULL,
+    revoked_at TIMESTAMP WITH TIME ZONE,
+    FOREIGN KEY (user_id) REFERENCES users(user_id)
+);
+
+CREATE INDEX IF NOT EXISTS idx_auth_sessions_active_user
+    ON auth_sessions(user_id) WHERE revoked_at IS NULL;
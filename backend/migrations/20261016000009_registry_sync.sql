@@ -0,0 +1,13 @@
+-- Migration: On-chain registry sync
+-- Tracks how far the registry contract's events have been mirrored into
+-- `merchants` and `users`.
+
+CREATE TABLE IF NOT EXISTS registry_sync_state (
+    contract_id VARCHAR(56) PRIMARY KEY,
+    last_ledger BIGINT NOT NULL,
+    updated_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW()
+);
+
+-- Users registered on-chain hold their own wallet and have no PIN until
+-- they set one.
+ALTER TABLE users ALTER COLUMN pin_hash DROP NOT NULL;
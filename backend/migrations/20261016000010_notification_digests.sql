@@ -0,0 +1,22 @@
+-- Migration: Notification digests
+-- Non-urgent notifications are held per user and delivered together as one
+-- digest when the user's window closes.
+
+CREATE TABLE IF NOT EXISTS pending_notifications (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    -- Notification job the entry came from, so a retried job isn't held twice
+    job_id UUID NOT NULL UNIQUE,
+    user_id VARCHAR(255) NOT NULL,
+    type VARCHAR(50) NOT NULL,
+    message TEXT NOT NULL,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_pending_notifications_user
+    ON pending_notifications(user_id, created_at);
+
+-- One row per user with an open window; its digest job is due at `due_at`.
+CREATE TABLE IF NOT EXISTS notification_digest_windows (
+    user_id VARCHAR(255) PRIMARY KEY,
+    due_at TIMESTAMP WITH TIME ZONE NOT NULL
+);
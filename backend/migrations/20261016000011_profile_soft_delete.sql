@@ -0,0 +1,8 @@
+-- Migration: Profile soft delete
+-- Deleted profiles are kept, hidden from reads, for a recovery window and
+-- then purged.
+
+ALTER TABLE user_profiles ADD COLUMN IF NOT EXISTS deleted_at TIMESTAMPTZ;
+
+CREATE INDEX IF NOT EXISTS idx_user_profiles_deleted_at
+    ON user_profiles(deleted_at) WHERE deleted_at IS NOT NULL;
@@ -0,0 +1,5 @@
+-- Migration: Profile version
+-- Bumped on every profile write so concurrent updates can detect a stale
+-- read instead of overwriting each other.
+
+ALTER TABLE user_profiles ADD COLUMN IF NOT EXISTS version INTEGER NOT NULL DEFAULT 1;
@@ -0,0 +1,12 @@
+-- Migration: Idempotent blockchain transaction submissions
+-- A `BlockchainTx` job reserves its idempotency key here before submitting,
+-- so a retry of a job that may already have reached the chain checks for
+-- that transaction instead of sending a second one.
+
+CREATE TABLE IF NOT EXISTS blockchain_tx_submissions (
+    idempotency_key VARCHAR(64) PRIMARY KEY,
+    -- NULL until the submission is known to have been accepted
+    tx_hash VARCHAR(128),
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+    submitted_at TIMESTAMP WITH TIME ZONE
+);
@@ -0,0 +1,14 @@
+-- Migration: Merchant settlement webhooks
+-- A merchant's webhook is POSTed a `payment.settled` event, signed with
+-- HMAC-SHA256 under `secret`, when one of its payments completes. The secret
+-- is kept in clear because every delivery is signed with it.
+
+CREATE TABLE IF NOT EXISTS merchant_webhooks (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    merchant_id VARCHAR(255) UNIQUE NOT NULL,
+    url TEXT NOT NULL,
+    secret VARCHAR(128) NOT NULL,
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
+    updated_at TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
+    FOREIGN KEY (merchant_id) REFERENCES merchants(merchant_id)
+);
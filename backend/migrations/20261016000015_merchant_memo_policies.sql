@@ -0,0 +1,14 @@
+-- Migration: Per-merchant payment memo policies
+-- A merchant's policy constrains the memo on its payments to `pattern` (a
+-- regex the whole memo must match) and, with `auto_generate`, fills in an
+-- order reference when a payment omits one.
+
+CREATE TABLE IF NOT EXISTS merchant_memo_policies (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    merchant_id VARCHAR(255) UNIQUE NOT NULL,
+    pattern TEXT,
+    auto_generate BOOLEAN NOT NULL DEFAULT FALSE,
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
+    updated_at TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
+    FOREIGN KEY (merchant_id) REFERENCES merchants(merchant_id)
+);
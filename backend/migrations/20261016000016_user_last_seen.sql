@@ -0,0 +1,5 @@
+-- Migration: When each user last authenticated
+-- Set by the auth middleware at most once per minute per user, so busy
+-- sessions don't turn every request into a write.
+
+ALTER TABLE users ADD COLUMN IF NOT EXISTS last_seen_at TIMESTAMP WITH TIME ZONE;
@@ -0,0 +1,24 @@
+-- Migration: Backfill balances from existing ledger history
+-- Transfers now debit the sender's balance, but balances were only ever
+-- written by the seed script. Give every owner without a balance row the net
+-- of what has already moved through the system: completed deposits and
+-- incoming transfers, less completed withdrawals and outgoing transfers that
+-- are pending or completed. Owners with a balance row are left alone.
+
+INSERT INTO balances (owner_id, asset, amount)
+SELECT owner_id, asset, GREATEST(SUM(amount), 0)
+FROM (
+    SELECT user_id AS owner_id, asset, amount
+    FROM deposits WHERE status = 'completed'
+    UNION ALL
+    SELECT user_id, asset, -amount
+    FROM withdrawals WHERE status = 'completed'
+    UNION ALL
+    SELECT to_user_id, asset, amount
+    FROM transfers WHERE status = 'completed'
+    UNION ALL
+    SELECT from_user_id, asset, -amount
+    FROM transfers WHERE status IN ('pending', 'completed')
+) AS movements
+GROUP BY owner_id, asset
+ON CONFLICT (owner_id, asset) DO NOTHING;
@@ -0,0 +1,7 @@
+-- Migration: Per-merchant fee strategies
+-- Who pays the network fee on a merchant's payments (`sponsored`,
+-- `sender_pays` or `merchant_pays`), and the strategy each payment was
+-- created with.
+
+ALTER TABLE merchants ADD COLUMN IF NOT EXISTS fee_strategy VARCHAR(20) NOT NULL DEFAULT 'sponsored';
+ALTER TABLE payments ADD COLUMN IF NOT EXISTS fee_strategy VARCHAR(20) NOT NULL DEFAULT 'sponsored';
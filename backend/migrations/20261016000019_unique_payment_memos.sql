@@ -0,0 +1,10 @@
+-- Migration: Unique order-reference memos
+-- Payments to a merchant with a memo policy carry an order reference, which
+-- must identify a single payment. `memo_unique` marks those payments; free
+-- text memos of merchants without a policy may repeat.
+
+ALTER TABLE payments ADD COLUMN IF NOT EXISTS memo_unique BOOLEAN NOT NULL DEFAULT FALSE;
+
+CREATE UNIQUE INDEX IF NOT EXISTS idx_payments_merchant_memo_unique
+    ON payments (merchant_id, memo)
+    WHERE memo_unique;
@@ -0,0 +1,13 @@
+-- Migration: Dead-letter anchor webhooks that keep failing
+-- A webhook for a transaction we don't know yet now stays pending and is
+-- replayed, since it can arrive before the withdrawal it reports on is
+-- stored. Entries that still fail after `reconciler.max_webhook_attempts`
+-- are moved to 'dead_letter' for an operator to look at.
+
+UPDATE anchor_webhooks
+SET processing_status = 'pending'
+WHERE processing_status = 'ignored';
+
+CREATE INDEX IF NOT EXISTS idx_anchor_webhooks_dead_letter
+    ON anchor_webhooks(received_at)
+    WHERE processing_status = 'dead_letter';
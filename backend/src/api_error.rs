@@ -5,7 +5,10 @@ use axum::{
 };
 use serde::Serialize;
 use serde_json::json;
+use std::collections::BTreeMap;
+use std::fmt;
 use thiserror::Error;
+use utoipa::ToSchema;
 
 #[derive(Debug, Error)]
 pub enum ApiError {
@@ -18,6 +21,9 @@ pub enum ApiError {
     #[error("Validation error: {0}")]
     Validation(String),
 
+    #[error("Validation error: {0}")]
+    InvalidFields(FieldErrors),
+
     #[error("Bad request: {0}")]
     BadRequest(String),
 
@@ -45,18 +51,101 @@ pub enum ApiError {
     #[error("Stellar error: {0}")]
     Stellar(String),
 
+    /// The network refused a transaction outright; resubmitting the same
+    /// envelope won't succeed.
+    #[error("Transaction rejected: {0}")]
+    TransactionRejected(String),
+
     #[error("Compliance violation: {0}")]
     Compliance(String),
 
     #[error("Rate limit exceeded: {0}")]
     RateLimit(String),
+
+    #[error("Request timed out: {0}")]
+    Timeout(String),
+
+    #[error("Service unavailable: {0}")]
+    ServiceUnavailable(String),
 }
 
-#[derive(Serialize)]
-struct ErrorResponse {
+/// Validation failures keyed by field, so a client can fix every offending
+/// field in one round trip.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+#[serde(transparent)]
+pub struct FieldErrors(BTreeMap<String, String>);
+
+impl FieldErrors {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record a violation. Only the first message per field is kept.
+    pub fn add(&mut self, field: &str, message: impl Into<String>) {
+        self.0
+            .entry(field.to_string())
+            .or_insert_with(|| message.into());
+    }
+
+    /// Record `result`'s error against `field`, passing its value through.
+    pub fn check<T>(&mut self, field: &str, result: Result<T, ApiError>) -> Option<T> {
+        match result {
+            Ok(value) => Some(value),
+            Err(ApiError::Validation(message)) => {
+                self.add(field, message);
+                None
+            }
+            Err(e) => {
+                self.add(field, e.to_string());
+                None
+            }
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    pub fn get(&self, field: &str) -> Option<&str> {
+        self.0.get(field).map(String::as_str)
+    }
+
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    /// `Ok` if nothing was recorded, otherwise every violation at once.
+    pub fn into_result(self) -> Result<(), ApiError> {
+        if self.is_empty() {
+            Ok(())
+        } else {
+            Err(ApiError::InvalidFields(self))
+        }
+    }
+}
+
+impl fmt::Display for FieldErrors {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, (field, message)) in self.0.iter().enumerate() {
+            if i > 0 {
+                f.write_str("; ")?;
+            }
+            write!(f, "{}: {}", field, message)?;
+        }
+        Ok(())
+    }
+}
+
+/// Body of every error response.
+#[derive(Serialize, ToSchema)]
+pub struct ErrorResponse {
     error: String,
     message: String,
     code: String,
+    /// Per-field messages for a `VALIDATION_ERROR`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[schema(value_type = Option<HashMap<String, String>>)]
+    fields: Option<FieldErrors>,
 }
 
 impl IntoResponse for ApiError {
@@ -64,7 +153,9 @@ impl IntoResponse for ApiError {
         let (status, code) = match &self {
             ApiError::Authentication(_) => (StatusCode::UNAUTHORIZED, "AUTHENTICATION_FAILED"),
             ApiError::Authorization(_) => (StatusCode::FORBIDDEN, "AUTHORIZATION_FAILED"),
-            ApiError::Validation(_) => (StatusCode::BAD_REQUEST, "VALIDATION_ERROR"),
+            ApiError::Validation(_) | ApiError::InvalidFields(_) => {
+                (StatusCode::BAD_REQUEST, "VALIDATION_ERROR")
+            }
             ApiError::BadRequest(_) => (StatusCode::BAD_REQUEST, "BAD_REQUEST"),
             ApiError::NotFound(_) => (StatusCode::NOT_FOUND, "NOT_FOUND"),
             ApiError::Conflict(_) => (StatusCode::CONFLICT, "CONFLICT"),
@@ -73,15 +164,33 @@ impl IntoResponse for ApiError {
             ApiError::Pool(_) => (StatusCode::INTERNAL_SERVER_ERROR, "DATABASE_ERROR"),
             ApiError::Json(_) => (StatusCode::BAD_REQUEST, "INVALID_JSON"),
             ApiError::Jwt(_) => (StatusCode::UNAUTHORIZED, "INVALID_TOKEN"),
-            ApiError::Stellar(_) => (StatusCode::BAD_REQUEST, "STELLAR_ERROR"),
+            ApiError::Stellar(_) => (StatusCode::BAD_GATEWAY, "STELLAR_ERROR"),
+            ApiError::TransactionRejected(_) => (StatusCode::BAD_REQUEST, "TRANSACTION_REJECTED"),
             ApiError::Compliance(_) => (StatusCode::FORBIDDEN, "COMPLIANCE_VIOLATION"),
             ApiError::RateLimit(_) => (StatusCode::TOO_MANY_REQUESTS, "RATE_LIMIT_EXCEEDED"),
+            ApiError::Timeout(_) => (StatusCode::GATEWAY_TIMEOUT, "GATEWAY_TIMEOUT"),
+            ApiError::ServiceUnavailable(_) => {
+                (StatusCode::SERVICE_UNAVAILABLE, "SERVICE_UNAVAILABLE")
+            }
         };
 
+        let fields = match &self {
+            ApiError::InvalidFields(fields) => Some(fields.clone()),
+            _ => None,
+        };
+        let message = match &self {
+            // Raw RPC and Horizon errors stay in the logs.
+            ApiError::Stellar(raw) => {
+                tracing::error!(error = %raw, "Stellar network request failed");
+                "Stellar network request failed".to_string()
+            }
+            _ => self.to_string(),
+        };
         let error_response = ErrorResponse {
             error: code.to_string(),
-            message: self.to_string(),
+            message,
             code: code.to_string(),
+            fields,
         };
 
         (status, Json(json!(error_response))).into_response()
@@ -99,3 +208,49 @@ impl ApiError {
         ApiError::InternalServerError
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn field_errors_are_listed_in_the_response() {
+        let mut errors = FieldErrors::new();
+        errors.add("bio", "Bio must be 500 characters or less");
+        errors.add("display_name", "Display name cannot be empty");
+        errors.add("bio", "ignored: first message wins");
+
+        let response = errors.into_result().unwrap_err().into_response();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(body["code"], "VALIDATION_ERROR");
+        assert_eq!(
+            body["fields"],
+            json!({
+                "bio": "Bio must be 500 characters or less",
+                "display_name": "Display name cannot be empty",
+            })
+        );
+        assert!(FieldErrors::new().into_result().is_ok());
+    }
+
+    #[tokio::test]
+    async fn stellar_errors_are_a_sanitized_bad_gateway() {
+        let response = ApiError::Stellar(
+            "sendTransaction request failed: http://rpc.internal:8000".to_string(),
+        )
+        .into_response();
+        assert_eq!(response.status(), StatusCode::BAD_GATEWAY);
+
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(body["code"], "STELLAR_ERROR");
+        assert_eq!(body["message"], "Stellar network request failed");
+    }
+}
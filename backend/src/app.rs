@@ -1,39 +1,137 @@
 use axum::{
     middleware,
-    routing::{delete, get, patch, post},
+    routing::{delete, get, patch, post, put},
     Router,
 };
 use deadpool_postgres::Pool;
 use std::sync::Arc;
+use std::time::Duration;
+use tokio::task::JoinHandle;
+use tokio_util::sync::CancellationToken;
 use tower_http::{cors::CorsLayer, trace::TraceLayer};
 
 use crate::{
     config::Config,
     http::{
         admin, audit, auth, files, health, identity, jobs, metrics as metrics_http, notifications,
-        payments, profiles, transfers, withdrawals,
+        openapi, payments, profiles, transfers, withdrawals,
     },
+    job_processors::{
+        AuditProcessor, BlockchainTxProcessor, DigestNotificationProcessor, JobProcessorRegistry,
+        MaintenanceProcessor, NotificationProcessor, PaymentConfirmationProcessor, SimulatedChain,
+        TransferConfirmationProcessor,
+    },
+    job_types::JobType,
     job_worker::JobWorker,
     middleware::{
-        audit_logging, auth as auth_middleware, metrics, rate_limit, request_id, role_guard,
+        audit_logging, auth as auth_middleware, maintenance, metrics, metrics_auth, rate_limit,
+        request_id, role_guard, timeout,
     },
     role::Role,
-    service::{MetricsService, ServiceContainer},
+    service::{
+        anchor_service::{WebhookReplay, WithdrawalReconciliation},
+        audit_service::AuditRetention,
+        MetricsService, ServiceContainer,
+    },
 };
 
+/// Build the router and start the background job workers.
+///
+/// Workers run until `shutdown` is cancelled; the returned handle resolves
+/// once every worker has finished its in-flight job and exited.
 pub async fn create_app(
     db_pool: Pool,
     config: Config,
-) -> Result<Router, Box<dyn std::error::Error>> {
+    shutdown: CancellationToken,
+) -> Result<(Router, JoinHandle<()>), Box<dyn std::error::Error>> {
     MetricsService::init();
 
     let services = Arc::new(ServiceContainer::new(db_pool, config.clone()).await?);
 
     // Start background job workers
-    let job_worker = Arc::new(JobWorker::new(config.clone()).await?);
+    let mut processors = JobProcessorRegistry::with_http_client(services.http.clone());
+    processors.register(
+        JobType::Audit,
+        Box::new(AuditProcessor::new(Arc::new(services.audit.clone()))),
+    );
+    processors.register(
+        JobType::BlockchainTx,
+        Box::new(PaymentConfirmationProcessor::new(
+            Arc::new(services.payment.clone()),
+            services.job_queue.clone(),
+            Box::new(
+                TransferConfirmationProcessor::new(
+                    Arc::new(services.transfer.clone()),
+                    services.job_queue.clone(),
+                )
+                .with_fallback(BlockchainTxProcessor::with_store(
+                    Arc::new(services.tx_submissions.clone()),
+                    Arc::new(SimulatedChain::default()),
+                )),
+            ),
+        )),
+    );
+    let digest_store = Arc::new(services.notification.clone());
+    processors.register(
+        JobType::Notification,
+        Box::new(NotificationProcessor::with_digest(
+            services.http.clone(),
+            digest_store.clone(),
+            services.job_queue.clone(),
+            config.notification_digest.clone(),
+        )),
+    );
+    processors.register(
+        JobType::DigestNotification,
+        Box::new(DigestNotificationProcessor::new(
+            digest_store,
+            services.job_queue.clone(),
+            &config.notification_digest,
+        )),
+    );
+    // Periodic tasks, fired by the recurring scheduler on one replica
+    processors.register(
+        JobType::Maintenance,
+        Box::new(
+            MaintenanceProcessor::new()
+                .with_task("registry_sync", Arc::new(services.registry_sync.clone()))
+                .with_task(
+                    "balance_reconciliation",
+                    Arc::new(services.balances.clone()),
+                )
+                .with_task("profile_purge", Arc::new(services.profile.clone()))
+                .with_task(
+                    "withdrawal_reconciliation",
+                    Arc::new(WithdrawalReconciliation::new(
+                        services.anchor.clone(),
+                        services.job_queue.clone(),
+                    )),
+                )
+                .with_task(
+                    "anchor_webhook_replay",
+                    Arc::new(WebhookReplay::new(
+                        services.anchor.clone(),
+                        services.job_queue.clone(),
+                    )),
+                )
+                .with_task(
+                    "audit_retention",
+                    Arc::new(AuditRetention::new(
+                        services.audit.clone(),
+                        services.storage.clone(),
+                    )),
+                ),
+        ),
+    );
+    let job_worker = Arc::new(JobWorker::new(
+        services.job_queue.clone(),
+        processors,
+        config.clone(),
+    ));
     let worker_clone = Arc::clone(&job_worker);
-    tokio::spawn(async move {
-        if let Err(e) = worker_clone.start_workers().await {
+    let worker_shutdown = shutdown.clone();
+    let workers = tokio::spawn(async move {
+        if let Err(e) = worker_clone.start_workers(worker_shutdown).await {
             tracing::error!("Job workers failed: {}", e);
         }
     });
@@ -48,7 +146,11 @@ pub async fn create_app(
     let metrics_routes = Router::new()
         .route("/metrics", get(metrics_http::prometheus_metrics))
         .route("/metrics/json", get(metrics_http::json_metrics))
-        .route("/metrics/alerts", get(metrics_http::check_alerts));
+        .route("/metrics/alerts", get(metrics_http::check_alerts))
+        .route_layer(middleware::from_fn_with_state(
+            config.metrics.clone(),
+            metrics_auth::metrics_auth,
+        ));
 
     // -------------------- Auth --------------------
     let auth_routes = Router::new()
@@ -56,9 +158,15 @@ pub async fn create_app(
         .route("/register", post(auth::register))
         .route("/refresh", post(auth::refresh_token));
 
+    // Session management needs an authenticated caller, so it is mounted
+    // under /auth in the protected router.
+    let session_routes = Router::new()
+        .route("/whoami", get(auth::whoami))
+        .route("/sessions", get(auth::list_sessions))
+        .route("/sessions/:id", delete(auth::revoke_session));
+
     // -------------------- User --------------------
-    let user_routes = Router::new()
-        .route("/register", post(auth::user_register));
+    let user_routes = Router::new().route("/register", post(auth::user_register));
 
     // -------------------- Identity --------------------
     let identity_routes = Router::new()
@@ -70,20 +178,26 @@ pub async fn create_app(
     // -------------------- Payments --------------------
     let payment_routes = Router::new()
         .route("/payments", post(payments::create_payment))
+        .route("/payments/simulate", post(payments::simulate_payment))
         .route("/payments/:id", get(payments::get_payment))
         .route("/payments/:id/status", get(payments::get_payment_status))
+        .route("/payments/:id/submit", post(payments::submit_payment))
+        .route("/payments/:id/events", get(payments::stream_payment_events))
         .route("/qr/generate", post(payments::generate_qr))
         .route("/nfc/validate", post(payments::validate_nfc));
 
     // -------------------- Transfers --------------------
     let transfer_routes = Router::new()
         .route("/transfers", post(transfers::create_transfer))
+        .route("/transfers/batch", post(transfers::create_transfer_batch))
         .route("/transfers/:id", get(transfers::get_transfer))
-        .route("/transfers/:id/status", get(transfers::get_transfer_status));
+        .route("/transfers/:id/status", get(transfers::get_transfer_status))
+        .route("/transfers/:id/submit", post(transfers::submit_transfer));
 
     // -------------------- Withdrawals --------------------
     let withdrawal_routes = Router::new()
         .route("/withdrawals", post(withdrawals::create_withdrawal))
+        .route("/withdrawals", get(withdrawals::list_withdrawals))
         .route("/withdrawals/:id", get(withdrawals::get_withdrawal))
         .route(
             "/withdrawals/:id/status",
@@ -105,7 +219,8 @@ pub async fn create_app(
         .route("/me", get(profiles::get_my_profile))
         .route("/:user_id", get(profiles::get_profile))
         .route("/:user_id", patch(profiles::update_profile))
-        .route("/:user_id", delete(profiles::delete_profile));
+        .route("/:user_id", delete(profiles::delete_profile))
+        .route("/:user_id/restore", post(profiles::restore_profile));
 
     // -------------------- Admin --------------------
     // Files routes
@@ -118,14 +233,53 @@ pub async fn create_app(
     // Admin routes (protected)
     let admin_routes = Router::new()
         .route("/dashboard/stats", get(admin::get_dashboard_stats))
+        .route(
+            "/dashboard/stats/export",
+            get(admin::export_dashboard_stats),
+        )
         .route("/transactions", get(admin::get_transactions))
+        .route("/users/:user_id", get(admin::get_user_details))
         .route("/users/:user_id/activity", get(admin::get_user_activity))
+        .route("/users/:user_id/role", get(admin::get_user_role))
+        .route("/users/:user_id/role", patch(admin::update_user_role))
+        .route(
+            "/withdrawals/:id/status",
+            patch(admin::override_withdrawal_status),
+        )
         .route("/system/health", get(admin::get_system_health))
+        .route("/jobs/dead-letter/replay", post(admin::replay_dead_letters))
+        .route("/registry/sync", post(admin::sync_registry))
+        .route(
+            "/merchants/:merchant_id",
+            delete(admin::deactivate_merchant),
+        )
+        .route(
+            "/merchants/:merchant_id/api-keys",
+            post(admin::issue_merchant_api_key),
+        )
+        .route(
+            "/merchants/:merchant_id/api-keys/:key_id",
+            delete(admin::revoke_merchant_api_key),
+        )
+        .route(
+            "/merchants/:merchant_id/webhook",
+            put(admin::register_merchant_webhook),
+        )
+        .route(
+            "/merchants/:merchant_id/memo-policy",
+            put(admin::set_merchant_memo_policy),
+        )
+        .route(
+            "/merchants/:merchant_id/fee-strategy",
+            put(admin::set_merchant_fee_strategy),
+        )
         .layer(middleware::from_fn(role_guard::require_role(Role::Admin)));
 
     // -------------------- Audit --------------------
     let audit_routes = Router::new()
         .route("/audit-logs", get(audit::list_audit_logs))
+        .route("/audit-logs/export", get(audit::export_audit_logs))
+        .route("/audit-logs/verify", get(audit::verify_audit_chain))
         .route("/audit-logs/:id", get(audit::get_audit_log))
         .layer(middleware::from_fn(role_guard::admin_only()));
 
@@ -134,6 +288,7 @@ pub async fn create_app(
 
     // -------------------- Protected Routes --------------------
     let protected_routes = Router::new()
+        .nest("/auth", session_routes)
         .nest("/identity", identity_routes)
         .nest("/payments", payment_routes)
         .nest("/transfers", transfer_routes)
@@ -147,10 +302,6 @@ pub async fn create_app(
             services.clone(),
             audit_logging,
         ))
-        .layer(middleware::from_fn_with_state(
-            services.clone(),
-            auth_middleware::authenticate,
-        ))
         .layer(middleware::from_fn_with_state(
             services.clone(),
             rate_limit::rate_limit,
@@ -165,16 +316,39 @@ pub async fn create_app(
         .nest("/auth", auth_routes)
         .nest("/user", user_routes)
         .nest("/health", health_routes)
+        .route("/openapi.json", get(openapi::openapi_json))
+        .route("/docs", get(openapi::swagger_ui))
         .merge(metrics_routes);
 
+    let maintenance_state = maintenance::MaintenanceState::new(
+        config.maintenance.clone(),
+        services.job_queue.clone(),
+        Duration::from_millis(config.health.probe_timeout_ms),
+    );
+
+    // Authentication wraps every route, public ones included: anything not on
+    // `PUBLIC_ROUTES` is rejected without credentials, whichever group it is in.
     let app = Router::new()
         .merge(public_routes)
         .merge(protected_routes)
+        .layer(middleware::from_fn_with_state(
+            services.clone(),
+            auth_middleware::authenticate,
+        ))
         .with_state(services)
-        .layer(middleware::from_fn(request_id::request_id))
+        .layer(middleware::from_fn_with_state(
+            maintenance_state,
+            maintenance::maintenance_mode,
+        ))
+        .layer(middleware::from_fn_with_state(
+            Duration::from_secs(config.server.request_timeout_seconds),
+            timeout::request_timeout,
+        ))
         .layer(middleware::from_fn(metrics::track_metrics))
         .layer(TraceLayer::new_for_http())
+        // Outside the trace layer so its logs carry the request id too
+        .layer(middleware::from_fn(request_id::request_id))
         .layer(CorsLayer::permissive());
 
-    Ok(app)
+    Ok((app, workers))
 }
@@ -0,0 +1,286 @@
+//! Decimal precision of the assets amounts are denominated in.
+//!
+//! Amounts are stored as `i64` counts of an asset's smallest unit. The
+//! registry records how many decimal places that unit sits below the display
+//! unit, so amounts can be parsed from and formatted as decimal strings
+//! (`"12.50"`) without going through floating point.
+
+use crate::api_error::ApiError;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+/// Every classic Stellar asset is divisible to 7 decimal places (stroops).
+pub const STELLAR_DECIMALS: u32 = 7;
+
+/// Precision and supply cap of an asset.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AssetInfo {
+    pub decimals: u32,
+    /// Largest amount, in smallest units, a single payment may carry.
+    pub max_supply: i64,
+}
+
+const STELLAR_ASSET: AssetInfo = AssetInfo {
+    decimals: STELLAR_DECIMALS,
+    max_supply: i64::MAX,
+};
+
+const FIAT: AssetInfo = AssetInfo {
+    decimals: 2,
+    max_supply: i64::MAX,
+};
+
+/// Known assets by bare code. Codes not listed are issued Stellar assets.
+const REGISTRY: &[(&str, AssetInfo)] = &[
+    (
+        "XLM",
+        AssetInfo {
+            decimals: STELLAR_DECIMALS,
+            // Total lumens ever created
+            max_supply: 50_001_806_812 * 10_000_000,
+        },
+    ),
+    ("USDC", STELLAR_ASSET),
+    ("EURC", STELLAR_ASSET),
+    ("USDT", STELLAR_ASSET),
+    ("USD", FIAT),
+    ("EUR", FIAT),
+    ("GBP", FIAT),
+    ("NGN", FIAT),
+    ("KES", FIAT),
+    ("GHS", FIAT),
+];
+
+/// Look up an asset given as `CODE` or `CODE:ISSUER`. An issued asset is
+/// always a Stellar asset, whatever its code: `NGN:G...` has 7 decimals,
+/// not the 2 of the `NGN` fiat currency.
+pub fn asset_info(asset: &str) -> AssetInfo {
+    if asset.contains(':') {
+        return STELLAR_ASSET;
+    }
+    REGISTRY
+        .iter()
+        .find(|(known, _)| known.eq_ignore_ascii_case(asset))
+        .map(|(_, info)| *info)
+        .unwrap_or(STELLAR_ASSET)
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum AmountError {
+    #[error("Amount must be a decimal number")]
+    Invalid,
+
+    #[error("Amount must be greater than zero")]
+    NotPositive,
+
+    #[error("Amount has more than {0} decimal places")]
+    SubUnit(u32),
+
+    #[error("Amount exceeds the maximum supply of the asset")]
+    ExceedsMaxSupply,
+}
+
+impl From<AmountError> for ApiError {
+    fn from(err: AmountError) -> Self {
+        ApiError::Validation(err.to_string())
+    }
+}
+
+impl AssetInfo {
+    fn unit(&self) -> i64 {
+        10i64.pow(self.decimals)
+    }
+
+    /// Check an amount already in smallest units.
+    pub fn validate(&self, units: i64) -> Result<i64, AmountError> {
+        if units <= 0 {
+            return Err(AmountError::NotPositive);
+        }
+        if units > self.max_supply {
+            return Err(AmountError::ExceedsMaxSupply);
+        }
+        Ok(units)
+    }
+
+    /// Convert a display amount such as `"12.5"` to smallest units.
+    ///
+    /// Digits past the asset's precision are rejected rather than rounded,
+    /// unless they are trailing zeros.
+    pub fn parse(&self, display: &str) -> Result<i64, AmountError> {
+        let display = display.trim();
+        if display.starts_with('-') {
+            return Err(AmountError::NotPositive);
+        }
+
+        let (whole, fraction) = display.split_once('.').unwrap_or((display, ""));
+        let all_digits = |s: &str| s.bytes().all(|b| b.is_ascii_digit());
+        if (whole.is_empty() && fraction.is_empty()) || !all_digits(whole) || !all_digits(fraction)
+        {
+            return Err(AmountError::Invalid);
+        }
+
+        let fraction = fraction.trim_end_matches('0');
+        if fraction.len() > self.decimals as usize {
+            return Err(AmountError::SubUnit(self.decimals));
+        }
+
+        // Only digits remain, so a parse failure means the value overflowed.
+        let whole: i64 = if whole.is_empty() {
+            0
+        } else {
+            whole.parse().map_err(|_| AmountError::ExceedsMaxSupply)?
+        };
+        let fraction: i64 = format!("{:0<width$}", fraction, width = self.decimals as usize)
+            .parse()
+            .unwrap_or(0);
+
+        let units = whole
+            .checked_mul(self.unit())
+            .and_then(|units| units.checked_add(fraction))
+            .ok_or(AmountError::ExceedsMaxSupply)?;
+
+        self.validate(units)
+    }
+
+    /// Render smallest units as a display amount, e.g. `1250` → `"12.50"`.
+    pub fn format(&self, units: i64) -> String {
+        let sign = if units < 0 { "-" } else { "" };
+        let abs = units.unsigned_abs();
+        if self.decimals == 0 {
+            return format!("{}{}", sign, abs);
+        }
+
+        let unit = self.unit() as u64;
+        format!(
+            "{}{}.{:0width$}",
+            sign,
+            abs / unit,
+            abs % unit,
+            width = self.decimals as usize
+        )
+    }
+}
+
+/// An amount as submitted by a client: an integer count of smallest units,
+/// or a decimal string in display units.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum AmountInput {
+    Units(i64),
+    Display(String),
+}
+
+impl AmountInput {
+    /// Validate against `asset` and return the amount in smallest units.
+    pub fn resolve(&self, asset: &str) -> Result<i64, AmountError> {
+        let info = asset_info(asset);
+        match self {
+            AmountInput::Units(units) => info.validate(*units),
+            AmountInput::Display(display) => info.parse(display),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn looks_up_by_code_and_defaults_to_stellar_precision() {
+        assert_eq!(asset_info("USD").decimals, 2);
+        assert_eq!(asset_info("usdc").decimals, 7);
+        assert_eq!(
+            asset_info("NGN:GA5ZSEJYB37JRC5AVCIA5MOP4RHTM335X2KGX3IHOJAPP5RE34K4KZVN"),
+            STELLAR_ASSET
+        );
+        assert_eq!(asset_info("XLM:GISSUER"), STELLAR_ASSET);
+        assert_eq!(asset_info("ZAP:GISSUER"), STELLAR_ASSET);
+    }
+
+    #[test]
+    fn parses_display_amounts_exactly() {
+        let usdc = asset_info("USDC");
+        assert_eq!(usdc.parse("0.1"), Ok(1_000_000));
+        assert_eq!(usdc.parse("12.5"), Ok(125_000_000));
+        assert_eq!(usdc.parse(".0000001"), Ok(1));
+        assert_eq!(usdc.parse("3."), Ok(30_000_000));
+
+        let usd = asset_info("USD");
+        assert_eq!(usd.parse("19.99"), Ok(1999));
+        // Trailing zeros past the precision aren't a sub-unit amount.
+        assert_eq!(usd.parse("1.5000"), Ok(150));
+    }
+
+    #[test]
+    fn rejects_sub_unit_amounts() {
+        assert_eq!(
+            asset_info("USD").parse("1.005"),
+            Err(AmountError::SubUnit(2))
+        );
+        assert_eq!(
+            asset_info("XLM").parse("0.00000001"),
+            Err(AmountError::SubUnit(7))
+        );
+    }
+
+    #[test]
+    fn rejects_overflow_and_max_supply() {
+        let usdc = asset_info("USDC");
+        assert_eq!(
+            usdc.parse("99999999999999999999"),
+            Err(AmountError::ExceedsMaxSupply)
+        );
+        // Fits in an i64 of whole units but not once scaled to stroops.
+        assert_eq!(
+            usdc.parse("922337203686.5"),
+            Err(AmountError::ExceedsMaxSupply)
+        );
+
+        let xlm = asset_info("XLM");
+        assert_eq!(xlm.parse("50001806812"), Ok(xlm.max_supply));
+        assert_eq!(
+            xlm.parse("50001806812.0000001"),
+            Err(AmountError::ExceedsMaxSupply)
+        );
+        assert_eq!(
+            xlm.validate(xlm.max_supply + 1),
+            Err(AmountError::ExceedsMaxSupply)
+        );
+    }
+
+    #[test]
+    fn rejects_malformed_and_non_positive_amounts() {
+        let usd = asset_info("USD");
+        for input in ["", ".", "1.2.3", "1e5", "12,50", " 1 0"] {
+            assert_eq!(usd.parse(input), Err(AmountError::Invalid), "{:?}", input);
+        }
+        assert_eq!(usd.parse("0.00"), Err(AmountError::NotPositive));
+        assert_eq!(usd.parse("-1"), Err(AmountError::NotPositive));
+        assert_eq!(usd.validate(0), Err(AmountError::NotPositive));
+    }
+
+    #[test]
+    fn formats_smallest_units() {
+        assert_eq!(asset_info("USD").format(1250), "12.50");
+        assert_eq!(asset_info("USD").format(5), "0.05");
+        assert_eq!(asset_info("USDC").format(1), "0.0000001");
+        assert_eq!(asset_info("USD").format(-250), "-2.50");
+        assert_eq!(asset_info("USD").format(i64::MIN), "-92233720368547758.08");
+
+        let xlm = asset_info("XLM");
+        assert_eq!(xlm.parse(&xlm.format(123_456_789)), Ok(123_456_789));
+    }
+
+    #[test]
+    fn resolves_either_input_form() {
+        let units: AmountInput = serde_json::from_str("1999").unwrap();
+        let display: AmountInput = serde_json::from_str("\"19.99\"").unwrap();
+
+        assert_eq!(units.resolve("USD"), Ok(1999));
+        assert_eq!(display.resolve("USD"), Ok(1999));
+        assert_eq!(
+            AmountInput::Units(-5).resolve("USD"),
+            Err(AmountError::NotPositive)
+        );
+    }
+}
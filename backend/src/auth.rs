@@ -1,9 +1,11 @@
 use crate::api_error::ApiError;
+use crate::config::{JwtConfig, PinPolicyConfig};
 use crate::role::Role;
-use bcrypt::{hash, verify};
+use bcrypt::{hash, verify, HashParts};
 use chrono::{Duration, Utc};
 use jsonwebtoken::{decode, encode, DecodingKey, EncodingKey, Header, Validation};
 use serde::{Deserialize, Serialize};
+use std::str::FromStr;
 
 /// Token type for distinguishing access vs refresh tokens
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
@@ -20,6 +22,31 @@ pub struct Claims {
     pub token_type: TokenType, // JWT token type
     pub exp: usize,            // expiration timestamp
     pub iat: usize,            // issued at timestamp
+    pub iss: String,           // issuing service
+    pub aud: String,           // deployment the token is meant for
+    /// Session (refresh-token family) a refresh token belongs to
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sid: Option<String>,
+    /// Id of this refresh token within its session; rotated on every refresh
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub jti: Option<String>,
+}
+
+/// The `iss`/`aud` pair a deployment stamps on its tokens and requires on
+/// the tokens it accepts.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TokenScope<'a> {
+    pub issuer: &'a str,
+    pub audience: &'a str,
+}
+
+impl<'a> From<&'a JwtConfig> for TokenScope<'a> {
+    fn from(config: &'a JwtConfig) -> Self {
+        Self {
+            issuer: &config.issuer,
+            audience: &config.audience,
+        }
+    }
 }
 
 /// Generate an access token (short-lived)
@@ -27,9 +54,10 @@ pub fn generate_access_token(
     user_id: &str,
     role: Role,
     secret: &str,
-    expiration_hours: i64,
+    scope: TokenScope,
+    ttl: Duration,
 ) -> Result<String, jsonwebtoken::errors::Error> {
-    generate_token(user_id, role, secret, expiration_hours, TokenType::Access)
+    generate_token(user_id, role, secret, scope, ttl, TokenType::Access)
 }
 
 /// Generate a refresh token (long-lived)
@@ -37,39 +65,82 @@ pub fn generate_refresh_token(
     user_id: &str,
     role: Role,
     secret: &str,
-    expiration_hours: i64,
+    scope: TokenScope,
+    ttl: Duration,
+) -> Result<String, jsonwebtoken::errors::Error> {
+    generate_token(user_id, role, secret, scope, ttl, TokenType::Refresh)
+}
+
+/// Generate a refresh token bound to a session, so it can be revoked
+pub fn generate_session_refresh_token(
+    user_id: &str,
+    role: Role,
+    secret: &str,
+    scope: TokenScope,
+    ttl: Duration,
+    session_id: &str,
+    token_id: &str,
 ) -> Result<String, jsonwebtoken::errors::Error> {
-    generate_token(user_id, role, secret, expiration_hours, TokenType::Refresh)
+    let mut claims = build_claims(user_id, role, scope, ttl, TokenType::Refresh);
+    claims.sid = Some(session_id.to_string());
+    claims.jti = Some(token_id.to_string());
+    encode_claims(&claims, secret)
 }
 
 fn generate_token(
     user_id: &str,
     role: Role,
     secret: &str,
-    expiration_hours: i64,
+    scope: TokenScope,
+    ttl: Duration,
     token_type: TokenType,
 ) -> Result<String, jsonwebtoken::errors::Error> {
+    let claims = build_claims(user_id, role, scope, ttl, token_type);
+    encode_claims(&claims, secret)
+}
+
+fn build_claims(
+    user_id: &str,
+    role: Role,
+    scope: TokenScope,
+    ttl: Duration,
+    token_type: TokenType,
+) -> Claims {
     let now = Utc::now();
-    let expire = now + Duration::hours(expiration_hours);
+    let expire = now + ttl;
 
-    let claims = Claims {
+    Claims {
         sub: user_id.to_string(),
         role,
         exp: expire.timestamp() as usize,
         iat: now.timestamp() as usize,
+        iss: scope.issuer.to_string(),
+        aud: scope.audience.to_string(),
         token_type,
-    };
+        sid: None,
+        jti: None,
+    }
+}
 
+fn encode_claims(claims: &Claims, secret: &str) -> Result<String, jsonwebtoken::errors::Error> {
     let header = Header::default();
     let encoding_key = EncodingKey::from_secret(secret.as_bytes());
 
-    encode(&header, &claims, &encoding_key)
+    encode(&header, claims, &encoding_key)
 }
 
-/// Validate a JWT token and return claims
-pub fn validate_jwt(token: &str, secret: &str) -> Result<Claims, jsonwebtoken::errors::Error> {
+/// Validate a JWT token and return claims. The token must have been issued
+/// by `scope.issuer` for `scope.audience`.
+pub fn validate_jwt(
+    token: &str,
+    secret: &str,
+    scope: TokenScope,
+) -> Result<Claims, jsonwebtoken::errors::Error> {
     let decoding_key = DecodingKey::from_secret(secret.as_bytes());
-    let validation = Validation::default();
+    let mut validation = Validation::default();
+    validation.set_issuer(&[scope.issuer]);
+    validation.set_audience(&[scope.audience]);
+    validation.set_required_spec_claims(&["exp", "iss", "aud"]);
 
     let token_data = decode::<Claims>(token, &decoding_key, &validation)?;
     Ok(token_data.claims)
@@ -79,8 +150,9 @@ pub fn validate_jwt(token: &str, secret: &str) -> Result<Claims, jsonwebtoken::e
 pub fn validate_access_token(
     token: &str,
     secret: &str,
+    scope: TokenScope,
 ) -> Result<Claims, jsonwebtoken::errors::Error> {
-    let claims = validate_jwt(token, secret)?;
+    let claims = validate_jwt(token, secret, scope)?;
     if claims.token_type != TokenType::Access {
         return Err(jsonwebtoken::errors::Error::from(
             jsonwebtoken::errors::ErrorKind::InvalidToken,
@@ -93,8 +165,9 @@ pub fn validate_access_token(
 pub fn validate_refresh_token(
     token: &str,
     secret: &str,
+    scope: TokenScope,
 ) -> Result<Claims, jsonwebtoken::errors::Error> {
-    let claims = validate_jwt(token, secret)?;
+    let claims = validate_jwt(token, secret, scope)?;
     if claims.token_type != TokenType::Refresh {
         return Err(jsonwebtoken::errors::Error::from(
             jsonwebtoken::errors::ErrorKind::InvalidToken,
@@ -103,9 +176,6 @@ pub fn validate_refresh_token(
     Ok(claims)
 }
 
-/// Bcrypt cost factor for PIN hashing (10 rounds).
-const PIN_BCRYPT_COST: u32 = 10;
-
 /// Validate that a PIN is 4–6 digits.
 pub fn validate_pin(pin: &str) -> Result<(), ApiError> {
     if pin.len() < 4 || pin.len() > 6 || !pin.chars().all(|c| c.is_ascii_digit()) {
@@ -114,10 +184,38 @@ pub fn validate_pin(pin: &str) -> Result<(), ApiError> {
     Ok(())
 }
 
-/// Hash a PIN using bcrypt with 10 rounds.
-pub fn hash_pin(pin: &str) -> Result<String, ApiError> {
+/// Check a newly chosen PIN against the configured policy, naming the
+/// first requirement it fails.
+pub fn check_pin_policy(pin: &str, policy: &PinPolicyConfig) -> Result<(), ApiError> {
     validate_pin(pin)?;
-    hash(pin, PIN_BCRYPT_COST).map_err(|_| ApiError::InternalServerError)
+    if pin.len() < policy.min_length {
+        return Err(ApiError::Validation(format!(
+            "PIN must be at least {} digits",
+            policy.min_length
+        )));
+    }
+    if policy.reject_trivial && is_trivial_pin(pin) {
+        return Err(ApiError::Validation(
+            "PIN must not be a repeated digit or a simple sequence".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+/// A single repeated digit, or digits that step up or down by one.
+fn is_trivial_pin(pin: &str) -> bool {
+    let digits = pin.as_bytes();
+    [0i16, 1, -1].iter().any(|&step| {
+        digits
+            .windows(2)
+            .all(|pair| pair[1] as i16 - pair[0] as i16 == step)
+    })
+}
+
+/// Hash a PIN using bcrypt at the given cost (`pin_hash.cost`).
+pub fn hash_pin(pin: &str, cost: u32) -> Result<String, ApiError> {
+    validate_pin(pin)?;
+    hash(pin, cost).map_err(|_| ApiError::InternalServerError)
 }
 
 /// Verify a PIN against a bcrypt hash.
@@ -125,20 +223,30 @@ pub fn verify_pin(pin: &str, hash: &str) -> Result<bool, ApiError> {
     verify(pin, hash).map_err(|_| ApiError::InternalServerError)
 }
 
+/// Whether a stored hash is weaker than `cost` and should be replaced once
+/// the PIN is next known, i.e. after a successful login.
+pub fn pin_needs_rehash(hash: &str, cost: u32) -> bool {
+    HashParts::from_str(hash).map_or(true, |parts| parts.get_cost() < cost)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
     const TEST_SECRET: &str = "test-secret-key";
+    const SCOPE: TokenScope = TokenScope {
+        issuer: "zaps",
+        audience: "zaps-api",
+    };
 
     #[test]
     fn test_access_token_generation_and_validation() {
         let user_id = "user123";
         let role = Role::Admin;
-        let token = generate_access_token(user_id, role, TEST_SECRET, 24)
+        let token = generate_access_token(user_id, role, TEST_SECRET, SCOPE, Duration::hours(24))
             .expect("Failed to generate token");
 
-        let claims = validate_jwt(&token, TEST_SECRET).expect("Failed to validate");
+        let claims = validate_jwt(&token, TEST_SECRET, SCOPE).expect("Failed to validate");
         assert_eq!(claims.sub, user_id);
         assert_eq!(claims.role, Role::Admin);
         assert_eq!(claims.token_type, TokenType::Access);
@@ -148,10 +256,10 @@ mod tests {
     fn test_refresh_token_generation_and_validation() {
         let user_id = "user123";
         let role = Role::User;
-        let token = generate_refresh_token(user_id, role, TEST_SECRET, 168)
+        let token = generate_refresh_token(user_id, role, TEST_SECRET, SCOPE, Duration::hours(168))
             .expect("Failed to generate token");
 
-        let claims = validate_jwt(&token, TEST_SECRET).expect("Failed to validate");
+        let claims = validate_jwt(&token, TEST_SECRET, SCOPE).expect("Failed to validate");
         assert_eq!(claims.sub, user_id);
         assert_eq!(claims.role, Role::User);
         assert_eq!(claims.token_type, TokenType::Refresh);
@@ -159,44 +267,221 @@ mod tests {
 
     #[test]
     fn test_access_token_rejected_as_refresh() {
-        let token = generate_access_token("user123", Role::User, TEST_SECRET, 24)
-            .expect("Failed to generate token");
+        let token = generate_access_token(
+            "user123",
+            Role::User,
+            TEST_SECRET,
+            SCOPE,
+            Duration::hours(24),
+        )
+        .expect("Failed to generate token");
 
-        let result = validate_refresh_token(&token, TEST_SECRET);
+        let result = validate_refresh_token(&token, TEST_SECRET, SCOPE);
         assert!(result.is_err());
     }
 
     #[test]
     fn test_jwt_with_different_roles() {
         for role in [Role::User, Role::Merchant, Role::Admin] {
-            let token = generate_access_token("user123", role, TEST_SECRET, 24)
-                .expect("Failed to generate token");
-            let claims = validate_jwt(&token, TEST_SECRET).expect("Failed to validate");
+            let token =
+                generate_access_token("user123", role, TEST_SECRET, SCOPE, Duration::hours(24))
+                    .expect("Failed to generate token");
+            let claims = validate_jwt(&token, TEST_SECRET, SCOPE).expect("Failed to validate");
             assert_eq!(claims.role, role);
         }
     }
 
+    #[test]
+    fn test_session_refresh_token_carries_session() {
+        let token = generate_session_refresh_token(
+            "user123",
+            Role::User,
+            TEST_SECRET,
+            SCOPE,
+            Duration::hours(168),
+            "sid-1",
+            "jti-1",
+        )
+        .expect("Failed to generate token");
+
+        let claims =
+            validate_refresh_token(&token, TEST_SECRET, SCOPE).expect("Failed to validate");
+        assert_eq!(claims.sid.as_deref(), Some("sid-1"));
+        assert_eq!(claims.jti.as_deref(), Some("jti-1"));
+
+        let plain = generate_refresh_token(
+            "user123",
+            Role::User,
+            TEST_SECRET,
+            SCOPE,
+            Duration::hours(168),
+        )
+        .unwrap();
+        assert!(validate_jwt(&plain, TEST_SECRET, SCOPE)
+            .unwrap()
+            .sid
+            .is_none());
+    }
+
+    #[test]
+    fn test_weaker_pin_hash_verifies_and_needs_rehash() {
+        let old = hash_pin("1234", 4).unwrap();
+
+        assert!(verify_pin("1234", &old).unwrap());
+        assert!(pin_needs_rehash(&old, 5));
+        assert!(!pin_needs_rehash(&old, 4));
+
+        let upgraded = hash_pin("1234", 5).unwrap();
+        assert!(verify_pin("1234", &upgraded).unwrap());
+        assert!(!pin_needs_rehash(&upgraded, 5));
+        assert!(pin_needs_rehash("not-a-bcrypt-hash", 5));
+    }
+
+    #[test]
+    fn test_pin_policy() {
+        let policy = PinPolicyConfig::default();
+        for pin in ["2580", "1357", "120934", "9012"] {
+            assert!(check_pin_policy(pin, &policy).is_ok(), "{}", pin);
+        }
+        for pin in ["0000", "1234", "4321", "345678", "987654", "12a4", "123"] {
+            assert!(
+                matches!(check_pin_policy(pin, &policy), Err(ApiError::Validation(_))),
+                "{}",
+                pin
+            );
+        }
+
+        let strict = PinPolicyConfig {
+            min_length: 6,
+            reject_trivial: true,
+        };
+        match check_pin_policy("2580", &strict) {
+            Err(ApiError::Validation(reason)) => assert!(reason.contains("at least 6")),
+            other => panic!("unexpected {:?}", other),
+        }
+        assert!(check_pin_policy("258014", &strict).is_ok());
+
+        let lenient = PinPolicyConfig {
+            min_length: 4,
+            reject_trivial: false,
+        };
+        assert!(check_pin_policy("1234", &lenient).is_ok());
+        assert!(check_pin_policy("1111", &lenient).is_ok());
+    }
+
     #[test]
     fn test_invalid_token() {
-        let result = validate_jwt("invalid-token", "secret");
+        let result = validate_jwt("invalid-token", "secret", SCOPE);
         assert!(result.is_err());
     }
 
     #[test]
     fn test_refresh_token_rejected_as_access() {
-        let token = generate_refresh_token("user123", Role::User, TEST_SECRET, 168)
-            .expect("Failed to generate token");
+        let token = generate_refresh_token(
+            "user123",
+            Role::User,
+            TEST_SECRET,
+            SCOPE,
+            Duration::hours(168),
+        )
+        .expect("Failed to generate token");
 
-        let result = validate_access_token(&token, TEST_SECRET);
+        let result = validate_access_token(&token, TEST_SECRET, SCOPE);
         assert!(result.is_err());
     }
 
     #[test]
-    fn test_invalid_secret_rejected() {
-        let token = generate_access_token("user123", Role::User, TEST_SECRET, 24)
+    fn test_token_for_another_audience_rejected() {
+        let token = generate_access_token(
+            "user123",
+            Role::User,
+            TEST_SECRET,
+            SCOPE,
+            Duration::hours(24),
+        )
+        .expect("Failed to generate token");
+
+        let staging = TokenScope {
+            audience: "zaps-staging",
+            ..SCOPE
+        };
+        let err = validate_access_token(&token, TEST_SECRET, staging).unwrap_err();
+        assert_eq!(
+            err.kind(),
+            &jsonwebtoken::errors::ErrorKind::InvalidAudience
+        );
+
+        let claims = validate_access_token(&token, TEST_SECRET, SCOPE).expect("Failed to validate");
+        assert_eq!(claims.aud, "zaps-api");
+        assert_eq!(claims.iss, "zaps");
+    }
+
+    #[test]
+    fn test_token_from_another_issuer_rejected() {
+        let foreign = TokenScope {
+            issuer: "someone-else",
+            ..SCOPE
+        };
+        let token = generate_refresh_token(
+            "user123",
+            Role::User,
+            TEST_SECRET,
+            foreign,
+            Duration::hours(168),
+        )
+        .expect("Failed to generate token");
+
+        let err = validate_refresh_token(&token, TEST_SECRET, SCOPE).unwrap_err();
+        assert_eq!(err.kind(), &jsonwebtoken::errors::ErrorKind::InvalidIssuer);
+    }
+
+    #[test]
+    fn test_token_expiry_matches_ttl() {
+        let ttl = Duration::minutes(15);
+        let token = generate_access_token("user123", Role::User, TEST_SECRET, SCOPE, ttl)
             .expect("Failed to generate token");
 
-        let result = validate_jwt(&token, "wrong-secret");
+        let claims = validate_access_token(&token, TEST_SECRET, SCOPE).expect("Failed to validate");
+        assert_eq!((claims.exp - claims.iat) as i64, ttl.num_seconds());
+    }
+
+    #[test]
+    fn test_expired_access_token_rejected_while_refresh_works() {
+        // Expired by more than the validator's clock-skew leeway
+        let access = generate_access_token(
+            "user123",
+            Role::User,
+            TEST_SECRET,
+            SCOPE,
+            Duration::minutes(-5),
+        )
+        .expect("Failed to generate token");
+        let refresh =
+            generate_refresh_token("user123", Role::User, TEST_SECRET, SCOPE, Duration::days(7))
+                .expect("Failed to generate token");
+
+        let err = validate_access_token(&access, TEST_SECRET, SCOPE).unwrap_err();
+        assert_eq!(
+            err.kind(),
+            &jsonwebtoken::errors::ErrorKind::ExpiredSignature
+        );
+        let claims =
+            validate_refresh_token(&refresh, TEST_SECRET, SCOPE).expect("Failed to validate");
+        assert_eq!(claims.sub, "user123");
+    }
+
+    #[test]
+    fn test_invalid_secret_rejected() {
+        let token = generate_access_token(
+            "user123",
+            Role::User,
+            TEST_SECRET,
+            SCOPE,
+            Duration::hours(24),
+        )
+        .expect("Failed to generate token");
+
+        let result = validate_jwt(&token, "wrong-secret", SCOPE);
         assert!(result.is_err());
     }
 }
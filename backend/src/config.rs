@@ -1,7 +1,80 @@
 use crate::models::{RateLimitConfig, RateLimitScope};
+use crate::scheduler::{CronSchedule, RecurringJob, RecurringScheduler};
 use config::{Config as ConfigBuilder, ConfigError, Environment, File};
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::env;
+use thiserror::Error;
+
+/// Shortest secret accepted for signing keys and shared webhook secrets.
+pub const MIN_SECRET_LENGTH: usize = 12;
+
+/// Placeholder JWT secret shipped in `config/default.toml`.
+const DEFAULT_JWT_SECRET: &str = "change-this-in-production";
+
+/// Placeholder custodial master seed shipped in `config/default.toml`.
+const DEFAULT_CUSTODIAL_MASTER_SEED: &str = "change-this-custodial-seed";
+
+/// Placeholder file URL signing secret shipped in `config/default.toml`.
+const DEFAULT_FILE_URL_SECRET: &str = "change-this-file-url-secret";
+
+/// Passphrase of the Stellar public network, where real funds move.
+pub const PUBLIC_NETWORK_PASSPHRASE: &str = "Public Global Stellar Network ; September 2015";
+
+/// A config value that would fail at runtime, named by its dotted path.
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+#[error("invalid config `{field}`: {reason}")]
+pub struct ConfigValidationError {
+    pub field: &'static str,
+    pub reason: String,
+}
+
+fn invalid(field: &'static str, reason: impl Into<String>) -> ConfigValidationError {
+    ConfigValidationError {
+        field,
+        reason: reason.into(),
+    }
+}
+
+fn check_secret(field: &'static str, secret: &str) -> Result<(), ConfigValidationError> {
+    if secret.trim().len() < MIN_SECRET_LENGTH {
+        return Err(invalid(
+            field,
+            format!("must be at least {} characters", MIN_SECRET_LENGTH),
+        ));
+    }
+    Ok(())
+}
+
+fn check_url(
+    field: &'static str,
+    url: &str,
+    schemes: &[&str],
+) -> Result<(), ConfigValidationError> {
+    let parsed = reqwest::Url::parse(url)
+        .map_err(|e| invalid(field, format!("{:?} is not a URL: {}", url, e)))?;
+    if !schemes.contains(&parsed.scheme()) {
+        return Err(invalid(
+            field,
+            format!("scheme must be one of {}", schemes.join(", ")),
+        ));
+    }
+    Ok(())
+}
+
+fn check_schedule(field: &'static str, cron_expr: &str) -> Result<(), ConfigValidationError> {
+    cron_expr
+        .parse::<CronSchedule>()
+        .map(|_| ())
+        .map_err(|e| invalid(field, format!("{:#}", e)))
+}
+
+fn check_positive(field: &'static str, value: u64) -> Result<(), ConfigValidationError> {
+    if value == 0 {
+        return Err(invalid(field, "must be greater than zero"));
+    }
+    Ok(())
+}
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Config {
@@ -22,6 +95,34 @@ pub struct Config {
     pub rate_limit: RateLimitConfig,
     #[serde(default)]
     pub storage: StorageConfig,
+    #[serde(default)]
+    pub health: HealthConfig,
+    #[serde(default)]
+    pub http_client: HttpClientConfig,
+    #[serde(default)]
+    pub audit: AuditConfig,
+    #[serde(default)]
+    pub reconciler: ReconcilerConfig,
+    #[serde(default)]
+    pub metrics: MetricsConfig,
+    #[serde(default)]
+    pub maintenance: MaintenanceConfig,
+    #[serde(default)]
+    pub registry_sync: RegistrySyncConfig,
+    #[serde(default)]
+    pub balance_reconciliation: BalanceReconciliationConfig,
+    #[serde(default)]
+    pub reputation: ReputationConfig,
+    #[serde(default)]
+    pub pin_hash: PinHashConfig,
+    #[serde(default)]
+    pub pin_policy: PinPolicyConfig,
+    #[serde(default)]
+    pub notification_digest: NotificationDigestConfig,
+    #[serde(default)]
+    pub profiles: ProfileConfig,
+    #[serde(default)]
+    pub custodial: CustodialConfig,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -32,13 +133,67 @@ pub struct DatabaseConfig {
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ServerConfig {
     pub port: u16,
+    /// How long to wait for in-flight requests and jobs to drain on shutdown
+    /// before closing whatever is left.
+    #[serde(default = "default_shutdown_timeout_seconds")]
+    pub shutdown_timeout_seconds: u64,
+    /// Deadline for a handler to produce a response; `0` disables it.
+    #[serde(default = "default_request_timeout_seconds")]
+    pub request_timeout_seconds: u64,
+}
+
+fn default_shutdown_timeout_seconds() -> u64 {
+    30
+}
+
+fn default_request_timeout_seconds() -> u64 {
+    30
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct JwtConfig {
     pub secret: String,
-    pub expiration_hours: i64,
-    pub refresh_expiration_hours: i64,
+    /// Lifetime of an access token. Kept short: a leaked access token can't
+    /// be revoked, whereas its refresh token's session can.
+    #[serde(default = "default_access_ttl_seconds")]
+    pub access_ttl_seconds: u64,
+    /// Lifetime of a refresh token, and of the session it belongs to.
+    #[serde(default = "default_refresh_ttl_seconds")]
+    pub refresh_ttl_seconds: u64,
+    /// `iss` claim stamped on issued tokens and required on incoming ones.
+    #[serde(default = "default_jwt_issuer")]
+    pub issuer: String,
+    /// `aud` claim naming this deployment. Deployments that share a secret
+    /// must use different audiences so their tokens can't be replayed
+    /// against each other.
+    #[serde(default = "default_jwt_audience")]
+    pub audience: String,
+}
+
+fn default_access_ttl_seconds() -> u64 {
+    15 * 60
+}
+
+fn default_refresh_ttl_seconds() -> u64 {
+    7 * 24 * 3600
+}
+
+impl JwtConfig {
+    pub fn access_ttl(&self) -> chrono::Duration {
+        chrono::Duration::seconds(self.access_ttl_seconds as i64)
+    }
+
+    pub fn refresh_ttl(&self) -> chrono::Duration {
+        chrono::Duration::seconds(self.refresh_ttl_seconds as i64)
+    }
+}
+
+fn default_jwt_issuer() -> String {
+    "zaps".to_string()
+}
+
+fn default_jwt_audience() -> String {
+    "zaps-api".to_string()
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -58,6 +213,47 @@ pub struct StellarNetwork {
     // Optional server-side secret used to sign as fee-payer (fee sponsorship / account abstraction)
     #[serde(default)]
     pub fee_payer_secret: Option<String>,
+    // Additional fee-payer secrets, comma-separated; sponsorship rotates across all of them
+    #[serde(default)]
+    pub fee_payer_secrets: Option<String>,
+    /// Issued assets payments may use. When any are listed, an asset is
+    /// only accepted from a listed code and issuer; XLM always is. Required
+    /// on the public network, where an empty list would accept any issuer's
+    /// look-alike asset.
+    #[serde(default)]
+    pub accepted_assets: Vec<AcceptedAsset>,
+}
+
+/// An issued asset accepted for payments.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AcceptedAsset {
+    pub code: String,
+    /// Issuing account (`G...`).
+    pub issuer: String,
+}
+
+impl StellarNetwork {
+    /// Every configured fee-payer secret, `fee_payer_secret` first.
+    pub fn fee_payer_secret_list(&self) -> Vec<String> {
+        self.fee_payer_secret
+            .iter()
+            .flat_map(|s| s.split(','))
+            .chain(self.fee_payer_secrets.iter().flat_map(|s| s.split(',')))
+            .map(str::trim)
+            .filter(|s| !s.is_empty())
+            .map(str::to_string)
+            .collect()
+    }
+
+    /// Whether the issued asset `code:issuer` may be used, which any
+    /// well-formed asset can while no assets are listed.
+    pub fn accepts_asset(&self, code: &str, issuer: &str) -> bool {
+        self.accepted_assets.is_empty()
+            || self
+                .accepted_assets
+                .iter()
+                .any(|asset| asset.code == code && asset.issuer == issuer)
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -65,7 +261,134 @@ pub struct AnchorConfig {
     pub sep24_url: String,
     pub sep31_url: String,
     pub webhook_secret: String,
+    /// Secrets the Anchor signed webhooks with before `webhook_secret`,
+    /// comma-separated. Kept while rotating so in-flight webhooks verify.
+    #[serde(default)]
+    pub previous_webhook_secrets: Option<String>,
     pub kyc_required: bool,
+    /// Per-asset bounds on a single withdrawal, keyed by asset as `CODE` or
+    /// `CODE:ISSUER`. A key only bounds withdrawals naming that exact asset:
+    /// `USDC` doesn't cover `USDC:G...`. Assets not listed are unbounded.
+    #[serde(default)]
+    pub withdrawal_limits: HashMap<String, WithdrawalLimit>,
+    #[serde(default)]
+    pub circuit_breaker: CircuitBreakerConfig,
+    #[serde(default)]
+    pub rate_limit_retry: RateLimitRetryConfig,
+    /// Most KYC checks a batch keeps in flight at the Anchor at once.
+    #[serde(default = "default_kyc_batch_concurrency")]
+    pub kyc_batch_concurrency: usize,
+}
+
+fn default_kyc_batch_concurrency() -> usize {
+    8
+}
+
+impl AnchorConfig {
+    /// Every accepted webhook secret, `webhook_secret` first.
+    pub fn webhook_secret_list(&self) -> Vec<String> {
+        std::iter::once(self.webhook_secret.as_str())
+            .chain(
+                self.previous_webhook_secrets
+                    .iter()
+                    .flat_map(|s| s.split(',')),
+            )
+            .map(str::trim)
+            .filter(|s| !s.is_empty())
+            .map(str::to_string)
+            .collect()
+    }
+
+    /// Limits for an asset given as `CODE` or `CODE:ISSUER`.
+    pub fn withdrawal_limit(&self, asset: &str) -> WithdrawalLimit {
+        find_withdrawal_limit(&self.withdrawal_limits, asset).unwrap_or_default()
+    }
+}
+
+/// Entry of `limits` for an asset given as `CODE` or `CODE:ISSUER`. Codes
+/// compare case-insensitively and issuers exactly, so an asset of the same
+/// code from another issuer doesn't pick up the entry.
+fn find_withdrawal_limit(
+    limits: &HashMap<String, WithdrawalLimit>,
+    asset: &str,
+) -> Option<WithdrawalLimit> {
+    let split = |asset: &str| match asset.split_once(':') {
+        Some((code, issuer)) => (code.to_ascii_uppercase(), Some(issuer.to_string())),
+        None => (asset.to_ascii_uppercase(), None),
+    };
+    let wanted = split(asset.trim());
+    limits
+        .iter()
+        .find(|(known, _)| split(known.trim()) == wanted)
+        .map(|(_, limit)| *limit)
+}
+
+fn check_withdrawal_limits(
+    field: &'static str,
+    limits: &HashMap<String, WithdrawalLimit>,
+) -> Result<(), ConfigValidationError> {
+    for (asset, limit) in limits {
+        if limit.min.is_some_and(|min| min <= 0) || limit.max.is_some_and(|max| max <= 0) {
+            return Err(invalid(
+                field,
+                format!("{} limits must be greater than zero", asset),
+            ));
+        }
+        if let (Some(min), Some(max)) = (limit.min, limit.max) {
+            if min > max {
+                return Err(invalid(field, format!("{} min must not exceed max", asset)));
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Bounds, in the asset's smallest units, on the amount of one withdrawal.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct WithdrawalLimit {
+    pub min: Option<i64>,
+    pub max: Option<i64>,
+}
+
+/// Fast-failing of anchor calls while the anchor is down. After
+/// `failure_threshold` consecutive failures, calls are refused for
+/// `cooldown_seconds`; the next call is then let through as a probe.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CircuitBreakerConfig {
+    pub failure_threshold: u32,
+    pub cooldown_seconds: u64,
+}
+
+impl Default for CircuitBreakerConfig {
+    fn default() -> Self {
+        Self {
+            failure_threshold: 5,
+            cooldown_seconds: 30,
+        }
+    }
+}
+
+/// Retrying of anchor calls answered with `429 Too Many Requests`. A retry
+/// waits as long as the anchor's `Retry-After` asks; once the waits for one
+/// call would add up to more than `max_wait_seconds` the `429` is returned
+/// instead. The budget must stay below `server.request_timeout_seconds` so a
+/// retried call can still answer before the request is cut off.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RateLimitRetryConfig {
+    pub max_retries: u32,
+    pub max_wait_seconds: u64,
+}
+
+impl Default for RateLimitRetryConfig {
+    fn default() -> Self {
+        Self {
+            max_retries: 2,
+            max_wait_seconds: 10,
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -104,12 +427,37 @@ pub struct RiskThresholds {
 pub struct QueueConfig {
     pub redis_url: String,
     pub max_retries: u32,
+    /// Per-job-type overrides of `max_retries`, keyed by snake_case job
+    /// type (e.g. `webhook`).
+    #[serde(default)]
+    pub max_retries_overrides: HashMap<String, u32>,
     pub visibility_timeout_seconds: u64,
+    /// Per-job-type overrides of `visibility_timeout_seconds`, keyed by
+    /// snake_case job type (e.g. `blockchain_tx`).
+    #[serde(default)]
+    pub visibility_timeout_overrides: HashMap<String, u64>,
     pub backoff_multiplier: f64,
     pub max_backoff_seconds: u64,
     pub dead_letter_max_size: usize,
+    /// Workers that take jobs of any type.
     pub worker_count: usize,
+    /// Workers dedicated to one job type, in addition to the shared
+    /// `worker_count`, keyed by snake_case job type (e.g. `email`). Gives
+    /// time-sensitive jobs capacity that a backlog of slow jobs can't take.
+    #[serde(default)]
+    pub worker_pools: HashMap<String, usize>,
     pub reclaim_interval_seconds: u64,
+    /// How long an idle worker blocks waiting for an enqueue before checking
+    /// the queue again. Also bounds how late a delayed job can be picked up.
+    #[serde(default = "default_dequeue_block_seconds")]
+    pub dequeue_block_seconds: u64,
+    /// Jobs enqueued on a cron schedule.
+    #[serde(default)]
+    pub recurring_jobs: Vec<RecurringJob>,
+}
+
+fn default_dequeue_block_seconds() -> u64 {
+    5
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -117,6 +465,34 @@ pub struct StorageConfig {
     #[serde(default)]
     pub backend: StorageBackend,
     pub local_path: Option<String>,
+    /// `Cache-Control: max-age` for served files. Files are immutable once
+    /// uploaded, so clients revalidate with their `ETag` after this.
+    #[serde(default = "default_cache_max_age_seconds")]
+    pub cache_max_age_seconds: u64,
+    /// Secret that local files are encrypted at rest under (AES-256-GCM).
+    /// Unset stores files in plaintext. Files written before it was set stay
+    /// readable, but changing it makes every encrypted file unreadable.
+    #[serde(default)]
+    pub encryption_key: Option<String>,
+    /// Secret that `GET /files/:id` URLs are signed with. Changing it
+    /// invalidates every outstanding file URL.
+    #[serde(default = "default_url_signing_secret")]
+    pub url_signing_secret: String,
+    /// How long a signed file URL stays valid after it is issued.
+    #[serde(default = "default_signed_url_ttl_seconds")]
+    pub signed_url_ttl_seconds: u64,
+}
+
+fn default_cache_max_age_seconds() -> u64 {
+    86400
+}
+
+fn default_url_signing_secret() -> String {
+    DEFAULT_FILE_URL_SECRET.to_string()
+}
+
+fn default_signed_url_ttl_seconds() -> u64 {
+    900
 }
 
 impl Default for StorageConfig {
@@ -124,6 +500,10 @@ impl Default for StorageConfig {
         Self {
             backend: StorageBackend::Local,
             local_path: Some("./uploads".to_string()),
+            cache_max_age_seconds: default_cache_max_age_seconds(),
+            encryption_key: None,
+            url_signing_secret: default_url_signing_secret(),
+            signed_url_ttl_seconds: default_signed_url_ttl_seconds(),
         }
     }
 }
@@ -137,7 +517,730 @@ pub enum StorageBackend {
     Ipfs,
 }
 
+/// How a failing dependency affects the readiness probe.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum DependencyCriticality {
+    /// The service is reported as not ready when the dependency is down.
+    Required,
+    /// The dependency is probed and reported, but never fails readiness.
+    Optional,
+    /// The dependency is not probed at all.
+    Disabled,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct HealthConfig {
+    pub redis: DependencyCriticality,
+    pub anchor: DependencyCriticality,
+    pub probe_timeout_ms: u64,
+}
+
+impl Default for HealthConfig {
+    fn default() -> Self {
+        Self {
+            redis: DependencyCriticality::Required,
+            anchor: DependencyCriticality::Optional,
+            probe_timeout_ms: 2000,
+        }
+    }
+}
+
+/// The outbound HTTP client shared by every service that calls the Anchor,
+/// Stellar RPC/Horizon, webhooks and other external APIs.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct HttpClientConfig {
+    pub connect_timeout_ms: u64,
+    /// Deadline for a whole request, from connecting to reading the body.
+    pub request_timeout_ms: u64,
+    /// Idle keep-alive connections kept per host for reuse.
+    pub pool_max_idle_per_host: usize,
+    pub pool_idle_timeout_seconds: u64,
+}
+
+impl Default for HttpClientConfig {
+    fn default() -> Self {
+        Self {
+            connect_timeout_ms: 5000,
+            request_timeout_ms: 30_000,
+            pool_max_idle_per_host: 32,
+            pool_idle_timeout_seconds: 90,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AuditConfig {
+    /// Entries older than this are purged by the retention task.
+    pub retention_days: u32,
+    /// Upload a gzipped JSON export to the storage adapter before purging.
+    pub archive_before_purge: bool,
+    /// Cron expression (UTC) for the retention task.
+    pub schedule: String,
+    /// Entries deleted, and archived, per transaction.
+    pub purge_batch_size: i64,
+}
+
+impl Default for AuditConfig {
+    fn default() -> Self {
+        Self {
+            retention_days: 365,
+            archive_before_purge: true,
+            schedule: "0 3 * * *".to_string(),
+            purge_batch_size: 1000,
+        }
+    }
+}
+
+/// Access control for the `/metrics` routes.
+///
+/// Unless `dev_mode` is set, a scrape must present `bearer_token` or come from
+/// an address in `allowed_ips`; with neither configured every scrape is refused.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct MetricsConfig {
+    pub dev_mode: bool,
+    pub bearer_token: Option<String>,
+    pub allowed_ips: Vec<std::net::IpAddr>,
+}
+
+/// Read-only maintenance mode. While on, write requests get `503` and reads
+/// are served as usual. It can also be switched on at runtime by setting the
+/// `zaps:maintenance` Redis key.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct MaintenanceConfig {
+    pub enabled: bool,
+    /// `Retry-After` sent with rejected writes.
+    pub retry_after_seconds: u64,
+}
+
+impl Default for MaintenanceConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            retry_after_seconds: 120,
+        }
+    }
+}
+
+/// Mirroring of the on-chain registry contract into `merchants` and `users`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RegistrySyncConfig {
+    pub enabled: bool,
+    /// Address (`C...`) of the deployed registry contract.
+    pub contract_id: String,
+    /// Ledger to start from on first run; `0` starts at the latest ledger.
+    pub start_ledger: u32,
+    /// Cron expression (UTC) the sync runs on.
+    pub schedule: String,
+    /// Events requested per `getEvents` page.
+    pub page_size: u32,
+    /// Account (`G...`) holding the registry's admin role, which admin
+    /// calls such as `deactivate_merchant` are sent from. Empty disables them.
+    pub admin_account: String,
+    /// Secret seed (`S...`) of `admin_account`, which admin calls are
+    /// signed with.
+    #[serde(default)]
+    pub admin_secret: String,
+}
+
+impl Default for RegistrySyncConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            contract_id: String::new(),
+            start_ledger: 0,
+            schedule: "* * * * *".to_string(),
+            page_size: 100,
+            admin_account: String::new(),
+            admin_secret: String::new(),
+        }
+    }
+}
+
+/// Withdrawal tiers chosen by a user's score in the reputation contract.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ReputationConfig {
+    pub enabled: bool,
+    /// Address (`C...`) of the deployed reputation contract.
+    pub contract_id: String,
+    /// A user gets the tier with the highest `min_score` their score
+    /// reaches; below every tier the anchor's base limits apply.
+    pub tiers: Vec<ReputationTier>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ReputationTier {
+    pub name: String,
+    pub min_score: u32,
+    /// Per-asset bounds replacing `anchor.withdrawal_limits` for this tier.
+    /// Assets not listed keep the anchor's limits.
+    pub withdrawal_limits: HashMap<String, WithdrawalLimit>,
+    /// Require cleared KYC even when `anchor.kyc_required` is off.
+    pub kyc_required: bool,
+}
+
+impl ReputationTier {
+    /// This tier's limits for an asset given as `CODE` or `CODE:ISSUER`.
+    pub fn withdrawal_limit(&self, asset: &str) -> Option<WithdrawalLimit> {
+        find_withdrawal_limit(&self.withdrawal_limits, asset)
+    }
+}
+
+/// Periodic comparison of the `balances` table against on-chain balances.
+/// Every drift found is raised as an alert.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct BalanceReconciliationConfig {
+    pub enabled: bool,
+    /// Cron expression (UTC) the reconciliation runs on.
+    pub schedule: String,
+    /// Balances read per page while a run works through the table.
+    pub batch_size: i64,
+    /// Endpoint each drift alert is POSTed to as JSON. Unset, alerts are
+    /// only logged.
+    pub alert_webhook_url: Option<String>,
+}
+
+impl Default for BalanceReconciliationConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            schedule: "0 * * * *".to_string(),
+            batch_size: 500,
+            alert_webhook_url: None,
+        }
+    }
+}
+
+/// Cost of the bcrypt hashes PINs are stored under. Raising it takes effect
+/// for existing users as they next log in.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PinHashConfig {
+    /// bcrypt cost factor (log2 of the rounds), 4–31.
+    pub cost: u32,
+}
+
+impl Default for PinHashConfig {
+    fn default() -> Self {
+        Self { cost: 10 }
+    }
+}
+
+/// Rules a new PIN must meet when it is chosen. Existing PINs are not
+/// re-checked at login, so tightening the policy locks nobody out.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PinPolicyConfig {
+    /// Fewest digits a PIN may have, 4–6.
+    pub min_length: usize,
+    /// Reject a repeated digit ("0000") or a run like "1234" or "9876".
+    pub reject_trivial: bool,
+}
+
+impl Default for PinPolicyConfig {
+    fn default() -> Self {
+        Self {
+            min_length: 4,
+            reject_trivial: true,
+        }
+    }
+}
+
+/// Batching of a user's non-urgent notifications into one digest per window.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct NotificationDigestConfig {
+    pub enabled: bool,
+    /// How long notifications are held after the first one in a window.
+    pub window_seconds: u64,
+    /// Notification types that are always delivered immediately.
+    pub urgent_types: Vec<String>,
+}
+
+impl Default for NotificationDigestConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            window_seconds: 900,
+            urgent_types: vec!["security".to_string(), "withdrawal".to_string()],
+        }
+    }
+}
+
+/// Soft-deleted profiles can be restored for `recovery_window_hours`, after
+/// which the purge task removes them for good.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ProfileConfig {
+    pub recovery_window_hours: u64,
+    /// Cron expression (UTC) for the purge task.
+    pub purge_schedule: String,
+}
+
+impl Default for ProfileConfig {
+    fn default() -> Self {
+        Self {
+            recovery_window_hours: 720,
+            purge_schedule: "30 3 * * *".to_string(),
+        }
+    }
+}
+
+/// Custodial accounts. Every user's Stellar keypair is derived from
+/// `master_seed` and their user ID, so this is the only secret to keep safe;
+/// changing it orphans every existing user address.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CustodialConfig {
+    pub master_seed: String,
+}
+
+impl Default for CustodialConfig {
+    fn default() -> Self {
+        Self {
+            master_seed: DEFAULT_CUSTODIAL_MASTER_SEED.to_string(),
+        }
+    }
+}
+
+/// Background reconciliation of withdrawal status against the anchor.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ReconcilerConfig {
+    pub enabled: bool,
+    /// Cron expression (UTC) withdrawal reconciliation runs on.
+    pub schedule: String,
+    /// Cron expression (UTC) unprocessed anchor webhooks are replayed on.
+    pub webhook_replay_schedule: String,
+    /// Maximum withdrawals polled per run.
+    pub batch_size: i64,
+    /// Delay before re-polling a withdrawal whose anchor status hasn't moved;
+    /// doubles on each unchanged poll up to `max_backoff_seconds`.
+    pub base_backoff_seconds: u64,
+    pub max_backoff_seconds: u64,
+    /// Deliveries and replays of one anchor webhook before it is moved to
+    /// the dead-letter state instead of being replayed again.
+    pub max_webhook_attempts: i32,
+}
+
+impl Default for ReconcilerConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            schedule: "* * * * *".to_string(),
+            webhook_replay_schedule: "* * * * *".to_string(),
+            batch_size: 50,
+            base_backoff_seconds: 60,
+            max_backoff_seconds: 3600,
+            max_webhook_attempts: 10,
+        }
+    }
+}
+
 impl Config {
+    /// Every job the recurring scheduler fires: the configured
+    /// `queue.recurring_jobs` plus the enabled built-in maintenance tasks.
+    pub fn recurring_jobs(&self) -> Vec<RecurringJob> {
+        let mut jobs = self.queue_config.recurring_jobs.clone();
+        jobs.push(RecurringJob::maintenance(
+            "audit_retention",
+            &self.audit.schedule,
+        ));
+        jobs.push(RecurringJob::maintenance(
+            "profile_purge",
+            &self.profiles.purge_schedule,
+        ));
+        if self.registry_sync.enabled {
+            jobs.push(RecurringJob::maintenance(
+                "registry_sync",
+                &self.registry_sync.schedule,
+            ));
+        }
+        if self.balance_reconciliation.enabled {
+            jobs.push(RecurringJob::maintenance(
+                "balance_reconciliation",
+                &self.balance_reconciliation.schedule,
+            ));
+        }
+        if self.reconciler.enabled {
+            jobs.push(RecurringJob::maintenance(
+                "withdrawal_reconciliation",
+                &self.reconciler.schedule,
+            ));
+            jobs.push(RecurringJob::maintenance(
+                "anchor_webhook_replay",
+                &self.reconciler.webhook_replay_schedule,
+            ));
+        }
+        jobs
+    }
+
+    /// Check values that would otherwise only fail once a request or job
+    /// touches them. Returns the first problem found.
+    pub fn validate(&self) -> Result<(), ConfigValidationError> {
+        const HTTP: &[&str] = &["http", "https"];
+
+        check_url(
+            "database.url",
+            &self.database.url,
+            &["postgres", "postgresql"],
+        )?;
+
+        check_secret("jwt.secret", &self.jwt.secret)?;
+        if matches!(self.environment, EnvironmentType::Production)
+            && self.jwt.secret == DEFAULT_JWT_SECRET
+        {
+            return Err(invalid(
+                "jwt.secret",
+                "the default secret must be replaced in production",
+            ));
+        }
+        check_positive("jwt.access_ttl_seconds", self.jwt.access_ttl_seconds)?;
+        check_positive("jwt.refresh_ttl_seconds", self.jwt.refresh_ttl_seconds)?;
+        if self.jwt.access_ttl_seconds > self.jwt.refresh_ttl_seconds {
+            return Err(invalid(
+                "jwt.access_ttl_seconds",
+                "must not exceed jwt.refresh_ttl_seconds",
+            ));
+        }
+        if self.jwt.refresh_ttl_seconds > i64::MAX as u64 / 1000 {
+            return Err(invalid("jwt.refresh_ttl_seconds", "is too large"));
+        }
+        if self.jwt.issuer.trim().is_empty() {
+            return Err(invalid("jwt.issuer", "must not be empty"));
+        }
+        if self.jwt.audience.trim().is_empty() {
+            return Err(invalid("jwt.audience", "must not be empty"));
+        }
+        check_secret("custodial.master_seed", &self.custodial.master_seed)?;
+        if matches!(self.environment, EnvironmentType::Production)
+            && self.custodial.master_seed == DEFAULT_CUSTODIAL_MASTER_SEED
+        {
+            return Err(invalid(
+                "custodial.master_seed",
+                "the default seed must be replaced in production",
+            ));
+        }
+        if let Some(key) = &self.storage.encryption_key {
+            check_secret("storage.encryption_key", key)?;
+        }
+        check_secret(
+            "storage.url_signing_secret",
+            &self.storage.url_signing_secret,
+        )?;
+        if matches!(self.environment, EnvironmentType::Production)
+            && self.storage.url_signing_secret == DEFAULT_FILE_URL_SECRET
+        {
+            return Err(invalid(
+                "storage.url_signing_secret",
+                "the default secret must be replaced in production",
+            ));
+        }
+        check_positive(
+            "storage.signed_url_ttl_seconds",
+            self.storage.signed_url_ttl_seconds,
+        )?;
+        if !(4..=31).contains(&self.pin_hash.cost) {
+            return Err(invalid("pin_hash.cost", "must be between 4 and 31"));
+        }
+        if !(4..=6).contains(&self.pin_policy.min_length) {
+            return Err(invalid("pin_policy.min_length", "must be between 4 and 6"));
+        }
+
+        if self.stellar_network.passphrase.trim().is_empty() {
+            return Err(invalid("stellar.passphrase", "must not be empty"));
+        }
+        check_url(
+            "stellar.horizon_url",
+            &self.stellar_network.horizon_url,
+            HTTP,
+        )?;
+        check_url("stellar.rpc_url", &self.stellar_network.rpc_url, HTTP)?;
+        let fee_payers = self.stellar_network.fee_payer_secret_list();
+        if fee_payers
+            .iter()
+            .enumerate()
+            .any(|(i, secret)| fee_payers[..i].contains(secret))
+        {
+            return Err(invalid(
+                "stellar.fee_payer_secrets",
+                "each fee-payer secret must be distinct",
+            ));
+        }
+        if self.stellar_network.passphrase == PUBLIC_NETWORK_PASSPHRASE
+            && self.stellar_network.accepted_assets.is_empty()
+        {
+            return Err(invalid(
+                "stellar.accepted_assets",
+                "must list the assets payments may use on the public network",
+            ));
+        }
+        for asset in &self.stellar_network.accepted_assets {
+            if asset.code.is_empty() || asset.code.len() > 12 {
+                return Err(invalid(
+                    "stellar.accepted_assets",
+                    format!("asset code {:?} must be 1 to 12 characters", asset.code),
+                ));
+            }
+            if stellar_strkey::ed25519::PublicKey::from_string(&asset.issuer).is_err() {
+                return Err(invalid(
+                    "stellar.accepted_assets",
+                    format!("{} issuer must be a Stellar G... address", asset.code),
+                ));
+            }
+        }
+
+        check_url("anchor.sep24_url", &self.anchor_config.sep24_url, HTTP)?;
+        check_url("anchor.sep31_url", &self.anchor_config.sep31_url, HTTP)?;
+        check_secret("anchor.webhook_secret", &self.anchor_config.webhook_secret)?;
+        for secret in self.anchor_config.webhook_secret_list().iter().skip(1) {
+            check_secret("anchor.previous_webhook_secrets", secret)?;
+        }
+        check_positive(
+            "anchor.circuit_breaker.failure_threshold",
+            self.anchor_config.circuit_breaker.failure_threshold as u64,
+        )?;
+        check_positive(
+            "anchor.circuit_breaker.cooldown_seconds",
+            self.anchor_config.circuit_breaker.cooldown_seconds,
+        )?;
+        check_positive(
+            "anchor.rate_limit_retry.max_wait_seconds",
+            self.anchor_config.rate_limit_retry.max_wait_seconds,
+        )?;
+        let request_timeout = self.server.request_timeout_seconds;
+        if request_timeout > 0
+            && self.anchor_config.rate_limit_retry.max_wait_seconds >= request_timeout
+        {
+            return Err(invalid(
+                "anchor.rate_limit_retry.max_wait_seconds",
+                format!(
+                    "must be less than server.request_timeout_seconds ({})",
+                    request_timeout
+                ),
+            ));
+        }
+        check_positive(
+            "anchor.kyc_batch_concurrency",
+            self.anchor_config.kyc_batch_concurrency as u64,
+        )?;
+        check_withdrawal_limits(
+            "anchor.withdrawal_limits",
+            &self.anchor_config.withdrawal_limits,
+        )?;
+
+        if self.bridge_config.min_bridge_amount > self.bridge_config.max_bridge_amount {
+            return Err(invalid(
+                "bridge.min_bridge_amount",
+                "must not exceed bridge.max_bridge_amount",
+            ));
+        }
+
+        let queue = &self.queue_config;
+        check_url("queue.redis_url", &queue.redis_url, &["redis", "rediss"])?;
+        check_positive("queue.worker_count", queue.worker_count as u64)?;
+        check_positive(
+            "queue.visibility_timeout_seconds",
+            queue.visibility_timeout_seconds,
+        )?;
+        check_positive(
+            "queue.dead_letter_max_size",
+            queue.dead_letter_max_size as u64,
+        )?;
+        check_positive(
+            "queue.reclaim_interval_seconds",
+            queue.reclaim_interval_seconds,
+        )?;
+        // BLPOP treats a zero timeout as "block forever"
+        check_positive("queue.dequeue_block_seconds", queue.dequeue_block_seconds)?;
+        if queue.backoff_multiplier < 1.0 {
+            return Err(invalid("queue.backoff_multiplier", "must be at least 1.0"));
+        }
+        crate::queue::parse_max_retries(&queue.max_retries_overrides)
+            .map_err(|e| invalid("queue.max_retries_overrides", format!("{:#}", e)))?;
+        crate::queue::parse_visibility_timeouts(&queue.visibility_timeout_overrides)
+            .map_err(|e| invalid("queue.visibility_timeout_overrides", format!("{:#}", e)))?;
+        let pools = crate::queue::parse_worker_pools(&queue.worker_pools)
+            .map_err(|e| invalid("queue.worker_pools", format!("{:#}", e)))?;
+        // Every worker can hold a Redis connection at once; leave some for
+        // enqueues from request handlers and the scheduler.
+        let workers = queue.worker_count + pools.values().sum::<usize>();
+        if workers >= crate::queue::REDIS_POOL_SIZE as usize {
+            return Err(invalid(
+                "queue.worker_count",
+                format!(
+                    "together with queue.worker_pools must be below the Redis pool size of {}",
+                    crate::queue::REDIS_POOL_SIZE
+                ),
+            ));
+        }
+        RecurringScheduler::new(queue.recurring_jobs.clone())
+            .map_err(|e| invalid("queue.recurring_jobs", format!("{:#}", e)))?;
+
+        check_positive("rate_limit.window_ms", self.rate_limit.window_ms)?;
+        check_positive(
+            "rate_limit.max_requests",
+            self.rate_limit.max_requests as u64,
+        )?;
+        for token in self.rate_limit.internal_tokens.values() {
+            check_secret("rate_limit.internal_tokens", token)?;
+        }
+
+        check_positive("health.probe_timeout_ms", self.health.probe_timeout_ms)?;
+        check_positive(
+            "http_client.connect_timeout_ms",
+            self.http_client.connect_timeout_ms,
+        )?;
+        check_positive(
+            "http_client.request_timeout_ms",
+            self.http_client.request_timeout_ms,
+        )?;
+        if self.http_client.connect_timeout_ms > self.http_client.request_timeout_ms {
+            return Err(invalid(
+                "http_client.connect_timeout_ms",
+                "must not exceed http_client.request_timeout_ms",
+            ));
+        }
+        check_schedule("audit.schedule", &self.audit.schedule)?;
+        if self.audit.purge_batch_size <= 0 {
+            return Err(invalid(
+                "audit.purge_batch_size",
+                "must be greater than zero",
+            ));
+        }
+        check_positive(
+            "profiles.recovery_window_hours",
+            self.profiles.recovery_window_hours,
+        )?;
+        check_schedule("profiles.purge_schedule", &self.profiles.purge_schedule)?;
+
+        if self.reconciler.enabled {
+            check_schedule("reconciler.schedule", &self.reconciler.schedule)?;
+            check_schedule(
+                "reconciler.webhook_replay_schedule",
+                &self.reconciler.webhook_replay_schedule,
+            )?;
+            if self.reconciler.batch_size <= 0 {
+                return Err(invalid(
+                    "reconciler.batch_size",
+                    "must be greater than zero",
+                ));
+            }
+            if self.reconciler.max_webhook_attempts <= 0 {
+                return Err(invalid(
+                    "reconciler.max_webhook_attempts",
+                    "must be greater than zero",
+                ));
+            }
+        }
+
+        if self.registry_sync.enabled {
+            if self.registry_sync.contract_id.len() != 56
+                || !self.registry_sync.contract_id.starts_with('C')
+            {
+                return Err(invalid(
+                    "registry_sync.contract_id",
+                    "must be a contract address (C...)",
+                ));
+            }
+            check_schedule("registry_sync.schedule", &self.registry_sync.schedule)?;
+            // getEvents serves at most 10,000 events per page
+            if !(1..=10_000).contains(&self.registry_sync.page_size) {
+                return Err(invalid(
+                    "registry_sync.page_size",
+                    "must be between 1 and 10000",
+                ));
+            }
+        }
+
+        if !self.registry_sync.admin_account.is_empty()
+            && !crate::custodial::is_valid_account_id(&self.registry_sync.admin_account)
+        {
+            return Err(invalid(
+                "registry_sync.admin_account",
+                "must be a Stellar account ID (G...)",
+            ));
+        }
+        if !self.registry_sync.admin_account.is_empty()
+            && crate::custodial::account_for_secret(&self.registry_sync.admin_secret).as_deref()
+                != Some(self.registry_sync.admin_account.as_str())
+        {
+            return Err(invalid(
+                "registry_sync.admin_secret",
+                "must be the secret seed (S...) of registry_sync.admin_account",
+            ));
+        }
+
+        if self.reputation.enabled {
+            if self.reputation.contract_id.len() != 56
+                || !self.reputation.contract_id.starts_with('C')
+            {
+                return Err(invalid(
+                    "reputation.contract_id",
+                    "must be a contract address (C...)",
+                ));
+            }
+            let mut min_scores: Vec<u32> =
+                self.reputation.tiers.iter().map(|t| t.min_score).collect();
+            min_scores.sort_unstable();
+            if min_scores.windows(2).any(|pair| pair[0] == pair[1]) {
+                return Err(invalid(
+                    "reputation.tiers",
+                    "each tier needs a distinct min_score",
+                ));
+            }
+            for tier in &self.reputation.tiers {
+                check_withdrawal_limits("reputation.tiers", &tier.withdrawal_limits)?;
+            }
+        }
+
+        if self.balance_reconciliation.enabled {
+            check_schedule(
+                "balance_reconciliation.schedule",
+                &self.balance_reconciliation.schedule,
+            )?;
+            if let Some(url) = &self.balance_reconciliation.alert_webhook_url {
+                check_url("balance_reconciliation.alert_webhook_url", url, HTTP)?;
+            }
+            if self.balance_reconciliation.batch_size <= 0 {
+                return Err(invalid(
+                    "balance_reconciliation.batch_size",
+                    "must be greater than zero",
+                ));
+            }
+        }
+
+        if self.notification_digest.enabled {
+            check_positive(
+                "notification_digest.window_seconds",
+                self.notification_digest.window_seconds,
+            )?;
+        }
+
+        if self
+            .metrics
+            .bearer_token
+            .as_deref()
+            .is_some_and(|token| token.trim().is_empty())
+        {
+            return Err(invalid(
+                "metrics.bearer_token",
+                "must not be empty when set",
+            ));
+        }
+
+        Ok(())
+    }
+
     pub fn load() -> Result<Self, ConfigError> {
         let mut builder = ConfigBuilder::builder()
             .add_source(File::with_name("config/default").required(false))
@@ -164,11 +1267,17 @@ impl Default for Config {
             database: DatabaseConfig {
                 url: "postgres://localhost/BLINKS".to_string(),
             },
-            server: ServerConfig { port: 3000 },
+            server: ServerConfig {
+                port: 3000,
+                shutdown_timeout_seconds: default_shutdown_timeout_seconds(),
+                request_timeout_seconds: default_request_timeout_seconds(),
+            },
             jwt: JwtConfig {
                 secret: "change-this-in-production".to_string(),
-                expiration_hours: 1,
-                refresh_expiration_hours: 168, // 7 days
+                access_ttl_seconds: default_access_ttl_seconds(),
+                refresh_ttl_seconds: default_refresh_ttl_seconds(),
+                issuer: default_jwt_issuer(),
+                audience: default_jwt_audience(),
             },
             stellar_network: StellarNetwork {
                 passphrase: "Test SDF Network ; September 2015".to_string(),
@@ -176,12 +1285,19 @@ impl Default for Config {
                 rpc_url: "https://soroban-testnet.stellar.org".to_string(),
                 network_id: "Test SDF Network ; September 2015".to_string(),
                 fee_payer_secret: None,
+                fee_payer_secrets: None,
+                accepted_assets: Vec::new(),
             },
             anchor_config: AnchorConfig {
                 sep24_url: "https://anchor.example.com/sep24".to_string(),
                 sep31_url: "https://anchor.example.com/sep31".to_string(),
                 webhook_secret: "webhook-secret".to_string(),
+                previous_webhook_secrets: None,
                 kyc_required: true,
+                withdrawal_limits: HashMap::new(),
+                circuit_breaker: CircuitBreakerConfig::default(),
+                rate_limit_retry: RateLimitRetryConfig::default(),
+                kyc_batch_concurrency: default_kyc_batch_concurrency(),
             },
             bridge_config: BridgeConfig {
                 ethereum_rpc_url: "https://mainnet.infura.io/v3/YOUR_PROJECT_ID".to_string(),
@@ -209,19 +1325,361 @@ impl Default for Config {
             queue_config: QueueConfig {
                 redis_url: "redis://localhost:6379".to_string(),
                 max_retries: 3,
+                max_retries_overrides: HashMap::new(),
                 visibility_timeout_seconds: 300,
+                visibility_timeout_overrides: HashMap::new(),
                 backoff_multiplier: 2.0,
                 max_backoff_seconds: 3600,
                 dead_letter_max_size: 10000,
                 worker_count: 4,
+                worker_pools: HashMap::new(),
                 reclaim_interval_seconds: 60,
+                dequeue_block_seconds: default_dequeue_block_seconds(),
+                recurring_jobs: Vec::new(),
             },
             rate_limit: RateLimitConfig {
                 window_ms: 60000, // 1 minute
                 max_requests: 100,
                 scope: RateLimitScope::Ip,
+                internal_tokens: HashMap::new(),
             },
             storage: StorageConfig::default(),
+            health: HealthConfig::default(),
+            http_client: HttpClientConfig::default(),
+            audit: AuditConfig::default(),
+            reconciler: ReconcilerConfig::default(),
+            metrics: MetricsConfig::default(),
+            maintenance: MaintenanceConfig::default(),
+            registry_sync: RegistrySyncConfig::default(),
+            balance_reconciliation: BalanceReconciliationConfig::default(),
+            reputation: ReputationConfig::default(),
+            pin_hash: PinHashConfig::default(),
+            pin_policy: PinPolicyConfig::default(),
+            notification_digest: NotificationDigestConfig::default(),
+            profiles: ProfileConfig::default(),
+            custodial: CustodialConfig::default(),
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_invalid(config: &Config, field: &str) {
+        let err = config.validate().unwrap_err();
+        assert_eq!(err.field, field, "{}", err);
+        assert!(err.to_string().contains(field));
+    }
+
+    #[test]
+    fn default_config_is_valid() {
+        Config::default().validate().unwrap();
+    }
+
+    #[test]
+    fn rejects_short_or_placeholder_secrets() {
+        let mut config = Config::default();
+        config.jwt.secret = "short".to_string();
+        assert_invalid(&config, "jwt.secret");
+
+        let config = Config {
+            environment: EnvironmentType::Production,
+            ..Config::default()
+        };
+        assert_invalid(&config, "jwt.secret");
+
+        let mut config = Config {
+            environment: EnvironmentType::Production,
+            ..Config::default()
+        };
+        config.jwt.secret = "a-production-signing-key".to_string();
+        assert_invalid(&config, "custodial.master_seed");
+
+        config.custodial.master_seed = "a-production-custodial-seed".to_string();
+        assert_invalid(&config, "storage.url_signing_secret");
+
+        let mut config = Config::default();
+        config.anchor_config.webhook_secret = String::new();
+        assert_invalid(&config, "anchor.webhook_secret");
+
+        let mut config = Config::default();
+        config.anchor_config.previous_webhook_secrets =
+            Some("old-webhook-secret, short".to_string());
+        assert_invalid(&config, "anchor.previous_webhook_secrets");
+
+        let mut config = Config::default();
+        config.storage.encryption_key = Some("short".to_string());
+        assert_invalid(&config, "storage.encryption_key");
+
+        let mut config = Config::default();
+        config.storage.url_signing_secret = "short".to_string();
+        assert_invalid(&config, "storage.url_signing_secret");
+
+        let mut config = Config::default();
+        config.storage.signed_url_ttl_seconds = 0;
+        assert_invalid(&config, "storage.signed_url_ttl_seconds");
+
+        let mut config = Config::default();
+        config.anchor_config.withdrawal_limits.insert(
+            "USDC".to_string(),
+            WithdrawalLimit {
+                min: Some(500),
+                max: Some(100),
+            },
+        );
+        assert_invalid(&config, "anchor.withdrawal_limits");
+
+        let mut config = Config::default();
+        config.stellar_network.fee_payer_secret = Some("SKEYONE".to_string());
+        config.stellar_network.fee_payer_secrets = Some("SKEYTWO,SKEYONE".to_string());
+        assert_invalid(&config, "stellar.fee_payer_secrets");
+
+        let mut config = Config::default();
+        config.stellar_network.accepted_assets = vec![AcceptedAsset {
+            code: "USDC".to_string(),
+            issuer: "GISSUER".to_string(),
+        }];
+        assert_invalid(&config, "stellar.accepted_assets");
+
+        let mut config = Config::default();
+        config.stellar_network.passphrase = PUBLIC_NETWORK_PASSPHRASE.to_string();
+        assert_invalid(&config, "stellar.accepted_assets");
+        config.stellar_network.accepted_assets = vec![AcceptedAsset {
+            code: "USDC".to_string(),
+            issuer: "GBBD47IF6LWK7P7MDEVSCWR7DPUWV3NY3DTQEVFL4NAT4AQH3ZLLFLA5".to_string(),
+        }];
+        config.validate().unwrap();
+
+        let mut config = Config::default();
+        config.jwt.access_ttl_seconds = 0;
+        assert_invalid(&config, "jwt.access_ttl_seconds");
+
+        let mut config = Config::default();
+        config.jwt.access_ttl_seconds = config.jwt.refresh_ttl_seconds + 1;
+        assert_invalid(&config, "jwt.access_ttl_seconds");
+
+        let mut config = Config::default();
+        config.jwt.refresh_ttl_seconds = u64::MAX;
+        assert_invalid(&config, "jwt.refresh_ttl_seconds");
+
+        let mut config = Config::default();
+        config.jwt.issuer = " ".to_string();
+        assert_invalid(&config, "jwt.issuer");
+
+        let mut config = Config::default();
+        config.jwt.audience = String::new();
+        assert_invalid(&config, "jwt.audience");
+
+        let mut config = Config::default();
+        config
+            .rate_limit
+            .internal_tokens
+            .insert("reconciler".to_string(), "short".to_string());
+        assert_invalid(&config, "rate_limit.internal_tokens");
+    }
+
+    #[test]
+    fn rejects_malformed_urls() {
+        let mut config = Config::default();
+        config.stellar_network.rpc_url = String::new();
+        assert_invalid(&config, "stellar.rpc_url");
+
+        let mut config = Config::default();
+        config.database.url = "mysql://localhost/blinks".to_string();
+        assert_invalid(&config, "database.url");
+
+        let mut config = Config::default();
+        config.queue_config.redis_url = "localhost:6379".to_string();
+        assert_invalid(&config, "queue.redis_url");
+
+        let mut config = Config::default();
+        config.registry_sync.admin_account = "GNOTANACCOUNT".to_string();
+        assert_invalid(&config, "registry_sync.admin_account");
+
+        let mut config = Config::default();
+        let admin = crate::custodial::CustodialKeys::new("registry-admin").derive("admin");
+        config.registry_sync.admin_account = admin.address.clone();
+        assert_invalid(&config, "registry_sync.admin_secret");
+        config.registry_sync.admin_secret = crate::custodial::CustodialKeys::new("other")
+            .derive("admin")
+            .secret_seed()
+            .to_string();
+        assert_invalid(&config, "registry_sync.admin_secret");
+        config.registry_sync.admin_secret = admin.secret_seed().to_string();
+        config.validate().unwrap();
+
+        let mut config = Config::default();
+        config.registry_sync.enabled = true;
+        config.registry_sync.contract_id = format!("C{}", "A".repeat(55));
+        config.registry_sync.schedule = "every 30 seconds".to_string();
+        assert_invalid(&config, "registry_sync.schedule");
+    }
+
+    #[test]
+    fn rejects_non_positive_sizes() {
+        let mut config = Config::default();
+        config.queue_config.worker_count = 0;
+        assert_invalid(&config, "queue.worker_count");
+
+        let mut config = Config::default();
+        config.queue_config.dequeue_block_seconds = 0;
+        assert_invalid(&config, "queue.dequeue_block_seconds");
+
+        let mut config = Config::default();
+        config.anchor_config.circuit_breaker.failure_threshold = 0;
+        assert_invalid(&config, "anchor.circuit_breaker.failure_threshold");
+
+        let mut config = Config::default();
+        config.anchor_config.kyc_batch_concurrency = 0;
+        assert_invalid(&config, "anchor.kyc_batch_concurrency");
+
+        let mut config = Config::default();
+        config.anchor_config.rate_limit_retry.max_wait_seconds = 0;
+        assert_invalid(&config, "anchor.rate_limit_retry.max_wait_seconds");
+
+        let mut config = Config::default();
+        config.anchor_config.rate_limit_retry.max_wait_seconds =
+            config.server.request_timeout_seconds;
+        assert_invalid(&config, "anchor.rate_limit_retry.max_wait_seconds");
+
+        let mut config = Config::default();
+        config.pin_hash.cost = 3;
+        assert_invalid(&config, "pin_hash.cost");
+
+        let mut config = Config::default();
+        config.pin_policy.min_length = 7;
+        assert_invalid(&config, "pin_policy.min_length");
+
+        let mut config = Config::default();
+        config.notification_digest.window_seconds = 0;
+        assert_invalid(&config, "notification_digest.window_seconds");
+
+        let mut config = Config::default();
+        config.profiles.recovery_window_hours = 0;
+        assert_invalid(&config, "profiles.recovery_window_hours");
+
+        let mut config = Config::default();
+        config.audit.schedule = "daily".to_string();
+        assert_invalid(&config, "audit.schedule");
+        config.audit.schedule = "0 3 * * *".to_string();
+        config.audit.purge_batch_size = 0;
+        assert_invalid(&config, "audit.purge_batch_size");
+
+        let mut config = Config::default();
+        config.profiles.purge_schedule = "daily".to_string();
+        assert_invalid(&config, "profiles.purge_schedule");
+
+        let mut config = Config::default();
+        config.reconciler.batch_size = 0;
+        assert_invalid(&config, "reconciler.batch_size");
+        config.reconciler.batch_size = 50;
+        config.reconciler.max_webhook_attempts = 0;
+        assert_invalid(&config, "reconciler.max_webhook_attempts");
+        config.reconciler.max_webhook_attempts = 10;
+        config.reconciler.webhook_replay_schedule = "every minute".to_string();
+        assert_invalid(&config, "reconciler.webhook_replay_schedule");
+
+        // Only checked while the reconciler runs.
+        config.reconciler.enabled = false;
+        config.validate().unwrap();
+        assert!(!config
+            .recurring_jobs()
+            .iter()
+            .any(|job| job.name == "anchor_webhook_replay"));
+
+        let mut config = Config::default();
+        config.balance_reconciliation.batch_size = 0;
+        config.validate().unwrap();
+        config.balance_reconciliation.enabled = true;
+        assert_invalid(&config, "balance_reconciliation.batch_size");
+        config.balance_reconciliation.batch_size = 500;
+        config.balance_reconciliation.schedule = "hourly".to_string();
+        assert_invalid(&config, "balance_reconciliation.schedule");
+        config.balance_reconciliation.schedule = "0 * * * *".to_string();
+        config.balance_reconciliation.alert_webhook_url = Some("ftp://alerts".to_string());
+        assert_invalid(&config, "balance_reconciliation.alert_webhook_url");
+
+        let mut config = Config::default();
+        config.reputation.tiers = vec![
+            ReputationTier {
+                name: "new".to_string(),
+                ..Default::default()
+            },
+            ReputationTier {
+                name: "duplicate".to_string(),
+                ..Default::default()
+            },
+        ];
+        config.validate().unwrap();
+        config.reputation.enabled = true;
+        config.reputation.contract_id = format!("C{}", "A".repeat(55));
+        assert_invalid(&config, "reputation.tiers");
+        config.reputation.tiers[1].min_score = 500;
+        config.validate().unwrap();
+        config.reputation.tiers[1].withdrawal_limits.insert(
+            "USDC".to_string(),
+            WithdrawalLimit {
+                min: Some(0),
+                max: None,
+            },
+        );
+        assert_invalid(&config, "reputation.tiers");
+
+        let mut config = Config::default();
+        config.http_client.request_timeout_ms = 0;
+        assert_invalid(&config, "http_client.request_timeout_ms");
+
+        let mut config = Config::default();
+        config.http_client.connect_timeout_ms = config.http_client.request_timeout_ms + 1;
+        assert_invalid(&config, "http_client.connect_timeout_ms");
+    }
+
+    #[test]
+    fn rejects_unknown_job_types_and_bad_cron() {
+        let mut config = Config::default();
+        config
+            .queue_config
+            .visibility_timeout_overrides
+            .insert("no_such_job".to_string(), 60);
+        assert_invalid(&config, "queue.visibility_timeout_overrides");
+
+        let mut config = Config::default();
+        config
+            .queue_config
+            .max_retries_overrides
+            .insert("no_such_job".to_string(), 5);
+        assert_invalid(&config, "queue.max_retries_overrides");
+
+        let mut config = Config::default();
+        config
+            .queue_config
+            .worker_pools
+            .insert("no_such_job".to_string(), 1);
+        assert_invalid(&config, "queue.worker_pools");
+
+        let mut config = Config::default();
+        config
+            .queue_config
+            .worker_pools
+            .insert("email".to_string(), 0);
+        assert_invalid(&config, "queue.worker_pools");
+
+        let mut config = Config::default();
+        config.queue_config.worker_count = 10;
+        config
+            .queue_config
+            .worker_pools
+            .insert("email".to_string(), 10);
+        assert_invalid(&config, "queue.worker_count");
+
+        let mut config = Config::default();
+        config.queue_config.recurring_jobs.push(RecurringJob {
+            name: "broken".to_string(),
+            job_type: crate::job_types::JobType::Sync,
+            payload: HashMap::new(),
+            cron_expr: "every day".to_string(),
+        });
+        assert_invalid(&config, "queue.recurring_jobs");
+    }
+}
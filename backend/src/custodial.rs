@@ -0,0 +1,159 @@
+//! Custodial Stellar keypairs for users.
+//!
+//! Each user's keypair is derived from the configured master seed and their
+//! `user_id`, so no per-user secret is ever stored: the signing key can be
+//! re-derived whenever it's needed, and the database only holds the public
+//! `G...` address. Changing the master seed orphans every existing address.
+
+use ring::{
+    hmac,
+    signature::{Ed25519KeyPair, KeyPair},
+};
+use stellar_strkey::ed25519::{PrivateKey, PublicKey};
+
+/// Domain separator so the master seed can't be reused to derive anything
+/// else under the same inputs.
+const DERIVATION_CONTEXT: &[u8] = b"zaps:custodial-account:v1:";
+
+/// A user's derived account.
+#[derive(Clone, PartialEq, Eq)]
+pub struct CustodialKeypair {
+    /// Stellar account ID (`G...`).
+    pub address: String,
+    /// Secret seed (`S...`) for signing as the account.
+    secret_seed: String,
+}
+
+impl CustodialKeypair {
+    pub fn secret_seed(&self) -> &str {
+        &self.secret_seed
+    }
+}
+
+impl std::fmt::Debug for CustodialKeypair {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("CustodialKeypair")
+            .field("address", &self.address)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Derives user keypairs from the custodial master seed.
+#[derive(Clone)]
+pub struct CustodialKeys {
+    master: hmac::Key,
+}
+
+impl CustodialKeys {
+    pub fn new(master_seed: &str) -> Self {
+        Self {
+            master: hmac::Key::new(hmac::HMAC_SHA256, master_seed.as_bytes()),
+        }
+    }
+
+    /// The keypair for `user_id`; the same inputs always give the same keys.
+    pub fn derive(&self, user_id: &str) -> CustodialKeypair {
+        let mut ctx = hmac::Context::with_key(&self.master);
+        ctx.update(DERIVATION_CONTEXT);
+        ctx.update(user_id.as_bytes());
+        let tag = ctx.sign();
+
+        let mut seed = [0u8; 32];
+        seed.copy_from_slice(tag.as_ref());
+        let keypair = Ed25519KeyPair::from_seed_unchecked(&seed)
+            .expect("an Ed25519 seed of 32 bytes is always valid");
+
+        let mut public = [0u8; 32];
+        public.copy_from_slice(keypair.public_key().as_ref());
+
+        CustodialKeypair {
+            address: PublicKey(public).to_string(),
+            secret_seed: PrivateKey(seed).to_string(),
+        }
+    }
+}
+
+/// The account ID (`G...`) of a secret seed (`S...`), or `None` if
+/// `secret` isn't one.
+pub fn account_for_secret(secret: &str) -> Option<String> {
+    let seed = PrivateKey::from_string(secret).ok()?;
+    let keypair = Ed25519KeyPair::from_seed_unchecked(&seed.0).ok()?;
+
+    let mut public = [0u8; 32];
+    public.copy_from_slice(keypair.public_key().as_ref());
+    Some(PublicKey(public).to_string())
+}
+
+/// Whether `address` is a well-formed Stellar account ID: a `G...` strkey
+/// with a valid checksum.
+pub fn is_valid_account_id(address: &str) -> bool {
+    PublicKey::from_string(address).is_ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn derived_address_is_a_valid_account_id() {
+        let keypair = CustodialKeys::new("test-master-seed").derive("alice");
+
+        assert_eq!(keypair.address.len(), 56);
+        assert!(keypair.address.starts_with('G'));
+        assert!(is_valid_account_id(&keypair.address));
+        assert!(keypair.secret_seed().starts_with('S'));
+        assert!(PrivateKey::from_string(keypair.secret_seed()).is_ok());
+    }
+
+    #[test]
+    fn secret_seed_signs_for_the_address() {
+        let keypair = CustodialKeys::new("test-master-seed").derive("alice");
+
+        let seed = PrivateKey::from_string(keypair.secret_seed()).unwrap().0;
+        let signer = Ed25519KeyPair::from_seed_unchecked(&seed).unwrap();
+        let public = PublicKey::from_string(&keypair.address).unwrap().0;
+        assert_eq!(signer.public_key().as_ref(), public);
+    }
+
+    #[test]
+    fn derivation_is_deterministic_per_user_and_seed() {
+        let keys = CustodialKeys::new("test-master-seed");
+
+        assert_eq!(keys.derive("alice"), keys.derive("alice"));
+        assert_ne!(keys.derive("alice").address, keys.derive("bob").address);
+        assert_ne!(
+            keys.derive("alice").address,
+            CustodialKeys::new("other-master-seed")
+                .derive("alice")
+                .address
+        );
+    }
+
+    #[test]
+    fn debug_output_omits_the_secret() {
+        let keypair = CustodialKeys::new("test-master-seed").derive("alice");
+        let debug = format!("{:?}", keypair);
+
+        assert!(debug.contains(&keypair.address));
+        assert!(!debug.contains(keypair.secret_seed()));
+    }
+
+    #[test]
+    fn rejects_malformed_account_ids() {
+        let valid = CustodialKeys::new("test-master-seed")
+            .derive("alice")
+            .address;
+        let mut corrupted = valid.clone().into_bytes();
+        corrupted[10] = if corrupted[10] == b'A' { b'B' } else { b'A' };
+
+        assert!(!is_valid_account_id(&String::from_utf8(corrupted).unwrap()));
+        assert!(!is_valid_account_id(&valid[..55]));
+        assert!(!is_valid_account_id(&format!(
+            "G{}",
+            uuid::Uuid::new_v4().simple().to_string().to_uppercase()
+        )));
+        assert!(!is_valid_account_id(
+            "SAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAA"
+        ));
+    }
+}
@@ -31,6 +31,41 @@ pub async fn run_migrations(database_url: &str) -> Result<(), Box<dyn std::error
     Ok(())
 }
 
+/// Version of the newest migration built into this binary, i.e. the schema
+/// version the code expects the database to be at.
+pub fn expected_schema_version() -> i64 {
+    sqlx::migrate!("./migrations")
+        .iter()
+        .map(|migration| migration.version)
+        .max()
+        .unwrap_or(0)
+}
+
+/// Version of the newest migration successfully applied to the database, or
+/// `None` if migrations have never been run against it.
+pub async fn applied_schema_version(
+    client: &tokio_postgres::Client,
+) -> Result<Option<i64>, tokio_postgres::Error> {
+    let tracked: bool = client
+        .query_one(
+            "SELECT to_regclass('public._sqlx_migrations') IS NOT NULL",
+            &[],
+        )
+        .await?
+        .get(0);
+    if !tracked {
+        return Ok(None);
+    }
+
+    client
+        .query_one(
+            "SELECT MAX(version) FROM _sqlx_migrations WHERE success",
+            &[],
+        )
+        .await
+        .map(|row| row.get(0))
+}
+
 /// Reset migrations for testing purposes
 /// This drops all tables, types, and the migration history to allow re-running migrations
 /// WARNING: Only use this in test environments! This will destroy all data in the database.
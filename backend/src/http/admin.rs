@@ -1,11 +1,39 @@
 use axum::{
-    extract::{Path, State},
+    body::Body,
+    extract::{Path, Query, State},
+    http::{header, StatusCode},
+    response::IntoResponse,
     Json,
 };
-use serde::Serialize;
+use futures::stream;
+use serde::{Deserialize, Serialize};
+use std::convert::Infallible;
 use std::sync::Arc;
+use uuid::Uuid;
 
-use crate::{api_error::ApiError, service::ServiceContainer};
+use crate::{
+    api_error::ApiError,
+    http::audit::csv_quote,
+    job_types::JobType,
+    middleware::auth::AuthenticatedUser,
+    models::{
+        DashboardStatsExportParams, FeeStrategy, StatsExportFormat, TransactionKind,
+        TransactionListResponse, TransactionQueryParams, TransactionStats,
+    },
+    queue::ReplayOutcome,
+    role::Role,
+    service::anchor_service::WithdrawalRecord,
+    service::api_key_service::IssuedApiKey,
+    service::memo_policy_service::MemoPolicy,
+    service::merchant_service::MerchantDeactivation,
+    service::merchant_webhook_service::MerchantWebhook,
+    service::registry_sync_service::RegistrySyncSummary,
+    service::transaction_service::MAX_TRANSACTION_PAGE_SIZE,
+    service::ServiceContainer,
+};
+
+/// Upper bound on jobs replayed by a single request.
+const MAX_DEAD_LETTER_REPLAY: usize = 10_000;
 
 #[derive(Debug, Serialize)]
 pub struct DashboardStats {
@@ -16,6 +44,70 @@ pub struct DashboardStats {
     pub active_merchants: i64,
 }
 
+#[derive(Debug, Deserialize)]
+pub struct DeadLetterReplayRequest {
+    pub job_type: Option<JobType>,
+    #[serde(default = "default_replay_max")]
+    pub max: usize,
+}
+
+fn default_replay_max() -> usize {
+    100
+}
+
+#[derive(Debug, Serialize)]
+pub struct UserRoleResponse {
+    pub user_id: String,
+    pub role: Role,
+}
+
+#[derive(Debug, Serialize)]
+pub struct AdminUserResponse {
+    pub user_id: String,
+    pub stellar_address: String,
+    pub role: Role,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+    /// When the user last authenticated, to within a minute; `null` if never.
+    pub last_seen_at: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct UpdateUserRoleRequest {
+    pub role: Role,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct RegisterMerchantWebhookRequest {
+    pub url: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SetMemoPolicyRequest {
+    /// Regex the whole memo must match; omit to accept any memo.
+    pub pattern: Option<String>,
+    #[serde(default)]
+    pub auto_generate: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct MerchantFeeStrategy {
+    pub fee_strategy: FeeStrategy,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct OverrideWithdrawalStatusRequest {
+    /// `completed`, `failed` or `refunded`.
+    pub status: String,
+    /// Why the status was forced, e.g. the out-of-band confirmation.
+    pub reason: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct RegistrySyncRequest {
+    #[serde(default)]
+    pub dry_run: bool,
+}
+
 #[derive(Debug, Serialize)]
 pub struct SystemHealth {
     pub database: String,
@@ -23,23 +115,116 @@ pub struct SystemHealth {
 }
 
 pub async fn get_dashboard_stats(
-    State(_services): State<Arc<ServiceContainer>>,
+    State(services): State<Arc<ServiceContainer>>,
 ) -> Result<Json<DashboardStats>, ApiError> {
-    // Placeholder implementation
+    let stats = services
+        .transactions
+        .stats_by_asset_and_status(None, None)
+        .await?;
+    let (total_users, active_merchants) = services.transactions.directory_totals().await?;
+
+    let total = |kind: TransactionKind| {
+        stats
+            .iter()
+            .filter(|s| s.kind == kind)
+            .map(|s| s.count)
+            .sum()
+    };
+
     Ok(Json(DashboardStats {
-        total_users: 0,
-        total_payments: 0,
-        total_transfers: 0,
-        total_withdrawals: 0,
-        active_merchants: 0,
+        total_users,
+        total_payments: total(TransactionKind::Payment),
+        total_transfers: total(TransactionKind::Transfer),
+        total_withdrawals: total(TransactionKind::Withdrawal),
+        active_merchants,
     }))
 }
 
+/// GET /admin/dashboard/stats/export?from=&to=&format=csv
+///
+/// Count and volume per transaction type, asset and status for transactions
+/// created in the window, as a download for finance.
+pub async fn export_dashboard_stats(
+    State(services): State<Arc<ServiceContainer>>,
+    Query(params): Query<DashboardStatsExportParams>,
+) -> Result<impl IntoResponse, ApiError> {
+    let stats = services
+        .transactions
+        .stats_by_asset_and_status(params.from, params.to)
+        .await?;
+
+    let (content_type, extension, lines) = match params.format {
+        StatsExportFormat::Csv => ("text/csv; charset=utf-8", "csv", stats_csv(&stats)),
+    };
+
+    let disposition = format!(
+        "attachment; filename=\"{}\"",
+        stats_export_filename(&params, extension)
+    );
+
+    Ok((
+        [
+            (header::CONTENT_TYPE, content_type.to_string()),
+            (header::CONTENT_DISPOSITION, disposition),
+        ],
+        Body::from_stream(stream::iter(lines.into_iter().map(Ok::<_, Infallible>))),
+    ))
+}
+
+const STATS_CSV_HEADER: &str = "type,asset,status,count,volume\n";
+
+/// Header line followed by one line per type, asset and status.
+fn stats_csv(stats: &[TransactionStats]) -> Vec<String> {
+    let rows = stats.iter().map(|s| {
+        let fields = [
+            s.kind.as_str(),
+            s.asset.as_str(),
+            s.status.as_deref().unwrap_or(""),
+            &s.count.to_string(),
+            &s.volume.to_string(),
+        ];
+        let mut line = fields
+            .iter()
+            .map(|f| csv_quote(f))
+            .collect::<Vec<_>>()
+            .join(",");
+        line.push('\n');
+        line
+    });
+
+    std::iter::once(STATS_CSV_HEADER.to_string())
+        .chain(rows)
+        .collect()
+}
+
+fn stats_export_filename(params: &DashboardStatsExportParams, extension: &str) -> String {
+    let from = params
+        .from
+        .map(|d| d.format("%Y%m%d").to_string())
+        .unwrap_or_else(|| "start".to_string());
+    let to = params
+        .to
+        .map(|d| d.format("%Y%m%d").to_string())
+        .unwrap_or_else(|| "now".to_string());
+
+    format!("dashboard-stats_{}_{}.{}", from, to, extension)
+}
+
+/// GET /admin/transactions?type=&status=&user_id=&from=&to=&limit=&offset=
+///
+/// Payments, transfers and withdrawals in one list, newest first.
 pub async fn get_transactions(
-    State(_services): State<Arc<ServiceContainer>>,
-) -> Result<Json<Vec<serde_json::Value>>, ApiError> {
-    // Placeholder implementation
-    Ok(Json(vec![]))
+    State(services): State<Arc<ServiceContainer>>,
+    Query(params): Query<TransactionQueryParams>,
+) -> Result<Json<TransactionListResponse>, ApiError> {
+    let (transactions, total) = services.transactions.list_transactions(&params).await?;
+
+    Ok(Json(TransactionListResponse {
+        transactions,
+        total,
+        limit: params.limit.clamp(1, MAX_TRANSACTION_PAGE_SIZE),
+        offset: params.offset.max(0),
+    }))
 }
 
 pub async fn get_user_activity(
@@ -50,6 +235,54 @@ pub async fn get_user_activity(
     Ok(Json(vec![]))
 }
 
+/// GET /admin/users/:user_id
+pub async fn get_user_details(
+    State(services): State<Arc<ServiceContainer>>,
+    Path(user_id): Path<String>,
+) -> Result<Json<AdminUserResponse>, ApiError> {
+    let user = services.identity.get_user_by_id(&user_id).await?;
+    Ok(Json(AdminUserResponse {
+        user_id: user.user_id,
+        stellar_address: user.stellar_address,
+        role: user.role,
+        created_at: user.created_at,
+        last_seen_at: user.last_seen_at,
+    }))
+}
+
+/// GET /admin/users/:user_id/role
+pub async fn get_user_role(
+    State(services): State<Arc<ServiceContainer>>,
+    Path(user_id): Path<String>,
+) -> Result<Json<UserRoleResponse>, ApiError> {
+    let user = services.identity.get_user_by_id(&user_id).await?;
+    Ok(Json(UserRoleResponse {
+        user_id: user.user_id,
+        role: user.role,
+    }))
+}
+
+/// PATCH /admin/users/:user_id/role - Promote or demote a user
+///
+/// Actual changes are recorded in the audit log. The last admin can't be
+/// demoted. Tokens already issued keep their old role until they expire.
+pub async fn update_user_role(
+    State(services): State<Arc<ServiceContainer>>,
+    admin: AuthenticatedUser,
+    Path(user_id): Path<String>,
+    Json(request): Json<UpdateUserRoleRequest>,
+) -> Result<Json<UserRoleResponse>, ApiError> {
+    services
+        .identity
+        .set_role(&user_id, request.role, &admin.user_id)
+        .await?;
+
+    Ok(Json(UserRoleResponse {
+        user_id,
+        role: request.role,
+    }))
+}
+
 pub async fn get_system_health(
     State(_services): State<Arc<ServiceContainer>>,
 ) -> Result<Json<SystemHealth>, ApiError> {
@@ -59,3 +292,202 @@ pub async fn get_system_health(
         services: vec!["identity".to_string(), "payment".to_string()],
     }))
 }
+
+/// POST /admin/jobs/dead-letter/replay - Re-enqueue dead-lettered jobs in bulk
+pub async fn replay_dead_letters(
+    State(services): State<Arc<ServiceContainer>>,
+    Json(request): Json<DeadLetterReplayRequest>,
+) -> Result<Json<ReplayOutcome>, ApiError> {
+    if request.max == 0 || request.max > MAX_DEAD_LETTER_REPLAY {
+        return Err(ApiError::Validation(format!(
+            "max must be between 1 and {}",
+            MAX_DEAD_LETTER_REPLAY
+        )));
+    }
+
+    let outcome = services
+        .job_queue
+        .replay_dead_letters(request.job_type.as_ref(), request.max)
+        .await
+        .map_err(|e| {
+            tracing::error!(error = %e, "Failed to replay dead letter queue");
+            ApiError::InternalServerError
+        })?;
+
+    Ok(Json(outcome))
+}
+
+/// POST /admin/registry/sync - Sync on-chain registrations now
+///
+/// With `dry_run` set nothing is written; the response lists the merchant
+/// and user changes a real sync would make.
+pub async fn sync_registry(
+    State(services): State<Arc<ServiceContainer>>,
+    Json(request): Json<RegistrySyncRequest>,
+) -> Result<Json<RegistrySyncSummary>, ApiError> {
+    let summary = services.registry_sync.sync(request.dry_run).await?;
+    Ok(Json(summary))
+}
+
+/// POST /admin/merchants/:merchant_id/api-keys - Issue a merchant API key
+///
+/// The plaintext key is only ever returned by this response.
+pub async fn issue_merchant_api_key(
+    State(services): State<Arc<ServiceContainer>>,
+    Path(merchant_id): Path<String>,
+) -> Result<(StatusCode, Json<IssuedApiKey>), ApiError> {
+    let issued = services.api_keys.issue_key(&merchant_id).await?;
+    Ok((StatusCode::CREATED, Json(issued)))
+}
+
+/// DELETE /admin/merchants/:merchant_id/api-keys/:key_id - Revoke a merchant API key
+pub async fn revoke_merchant_api_key(
+    State(services): State<Arc<ServiceContainer>>,
+    Path((merchant_id, key_id)): Path<(String, Uuid)>,
+) -> Result<StatusCode, ApiError> {
+    services
+        .api_keys
+        .revoke_key(&merchant_id, &key_id.to_string())
+        .await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// PUT /admin/merchants/:merchant_id/webhook - Register where a merchant's
+/// settlement events are sent. Returns a fresh signing secret, replacing any
+/// earlier webhook and secret.
+pub async fn register_merchant_webhook(
+    State(services): State<Arc<ServiceContainer>>,
+    Path(merchant_id): Path<String>,
+    Json(request): Json<RegisterMerchantWebhookRequest>,
+) -> Result<Json<MerchantWebhook>, ApiError> {
+    let webhook = services
+        .merchant_webhooks
+        .register(&merchant_id, &request.url)
+        .await?;
+    Ok(Json(webhook))
+}
+
+/// PUT /admin/merchants/:merchant_id/memo-policy - Set the format a
+/// merchant's payment memos must follow, and whether one is generated when a
+/// payment omits it.
+pub async fn set_merchant_memo_policy(
+    State(services): State<Arc<ServiceContainer>>,
+    Path(merchant_id): Path<String>,
+    Json(request): Json<SetMemoPolicyRequest>,
+) -> Result<Json<MemoPolicy>, ApiError> {
+    let policy = services
+        .memo_policies
+        .set_policy(
+            &merchant_id,
+            request.pattern.as_deref(),
+            request.auto_generate,
+        )
+        .await?;
+    Ok(Json(policy))
+}
+
+/// PUT /admin/merchants/:merchant_id/fee-strategy - Set who pays the
+/// network fee on a merchant's payments. Payers may still choose to pay it
+/// themselves.
+pub async fn set_merchant_fee_strategy(
+    State(services): State<Arc<ServiceContainer>>,
+    Path(merchant_id): Path<String>,
+    Json(request): Json<MerchantFeeStrategy>,
+) -> Result<Json<MerchantFeeStrategy>, ApiError> {
+    services
+        .merchants
+        .set_fee_strategy(&merchant_id, request.fee_strategy)
+        .await?;
+    Ok(Json(request))
+}
+
+/// PATCH /admin/withdrawals/:id/status - Force a stuck withdrawal to a
+/// terminal status once it has been confirmed out of band. The reason is
+/// kept in the audit log.
+pub async fn override_withdrawal_status(
+    State(services): State<Arc<ServiceContainer>>,
+    admin: AuthenticatedUser,
+    Path(withdrawal_id): Path<Uuid>,
+    Json(request): Json<OverrideWithdrawalStatusRequest>,
+) -> Result<Json<WithdrawalRecord>, ApiError> {
+    let reason = request.reason.trim();
+    if reason.is_empty() {
+        return Err(ApiError::Validation("reason is required".to_string()));
+    }
+
+    let transition = services
+        .anchor
+        .override_withdrawal_status(
+            &withdrawal_id.to_string(),
+            &request.status,
+            &admin.user_id,
+            reason,
+            services.job_queue.as_ref(),
+        )
+        .await?;
+
+    Ok(Json(transition.record))
+}
+
+/// DELETE /admin/merchants/:merchant_id - Deactivate a merchant here and in
+/// the on-chain registry, once the chain call has succeeded. Nothing changes
+/// if it fails.
+pub async fn deactivate_merchant(
+    State(services): State<Arc<ServiceContainer>>,
+    Path(merchant_id): Path<String>,
+) -> Result<Json<MerchantDeactivation>, ApiError> {
+    let deactivation = services.merchants.deactivate(&merchant_id).await?;
+    Ok(Json(deactivation))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn stats(
+        kind: TransactionKind,
+        status: Option<&str>,
+        count: i64,
+        volume: i64,
+    ) -> TransactionStats {
+        TransactionStats {
+            kind,
+            asset: "USDC".to_string(),
+            status: status.map(str::to_string),
+            count,
+            volume,
+        }
+    }
+
+    #[test]
+    fn stats_csv_has_header_and_one_line_per_group() {
+        let lines = stats_csv(&[
+            stats(TransactionKind::Payment, Some("completed"), 3, 1_500),
+            stats(TransactionKind::Transfer, None, 1, 20),
+        ]);
+
+        assert_eq!(
+            lines,
+            [
+                STATS_CSV_HEADER,
+                "\"payment\",\"USDC\",\"completed\",\"3\",\"1500\"\n",
+                "\"transfer\",\"USDC\",\"\",\"1\",\"20\"\n",
+            ]
+        );
+    }
+
+    #[test]
+    fn stats_export_filename_reflects_date_range() {
+        let params = DashboardStatsExportParams {
+            format: StatsExportFormat::Csv,
+            from: Some(chrono::Utc.with_ymd_and_hms(2026, 1, 1, 0, 0, 0).unwrap()),
+            to: None,
+        };
+
+        assert_eq!(
+            stats_export_filename(&params, "csv"),
+            "dashboard-stats_20260101_now.csv"
+        );
+    }
+}
@@ -13,7 +13,13 @@ use serde::{Deserialize, Serialize};
 use std::sync::Arc;
 use tracing::{error, info, warn};
 
-use crate::{api_error::ApiError, service::ServiceContainer};
+use crate::{
+    api_error::ApiError,
+    service::{
+        anchor_service::{AnchorWebhookEvent, WebhookDisposition},
+        ServiceContainer,
+    },
+};
 
 // ──────────────────────────────────────────────────────────────────────────────
 // Webhook payload shape
@@ -44,10 +50,14 @@ pub struct WebhookAck {
 ///
 /// 1. Verify the `X-Stellar-Signature` HMAC-SHA256 header.
 /// 2. Parse the JSON body.
-/// 3. Look up the withdrawal by `anchor_tx_id` and update its status.
+/// 3. Record the event in the webhook ledger, then look up the deposit or
+///    withdrawal by `anchor_tx_id` and update its status, notifying the user
+///    when a withdrawal reaches a terminal status. Redeliveries of an already-processed event are
+///    acknowledged without being applied again.
 ///
 /// Returns `200 OK` with `{"received": true}` on success so the Anchor stops
-/// retrying.  Returns `401` on signature failure so misconfigured senders are
+/// retrying.  If processing fails the error is returned and the ledger entry is
+/// replayed by the reconciler.  Returns `401` on signature failure so misconfigured senders are
 /// clearly rejected.
 pub async fn anchor_webhook(
     State(services): State<Arc<ServiceContainer>>,
@@ -81,62 +91,31 @@ pub async fn anchor_webhook(
         "Anchor webhook received"
     );
 
-    // ── Step 3: Sync withdrawal status ────────────────────────────────────────
-    // Map anchor status strings to our internal withdrawal status values
-    let internal_status = match payload.status.as_str() {
-        "completed" => "completed",
-        "error" | "expired" => "failed",
-        "refunded" => "refunded",
-        "pending_stellar"
-        | "pending_anchor"
-        | "pending_external"
-        | "pending_user"
-        | "pending_user_transfer_start" => "processing",
-        _ => "pending",
+    // ── Step 3: Record and sync transaction status ────────────────────────────
+    let event = AnchorWebhookEvent {
+        transaction_id: payload.transaction_id,
+        status: payload.status,
+        message: payload.message,
     };
-
-    // Find the withdrawal by anchor_tx_id and update it
-    match find_withdrawal_by_anchor_tx_id(&services, &payload.transaction_id).await {
-        Some(withdrawal_id) => {
-            services
-                .anchor
-                .update_withdrawal_status(&withdrawal_id, internal_status, None)
-                .await?;
-
-            if let Some(ref msg) = payload.message {
-                info!(
-                    withdrawal_id = %withdrawal_id,
-                    anchor_message = %msg,
-                    "Anchor webhook message logged"
-                );
-            }
-        }
-        None => {
-            warn!(
-                anchor_tx_id = %payload.transaction_id,
-                "Anchor webhook received for unknown transaction — ignoring"
-            );
-        }
+    let disposition = services
+        .anchor
+        .handle_webhook(&event, services.job_queue.as_ref())
+        .await?;
+
+    if let Some(ref msg) = event.message {
+        info!(
+            anchor_tx_id = %event.transaction_id,
+            anchor_message = %msg,
+            "Anchor webhook message logged"
+        );
+    }
+    if disposition == WebhookDisposition::Duplicate {
+        info!(
+            anchor_tx_id = %event.transaction_id,
+            status = %event.status,
+            "Anchor webhook already processed — acknowledging"
+        );
     }
 
     Ok((StatusCode::OK, Json(WebhookAck { received: true })))
 }
-
-/// Look up a withdrawal by its `anchor_tx_id` column.
-/// Returns the withdrawal's internal UUID as a string, or `None` if not found.
-async fn find_withdrawal_by_anchor_tx_id(
-    services: &Arc<ServiceContainer>,
-    anchor_tx_id: &str,
-) -> Option<String> {
-    let client = services.db_pool.get().await.ok()?;
-
-    let row = client
-        .query_opt(
-            "SELECT id FROM withdrawals WHERE anchor_tx_id = $1",
-            &[&anchor_tx_id],
-        )
-        .await
-        .ok()??;
-
-    Some(row.get::<_, String>("id"))
-}
@@ -1,12 +1,19 @@
 use axum::{
+    body::Body,
     extract::{Path, Query, State},
+    http::header,
+    response::IntoResponse,
     Json,
 };
+use futures::{stream, Stream, StreamExt};
 use std::sync::Arc;
 
 use crate::{
     api_error::ApiError,
-    models::{AuditLogListResponse, AuditLogQueryParams, AuditLogResponse},
+    models::{
+        AuditChainVerification, AuditExportFormat, AuditLogEntry, AuditLogExportParams,
+        AuditLogQueryParams, AuditLogResponse, Paginated,
+    },
     service::ServiceContainer,
 };
 
@@ -15,7 +22,7 @@ use crate::{
 pub async fn list_audit_logs(
     State(services): State<Arc<ServiceContainer>>,
     Query(params): Query<AuditLogQueryParams>,
-) -> Result<Json<AuditLogListResponse>, ApiError> {
+) -> Result<Json<Paginated<AuditLogResponse>>, ApiError> {
     let total = services.audit.count_audit_logs(&params).await?;
     let logs = services.audit.list_audit_logs(&params).await?;
 
@@ -33,12 +40,12 @@ pub async fn list_audit_logs(
         })
         .collect();
 
-    Ok(Json(AuditLogListResponse {
-        logs: log_responses,
+    Ok(Json(Paginated::new(
+        log_responses,
         total,
-        limit: params.limit.clamp(1, 100),
-        offset: params.offset.max(0),
-    }))
+        params.limit.clamp(1, 100),
+        params.offset.max(0),
+    )))
 }
 
 /// GET /audit-logs/:id - Get a single audit log by ID
@@ -58,3 +65,271 @@ pub async fn get_audit_log(
         timestamp: log.timestamp,
     }))
 }
+
+/// GET /audit-logs/verify - Verify the audit log hash chain
+///
+/// Walks every chained entry and reports the first one whose hash or link to
+/// its predecessor does not match.
+pub async fn verify_audit_chain(
+    State(services): State<Arc<ServiceContainer>>,
+) -> Result<Json<AuditChainVerification>, ApiError> {
+    let result = services.audit.verify_chain().await?;
+
+    if !result.valid {
+        tracing::error!(
+            broken_at = ?result.broken_at,
+            reason = ?result.reason,
+            "Audit log hash chain verification failed"
+        );
+    }
+
+    Ok(Json(result))
+}
+
+/// GET /audit-logs/export - Export every matching audit log as CSV or JSON
+///
+/// Unlike the list endpoint this is not paginated: rows are streamed from the
+/// database straight into the response body.
+pub async fn export_audit_logs(
+    State(services): State<Arc<ServiceContainer>>,
+    Query(params): Query<AuditLogExportParams>,
+) -> Result<impl IntoResponse, ApiError> {
+    let entries = services.audit.export_audit_logs(&params.filter()).await?;
+
+    let (content_type, extension, body) = match params.format {
+        AuditExportFormat::Csv => (
+            "text/csv; charset=utf-8",
+            "csv",
+            Body::from_stream(csv_body(entries)),
+        ),
+        AuditExportFormat::Json => (
+            "application/json",
+            "json",
+            Body::from_stream(json_body(entries)),
+        ),
+    };
+
+    let disposition = format!(
+        "attachment; filename=\"{}\"",
+        export_filename(&params, extension)
+    );
+
+    Ok((
+        [
+            (header::CONTENT_TYPE, content_type.to_string()),
+            (header::CONTENT_DISPOSITION, disposition),
+        ],
+        body,
+    ))
+}
+
+type ExportChunk = Result<String, ApiError>;
+
+/// Header line followed by one CSV line per entry.
+fn csv_body<S>(entries: S) -> impl Stream<Item = ExportChunk>
+where
+    S: Stream<Item = Result<AuditLogEntry, ApiError>>,
+{
+    let rows = entries.map(|entry| entry.map(|e| csv_row(&e)));
+    stream::once(async { Ok(CSV_HEADER.to_string()) }).chain(rows)
+}
+
+/// A single JSON array, emitted one element at a time.
+fn json_body<S>(entries: S) -> impl Stream<Item = ExportChunk>
+where
+    S: Stream<Item = Result<AuditLogEntry, ApiError>>,
+{
+    let items = entries.enumerate().map(|(i, entry)| {
+        let separator = if i == 0 { "" } else { "," };
+        entry.and_then(|e| Ok(format!("{}{}", separator, serde_json::to_string(&e)?)))
+    });
+    stream::once(async { Ok("[".to_string()) })
+        .chain(items)
+        .chain(stream::once(async { Ok("]".to_string()) }))
+}
+
+const CSV_HEADER: &str =
+    "id,actor_id,action,resource,resource_id,timestamp,ip_address,user_agent,metadata\n";
+
+/// Render one audit log as a CSV line. Every field is quoted and `metadata`
+/// is flattened into `key=value` pairs so the file stays one row per entry.
+fn csv_row(entry: &AuditLogEntry) -> String {
+    let metadata = entry
+        .metadata
+        .as_ref()
+        .map(flatten_metadata)
+        .unwrap_or_default();
+
+    let fields = [
+        entry.id.as_str(),
+        entry.actor_id.as_str(),
+        entry.action.as_str(),
+        entry.resource.as_str(),
+        entry.resource_id.as_deref().unwrap_or(""),
+        &entry.timestamp.to_rfc3339(),
+        entry.ip_address.as_deref().unwrap_or(""),
+        entry.user_agent.as_deref().unwrap_or(""),
+        &metadata,
+    ];
+
+    let mut line = fields
+        .iter()
+        .map(|f| csv_quote(f))
+        .collect::<Vec<_>>()
+        .join(",");
+    line.push('\n');
+    line
+}
+
+pub(crate) fn csv_quote(field: &str) -> String {
+    format!("\"{}\"", field.replace('"', "\"\""))
+}
+
+/// Flatten nested JSON into `a.b=value` pairs joined by `; `.
+fn flatten_metadata(value: &serde_json::Value) -> String {
+    fn walk(prefix: &str, value: &serde_json::Value, out: &mut Vec<String>) {
+        match value {
+            serde_json::Value::Object(map) => {
+                for (key, child) in map {
+                    let path = if prefix.is_empty() {
+                        key.clone()
+                    } else {
+                        format!("{}.{}", prefix, key)
+                    };
+                    walk(&path, child, out);
+                }
+            }
+            serde_json::Value::String(s) => out.push(format!("{}={}", prefix, s)),
+            other => out.push(format!("{}={}", prefix, other)),
+        }
+    }
+
+    let mut pairs = Vec::new();
+    walk("", value, &mut pairs);
+    pairs.join("; ")
+}
+
+fn export_filename(params: &AuditLogExportParams, extension: &str) -> String {
+    let from = params
+        .from
+        .map(|d| d.format("%Y%m%d").to_string())
+        .unwrap_or_else(|| "start".to_string());
+    let to = params
+        .to
+        .map(|d| d.format("%Y%m%d").to_string())
+        .unwrap_or_else(|| "now".to_string());
+
+    format!("audit-logs_{}_{}.{}", from, to, extension)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+    use serde_json::json;
+
+    fn entry(metadata: Option<serde_json::Value>) -> AuditLogEntry {
+        AuditLogEntry {
+            id: "log-1".to_string(),
+            actor_id: "admin".to_string(),
+            action: "POST".to_string(),
+            resource: "/payments".to_string(),
+            resource_id: None,
+            metadata,
+            timestamp: chrono::Utc.with_ymd_and_hms(2026, 3, 1, 12, 0, 0).unwrap(),
+            ip_address: Some("127.0.0.1".to_string()),
+            user_agent: Some("curl \"8.0\", test".to_string()),
+            prev_hash: None,
+            entry_hash: None,
+        }
+    }
+
+    #[test]
+    fn csv_row_quotes_every_field() {
+        let row = csv_row(&entry(None));
+
+        assert_eq!(
+            row,
+            "\"log-1\",\"admin\",\"POST\",\"/payments\",\"\",\"2026-03-01T12:00:00+00:00\",\"127.0.0.1\",\"curl \"\"8.0\"\", test\",\"\"\n"
+        );
+        assert_eq!(row.matches('\n').count(), 1);
+    }
+
+    #[test]
+    fn csv_row_flattens_metadata() {
+        let row = csv_row(&entry(Some(json!({
+            "status": 201,
+            "request": { "method": "POST", "path": "/payments" }
+        }))));
+
+        assert!(row.ends_with("\"request.method=POST; request.path=/payments; status=201\"\n"));
+    }
+
+    #[test]
+    fn csv_header_matches_row_width() {
+        let header_columns = CSV_HEADER.trim_end().split(',').count();
+        let row = csv_row(&entry(None));
+        let row_columns = row.trim_end().split("\",\"").count();
+
+        assert_eq!(header_columns, row_columns);
+    }
+
+    async fn collect_body<S: Stream<Item = ExportChunk>>(body: S) -> String {
+        body.map(|chunk| chunk.unwrap())
+            .collect::<Vec<_>>()
+            .await
+            .concat()
+    }
+
+    #[tokio::test]
+    async fn csv_export_has_header_and_one_line_per_entry() {
+        let entries = stream::iter(vec![Ok(entry(None)), Ok(entry(None))]);
+        let body = collect_body(csv_body(entries)).await;
+        let lines: Vec<&str> = body.lines().collect();
+
+        assert_eq!(lines.len(), 3);
+        assert_eq!(lines[0], CSV_HEADER.trim_end());
+        assert!(lines[1].starts_with("\"log-1\""));
+    }
+
+    #[tokio::test]
+    async fn json_export_is_a_valid_array() {
+        let entries = stream::iter(vec![
+            Ok(entry(Some(json!({ "status": 201 })))),
+            Ok(entry(None)),
+        ]);
+        let body = collect_body(json_body(entries)).await;
+        let parsed: Vec<serde_json::Value> = serde_json::from_str(&body).unwrap();
+
+        assert_eq!(parsed.len(), 2);
+        assert_eq!(parsed[0]["metadata"]["status"], 201);
+        assert_eq!(parsed[1]["actor_id"], "admin");
+    }
+
+    #[tokio::test]
+    async fn json_export_of_no_entries_is_empty_array() {
+        let entries = stream::iter(Vec::<Result<AuditLogEntry, ApiError>>::new());
+
+        assert_eq!(collect_body(json_body(entries)).await, "[]");
+    }
+
+    #[test]
+    fn export_filename_reflects_date_range() {
+        let params = AuditLogExportParams {
+            format: AuditExportFormat::Csv,
+            from: Some(chrono::Utc.with_ymd_and_hms(2026, 1, 1, 0, 0, 0).unwrap()),
+            to: Some(
+                chrono::Utc
+                    .with_ymd_and_hms(2026, 1, 31, 23, 59, 59)
+                    .unwrap(),
+            ),
+            actor_id: None,
+            action: None,
+        };
+
+        assert_eq!(
+            export_filename(&params, "csv"),
+            "audit-logs_20260101_20260131.csv"
+        );
+    }
+}
@@ -1,16 +1,28 @@
-use axum::{extract::State, Json};
+use axum::{
+    extract::{Extension, Path, Query, State},
+    http::{header, HeaderMap, StatusCode},
+    Json,
+};
 use serde::{Deserialize, Serialize};
 use std::sync::Arc;
+use utoipa::ToSchema;
+use uuid::Uuid;
 
-use crate::{api_error::ApiError, auth, service::ServiceContainer};
+use crate::{
+    api_error::ApiError,
+    auth,
+    middleware::auth::AuthenticatedUser,
+    role::Role,
+    service::{session_service::SessionToken, ServiceContainer},
+};
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, ToSchema)]
 pub struct LoginRequest {
     pub user_id: String,
     pub pin: String,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, ToSchema)]
 pub struct RegisterRequest {
     pub user_id: String,
     pub pin: String,
@@ -18,7 +30,7 @@ pub struct RegisterRequest {
     pub role: Option<String>, // Optional role for registration (admin-only in production)
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 pub struct AuthResponse {
     pub token: String,
     pub refresh_token: String,
@@ -28,53 +40,116 @@ pub struct AuthResponse {
     pub refresh_expires_in: i64,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, ToSchema)]
 pub struct RefreshTokenRequest {
     pub token: String,
 }
 
-pub async fn login(
-    State(services): State<Arc<ServiceContainer>>,
-    Json(request): Json<LoginRequest>,
-) -> Result<Json<AuthResponse>, ApiError> {
-    // Get user with pin hash
-    let (user, pin_hash) = services
-        .identity
-        .get_user_with_pin_hash(&request.user_id)
-        .await?;
+#[derive(Debug, Serialize, ToSchema)]
+pub struct WhoAmIResponse {
+    pub user_id: String,
+    pub role: String,
+    pub stellar_address: String,
+}
 
-    // Verify PIN
-    if !auth::verify_pin(&request.pin, &pin_hash)? {
-        return Err(ApiError::Authentication("Invalid credentials".to_string()));
-    }
+#[derive(Debug, Deserialize)]
+pub struct SessionQuery {
+    /// Whose sessions to list; admins only. Defaults to the caller.
+    pub user_id: Option<String>,
+}
 
-    // Generate token pair
-    let token = auth::generate_access_token(
-        &user.user_id,
-        user.role,
-        &services.config.jwt.secret,
-        services.config.jwt.expiration_hours,
-    )?;
+#[derive(Debug, Serialize)]
+pub struct SessionResponse {
+    pub id: String,
+    pub user_id: String,
+    pub device: Option<String>,
+    pub issued_at: chrono::DateTime<chrono::Utc>,
+    pub last_used_at: chrono::DateTime<chrono::Utc>,
+    pub expires_at: chrono::DateTime<chrono::Utc>,
+}
 
-    let refresh_token = auth::generate_refresh_token(
-        &user.user_id,
-        user.role,
-        &services.config.jwt.secret,
-        services.config.jwt.refresh_expiration_hours,
+/// Device label recorded for a new session.
+fn device(headers: &HeaderMap) -> Option<&str> {
+    headers
+        .get(header::USER_AGENT)
+        .and_then(|value| value.to_str().ok())
+        .filter(|agent| !agent.is_empty())
+}
+
+/// Build the token pair for a session's current refresh token.
+async fn issue_tokens(
+    services: &ServiceContainer,
+    user_id: String,
+    role: Role,
+    session: &SessionToken,
+) -> Result<Json<AuthResponse>, ApiError> {
+    let jwt = &services.config.jwt;
+    let token =
+        auth::generate_access_token(&user_id, role, &jwt.secret, jwt.into(), jwt.access_ttl())?;
+
+    let refresh_token = auth::generate_session_refresh_token(
+        &user_id,
+        role,
+        &jwt.secret,
+        jwt.into(),
+        jwt.refresh_ttl(),
+        &session.session_id,
+        &session.token_id,
     )?;
 
     Ok(Json(AuthResponse {
         token,
         refresh_token,
-        user_id: user.user_id,
-        role: user.role.to_string(),
-        expires_in: services.config.jwt.expiration_hours * 3600,
-        refresh_expires_in: services.config.jwt.refresh_expiration_hours * 3600,
+        user_id,
+        role: role.to_string(),
+        expires_in: jwt.access_ttl().num_seconds(),
+        refresh_expires_in: jwt.refresh_ttl().num_seconds(),
     }))
 }
 
+#[utoipa::path(
+    post,
+    path = "/auth/login",
+    tag = "auth",
+    request_body = LoginRequest,
+    responses(
+        (status = 200, description = "Token pair for a new session", body = AuthResponse),
+        (status = 401, description = "Unknown user or wrong PIN", body = crate::api_error::ErrorResponse),
+    )
+)]
+pub async fn login(
+    State(services): State<Arc<ServiceContainer>>,
+    headers: HeaderMap,
+    Json(request): Json<LoginRequest>,
+) -> Result<Json<AuthResponse>, ApiError> {
+    // Verify PIN, upgrading its hash if it predates the current cost
+    let user = services
+        .identity
+        .verify_credentials(&request.user_id, &request.pin)
+        .await?;
+
+    // Start a session and hand out its first token pair
+    let session = services
+        .sessions
+        .start_session(&user.user_id, device(&headers))
+        .await?;
+    issue_tokens(&services, user.user_id, user.role, &session).await
+}
+
+#[utoipa::path(
+    post,
+    path = "/auth/register",
+    tag = "auth",
+    request_body = RegisterRequest,
+    responses(
+        (status = 200, description = "Token pair for the new user's first session", body = AuthResponse),
+        (status = 400, description = "PIN does not meet the PIN policy", body = crate::api_error::ErrorResponse),
+        (status = 409, description = "User already exists", body = crate::api_error::ErrorResponse),
+    )
+)]
 pub async fn register(
     State(services): State<Arc<ServiceContainer>>,
+    headers: HeaderMap,
     Json(request): Json<RegisterRequest>,
 ) -> Result<Json<AuthResponse>, ApiError> {
     // Check if user already exists
@@ -82,8 +157,9 @@ pub async fn register(
         return Err(ApiError::Conflict("User already exists".to_string()));
     }
 
-    // Validate and hash the PIN (enforces 4–6 digit format, bcrypt 10 rounds)
-    let pin_hash = auth::hash_pin(&request.pin)?;
+    // Check the PIN against pin_policy, then hash it at pin_hash.cost
+    auth::check_pin_policy(&request.pin, &services.config.pin_policy)?;
+    let pin_hash = auth::hash_pin(&request.pin, services.config.pin_hash.cost)?;
 
     // Create user with default role (User)
     // In production, role assignment should be restricted
@@ -92,100 +168,149 @@ pub async fn register(
         .create_user(request.user_id.clone(), pin_hash)
         .await?;
 
-    // Generate token pair
-    let token = auth::generate_access_token(
-        &user.user_id,
-        user.role,
-        &services.config.jwt.secret,
-        services.config.jwt.expiration_hours,
-    )?;
-
-    let refresh_token = auth::generate_refresh_token(
-        &user.user_id,
-        user.role,
-        &services.config.jwt.secret,
-        services.config.jwt.refresh_expiration_hours,
-    )?;
-
-    Ok(Json(AuthResponse {
-        token,
-        refresh_token,
-        user_id: user.user_id,
-        role: user.role.to_string(),
-        expires_in: services.config.jwt.expiration_hours * 3600,
-        refresh_expires_in: services.config.jwt.refresh_expiration_hours * 3600,
-    }))
+    // Start a session and hand out its first token pair
+    let session = services
+        .sessions
+        .start_session(&user.user_id, device(&headers))
+        .await?;
+    issue_tokens(&services, user.user_id, user.role, &session).await
 }
 
+#[utoipa::path(
+    post,
+    path = "/auth/refresh",
+    tag = "auth",
+    request_body = RefreshTokenRequest,
+    responses(
+        (status = 200, description = "Rotated token pair", body = AuthResponse),
+        (status = 401, description = "Refresh token is invalid, expired or revoked", body = crate::api_error::ErrorResponse),
+    )
+)]
 pub async fn refresh_token(
     State(services): State<Arc<ServiceContainer>>,
     Json(request): Json<RefreshTokenRequest>,
 ) -> Result<Json<AuthResponse>, ApiError> {
-    let claims = auth::validate_refresh_token(&request.token, &services.config.jwt.secret)?;
+    let jwt = &services.config.jwt;
+    let claims = auth::validate_refresh_token(&request.token, &jwt.secret, jwt.into())?;
 
     if !services.identity.user_exists(&claims.sub).await? {
         return Err(ApiError::Authentication("User not found".to_string()));
     }
 
-    let token = auth::generate_access_token(
-        &claims.sub,
-        claims.role,
-        &services.config.jwt.secret,
-        services.config.jwt.expiration_hours,
-    )?;
+    // Refresh tokens issued before sessions existed can't be revoked, so
+    // they are no longer honoured.
+    let (Some(session_id), Some(token_id)) = (claims.sid.as_deref(), claims.jti.as_deref()) else {
+        return Err(ApiError::Authentication(
+            "Refresh token is no longer supported, please log in again".to_string(),
+        ));
+    };
 
-    let refresh_token = auth::generate_refresh_token(
-        &claims.sub,
-        claims.role,
-        &services.config.jwt.secret,
-        services.config.jwt.refresh_expiration_hours,
-    )?;
-
-    Ok(Json(AuthResponse {
-        token,
-        refresh_token,
-        user_id: claims.sub,
-        role: claims.role.to_string(),
-        expires_in: services.config.jwt.expiration_hours * 3600,
-        refresh_expires_in: services.config.jwt.refresh_expiration_hours * 3600,
-    }))
+    let session = services
+        .sessions
+        .rotate(&claims.sub, session_id, token_id)
+        .await?;
+    issue_tokens(&services, claims.sub, claims.role, &session).await
 }
 
 pub async fn user_register(
     State(services): State<Arc<ServiceContainer>>,
+    headers: HeaderMap,
     Json(request): Json<RegisterRequest>,
 ) -> Result<Json<AuthResponse>, ApiError> {
     if services.identity.user_exists(&request.user_id).await? {
         return Err(ApiError::Conflict("User already exists".to_string()));
     }
 
-    let pin_hash = auth::hash_pin(&request.pin)?;
+    auth::check_pin_policy(&request.pin, &services.config.pin_policy)?;
+    let pin_hash = auth::hash_pin(&request.pin, services.config.pin_hash.cost)?;
 
     let user = services
         .identity
         .create_user(request.user_id.clone(), pin_hash)
         .await?;
 
-    let token = auth::generate_access_token(
-        &user.user_id,
-        user.role,
-        &services.config.jwt.secret,
-        services.config.jwt.expiration_hours,
-    )?;
+    let session = services
+        .sessions
+        .start_session(&user.user_id, device(&headers))
+        .await?;
+    issue_tokens(&services, user.user_id, user.role, &session).await
+}
 
-    let refresh_token = auth::generate_refresh_token(
-        &user.user_id,
-        user.role,
-        &services.config.jwt.secret,
-        services.config.jwt.refresh_expiration_hours,
-    )?;
+#[utoipa::path(
+    get,
+    path = "/auth/whoami",
+    tag = "auth",
+    responses(
+        (status = 200, description = "The caller's identity", body = WhoAmIResponse),
+        (status = 401, description = "Token is missing, invalid or expired, or its user no longer exists", body = crate::api_error::ErrorResponse),
+    ),
+    security(("bearer_auth" = []))
+)]
+pub async fn whoami(
+    State(services): State<Arc<ServiceContainer>>,
+    Extension(user): Extension<AuthenticatedUser>,
+) -> Result<Json<WhoAmIResponse>, ApiError> {
+    // The role comes from the token, since that is what this request is
+    // authorised with; the address is looked up.
+    let record = services
+        .identity
+        .get_user_by_id(&user.user_id)
+        .await
+        .map_err(|e| match e {
+            ApiError::NotFound(_) => ApiError::Authentication("User not found".to_string()),
+            other => other,
+        })?;
 
-    Ok(Json(AuthResponse {
-        token,
-        refresh_token,
+    Ok(Json(WhoAmIResponse {
         user_id: user.user_id,
         role: user.role.to_string(),
-        expires_in: services.config.jwt.expiration_hours * 3600,
-        refresh_expires_in: services.config.jwt.refresh_expiration_hours * 3600,
+        stellar_address: record.stellar_address,
     }))
 }
+
+/// List active sessions. Admins may pass `user_id` to see another user's.
+pub async fn list_sessions(
+    State(services): State<Arc<ServiceContainer>>,
+    Extension(user): Extension<AuthenticatedUser>,
+    Query(query): Query<SessionQuery>,
+) -> Result<Json<Vec<SessionResponse>>, ApiError> {
+    let target = query.user_id.unwrap_or_else(|| user.user_id.clone());
+    if target != user.user_id && user.role != Role::Admin {
+        return Err(ApiError::Authorization(
+            "You can only list your own sessions".to_string(),
+        ));
+    }
+
+    let sessions = services.sessions.list_sessions(&target).await?;
+
+    Ok(Json(
+        sessions
+            .into_iter()
+            .map(|s| SessionResponse {
+                id: s.id,
+                user_id: s.user_id,
+                device: s.device,
+                issued_at: s.issued_at,
+                last_used_at: s.last_used_at,
+                expires_at: s.expires_at,
+            })
+            .collect(),
+    ))
+}
+
+/// Revoke a session, invalidating its refresh token. Admins may revoke any
+/// user's session.
+pub async fn revoke_session(
+    State(services): State<Arc<ServiceContainer>>,
+    Extension(user): Extension<AuthenticatedUser>,
+    Path(session_id): Path<Uuid>,
+) -> Result<StatusCode, ApiError> {
+    let owner = (user.role != Role::Admin).then_some(user.user_id.as_str());
+
+    services
+        .sessions
+        .revoke_session(&session_id.to_string(), owner)
+        .await?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
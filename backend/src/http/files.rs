@@ -2,12 +2,21 @@ use std::sync::Arc;
 
 use axum::{
     body::{Body, Bytes},
-    extract::{Multipart, Path, State},
-    http::{header, HeaderMap, StatusCode},
+    extract::{Multipart, Path, Query, State},
+    http::{header, HeaderMap, HeaderValue, StatusCode},
+    response::{IntoResponse, Response},
     Json,
 };
+use ring::{digest, hmac};
 
-use crate::{api_error::ApiError, models::FileUploadResponseDto, service::ServiceContainer};
+use crate::{
+    api_error::ApiError,
+    middleware::auth::AuthenticatedUser,
+    models::{FileAccessParams, FileUploadResponseDto},
+    role::Role,
+    service::ServiceContainer,
+    storage::StoredFile,
+};
 
 const MAX_FILE_SIZE: u64 = 10 * 1024 * 1024; // 10MB
 const ALLOWED_MIME_TYPES: &[&str] = &["image/png", "image/jpeg", "application/pdf"];
@@ -18,8 +27,31 @@ async fn virus_scan_placeholder(_data: &Bytes) -> Result<(), ApiError> {
     Ok(())
 }
 
+/// Reject a file whose magic number isn't an allowed type, or isn't the
+/// type it was declared as. The multipart `Content-Type` is client-supplied,
+/// so it can't be trusted on its own.
+fn check_content_matches(data: &[u8], declared: &str) -> Result<(), ApiError> {
+    if data.is_empty() {
+        return Err(ApiError::Validation("File is empty".to_string()));
+    }
+
+    let detected = infer::get(data)
+        .map(|kind| kind.mime_type())
+        .filter(|mime| ALLOWED_MIME_TYPES.contains(mime))
+        .ok_or_else(|| ApiError::Validation("Unsupported file type".to_string()))?;
+
+    if detected != declared {
+        return Err(ApiError::Validation(format!(
+            "File content is {} but was declared as {}",
+            detected, declared
+        )));
+    }
+    Ok(())
+}
+
 pub async fn upload_file(
     State(services): State<Arc<ServiceContainer>>,
+    auth_user: AuthenticatedUser,
     mut multipart: Multipart,
 ) -> Result<Json<FileUploadResponseDto>, ApiError> {
     let mut file_name = None;
@@ -59,73 +91,256 @@ pub async fn upload_file(
     if !ALLOWED_MIME_TYPES.contains(&mime_type.as_str()) {
         return Err(ApiError::Validation("Unsupported file type".to_string()));
     }
+    check_content_matches(&data, &mime_type)?;
 
     virus_scan_placeholder(&data).await?;
 
     let stored = services
         .storage
         .adapter
-        .upload(data, &file_name, &mime_type)
+        .upload(data, &file_name, &mime_type, Some(&auth_user.user_id))
         .map_err(|_| ApiError::InternalServerError)?;
 
     Ok(Json(FileUploadResponseDto {
+        url: signed_url_for(&services, &stored.id),
         file_id: stored.id,
         original_name: stored.original_name,
         mime_type: stored.mime_type,
         size: stored.size,
-        url: stored.url,
     }))
 }
 
-pub async fn get_file(
-    State(services): State<Arc<ServiceContainer>>,
-    Path(id): Path<String>,
-) -> Result<(HeaderMap, Body), ApiError> {
-    // Serve raw bytes (local storage only). For non-local backends this may fail until implemented.
+/// Look up file `id` for `user`, who must have uploaded it or be an admin.
+/// Other people's files are reported missing rather than forbidden.
+fn owned_file(
+    services: &ServiceContainer,
+    user: &AuthenticatedUser,
+    id: &str,
+) -> Result<StoredFile, ApiError> {
     let stored = services
         .storage
         .adapter
-        .get(&id)
-        .map_err(|_| ApiError::InternalServerError)?;
+        .get(id)
+        .map_err(|_| ApiError::InternalServerError)?
+        .ok_or_else(|| ApiError::NotFound("File not found".to_string()))?;
 
-    let stored = stored.ok_or_else(|| ApiError::NotFound("File not found".to_string()))?;
+    if user.role != Role::Admin && stored.owner_id.as_deref() != Some(user.user_id.as_str()) {
+        return Err(ApiError::NotFound("File not found".to_string()));
+    }
+    Ok(stored)
+}
 
-    // LocalStorageAdapter is currently storing files as-is under `storage.local_path` with filename == id.
-    // We read the file directly based on config.
-    let base_path = services
-        .config
-        .storage
-        .local_path
-        .clone()
-        .unwrap_or_else(|| "./uploads".to_string());
+fn url_message(id: &str, expires: i64) -> String {
+    format!("{}:{}", id, expires)
+}
 
-    let file_path = std::path::Path::new(&base_path).join(&stored.id);
-    let bytes = tokio::fs::read(file_path)
-        .await
-        .map_err(|_| ApiError::NotFound("File not found".to_string()))?;
+/// Sign `GET /files/:id` for file `id`, valid until the Unix timestamp
+/// `expires`. Returns the path with its `expires` and `token` query
+/// parameters.
+pub fn sign_url(secret: &str, id: &str, expires: i64) -> String {
+    let key = hmac::Key::new(hmac::HMAC_SHA256, secret.as_bytes());
+    let tag = hmac::sign(&key, url_message(id, expires).as_bytes());
+    format!(
+        "/files/{}?expires={}&token={}",
+        id,
+        expires,
+        hex::encode(tag.as_ref())
+    )
+}
+
+/// A signed URL for file `id` that expires after the configured TTL.
+fn signed_url_for(services: &ServiceContainer, id: &str) -> String {
+    let storage = &services.config.storage;
+    let expires = chrono::Utc::now().timestamp() + storage.signed_url_ttl_seconds as i64;
+    sign_url(&storage.url_signing_secret, id, expires)
+}
+
+/// Check a signed URL for file `id` at Unix time `now`, returning the seconds
+/// it has left. Missing, tampered and expired signatures are all `403`.
+fn verify_signed_url(
+    secret: &str,
+    id: &str,
+    params: &FileAccessParams,
+    now: i64,
+) -> Result<u64, ApiError> {
+    let denied = || ApiError::Authorization("Invalid or expired file URL".to_string());
+    let (Some(expires), Some(token)) = (params.expires, params.token.as_deref()) else {
+        return Err(denied());
+    };
+    let tag = hex::decode(token).map_err(|_| denied())?;
+
+    let key = hmac::Key::new(hmac::HMAC_SHA256, secret.as_bytes());
+    hmac::verify(&key, url_message(id, expires).as_bytes(), &tag).map_err(|_| denied())?;
+
+    if expires <= now {
+        return Err(denied());
+    }
+    Ok((expires - now) as u64)
+}
+
+/// Strong `ETag` for a file's contents: the quoted hex SHA-256 of its bytes.
+fn etag_for(bytes: &[u8]) -> String {
+    format!(
+        "\"{}\"",
+        hex::encode(digest::digest(&digest::SHA256, bytes))
+    )
+}
+
+/// Whether an `If-None-Match` header matches `etag`, using the weak
+/// comparison RFC 9110 prescribes for this header.
+fn if_none_match_matches(headers: &HeaderMap, etag: &str) -> bool {
+    let strip_weak = |tag: &str| tag.trim().trim_start_matches("W/").to_string();
+    let etag = strip_weak(etag);
+
+    headers
+        .get_all(header::IF_NONE_MATCH)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(','))
+        .any(|tag| tag.trim() == "*" || strip_weak(tag) == etag)
+}
+
+/// What a `Range` header asks for out of a body of a given length.
+#[derive(Debug, PartialEq, Eq)]
+enum ByteRange {
+    /// No usable range; serve the whole body.
+    Full,
+    /// Inclusive byte offsets.
+    Partial(u64, u64),
+    /// A range that lies entirely outside the body.
+    Unsatisfiable,
+}
+
+/// Parse a single `bytes=` range against a body of `len` bytes.
+///
+/// Headers we can't parse, ranges that end before they start (invalid under
+/// RFC 9110) and multi-range requests are ignored, so the client just gets
+/// the full body.
+fn parse_range(headers: &HeaderMap, len: u64) -> ByteRange {
+    let Some(spec) = headers
+        .get(header::RANGE)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.trim().strip_prefix("bytes="))
+    else {
+        return ByteRange::Full;
+    };
+    if spec.contains(',') {
+        return ByteRange::Full;
+    }
+    let Some((start, end)) = spec.trim().split_once('-') else {
+        return ByteRange::Full;
+    };
+    let parse = |n: &str| n.trim().parse::<u64>().ok();
+
+    match (start.trim(), end.trim()) {
+        // Suffix range: the last `n` bytes
+        ("", suffix) => match parse(suffix) {
+            Some(0) => ByteRange::Unsatisfiable,
+            Some(_) if len == 0 => ByteRange::Unsatisfiable,
+            Some(n) => ByteRange::Partial(len.saturating_sub(n), len - 1),
+            None => ByteRange::Full,
+        },
+        (start, "") => match parse(start) {
+            Some(start) if start < len => ByteRange::Partial(start, len - 1),
+            Some(_) => ByteRange::Unsatisfiable,
+            None => ByteRange::Full,
+        },
+        (start, end) => match (parse(start), parse(end)) {
+            (Some(start), Some(end)) if start > end => ByteRange::Full,
+            (Some(start), Some(_)) if start >= len => ByteRange::Unsatisfiable,
+            (Some(start), Some(end)) => ByteRange::Partial(start, end.min(len - 1)),
+            _ => ByteRange::Full,
+        },
+    }
+}
+
+/// Whether a `Range` request should be honoured: only without `If-Range` or
+/// when it names the current `ETag`.
+fn if_range_matches(headers: &HeaderMap, etag: &str) -> bool {
+    match headers
+        .get(header::IF_RANGE)
+        .and_then(|value| value.to_str().ok())
+    {
+        Some(tag) => tag.trim() == etag,
+        None => true,
+    }
+}
+
+/// Build the response for a file body, answering `304 Not Modified` when the
+/// client already holds the current version and `206 Partial Content` for a
+/// byte range.
+fn file_response(
+    request_headers: &HeaderMap,
+    bytes: Vec<u8>,
+    mime_type: &str,
+    original_name: &str,
+    max_age_seconds: u64,
+) -> Response {
+    let etag = etag_for(&bytes);
 
     let mut headers = HeaderMap::new();
+    if let Ok(value) = HeaderValue::from_str(&etag) {
+        headers.insert(header::ETAG, value);
+    }
+    if let Ok(value) = HeaderValue::from_str(&format!("public, max-age={}", max_age_seconds)) {
+        headers.insert(header::CACHE_CONTROL, value);
+    }
+    headers.insert(header::ACCEPT_RANGES, HeaderValue::from_static("bytes"));
+
+    if if_none_match_matches(request_headers, &etag) {
+        return (StatusCode::NOT_MODIFIED, headers).into_response();
+    }
+
     headers.insert(
         header::CONTENT_TYPE,
-        stored
-            .mime_type
+        mime_type
             .parse()
-            .unwrap_or_else(|_| header::HeaderValue::from_static("application/octet-stream")),
+            .unwrap_or_else(|_| HeaderValue::from_static("application/octet-stream")),
     );
     headers.insert(
         header::CONTENT_DISPOSITION,
-        header::HeaderValue::from_str(&format!("inline; filename=\"{}\"", stored.original_name))
-            .unwrap_or_else(|_| header::HeaderValue::from_static("inline")),
+        HeaderValue::from_str(&format!("inline; filename=\"{}\"", original_name))
+            .unwrap_or_else(|_| HeaderValue::from_static("inline")),
     );
 
-    Ok((headers, Body::from(bytes)))
+    let len = bytes.len() as u64;
+    let range = if if_range_matches(request_headers, &etag) {
+        parse_range(request_headers, len)
+    } else {
+        ByteRange::Full
+    };
+
+    match range {
+        ByteRange::Full => (headers, Body::from(bytes)).into_response(),
+        ByteRange::Partial(start, end) => {
+            if let Ok(value) = HeaderValue::from_str(&format!("bytes {}-{}/{}", start, end, len)) {
+                headers.insert(header::CONTENT_RANGE, value);
+            }
+            let body = bytes[start as usize..=end as usize].to_vec();
+            (StatusCode::PARTIAL_CONTENT, headers, Body::from(body)).into_response()
+        }
+        ByteRange::Unsatisfiable => {
+            if let Ok(value) = HeaderValue::from_str(&format!("bytes */{}", len)) {
+                headers.insert(header::CONTENT_RANGE, value);
+            }
+            (StatusCode::RANGE_NOT_SATISFIABLE, headers).into_response()
+        }
+    }
 }
 
-// New: JSON metadata endpoint (keeps previous behavior)
-pub async fn get_file_metadata(
+pub async fn get_file(
     State(services): State<Arc<ServiceContainer>>,
     Path(id): Path<String>,
-) -> Result<Json<FileUploadResponseDto>, ApiError> {
+    Query(params): Query<FileAccessParams>,
+    request_headers: HeaderMap,
+) -> Result<Response, ApiError> {
+    let remaining = verify_signed_url(
+        &services.config.storage.url_signing_secret,
+        &id,
+        &params,
+        chrono::Utc::now().timestamp(),
+    )?;
+
     let stored = services
         .storage
         .adapter
@@ -134,19 +349,50 @@ pub async fn get_file_metadata(
 
     let stored = stored.ok_or_else(|| ApiError::NotFound("File not found".to_string()))?;
 
+    // Decrypted by the adapter when encryption at rest is enabled.
+    let bytes = services
+        .storage
+        .adapter
+        .read(&stored.id)
+        .map_err(|e| {
+            tracing::error!(file_id = %stored.id, error = %e, "Failed to read stored file");
+            ApiError::InternalServerError
+        })?
+        .ok_or_else(|| ApiError::NotFound("File not found".to_string()))?;
+
+    Ok(file_response(
+        &request_headers,
+        bytes,
+        &stored.mime_type,
+        &stored.original_name,
+        // Caches mustn't keep serving the file once its URL has expired.
+        services.config.storage.cache_max_age_seconds.min(remaining),
+    ))
+}
+
+// New: JSON metadata endpoint (keeps previous behavior)
+pub async fn get_file_metadata(
+    State(services): State<Arc<ServiceContainer>>,
+    auth_user: AuthenticatedUser,
+    Path(id): Path<String>,
+) -> Result<Json<FileUploadResponseDto>, ApiError> {
+    let stored = owned_file(&services, &auth_user, &id)?;
+
     Ok(Json(FileUploadResponseDto {
+        url: signed_url_for(&services, &stored.id),
         file_id: stored.id,
         original_name: stored.original_name,
         mime_type: stored.mime_type,
         size: stored.size,
-        url: stored.url,
     }))
 }
 
 pub async fn delete_file(
     State(services): State<Arc<ServiceContainer>>,
+    auth_user: AuthenticatedUser,
     Path(id): Path<String>,
 ) -> Result<StatusCode, ApiError> {
+    owned_file(&services, &auth_user, &id)?;
     services
         .storage
         .adapter
@@ -155,3 +401,235 @@ pub async fn delete_file(
 
     Ok(StatusCode::NO_CONTENT)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use http_body_util::BodyExt;
+
+    fn request_with(if_none_match: Option<&str>) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        if let Some(tag) = if_none_match {
+            headers.insert(header::IF_NONE_MATCH, HeaderValue::from_str(tag).unwrap());
+        }
+        headers
+    }
+
+    fn serve(request: &HeaderMap) -> Response {
+        file_response(request, b"avatar".to_vec(), "image/png", "a.png", 3600)
+    }
+
+    #[tokio::test]
+    async fn matching_if_none_match_is_not_modified() {
+        let etag = etag_for(b"avatar");
+
+        for header in [
+            etag.clone(),
+            format!("W/{}", etag),
+            format!("\"other\", {}", etag),
+            "*".to_string(),
+        ] {
+            let response = serve(&request_with(Some(&header)));
+            assert_eq!(response.status(), StatusCode::NOT_MODIFIED, "{}", header);
+            assert_eq!(response.headers()[header::ETAG], etag.as_str());
+
+            let body = response.into_body().collect().await.unwrap().to_bytes();
+            assert!(body.is_empty());
+        }
+    }
+
+    #[tokio::test]
+    async fn stale_or_missing_if_none_match_gets_full_body() {
+        for header in [Some("\"stale\""), None] {
+            let response = serve(&request_with(header));
+            assert_eq!(response.status(), StatusCode::OK);
+
+            let headers = response.headers();
+            assert_eq!(headers[header::ETAG], etag_for(b"avatar").as_str());
+            assert_eq!(headers[header::CACHE_CONTROL], "public, max-age=3600");
+            assert_eq!(headers[header::CONTENT_TYPE], "image/png");
+
+            let body = response.into_body().collect().await.unwrap().to_bytes();
+            assert_eq!(&body[..], b"avatar");
+        }
+    }
+
+    fn ranged(range: &str) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        headers.insert(header::RANGE, HeaderValue::from_str(range).unwrap());
+        headers
+    }
+
+    async fn body_of(response: Response) -> Vec<u8> {
+        response
+            .into_body()
+            .collect()
+            .await
+            .unwrap()
+            .to_bytes()
+            .to_vec()
+    }
+
+    #[tokio::test]
+    async fn single_range_returns_partial_content() {
+        let response = serve(&ranged("bytes=1-3"));
+        assert_eq!(response.status(), StatusCode::PARTIAL_CONTENT);
+        assert_eq!(response.headers()[header::CONTENT_RANGE], "bytes 1-3/6");
+        assert_eq!(response.headers()[header::ACCEPT_RANGES], "bytes");
+        assert_eq!(body_of(response).await, b"vat");
+
+        // An end past the last byte is clamped.
+        let response = serve(&ranged("bytes=4-100"));
+        assert_eq!(response.headers()[header::CONTENT_RANGE], "bytes 4-5/6");
+        assert_eq!(body_of(response).await, b"ar");
+    }
+
+    #[tokio::test]
+    async fn open_ended_and_suffix_ranges() {
+        let response = serve(&ranged("bytes=2-"));
+        assert_eq!(response.status(), StatusCode::PARTIAL_CONTENT);
+        assert_eq!(response.headers()[header::CONTENT_RANGE], "bytes 2-5/6");
+        assert_eq!(body_of(response).await, b"atar");
+
+        let response = serve(&ranged("bytes=-2"));
+        assert_eq!(response.headers()[header::CONTENT_RANGE], "bytes 4-5/6");
+        assert_eq!(body_of(response).await, b"ar");
+    }
+
+    #[tokio::test]
+    async fn unsatisfiable_range_is_416() {
+        for range in ["bytes=6-", "bytes=10-20", "bytes=-0"] {
+            let response = serve(&ranged(range));
+            assert_eq!(
+                response.status(),
+                StatusCode::RANGE_NOT_SATISFIABLE,
+                "{}",
+                range
+            );
+            assert_eq!(response.headers()[header::CONTENT_RANGE], "bytes */6");
+        }
+    }
+
+    #[tokio::test]
+    async fn unusable_range_serves_full_body() {
+        for range in ["items=0-1", "bytes=0-1,3-4", "bytes=a-b", "bytes=4-2"] {
+            let response = serve(&ranged(range));
+            assert_eq!(response.status(), StatusCode::OK, "{}", range);
+            assert_eq!(body_of(response).await, b"avatar");
+        }
+
+        let mut stale = ranged("bytes=0-1");
+        stale.insert(header::IF_RANGE, HeaderValue::from_static("\"stale\""));
+        let response = serve(&stale);
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(body_of(response).await, b"avatar");
+    }
+
+    /// The 8-byte signature and IHDR chunk of a 1x1 PNG.
+    const PNG: &[u8] = &[
+        0x89, b'P', b'N', b'G', 0x0d, 0x0a, 0x1a, 0x0a, 0x00, 0x00, 0x00, 0x0d, b'I', b'H', b'D',
+        b'R', 0x00, 0x00, 0x00, 0x01, 0x00, 0x00, 0x00, 0x01, 0x08, 0x06, 0x00, 0x00, 0x00, 0x1f,
+        0x15, 0xc4, 0x89,
+    ];
+
+    fn assert_rejected(data: &[u8], declared: &str) {
+        let err = check_content_matches(data, declared).unwrap_err();
+        assert!(matches!(err, ApiError::Validation(_)), "{:?}", err);
+    }
+
+    #[test]
+    fn real_png_passes_sniffing() {
+        check_content_matches(PNG, "image/png").unwrap();
+        check_content_matches(b"%PDF-1.7\n%\xe2\xe3", "application/pdf").unwrap();
+    }
+
+    #[test]
+    fn renamed_binary_is_rejected() {
+        // An ELF executable and a Windows PE labelled as images
+        assert_rejected(b"\x7fELF\x02\x01\x01\x00\x00\x00\x00\x00", "image/png");
+        assert_rejected(b"MZ\x90\x00\x03\x00\x00\x00\x04\x00", "image/jpeg");
+        // Unrecognised bytes
+        assert_rejected(b"just some text", "image/png");
+        // An allowed type that isn't the declared one
+        assert_rejected(PNG, "application/pdf");
+    }
+
+    #[test]
+    fn empty_file_is_rejected() {
+        assert_rejected(b"", "image/png");
+    }
+
+    const SECRET: &str = "test-file-url-secret";
+
+    fn params_of(url: &str) -> FileAccessParams {
+        let uri: axum::http::Uri = url.parse().unwrap();
+        Query::<FileAccessParams>::try_from_uri(&uri).unwrap().0
+    }
+
+    fn assert_forbidden(result: Result<u64, ApiError>) {
+        let err = result.unwrap_err();
+        assert_eq!(err.into_response().status(), StatusCode::FORBIDDEN);
+    }
+
+    #[test]
+    fn signed_url_is_accepted_until_it_expires() {
+        let url = sign_url(SECRET, "file-1", 1_000);
+        assert!(url.starts_with("/files/file-1?expires=1000&token="));
+
+        let params = params_of(&url);
+        assert_eq!(
+            verify_signed_url(SECRET, "file-1", &params, 400).unwrap(),
+            600
+        );
+    }
+
+    #[test]
+    fn expired_signed_url_is_forbidden() {
+        let params = params_of(&sign_url(SECRET, "file-1", 1_000));
+
+        assert_forbidden(verify_signed_url(SECRET, "file-1", &params, 1_000));
+        assert_forbidden(verify_signed_url(SECRET, "file-1", &params, 5_000));
+    }
+
+    #[test]
+    fn tampered_signed_url_is_forbidden() {
+        let params = params_of(&sign_url(SECRET, "file-1", 1_000));
+
+        // Another file, a pushed-back expiry, or another secret
+        assert_forbidden(verify_signed_url(SECRET, "file-2", &params, 400));
+        let extended = FileAccessParams {
+            expires: Some(9_999),
+            token: params.token.clone(),
+        };
+        assert_forbidden(verify_signed_url(SECRET, "file-1", &extended, 400));
+        assert_forbidden(verify_signed_url(
+            "another-file-url-secret",
+            "file-1",
+            &params,
+            400,
+        ));
+
+        let mut token = params.token.clone().unwrap();
+        let flipped = if token.ends_with('0') { "1" } else { "0" };
+        token.replace_range(token.len() - 1.., flipped);
+        let tampered = FileAccessParams {
+            expires: params.expires,
+            token: Some(token),
+        };
+        assert_forbidden(verify_signed_url(SECRET, "file-1", &tampered, 400));
+
+        for (expires, token) in [(None, params.token.clone()), (Some(1_000), None)] {
+            let missing = FileAccessParams { expires, token };
+            assert_forbidden(verify_signed_url(SECRET, "file-1", &missing, 400));
+        }
+        let garbled = params_of("/files/file-1?expires=1000&token=not-hex");
+        assert_forbidden(verify_signed_url(SECRET, "file-1", &garbled, 400));
+    }
+
+    #[test]
+    fn etag_tracks_content() {
+        assert_eq!(etag_for(b"a"), etag_for(b"a"));
+        assert_ne!(etag_for(b"a"), etag_for(b"b"));
+        assert!(etag_for(b"a").starts_with('"') && etag_for(b"a").ends_with('"'));
+    }
+}
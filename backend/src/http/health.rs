@@ -1,11 +1,16 @@
 use axum::{extract::State, http::StatusCode, response::IntoResponse, Json};
 use serde::Serialize;
-use std::sync::Arc;
+use std::{fmt::Display, future::Future, sync::Arc, time::Duration};
+use utoipa::ToSchema;
 
-use crate::service::{MetricsService, ServiceContainer};
+use crate::{
+    config::DependencyCriticality,
+    db,
+    service::{circuit_breaker::BreakerState, MetricsService, ServiceContainer},
+};
 
 /// Basic health check response
-#[derive(Serialize)]
+#[derive(Serialize, ToSchema)]
 pub struct HealthResponse {
     pub status: String,
     pub timestamp: chrono::DateTime<chrono::Utc>,
@@ -18,6 +23,14 @@ pub struct HealthResponse {
 pub struct ReadinessResponse {
     pub status: String,
     pub database: DatabaseHealth,
+    pub schema: SchemaHealth,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub redis: Option<DependencyHealth>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub anchor: Option<DependencyHealth>,
+    /// Whether anchor calls are currently being fast-failed. Reported only;
+    /// an open breaker doesn't fail readiness.
+    pub anchor_circuit: BreakerState,
     pub uptime_seconds: u64,
     pub timestamp: chrono::DateTime<chrono::Utc>,
 }
@@ -31,8 +44,65 @@ pub struct DatabaseHealth {
     pub available_connections: u32,
 }
 
-/// Liveness probe response for Kubernetes
+/// Applied migration version compared with the one this build expects
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SchemaHealth {
+    /// `current`, `behind`, or `unknown` when the version couldn't be read.
+    pub status: String,
+    pub expected_version: i64,
+    pub applied_version: Option<i64>,
+}
+
+impl SchemaHealth {
+    /// Compare the database's applied version with `expected_version`. A
+    /// database ahead of this build (e.g. mid-rollout) still counts as
+    /// current.
+    pub fn new(expected_version: i64, applied_version: Option<i64>) -> Self {
+        let current = applied_version.is_some_and(|applied| applied >= expected_version);
+        Self {
+            status: if current { "current" } else { "behind" }.to_string(),
+            expected_version,
+            applied_version,
+        }
+    }
+
+    fn unknown(expected_version: i64) -> Self {
+        Self {
+            status: "unknown".to_string(),
+            expected_version,
+            applied_version: None,
+        }
+    }
+
+    pub fn is_current(&self) -> bool {
+        self.status == "current"
+    }
+}
+
+/// Health of an external dependency probed during readiness
+///
+/// `status` is `up`, `down` for a required dependency that failed, or
+/// `degraded` for an optional one.
 #[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DependencyHealth {
+    pub status: String,
+    pub required: bool,
+    pub latency_ms: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+impl DependencyHealth {
+    /// A dependency only fails readiness when it is both down and required.
+    fn blocks_readiness(&self) -> bool {
+        self.required && self.status != "up"
+    }
+}
+
+/// Liveness probe response for Kubernetes
+#[derive(Serialize, ToSchema)]
 pub struct LivenessResponse {
     pub status: String,
     pub timestamp: chrono::DateTime<chrono::Utc>,
@@ -42,6 +112,12 @@ pub struct LivenessResponse {
 ///
 /// Returns a simple health status indicating the service is running.
 /// This endpoint is suitable for basic load balancer health checks.
+#[utoipa::path(
+    get,
+    path = "/health/health",
+    tag = "health",
+    responses((status = 200, description = "Service is running", body = HealthResponse))
+)]
 pub async fn health_check() -> Json<HealthResponse> {
     Json(HealthResponse {
         status: "healthy".to_string(),
@@ -52,12 +128,32 @@ pub async fn health_check() -> Json<HealthResponse> {
 
 /// GET /health/ready - Readiness probe
 ///
-/// Returns detailed health status including database connectivity.
+/// Returns detailed health status including database, Redis and Anchor
+/// connectivity. The database must also have every migration this build
+/// ships applied; a schema that is behind reports both versions and fails
+/// readiness. Redis and the Anchor are probed according to their
+/// configured criticality; only required dependencies can fail readiness.
+/// The state of the Anchor circuit breaker is included alongside.
 /// This endpoint is suitable for Kubernetes readiness probes.
 pub async fn readiness_check(State(services): State<Arc<ServiceContainer>>) -> impl IntoResponse {
-    // Check database connectivity
-    let (db_status, pool_status) = match services.db_pool.get().await {
-        Ok(_client) => {
+    // Check database connectivity and how far its migrations have got
+    let expected_version = db::expected_schema_version();
+    let (db_status, pool_status, schema) = match services.db_pool.get().await {
+        Ok(client) => {
+            let schema = match db::applied_schema_version(&client).await {
+                Ok(applied) => SchemaHealth::new(expected_version, applied),
+                Err(e) => {
+                    tracing::error!(error = %e, "Schema version check failed");
+                    SchemaHealth::unknown(expected_version)
+                }
+            };
+            if !schema.is_current() {
+                tracing::warn!(
+                    expected_version,
+                    applied_version = ?schema.applied_version,
+                    "Database schema is behind this build"
+                );
+            }
             let status = services.db_pool.status();
             (
                 "connected",
@@ -66,6 +162,7 @@ pub async fn readiness_check(State(services): State<Arc<ServiceContainer>>) -> i
                     pool_size: status.size as u32,
                     available_connections: status.available as u32,
                 },
+                schema,
             )
         }
         Err(e) => {
@@ -77,14 +174,31 @@ pub async fn readiness_check(State(services): State<Arc<ServiceContainer>>) -> i
                     pool_size: 0,
                     available_connections: 0,
                 },
+                SchemaHealth::unknown(expected_version),
             )
         }
     };
 
-    let is_ready = db_status == "connected";
+    let health_config = &services.config.health;
+    let timeout = Duration::from_millis(health_config.probe_timeout_ms);
+    let (redis, anchor) = tokio::join!(
+        probe_dependency(health_config.redis, timeout, services.job_queue.ping()),
+        probe_dependency(health_config.anchor, timeout, services.anchor.probe_info()),
+    );
+
+    let is_ready = db_status == "connected"
+        && schema.is_current()
+        && ![&redis, &anchor]
+            .into_iter()
+            .flatten()
+            .any(DependencyHealth::blocks_readiness);
     let response = ReadinessResponse {
         status: if is_ready { "ready" } else { "not ready" }.to_string(),
         database: pool_status,
+        schema,
+        redis,
+        anchor,
+        anchor_circuit: services.anchor.breaker_state(),
         uptime_seconds: MetricsService::get_uptime(),
         timestamp: chrono::Utc::now(),
     };
@@ -96,13 +210,168 @@ pub async fn readiness_check(State(services): State<Arc<ServiceContainer>>) -> i
     }
 }
 
+/// Run a single dependency check under a timeout.
+///
+/// Returns `None` when the dependency is disabled, in which case `check` is
+/// never polled.
+async fn probe_dependency<F, E>(
+    criticality: DependencyCriticality,
+    timeout: Duration,
+    check: F,
+) -> Option<DependencyHealth>
+where
+    F: Future<Output = Result<(), E>>,
+    E: Display,
+{
+    if criticality == DependencyCriticality::Disabled {
+        return None;
+    }
+
+    let started = std::time::Instant::now();
+    let error = match tokio::time::timeout(timeout, check).await {
+        Ok(Ok(())) => None,
+        Ok(Err(e)) => Some(e.to_string()),
+        Err(_) => Some(format!("timed out after {}ms", timeout.as_millis())),
+    };
+
+    if let Some(error) = &error {
+        tracing::warn!(error = %error, ?criticality, "Dependency health check failed");
+    }
+
+    let required = criticality == DependencyCriticality::Required;
+    let status = match (&error, required) {
+        (None, _) => "up",
+        (Some(_), true) => "down",
+        // Still serving, just without what the dependency backs
+        (Some(_), false) => "degraded",
+    };
+    Some(DependencyHealth {
+        status: status.to_string(),
+        required,
+        latency_ms: started.elapsed().as_millis() as u64,
+        error,
+    })
+}
+
 /// GET /health/live - Liveness probe
 ///
 /// Returns a simple "alive" status. This endpoint should always return 200
 /// as long as the process is running. Suitable for Kubernetes liveness probes.
+#[utoipa::path(
+    get,
+    path = "/health/live",
+    tag = "health",
+    responses((status = 200, description = "Process is alive", body = LivenessResponse))
+)]
 pub async fn liveness_check() -> Json<LivenessResponse> {
     Json(LivenessResponse {
         status: "alive".to_string(),
         timestamp: chrono::Utc::now(),
     })
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TIMEOUT: Duration = Duration::from_millis(50);
+
+    #[tokio::test]
+    async fn healthy_dependency_is_up() {
+        let health = probe_dependency(DependencyCriticality::Required, TIMEOUT, async {
+            Ok::<(), String>(())
+        })
+        .await
+        .unwrap();
+
+        assert_eq!(health.status, "up");
+        assert!(health.required);
+        assert!(health.error.is_none());
+        assert!(!health.blocks_readiness());
+    }
+
+    #[tokio::test]
+    async fn failing_required_dependency_blocks_readiness() {
+        let health = probe_dependency(DependencyCriticality::Required, TIMEOUT, async {
+            Err::<(), _>("connection refused")
+        })
+        .await
+        .unwrap();
+
+        assert_eq!(health.status, "down");
+        assert_eq!(health.error.as_deref(), Some("connection refused"));
+        assert!(health.blocks_readiness());
+    }
+
+    #[tokio::test]
+    async fn failing_optional_dependency_does_not_block_readiness() {
+        let health = probe_dependency(DependencyCriticality::Optional, TIMEOUT, async {
+            Err::<(), _>("connection refused")
+        })
+        .await
+        .unwrap();
+
+        assert_eq!(health.status, "degraded");
+        assert!(!health.required);
+        assert!(!health.blocks_readiness());
+    }
+
+    #[tokio::test]
+    async fn slow_dependency_times_out() {
+        let health = probe_dependency(DependencyCriticality::Required, TIMEOUT, async {
+            tokio::time::sleep(Duration::from_secs(5)).await;
+            Ok::<(), String>(())
+        })
+        .await
+        .unwrap();
+
+        assert_eq!(health.status, "down");
+        assert!(health.error.unwrap().starts_with("timed out"));
+    }
+
+    #[tokio::test]
+    async fn disabled_dependency_is_not_probed() {
+        let health = probe_dependency(DependencyCriticality::Disabled, TIMEOUT, async {
+            panic!("disabled dependency must not be polled");
+            #[allow(unreachable_code)]
+            Ok::<(), String>(())
+        })
+        .await;
+
+        assert!(health.is_none());
+    }
+
+    #[test]
+    fn up_to_date_schema_is_current() {
+        let schema = SchemaHealth::new(20261016000014, Some(20261016000014));
+        assert_eq!(schema.status, "current");
+        assert!(schema.is_current());
+
+        // A newer schema from a rollout in progress doesn't fail readiness
+        assert!(SchemaHealth::new(20261016000014, Some(20261017000001)).is_current());
+    }
+
+    #[test]
+    fn schema_behind_the_build_is_not_current() {
+        let schema = SchemaHealth::new(20261016000014, Some(20261016000013));
+        assert_eq!(schema.status, "behind");
+        assert!(!schema.is_current());
+
+        let unmigrated = SchemaHealth::new(20261016000014, None);
+        assert_eq!(unmigrated.status, "behind");
+        assert!(!unmigrated.is_current());
+    }
+
+    #[test]
+    fn expected_version_is_the_newest_migration() {
+        let newest = std::fs::read_dir(concat!(env!("CARGO_MANIFEST_DIR"), "/migrations"))
+            .unwrap()
+            .filter_map(|entry| {
+                let name = entry.unwrap().file_name().into_string().unwrap();
+                name.split('_').next()?.parse::<i64>().ok()
+            })
+            .max()
+            .unwrap();
+        assert_eq!(db::expected_schema_version(), newest);
+    }
+}
@@ -31,7 +31,8 @@ pub async fn create_user(
     State(services): State<Arc<ServiceContainer>>,
     Json(request): Json<CreateUserRequest>,
 ) -> Result<Json<UserResponse>, ApiError> {
-    let pin_hash = auth::hash_pin(&request.pin)?;
+    auth::check_pin_policy(&request.pin, &services.config.pin_policy)?;
+    let pin_hash = auth::hash_pin(&request.pin, services.config.pin_hash.cost)?;
     let user = services
         .identity
         .create_user(request.user_id, pin_hash)
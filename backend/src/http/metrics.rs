@@ -8,7 +8,7 @@ use axum::{
 use serde::Serialize;
 use std::sync::Arc;
 
-use crate::service::{MetricsService, ServiceContainer};
+use crate::service::{metrics_service::EndpointLatency, MetricsService, ServiceContainer};
 
 /// Response for the /metrics endpoint (JSON format)
 #[derive(Serialize)]
@@ -19,6 +19,7 @@ pub struct MetricsResponse {
     pub error_rate: f64,
     pub active_connections: f64,
     pub db_pool_connections: f64,
+    pub latency: Vec<EndpointLatency>,
     pub timestamp: chrono::DateTime<chrono::Utc>,
 }
 
@@ -59,6 +60,7 @@ pub async fn prometheus_metrics() -> impl IntoResponse {
 /// - uptime: number (seconds)
 /// - requestCount: number
 /// - errorRate: number (percentage)
+/// - latency: p50/p95/p99 seconds per normalized path
 pub async fn json_metrics(State(services): State<Arc<ServiceContainer>>) -> Json<MetricsResponse> {
     // Update database pool metrics
     let db_pool_size = services.db_pool.status().size;
@@ -72,6 +74,7 @@ pub async fn json_metrics(State(services): State<Arc<ServiceContainer>>) -> Json
         error_rate: detailed.basic.error_rate,
         active_connections: detailed.active_connections,
         db_pool_connections: detailed.db_pool_connections,
+        latency: detailed.latency,
         timestamp: detailed.timestamp,
     })
 }
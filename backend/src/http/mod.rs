@@ -8,6 +8,7 @@ pub mod identity;
 pub mod jobs;
 pub mod metrics;
 pub mod notifications;
+pub mod openapi;
 pub mod payments;
 pub mod profiles;
 pub mod transfers;
@@ -23,6 +24,7 @@ pub use identity::*;
 pub use jobs::*;
 pub use metrics::*;
 pub use notifications::*;
+pub use openapi::*;
 pub use payments::*;
 pub use profiles::*;
 pub use transfers::*;
@@ -0,0 +1,111 @@
+use axum::{response::Html, Json};
+use utoipa::{
+    openapi::security::{HttpAuthScheme, HttpBuilder, SecurityScheme},
+    Modify, OpenApi,
+};
+
+use crate::{
+    api_error::ErrorResponse,
+    http::{auth, health, profiles, transfers, withdrawals},
+};
+
+/// Machine-readable description of the public API, served at
+/// `GET /openapi.json`.
+#[derive(OpenApi)]
+#[openapi(
+    info(title = "Zaps API"),
+    paths(
+        auth::login,
+        auth::register,
+        auth::refresh_token,
+        auth::whoami,
+        health::health_check,
+        health::liveness_check,
+        profiles::create_profile,
+        profiles::get_my_profile,
+        profiles::get_profile,
+        profiles::update_profile,
+        transfers::submit_transfer,
+        transfers::get_transfer_status,
+        withdrawals::create_withdrawal,
+        withdrawals::list_withdrawals,
+        withdrawals::get_withdrawal,
+        withdrawals::get_withdrawal_status,
+    ),
+    components(schemas(
+        ErrorResponse,
+        auth::LoginRequest,
+        auth::RegisterRequest,
+        auth::RefreshTokenRequest,
+        auth::AuthResponse,
+        auth::WhoAmIResponse,
+        health::HealthResponse,
+        health::LivenessResponse,
+        profiles::CreateUserProfileDto,
+        profiles::UpdateUserProfileDto,
+        profiles::UserProfileResponseDto,
+        transfers::SubmitTransferRequest,
+        transfers::TransferStatusResponse,
+        withdrawals::CreateWithdrawalRequest,
+        withdrawals::WithdrawalResponse,
+        withdrawals::WithdrawalListResponse,
+        withdrawals::WithdrawalStatusResponse,
+    )),
+    modifiers(&BearerAuth),
+    tags(
+        (name = "auth", description = "Login, registration and token refresh"),
+        (name = "health", description = "Liveness and health probes"),
+        (name = "profiles", description = "User profiles"),
+        (name = "transfers", description = "User-to-user transfers"),
+        (name = "withdrawals", description = "Withdrawals to an Anchor"),
+    )
+)]
+pub struct ApiDoc;
+
+/// Registers the `bearer_auth` scheme that protected paths refer to.
+struct BearerAuth;
+
+impl Modify for BearerAuth {
+    fn modify(&self, openapi: &mut utoipa::openapi::OpenApi) {
+        let components = openapi.components.get_or_insert_with(Default::default);
+        components.add_security_scheme(
+            "bearer_auth",
+            SecurityScheme::Http(
+                HttpBuilder::new()
+                    .scheme(HttpAuthScheme::Bearer)
+                    .bearer_format("JWT")
+                    .build(),
+            ),
+        );
+    }
+}
+
+/// GET /openapi.json
+pub async fn openapi_json() -> Json<utoipa::openapi::OpenApi> {
+    Json(ApiDoc::openapi())
+}
+
+/// Swagger UI for `/openapi.json`, with its assets loaded from a CDN.
+const SWAGGER_UI_HTML: &str = r##"<!DOCTYPE html>
+<html lang="en">
+<head>
+  <meta charset="utf-8" />
+  <title>Zaps API</title>
+  <link rel="stylesheet" href="https://unpkg.com/swagger-ui-dist@5/swagger-ui.css" />
+</head>
+<body>
+  <div id="swagger-ui"></div>
+  <script src="https://unpkg.com/swagger-ui-dist@5/swagger-ui-bundle.js" crossorigin></script>
+  <script>
+    window.onload = () => {
+      window.ui = SwaggerUIBundle({ url: "/openapi.json", dom_id: "#swagger-ui" });
+    };
+  </script>
+</body>
+</html>
+"##;
+
+/// GET /docs
+pub async fn swagger_ui() -> Html<&'static str> {
+    Html(SWAGGER_UI_HTML)
+}
@@ -1,16 +1,51 @@
 use axum::{
     extract::{Path, State},
-    Json,
+    response::sse::{Event, KeepAlive, Sse},
+    Extension, Json,
 };
+use futures::{Stream, StreamExt};
 use serde::{Deserialize, Serialize};
+use std::convert::Infallible;
 use std::sync::Arc;
+use std::time::Duration;
 use uuid::Uuid;
 
 use crate::{
-    api_error::ApiError,
-    service::{payment_service::CreatePaymentRequest, ServiceContainer},
+    api_error::{ApiError, FieldErrors},
+    assets::{asset_info, AmountInput},
+    middleware::auth::{AuthenticatedUser, MerchantPrincipal},
+    models::{FeeStrategy, Payment},
+    role::Role,
+    service::{
+        payment_events::{self, PaymentStatusChange},
+        payment_service::{fee_strategy_for, CreatePaymentRequest},
+        qr_cache::{CachedQr, QrCacheKey},
+        ServiceContainer, SorobanService,
+    },
 };
 
+/// Whether the caller may act for `merchant_id`. JWT callers are unrestricted
+/// here; a merchant API key only covers its own merchant.
+fn key_covers(principal: &Option<Extension<MerchantPrincipal>>, merchant_id: &str) -> bool {
+    match principal {
+        Some(Extension(principal)) => principal.merchant_id == merchant_id,
+        None => true,
+    }
+}
+
+fn ensure_key_covers(
+    principal: &Option<Extension<MerchantPrincipal>>,
+    merchant_id: &str,
+) -> Result<(), ApiError> {
+    if key_covers(principal, merchant_id) {
+        Ok(())
+    } else {
+        Err(ApiError::Authorization(
+            "API key is not valid for this merchant".to_string(),
+        ))
+    }
+}
+
 #[derive(Debug, Serialize)]
 pub struct PaymentResponse {
     pub id: Uuid,
@@ -19,12 +54,62 @@ pub struct PaymentResponse {
     pub merchant_id: String,
     pub send_asset: String,
     pub send_amount: i64,
+    /// `send_amount` in display units of `send_asset`, e.g. `"12.50"`
+    pub display_send_amount: String,
     pub receive_amount: Option<i64>,
     pub status: String,
     pub memo: Option<String>,
     pub created_at: chrono::DateTime<chrono::Utc>,
     // base64 XDR pre-sponsored by server as fee-payer (if available)
     pub sponsored_xdr: Option<String>,
+    /// Who pays the network fee
+    pub fee_strategy: FeeStrategy,
+    /// Unsigned base64 XDR, for payments the server doesn't sponsor
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub unsigned_xdr: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CreatePaymentBody {
+    pub merchant_id: String,
+    pub send_asset: String,
+    /// Smallest units as an integer, or a decimal string in display units
+    pub send_amount: AmountInput,
+    /// In the merchant's settlement asset
+    pub min_receive: Option<AmountInput>,
+    pub memo: Option<String>,
+    /// Who pays the network fee: the merchant's setting unless the payer
+    /// chooses `sender_pays`
+    pub fee_strategy: Option<FeeStrategy>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SubmitPaymentBody {
+    /// The payment's XDR, signed by the payer
+    pub signed_xdr: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SimulatePaymentBody {
+    pub merchant_id: String,
+    pub send_asset: String,
+    /// Smallest units as an integer, or a decimal string in display units
+    pub send_amount: AmountInput,
+    pub memo: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct PaymentSimulationResponse {
+    /// Whether the payment would succeed as built
+    pub success: bool,
+    /// Estimated fee in stroops
+    pub fee: Option<u32>,
+    /// Number of ledger entries the transaction touches
+    pub footprint: Option<u32>,
+    pub error: Option<String>,
+    pub send_asset: String,
+    pub send_amount: i64,
+    pub display_send_amount: String,
 }
 
 #[derive(Debug, Serialize)]
@@ -34,6 +119,20 @@ pub struct PaymentStatusResponse {
     pub updated_at: chrono::DateTime<chrono::Utc>,
 }
 
+impl From<PaymentStatusChange> for PaymentStatusResponse {
+    fn from(change: PaymentStatusChange) -> Self {
+        Self {
+            id: change.payment_id,
+            status: change.status.to_string(),
+            updated_at: change.updated_at,
+        }
+    }
+}
+
+/// How long `GET /payments/:id/events` stays open without the payment
+/// settling. Clients reconnect if they still care.
+const PAYMENT_EVENTS_TIMEOUT: Duration = Duration::from_secs(5 * 60);
+
 #[derive(Debug, Clone, Deserialize)]
 pub struct QrPaymentRequest {
     pub merchant_id: String,
@@ -71,46 +170,96 @@ pub struct NfcValidationResponse {
     pub xdr_payload: Option<String>,
 }
 
+/// Check a payment's merchant, asset and amount together, reporting every
+/// invalid field at once. Returns the amount in smallest units.
+fn validate_payment_input(
+    soroban: &SorobanService,
+    merchant_id: &str,
+    send_asset: &str,
+    send_amount: &AmountInput,
+) -> Result<i64, ApiError> {
+    let mut errors = FieldErrors::new();
+
+    if merchant_id.trim().is_empty() {
+        errors.add("merchant_id", "Merchant ID is required");
+    }
+    errors.check("send_asset", soroban.validate_asset(send_asset));
+    let units = errors.check(
+        "send_amount",
+        send_amount.resolve(send_asset).map_err(ApiError::from),
+    );
+
+    errors.into_result()?;
+    // Always set once no errors were recorded.
+    Ok(units.unwrap_or_default())
+}
+
 pub async fn create_payment(
     State(services): State<Arc<ServiceContainer>>,
-    Json(request): Json<CreatePaymentRequest>,
+    principal: Option<Extension<MerchantPrincipal>>,
+    Json(request): Json<CreatePaymentBody>,
 ) -> Result<Json<PaymentResponse>, ApiError> {
+    ensure_key_covers(&principal, &request.merchant_id)?;
+
     // Get user from auth context (would need to implement proper auth extraction)
     // For now, using a placeholder address
     let from_address = "GEXAMPLE_ADDRESS".to_string();
 
-    // Validate asset format early (XLM or CODE:ISSUER)
-    services.soroban.validate_asset(&request.send_asset)?;
+    // Validate asset format (XLM or CODE:ISSUER) and amount early
+    let send_amount = validate_payment_input(
+        &services.soroban,
+        &request.merchant_id,
+        &request.send_asset,
+        &request.send_amount,
+    )?;
 
     // Ensure merchant exists and fetch vault address
     let merchant = services.payment.get_merchant(&request.merchant_id).await?;
+    let memo = services
+        .memo_policies
+        .resolve_memo(&request.merchant_id, request.memo.as_deref())
+        .await?;
 
-    // Build payment XDR (base64) for client signing; this is pre-sponsorship build
-    let tx_xdr = services
+    let min_receive = request
+        .min_receive
+        .as_ref()
+        .map(|amount| amount.resolve(&merchant.settlement_asset))
+        .transpose()?;
+    let fee_strategy = fee_strategy_for(&merchant, request.fee_strategy)?;
+
+    // Build payment XDR (base64), signed by a fee payer if sponsored
+    let envelope = services
         .soroban
-        .build_payment_xdr(
+        .build_payment_envelope(
             &from_address,
             &merchant.vault_address,
             &request.send_asset,
-            request.send_amount,
-            request.memo.as_deref(),
+            send_amount,
+            memo.memo.as_deref(),
+            fee_strategy,
         )
         .await?;
-
-    // Optionally simulate to get accurate fees/footprint (not currently returned)
-    let _sim = services.soroban.simulate_transaction(&tx_xdr).await?;
-
-    // Sign as fee payer (server-side) to produce a pre-sponsored XDR
-    let sponsored_xdr = services
-        .soroban
-        .sign_transaction_as_fee_payer(&tx_xdr)
-        .await
-        .map(Some)?;
+    let (sponsored_xdr, unsigned_xdr) = if envelope.sponsored {
+        (Some(envelope.xdr), None)
+    } else {
+        (None, Some(envelope.xdr))
+    };
 
     // Persist payment (status pending)
     let payment = services
         .payment
-        .create_payment(from_address, request)
+        .create_payment(
+            from_address,
+            CreatePaymentRequest {
+                merchant_id: request.merchant_id,
+                send_asset: request.send_asset,
+                send_amount,
+                min_receive,
+                memo: memo.memo,
+                memo_unique: memo.unique,
+                fee_strategy,
+            },
+        )
         .await?;
 
     Ok(Json(PaymentResponse {
@@ -118,6 +267,7 @@ pub async fn create_payment(
         tx_hash: payment.tx_hash,
         from_address: payment.from_address,
         merchant_id: payment.merchant_id,
+        display_send_amount: asset_info(&payment.send_asset).format(payment.send_amount),
         send_asset: payment.send_asset,
         send_amount: payment.send_amount,
         receive_amount: payment.receive_amount,
@@ -125,23 +275,111 @@ pub async fn create_payment(
         memo: payment.memo,
         created_at: payment.created_at,
         sponsored_xdr,
+        fee_strategy: payment.fee_strategy,
+        unsigned_xdr,
+    }))
+}
+
+/// `POST /payments/:id/submit`
+///
+/// Submit the payer's signed payment XDR to the network. The payment is
+/// `processing` until a background job confirms the transaction on-chain.
+pub async fn submit_payment(
+    State(services): State<Arc<ServiceContainer>>,
+    principal: Option<Extension<MerchantPrincipal>>,
+    Path(payment_id): Path<String>,
+    Json(request): Json<SubmitPaymentBody>,
+) -> Result<Json<PaymentStatusResponse>, ApiError> {
+    let payment_uuid = Uuid::parse_str(&payment_id)
+        .map_err(|_| ApiError::Validation("Invalid Payment ID".to_string()))?;
+    if request.signed_xdr.trim().is_empty() {
+        return Err(ApiError::Validation("signed_xdr is required".to_string()));
+    }
+
+    let payment = services.payment.get_payment(payment_uuid).await?;
+    if !key_covers(&principal, &payment.merchant_id) {
+        return Err(ApiError::NotFound("Payment not found".to_string()));
+    }
+
+    let payment = services
+        .payment
+        .submit_payment(
+            payment_uuid,
+            request.signed_xdr,
+            services.job_queue.as_ref(),
+        )
+        .await?;
+
+    Ok(Json(PaymentStatusResponse {
+        id: payment_uuid,
+        status: payment.status.to_string(),
+        updated_at: payment.updated_at,
+    }))
+}
+
+/// Dry-run a payment: build and simulate it without signing or persisting,
+/// so clients can show the fee before committing.
+pub async fn simulate_payment(
+    State(services): State<Arc<ServiceContainer>>,
+    principal: Option<Extension<MerchantPrincipal>>,
+    Json(request): Json<SimulatePaymentBody>,
+) -> Result<Json<PaymentSimulationResponse>, ApiError> {
+    ensure_key_covers(&principal, &request.merchant_id)?;
+
+    let send_amount = validate_payment_input(
+        &services.soroban,
+        &request.merchant_id,
+        &request.send_asset,
+        &request.send_amount,
+    )?;
+
+    let merchant = services.payment.get_merchant(&request.merchant_id).await?;
+    let memo = services
+        .memo_policies
+        .resolve_memo(&request.merchant_id, request.memo.as_deref())
+        .await?;
+
+    let simulation = services
+        .soroban
+        .simulate_payment(
+            "GEXAMPLE_ADDRESS",
+            &merchant.vault_address,
+            &request.send_asset,
+            send_amount,
+            memo.memo.as_deref(),
+        )
+        .await?;
+
+    Ok(Json(PaymentSimulationResponse {
+        success: simulation.error.is_none(),
+        fee: simulation.fee,
+        footprint: simulation.footprint,
+        error: simulation.error,
+        display_send_amount: asset_info(&request.send_asset).format(send_amount),
+        send_asset: request.send_asset,
+        send_amount,
     }))
 }
 
 pub async fn get_payment(
     State(services): State<Arc<ServiceContainer>>,
+    principal: Option<Extension<MerchantPrincipal>>,
     Path(payment_id): Path<String>,
 ) -> Result<Json<PaymentResponse>, ApiError> {
     let payment_uuid = Uuid::parse_str(&payment_id)
         .map_err(|_| ApiError::Validation("Invalid Payment ID".to_string()))?;
 
     let payment = services.payment.get_payment(payment_uuid).await?;
+    if !key_covers(&principal, &payment.merchant_id) {
+        return Err(ApiError::NotFound("Payment not found".to_string()));
+    }
 
     Ok(Json(PaymentResponse {
         id: Uuid::parse_str(&payment.id).unwrap_or_default(),
         tx_hash: payment.tx_hash,
         from_address: payment.from_address,
         merchant_id: payment.merchant_id,
+        display_send_amount: asset_info(&payment.send_asset).format(payment.send_amount),
         send_asset: payment.send_asset,
         send_amount: payment.send_amount,
         receive_amount: payment.receive_amount,
@@ -149,17 +387,23 @@ pub async fn get_payment(
         memo: payment.memo,
         created_at: payment.created_at,
         sponsored_xdr: None,
+        fee_strategy: payment.fee_strategy,
+        unsigned_xdr: None,
     }))
 }
 
 pub async fn get_payment_status(
     State(services): State<Arc<ServiceContainer>>,
+    principal: Option<Extension<MerchantPrincipal>>,
     Path(payment_id): Path<String>,
 ) -> Result<Json<PaymentStatusResponse>, ApiError> {
     let payment_uuid = Uuid::parse_str(&payment_id)
         .map_err(|_| ApiError::Validation("Invalid Payment ID".to_string()))?;
 
     let payment = services.payment.get_payment(payment_uuid).await?;
+    if !key_covers(&principal, &payment.merchant_id) {
+        return Err(ApiError::NotFound("Payment not found".to_string()));
+    }
 
     Ok(Json(PaymentStatusResponse {
         id: Uuid::parse_str(&payment.id).unwrap_or_default(),
@@ -168,53 +412,135 @@ pub async fn get_payment_status(
     }))
 }
 
+/// Whether the caller may watch `payment`: its merchant (by API key or
+/// merchant login), the user who sent it, or an admin.
+async fn may_watch(
+    services: &ServiceContainer,
+    user: &AuthenticatedUser,
+    principal: &Option<Extension<MerchantPrincipal>>,
+    payment: &Payment,
+) -> Result<bool, ApiError> {
+    if principal.is_some() {
+        return Ok(key_covers(principal, &payment.merchant_id));
+    }
+    if user.role == Role::Admin
+        || (user.role == Role::Merchant && user.user_id == payment.merchant_id)
+    {
+        return Ok(true);
+    }
+
+    let wallet = services.identity.get_user_wallet(&user.user_id).await?;
+    Ok(wallet.address == payment.from_address)
+}
+
+/// Server-Sent Events stream of a payment's status: the current status as a
+/// `status` event, then one per transition until the payment settles or
+/// [`PAYMENT_EVENTS_TIMEOUT`] passes.
+pub async fn stream_payment_events(
+    State(services): State<Arc<ServiceContainer>>,
+    user: AuthenticatedUser,
+    principal: Option<Extension<MerchantPrincipal>>,
+    Path(payment_id): Path<String>,
+) -> Result<Sse<impl Stream<Item = Result<Event, Infallible>>>, ApiError> {
+    let payment_uuid = Uuid::parse_str(&payment_id)
+        .map_err(|_| ApiError::Validation("Invalid Payment ID".to_string()))?;
+
+    // Subscribe before reading so a change landing in between isn't lost.
+    let changes = services.payment.subscribe(payment_uuid);
+    let payment = services.payment.get_payment(payment_uuid).await?;
+    if !may_watch(&services, &user, &principal, &payment).await? {
+        return Err(ApiError::NotFound("Payment not found".to_string()));
+    }
+
+    let current = PaymentStatusChange {
+        payment_id: payment_uuid,
+        status: payment.status,
+        updated_at: payment.updated_at,
+    };
+    let events =
+        payment_events::status_changes(current, changes, PAYMENT_EVENTS_TIMEOUT).map(|change| {
+            let event = Event::default()
+                .event("status")
+                .json_data(PaymentStatusResponse::from(change))
+                .expect("payment status serializes to JSON");
+            Ok(event)
+        });
+
+    Ok(Sse::new(events).keep_alive(KeepAlive::default()))
+}
+
 pub async fn generate_qr(
     State(services): State<Arc<ServiceContainer>>,
+    principal: Option<Extension<MerchantPrincipal>>,
     Json(request): Json<QrPaymentRequest>,
 ) -> Result<Json<QrPaymentResponse>, ApiError> {
+    ensure_key_covers(&principal, &request.merchant_id)?;
+
     // Validate asset format early
     services.soroban.validate_asset(&request.asset)?;
 
-    // Get merchant vault address
-    let merchant = services.payment.get_merchant(&request.merchant_id).await?;
-
-    // Build XDR for QR payload
-    let tx_xdr = services
-        .soroban
-        .build_payment_xdr(
-            "GQRCODE_PLACEHOLDER", // Will be replaced by client with actual sender
-            &merchant.vault_address,
-            &request.asset,
-            request.amount,
-            request.memo.as_deref(),
-        )
-        .await?;
-
-    // Sign as fee payer if available
-    let xdr_payload = services
-        .soroban
-        .sign_transaction_as_fee_payer(&tx_xdr)
-        .await
-        .ok();
-
-    let qr_data = services
-        .payment
-        .generate_qr_payment(request.clone())
+    let key = QrCacheKey {
+        merchant_id: request.merchant_id.clone(),
+        amount: request.amount,
+        asset: request.asset.clone(),
+        memo: request.memo.clone(),
+        expiry: request.expiry,
+    };
+
+    // Repeat scans of the same QR reuse the payload instead of re-signing
+    let cached = services
+        .qr_cache
+        .get_or_build(key, || async {
+            // Get merchant vault address
+            let merchant = services.payment.get_merchant(&request.merchant_id).await?;
+
+            // Build XDR for QR payload
+            let tx_xdr = services
+                .soroban
+                .build_payment_xdr(
+                    "GQRCODE_PLACEHOLDER", // Will be replaced by client with actual sender
+                    &merchant.vault_address,
+                    &request.asset,
+                    request.amount,
+                    request.memo.as_deref(),
+                )
+                .await?;
+
+            // Sign as fee payer if available
+            let xdr_payload = services
+                .soroban
+                .sign_transaction_as_fee_payer(&tx_xdr)
+                .await
+                .ok();
+
+            let qr_data = services
+                .payment
+                .generate_qr_payment(request.clone())
+                .await?;
+
+            Ok(CachedQr {
+                qr_data,
+                xdr_payload,
+            })
+        })
         .await?;
 
     Ok(Json(QrPaymentResponse {
-        qr_data,
+        qr_data: cached.qr_data,
         merchant_id: request.merchant_id,
         amount: request.amount,
         asset: request.asset,
-        xdr_payload,
+        xdr_payload: cached.xdr_payload,
     }))
 }
 
 pub async fn validate_nfc(
     State(services): State<Arc<ServiceContainer>>,
+    principal: Option<Extension<MerchantPrincipal>>,
     Json(request): Json<NfcPaymentRequest>,
 ) -> Result<Json<NfcValidationResponse>, ApiError> {
+    ensure_key_covers(&principal, &request.merchant_id)?;
+
     // Validate asset format early
     services.soroban.validate_asset(&request.asset)?;
 
@@ -252,3 +578,42 @@ pub async fn validate_nfc(
         xdr_payload,
     }))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Config;
+
+    #[test]
+    fn every_invalid_payment_field_is_reported() {
+        let soroban = SorobanService::new(Config::default());
+
+        let Err(ApiError::InvalidFields(errors)) = validate_payment_input(
+            &soroban,
+            " ",
+            "USDC",
+            &AmountInput::Display("1.2.3".to_string()),
+        ) else {
+            panic!("expected field errors");
+        };
+
+        assert_eq!(errors.len(), 3);
+        for field in ["merchant_id", "send_asset", "send_amount"] {
+            assert!(errors.get(field).is_some(), "{} not reported", field);
+        }
+    }
+
+    #[test]
+    fn valid_payment_input_resolves_amount() {
+        let soroban = SorobanService::new(Config::default());
+
+        let units = validate_payment_input(
+            &soroban,
+            "merchant-1",
+            "XLM",
+            &AmountInput::Display("1.5".to_string()),
+        )
+        .unwrap();
+        assert_eq!(units, 15_000_000);
+    }
+}
@@ -1,14 +1,18 @@
 use axum::{
     extract::{Extension, Path, State},
-    http::StatusCode,
+    http::{header, HeaderMap, StatusCode},
     Json,
 };
 use serde::{Deserialize, Serialize};
 use std::sync::Arc;
+use utoipa::ToSchema;
 use uuid::Uuid;
 
 use crate::{
-    api_error::ApiError, middleware::auth::AuthenticatedUser, role::Role, service::ServiceContainer,
+    api_error::{ApiError, FieldErrors},
+    middleware::auth::AuthenticatedUser,
+    role::Role,
+    service::{profile_service::ProfileChanges, ServiceContainer},
 };
 
 /// Helper function to check if a user can access a resource (own resource or admin)
@@ -16,70 +20,211 @@ fn can_access_resource(user: &AuthenticatedUser, resource_user_id: &str) -> bool
     user.user_id == resource_user_id || user.role == Role::Admin
 }
 
-/// Validate profile input fields
+/// ISO 3166-1 alpha-2 country codes, sorted for binary search.
+const ISO_COUNTRY_CODES: [&str; 249] = [
+    "AD", "AE", "AF", "AG", "AI", "AL", "AM", "AO", "AQ", "AR", "AS", "AT", "AU", "AW", "AX", "AZ",
+    "BA", "BB", "BD", "BE", "BF", "BG", "BH", "BI", "BJ", "BL", "BM", "BN", "BO", "BQ", "BR", "BS",
+    "BT", "BV", "BW", "BY", "BZ", "CA", "CC", "CD", "CF", "CG", "CH", "CI", "CK", "CL", "CM", "CN",
+    "CO", "CR", "CU", "CV", "CW", "CX", "CY", "CZ", "DE", "DJ", "DK", "DM", "DO", "DZ", "EC", "EE",
+    "EG", "EH", "ER", "ES", "ET", "FI", "FJ", "FK", "FM", "FO", "FR", "GA", "GB", "GD", "GE", "GF",
+    "GG", "GH", "GI", "GL", "GM", "GN", "GP", "GQ", "GR", "GS", "GT", "GU", "GW", "GY", "HK", "HM",
+    "HN", "HR", "HT", "HU", "ID", "IE", "IL", "IM", "IN", "IO", "IQ", "IR", "IS", "IT", "JE", "JM",
+    "JO", "JP", "KE", "KG", "KH", "KI", "KM", "KN", "KP", "KR", "KW", "KY", "KZ", "LA", "LB", "LC",
+    "LI", "LK", "LR", "LS", "LT", "LU", "LV", "LY", "MA", "MC", "MD", "ME", "MF", "MG", "MH", "MK",
+    "ML", "MM", "MN", "MO", "MP", "MQ", "MR", "MS", "MT", "MU", "MV", "MW", "MX", "MY", "MZ", "NA",
+    "NC", "NE", "NF", "NG", "NI", "NL", "NO", "NP", "NR", "NU", "NZ", "OM", "PA", "PE", "PF", "PG",
+    "PH", "PK", "PL", "PM", "PN", "PR", "PS", "PT", "PW", "PY", "QA", "RE", "RO", "RS", "RU", "RW",
+    "SA", "SB", "SC", "SD", "SE", "SG", "SH", "SI", "SJ", "SK", "SL", "SM", "SN", "SO", "SR", "SS",
+    "ST", "SV", "SX", "SY", "SZ", "TC", "TD", "TF", "TG", "TH", "TJ", "TK", "TL", "TM", "TN", "TO",
+    "TR", "TT", "TV", "TW", "TZ", "UA", "UG", "UM", "US", "UY", "UZ", "VA", "VC", "VE", "VG", "VI",
+    "VN", "VU", "WF", "WS", "YE", "YT", "ZA", "ZM", "ZW",
+];
+
+/// Validate an optional country against ISO 3166-1 alpha-2 and return it
+/// uppercased. `None` stays unset.
+fn normalize_country(country: Option<String>) -> Result<Option<String>, ApiError> {
+    let Some(country) = country else {
+        return Ok(None);
+    };
+
+    let code = country.trim().to_ascii_uppercase();
+    if ISO_COUNTRY_CODES.binary_search(&code.as_str()).is_err() {
+        return Err(ApiError::Validation(
+            "Country must be an ISO 3166-1 alpha-2 code".into(),
+        ));
+    }
+
+    Ok(Some(code))
+}
+
+/// Largest serialized `metadata` object accepted on a profile.
+const MAX_METADATA_BYTES: usize = 16 * 1024;
+
+/// Deepest nesting of objects/arrays accepted in `metadata`.
+const MAX_METADATA_DEPTH: usize = 8;
+
+/// Nesting depth of a JSON value; scalars are depth 0.
+fn json_depth(value: &serde_json::Value) -> usize {
+    let mut max_depth = 0;
+    let mut stack = vec![(value, 0)];
+
+    while let Some((value, depth)) = stack.pop() {
+        let children: Box<dyn Iterator<Item = &serde_json::Value>> = match value {
+            serde_json::Value::Array(items) => Box::new(items.iter()),
+            serde_json::Value::Object(map) => Box::new(map.values()),
+            _ => continue,
+        };
+        max_depth = max_depth.max(depth + 1);
+        stack.extend(children.map(|child| (child, depth + 1)));
+    }
+
+    max_depth
+}
+
+/// Reject profile metadata that is too large or too deeply nested.
+fn validate_metadata(metadata: Option<&serde_json::Value>) -> Result<(), ApiError> {
+    let Some(metadata) = metadata else {
+        return Ok(());
+    };
+
+    let size = serde_json::to_vec(metadata)
+        .map_err(|_| ApiError::Validation("Metadata must be valid JSON".into()))?
+        .len();
+    if size > MAX_METADATA_BYTES {
+        return Err(ApiError::Validation(format!(
+            "Metadata must be {} bytes or less",
+            MAX_METADATA_BYTES
+        )));
+    }
+
+    if json_depth(metadata) > MAX_METADATA_DEPTH {
+        return Err(ApiError::Validation(format!(
+            "Metadata must be nested {} levels or less",
+            MAX_METADATA_DEPTH
+        )));
+    }
+
+    Ok(())
+}
+
+fn validate_display_name(name: &str) -> Result<(), ApiError> {
+    if name.trim().is_empty() {
+        return Err(ApiError::Validation("Display name cannot be empty".into()));
+    }
+    if name.len() > 100 {
+        return Err(ApiError::Validation(
+            "Display name must be 100 characters or less".into(),
+        ));
+    }
+    Ok(())
+}
+
+fn validate_avatar_url(url: &str) -> Result<(), ApiError> {
+    if url.len() > 2048 {
+        return Err(ApiError::Validation(
+            "Avatar URL must be 2048 characters or less".into(),
+        ));
+    }
+    // Basic URL format validation
+    if !url.starts_with("http://") && !url.starts_with("https://") && !url.starts_with("/") {
+        return Err(ApiError::Validation(
+            "Avatar URL must be a valid HTTP/HTTPS URL or relative path".into(),
+        ));
+    }
+    Ok(())
+}
+
+fn validate_bio(bio: &str) -> Result<(), ApiError> {
+    if bio.len() > 500 {
+        return Err(ApiError::Validation(
+            "Bio must be 500 characters or less".into(),
+        ));
+    }
+    Ok(())
+}
+
+/// Validate profile input fields, reporting every invalid field at once.
+/// Returns the normalized country code.
 fn validate_profile_input(
     display_name: Option<&String>,
     avatar_url: Option<&String>,
     bio: Option<&String>,
-) -> Result<(), ApiError> {
-    // Validate display_name if provided
+    country: Option<String>,
+    metadata: Option<&serde_json::Value>,
+) -> Result<Option<String>, ApiError> {
+    let mut errors = FieldErrors::new();
+
     if let Some(name) = display_name {
-        if name.trim().is_empty() {
-            return Err(ApiError::Validation("Display name cannot be empty".into()));
-        }
-        if name.len() > 100 {
-            return Err(ApiError::Validation(
-                "Display name must be 100 characters or less".into(),
-            ));
-        }
+        errors.check("display_name", validate_display_name(name));
     }
-
-    // Validate avatar_url if provided
     if let Some(url) = avatar_url {
-        if url.len() > 2048 {
-            return Err(ApiError::Validation(
-                "Avatar URL must be 2048 characters or less".into(),
-            ));
-        }
-        // Basic URL format validation
-        if !url.starts_with("http://") && !url.starts_with("https://") && !url.starts_with("/") {
-            return Err(ApiError::Validation(
-                "Avatar URL must be a valid HTTP/HTTPS URL or relative path".into(),
-            ));
-        }
+        errors.check("avatar_url", validate_avatar_url(url));
     }
-
-    // Validate bio if provided
-    if let Some(bio_text) = bio {
-        if bio_text.len() > 500 {
-            return Err(ApiError::Validation(
-                "Bio must be 500 characters or less".into(),
-            ));
-        }
+    if let Some(bio) = bio {
+        errors.check("bio", validate_bio(bio));
     }
+    let country = errors.check("country", normalize_country(country));
+    errors.check("metadata", validate_metadata(metadata));
 
-    Ok(())
+    errors.into_result()?;
+    Ok(country.flatten())
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, ToSchema)]
+#[schema(example = json!({
+    "display_name": "Ada",
+    "avatar_url": "https://cdn.example.com/ada.png",
+    "bio": "Paying it forward",
+    "country": "NG"
+}))]
 pub struct CreateUserProfileDto {
     pub display_name: String,
     pub avatar_url: Option<String>,
     pub bio: Option<String>,
     pub country: Option<String>,
+    #[schema(value_type = Option<Object>)]
     pub metadata: Option<serde_json::Value>,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, ToSchema)]
 pub struct UpdateUserProfileDto {
     pub display_name: Option<String>,
     pub avatar_url: Option<String>,
     pub bio: Option<String>,
     pub country: Option<String>,
+    #[schema(value_type = Option<Object>)]
     pub metadata: Option<serde_json::Value>,
+    /// Version the client last read; may be sent as `If-Match` instead.
+    pub version: Option<i32>,
+}
+
+/// The profile version an update was based on, from `If-Match` (an entity
+/// tag such as `"3"`) or the body's `version`.
+fn expected_version(headers: &HeaderMap, body_version: Option<i32>) -> Result<i32, ApiError> {
+    let header_version =
+        match headers.get(header::IF_MATCH) {
+            Some(value) => {
+                let tag = value.to_str().unwrap_or_default().trim();
+                let tag = tag.strip_prefix("W/").unwrap_or(tag).trim_matches('"');
+                Some(tag.parse::<i32>().map_err(|_| {
+                    ApiError::Validation("If-Match must be a profile version".into())
+                })?)
+            }
+            None => None,
+        };
+
+    match (header_version, body_version) {
+        (Some(h), Some(b)) if h != b => {
+            Err(ApiError::Validation("If-Match and version disagree".into()))
+        }
+        (Some(v), _) | (None, Some(v)) => Ok(v),
+        (None, None) => Err(ApiError::Validation(
+            "Profile version is required (If-Match header or version field)".into(),
+        )),
+    }
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 pub struct UserProfileResponseDto {
     pub id: String,
     pub user_id: String,
@@ -87,20 +232,34 @@ pub struct UserProfileResponseDto {
     pub avatar_url: Option<String>,
     pub bio: Option<String>,
     pub country: Option<String>,
+    pub version: i32,
     pub created_at: chrono::DateTime<chrono::Utc>,
     pub updated_at: chrono::DateTime<chrono::Utc>,
 }
 
+#[utoipa::path(
+    post,
+    path = "/profiles",
+    tag = "profiles",
+    request_body = CreateUserProfileDto,
+    responses(
+        (status = 200, description = "The new profile", body = UserProfileResponseDto),
+        (status = 400, description = "Invalid fields", body = crate::api_error::ErrorResponse),
+    ),
+    security(("bearer_auth" = []))
+)]
 pub async fn create_profile(
     State(services): State<Arc<ServiceContainer>>,
     Extension(user): Extension<AuthenticatedUser>,
     Json(request): Json<CreateUserProfileDto>,
 ) -> Result<Json<UserProfileResponseDto>, ApiError> {
     // Validate input
-    validate_profile_input(
+    let country = validate_profile_input(
         Some(&request.display_name),
         request.avatar_url.as_ref(),
         request.bio.as_ref(),
+        request.country,
+        request.metadata.as_ref(),
     )?;
 
     // Resolve username to internal UUID
@@ -122,7 +281,7 @@ pub async fn create_profile(
             request.display_name,
             request.avatar_url,
             request.bio,
-            request.country,
+            country,
             request.metadata,
         )
         .await?;
@@ -134,11 +293,23 @@ pub async fn create_profile(
         avatar_url: profile.avatar_url,
         bio: profile.bio,
         country: profile.country,
+        version: profile.version,
         created_at: profile.created_at,
         updated_at: profile.updated_at,
     }))
 }
 
+#[utoipa::path(
+    get,
+    path = "/profiles/{user_id}",
+    tag = "profiles",
+    params(("user_id" = String, Path, description = "User whose profile to fetch")),
+    responses(
+        (status = 200, description = "The profile", body = UserProfileResponseDto),
+        (status = 404, description = "No profile", body = crate::api_error::ErrorResponse),
+    ),
+    security(("bearer_auth" = []))
+)]
 pub async fn get_profile(
     State(services): State<Arc<ServiceContainer>>,
     Path(user_id): Path<String>,
@@ -161,15 +332,32 @@ pub async fn get_profile(
         avatar_url: profile.avatar_url,
         bio: profile.bio,
         country: profile.country,
+        version: profile.version,
         created_at: profile.created_at,
         updated_at: profile.updated_at,
     }))
 }
 
+#[utoipa::path(
+    patch,
+    path = "/profiles/{user_id}",
+    tag = "profiles",
+    request_body = UpdateUserProfileDto,
+    params(
+        ("user_id" = String, Path, description = "User whose profile to update"),
+        ("If-Match" = Option<String>, Header, description = "Profile version the update is based on"),
+    ),
+    responses(
+        (status = 200, description = "The updated profile", body = UserProfileResponseDto),
+        (status = 409, description = "Profile changed since it was read", body = crate::api_error::ErrorResponse),
+    ),
+    security(("bearer_auth" = []))
+)]
 pub async fn update_profile(
     State(services): State<Arc<ServiceContainer>>,
     Extension(user): Extension<AuthenticatedUser>,
     Path(user_id): Path<String>,
+    headers: HeaderMap,
     Json(request): Json<UpdateUserProfileDto>,
 ) -> Result<Json<UserProfileResponseDto>, ApiError> {
     // Authorization check: User can only update their own profile, unless Admin
@@ -179,11 +367,15 @@ pub async fn update_profile(
         ));
     }
 
+    let version = expected_version(&headers, request.version)?;
+
     // Validate input
-    validate_profile_input(
+    let country = validate_profile_input(
         request.display_name.as_ref(),
         request.avatar_url.as_ref(),
         request.bio.as_ref(),
+        request.country,
+        request.metadata.as_ref(),
     )?;
 
     // Resolve username to internal UUID
@@ -195,11 +387,14 @@ pub async fn update_profile(
         .profile
         .update_profile(
             target_uuid,
-            request.display_name,
-            request.avatar_url,
-            request.bio,
-            request.country,
-            request.metadata,
+            version,
+            ProfileChanges {
+                display_name: request.display_name,
+                avatar_url: request.avatar_url,
+                bio: request.bio,
+                country,
+                metadata: request.metadata,
+            },
         )
         .await?;
 
@@ -210,6 +405,7 @@ pub async fn update_profile(
         avatar_url: profile.avatar_url,
         bio: profile.bio,
         country: profile.country,
+        version: profile.version,
         created_at: profile.created_at,
         updated_at: profile.updated_at,
     }))
@@ -237,7 +433,50 @@ pub async fn delete_profile(
     Ok(StatusCode::NO_CONTENT)
 }
 
+/// Restore a profile deleted within the recovery window
+pub async fn restore_profile(
+    State(services): State<Arc<ServiceContainer>>,
+    Extension(user): Extension<AuthenticatedUser>,
+    Path(user_id): Path<String>,
+) -> Result<Json<UserProfileResponseDto>, ApiError> {
+    // Authorization check: User can only restore their own profile, unless Admin
+    if !can_access_resource(&user, &user_id) {
+        return Err(ApiError::Authorization(
+            "You can only restore your own profile".into(),
+        ));
+    }
+
+    // Resolve username to internal UUID
+    let user_model = services.identity.get_user_by_id(&user_id).await?;
+    let target_uuid = Uuid::parse_str(&user_model.id)
+        .map_err(|_| ApiError::Validation("Invalid user internal ID".into()))?;
+
+    let profile = services.profile.restore_profile(target_uuid).await?;
+
+    Ok(Json(UserProfileResponseDto {
+        id: profile.id,
+        user_id,
+        display_name: profile.display_name,
+        avatar_url: profile.avatar_url,
+        bio: profile.bio,
+        country: profile.country,
+        version: profile.version,
+        created_at: profile.created_at,
+        updated_at: profile.updated_at,
+    }))
+}
+
 /// Get the authenticated user's own profile
+#[utoipa::path(
+    get,
+    path = "/profiles/me",
+    tag = "profiles",
+    responses(
+        (status = 200, description = "The caller's profile", body = UserProfileResponseDto),
+        (status = 404, description = "No profile", body = crate::api_error::ErrorResponse),
+    ),
+    security(("bearer_auth" = []))
+)]
 pub async fn get_my_profile(
     State(services): State<Arc<ServiceContainer>>,
     Extension(user): Extension<AuthenticatedUser>,
@@ -260,7 +499,157 @@ pub async fn get_my_profile(
         avatar_url: profile.avatar_url,
         bio: profile.bio,
         country: profile.country,
+        version: profile.version,
         created_at: profile.created_at,
         updated_at: profile.updated_at,
     }))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn country_codes_are_sorted() {
+        assert!(ISO_COUNTRY_CODES.windows(2).all(|w| w[0] < w[1]));
+    }
+
+    #[test]
+    fn valid_country_code_is_accepted() {
+        assert_eq!(
+            normalize_country(Some("NG".to_string())).unwrap(),
+            Some("NG".to_string())
+        );
+    }
+
+    #[test]
+    fn lowercase_country_code_is_normalized() {
+        assert_eq!(
+            normalize_country(Some("gb".to_string())).unwrap(),
+            Some("GB".to_string())
+        );
+    }
+
+    #[test]
+    fn invalid_country_is_rejected() {
+        for country in ["Nigeria", "XX", "", "U"] {
+            assert!(
+                matches!(
+                    normalize_country(Some(country.to_string())),
+                    Err(ApiError::Validation(_))
+                ),
+                "{}",
+                country
+            );
+        }
+    }
+
+    fn nested(depth: usize) -> serde_json::Value {
+        (0..depth).fold(
+            serde_json::json!("leaf"),
+            |inner, _| serde_json::json!({ "child": inner }),
+        )
+    }
+
+    #[test]
+    fn reasonable_metadata_is_accepted() {
+        let metadata = serde_json::json!({
+            "theme": "dark",
+            "links": ["https://example.com"],
+            "preferences": { "currency": "USD", "notifications": { "email": true } },
+        });
+
+        assert!(validate_metadata(Some(&metadata)).is_ok());
+        assert!(validate_metadata(None).is_ok());
+    }
+
+    #[test]
+    fn oversized_metadata_is_rejected() {
+        let metadata = serde_json::json!({ "blob": "x".repeat(MAX_METADATA_BYTES) });
+
+        assert!(matches!(
+            validate_metadata(Some(&metadata)),
+            Err(ApiError::Validation(_))
+        ));
+    }
+
+    #[test]
+    fn deeply_nested_metadata_is_rejected() {
+        assert_eq!(json_depth(&nested(MAX_METADATA_DEPTH)), MAX_METADATA_DEPTH);
+        assert!(validate_metadata(Some(&nested(MAX_METADATA_DEPTH))).is_ok());
+        assert!(matches!(
+            validate_metadata(Some(&nested(MAX_METADATA_DEPTH + 1))),
+            Err(ApiError::Validation(_))
+        ));
+    }
+
+    #[test]
+    fn every_invalid_field_is_reported() {
+        let name = "   ".to_string();
+        let avatar = "ftp://example.com/me.png".to_string();
+        let bio = "b".repeat(501);
+
+        let Err(ApiError::InvalidFields(errors)) = validate_profile_input(
+            Some(&name),
+            Some(&avatar),
+            Some(&bio),
+            Some("NG".to_string()),
+            None,
+        ) else {
+            panic!("expected field errors");
+        };
+
+        assert_eq!(errors.len(), 3);
+        assert_eq!(
+            errors.get("display_name"),
+            Some("Display name cannot be empty")
+        );
+        assert!(errors.get("avatar_url").is_some());
+        assert_eq!(
+            errors.get("bio"),
+            Some("Bio must be 500 characters or less")
+        );
+    }
+
+    #[test]
+    fn valid_profile_input_returns_normalized_country() {
+        let name = "Ada".to_string();
+        assert_eq!(
+            validate_profile_input(Some(&name), None, None, Some("ng".to_string()), None).unwrap(),
+            Some("NG".to_string())
+        );
+    }
+
+    fn if_match(value: &str) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        headers.insert(header::IF_MATCH, value.parse().unwrap());
+        headers
+    }
+
+    #[test]
+    fn expected_version_comes_from_if_match_or_body() {
+        assert_eq!(expected_version(&if_match("\"3\""), None).unwrap(), 3);
+        assert_eq!(expected_version(&if_match("W/\"4\""), None).unwrap(), 4);
+        assert_eq!(expected_version(&HeaderMap::new(), Some(5)).unwrap(), 5);
+        assert_eq!(expected_version(&if_match("6"), Some(6)).unwrap(), 6);
+    }
+
+    #[test]
+    fn missing_or_conflicting_version_is_rejected() {
+        for (headers, body) in [
+            (HeaderMap::new(), None),
+            (if_match("\"*\""), None),
+            (if_match("\"2\""), Some(3)),
+        ] {
+            assert!(matches!(
+                expected_version(&headers, body),
+                Err(ApiError::Validation(_))
+            ));
+        }
+    }
+
+    #[test]
+    fn missing_country_stays_unset() {
+        assert_eq!(normalize_country(None).unwrap(), None);
+    }
+}
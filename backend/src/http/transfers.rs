@@ -3,14 +3,17 @@ use axum::{
     Json,
 };
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::sync::Arc;
+use utoipa::ToSchema;
 use uuid::Uuid;
 
 use crate::{
-    api_error::ApiError,
+    api_error::{ApiError, FieldErrors},
+    assets::{asset_info, AmountInput},
     middleware::auth::AuthenticatedUser,
-    models::BuildTransactionDto,
-    service::soroban_service::TransactionBuilder,
+    models::Wallet,
+    service::transfer_service::{CreateTransferParams, TransferRecord},
     service::ServiceContainer,
 };
 
@@ -20,6 +23,8 @@ pub struct TransferResponse {
     pub from_user_id: String,
     pub to_user_id: String,
     pub amount: i64,
+    /// `amount` in display units of `asset`, e.g. `"12.50"`
+    pub display_amount: String,
     pub asset: String,
     pub status: String,
     pub memo: Option<String>,
@@ -27,10 +32,36 @@ pub struct TransferResponse {
     pub unsigned_xdr: String,
 }
 
+#[derive(Debug, Serialize, ToSchema)]
+pub struct TransferStatusResponse {
+    pub id: String,
+    pub status: String,
+    pub tx_hash: Option<String>,
+    pub updated_at: chrono::DateTime<chrono::Utc>,
+}
+
+impl From<TransferRecord> for TransferStatusResponse {
+    fn from(record: TransferRecord) -> Self {
+        Self {
+            id: record.id,
+            status: record.status,
+            tx_hash: record.tx_hash,
+            updated_at: record.updated_at,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct SubmitTransferRequest {
+    /// Base64 transaction envelope signed by the sender.
+    pub signed_xdr: String,
+}
+
 #[derive(Debug, Deserialize)]
 pub struct CreateTransferRequest {
     pub to_user_id: String,
-    pub amount: i64,
+    /// Smallest units as an integer, or a decimal string in display units
+    pub amount: AmountInput,
     pub asset: String,
     pub memo: Option<String>,
 }
@@ -45,11 +76,7 @@ pub async fn create_transfer(
     auth_user: AuthenticatedUser,
     Json(request): Json<CreateTransferRequest>,
 ) -> Result<Json<TransferResponse>, ApiError> {
-    if request.amount <= 0 {
-        return Err(ApiError::Validation(
-            "Amount must be greater than zero".to_string(),
-        ));
-    }
+    let amount = request.amount.resolve(&request.asset)?;
 
     // Resolve sender and recipient wallets and validate recipient
     let from_wallet = services
@@ -68,35 +95,197 @@ pub async fn create_transfer(
         ));
     }
 
-    // Build an unsigned transaction XDR for the direct transfer
-    let dto = BuildTransactionDto {
-        contract_id: "user_to_user_transfer".to_string(),
-        method: "transfer".to_string(),
-        args: vec![
-            serde_json::json!({ "from_user_id": auth_user.user_id }),
-            serde_json::json!({ "from_address": from_wallet.address }),
-            serde_json::json!({ "to_user_id": request.to_user_id }),
-            serde_json::json!({ "to_address": to_user.stellar_address }),
-            serde_json::json!({ "asset": request.asset }),
-            serde_json::json!({ "amount": request.amount }),
-            serde_json::json!({ "memo": request.memo }),
-        ],
-    };
-
-    let unsigned_xdr = services.soroban.build_transaction(dto).await?;
-
-    let transfer_id = Uuid::new_v4();
-
-    Ok(Json(TransferResponse {
-        id: transfer_id,
+    let params = CreateTransferParams {
         from_user_id: auth_user.user_id,
         to_user_id: request.to_user_id,
-        amount: request.amount,
+        amount,
         asset: request.asset,
-        status: "pending".to_string(),
         memo: request.memo,
+    };
+
+    // Build an unsigned transaction XDR for the direct transfer
+    let unsigned_xdr = transfer_xdr(&services, &from_wallet, &to_user.stellar_address, &params);
+
+    let transfer = services.transfer.create_transfer(params).await?;
+
+    transfer_response(transfer, unsigned_xdr).map(Json)
+}
+
+/// Most transfers accepted in one `POST /transfers/batch`.
+const MAX_BATCH_TRANSFERS: usize = 100;
+
+#[derive(Debug, Deserialize)]
+pub struct CreateTransferBatchRequest {
+    pub transfers: Vec<CreateTransferRequest>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct TransferBatchResponse {
+    /// Created transfers, in request order.
+    pub transfers: Vec<TransferResponse>,
+}
+
+/// `POST /transfers/batch`
+///
+/// Create many transfers from the caller in one call, e.g. for payroll.
+/// Every entry is validated and every recipient resolved before anything is
+/// written; a single bad entry rejects the whole batch, with the offending
+/// fields keyed as `transfers[i].<field>`. The transfers are then created
+/// atomically, each with its own unsigned XDR to sign and submit.
+pub async fn create_transfer_batch(
+    State(services): State<Arc<ServiceContainer>>,
+    auth_user: AuthenticatedUser,
+    Json(request): Json<CreateTransferBatchRequest>,
+) -> Result<Json<TransferBatchResponse>, ApiError> {
+    if request.transfers.is_empty() || request.transfers.len() > MAX_BATCH_TRANSFERS {
+        return Err(ApiError::Validation(format!(
+            "A batch must contain between 1 and {} transfers",
+            MAX_BATCH_TRANSFERS
+        )));
+    }
+
+    let from_wallet = services
+        .identity
+        .get_user_wallet(&auth_user.user_id)
+        .await?;
+
+    let mut errors = FieldErrors::new();
+    let mut recipients: HashMap<String, Option<String>> = HashMap::new();
+    let mut batch = Vec::with_capacity(request.transfers.len());
+    for (i, entry) in request.transfers.into_iter().enumerate() {
+        let amount = errors.check(
+            &format!("transfers[{}].amount", i),
+            entry.amount.resolve(&entry.asset).map_err(ApiError::from),
+        );
+
+        if !recipients.contains_key(&entry.to_user_id) {
+            let address = services
+                .identity
+                .get_user_by_id(&entry.to_user_id)
+                .await
+                .ok()
+                .map(|user| user.stellar_address)
+                .filter(|address| is_valid_stellar_address(address));
+            recipients.insert(entry.to_user_id.clone(), address);
+        }
+        let to_address = recipients[&entry.to_user_id].clone();
+        if to_address.is_none() {
+            errors.add(
+                &format!("transfers[{}].to_user_id", i),
+                format!("Recipient {} could not be resolved", entry.to_user_id),
+            );
+        }
+
+        if let (Some(amount), Some(to_address)) = (amount, to_address) {
+            let params = CreateTransferParams {
+                from_user_id: auth_user.user_id.clone(),
+                to_user_id: entry.to_user_id,
+                amount,
+                asset: entry.asset,
+                memo: entry.memo,
+            };
+            batch.push((to_address, params));
+        }
+    }
+    errors.into_result()?;
+
+    let unsigned_xdrs: Vec<String> = batch
+        .iter()
+        .map(|(to_address, params)| transfer_xdr(&services, &from_wallet, to_address, params))
+        .collect();
+
+    let created = services
+        .transfer
+        .create_transfers(batch.into_iter().map(|(_, params)| params).collect())
+        .await?;
+
+    let transfers = created
+        .into_iter()
+        .zip(unsigned_xdrs)
+        .map(|(transfer, unsigned_xdr)| transfer_response(transfer, unsigned_xdr))
+        .collect::<Result<_, _>>()?;
+
+    Ok(Json(TransferBatchResponse { transfers }))
+}
+
+/// Unsigned payment for a direct user-to-user transfer.
+fn transfer_xdr(
+    services: &ServiceContainer,
+    from_wallet: &Wallet,
+    to_address: &str,
+    params: &CreateTransferParams,
+) -> String {
+    services.soroban.build_transfer_xdr(
+        &from_wallet.address,
+        to_address,
+        &params.asset,
+        params.amount,
+        params.memo.as_deref(),
+    )
+}
+
+fn transfer_response(
+    transfer: TransferRecord,
+    unsigned_xdr: String,
+) -> Result<TransferResponse, ApiError> {
+    Ok(TransferResponse {
+        id: parse_transfer_id(&transfer.id)?,
+        from_user_id: transfer.from_user_id,
+        to_user_id: transfer.to_user_id,
+        amount: transfer.amount,
+        display_amount: asset_info(&transfer.asset).format(transfer.amount),
+        asset: transfer.asset,
+        status: transfer.status,
+        memo: transfer.memo,
         unsigned_xdr,
-    }))
+    })
+}
+
+fn parse_transfer_id(id: &str) -> Result<Uuid, ApiError> {
+    Uuid::parse_str(id).map_err(|_| ApiError::InternalServerError)
+}
+
+/// `POST /transfers/:id/submit`
+///
+/// Submit the sender's signed transfer XDR to the network. The transfer
+/// stays `pending` until a background job confirms the transaction on-chain;
+/// one the network rejects outright is marked `failed` immediately.
+#[utoipa::path(
+    post,
+    path = "/transfers/{id}/submit",
+    tag = "transfers",
+    request_body = SubmitTransferRequest,
+    params(("id" = Uuid, Path, description = "Transfer id")),
+    responses(
+        (status = 200, description = "Submitted; awaiting on-chain confirmation", body = TransferStatusResponse),
+        (status = 400, description = "The transaction doesn't match the transfer, or the network rejected it", body = crate::api_error::ErrorResponse),
+        (status = 409, description = "Transfer was already submitted", body = crate::api_error::ErrorResponse),
+        (status = 502, description = "The network couldn't be reached; the transfer stays pending", body = crate::api_error::ErrorResponse),
+        (status = 503, description = "The network is busy; retry the submission", body = crate::api_error::ErrorResponse),
+    ),
+    security(("bearer_auth" = []))
+)]
+pub async fn submit_transfer(
+    State(services): State<Arc<ServiceContainer>>,
+    auth_user: AuthenticatedUser,
+    Path(transfer_id): Path<Uuid>,
+    Json(request): Json<SubmitTransferRequest>,
+) -> Result<Json<TransferStatusResponse>, ApiError> {
+    if request.signed_xdr.trim().is_empty() {
+        return Err(ApiError::Validation("signed_xdr is required".to_string()));
+    }
+
+    let transfer = services
+        .transfer
+        .submit_transfer(
+            &transfer_id.to_string(),
+            &auth_user.user_id,
+            request.signed_xdr,
+            services.job_queue.as_ref(),
+        )
+        .await?;
+
+    Ok(Json(transfer.into()))
 }
 
 pub async fn get_transfer(
@@ -107,10 +296,33 @@ pub async fn get_transfer(
     Err(ApiError::NotFound("Not implemented".to_string()))
 }
 
+#[utoipa::path(
+    get,
+    path = "/transfers/{id}/status",
+    tag = "transfers",
+    params(("id" = Uuid, Path, description = "Transfer id")),
+    responses(
+        (status = 200, description = "Current transfer status", body = TransferStatusResponse),
+        (status = 404, description = "No such transfer for the caller", body = crate::api_error::ErrorResponse),
+    ),
+    security(("bearer_auth" = []))
+)]
 pub async fn get_transfer_status(
-    State(_services): State<Arc<ServiceContainer>>,
-    Path(_transfer_id): Path<Uuid>,
-) -> Result<Json<serde_json::Value>, ApiError> {
-    // Placeholder implementation
-    Err(ApiError::NotFound("Not implemented".to_string()))
+    State(services): State<Arc<ServiceContainer>>,
+    auth_user: AuthenticatedUser,
+    Path(transfer_id): Path<Uuid>,
+) -> Result<Json<TransferStatusResponse>, ApiError> {
+    let transfer = services
+        .transfer
+        .get_transfer(&transfer_id.to_string())
+        .await?;
+
+    if transfer.from_user_id != auth_user.user_id && transfer.to_user_id != auth_user.user_id {
+        return Err(ApiError::NotFound(format!(
+            "Transfer {} not found",
+            transfer_id
+        )));
+    }
+
+    Ok(Json(transfer.into()))
 }
@@ -1,18 +1,21 @@
 use axum::{
-    extract::{Path, State},
-    http::StatusCode,
+    extract::{Path, Query, State},
+    http::{HeaderMap, StatusCode},
     Json,
 };
 use serde::{Deserialize, Serialize};
 use std::sync::Arc;
-use tracing::info;
+use utoipa::{IntoParams, ToSchema};
 use uuid::Uuid;
 
 use crate::{
     api_error::ApiError,
     middleware::auth::AuthenticatedUser,
     service::{
-        anchor_service::{CreateWithdrawalParams, KycStatus, Sep31PayoutParams},
+        anchor_service::{
+            InitiateWithdrawalParams, Sep31PayoutParams, WithdrawalCursor, WithdrawalRecord,
+            TERMINAL_WITHDRAWAL_STATUSES,
+        },
         ServiceContainer,
     },
 };
@@ -21,7 +24,12 @@ use crate::{
 // Request / Response shapes
 // ──────────────────────────────────────────────────────────────────────────────
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, ToSchema)]
+#[schema(example = json!({
+    "destination_address": "GBRPYHIL2CI3FNQ4BXLFMNDLFJUNPU2HY3ZMFSHONUCEOASW7QC7OX2H",
+    "amount": 250000000,
+    "asset": "USDC"
+}))]
 pub struct CreateWithdrawalRequest {
     pub destination_address: String,
     /// Amount in the asset's smallest unit (e.g. stroops for XLM).
@@ -30,6 +38,19 @@ pub struct CreateWithdrawalRequest {
     pub asset: String,
 }
 
+#[derive(Debug, Deserialize, IntoParams)]
+pub struct ListWithdrawalsQuery {
+    pub status: Option<String>,
+    #[serde(default = "default_list_limit")]
+    pub limit: i64,
+    /// Opaque cursor returned as `next_cursor` by the previous page.
+    pub cursor: Option<String>,
+}
+
+fn default_list_limit() -> i64 {
+    20
+}
+
 #[derive(Debug, Deserialize)]
 pub struct InitiateSep31PayoutRequest {
     pub amount: i64,
@@ -37,9 +58,11 @@ pub struct InitiateSep31PayoutRequest {
     pub asset_issuer: Option<String>,
     pub receiver_id: String,
     pub memo: Option<String>,
+    /// `text` (the default), `id` or `hash`.
+    pub memo_type: Option<String>,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 pub struct WithdrawalResponse {
     pub id: String,
     pub user_id: String,
@@ -55,7 +78,31 @@ pub struct WithdrawalResponse {
     pub created_at: chrono::DateTime<chrono::Utc>,
 }
 
-#[derive(Debug, Serialize)]
+impl From<WithdrawalRecord> for WithdrawalResponse {
+    fn from(record: WithdrawalRecord) -> Self {
+        Self {
+            id: record.id,
+            user_id: record.user_id,
+            destination_address: record.destination_address,
+            amount: record.amount,
+            asset: record.asset,
+            status: record.status,
+            anchor_tx_id: record.anchor_tx_id,
+            kyc_status: record.kyc_status,
+            sep24_interactive_url: record.sep24_interactive_url,
+            created_at: record.created_at,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct WithdrawalListResponse {
+    pub withdrawals: Vec<WithdrawalResponse>,
+    /// Pass as `cursor` to fetch the next page; `null` on the last page.
+    pub next_cursor: Option<String>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
 pub struct WithdrawalStatusResponse {
     pub id: String,
     pub status: String,
@@ -72,6 +119,33 @@ pub struct Sep31PayoutInitResponse {
     pub stellar_memo: Option<String>,
 }
 
+/// Header carrying the client's idempotency key for `POST /withdrawals`.
+const IDEMPOTENCY_KEY_HEADER: &str = "Idempotency-Key";
+
+/// Longest idempotency key we accept (matches the column width).
+const MAX_IDEMPOTENCY_KEY_LEN: usize = 255;
+
+/// Read and validate the optional `Idempotency-Key` header.
+fn idempotency_key(headers: &HeaderMap) -> Result<Option<String>, ApiError> {
+    let Some(value) = headers.get(IDEMPOTENCY_KEY_HEADER) else {
+        return Ok(None);
+    };
+
+    let key = value
+        .to_str()
+        .map_err(|_| ApiError::Validation("Idempotency-Key must be visible ASCII".to_string()))?
+        .trim();
+
+    if key.is_empty() || key.len() > MAX_IDEMPOTENCY_KEY_LEN {
+        return Err(ApiError::Validation(format!(
+            "Idempotency-Key must be between 1 and {} characters",
+            MAX_IDEMPOTENCY_KEY_LEN
+        )));
+    }
+
+    Ok(Some(key.to_string()))
+}
+
 // ──────────────────────────────────────────────────────────────────────────────
 // Handlers
 // ──────────────────────────────────────────────────────────────────────────────
@@ -83,12 +157,35 @@ pub struct Sep31PayoutInitResponse {
 /// 2. Obtain a SEP-24 interactive URL + `anchor_tx_id` from the Anchor.
 /// 3. Persist the withdrawal record.
 /// 4. Return the interactive URL to the client → client opens it in a browser/web-view.
+///
+/// An optional `Idempotency-Key` header makes retries safe: a repeat key
+/// from the same user returns the original withdrawal (`200 OK`) without
+/// contacting the Anchor again. Reusing a key for a different amount, asset
+/// or destination is a `409 Conflict`.
+#[utoipa::path(
+    post,
+    path = "/withdrawals",
+    tag = "withdrawals",
+    request_body = CreateWithdrawalRequest,
+    params(
+        ("Idempotency-Key" = Option<String>, Header, description = "Makes retries return the original withdrawal"),
+    ),
+    responses(
+        (status = 201, description = "Withdrawal started", body = WithdrawalResponse),
+        (status = 200, description = "Replay of an earlier request with the same key", body = WithdrawalResponse),
+        (status = 400, description = "Invalid amount, asset or destination", body = crate::api_error::ErrorResponse),
+        (status = 409, description = "Idempotency key already used for a different withdrawal", body = crate::api_error::ErrorResponse),
+    ),
+    security(("bearer_auth" = []))
+)]
 pub async fn create_withdrawal(
     State(services): State<Arc<ServiceContainer>>,
     auth: AuthenticatedUser,
+    headers: HeaderMap,
     Json(request): Json<CreateWithdrawalRequest>,
 ) -> Result<(StatusCode, Json<WithdrawalResponse>), ApiError> {
     let user_id = &auth.user_id;
+    let idempotency_key = idempotency_key(&headers)?;
 
     // Resolve the user's Stellar address from identity service
     let wallet = services
@@ -96,73 +193,85 @@ pub async fn create_withdrawal(
         .get_user_wallet(user_id)
         .await
         .map_err(|_| ApiError::NotFound(format!("No wallet found for user {}", user_id)))?;
-    let stellar_address = &wallet.address;
-
-    // ── Step 1: KYC gate ──────────────────────────────────────────────────────
-    let kyc_status = if services.config.anchor_config.kyc_required {
-        let status = services
-            .anchor
-            .check_kyc_status(user_id, stellar_address)
-            .await?;
-
-        if status != KycStatus::Cleared {
-            return Err(ApiError::Authorization(format!(
-                "KYC check failed: your status is {}. \
-                 Please complete identity verification at the anchor before withdrawing.",
-                status
-            )));
-        }
-        status
-    } else {
-        KycStatus::Cleared
-    };
-
-    // ── Step 2: Obtain SEP-24 interactive URL ─────────────────────────────────
-    let sep24 = services
-        .anchor
-        .get_sep24_interactive_url(user_id, stellar_address, &request.asset, request.amount)
-        .await?;
 
-    info!(
-        user_id,
-        anchor_tx_id = %sep24.anchor_tx_id,
-        "SEP-24 URL obtained — persisting withdrawal"
-    );
+    // Reputation decides which withdrawal limits apply
+    let reputation_tier = services.reputation.withdrawal_tier(&wallet.address).await?;
 
-    // ── Step 3: Persist withdrawal record ─────────────────────────────────────
-    let record = services
+    let outcome = services
         .anchor
-        .create_withdrawal_record(CreateWithdrawalParams {
+        .initiate_withdrawal(InitiateWithdrawalParams {
             user_id: user_id.clone(),
-            destination_address: request.destination_address.clone(),
+            stellar_address: wallet.address,
+            destination_address: request.destination_address,
             amount: request.amount,
-            asset: request.asset.clone(),
-            anchor_tx_id: Some(sep24.anchor_tx_id.clone()),
-            kyc_status,
-            sep24_interactive_url: Some(sep24.url.clone()),
+            asset: request.asset,
+            idempotency_key,
+            reputation_tier,
         })
         .await?;
 
-    Ok((
-        StatusCode::CREATED,
-        Json(WithdrawalResponse {
-            id: record.id,
-            user_id: record.user_id,
-            destination_address: record.destination_address,
-            amount: record.amount,
-            asset: record.asset,
-            status: record.status,
-            anchor_tx_id: record.anchor_tx_id,
-            kyc_status: record.kyc_status,
-            sep24_interactive_url: record.sep24_interactive_url,
-            created_at: record.created_at,
-        }),
-    ))
+    let status = if outcome.replayed {
+        StatusCode::OK
+    } else {
+        StatusCode::CREATED
+    };
+    Ok((status, Json(outcome.record.into())))
+}
+
+/// `GET /withdrawals?status=&limit=&cursor=`
+///
+/// Page through the caller's own withdrawals, newest first.
+#[utoipa::path(
+    get,
+    path = "/withdrawals",
+    tag = "withdrawals",
+    params(ListWithdrawalsQuery),
+    responses(
+        (status = 200, description = "One page of withdrawals", body = WithdrawalListResponse),
+    ),
+    security(("bearer_auth" = []))
+)]
+pub async fn list_withdrawals(
+    State(services): State<Arc<ServiceContainer>>,
+    auth: AuthenticatedUser,
+    Query(params): Query<ListWithdrawalsQuery>,
+) -> Result<Json<WithdrawalListResponse>, ApiError> {
+    let cursor = params
+        .cursor
+        .as_deref()
+        .map(WithdrawalCursor::decode)
+        .transpose()?;
+
+    let page = services
+        .anchor
+        .list_withdrawals(
+            &auth.user_id,
+            params.status.as_deref(),
+            params.limit.clamp(1, 100),
+            cursor.as_ref(),
+        )
+        .await?;
+
+    Ok(Json(WithdrawalListResponse {
+        withdrawals: page.withdrawals.into_iter().map(Into::into).collect(),
+        next_cursor: page.next_cursor.map(|c| c.encode()),
+    }))
 }
 
 /// `GET /withdrawals/:id`
 ///
 /// Fetch the current state of a withdrawal from our database.
+#[utoipa::path(
+    get,
+    path = "/withdrawals/{id}",
+    tag = "withdrawals",
+    params(("id" = Uuid, Path, description = "Withdrawal id")),
+    responses(
+        (status = 200, description = "The withdrawal", body = WithdrawalResponse),
+        (status = 404, description = "No such withdrawal", body = crate::api_error::ErrorResponse),
+    ),
+    security(("bearer_auth" = []))
+)]
 pub async fn get_withdrawal(
     State(services): State<Arc<ServiceContainer>>,
     Path(withdrawal_id): Path<Uuid>,
@@ -172,24 +281,24 @@ pub async fn get_withdrawal(
         .get_withdrawal_by_id(&withdrawal_id.to_string())
         .await?;
 
-    Ok(Json(WithdrawalResponse {
-        id: record.id,
-        user_id: record.user_id,
-        destination_address: record.destination_address,
-        amount: record.amount,
-        asset: record.asset,
-        status: record.status,
-        anchor_tx_id: record.anchor_tx_id,
-        kyc_status: record.kyc_status,
-        sep24_interactive_url: record.sep24_interactive_url,
-        created_at: record.created_at,
-    }))
+    Ok(Json(record.into()))
 }
 
 /// `GET /withdrawals/:id/status`
 ///
 /// Returns our DB status AND a live probe of the Anchor's status so the client
 /// always has the freshest view.
+#[utoipa::path(
+    get,
+    path = "/withdrawals/{id}/status",
+    tag = "withdrawals",
+    params(("id" = Uuid, Path, description = "Withdrawal id")),
+    responses(
+        (status = 200, description = "Stored and live Anchor status", body = WithdrawalStatusResponse),
+        (status = 404, description = "No such withdrawal", body = crate::api_error::ErrorResponse),
+    ),
+    security(("bearer_auth" = []))
+)]
 pub async fn get_withdrawal_status(
     State(services): State<Arc<ServiceContainer>>,
     Path(withdrawal_id): Path<Uuid>,
@@ -205,13 +314,15 @@ pub async fn get_withdrawal_status(
             Ok(status) => {
                 let label = format!("{:?}", status).to_lowercase();
                 // Reconcile: if anchor says completed/failed, sync our DB
-                if matches!(
-                    label.as_str(),
-                    "completed" | "error" | "refunded" | "expired"
-                ) {
+                let internal = status.withdrawal_status();
+                if TERMINAL_WITHDRAWAL_STATUSES.contains(&internal) {
                     let _ = services
                         .anchor
-                        .update_withdrawal_status(&withdrawal_id.to_string(), &label, None)
+                        .apply_withdrawal_status(
+                            &withdrawal_id.to_string(),
+                            internal,
+                            services.job_queue.as_ref(),
+                        )
                         .await;
                 }
                 Some(label)
@@ -243,6 +354,15 @@ pub async fn initiate_sep31_payout(
     auth: AuthenticatedUser,
     Json(request): Json<InitiateSep31PayoutRequest>,
 ) -> Result<Json<Sep31PayoutInitResponse>, ApiError> {
+    let memo_type = match (&request.memo, request.memo_type.as_deref()) {
+        (None, Some(_)) => {
+            return Err(ApiError::Validation(
+                "memo_type was given without a memo".to_string(),
+            ))
+        }
+        (_, memo_type) => memo_type.map(str::parse).transpose()?.unwrap_or_default(),
+    };
+
     let result = services
         .anchor
         .initiate_sep31_payout(&Sep31PayoutParams {
@@ -252,6 +372,7 @@ pub async fn initiate_sep31_payout(
             sender_id: auth.user_id.clone(),
             receiver_id: request.receiver_id,
             memo: request.memo,
+            memo_type,
         })
         .await?;
 
@@ -262,3 +383,42 @@ pub async fn initiate_sep31_payout(
         stellar_memo: result.stellar_memo,
     }))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::http::HeaderValue;
+
+    #[test]
+    fn idempotency_key_is_optional() {
+        assert_eq!(idempotency_key(&HeaderMap::new()).unwrap(), None);
+    }
+
+    #[test]
+    fn idempotency_key_is_trimmed() {
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            IDEMPOTENCY_KEY_HEADER,
+            HeaderValue::from_static(" retry-1 "),
+        );
+
+        assert_eq!(
+            idempotency_key(&headers).unwrap().as_deref(),
+            Some("retry-1")
+        );
+    }
+
+    #[test]
+    fn idempotency_key_rejects_blank_and_oversized_values() {
+        let mut headers = HeaderMap::new();
+        headers.insert(IDEMPOTENCY_KEY_HEADER, HeaderValue::from_static("  "));
+        assert!(idempotency_key(&headers).is_err());
+
+        let long = "k".repeat(MAX_IDEMPOTENCY_KEY_LEN + 1);
+        headers.insert(
+            IDEMPOTENCY_KEY_HEADER,
+            HeaderValue::from_str(&long).unwrap(),
+        );
+        assert!(idempotency_key(&headers).is_err());
+    }
+}
@@ -0,0 +1,19 @@
+use std::time::Duration;
+
+use reqwest::Client;
+
+use crate::config::HttpClientConfig;
+
+/// Build the outbound HTTP client described by `config`.
+///
+/// `reqwest::Client` is a handle to a connection pool, so build it once and
+/// clone it into every service that needs it; clones share connections.
+pub fn build(config: &HttpClientConfig) -> Client {
+    Client::builder()
+        .connect_timeout(Duration::from_millis(config.connect_timeout_ms))
+        .timeout(Duration::from_millis(config.request_timeout_ms))
+        .pool_max_idle_per_host(config.pool_max_idle_per_host)
+        .pool_idle_timeout(Duration::from_secs(config.pool_idle_timeout_seconds))
+        .build()
+        .expect("Failed to build reqwest client")
+}
@@ -1,11 +1,22 @@
+use crate::api_error::ApiError;
+use crate::config::NotificationDigestConfig;
 use crate::job_types::{JobPayload, JobResult, JobType};
-use crate::queue::JobProcessor;
+use crate::models::{CreateAuditLogParams, PaymentStatus};
+use crate::queue::{JobEnqueuer, JobProcessor};
+use crate::service::anchor_service::WithdrawalRecord;
+use crate::service::notification_service::HeldNotification;
+use crate::service::transfer_service::{TransferConfirmation, TransferService};
+use crate::service::tx_submission_service::SubmissionState;
+use crate::service::{AuditService, NotificationService, PaymentService, TxSubmissionService};
 use anyhow::Result;
 use async_trait::async_trait;
 use reqwest::Client;
+use ring::{digest, hmac};
 use serde_json::Value;
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
+use std::sync::{Arc, Mutex};
 use tracing::{debug, error, info};
+use uuid::Uuid;
 
 pub struct EmailProcessor {
     #[allow(dead_code)]
@@ -20,9 +31,11 @@ impl Default for EmailProcessor {
 
 impl EmailProcessor {
     pub fn new() -> Self {
-        Self {
-            http_client: Client::new(),
-        }
+        Self::with_http_client(Client::new())
+    }
+
+    pub fn with_http_client(http_client: Client) -> Self {
+        Self { http_client }
     }
 
     async fn send_email(&self, to: &str, subject: &str, _body: &str) -> Result<()> {
@@ -113,6 +126,42 @@ impl JobProcessor for EmailProcessor {
 pub struct NotificationProcessor {
     #[allow(dead_code)]
     http_client: Client,
+    digest: Option<NotificationDigest>,
+}
+
+/// Where non-urgent notifications are held until their digest goes out.
+struct NotificationDigest {
+    store: Arc<dyn DigestStore>,
+    queue: Arc<dyn JobEnqueuer>,
+    config: NotificationDigestConfig,
+}
+
+impl NotificationDigest {
+    /// Hold the notification for a digest if its type and the user's
+    /// preferences allow it. Returns whether it was held.
+    async fn hold(
+        &self,
+        job: &JobPayload,
+        user_id: &str,
+        message: &str,
+        kind: &str,
+    ) -> Result<bool> {
+        if !self.config.enabled
+            || kind == DIGEST_NOTIFICATION_TYPE
+            || self.config.urgent_types.iter().any(|t| t == kind)
+            || !self.store.wants_digest(user_id).await?
+        {
+            return Ok(false);
+        }
+
+        if self.store.hold(user_id, job.id, kind, message).await? {
+            let window = chrono::Duration::seconds(self.config.window_seconds as i64);
+            self.queue
+                .enqueue(digest_notification_job(user_id, window))
+                .await?;
+        }
+        Ok(true)
+    }
 }
 
 impl Default for NotificationProcessor {
@@ -123,8 +172,31 @@ impl Default for NotificationProcessor {
 
 impl NotificationProcessor {
     pub fn new() -> Self {
+        Self::with_http_client(Client::new())
+    }
+
+    pub fn with_http_client(http_client: Client) -> Self {
+        Self {
+            http_client,
+            digest: None,
+        }
+    }
+
+    /// Batch non-urgent notifications into per-user digests instead of
+    /// sending each one.
+    pub fn with_digest(
+        http_client: Client,
+        store: Arc<dyn DigestStore>,
+        queue: Arc<dyn JobEnqueuer>,
+        config: NotificationDigestConfig,
+    ) -> Self {
         Self {
-            http_client: Client::new(),
+            http_client,
+            digest: Some(NotificationDigest {
+                store,
+                queue,
+                config,
+            }),
         }
     }
 
@@ -170,10 +242,23 @@ impl JobProcessor for NotificationProcessor {
             .and_then(|v| v.as_str())
             .unwrap_or("info");
 
-        match self
-            .send_notification(user_id, message, notification_type)
-            .await
-        {
+        let held = match &self.digest {
+            Some(digest) => digest.hold(job, user_id, message, notification_type).await,
+            None => Ok(false),
+        };
+        let outcome = match held {
+            Ok(true) => {
+                debug!("Notification job {} held for digest", job.id);
+                Ok(())
+            }
+            Ok(false) => {
+                self.send_notification(user_id, message, notification_type)
+                    .await
+            }
+            Err(e) => Err(e),
+        };
+
+        match outcome {
             Ok(_) => {
                 info!("Notification job {} completed successfully", job.id);
                 Ok(JobResult {
@@ -211,9 +296,11 @@ impl Default for SyncProcessor {
 
 impl SyncProcessor {
     pub fn new() -> Self {
-        Self {
-            http_client: Client::new(),
-        }
+        Self::with_http_client(Client::new())
+    }
+
+    pub fn with_http_client(http_client: Client) -> Self {
+        Self { http_client }
     }
 
     async fn perform_sync(&self, sync_type: &str, data: &HashMap<String, Value>) -> Result<()> {
@@ -288,55 +375,213 @@ impl JobProcessor for SyncProcessor {
     }
 }
 
-pub struct BlockchainTxProcessor {
-    #[allow(dead_code)]
-    http_client: Client,
+/// A transfer a `BlockchainTx` job asks to be submitted.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BlockchainTx {
+    /// The payment the transfer settles, or the job id when the payload
+    /// names none.
+    pub reference: String,
+    pub network: String,
+    pub from_address: String,
+    pub to_address: String,
+    pub amount: String,
+    pub memo: Option<String>,
 }
 
-impl Default for BlockchainTxProcessor {
-    fn default() -> Self {
-        Self::new()
+impl BlockchainTx {
+    fn from_job(job: &JobPayload) -> Result<Self> {
+        let tx_data = &job.payload;
+        let field = |name: &str| {
+            tx_data
+                .get(name)
+                .and_then(|v| v.as_str())
+                .map(str::to_string)
+                .ok_or_else(|| anyhow::anyhow!("Missing '{}' in transaction data", name))
+        };
+
+        Ok(Self {
+            reference: tx_data
+                .get("payment_id")
+                .and_then(|v| v.as_str())
+                .map(str::to_string)
+                .unwrap_or_else(|| job.id.to_string()),
+            network: tx_data
+                .get("network")
+                .and_then(|v| v.as_str())
+                .unwrap_or("stellar")
+                .to_string(),
+            from_address: field("from_address")?,
+            to_address: field("to_address")?,
+            amount: field("amount")?,
+            memo: tx_data
+                .get("memo")
+                .and_then(|v| v.as_str())
+                .map(str::to_string),
+        })
     }
-}
 
-impl BlockchainTxProcessor {
-    pub fn new() -> Self {
-        Self {
-            http_client: Client::new(),
+    /// Hex SHA-256 over the reference, network, addresses, amount and memo.
+    /// Retries of one job share it, while two payments with the same
+    /// transfer details still get different keys.
+    pub fn idempotency_key(&self) -> String {
+        let fields = [
+            self.reference.as_str(),
+            self.network.as_str(),
+            &self.from_address,
+            &self.to_address,
+            &self.amount,
+            self.memo.as_deref().unwrap_or(""),
+        ];
+        // Length-prefixed so no field can run into the next
+        let mut ctx = digest::Context::new(&digest::SHA256);
+        for field in fields {
+            ctx.update(&(field.len() as u64).to_be_bytes());
+            ctx.update(field.as_bytes());
         }
+        hex::encode(ctx.finish().as_ref())
     }
+}
 
-    async fn process_transaction(&self, tx_data: &HashMap<String, Value>) -> Result<String> {
-        let network = tx_data
-            .get("network")
-            .and_then(|v| v.as_str())
-            .unwrap_or("stellar");
+/// Where submissions are reserved before they are sent, so a retry can tell
+/// whether an earlier attempt already got as far as the chain.
+#[async_trait]
+pub trait TxSubmissionStore: Send + Sync {
+    async fn reserve(&self, key: &str) -> Result<SubmissionState, ApiError>;
 
-        let from_address = tx_data
-            .get("from_address")
-            .and_then(|v| v.as_str())
-            .ok_or_else(|| anyhow::anyhow!("Missing 'from_address' in transaction data"))?;
+    async fn record_submission(&self, key: &str, tx_hash: &str) -> Result<(), ApiError>;
+}
 
-        let to_address = tx_data
-            .get("to_address")
-            .and_then(|v| v.as_str())
-            .ok_or_else(|| anyhow::anyhow!("Missing 'to_address' in transaction data"))?;
+#[async_trait]
+impl TxSubmissionStore for TxSubmissionService {
+    async fn reserve(&self, key: &str) -> Result<SubmissionState, ApiError> {
+        TxSubmissionService::reserve(self, key).await
+    }
 
-        let amount = tx_data
-            .get("amount")
-            .and_then(|v| v.as_str())
-            .ok_or_else(|| anyhow::anyhow!("Missing 'amount' in transaction data"))?;
+    async fn record_submission(&self, key: &str, tx_hash: &str) -> Result<(), ApiError> {
+        TxSubmissionService::record_submission(self, key, tx_hash).await
+    }
+}
+
+/// In-process [`TxSubmissionStore`], for when there's no database. Retries
+/// are only recognised until the process exits.
+#[derive(Default)]
+pub struct InMemoryTxSubmissionStore {
+    submissions: Mutex<HashMap<String, Option<String>>>,
+}
 
+#[async_trait]
+impl TxSubmissionStore for InMemoryTxSubmissionStore {
+    async fn reserve(&self, key: &str) -> Result<SubmissionState, ApiError> {
+        let mut submissions = self.submissions.lock().unwrap();
+        Ok(match submissions.get(key) {
+            Some(Some(tx_hash)) => SubmissionState::Submitted(tx_hash.clone()),
+            Some(None) => SubmissionState::Unconfirmed,
+            None => {
+                submissions.insert(key.to_string(), None);
+                SubmissionState::New
+            }
+        })
+    }
+
+    async fn record_submission(&self, key: &str, tx_hash: &str) -> Result<(), ApiError> {
+        self.submissions
+            .lock()
+            .unwrap()
+            .insert(key.to_string(), Some(tx_hash.to_string()));
+        Ok(())
+    }
+}
+
+/// Sends transactions to a chain.
+#[async_trait]
+pub trait ChainSubmitter: Send + Sync {
+    /// Hash of a transaction already on-chain for idempotency key `key`.
+    async fn find_submitted(&self, key: &str) -> Result<Option<String>>;
+
+    /// Submit `tx` under `key`, returning its hash.
+    async fn submit(&self, key: &str, tx: &BlockchainTx) -> Result<String>;
+}
+
+/// Stand-in chain that accepts every transaction with a made-up hash.
+#[derive(Default)]
+pub struct SimulatedChain {
+    submitted: Mutex<HashMap<String, String>>,
+}
+
+#[async_trait]
+impl ChainSubmitter for SimulatedChain {
+    async fn find_submitted(&self, key: &str) -> Result<Option<String>> {
+        Ok(self.submitted.lock().unwrap().get(key).cloned())
+    }
+
+    async fn submit(&self, key: &str, tx: &BlockchainTx) -> Result<String> {
         debug!(
             "Processing {} transaction from {} to {} for amount {}",
-            network, from_address, to_address, amount
+            tx.network, tx.from_address, tx.to_address, tx.amount
         );
 
         // Simulate blockchain transaction
         tokio::time::sleep(tokio::time::Duration::from_millis(500)).await;
 
         // For demo purposes, return a mock transaction hash
-        let tx_hash = format!("tx_{}", uuid::Uuid::new_v4().to_string().replace("-", ""));
+        let tx_hash = format!("tx_{}", uuid::Uuid::new_v4().simple());
+        self.submitted
+            .lock()
+            .unwrap()
+            .insert(key.to_string(), tx_hash.clone());
+        Ok(tx_hash)
+    }
+}
+
+/// Submits `BlockchainTx` jobs at most once per idempotency key.
+///
+/// The key is reserved before anything is sent. A retry whose key was
+/// reserved but never recorded as submitted asks the chain for a matching
+/// transaction before sending again, so a job that died mid-submission
+/// isn't paid twice.
+pub struct BlockchainTxProcessor {
+    store: Arc<dyn TxSubmissionStore>,
+    chain: Arc<dyn ChainSubmitter>,
+}
+
+impl Default for BlockchainTxProcessor {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl BlockchainTxProcessor {
+    pub fn new() -> Self {
+        Self::with_store(
+            Arc::new(InMemoryTxSubmissionStore::default()),
+            Arc::new(SimulatedChain::default()),
+        )
+    }
+
+    pub fn with_store(store: Arc<dyn TxSubmissionStore>, chain: Arc<dyn ChainSubmitter>) -> Self {
+        Self { store, chain }
+    }
+
+    async fn process_transaction(&self, job: &JobPayload) -> Result<String> {
+        let tx = BlockchainTx::from_job(job)?;
+        let key = tx.idempotency_key();
+
+        let existing = match self.store.reserve(&key).await? {
+            SubmissionState::Submitted(tx_hash) => Some(tx_hash),
+            SubmissionState::Unconfirmed => self.chain.find_submitted(&key).await?,
+            SubmissionState::New => None,
+        };
+        if let Some(tx_hash) = existing {
+            info!(
+                "Blockchain transaction {} was already submitted. Hash: {}",
+                key, tx_hash
+            );
+            self.store.record_submission(&key, &tx_hash).await?;
+            return Ok(tx_hash);
+        }
+
+        let tx_hash = self.chain.submit(&key, &tx).await?;
+        self.store.record_submission(&key, &tx_hash).await?;
 
         info!(
             "Blockchain transaction processed successfully. Hash: {}",
@@ -349,12 +594,7 @@ impl BlockchainTxProcessor {
 #[async_trait]
 impl JobProcessor for BlockchainTxProcessor {
     async fn process(&self, job: &JobPayload) -> Result<JobResult> {
-        let mut tx_data = HashMap::new();
-        for (key, value) in &job.payload {
-            tx_data.insert(key.clone(), value.clone());
-        }
-
-        match self.process_transaction(&tx_data).await {
+        match self.process_transaction(job).await {
             Ok(tx_hash) => {
                 info!(
                     "Blockchain transaction job {} completed successfully. Hash: {}",
@@ -382,28 +622,722 @@ impl JobProcessor for BlockchainTxProcessor {
     }
 }
 
+/// Destination for audit entries taken off the queue.
+#[async_trait]
+pub trait AuditSink: Send + Sync {
+    async fn write(&self, params: CreateAuditLogParams) -> Result<(), ApiError>;
+}
+
+#[async_trait]
+impl AuditSink for AuditService {
+    async fn write(&self, params: CreateAuditLogParams) -> Result<(), ApiError> {
+        self.create_audit_log(params).await.map(|_| ())
+    }
+}
+
+/// Build an `Audit` job carrying `params` as its payload.
+pub fn audit_job(params: &CreateAuditLogParams) -> Result<JobPayload> {
+    let payload = match serde_json::to_value(params)? {
+        Value::Object(map) => map.into_iter().collect(),
+        _ => anyhow::bail!("Audit params did not serialize to an object"),
+    };
+
+    Ok(JobPayload::new(JobType::Audit, payload, None))
+}
+
+/// Build the `Notification` job telling a user their withdrawal reached a
+/// terminal status.
+pub fn withdrawal_notification_job(record: &WithdrawalRecord) -> JobPayload {
+    let message = match record.status.as_str() {
+        "completed" => format!(
+            "Your withdrawal of {} {} has completed.",
+            record.amount, record.asset
+        ),
+        "refunded" => format!(
+            "Your withdrawal of {} {} was refunded.",
+            record.amount, record.asset
+        ),
+        _ => format!(
+            "Your withdrawal of {} {} has failed.",
+            record.amount, record.asset
+        ),
+    };
+
+    let payload = HashMap::from([
+        ("user_id".to_string(), Value::from(record.user_id.clone())),
+        ("type".to_string(), Value::from("withdrawal")),
+        ("message".to_string(), Value::from(message)),
+        ("withdrawal_id".to_string(), Value::from(record.id.clone())),
+        ("amount".to_string(), Value::from(record.amount)),
+        ("asset".to_string(), Value::from(record.asset.clone())),
+        ("status".to_string(), Value::from(record.status.clone())),
+    ]);
+
+    JobPayload::new(JobType::Notification, payload, None)
+}
+
+/// Notification type of a digest; digests themselves are never held.
+pub const DIGEST_NOTIFICATION_TYPE: &str = "digest";
+
+/// Build the `DigestNotification` job that closes a user's window after
+/// `window`.
+pub fn digest_notification_job(user_id: &str, window: chrono::Duration) -> JobPayload {
+    let payload = HashMap::from([("user_id".to_string(), Value::from(user_id))]);
+    JobPayload::with_delay(JobType::DigestNotification, payload, None, window)
+}
+
+/// One-line summary of held notifications, e.g.
+/// "You have 3 new notifications: 2 payment, 1 transfer".
+pub fn digest_summary(held: &[HeldNotification]) -> String {
+    let mut by_type: BTreeMap<&str, usize> = BTreeMap::new();
+    for notification in held {
+        *by_type.entry(&notification.notification_type).or_default() += 1;
+    }
+    let breakdown: Vec<String> = by_type
+        .iter()
+        .map(|(kind, count)| format!("{} {}", count, kind))
+        .collect();
+
+    format!(
+        "You have {} new notification{}: {}",
+        held.len(),
+        if held.len() == 1 { "" } else { "s" },
+        breakdown.join(", ")
+    )
+}
+
+/// Holds non-urgent notifications between digests.
+#[async_trait]
+pub trait DigestStore: Send + Sync {
+    async fn wants_digest(&self, user_id: &str) -> Result<bool, ApiError>;
+
+    /// Returns whether a digest job must be scheduled for the user.
+    async fn hold(
+        &self,
+        user_id: &str,
+        job_id: uuid::Uuid,
+        notification_type: &str,
+        message: &str,
+    ) -> Result<bool, ApiError>;
+
+    async fn held(&self, user_id: &str) -> Result<Vec<HeldNotification>, ApiError>;
+
+    /// Returns whether more were held meanwhile and need another digest.
+    async fn clear(&self, user_id: &str, delivered: &[String]) -> Result<bool, ApiError>;
+}
+
+#[async_trait]
+impl DigestStore for NotificationService {
+    async fn wants_digest(&self, user_id: &str) -> Result<bool, ApiError> {
+        NotificationService::wants_digest(self, user_id).await
+    }
+
+    async fn hold(
+        &self,
+        user_id: &str,
+        job_id: uuid::Uuid,
+        notification_type: &str,
+        message: &str,
+    ) -> Result<bool, ApiError> {
+        self.hold_for_digest(user_id, job_id, notification_type, message)
+            .await
+    }
+
+    async fn held(&self, user_id: &str) -> Result<Vec<HeldNotification>, ApiError> {
+        self.held_notifications(user_id).await
+    }
+
+    async fn clear(&self, user_id: &str, delivered: &[String]) -> Result<bool, ApiError> {
+        self.clear_digest(user_id, delivered).await
+    }
+}
+
+/// Handles `DigestNotification` jobs: sends everything held for the user as
+/// one `digest` notification and closes their window.
+///
+/// Held notifications are only dropped once the digest is enqueued, so a
+/// failure at any step retries the whole digest rather than losing it.
+pub struct DigestNotificationProcessor {
+    store: Arc<dyn DigestStore>,
+    queue: Arc<dyn JobEnqueuer>,
+    window: chrono::Duration,
+}
+
+impl DigestNotificationProcessor {
+    pub fn new(
+        store: Arc<dyn DigestStore>,
+        queue: Arc<dyn JobEnqueuer>,
+        config: &NotificationDigestConfig,
+    ) -> Self {
+        Self {
+            store,
+            queue,
+            window: chrono::Duration::seconds(config.window_seconds as i64),
+        }
+    }
+
+    async fn deliver(&self, user_id: &str) -> Result<usize> {
+        let held = self.store.held(user_id).await?;
+
+        if !held.is_empty() {
+            let items: Vec<Value> = held
+                .iter()
+                .map(|n| {
+                    serde_json::json!({
+                        "type": n.notification_type,
+                        "message": n.message,
+                        "created_at": n.created_at,
+                    })
+                })
+                .collect();
+            let payload = HashMap::from([
+                ("user_id".to_string(), Value::from(user_id)),
+                ("type".to_string(), Value::from(DIGEST_NOTIFICATION_TYPE)),
+                ("message".to_string(), Value::from(digest_summary(&held))),
+                ("count".to_string(), Value::from(held.len())),
+                ("items".to_string(), Value::from(items)),
+            ]);
+            self.queue
+                .enqueue(JobPayload::new(JobType::Notification, payload, None))
+                .await?;
+        }
+
+        let delivered: Vec<String> = held.iter().map(|n| n.id.clone()).collect();
+        if self.store.clear(user_id, &delivered).await? {
+            self.queue
+                .enqueue(digest_notification_job(user_id, self.window))
+                .await?;
+        }
+        Ok(held.len())
+    }
+}
+
+#[async_trait]
+impl JobProcessor for DigestNotificationProcessor {
+    async fn process(&self, job: &JobPayload) -> Result<JobResult> {
+        let user_id = job
+            .payload
+            .get("user_id")
+            .and_then(Value::as_str)
+            .ok_or_else(|| anyhow::anyhow!("Missing 'user_id' in digest notification job"))?;
+
+        let outcome = self.deliver(user_id).await;
+        match &outcome {
+            Ok(count) => debug!("Digest job {} sent {} notifications", job.id, count),
+            Err(e) => error!("Digest job {} failed: {}", job.id, e),
+        }
+
+        Ok(JobResult {
+            job_id: job.id,
+            success: outcome.is_ok(),
+            error: outcome.err().map(|e| e.to_string()),
+            processed_at: chrono::Utc::now(),
+            attempt: job.retries.unwrap_or(0) + 1,
+        })
+    }
+}
+
+/// Delay between on-chain checks of a submitted transfer.
+pub const TRANSFER_CONFIRMATION_INTERVAL_SECONDS: i64 = 5;
+/// Checks before a transfer whose transaction never lands is marked failed.
+pub const MAX_TRANSFER_CONFIRMATION_POLLS: u64 = 60;
+
+/// Build the `BlockchainTx` job that checks whether a submitted transfer's
+/// transaction has landed. `attempt` counts checks, starting at 1.
+pub fn transfer_confirmation_job(transfer_id: &str, tx_hash: &str, attempt: u64) -> JobPayload {
+    let payload = HashMap::from([
+        ("transfer_id".to_string(), Value::from(transfer_id)),
+        ("tx_hash".to_string(), Value::from(tx_hash)),
+        ("attempt".to_string(), Value::from(attempt)),
+    ]);
+
+    JobPayload::with_delay(
+        JobType::BlockchainTx,
+        payload,
+        None,
+        chrono::Duration::seconds(TRANSFER_CONFIRMATION_INTERVAL_SECONDS),
+    )
+}
+
+/// Settles transfers once their transaction is seen on-chain.
+#[async_trait]
+pub trait TransferConfirmer: Send + Sync {
+    async fn confirm(
+        &self,
+        transfer_id: &str,
+        tx_hash: &str,
+    ) -> Result<TransferConfirmation, ApiError>;
+
+    async fn expire(&self, transfer_id: &str, tx_hash: &str) -> Result<(), ApiError>;
+}
+
+#[async_trait]
+impl TransferConfirmer for TransferService {
+    async fn confirm(
+        &self,
+        transfer_id: &str,
+        tx_hash: &str,
+    ) -> Result<TransferConfirmation, ApiError> {
+        self.confirm_transfer(transfer_id, tx_hash).await
+    }
+
+    async fn expire(&self, transfer_id: &str, tx_hash: &str) -> Result<(), ApiError> {
+        self.expire_transfer(transfer_id, tx_hash).await
+    }
+}
+
+/// Handles `BlockchainTx` jobs, confirming submitted transfers on-chain.
+///
+/// A transfer whose transaction isn't in a ledger yet is checked again by a
+/// follow-up job, up to [`MAX_TRANSFER_CONFIRMATION_POLLS`] times. Jobs that
+/// aren't transfer confirmations go to the generic [`BlockchainTxProcessor`].
+pub struct TransferConfirmationProcessor {
+    confirmer: Arc<dyn TransferConfirmer>,
+    queue: Arc<dyn JobEnqueuer>,
+    fallback: BlockchainTxProcessor,
+}
+
+impl TransferConfirmationProcessor {
+    pub fn new(confirmer: Arc<dyn TransferConfirmer>, queue: Arc<dyn JobEnqueuer>) -> Self {
+        Self {
+            confirmer,
+            queue,
+            fallback: BlockchainTxProcessor::new(),
+        }
+    }
+
+    /// Hand jobs that aren't transfer confirmations to `fallback`.
+    pub fn with_fallback(mut self, fallback: BlockchainTxProcessor) -> Self {
+        self.fallback = fallback;
+        self
+    }
+
+    async fn check(&self, transfer_id: &str, tx_hash: &str, attempt: u64) -> Result<()> {
+        match self.confirmer.confirm(transfer_id, tx_hash).await? {
+            TransferConfirmation::Settled(status) => {
+                debug!("Transfer {} settled as {}", transfer_id, status);
+            }
+            TransferConfirmation::Pending if attempt >= MAX_TRANSFER_CONFIRMATION_POLLS => {
+                self.confirmer.expire(transfer_id, tx_hash).await?;
+            }
+            TransferConfirmation::Pending => {
+                let next = transfer_confirmation_job(transfer_id, tx_hash, attempt + 1);
+                self.queue.enqueue(next).await?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl JobProcessor for TransferConfirmationProcessor {
+    async fn process(&self, job: &JobPayload) -> Result<JobResult> {
+        let Some(transfer_id) = job.payload.get("transfer_id").and_then(Value::as_str) else {
+            return self.fallback.process(job).await;
+        };
+        let tx_hash = job
+            .payload
+            .get("tx_hash")
+            .and_then(Value::as_str)
+            .ok_or_else(|| anyhow::anyhow!("Missing 'tx_hash' in transfer confirmation job"))?;
+        let attempt = job
+            .payload
+            .get("attempt")
+            .and_then(Value::as_u64)
+            .unwrap_or(1);
+
+        let outcome = self.check(transfer_id, tx_hash, attempt).await;
+        if let Err(e) = &outcome {
+            error!("Transfer confirmation job {} failed: {}", job.id, e);
+        }
+
+        Ok(JobResult {
+            job_id: job.id,
+            success: outcome.is_ok(),
+            error: outcome.err().map(|e| e.to_string()),
+            processed_at: chrono::Utc::now(),
+            attempt: job.retries.unwrap_or(0) + 1,
+        })
+    }
+}
+
+/// Build the `BlockchainTx` job that checks whether a submitted payment's
+/// transaction has landed. `attempt` counts checks, starting at 1.
+pub fn payment_confirmation_job(payment_id: Uuid, tx_hash: &str, attempt: u64) -> JobPayload {
+    let payload = HashMap::from([
+        (
+            "payment_id".to_string(),
+            Value::from(payment_id.to_string()),
+        ),
+        ("tx_hash".to_string(), Value::from(tx_hash)),
+        ("attempt".to_string(), Value::from(attempt)),
+    ]);
+
+    JobPayload::with_delay(
+        JobType::BlockchainTx,
+        payload,
+        None,
+        chrono::Duration::seconds(TRANSFER_CONFIRMATION_INTERVAL_SECONDS),
+    )
+}
+
+/// Settles payments once their transaction is seen on-chain. Settling
+/// publishes the status change and queues the merchant's webhook.
+#[async_trait]
+pub trait PaymentConfirmer: Send + Sync {
+    /// The payment's new status, or `None` while it isn't in a ledger yet.
+    async fn confirm(
+        &self,
+        payment_id: Uuid,
+        tx_hash: &str,
+        notifier: &dyn JobEnqueuer,
+    ) -> Result<Option<PaymentStatus>, ApiError>;
+
+    async fn expire(
+        &self,
+        payment_id: Uuid,
+        tx_hash: &str,
+        notifier: &dyn JobEnqueuer,
+    ) -> Result<(), ApiError>;
+}
+
+#[async_trait]
+impl PaymentConfirmer for PaymentService {
+    async fn confirm(
+        &self,
+        payment_id: Uuid,
+        tx_hash: &str,
+        notifier: &dyn JobEnqueuer,
+    ) -> Result<Option<PaymentStatus>, ApiError> {
+        self.confirm_payment(payment_id, tx_hash, notifier).await
+    }
+
+    async fn expire(
+        &self,
+        payment_id: Uuid,
+        tx_hash: &str,
+        notifier: &dyn JobEnqueuer,
+    ) -> Result<(), ApiError> {
+        self.expire_payment(payment_id, tx_hash, notifier).await
+    }
+}
+
+/// Handles `BlockchainTx` jobs confirming submitted payments, polling like
+/// [`TransferConfirmationProcessor`]. Other jobs go to `fallback`.
+pub struct PaymentConfirmationProcessor {
+    confirmer: Arc<dyn PaymentConfirmer>,
+    queue: Arc<dyn JobEnqueuer>,
+    fallback: Box<dyn JobProcessor>,
+}
+
+impl PaymentConfirmationProcessor {
+    pub fn new(
+        confirmer: Arc<dyn PaymentConfirmer>,
+        queue: Arc<dyn JobEnqueuer>,
+        fallback: Box<dyn JobProcessor>,
+    ) -> Self {
+        Self {
+            confirmer,
+            queue,
+            fallback,
+        }
+    }
+
+    async fn check(&self, payment_id: Uuid, tx_hash: &str, attempt: u64) -> Result<()> {
+        let notifier = self.queue.as_ref();
+        match self
+            .confirmer
+            .confirm(payment_id, tx_hash, notifier)
+            .await?
+        {
+            Some(status) => {
+                debug!("Payment {} settled as {}", payment_id, status);
+            }
+            None if attempt >= MAX_TRANSFER_CONFIRMATION_POLLS => {
+                self.confirmer.expire(payment_id, tx_hash, notifier).await?;
+            }
+            None => {
+                let next = payment_confirmation_job(payment_id, tx_hash, attempt + 1);
+                self.queue.enqueue(next).await?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl JobProcessor for PaymentConfirmationProcessor {
+    async fn process(&self, job: &JobPayload) -> Result<JobResult> {
+        let Some(payment_id) = job.payload.get("payment_id").and_then(Value::as_str) else {
+            return self.fallback.process(job).await;
+        };
+        let payment_id = Uuid::parse_str(payment_id)?;
+        let tx_hash = job
+            .payload
+            .get("tx_hash")
+            .and_then(Value::as_str)
+            .ok_or_else(|| anyhow::anyhow!("Missing 'tx_hash' in payment confirmation job"))?;
+        let attempt = job
+            .payload
+            .get("attempt")
+            .and_then(Value::as_u64)
+            .unwrap_or(1);
+
+        let outcome = self.check(payment_id, tx_hash, attempt).await;
+        if let Err(e) = &outcome {
+            error!("Payment confirmation job {} failed: {}", job.id, e);
+        }
+
+        Ok(JobResult {
+            job_id: job.id,
+            success: outcome.is_ok(),
+            error: outcome.err().map(|e| e.to_string()),
+            processed_at: chrono::Utc::now(),
+            attempt: job.retries.unwrap_or(0) + 1,
+        })
+    }
+}
+
+/// Persists audit entries enqueued by the audit middleware.
+///
+/// A failed write is reported as an unsuccessful result so the queue retries
+/// it with backoff and, if the database stays down, moves it to the DLQ.
+pub struct AuditProcessor {
+    sink: Arc<dyn AuditSink>,
+}
+
+impl AuditProcessor {
+    pub fn new(sink: Arc<dyn AuditSink>) -> Self {
+        Self { sink }
+    }
+}
+
+#[async_trait]
+impl JobProcessor for AuditProcessor {
+    async fn process(&self, job: &JobPayload) -> Result<JobResult> {
+        let payload = Value::Object(job.payload.clone().into_iter().collect());
+        let params: CreateAuditLogParams = serde_json::from_value(payload)
+            .map_err(|e| anyhow::anyhow!("Invalid audit job payload: {}", e))?;
+
+        match self.sink.write(params).await {
+            Ok(()) => {
+                debug!("Audit job {} persisted", job.id);
+                Ok(JobResult {
+                    job_id: job.id,
+                    success: true,
+                    error: None,
+                    processed_at: chrono::Utc::now(),
+                    attempt: job.retries.unwrap_or(0) + 1,
+                })
+            }
+            Err(e) => {
+                error!("Audit job {} failed: {}", job.id, e);
+                Ok(JobResult {
+                    job_id: job.id,
+                    success: false,
+                    error: Some(e.to_string()),
+                    processed_at: chrono::Utc::now(),
+                    attempt: job.retries.unwrap_or(0) + 1,
+                })
+            }
+        }
+    }
+}
+
+/// Header carrying the hex HMAC-SHA256 of a merchant webhook's body.
+pub const WEBHOOK_SIGNATURE_HEADER: &str = "X-Zaps-Signature";
+/// How long a merchant endpoint gets to answer before delivery is retried.
+const WEBHOOK_TIMEOUT_SECONDS: u64 = 10;
+
+/// Hex HMAC-SHA256 of `body` under a merchant's webhook secret.
+pub fn sign_webhook(secret: &str, body: &str) -> String {
+    let key = hmac::Key::new(hmac::HMAC_SHA256, secret.as_bytes());
+    hex::encode(hmac::sign(&key, body.as_bytes()).as_ref())
+}
+
+/// Build the `Webhook` job delivering `event` to `url`. The body is signed
+/// here so the secret itself never goes on the queue.
+pub fn merchant_webhook_job(url: &str, secret: &str, event: &Value) -> JobPayload {
+    let body = event.to_string();
+    let payload = HashMap::from([
+        ("url".to_string(), Value::from(url)),
+        (
+            "signature".to_string(),
+            Value::from(sign_webhook(secret, &body)),
+        ),
+        ("body".to_string(), Value::from(body)),
+    ]);
+
+    JobPayload::new(JobType::Webhook, payload, None)
+}
+
+/// Delivers `Webhook` jobs. Anything but a 2xx answer fails the job so the
+/// queue retries it with backoff.
+pub struct WebhookProcessor {
+    http_client: Client,
+}
+
+impl Default for WebhookProcessor {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl WebhookProcessor {
+    pub fn new() -> Self {
+        Self::with_http_client(Client::new())
+    }
+
+    pub fn with_http_client(http_client: Client) -> Self {
+        Self { http_client }
+    }
+
+    async fn deliver(&self, job: &JobPayload) -> Result<()> {
+        let field = |name: &str| {
+            job.payload
+                .get(name)
+                .and_then(Value::as_str)
+                .ok_or_else(|| anyhow::anyhow!("Missing '{}' in webhook job payload", name))
+        };
+        let url = field("url")?;
+
+        let response = self
+            .http_client
+            .post(url)
+            .header(reqwest::header::CONTENT_TYPE, "application/json")
+            .header(WEBHOOK_SIGNATURE_HEADER, field("signature")?)
+            .body(field("body")?.to_string())
+            .timeout(std::time::Duration::from_secs(WEBHOOK_TIMEOUT_SECONDS))
+            .send()
+            .await?;
+
+        if !response.status().is_success() {
+            anyhow::bail!("Webhook {} answered {}", url, response.status());
+        }
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl JobProcessor for WebhookProcessor {
+    async fn process(&self, job: &JobPayload) -> Result<JobResult> {
+        let outcome = self.deliver(job).await;
+        match &outcome {
+            Ok(()) => debug!("Webhook job {} delivered", job.id),
+            Err(e) => error!("Webhook job {} failed: {}", job.id, e),
+        }
+
+        Ok(JobResult {
+            job_id: job.id,
+            success: outcome.is_ok(),
+            error: outcome.err().map(|e| e.to_string()),
+            processed_at: chrono::Utc::now(),
+            attempt: job.retries.unwrap_or(0) + 1,
+        })
+    }
+}
+
+/// Payload field naming the task a `Maintenance` job runs.
+pub const MAINTENANCE_TASK_FIELD: &str = "task";
+
+/// Periodic work run by a `Maintenance` job.
+#[async_trait]
+pub trait MaintenanceTask: Send + Sync {
+    async fn run(&self) -> Result<()>;
+}
+
+/// Runs the [`MaintenanceTask`] a `Maintenance` job names. A failed run is
+/// reported as unsuccessful so the queue retries it with backoff.
+#[derive(Default)]
+pub struct MaintenanceProcessor {
+    tasks: HashMap<String, Arc<dyn MaintenanceTask>>,
+}
+
+impl MaintenanceProcessor {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Run `task` for jobs naming `name`.
+    pub fn with_task(mut self, name: &str, task: Arc<dyn MaintenanceTask>) -> Self {
+        self.tasks.insert(name.to_string(), task);
+        self
+    }
+}
+
+#[async_trait]
+impl JobProcessor for MaintenanceProcessor {
+    async fn process(&self, job: &JobPayload) -> Result<JobResult> {
+        let name = job
+            .payload
+            .get(MAINTENANCE_TASK_FIELD)
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| anyhow::anyhow!("Missing 'task' field in maintenance job payload"))?;
+        let task = self
+            .tasks
+            .get(name)
+            .ok_or_else(|| anyhow::anyhow!("Unknown maintenance task: {}", name))?;
+
+        let outcome = task.run().await;
+        if let Err(e) = &outcome {
+            error!("Maintenance task {} failed: {}", name, e);
+        }
+        Ok(JobResult {
+            job_id: job.id,
+            success: outcome.is_ok(),
+            error: outcome.err().map(|e| e.to_string()),
+            processed_at: chrono::Utc::now(),
+            attempt: job.retries.unwrap_or(0) + 1,
+        })
+    }
+}
+
 pub struct JobProcessorRegistry {
     processors: HashMap<JobType, Box<dyn JobProcessor>>,
 }
 
 impl JobProcessorRegistry {
     pub fn new() -> Self {
+        Self::with_http_client(Client::new())
+    }
+
+    /// The built-in processors, making their HTTP calls through
+    /// `http_client`.
+    pub fn with_http_client(http_client: Client) -> Self {
         let mut processors: HashMap<JobType, Box<dyn JobProcessor>> = HashMap::new();
 
-        processors.insert(JobType::Email, Box::new(EmailProcessor::new()));
+        processors.insert(
+            JobType::Email,
+            Box::new(EmailProcessor::with_http_client(http_client.clone())),
+        );
         processors.insert(
             JobType::Notification,
-            Box::new(NotificationProcessor::new()),
+            Box::new(NotificationProcessor::with_http_client(http_client.clone())),
+        );
+        processors.insert(
+            JobType::Sync,
+            Box::new(SyncProcessor::with_http_client(http_client.clone())),
         );
-        processors.insert(JobType::Sync, Box::new(SyncProcessor::new()));
         processors.insert(
             JobType::BlockchainTx,
             Box::new(BlockchainTxProcessor::new()),
         );
+        processors.insert(
+            JobType::Webhook,
+            Box::new(WebhookProcessor::with_http_client(http_client)),
+        );
 
         Self { processors }
     }
 
+    /// Add or replace the processor for `job_type`.
+    ///
+    /// Used for processors that depend on services, which the built-in
+    /// defaults in `new` cannot construct.
+    pub fn register(&mut self, job_type: JobType, processor: Box<dyn JobProcessor>) {
+        self.processors.insert(job_type, processor);
+    }
+
     pub fn get_processor(&self, job_type: &JobType) -> Option<&dyn JobProcessor> {
         self.processors.get(job_type).map(|p| p.as_ref())
     }
@@ -414,3 +1348,380 @@ impl Default for JobProcessorRegistry {
         Self::new()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Sink that fails a fixed number of writes before accepting them.
+    struct FlakySink {
+        failures_left: Mutex<u32>,
+        written: Mutex<Vec<CreateAuditLogParams>>,
+    }
+
+    impl FlakySink {
+        fn new(failures: u32) -> Self {
+            Self {
+                failures_left: Mutex::new(failures),
+                written: Mutex::new(Vec::new()),
+            }
+        }
+    }
+
+    #[async_trait]
+    impl AuditSink for FlakySink {
+        async fn write(&self, params: CreateAuditLogParams) -> Result<(), ApiError> {
+            let mut failures_left = self.failures_left.lock().unwrap();
+            if *failures_left > 0 {
+                *failures_left -= 1;
+                return Err(ApiError::InternalServerError);
+            }
+            self.written.lock().unwrap().push(params);
+            Ok(())
+        }
+    }
+
+    fn params() -> CreateAuditLogParams {
+        CreateAuditLogParams {
+            actor_id: "user-1".to_string(),
+            action: "create_payments".to_string(),
+            resource: "payments".to_string(),
+            resource_id: None,
+            metadata: Some(serde_json::json!({ "status_code": 201 })),
+            ip_address: Some("127.0.0.1".to_string()),
+            user_agent: None,
+            occurred_at: Some(
+                chrono::DateTime::parse_from_rfc3339("2026-01-02T03:04:05.678901Z")
+                    .unwrap()
+                    .into(),
+            ),
+        }
+    }
+
+    #[tokio::test]
+    async fn audit_job_round_trips_params() {
+        let sink = Arc::new(FlakySink::new(0));
+        let processor = AuditProcessor::new(sink.clone());
+        let job = audit_job(&params()).unwrap();
+
+        assert_eq!(job.job_type, JobType::Audit);
+        let result = processor.process(&job).await.unwrap();
+
+        assert!(result.success);
+        let written = sink.written.lock().unwrap();
+        assert_eq!(written.len(), 1);
+        assert_eq!(written[0].actor_id, "user-1");
+        assert_eq!(written[0].metadata, params().metadata);
+        // The action's time travels with the job, not the time it is written
+        assert_eq!(written[0].occurred_at, params().occurred_at);
+    }
+
+    #[tokio::test]
+    async fn transient_write_failure_is_retried_until_persisted() {
+        let sink = Arc::new(FlakySink::new(2));
+        let processor = AuditProcessor::new(sink.clone());
+        let mut job = audit_job(&params()).unwrap();
+
+        // Mirror the worker: an unsuccessful result sends the job back to the
+        // queue with its retry counter bumped.
+        let mut attempts = 0;
+        loop {
+            let result = processor.process(&job).await.unwrap();
+            attempts += 1;
+            if result.success {
+                break;
+            }
+            assert!(result.error.is_some());
+            job.retries = Some(job.retries.unwrap_or(0) + 1);
+        }
+
+        assert_eq!(attempts, 3);
+        assert_eq!(sink.written.lock().unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn malformed_payload_is_an_error() {
+        let processor = AuditProcessor::new(Arc::new(FlakySink::new(0)));
+        let job = JobPayload::new(JobType::Audit, HashMap::new(), None);
+
+        assert!(processor.process(&job).await.is_err());
+    }
+
+    #[test]
+    fn withdrawal_notification_carries_amount_asset_and_status() {
+        let now = chrono::Utc::now();
+        let record = WithdrawalRecord {
+            id: "w-1".to_string(),
+            user_id: "user-1".to_string(),
+            destination_address: "GDEST".to_string(),
+            amount: 2500,
+            asset: "USDC".to_string(),
+            status: "completed".to_string(),
+            anchor_tx_id: Some("anchor-1".to_string()),
+            kyc_status: "CLEARED".to_string(),
+            sep24_interactive_url: None,
+            idempotency_key: None,
+            created_at: now,
+            updated_at: now,
+        };
+
+        let job = withdrawal_notification_job(&record);
+
+        assert_eq!(job.job_type, JobType::Notification);
+        assert_eq!(job.payload["user_id"], "user-1");
+        assert_eq!(job.payload["withdrawal_id"], "w-1");
+        assert_eq!(job.payload["amount"], 2500);
+        assert_eq!(job.payload["asset"], "USDC");
+        assert_eq!(job.payload["status"], "completed");
+        assert_eq!(
+            job.payload["message"],
+            "Your withdrawal of 2500 USDC has completed."
+        );
+    }
+
+    /// In-memory digest store mirroring the windowing of `NotificationService`.
+    #[derive(Default)]
+    struct MemoryDigestStore {
+        held: Mutex<Vec<(String, HeldNotification)>>,
+        open_windows: Mutex<Vec<String>>,
+        opted_out: Vec<String>,
+    }
+
+    #[async_trait]
+    impl DigestStore for MemoryDigestStore {
+        async fn wants_digest(&self, user_id: &str) -> Result<bool, ApiError> {
+            Ok(!self.opted_out.iter().any(|u| u == user_id))
+        }
+
+        async fn hold(
+            &self,
+            user_id: &str,
+            job_id: uuid::Uuid,
+            notification_type: &str,
+            message: &str,
+        ) -> Result<bool, ApiError> {
+            self.held.lock().unwrap().push((
+                user_id.to_string(),
+                HeldNotification {
+                    id: job_id.to_string(),
+                    notification_type: notification_type.to_string(),
+                    message: message.to_string(),
+                    created_at: chrono::Utc::now(),
+                },
+            ));
+            let mut windows = self.open_windows.lock().unwrap();
+            if windows.iter().any(|u| u == user_id) {
+                return Ok(false);
+            }
+            windows.push(user_id.to_string());
+            Ok(true)
+        }
+
+        async fn held(&self, user_id: &str) -> Result<Vec<HeldNotification>, ApiError> {
+            Ok(self
+                .held
+                .lock()
+                .unwrap()
+                .iter()
+                .filter(|(u, _)| u == user_id)
+                .map(|(_, n)| n.clone())
+                .collect())
+        }
+
+        async fn clear(&self, user_id: &str, delivered: &[String]) -> Result<bool, ApiError> {
+            let mut held = self.held.lock().unwrap();
+            held.retain(|(u, n)| u != user_id || !delivered.contains(&n.id));
+            let remaining = held.iter().any(|(u, _)| u == user_id);
+            if !remaining {
+                self.open_windows.lock().unwrap().retain(|u| u != user_id);
+            }
+            Ok(remaining)
+        }
+    }
+
+    #[derive(Default)]
+    struct RecordingQueue {
+        jobs: Mutex<Vec<JobPayload>>,
+    }
+
+    #[async_trait]
+    impl JobEnqueuer for RecordingQueue {
+        async fn enqueue(&self, job: JobPayload) -> Result<()> {
+            self.jobs.lock().unwrap().push(job);
+            Ok(())
+        }
+    }
+
+    fn notification(user_id: &str, kind: &str, message: &str) -> JobPayload {
+        let payload = HashMap::from([
+            ("user_id".to_string(), Value::from(user_id)),
+            ("type".to_string(), Value::from(kind)),
+            ("message".to_string(), Value::from(message)),
+        ]);
+        JobPayload::new(JobType::Notification, payload, None)
+    }
+
+    fn digest_processors(
+        store: Arc<MemoryDigestStore>,
+        queue: Arc<RecordingQueue>,
+    ) -> (NotificationProcessor, DigestNotificationProcessor) {
+        let config = NotificationDigestConfig::default();
+        (
+            NotificationProcessor::with_digest(
+                Client::new(),
+                store.clone(),
+                queue.clone(),
+                config.clone(),
+            ),
+            DigestNotificationProcessor::new(store, queue, &config),
+        )
+    }
+
+    #[tokio::test]
+    async fn events_within_a_window_collapse_into_one_digest() {
+        let store = Arc::new(MemoryDigestStore::default());
+        let queue = Arc::new(RecordingQueue::default());
+        let (notifications, digests) = digest_processors(store.clone(), queue.clone());
+
+        for message in ["Paid 5 USDC", "Paid 7 USDC", "Received 2 XLM"] {
+            let kind = if message.starts_with("Paid") {
+                "payment"
+            } else {
+                "transfer"
+            };
+            let result = notifications
+                .process(&notification("user-1", kind, message))
+                .await
+                .unwrap();
+            assert!(result.success);
+        }
+
+        // Only the first event opens a window and schedules its digest.
+        let digest_job = {
+            let jobs = queue.jobs.lock().unwrap();
+            assert_eq!(jobs.len(), 1);
+            assert_eq!(jobs[0].job_type, JobType::DigestNotification);
+            assert!(jobs[0].scheduled_at.is_some());
+            jobs[0].clone()
+        };
+        assert_eq!(store.held("user-1").await.unwrap().len(), 3);
+
+        let result = digests.process(&digest_job).await.unwrap();
+        assert!(result.success);
+
+        let jobs = queue.jobs.lock().unwrap();
+        assert_eq!(jobs.len(), 2);
+        let summary = &jobs[1];
+        assert_eq!(summary.job_type, JobType::Notification);
+        assert_eq!(summary.payload["type"], DIGEST_NOTIFICATION_TYPE);
+        assert_eq!(summary.payload["count"], 3);
+        assert_eq!(
+            summary.payload["message"],
+            "You have 3 new notifications: 2 payment, 1 transfer"
+        );
+        assert!(store.held.lock().unwrap().is_empty());
+        assert!(store.open_windows.lock().unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn urgent_and_digest_notifications_bypass_batching() {
+        let store = Arc::new(MemoryDigestStore::default());
+        let queue = Arc::new(RecordingQueue::default());
+        let (notifications, _) = digest_processors(store.clone(), queue.clone());
+
+        notifications
+            .process(&notification("user-1", "payment", "Paid 5 USDC"))
+            .await
+            .unwrap();
+        for kind in ["security", DIGEST_NOTIFICATION_TYPE] {
+            let result = notifications
+                .process(&notification("user-1", kind, "Sent now"))
+                .await
+                .unwrap();
+            assert!(result.success);
+        }
+
+        let held = store.held("user-1").await.unwrap();
+        assert_eq!(held.len(), 1);
+        assert_eq!(held[0].notification_type, "payment");
+        assert_eq!(queue.jobs.lock().unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn opted_out_users_are_not_batched() {
+        let store = Arc::new(MemoryDigestStore {
+            opted_out: vec!["user-2".to_string()],
+            ..Default::default()
+        });
+        let queue = Arc::new(RecordingQueue::default());
+        let (notifications, _) = digest_processors(store.clone(), queue.clone());
+
+        let result = notifications
+            .process(&notification("user-2", "payment", "Paid 5 USDC"))
+            .await
+            .unwrap();
+
+        assert!(result.success);
+        assert!(store.held("user-2").await.unwrap().is_empty());
+        assert!(queue.jobs.lock().unwrap().is_empty());
+    }
+
+    struct CountingTask {
+        runs: Mutex<u32>,
+        fail: bool,
+    }
+
+    #[async_trait]
+    impl MaintenanceTask for CountingTask {
+        async fn run(&self) -> Result<()> {
+            *self.runs.lock().unwrap() += 1;
+            if self.fail {
+                anyhow::bail!("chain unreachable");
+            }
+            Ok(())
+        }
+    }
+
+    fn maintenance_job(task: &str) -> JobPayload {
+        JobPayload::new(
+            JobType::Maintenance,
+            HashMap::from([(MAINTENANCE_TASK_FIELD.to_string(), Value::from(task))]),
+            None,
+        )
+    }
+
+    #[tokio::test]
+    async fn maintenance_jobs_run_the_named_task() {
+        let sync = Arc::new(CountingTask {
+            runs: Mutex::new(0),
+            fail: false,
+        });
+        let purge = Arc::new(CountingTask {
+            runs: Mutex::new(0),
+            fail: true,
+        });
+        let processor = MaintenanceProcessor::new()
+            .with_task("registry_sync", sync.clone())
+            .with_task("profile_purge", purge.clone());
+
+        let result = processor
+            .process(&maintenance_job("registry_sync"))
+            .await
+            .unwrap();
+        assert!(result.success);
+        assert_eq!(*sync.runs.lock().unwrap(), 1);
+
+        let result = processor
+            .process(&maintenance_job("profile_purge"))
+            .await
+            .unwrap();
+        assert!(!result.success);
+        assert_eq!(result.error.as_deref(), Some("chain unreachable"));
+
+        assert!(processor
+            .process(&maintenance_job("unknown"))
+            .await
+            .is_err());
+        assert_eq!(*sync.runs.lock().unwrap(), 1);
+    }
+}
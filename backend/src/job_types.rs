@@ -9,6 +9,28 @@ pub enum JobType {
     Notification,
     Sync,
     BlockchainTx,
+    Audit,
+    /// Delivers a user's held notifications as a single summary.
+    DigestNotification,
+    /// POSTs a signed event to a merchant's webhook URL.
+    Webhook,
+    /// Runs the periodic task named by its `task` field, e.g. the registry
+    /// sync. Fired by the recurring scheduler so only one replica runs it.
+    Maintenance,
+}
+
+impl JobType {
+    /// Every job type, each of which has its own ready queue.
+    pub const ALL: [JobType; 8] = [
+        JobType::Email,
+        JobType::Notification,
+        JobType::Sync,
+        JobType::BlockchainTx,
+        JobType::Audit,
+        JobType::DigestNotification,
+        JobType::Webhook,
+        JobType::Maintenance,
+    ];
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -73,3 +95,13 @@ pub struct DeadLetterJob {
     pub failed_at: chrono::DateTime<chrono::Utc>,
     pub total_attempts: u32,
 }
+
+/// A queue entry that could not be parsed as a [`JobPayload`], moved verbatim
+/// to the dead-letter queue so it stops blocking the queue it was found in.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QuarantinedJob {
+    pub raw: String,
+    pub error: String,
+    pub source_queue: String,
+    pub quarantined_at: chrono::DateTime<chrono::Utc>,
+}
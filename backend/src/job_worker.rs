@@ -1,12 +1,14 @@
 use crate::config::Config;
 use crate::job_processors::JobProcessorRegistry;
-use crate::job_types::{JobPayload, JobType};
-use crate::queue::{JobQueue, QueueConfig};
-use anyhow::{Context, Result};
+use crate::job_types::{JobPayload, JobResult, JobType};
+use crate::queue::{parse_worker_pools, JobProcessor, JobQueue};
+use crate::scheduler::RecurringScheduler;
+use anyhow::Result;
 use std::collections::HashMap;
 use std::sync::Arc;
 use std::time::Duration;
 use tokio::time::interval;
+use tokio_util::sync::CancellationToken;
 use tracing::{debug, error, info, warn};
 
 pub struct JobWorker {
@@ -16,62 +18,71 @@ pub struct JobWorker {
 }
 
 impl JobWorker {
-    pub async fn new(config: Config) -> Result<Self> {
-        let queue_config = QueueConfig {
-            max_retries: config.queue_config.max_retries,
-            visibility_timeout: Duration::from_secs(config.queue_config.visibility_timeout_seconds),
-            backoff_multiplier: config.queue_config.backoff_multiplier,
-            max_backoff: Duration::from_secs(config.queue_config.max_backoff_seconds),
-            dead_letter_max_size: config.queue_config.dead_letter_max_size,
-        };
-
-        let queue = Arc::new(
-            JobQueue::new(&config.queue_config.redis_url, queue_config)
-                .await
-                .context("Failed to create job queue")?,
-        );
-
-        let processor_registry = Arc::new(JobProcessorRegistry::new());
-
-        Ok(Self {
+    /// Create a worker that drains the given queue.
+    ///
+    /// The queue is shared with the `ServiceContainer` so request handlers
+    /// and the workers operate on the same Redis pool.
+    pub fn new(queue: Arc<JobQueue>, processors: JobProcessorRegistry, config: Config) -> Self {
+        Self {
             queue,
-            processor_registry,
+            processor_registry: Arc::new(processors),
             config,
-        })
+        }
     }
 
-    pub async fn start_workers(&self) -> Result<()> {
+    /// Run the worker pool until `shutdown` is cancelled.
+    ///
+    /// `worker_count` shared workers take jobs of any type, and each entry in
+    /// `worker_pools` adds workers that only take their own type.
+    ///
+    /// Workers stop dequeuing once the signal fires — an idle worker after its
+    /// current `dequeue_block_seconds` wait — but always finish the job they
+    /// are currently processing, so nothing is abandoned in the processing
+    /// queue on a clean deploy.
+    ///
+    /// If Redis is unreachable the workers wait for it, retrying in the
+    /// background, rather than failing; the HTTP server keeps serving
+    /// routes that don't need the queue in the meantime.
+    pub async fn start_workers(&self, shutdown: CancellationToken) -> Result<()> {
+        if !wait_for_queue(&shutdown, || self.queue.ping()).await {
+            return Ok(());
+        }
+
+        let pools = parse_worker_pools(&self.config.queue_config.worker_pools)?;
+        let assignments = worker_assignments(self.config.queue_config.worker_count, &pools);
         info!(
-            "Starting {} job workers",
+            "Starting {} job workers ({} shared)",
+            assignments.len(),
             self.config.queue_config.worker_count
         );
 
         let mut handles = vec![];
 
         // Spawn worker tasks
-        for i in 0..self.config.queue_config.worker_count {
+        for (i, job_type) in assignments.into_iter().enumerate() {
             let queue = Arc::clone(&self.queue);
             let processor_registry = Arc::clone(&self.processor_registry);
+            let shutdown = shutdown.clone();
+            let block_for = Duration::from_secs(self.config.queue_config.dequeue_block_seconds);
 
             let handle = tokio::spawn(async move {
                 let worker_id = i + 1;
-                info!("Job worker {} started", worker_id);
-
-                loop {
-                    match Self::process_next_job(&queue, &processor_registry).await {
-                        Ok(Some(())) => {
-                            // Successfully processed a job
-                        }
-                        Ok(None) => {
-                            // No jobs available, wait a bit
-                            tokio::time::sleep(Duration::from_millis(100)).await;
-                        }
-                        Err(e) => {
-                            error!("Worker {} encountered error: {}", worker_id, e);
-                            tokio::time::sleep(Duration::from_secs(1)).await;
-                        }
-                    }
+                match &job_type {
+                    Some(job_type) => info!("Job worker {} started for {:?}", worker_id, job_type),
+                    None => info!("Job worker {} started", worker_id),
                 }
+
+                run_worker_loop(worker_id, shutdown, || {
+                    Self::process_next_job(
+                        &queue,
+                        &processor_registry,
+                        job_type.as_ref(),
+                        block_for,
+                    )
+                })
+                .await;
+
+                info!("Job worker {} stopped", worker_id);
             });
 
             handles.push(handle);
@@ -79,12 +90,16 @@ impl JobWorker {
 
         // Spawn retry queue processor
         let retry_queue = Arc::clone(&self.queue);
+        let retry_shutdown = shutdown.clone();
         let retry_handle = tokio::spawn(async move {
             info!("Retry queue processor started");
             let mut interval = interval(Duration::from_secs(30)); // Check every 30 seconds
 
             loop {
-                interval.tick().await;
+                tokio::select! {
+                    _ = retry_shutdown.cancelled() => break,
+                    _ = interval.tick() => {}
+                }
                 if let Err(e) = retry_queue.process_retry_queue().await {
                     error!("Failed to process retry queue: {}", e);
                 }
@@ -92,16 +107,37 @@ impl JobWorker {
         });
         handles.push(retry_handle);
 
+        // Spawn recurring job scheduler
+        let scheduler = RecurringScheduler::new(self.config.recurring_jobs())?;
+        if !scheduler.is_empty() {
+            let scheduler_queue = Arc::clone(&self.queue);
+            let scheduler_shutdown = shutdown.clone();
+            handles.push(tokio::spawn(async move {
+                info!("Recurring job scheduler started");
+                scheduler
+                    .run(
+                        scheduler_queue.as_ref(),
+                        scheduler_queue.as_ref(),
+                        scheduler_shutdown,
+                    )
+                    .await;
+            }));
+        }
+
         // Spawn stalled job reclaimer
         let reclaim_queue = Arc::clone(&self.queue);
         let reclaim_interval =
             Duration::from_secs(self.config.queue_config.reclaim_interval_seconds);
+        let reclaim_shutdown = shutdown.clone();
         let reclaim_handle = tokio::spawn(async move {
             info!("Stalled job reclaimer started");
             let mut interval = interval(reclaim_interval);
 
             loop {
-                interval.tick().await;
+                tokio::select! {
+                    _ = reclaim_shutdown.cancelled() => break,
+                    _ = interval.tick() => {}
+                }
                 match reclaim_queue.reclaim_stalled_jobs().await {
                     Ok(count) if count > 0 => {
                         info!("Reclaimed {} stalled jobs", count);
@@ -115,12 +151,8 @@ impl JobWorker {
         });
         handles.push(reclaim_handle);
 
-        // Wait for all workers (they run indefinitely)
-        tokio::select! {
-            _ = futures::future::join_all(handles) => {
-                warn!("All job workers have stopped");
-            }
-        }
+        futures::future::join_all(handles).await;
+        info!("All job workers have stopped");
 
         Ok(())
     }
@@ -128,8 +160,10 @@ impl JobWorker {
     async fn process_next_job(
         queue: &JobQueue,
         processor_registry: &JobProcessorRegistry,
+        job_type: Option<&JobType>,
+        block_for: Duration,
     ) -> Result<Option<()>> {
-        let job = match queue.dequeue().await? {
+        let job = match queue.dequeue_blocking_for(job_type, block_for).await? {
             Some(job) => job,
             None => return Ok(None),
         };
@@ -146,7 +180,7 @@ impl JobWorker {
             }
         };
 
-        match processor.process(&job).await {
+        match Self::process_with_heartbeat(queue, processor, &job).await {
             Ok(result) => {
                 if result.success {
                     queue.complete_job(job.id, result).await?;
@@ -166,6 +200,34 @@ impl JobWorker {
         Ok(Some(()))
     }
 
+    /// Run `processor` on `job`, extending the job's heartbeat meanwhile so
+    /// it isn't reclaimed from under us however long it takes.
+    async fn process_with_heartbeat(
+        queue: &JobQueue,
+        processor: &dyn JobProcessor,
+        job: &JobPayload,
+    ) -> Result<JobResult> {
+        let processing = processor.process(job);
+        tokio::pin!(processing);
+        let mut beat = interval(queue.heartbeat_interval(&job.job_type));
+        // The first tick is immediate and the heartbeat was set on dequeue.
+        beat.tick().await;
+
+        loop {
+            tokio::select! {
+                result = &mut processing => return result,
+                _ = beat.tick() => match queue.heartbeat(job).await {
+                    Ok(true) => {}
+                    Ok(false) => warn!(
+                        "Heartbeat of job {} lapsed; it may be reclaimed and run again",
+                        job.id
+                    ),
+                    Err(e) => warn!("Failed to extend heartbeat of job {}: {}", job.id, e),
+                },
+            }
+        }
+    }
+
     pub async fn enqueue_job(
         &self,
         job_type: JobType,
@@ -192,6 +254,93 @@ impl JobWorker {
     }
 }
 
+/// The job type each worker takes, `None` for a shared worker: `shared`
+/// shared workers followed by each dedicated pool.
+fn worker_assignments(shared: usize, pools: &HashMap<JobType, usize>) -> Vec<Option<JobType>> {
+    let mut pools: Vec<_> = pools.iter().collect();
+    // Stable worker ids across restarts.
+    pools.sort_by_key(|(job_type, _)| format!("{:?}", job_type));
+
+    std::iter::repeat_n(None, shared)
+        .chain(
+            pools
+                .into_iter()
+                .flat_map(|(job_type, &count)| std::iter::repeat_n(Some(job_type.clone()), count)),
+        )
+        .collect()
+}
+
+/// Longest wait between two attempts to reach an unavailable queue.
+const MAX_QUEUE_RETRY_DELAY: Duration = Duration::from_secs(30);
+
+/// Retry `ping` with exponential backoff until it succeeds. Returns `false`
+/// if `shutdown` is cancelled first.
+async fn wait_for_queue<F, Fut>(shutdown: &CancellationToken, mut ping: F) -> bool
+where
+    F: FnMut() -> Fut,
+    Fut: std::future::Future<Output = Result<()>>,
+{
+    let mut delay = Duration::from_secs(1);
+    let mut attempts = 0;
+
+    loop {
+        // A ping can wait out the pool's connection timeout, so it races
+        // shutdown too.
+        let result = tokio::select! {
+            _ = shutdown.cancelled() => return false,
+            result = ping() => result,
+        };
+        match result {
+            Ok(()) => {
+                if attempts > 0 {
+                    info!("Job queue is reachable again, starting workers");
+                }
+                return true;
+            }
+            Err(e) => {
+                attempts += 1;
+                warn!(
+                    attempts,
+                    "Job queue unavailable, retrying in {:?}: {:#}", delay, e
+                );
+            }
+        }
+
+        tokio::select! {
+            _ = shutdown.cancelled() => return false,
+            _ = tokio::time::sleep(delay) => {}
+        }
+        delay = (delay * 2).min(MAX_QUEUE_RETRY_DELAY);
+    }
+}
+
+/// Drive a single worker until `shutdown` is cancelled.
+///
+/// The signal is only observed between jobs and while idle, so a job that has
+/// already been dequeued always runs to completion.
+async fn run_worker_loop<F, Fut>(worker_id: usize, shutdown: CancellationToken, mut next_job: F)
+where
+    F: FnMut() -> Fut,
+    Fut: std::future::Future<Output = Result<Option<()>>>,
+{
+    while !shutdown.is_cancelled() {
+        let idle = match next_job().await {
+            Ok(Some(())) => continue,
+            // Nothing arrived while blocked on the queue, wait a bit
+            Ok(None) => Duration::from_millis(100),
+            Err(e) => {
+                error!("Worker {} encountered error: {}", worker_id, e);
+                Duration::from_secs(1)
+            }
+        };
+
+        tokio::select! {
+            _ = shutdown.cancelled() => break,
+            _ = tokio::time::sleep(idle) => {}
+        }
+    }
+}
+
 // HTTP API endpoints for job management
 pub async fn enqueue_email_job(
     worker: Arc<JobWorker>,
@@ -263,3 +412,95 @@ pub async fn enqueue_blockchain_tx_job(
 
     worker.enqueue_job(JobType::BlockchainTx, payload).await
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    #[test]
+    fn dedicated_pools_are_added_to_the_shared_workers() {
+        let pools = HashMap::from([(JobType::Email, 2), (JobType::BlockchainTx, 1)]);
+
+        let assignments = worker_assignments(3, &pools);
+
+        assert_eq!(assignments.len(), 6);
+        assert_eq!(assignments.iter().filter(|t| t.is_none()).count(), 3);
+        let email = Some(JobType::Email);
+        assert_eq!(assignments.iter().filter(|t| **t == email).count(), 2);
+        assert_eq!(
+            assignments[3..],
+            [Some(JobType::BlockchainTx), email.clone(), email]
+        );
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn workers_wait_for_an_unavailable_queue() {
+        let shutdown = CancellationToken::new();
+        let attempts = AtomicUsize::new(0);
+
+        let reachable = wait_for_queue(&shutdown, || async {
+            if attempts.fetch_add(1, Ordering::SeqCst) < 2 {
+                anyhow::bail!("connection refused")
+            }
+            Ok(())
+        })
+        .await;
+
+        assert!(reachable);
+        assert_eq!(attempts.load(Ordering::SeqCst), 3);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn shutdown_stops_waiting_for_the_queue() {
+        let shutdown = CancellationToken::new();
+        shutdown.cancel();
+
+        let reachable =
+            wait_for_queue(&shutdown, || async { anyhow::bail!("connection refused") }).await;
+
+        assert!(!reachable);
+    }
+
+    #[tokio::test]
+    async fn shutdown_stops_idle_worker_promptly() {
+        let shutdown = CancellationToken::new();
+        let worker = tokio::spawn(run_worker_loop(1, shutdown.clone(), || async { Ok(None) }));
+
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        shutdown.cancel();
+
+        tokio::time::timeout(Duration::from_millis(50), worker)
+            .await
+            .expect("worker did not stop after shutdown")
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn shutdown_lets_in_flight_job_finish() {
+        let shutdown = CancellationToken::new();
+        let started = Arc::new(AtomicUsize::new(0));
+        let finished = Arc::new(AtomicUsize::new(0));
+
+        let worker = {
+            let (shutdown, started, finished) =
+                (shutdown.clone(), started.clone(), finished.clone());
+            tokio::spawn(run_worker_loop(1, shutdown, move || {
+                let (started, finished) = (started.clone(), finished.clone());
+                async move {
+                    started.fetch_add(1, Ordering::SeqCst);
+                    tokio::time::sleep(Duration::from_millis(50)).await;
+                    finished.fetch_add(1, Ordering::SeqCst);
+                    Ok(Some(()))
+                }
+            }))
+        };
+
+        tokio::time::sleep(Duration::from_millis(10)).await;
+        shutdown.cancel();
+        worker.await.unwrap();
+
+        assert_eq!(started.load(Ordering::SeqCst), 1);
+        assert_eq!(finished.load(Ordering::SeqCst), 1);
+    }
+}
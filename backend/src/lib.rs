@@ -1,9 +1,12 @@
 pub mod api_error;
 pub mod app;
+pub mod assets;
 pub mod auth;
 pub mod config;
+pub mod custodial;
 pub mod db;
 pub mod http;
+pub mod http_client;
 pub mod job_processors;
 pub mod job_types;
 pub mod job_worker;
@@ -11,6 +14,7 @@ pub mod middleware;
 pub mod models;
 pub mod queue;
 pub mod role;
+pub mod scheduler;
 // pub mod realtime; // TODO: Implement when needed
 pub mod service;
 pub mod storage;
@@ -1,14 +1,19 @@
 use blinks_backend::{app::create_app, config::Config, db, telemetry};
-use std::net::SocketAddr;
-use tracing::info;
+use std::{future::IntoFuture, net::SocketAddr, time::Duration};
+use tokio_util::sync::CancellationToken;
+use tracing::{info, warn};
 
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
     // Initialize telemetry
     telemetry::init_tracing()?;
 
-    // Load configuration
+    // Load configuration and fail fast on values that would break at runtime
     let config = Config::load()?;
+    config.validate()?;
+    if config.stellar_network.accepted_assets.is_empty() {
+        warn!("stellar.accepted_assets is empty: payments may use any issued asset");
+    }
 
     // Initialize database
     let db_pool = db::create_pool(&config.database.url).await?;
@@ -17,18 +22,80 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     db::run_migrations(&config.database.url).await?;
 
     // Create application
-    let app = create_app(db_pool, config.clone()).await?;
+    let shutdown = CancellationToken::new();
+    let (app, workers) = create_app(db_pool, config.clone(), shutdown.clone()).await?;
 
     // Start server
     let addr = SocketAddr::from(([0, 0, 0, 0], config.server.port));
     info!("Starting BLINKS backend server on {}", addr);
 
     let listener = tokio::net::TcpListener::bind(addr).await?;
-    axum::serve(
-        listener,
-        app.into_make_service_with_connect_info::<SocketAddr>(),
-    )
-    .await?;
+    let signal = shutdown.clone();
+    tokio::spawn(async move {
+        shutdown_signal().await;
+        info!("Shutdown signal received, draining in-flight requests and jobs");
+        signal.cancel();
+    });
+    let mut server = tokio::spawn(
+        axum::serve(
+            listener,
+            app.into_make_service_with_connect_info::<SocketAddr>(),
+        )
+        .with_graceful_shutdown(shutdown.clone().cancelled_owned())
+        .into_future(),
+    );
 
+    let served = tokio::select! {
+        result = &mut server => Some(result),
+        _ = shutdown.cancelled() => None,
+    };
+
+    // Requests and workers share one drain window, so a long-lived
+    // connection can't hold the process up past it. Anything still running
+    // then is dropped; unfinished jobs are reclaimed on next start.
+    let timeout = Duration::from_secs(config.server.shutdown_timeout_seconds);
+    let deadline = tokio::time::Instant::now() + timeout;
+    let served = match served {
+        Some(result) => Some(result),
+        None => tokio::time::timeout_at(deadline, &mut server).await.ok(),
+    };
+    match served {
+        Some(result) => result??,
+        None => {
+            warn!("In-flight requests did not drain within {:?}", timeout);
+            server.abort();
+        }
+    }
+
+    if tokio::time::timeout_at(deadline, workers).await.is_err() {
+        warn!("Job workers did not stop within {:?}", timeout);
+    }
+
+    info!("Shutdown complete");
     Ok(())
 }
+
+/// Resolve on SIGINT (Ctrl+C) or, on Unix, SIGTERM.
+async fn shutdown_signal() {
+    let ctrl_c = async {
+        tokio::signal::ctrl_c()
+            .await
+            .expect("Failed to install Ctrl+C handler");
+    };
+
+    #[cfg(unix)]
+    let terminate = async {
+        tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate())
+            .expect("Failed to install SIGTERM handler")
+            .recv()
+            .await;
+    };
+
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<()>();
+
+    tokio::select! {
+        _ = ctrl_c => {},
+        _ = terminate => {},
+    }
+}
@@ -1,17 +1,21 @@
 use axum::{
-    body::Body,
+    body::{Body, HttpBody},
     extract::{Request, State},
-    http::Method,
+    http::{header, Method},
     middleware::Next,
     response::Response,
 };
 use http_body_util::BodyExt;
 use std::sync::Arc;
 
+use crate::job_processors::audit_job;
 use crate::middleware::auth::AuthenticatedUser;
+use crate::models::CreateAuditLogParams;
 use crate::service::ServiceContainer;
 
 const MAX_BODY_SNIPPET_LEN: usize = 2048;
+/// Largest response body the middleware will buffer to take a snippet from.
+const MAX_CAPTURED_RESPONSE_LEN: u64 = 64 * 1024;
 
 pub async fn audit_logging(
     State(services): State<Arc<ServiceContainer>>,
@@ -27,6 +31,9 @@ pub async fn audit_logging(
         return next.run(request).await;
     }
 
+    // Taken before the handler runs: the entry may be written much later
+    let occurred_at = chrono::Utc::now();
+
     let actor_id = request
         .extensions()
         .get::<AuthenticatedUser>()
@@ -78,42 +85,91 @@ pub async fn audit_logging(
 
     let status_code = response.status().as_u16();
 
-    let (res_parts, res_body) = response.into_parts();
-    let res_bytes = res_body
-        .collect()
-        .await
-        .map(|c| c.to_bytes())
-        .unwrap_or_default();
-    let response_snippet = body_snippet(&res_bytes);
-    let response = Response::from_parts(res_parts, Body::from(res_bytes));
+    let response_content_type = response
+        .headers()
+        .get(header::CONTENT_TYPE)
+        .and_then(|ct| ct.to_str().ok())
+        .map(|ct| ct.to_string());
+    let (response, response_snippet) = capture_response_snippet(response).await;
 
     let metadata = serde_json::json!({
         "request_body": request_snippet,
         "response_body": response_snippet,
+        "response_content_type": response_content_type,
         "status_code": status_code,
     });
 
-    let audit_service = services.audit.clone();
+    let params = CreateAuditLogParams {
+        actor_id,
+        action,
+        resource,
+        resource_id,
+        metadata: Some(metadata),
+        ip_address,
+        user_agent,
+        occurred_at: Some(occurred_at),
+    };
+
+    // Persist through the job queue so a failed write is retried rather than
+    // lost. If Redis itself is unavailable, fall back to a direct write.
     tokio::spawn(async move {
-        if let Err(e) = audit_service
-            .create_audit_log(crate::models::CreateAuditLogParams {
-                actor_id,
-                action,
-                resource,
-                resource_id,
-                metadata: Some(metadata),
-                ip_address,
-                user_agent,
-            })
-            .await
-        {
-            tracing::error!("failed to write audit log: {}", e);
+        let enqueued = match audit_job(&params) {
+            Ok(job) => services.job_queue.enqueue(job).await,
+            Err(e) => Err(e),
+        };
+
+        if let Err(e) = enqueued {
+            tracing::warn!("failed to enqueue audit log, writing directly: {}", e);
+            if let Err(e) = services.audit.create_audit_log(params).await {
+                tracing::error!("failed to write audit log: {}", e);
+            }
         }
     });
 
     response
 }
 
+/// Buffer the response body to snippet it, but only when that is cheap.
+///
+/// Bodies are captured only for JSON responses whose size is known up front
+/// and at most `MAX_CAPTURED_RESPONSE_LEN`. Anything else (file downloads,
+/// streams of unknown length) is passed through untouched.
+async fn capture_response_snippet(response: Response) -> (Response, Option<serde_json::Value>) {
+    if !should_capture_response(&response) {
+        return (response, None);
+    }
+
+    let (res_parts, res_body) = response.into_parts();
+    let res_bytes = res_body
+        .collect()
+        .await
+        .map(|c| c.to_bytes())
+        .unwrap_or_default();
+    let snippet = body_snippet(&res_bytes);
+    (
+        Response::from_parts(res_parts, Body::from(res_bytes)),
+        snippet,
+    )
+}
+
+fn should_capture_response(response: &Response) -> bool {
+    let is_json = response
+        .headers()
+        .get(header::CONTENT_TYPE)
+        .and_then(|ct| ct.to_str().ok())
+        .map(|ct| ct.starts_with("application/json"))
+        .unwrap_or(false);
+
+    let declared_len = response
+        .headers()
+        .get(header::CONTENT_LENGTH)
+        .and_then(|len| len.to_str().ok())
+        .and_then(|len| len.parse::<u64>().ok());
+    let len = declared_len.or_else(|| response.body().size_hint().exact());
+
+    is_json && len.is_some_and(|len| len <= MAX_CAPTURED_RESPONSE_LEN)
+}
+
 fn body_snippet(bytes: &[u8]) -> Option<serde_json::Value> {
     if bytes.is_empty() {
         return None;
@@ -190,6 +246,68 @@ fn is_subresource(segment: &str) -> bool {
 mod tests {
     use super::*;
     use axum::http::Method;
+    use std::time::Duration;
+
+    fn response_with(content_type: &str, body: Body) -> Response {
+        Response::builder()
+            .header(header::CONTENT_TYPE, content_type)
+            .body(body)
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn small_json_response_is_snippeted() {
+        let response = response_with("application/json", Body::from(r#"{"id":"p1","token":"x"}"#));
+
+        let (response, snippet) = capture_response_snippet(response).await;
+
+        let snippet = snippet.unwrap();
+        assert_eq!(snippet["id"], "p1");
+        assert_eq!(snippet["token"], "[REDACTED]");
+        let body = response.into_body().collect().await.unwrap().to_bytes();
+        assert_eq!(&body[..], br#"{"id":"p1","token":"x"}"#);
+    }
+
+    #[tokio::test]
+    async fn large_octet_stream_response_is_not_buffered() {
+        let data = vec![7u8; 8 * 1024 * 1024];
+        let response = Response::builder()
+            .header(header::CONTENT_TYPE, "application/octet-stream")
+            .header(header::CONTENT_LENGTH, data.len())
+            .body(Body::from(data))
+            .unwrap();
+
+        let (response, snippet) = capture_response_snippet(response).await;
+
+        assert!(snippet.is_none());
+        assert_eq!(response.body().size_hint().exact(), Some(8 * 1024 * 1024));
+    }
+
+    #[tokio::test]
+    async fn oversized_json_response_is_not_buffered() {
+        let body = format!(r#"{{"data":"{}"}}"#, "a".repeat(100 * 1024));
+        let response = response_with("application/json", Body::from(body));
+
+        let (_, snippet) = capture_response_snippet(response).await;
+
+        assert!(snippet.is_none());
+    }
+
+    #[tokio::test]
+    async fn streaming_response_passes_through_untouched() {
+        // A body that never completes would hang if the middleware collected it.
+        let stream = futures::stream::pending::<Result<axum::body::Bytes, std::io::Error>>();
+        let response = response_with("application/json", Body::from_stream(stream));
+
+        let (_, snippet) = tokio::time::timeout(
+            Duration::from_millis(100),
+            capture_response_snippet(response),
+        )
+        .await
+        .expect("streaming body was buffered");
+
+        assert!(snippet.is_none());
+    }
 
     #[test]
     fn test_parse_post() {
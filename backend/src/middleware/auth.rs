@@ -1,9 +1,12 @@
 use crate::role::Role;
-use crate::{auth, service::ServiceContainer};
+use crate::{
+    auth,
+    service::{ApiKeyService, ServiceContainer},
+};
 use axum::{
     async_trait,
     extract::{FromRequestParts, Request, State},
-    http::{request::Parts, StatusCode},
+    http::{request::Parts, Method, StatusCode},
     middleware::Next,
     response::Response,
 };
@@ -17,12 +20,119 @@ pub struct AuthenticatedUser {
     pub role: Role,
 }
 
-/// Authentication middleware - validates JWT and extracts user info
+/// Merchant resolved from an `X-API-Key` header.
+///
+/// Only present on key-authenticated requests, so handlers can confine a key
+/// to its own merchant's resources.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MerchantPrincipal {
+    pub merchant_id: String,
+}
+
+/// Path prefixes an API key may access. Everything else requires a user JWT.
+pub const MERCHANT_KEY_SCOPES: &[&str] = &["/payments"];
+
+/// Path prefixes served without authentication. Every other route requires
+/// a JWT or API key, so a route is only public once it is listed here (or,
+/// for signed file downloads, matches [`is_signed_file_download`]).
+pub const PUBLIC_ROUTES: &[&str] = &[
+    "/anchor/webhook",
+    "/auth/login",
+    "/auth/register",
+    "/auth/refresh",
+    "/user/register",
+    "/health",
+    "/openapi.json",
+    "/docs",
+    // Guarded by its own token and IP allowlist
+    "/metrics",
+];
+
+/// Whether `path` is `prefix` or lies beneath it.
+fn matches_prefix(path: &str, prefix: &str) -> bool {
+    path.strip_prefix(prefix)
+        .is_some_and(|rest| rest.is_empty() || rest.starts_with('/'))
+}
+
+/// Whether a request path is open to merchant API keys.
+pub fn is_merchant_scoped(path: &str) -> bool {
+    MERCHANT_KEY_SCOPES
+        .iter()
+        .any(|scope| matches_prefix(path, scope))
+}
+
+/// Whether a request path is exempt from authentication.
+pub fn is_public_route(path: &str) -> bool {
+    PUBLIC_ROUTES
+        .iter()
+        .any(|route| matches_prefix(path, route))
+}
+
+/// Whether a request is a file download, `GET /files/:id`. Those are
+/// authorized by the signed URL itself, which the handler checks, so they
+/// can be shared with clients that hold no token.
+pub fn is_signed_file_download(method: &Method, path: &str) -> bool {
+    method == Method::GET
+        && path
+            .strip_prefix("/files/")
+            .is_some_and(|id| !id.is_empty() && !id.contains('/'))
+}
+
+/// Resolve an `X-API-Key` to the merchant identity for a request to `path`.
+///
+/// Unknown or revoked keys are `401`; a valid key outside
+/// [`MERCHANT_KEY_SCOPES`] is `403`.
+pub async fn authenticate_api_key(
+    api_keys: &ApiKeyService,
+    api_key: &str,
+    path: &str,
+) -> Result<(AuthenticatedUser, MerchantPrincipal), StatusCode> {
+    let merchant_id = match api_keys.authenticate(api_key).await {
+        Ok(Some(merchant_id)) => merchant_id,
+        Ok(None) => return Err(StatusCode::UNAUTHORIZED),
+        Err(_) => return Err(StatusCode::INTERNAL_SERVER_ERROR),
+    };
+
+    if !is_merchant_scoped(path) {
+        return Err(StatusCode::FORBIDDEN);
+    }
+
+    Ok((
+        AuthenticatedUser {
+            user_id: merchant_id.clone(),
+            role: Role::Merchant,
+        },
+        MerchantPrincipal { merchant_id },
+    ))
+}
+
+/// Authentication middleware - validates a JWT or merchant API key and
+/// extracts the caller's identity. Requests to [`PUBLIC_ROUTES`] pass
+/// through untouched.
 pub async fn authenticate(
     State(services): State<Arc<ServiceContainer>>,
     mut req: Request,
     next: Next,
 ) -> Result<Response, StatusCode> {
+    if is_public_route(req.uri().path()) || is_signed_file_download(req.method(), req.uri().path())
+    {
+        return Ok(next.run(req).await);
+    }
+
+    let api_key = req
+        .headers()
+        .get("x-api-key")
+        .and_then(|header| header.to_str().ok())
+        .map(str::to_string);
+
+    if let Some(api_key) = api_key {
+        let (auth_user, principal) =
+            authenticate_api_key(&services.api_keys, &api_key, req.uri().path()).await?;
+        req.extensions_mut().insert(auth_user);
+        req.extensions_mut().insert(principal);
+        return Ok(next.run(req).await);
+    }
+
     let auth_header = req
         .headers()
         .get("authorization")
@@ -34,9 +144,11 @@ pub async fn authenticate(
         None => return Err(StatusCode::UNAUTHORIZED),
     };
 
-    // Validate as access token using secret from config
-    match auth::validate_access_token(token, &services.config.jwt.secret) {
+    // Validate as access token using secret, issuer and audience from config
+    let jwt = &services.config.jwt;
+    match auth::validate_access_token(token, &jwt.secret, jwt.into()) {
         Ok(claims) => {
+            record_last_seen(&services, &claims.sub);
             let auth_user = AuthenticatedUser {
                 user_id: claims.sub,
                 role: claims.role,
@@ -48,6 +160,22 @@ pub async fn authenticate(
     }
 }
 
+/// Update the user's `last_seen_at` off the request path; a failed write
+/// only costs accuracy, so it is logged rather than failing the request.
+/// Users already written within the throttle window are skipped in-process.
+fn record_last_seen(services: &ServiceContainer, user_id: &str) {
+    if !services.identity.last_seen_due(user_id) {
+        return;
+    }
+    let identity = services.identity.clone();
+    let user_id = user_id.to_string();
+    tokio::spawn(async move {
+        if let Err(e) = identity.touch_last_seen(&user_id).await {
+            tracing::warn!(user_id, error = %e, "Failed to record last seen time");
+        }
+    });
+}
+
 /// Axum extractor for getting the authenticated user from request
 #[async_trait]
 impl<S> FromRequestParts<S> for AuthenticatedUser
@@ -74,3 +202,49 @@ pub fn get_authenticated_user(req: &Request) -> Option<AuthenticatedUser> {
 pub fn get_user_id_from_request(req: &Request) -> Option<String> {
     get_authenticated_user(req).map(|u| u.user_id)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn merchant_scope_matches_whole_path_segments() {
+        assert!(is_merchant_scoped("/payments"));
+        assert!(is_merchant_scoped("/payments/payments/123"));
+        assert!(is_merchant_scoped("/payments/qr/generate"));
+
+        assert!(!is_merchant_scoped("/paymentsx"));
+        assert!(!is_merchant_scoped("/transfers/transfers"));
+        assert!(!is_merchant_scoped("/admin/dashboard/stats"));
+        assert!(!is_merchant_scoped("/"));
+    }
+
+    #[test]
+    fn only_listed_routes_are_public() {
+        assert!(is_public_route("/auth/login"));
+        assert!(is_public_route("/health/ready"));
+        assert!(is_public_route("/metrics/json"));
+        assert!(is_public_route("/anchor/webhook"));
+
+        assert!(!is_public_route("/auth/sessions"));
+        assert!(!is_public_route("/auth/loginx"));
+        assert!(!is_public_route("/healthz"));
+        assert!(!is_public_route("/admin/users/alice"));
+        assert!(!is_public_route("/reports"));
+        assert!(!is_public_route("/"));
+    }
+
+    #[test]
+    fn only_file_downloads_skip_authentication_for_their_signature() {
+        assert!(is_signed_file_download(&Method::GET, "/files/abc-123"));
+
+        assert!(!is_signed_file_download(
+            &Method::GET,
+            "/files/abc-123/meta"
+        ));
+        assert!(!is_signed_file_download(&Method::DELETE, "/files/abc-123"));
+        assert!(!is_signed_file_download(&Method::POST, "/files/upload"));
+        assert!(!is_signed_file_download(&Method::GET, "/files/"));
+        assert!(!is_signed_file_download(&Method::GET, "/filesx/abc"));
+    }
+}
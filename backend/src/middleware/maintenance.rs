@@ -0,0 +1,299 @@
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use async_trait::async_trait;
+use axum::{
+    extract::{Request, State},
+    http::{header, HeaderValue, Method, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+    Json,
+};
+use serde_json::json;
+use tracing::warn;
+
+use crate::{config::MaintenanceConfig, queue::JobQueue};
+
+/// Redis key that switches maintenance mode on while it exists.
+pub const MAINTENANCE_KEY: &str = "zaps:maintenance";
+
+/// How long a read of the runtime flag is reused before Redis is asked again.
+const FLAG_CACHE_TTL: Duration = Duration::from_secs(5);
+
+/// Runtime maintenance switch, checked in addition to the config flag.
+#[async_trait]
+pub trait MaintenanceFlag: Send + Sync {
+    async fn is_set(&self) -> anyhow::Result<bool>;
+}
+
+#[async_trait]
+impl MaintenanceFlag for JobQueue {
+    async fn is_set(&self) -> anyhow::Result<bool> {
+        self.key_exists(MAINTENANCE_KEY).await
+    }
+}
+
+#[derive(Clone)]
+pub struct MaintenanceState {
+    config: MaintenanceConfig,
+    flag: Arc<dyn MaintenanceFlag>,
+    lookup_timeout: Duration,
+    /// Last flag read and when it was made.
+    cached: Arc<Mutex<Option<(Instant, bool)>>>,
+}
+
+impl MaintenanceState {
+    /// A flag lookup taking longer than `lookup_timeout` counts as unset.
+    pub fn new(
+        config: MaintenanceConfig,
+        flag: Arc<dyn MaintenanceFlag>,
+        lookup_timeout: Duration,
+    ) -> Self {
+        Self {
+            config,
+            flag,
+            lookup_timeout,
+            cached: Arc::new(Mutex::new(None)),
+        }
+    }
+
+    async fn is_active(&self) -> bool {
+        if self.config.enabled {
+            return true;
+        }
+        if let Some((read_at, set)) = *self.cached.lock().unwrap() {
+            if read_at.elapsed() < FLAG_CACHE_TTL {
+                return set;
+            }
+        }
+
+        // An unreachable or stalled Redis shouldn't turn every write into a
+        // 503, nor hold it until the request timeout.
+        let set = match tokio::time::timeout(self.lookup_timeout, self.flag.is_set()).await {
+            Ok(Ok(set)) => set,
+            Ok(Err(e)) => {
+                warn!(error = %e, "Failed to read maintenance flag");
+                false
+            }
+            Err(_) => {
+                warn!(
+                    timeout_ms = self.lookup_timeout.as_millis() as u64,
+                    "Timed out reading maintenance flag"
+                );
+                false
+            }
+        };
+        *self.cached.lock().unwrap() = Some((Instant::now(), set));
+        set
+    }
+}
+
+/// Writes that stay open during maintenance: signing in and refreshing a
+/// session, so reads keep working, and anchor webhooks, which would
+/// otherwise be retried against us until the anchor gives up.
+const MAINTENANCE_EXEMPT_PATHS: &[&str] = &["/auth/login", "/auth/refresh", "/anchor/webhook"];
+
+fn is_read(method: &Method) -> bool {
+    matches!(*method, Method::GET | Method::HEAD | Method::OPTIONS)
+}
+
+fn is_exempt(path: &str) -> bool {
+    MAINTENANCE_EXEMPT_PATHS.contains(&path)
+}
+
+/// Reject writes with `503 Service Unavailable` while maintenance mode is on.
+///
+/// Reads, including the health probes, and the writes in
+/// `MAINTENANCE_EXEMPT_PATHS` are always let through.
+pub async fn maintenance_mode(
+    State(state): State<MaintenanceState>,
+    request: Request,
+    next: Next,
+) -> Response {
+    if is_read(request.method()) || is_exempt(request.uri().path()) || !state.is_active().await {
+        return next.run(request).await;
+    }
+
+    let mut response = (
+        StatusCode::SERVICE_UNAVAILABLE,
+        Json(json!({
+            "error": "Service unavailable: down for maintenance",
+            "message": "Down for maintenance, please retry later",
+            "code": "MAINTENANCE",
+        })),
+    )
+        .into_response();
+    response.headers_mut().insert(
+        header::RETRY_AFTER,
+        HeaderValue::from(state.config.retry_after_seconds),
+    );
+    response
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{
+        body::Body,
+        middleware,
+        routing::{get, post},
+        Router,
+    };
+    use tower::util::ServiceExt;
+
+    struct StaticFlag(bool);
+
+    #[async_trait]
+    impl MaintenanceFlag for StaticFlag {
+        async fn is_set(&self) -> anyhow::Result<bool> {
+            Ok(self.0)
+        }
+    }
+
+    struct BrokenFlag;
+
+    /// A lookup that never completes, like a Redis pool waiting on a dead
+    /// server.
+    struct HangingFlag;
+
+    #[async_trait]
+    impl MaintenanceFlag for HangingFlag {
+        async fn is_set(&self) -> anyhow::Result<bool> {
+            std::future::pending().await
+        }
+    }
+
+    struct CountingFlag(std::sync::atomic::AtomicUsize);
+
+    #[async_trait]
+    impl MaintenanceFlag for CountingFlag {
+        async fn is_set(&self) -> anyhow::Result<bool> {
+            self.0.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+            Ok(false)
+        }
+    }
+
+    #[async_trait]
+    impl MaintenanceFlag for BrokenFlag {
+        async fn is_set(&self) -> anyhow::Result<bool> {
+            Err(anyhow::anyhow!("redis down"))
+        }
+    }
+
+    fn app(enabled: bool, flag: Arc<dyn MaintenanceFlag>) -> Router {
+        let state = MaintenanceState::new(
+            MaintenanceConfig {
+                enabled,
+                retry_after_seconds: 60,
+            },
+            flag,
+            Duration::from_millis(50),
+        );
+        Router::new()
+            .route("/health", get(|| async { "ok" }))
+            .route(
+                "/payments",
+                get(|| async { "list" }).post(|| async { "created" }),
+            )
+            .route("/profiles/me", axum::routing::delete(|| async { "gone" }))
+            .route("/auth/login", post(|| async { "token" }))
+            .route("/auth/refresh", post(|| async { "token" }))
+            .route("/auth/logout", post(|| async { "bye" }))
+            .route("/anchor/webhook", post(|| async { "ack" }))
+            .layer(middleware::from_fn_with_state(state, maintenance_mode))
+    }
+
+    async fn send(app: Router, method: Method, uri: &str) -> Response {
+        let request = Request::builder()
+            .method(method)
+            .uri(uri)
+            .body(Body::empty())
+            .unwrap();
+        app.oneshot(request).await.unwrap()
+    }
+
+    #[tokio::test]
+    async fn writes_are_blocked_when_enabled() {
+        for (method, uri) in [
+            (Method::POST, "/payments"),
+            (Method::DELETE, "/profiles/me"),
+        ] {
+            let response = send(app(true, Arc::new(StaticFlag(false))), method, uri).await;
+            assert_eq!(response.status(), StatusCode::SERVICE_UNAVAILABLE);
+            assert_eq!(response.headers()[header::RETRY_AFTER], "60");
+        }
+    }
+
+    #[tokio::test]
+    async fn reads_pass_when_enabled() {
+        for uri in ["/health", "/payments"] {
+            let response = send(app(true, Arc::new(StaticFlag(false))), Method::GET, uri).await;
+            assert_eq!(response.status(), StatusCode::OK, "{}", uri);
+        }
+    }
+
+    #[tokio::test]
+    async fn sign_in_and_anchor_webhooks_pass_when_enabled() {
+        for uri in ["/auth/login", "/auth/refresh", "/anchor/webhook"] {
+            let response = send(app(true, Arc::new(StaticFlag(false))), Method::POST, uri).await;
+            assert_eq!(response.status(), StatusCode::OK, "{}", uri);
+        }
+
+        let response = send(
+            app(true, Arc::new(StaticFlag(false))),
+            Method::POST,
+            "/auth/logout",
+        )
+        .await;
+        assert_eq!(response.status(), StatusCode::SERVICE_UNAVAILABLE);
+    }
+
+    #[tokio::test]
+    async fn redis_flag_enables_maintenance() {
+        let response = send(
+            app(false, Arc::new(StaticFlag(true))),
+            Method::POST,
+            "/payments",
+        )
+        .await;
+        assert_eq!(response.status(), StatusCode::SERVICE_UNAVAILABLE);
+    }
+
+    #[tokio::test]
+    async fn everything_passes_when_off() {
+        for (method, uri) in [
+            (Method::GET, "/payments"),
+            (Method::POST, "/payments"),
+            (Method::DELETE, "/profiles/me"),
+        ] {
+            let response = send(app(false, Arc::new(StaticFlag(false))), method, uri).await;
+            assert_eq!(response.status(), StatusCode::OK);
+        }
+
+        // A failing flag lookup doesn't block writes.
+        let response = send(app(false, Arc::new(BrokenFlag)), Method::POST, "/payments").await;
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn stalled_flag_lookup_lets_writes_through() {
+        let response = tokio::time::timeout(
+            Duration::from_secs(1),
+            send(app(false, Arc::new(HangingFlag)), Method::POST, "/payments"),
+        )
+        .await
+        .expect("write waited on the flag");
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn flag_reads_are_cached() {
+        let flag = Arc::new(CountingFlag(Default::default()));
+        let router = app(false, flag.clone());
+        for _ in 0..3 {
+            let response = send(router.clone(), Method::POST, "/payments").await;
+            assert_eq!(response.status(), StatusCode::OK);
+        }
+        assert_eq!(flag.0.load(std::sync::atomic::Ordering::SeqCst), 1);
+    }
+}
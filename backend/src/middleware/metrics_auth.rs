@@ -0,0 +1,140 @@
+use std::net::{IpAddr, SocketAddr};
+
+use axum::{
+    extract::{ConnectInfo, Request, State},
+    http::header,
+    middleware::Next,
+    response::Response,
+};
+
+use crate::{api_error::ApiError, config::MetricsConfig};
+
+/// Guard for the metrics routes: accept the configured bearer token or an
+/// allowlisted client IP, or anything at all in `dev_mode`.
+pub async fn metrics_auth(
+    State(config): State<MetricsConfig>,
+    request: Request,
+    next: Next,
+) -> Result<Response, ApiError> {
+    if config.dev_mode || is_authorized(&config, &request) {
+        return Ok(next.run(request).await);
+    }
+
+    Err(ApiError::Authentication(
+        "Metrics require a valid scrape token or an allowlisted address".to_string(),
+    ))
+}
+
+fn is_authorized(config: &MetricsConfig, request: &Request) -> bool {
+    let token_ok = match (&config.bearer_token, bearer_token(request)) {
+        (Some(expected), Some(presented)) => {
+            constant_time_eq(expected.as_bytes(), presented.as_bytes())
+        }
+        _ => false,
+    };
+
+    token_ok || client_ip(request).is_some_and(|ip| config.allowed_ips.contains(&ip))
+}
+
+fn bearer_token(request: &Request) -> Option<&str> {
+    request
+        .headers()
+        .get(header::AUTHORIZATION)?
+        .to_str()
+        .ok()?
+        .strip_prefix("Bearer ")
+}
+
+fn client_ip(request: &Request) -> Option<IpAddr> {
+    let ConnectInfo(addr) = request.extensions().get::<ConnectInfo<SocketAddr>>()?;
+    // Treat IPv4-mapped IPv6 peers as their IPv4 address.
+    Some(addr.ip().to_canonical())
+}
+
+pub(crate) fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{body::Body, http::StatusCode, middleware, routing::get, Router};
+    use tower::util::ServiceExt;
+
+    fn app(config: MetricsConfig) -> Router {
+        Router::new()
+            .route("/metrics", get(|| async { "up 1" }))
+            .layer(middleware::from_fn_with_state(config, metrics_auth))
+    }
+
+    fn scrape(ip: [u8; 4], token: Option<&str>) -> Request {
+        let mut builder = Request::builder()
+            .uri("/metrics")
+            .extension(ConnectInfo(SocketAddr::from((ip, 9090))));
+        if let Some(token) = token {
+            builder = builder.header(header::AUTHORIZATION, format!("Bearer {}", token));
+        }
+        builder.body(Body::empty()).unwrap()
+    }
+
+    fn protected() -> MetricsConfig {
+        MetricsConfig {
+            dev_mode: false,
+            bearer_token: Some("scrape-secret".to_string()),
+            allowed_ips: vec!["10.0.0.5".parse().unwrap()],
+        }
+    }
+
+    async fn status(config: MetricsConfig, request: Request) -> StatusCode {
+        app(config).oneshot(request).await.unwrap().status()
+    }
+
+    #[tokio::test]
+    async fn unauthenticated_scrape_is_rejected_when_protected() {
+        assert_eq!(
+            status(protected(), scrape([203, 0, 113, 7], None)).await,
+            StatusCode::UNAUTHORIZED
+        );
+        assert_eq!(
+            status(protected(), scrape([203, 0, 113, 7], Some("wrong"))).await,
+            StatusCode::UNAUTHORIZED
+        );
+    }
+
+    #[tokio::test]
+    async fn scrape_is_allowed_when_protection_is_disabled() {
+        let config = MetricsConfig {
+            dev_mode: true,
+            ..protected()
+        };
+
+        assert_eq!(
+            status(config, scrape([203, 0, 113, 7], None)).await,
+            StatusCode::OK
+        );
+    }
+
+    #[tokio::test]
+    async fn bearer_token_grants_access() {
+        assert_eq!(
+            status(protected(), scrape([203, 0, 113, 7], Some("scrape-secret"))).await,
+            StatusCode::OK
+        );
+    }
+
+    #[tokio::test]
+    async fn allowlisted_ip_grants_access() {
+        assert_eq!(
+            status(protected(), scrape([10, 0, 0, 5], None)).await,
+            StatusCode::OK
+        );
+    }
+
+    #[tokio::test]
+    async fn nothing_configured_rejects_everyone() {
+        assert_eq!(
+            status(MetricsConfig::default(), scrape([127, 0, 0, 1], None)).await,
+            StatusCode::UNAUTHORIZED
+        );
+    }
+}
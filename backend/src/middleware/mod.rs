@@ -1,12 +1,18 @@
 pub mod audit;
 pub mod auth;
+pub mod maintenance;
 pub mod metrics;
+pub mod metrics_auth;
 pub mod rate_limit;
 pub mod request_id;
 pub mod role_guard;
+pub mod timeout;
 
 pub use audit::*;
 pub use auth::*;
+pub use maintenance::*;
 pub use metrics::*;
+pub use metrics_auth::*;
 pub use request_id::*;
 pub use role_guard::*;
+pub use timeout::*;
@@ -1,6 +1,8 @@
 use crate::api_error::ApiError;
-use crate::models::RateLimitScope;
-use crate::service::ServiceContainer;
+use crate::job_processors::audit_job;
+use crate::middleware::metrics_auth::constant_time_eq;
+use crate::models::{CreateAuditLogParams, RateLimitConfig, RateLimitScope};
+use crate::service::{RateLimitService, ServiceContainer};
 use axum::{
     extract::{ConnectInfo, Request, State},
     middleware::Next,
@@ -9,6 +11,9 @@ use axum::{
 use std::net::SocketAddr;
 use std::sync::Arc;
 
+/// Header internal services send their `rate_limit.internal_tokens` entry in.
+pub const INTERNAL_TOKEN_HEADER: &str = "X-Internal-Token";
+
 pub async fn rate_limit(
     State(services): State<Arc<ServiceContainer>>,
     ConnectInfo(addr): ConnectInfo<SocketAddr>,
@@ -17,6 +22,30 @@ pub async fn rate_limit(
 ) -> Result<Response, ApiError> {
     let config = &services.config.rate_limit;
 
+    if let Some(service) = admit(&services.rate_limit, config, &request, addr)? {
+        tracing::info!(
+            service,
+            path = %request.uri().path(),
+            "Internal caller bypassed rate limiting"
+        );
+        record_bypass(services.clone(), service, &request, addr);
+    }
+
+    Ok(next.run(request).await)
+}
+
+/// Let `request` through or throttle it. Returns the internal service it
+/// was exempted as, if any; everyone else is counted against the limiter.
+pub fn admit(
+    limiter: &RateLimitService,
+    config: &RateLimitConfig,
+    request: &Request,
+    addr: SocketAddr,
+) -> Result<Option<String>, ApiError> {
+    if let Some(service) = internal_caller(config, request) {
+        return Ok(Some(service.to_string()));
+    }
+
     let key = match config.scope {
         RateLimitScope::Ip => addr.ip().to_string(),
         RateLimitScope::User => {
@@ -35,9 +64,138 @@ pub async fn rate_limit(
             .unwrap_or_else(|| addr.ip().to_string()),
     };
 
-    if !services.rate_limit.check_rate_limit(key) {
+    if !limiter.check_rate_limit(key) {
         return Err(ApiError::RateLimit("Too many requests".to_string()));
     }
 
-    Ok(next.run(request).await)
+    Ok(None)
+}
+
+/// The service whose internal token `request` presents, if any.
+fn internal_caller<'a>(config: &'a RateLimitConfig, request: &Request) -> Option<&'a str> {
+    let presented = request
+        .headers()
+        .get(INTERNAL_TOKEN_HEADER)?
+        .to_str()
+        .ok()?;
+
+    config
+        .internal_tokens
+        .iter()
+        .find(|(_, token)| constant_time_eq(token.as_bytes(), presented.as_bytes()))
+        .map(|(service, _)| service.as_str())
+}
+
+/// Audit-log a bypass through the job queue, writing directly if Redis is
+/// unavailable, the same way `audit_logging` does.
+fn record_bypass(
+    services: Arc<ServiceContainer>,
+    service: String,
+    request: &Request,
+    addr: SocketAddr,
+) {
+    let params = CreateAuditLogParams {
+        actor_id: format!("service:{}", service),
+        action: "rate_limit_bypass".to_string(),
+        resource: "rate_limit".to_string(),
+        resource_id: None,
+        metadata: Some(serde_json::json!({
+            "method": request.method().as_str(),
+            "path": request.uri().path(),
+        })),
+        ip_address: Some(addr.ip().to_string()),
+        user_agent: request
+            .headers()
+            .get("user-agent")
+            .and_then(|h| h.to_str().ok())
+            .map(|s| s.to_string()),
+        occurred_at: Some(chrono::Utc::now()),
+    };
+
+    tokio::spawn(async move {
+        let enqueued = match audit_job(&params) {
+            Ok(job) => services.job_queue.enqueue(job).await,
+            Err(e) => Err(e),
+        };
+
+        if let Err(e) = enqueued {
+            tracing::warn!("failed to enqueue audit log, writing directly: {}", e);
+            if let Err(e) = services.audit.create_audit_log(params).await {
+                tracing::error!("failed to write audit log: {}", e);
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Config;
+    use axum::body::Body;
+
+    const TOKEN: &str = "reconciler-internal-token";
+
+    fn limiter_and_config() -> (RateLimitService, RateLimitConfig) {
+        let mut config = Config::default();
+        config.rate_limit.max_requests = 2;
+        config
+            .rate_limit
+            .internal_tokens
+            .insert("reconciler".to_string(), TOKEN.to_string());
+        (
+            RateLimitService::new(config.clone()),
+            config.rate_limit.clone(),
+        )
+    }
+
+    fn request(token: Option<&str>) -> Request {
+        let mut builder = Request::builder().uri("/withdrawals");
+        if let Some(token) = token {
+            builder = builder.header(INTERNAL_TOKEN_HEADER, token);
+        }
+        builder.body(Body::empty()).unwrap()
+    }
+
+    fn addr() -> SocketAddr {
+        SocketAddr::from(([10, 0, 0, 7], 443))
+    }
+
+    #[test]
+    fn allowlisted_caller_is_never_throttled() {
+        let (limiter, config) = limiter_and_config();
+
+        for _ in 0..10 {
+            let service = admit(&limiter, &config, &request(Some(TOKEN)), addr()).unwrap();
+            assert_eq!(service.as_deref(), Some("reconciler"));
+        }
+    }
+
+    #[test]
+    fn normal_caller_is_still_throttled() {
+        let (limiter, config) = limiter_and_config();
+
+        for _ in 0..2 {
+            assert_eq!(
+                admit(&limiter, &config, &request(None), addr()).unwrap(),
+                None
+            );
+        }
+        assert!(matches!(
+            admit(&limiter, &config, &request(None), addr()),
+            Err(ApiError::RateLimit(_))
+        ));
+    }
+
+    #[test]
+    fn wrong_token_counts_against_the_limit() {
+        let (limiter, config) = limiter_and_config();
+
+        for _ in 0..2 {
+            assert_eq!(
+                admit(&limiter, &config, &request(Some("guessed-token")), addr()).unwrap(),
+                None
+            );
+        }
+        assert!(admit(&limiter, &config, &request(Some("guessed-token")), addr()).is_err());
+    }
 }
@@ -1,22 +1,120 @@
-use axum::{extract::Request, http::HeaderName, middleware::Next, response::Response};
+use axum::{
+    extract::Request,
+    http::{HeaderName, HeaderValue},
+    middleware::Next,
+    response::Response,
+};
+use tracing::Instrument;
 use uuid::Uuid;
 
 static X_REQUEST_ID: HeaderName = HeaderName::from_static("x-request-id");
 
+/// Longest inbound request id that is accepted as-is.
+const MAX_REQUEST_ID_LEN: usize = 128;
+
+/// Id of the current request, available to handlers as an extension.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RequestId(pub String);
+
+/// An inbound id is reused only if it is short and made of characters that
+/// are safe to log and echo back.
+fn is_valid_request_id(id: &str) -> bool {
+    !id.is_empty()
+        && id.len() <= MAX_REQUEST_ID_LEN
+        && id
+            .bytes()
+            .all(|b| b.is_ascii_alphanumeric() || matches!(b, b'-' | b'_' | b'.' | b':'))
+}
+
+/// Tag every request with an id.
+///
+/// An inbound `X-Request-Id` is propagated when valid, otherwise a UUID is
+/// generated. The id is echoed in the response header and recorded on a
+/// `request` span so every log line for the request carries it.
 pub async fn request_id(mut req: Request, next: Next) -> Response {
     let request_id = req
         .headers()
         .get(&X_REQUEST_ID)
         .and_then(|id| id.to_str().ok())
+        .filter(|id| is_valid_request_id(id))
         .map(|s| s.to_string())
         .unwrap_or_else(|| Uuid::new_v4().to_string());
 
-    req.extensions_mut().insert(request_id.clone());
+    let span = tracing::info_span!(
+        "request",
+        request_id = %request_id,
+        method = %req.method(),
+        path = %req.uri().path(),
+    );
 
-    let mut res = next.run(req).await;
+    req.extensions_mut().insert(RequestId(request_id.clone()));
 
-    res.headers_mut()
-        .insert(X_REQUEST_ID.clone(), request_id.parse().unwrap());
+    let mut res = next.run(req).instrument(span).await;
+
+    // Validated ids are always valid header values.
+    if let Ok(value) = HeaderValue::from_str(&request_id) {
+        res.headers_mut().insert(X_REQUEST_ID.clone(), value);
+    }
 
     res
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{body::Body, middleware, routing::get, Extension, Router};
+    use tower::util::ServiceExt;
+
+    fn app() -> Router {
+        Router::new()
+            .route(
+                "/",
+                get(|Extension(id): Extension<RequestId>| async move { id.0 }),
+            )
+            .layer(middleware::from_fn(request_id))
+    }
+
+    async fn send(inbound: Option<&str>) -> (String, String) {
+        let mut request = Request::builder().uri("/");
+        if let Some(id) = inbound {
+            request = request.header("x-request-id", id);
+        }
+        let response = app()
+            .oneshot(request.body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+
+        let header = response.headers()["x-request-id"]
+            .to_str()
+            .unwrap()
+            .to_string();
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        (header, String::from_utf8(body.to_vec()).unwrap())
+    }
+
+    #[tokio::test]
+    async fn generates_an_id_when_none_is_sent() {
+        let (header, seen_by_handler) = send(None).await;
+        assert!(Uuid::parse_str(&header).is_ok());
+        assert_eq!(header, seen_by_handler);
+    }
+
+    #[tokio::test]
+    async fn propagates_a_valid_inbound_id() {
+        let (header, seen_by_handler) = send(Some("edge-7f3a.01:retry_2")).await;
+        assert_eq!(header, "edge-7f3a.01:retry_2");
+        assert_eq!(seen_by_handler, header);
+    }
+
+    #[tokio::test]
+    async fn replaces_an_invalid_inbound_id() {
+        let too_long = "a".repeat(MAX_REQUEST_ID_LEN + 1);
+        for inbound in ["has spaces", "semi;colon", "", too_long.as_str()] {
+            let (header, _) = send(Some(inbound)).await;
+            assert_ne!(header, inbound);
+            assert!(Uuid::parse_str(&header).is_ok(), "{:?}", inbound);
+        }
+    }
+}
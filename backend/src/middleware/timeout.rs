@@ -0,0 +1,110 @@
+use std::time::Duration;
+
+use axum::{
+    extract::{Request, State},
+    http::header,
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use tracing::warn;
+
+use crate::api_error::ApiError;
+
+/// Fail requests whose handler takes longer than `timeout` with `504 Gateway Timeout`.
+///
+/// Only the time to produce the response head is bounded, so streamed bodies
+/// (e.g. audit exports) keep flowing. WebSocket upgrades are long-lived by
+/// design and are never cut off.
+pub async fn request_timeout(
+    State(timeout): State<Duration>,
+    req: Request,
+    next: Next,
+) -> Response {
+    if timeout.is_zero() || is_websocket_upgrade(&req) {
+        return next.run(req).await;
+    }
+
+    let method = req.method().clone();
+    let path = req.uri().path().to_string();
+
+    match tokio::time::timeout(timeout, next.run(req)).await {
+        Ok(response) => response,
+        Err(_) => {
+            warn!(%method, %path, timeout_ms = timeout.as_millis() as u64, "Request timed out");
+            ApiError::Timeout(format!("no response within {}ms", timeout.as_millis()))
+                .into_response()
+        }
+    }
+}
+
+fn is_websocket_upgrade(req: &Request) -> bool {
+    req.headers()
+        .get(header::UPGRADE)
+        .and_then(|v| v.to_str().ok())
+        .is_some_and(|v| v.eq_ignore_ascii_case("websocket"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{body::Body, http::StatusCode, middleware, routing::get, Router};
+    use tower::util::ServiceExt;
+
+    fn app(timeout: Duration) -> Router {
+        Router::new()
+            .route(
+                "/slow",
+                get(|| async {
+                    tokio::time::sleep(Duration::from_millis(300)).await;
+                    "done"
+                }),
+            )
+            .route("/fast", get(|| async { "done" }))
+            .layer(middleware::from_fn_with_state(timeout, request_timeout))
+    }
+
+    async fn status(app: Router, request: Request) -> StatusCode {
+        app.oneshot(request).await.unwrap().status()
+    }
+
+    fn get_request(uri: &str) -> Request {
+        Request::builder().uri(uri).body(Body::empty()).unwrap()
+    }
+
+    #[tokio::test]
+    async fn slow_handler_is_cut_off() {
+        let started = std::time::Instant::now();
+
+        let status = status(app(Duration::from_millis(100)), get_request("/slow")).await;
+
+        assert_eq!(status, StatusCode::GATEWAY_TIMEOUT);
+        assert!(started.elapsed() < Duration::from_secs(2));
+    }
+
+    #[tokio::test]
+    async fn fast_handler_is_unaffected() {
+        let status = status(app(Duration::from_millis(100)), get_request("/fast")).await;
+
+        assert_eq!(status, StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn websocket_upgrades_are_exempt() {
+        let request = Request::builder()
+            .uri("/slow")
+            .header(header::UPGRADE, "websocket")
+            .body(Body::empty())
+            .unwrap();
+
+        let status = status(app(Duration::from_millis(100)), request).await;
+
+        assert_eq!(status, StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn zero_disables_the_timeout() {
+        let status = status(app(Duration::ZERO), get_request("/slow")).await;
+
+        assert_eq!(status, StatusCode::OK);
+    }
+}
@@ -1,5 +1,6 @@
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::fmt;
 use std::str::FromStr;
 
@@ -13,6 +14,8 @@ pub struct User {
     pub role: Role,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
+    /// When the user last authenticated, to within a minute.
+    pub last_seen_at: Option<DateTime<Utc>>,
     pub(crate) address: String,
 }
 
@@ -28,11 +31,50 @@ pub struct Merchant {
     pub merchant_id: String,
     pub vault_address: String,
     pub settlement_asset: String,
+    /// Who pays the network fee on payments to this merchant.
+    pub fee_strategy: FeeStrategy,
     pub active: bool,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
 }
 
+/// Who pays the network fee of a payment.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FeeStrategy {
+    /// A server fee payer signs for the fee before the envelope is returned.
+    #[default]
+    Sponsored,
+    /// The sender's own signature covers the fee.
+    SenderPays,
+    /// The merchant's vault is named as fee source and must co-sign.
+    MerchantPays,
+}
+
+impl FromStr for FeeStrategy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "sponsored" => Ok(FeeStrategy::Sponsored),
+            "sender_pays" => Ok(FeeStrategy::SenderPays),
+            "merchant_pays" => Ok(FeeStrategy::MerchantPays),
+            other => Err(format!("Unknown fee strategy {}", other)),
+        }
+    }
+}
+
+impl fmt::Display for FeeStrategy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let s = match self {
+            FeeStrategy::Sponsored => "sponsored",
+            FeeStrategy::SenderPays => "sender_pays",
+            FeeStrategy::MerchantPays => "merchant_pays",
+        };
+        write!(f, "{}", s)
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum PaymentStatus {
     Pending,
@@ -42,6 +84,16 @@ pub enum PaymentStatus {
     Refunded,
 }
 
+impl PaymentStatus {
+    /// Whether the payment has settled and won't change status again.
+    pub fn is_terminal(&self) -> bool {
+        matches!(
+            self,
+            PaymentStatus::Completed | PaymentStatus::Failed | PaymentStatus::Refunded
+        )
+    }
+}
+
 impl FromStr for PaymentStatus {
     type Err = std::convert::Infallible;
 
@@ -80,6 +132,8 @@ pub struct Payment {
     pub receive_amount: Option<i64>,
     pub status: PaymentStatus,
     pub memo: Option<String>,
+    /// Who pays the network fee, as applied when the payment was created.
+    pub fee_strategy: FeeStrategy,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
 }
@@ -198,6 +252,10 @@ pub struct AuditLogEntry {
     pub timestamp: DateTime<Utc>,
     pub ip_address: Option<String>,
     pub user_agent: Option<String>,
+    /// Hash of the preceding entry in the chain (`None` for pre-chain rows).
+    pub prev_hash: Option<String>,
+    /// SHA-256 over this entry's fields and `prev_hash`.
+    pub entry_hash: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -209,6 +267,10 @@ pub struct CreateAuditLogParams {
     pub metadata: Option<serde_json::Value>,
     pub ip_address: Option<String>,
     pub user_agent: Option<String>,
+    /// When the audited action happened. Entries written later, such as
+    /// from the job queue, keep this rather than the time of the write.
+    #[serde(default)]
+    pub occurred_at: Option<DateTime<Utc>>,
 }
 
 // DTOs for Audit Log API
@@ -228,6 +290,55 @@ fn default_limit() -> i64 {
     50
 }
 
+impl AuditLogQueryParams {
+    pub fn filter(&self) -> AuditLogFilter {
+        AuditLogFilter {
+            actor_id: self.actor_id.clone(),
+            action: self.action.clone(),
+            from_date: self.from_date,
+            to_date: self.to_date,
+        }
+    }
+}
+
+/// Row filters shared by the audit log list, count and export queries.
+#[derive(Debug, Clone, Default)]
+pub struct AuditLogFilter {
+    pub actor_id: Option<String>,
+    pub action: Option<String>,
+    pub from_date: Option<DateTime<Utc>>,
+    pub to_date: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AuditExportFormat {
+    #[default]
+    Json,
+    Csv,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct AuditLogExportParams {
+    #[serde(default)]
+    pub format: AuditExportFormat,
+    pub from: Option<DateTime<Utc>>,
+    pub to: Option<DateTime<Utc>>,
+    pub actor_id: Option<String>,
+    pub action: Option<String>,
+}
+
+impl AuditLogExportParams {
+    pub fn filter(&self) -> AuditLogFilter {
+        AuditLogFilter {
+            actor_id: self.actor_id.clone(),
+            action: self.action.clone(),
+            from_date: self.from,
+            to_date: self.to,
+        }
+    }
+}
+
 #[derive(Debug, Serialize)]
 pub struct AuditLogResponse {
     pub id: String,
@@ -239,14 +350,152 @@ pub struct AuditLogResponse {
     pub timestamp: DateTime<Utc>,
 }
 
+/// Result of walking the audit log hash chain.
+#[derive(Debug, Serialize, PartialEq, Eq)]
+pub struct AuditChainVerification {
+    pub valid: bool,
+    pub entries_checked: i64,
+    /// First entry whose hash or link did not match, if any.
+    pub broken_at: Option<String>,
+    pub reason: Option<String>,
+}
+
+/// Result of an audit log retention purge.
+#[derive(Debug, Serialize)]
+pub struct AuditPurgeResult {
+    pub deleted: u64,
+    /// Storage ids of the archives uploaded before deleting, one per batch.
+    pub archive_ids: Vec<String>,
+}
+
+/// One page of an offset-paginated list.
+#[derive(Debug, Serialize)]
+pub struct Paginated<T> {
+    pub items: Vec<T>,
+    /// Matching items across all pages.
+    pub total: i64,
+    pub limit: i64,
+    pub offset: i64,
+    /// Whether items remain past this page.
+    pub has_more: bool,
+}
+
+impl<T> Paginated<T> {
+    /// `limit` and `offset` are the values the page was actually queried
+    /// with, after any clamping.
+    pub fn new(items: Vec<T>, total: i64, limit: i64, offset: i64) -> Self {
+        let has_more = offset.saturating_add(items.len() as i64) < total;
+        Self {
+            items,
+            total,
+            limit,
+            offset,
+            has_more,
+        }
+    }
+}
+
+/// Kind of money movement listed by `GET /admin/transactions`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TransactionKind {
+    Payment,
+    Transfer,
+    Withdrawal,
+}
+
+impl TransactionKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            TransactionKind::Payment => "payment",
+            TransactionKind::Transfer => "transfer",
+            TransactionKind::Withdrawal => "withdrawal",
+        }
+    }
+}
+
+impl FromStr for TransactionKind {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "payment" => Ok(TransactionKind::Payment),
+            "transfer" => Ok(TransactionKind::Transfer),
+            "withdrawal" => Ok(TransactionKind::Withdrawal),
+            other => Err(format!("Unknown transaction type: {}", other)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct TransactionQueryParams {
+    #[serde(rename = "type")]
+    pub kind: Option<TransactionKind>,
+    pub status: Option<String>,
+    /// Matches the payer of a payment, either side of a transfer, or the
+    /// owner of a withdrawal.
+    pub user_id: Option<String>,
+    pub from: Option<DateTime<Utc>>,
+    pub to: Option<DateTime<Utc>>,
+    #[serde(default = "default_limit")]
+    pub limit: i64,
+    #[serde(default)]
+    pub offset: i64,
+}
+
+/// A payment, transfer or withdrawal in one shape.
+#[derive(Debug, Clone, Serialize)]
+pub struct TransactionRecord {
+    pub id: String,
+    #[serde(rename = "type")]
+    pub kind: TransactionKind,
+    pub status: Option<String>,
+    /// Payer, sender or withdrawing user; `None` for a payment from an
+    /// address that isn't a registered user.
+    pub user_id: Option<String>,
+    /// Merchant, recipient user or withdrawal destination.
+    pub counterparty: String,
+    pub amount: i64,
+    pub asset: String,
+    pub tx_hash: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
 #[derive(Debug, Serialize)]
-pub struct AuditLogListResponse {
-    pub logs: Vec<AuditLogResponse>,
+pub struct TransactionListResponse {
+    pub transactions: Vec<TransactionRecord>,
     pub total: i64,
     pub limit: i64,
     pub offset: i64,
 }
 
+/// Count and summed amount of one kind of transaction in one asset and
+/// status.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct TransactionStats {
+    #[serde(rename = "type")]
+    pub kind: TransactionKind,
+    pub asset: String,
+    pub status: Option<String>,
+    pub count: i64,
+    pub volume: i64,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum StatsExportFormat {
+    #[default]
+    Csv,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct DashboardStatsExportParams {
+    #[serde(default)]
+    pub format: StatsExportFormat,
+    pub from: Option<DateTime<Utc>>,
+    pub to: Option<DateTime<Utc>>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum BridgeTransactionStatus {
     Pending,
@@ -352,6 +601,11 @@ pub struct RateLimitConfig {
     pub window_ms: u64,
     pub max_requests: u32,
     pub scope: RateLimitScope,
+    /// Internal services exempt from rate limiting, keyed by service name.
+    /// A caller presenting a service's token in `X-Internal-Token` skips the
+    /// limiter, and each such request is audit-logged.
+    #[serde(default)]
+    pub internal_tokens: HashMap<String, String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -363,14 +617,23 @@ pub struct UserProfile {
     pub bio: Option<String>,
     pub country: Option<String>,
     pub metadata: Option<serde_json::Value>,
+    /// Incremented on every write; updates must name the version they read.
+    pub version: i32,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct BuildTransactionDto {
+    /// Account (`G...`) that pays the fee and will sign the transaction.
+    pub source_account: String,
+    /// Sequence number of the transaction: one past the account's current
+    /// sequence. Simulation ignores it.
+    pub sequence: i64,
+    /// Contract address (`C...`).
     pub contract_id: String,
     pub method: String,
+    /// Arguments as JSON-encoded `ScVal`s, e.g. `{"address": "G..."}`.
     pub args: Vec<serde_json::Value>,
 }
 
@@ -387,6 +650,15 @@ pub struct SignedTransactionResponse {
     pub status: TransactionStatus,
 }
 
+/// Query parameters of a signed `GET /files/:id` URL.
+#[derive(Debug, Deserialize)]
+pub struct FileAccessParams {
+    /// Unix timestamp after which the URL is rejected.
+    pub expires: Option<i64>,
+    /// Hex HMAC-SHA256 over the file ID and `expires`.
+    pub token: Option<String>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct FileUploadResponseDto {
     pub file_id: String,
@@ -395,3 +667,54 @@ pub struct FileUploadResponseDto {
     pub size: u64,
     pub url: String,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn page(total: i64, limit: i64, offset: i64) -> Paginated<i64> {
+        let items: Vec<i64> = (offset..total.min(offset + limit)).collect();
+        Paginated::new(items, total, limit, offset)
+    }
+
+    #[test]
+    fn has_more_until_the_last_page() {
+        assert!(page(25, 10, 0).has_more);
+        assert!(page(25, 10, 10).has_more);
+
+        let last = page(25, 10, 20);
+        assert_eq!(last.items.len(), 5);
+        assert!(!last.has_more);
+    }
+
+    #[test]
+    fn exactly_full_last_page_has_no_more() {
+        assert!(page(20, 10, 0).has_more);
+        assert!(!page(20, 10, 10).has_more);
+    }
+
+    #[test]
+    fn past_the_end_is_empty_with_no_more() {
+        let past = page(20, 10, 30);
+        assert!(past.items.is_empty());
+        assert!(!past.has_more);
+        assert_eq!(past.total, 20);
+
+        assert!(!page(0, 10, 0).has_more);
+    }
+
+    #[test]
+    fn serializes_as_an_envelope() {
+        let json = serde_json::to_value(page(3, 2, 0)).unwrap();
+        assert_eq!(
+            json,
+            serde_json::json!({
+                "items": [0, 1],
+                "total": 3,
+                "limit": 2,
+                "offset": 0,
+                "has_more": true,
+            })
+        );
+    }
+}
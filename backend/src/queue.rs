@@ -1,22 +1,62 @@
-use crate::job_types::{DeadLetterJob, JobPayload, JobResult};
+use crate::job_types::{DeadLetterJob, JobPayload, JobResult, JobType, QuarantinedJob};
 use anyhow::{Context, Result};
 use async_trait::async_trait;
-use bb8_redis::{bb8::Pool, redis::AsyncCommands, RedisConnectionManager};
+use bb8_redis::{
+    bb8::{Pool, PooledConnection},
+    redis::AsyncCommands,
+    RedisConnectionManager,
+};
 use chrono::Utc;
+use serde::Serialize;
 use serde_json;
+use std::collections::HashMap;
 use std::time::Duration;
 use tracing::{debug, error, info, warn};
 use uuid::Uuid;
 
-const DEFAULT_QUEUE: &str = "zaps:jobs:queue";
+/// Ready jobs of one type wait in `<prefix><job_type>`, scored by when they
+/// become due, so a worker only ever looks at the head of each queue.
+const READY_QUEUE_PREFIX: &str = "zaps:jobs:queue:";
+/// The single ready queue every job type shared before per-type queues.
+/// Drained into the per-type queues at startup.
+const LEGACY_QUEUE: &str = "zaps:jobs:queue";
 const PROCESSING_QUEUE: &str = "zaps:jobs:processing";
 const DEAD_LETTER_QUEUE: &str = "zaps:jobs:dead_letter";
 const RETRY_QUEUE: &str = "zaps:jobs:retry";
+/// Pushed to whenever a job becomes ready so idle workers blocked in
+/// [`JobQueue::dequeue_blocking`] wake up. Tokens carry no data.
+const WAKEUP_LIST: &str = "zaps:jobs:wakeup";
+/// Per-type counterpart of [`WAKEUP_LIST`] for workers dedicated to one job
+/// type, so a burst of other jobs can't use up the tokens meant for them.
+const TYPED_WAKEUP_PREFIX: &str = "zaps:jobs:wakeup:";
+/// Upper bound on buffered wakeup tokens while no worker is waiting.
+const MAX_WAKEUP_TOKENS: isize = 1024;
+/// Per-job key a worker keeps alive while it processes the job. A stalled
+/// job is only reclaimed once its heartbeat has expired.
+const HEARTBEAT_PREFIX: &str = "zaps:jobs:heartbeat:";
+/// Connections in each of the queue's Redis pools.
+pub const REDIS_POOL_SIZE: u32 = 20;
+
+/// Move ARGV[1] from the ready queue KEYS[1] to the processing queue KEYS[2]
+/// with score ARGV[2], in one step. Returns 0 when another worker already
+/// took it.
+const CLAIM_SCRIPT: &str = r#"
+if redis.call('ZREM', KEYS[1], ARGV[1]) == 1 then
+    redis.call('ZADD', KEYS[2], ARGV[2], ARGV[1])
+    return 1
+end
+return 0
+"#;
 
 #[derive(Debug, Clone)]
 pub struct QueueConfig {
     pub max_retries: u32,
+    /// Attempts for job types that need a different limit than `max_retries`.
+    pub max_retries_by_type: HashMap<JobType, u32>,
     pub visibility_timeout: Duration,
+    /// Visibility timeouts for job types that need a different window than
+    /// `visibility_timeout`.
+    pub visibility_timeouts: HashMap<JobType, Duration>,
     pub backoff_multiplier: f64,
     pub max_backoff: Duration,
     pub dead_letter_max_size: usize,
@@ -26,7 +66,9 @@ impl Default for QueueConfig {
     fn default() -> Self {
         Self {
             max_retries: 3,
+            max_retries_by_type: HashMap::new(),
             visibility_timeout: Duration::from_secs(300), // 5 minutes
+            visibility_timeouts: HashMap::new(),
             backoff_multiplier: 2.0,
             max_backoff: Duration::from_secs(3600), // 1 hour
             dead_letter_max_size: 10000,
@@ -34,6 +76,130 @@ impl Default for QueueConfig {
     }
 }
 
+impl QueueConfig {
+    /// How many attempts a job of `job_type` gets before it is dead-lettered.
+    pub fn max_retries_for(&self, job_type: &JobType) -> u32 {
+        self.max_retries_by_type
+            .get(job_type)
+            .copied()
+            .unwrap_or(self.max_retries)
+    }
+
+    /// Whether a job of `job_type` that just failed its `attempt`th attempt
+    /// goes to the dead-letter queue instead of being retried.
+    fn exhausted(&self, job_type: &JobType, attempt: u32) -> bool {
+        attempt >= self.max_retries_for(job_type)
+    }
+
+    /// How long a job of `job_type` may stay in the processing queue before
+    /// it is considered stalled and reclaimed.
+    pub fn visibility_timeout_for(&self, job_type: &JobType) -> Duration {
+        self.visibility_timeouts
+            .get(job_type)
+            .copied()
+            .unwrap_or(self.visibility_timeout)
+    }
+
+    /// How often a worker should extend the heartbeat of a `job_type` job,
+    /// leaving room for two missed beats before it lapses.
+    pub fn heartbeat_interval_for(&self, job_type: &JobType) -> Duration {
+        self.visibility_timeout_for(job_type) / 3
+    }
+
+    /// When a job of `job_type` dequeued at `now` becomes eligible for reclaim.
+    fn processing_deadline(
+        &self,
+        job_type: &JobType,
+        now: chrono::DateTime<Utc>,
+    ) -> chrono::DateTime<Utc> {
+        let timeout = chrono::Duration::from_std(self.visibility_timeout_for(job_type))
+            .unwrap_or(chrono::Duration::MAX);
+        now.checked_add_signed(timeout)
+            .unwrap_or(chrono::DateTime::<Utc>::MAX_UTC)
+    }
+}
+
+/// Parse a snake_case job type name as used in config tables.
+fn parse_job_type(name: &str) -> Result<JobType> {
+    serde_json::from_value(serde_json::Value::String(name.to_uppercase()))
+        .with_context(|| format!("Unknown job type: {}", name))
+}
+
+/// Parse the `[queue.max_retries_overrides]` table, keyed by snake_case job
+/// type.
+pub(crate) fn parse_max_retries(overrides: &HashMap<String, u32>) -> Result<HashMap<JobType, u32>> {
+    overrides
+        .iter()
+        .map(|(name, retries)| {
+            let job_type = parse_job_type(name).context("Invalid max retries override")?;
+            Ok((job_type, *retries))
+        })
+        .collect()
+}
+
+/// Parse the `[queue.visibility_timeout_overrides]` table, keyed by
+/// snake_case job type.
+pub(crate) fn parse_visibility_timeouts(
+    overrides: &HashMap<String, u64>,
+) -> Result<HashMap<JobType, Duration>> {
+    overrides
+        .iter()
+        .map(|(name, seconds)| {
+            let job_type = parse_job_type(name).context("Invalid visibility timeout override")?;
+            if *seconds == 0 {
+                anyhow::bail!("Visibility timeout for {} must be greater than zero", name);
+            }
+            Ok((job_type, Duration::from_secs(*seconds)))
+        })
+        .collect()
+}
+
+/// Parse the `[queue.worker_pools]` table, keyed by snake_case job type.
+pub(crate) fn parse_worker_pools(
+    pools: &HashMap<String, usize>,
+) -> Result<HashMap<JobType, usize>> {
+    pools
+        .iter()
+        .map(|(name, workers)| {
+            let job_type = parse_job_type(name).context("Invalid worker pool")?;
+            if *workers == 0 {
+                anyhow::bail!("Worker pool for {} must have at least one worker", name);
+            }
+            Ok((job_type, *workers))
+        })
+        .collect()
+}
+
+fn heartbeat_key(job_id: Uuid) -> String {
+    format!("{}{}", HEARTBEAT_PREFIX, job_id)
+}
+
+fn ready_queue(job_type: &JobType) -> String {
+    format!("{}{}", READY_QUEUE_PREFIX, job_type_key(job_type))
+}
+
+fn typed_wakeup_list(job_type: &JobType) -> String {
+    format!("{}{}", TYPED_WAKEUP_PREFIX, job_type_key(job_type))
+}
+
+/// Whole seconds BLPOP can block for without overshooting `remaining`, or
+/// `None` when under a second is left: BLPOP takes whole seconds and treats
+/// 0 as "block forever".
+fn blpop_timeout(remaining: Duration) -> Option<usize> {
+    match remaining.as_secs() {
+        0 => None,
+        secs => Some(secs as usize),
+    }
+}
+
+/// The snake_case name of `job_type`, as used in config and Redis keys.
+fn job_type_key(job_type: &JobType) -> String {
+    serde_json::to_value(job_type)
+        .ok()
+        .and_then(|v| v.as_str().map(str::to_lowercase))
+        .unwrap_or_default()
+}
+
 #[async_trait]
 pub trait JobProcessor: Send + Sync {
     async fn process(&self, job: &JobPayload) -> Result<JobResult>;
@@ -41,20 +207,121 @@ pub trait JobProcessor: Send + Sync {
 
 pub struct JobQueue {
     pool: Pool<RedisConnectionManager>,
+    /// Connections held by workers blocked in BLPOP, kept apart from `pool`
+    /// so idle workers can't take the connections enqueues and claims need.
+    wait_pool: Pool<RedisConnectionManager>,
     config: QueueConfig,
 }
 
 impl JobQueue {
     pub async fn new(redis_url: &str, config: QueueConfig) -> Result<Self> {
+        let pool = Self::build_pool(redis_url).await?;
+        let wait_pool = Self::build_pool(redis_url).await?;
+
+        Ok(Self {
+            pool,
+            wait_pool,
+            config,
+        })
+    }
+
+    async fn build_pool(redis_url: &str) -> Result<Pool<RedisConnectionManager>> {
         let manager = RedisConnectionManager::new(redis_url)
             .context("Failed to create Redis connection manager")?;
-        let pool = Pool::builder()
-            .max_size(20)
+        Pool::builder()
+            .max_size(REDIS_POOL_SIZE)
             .build(manager)
             .await
-            .context("Failed to create Redis connection pool")?;
+            .context("Failed to create Redis connection pool")
+    }
 
-        Ok(Self { pool, config })
+    /// Move jobs in the old shared ready queue, left over from or still
+    /// enqueued by replicas predating per-type queues, to their per-type
+    /// queues, keeping when they are due.
+    async fn drain_legacy_queue(
+        &self,
+        conn: &mut PooledConnection<'_, RedisConnectionManager>,
+    ) -> Result<()> {
+        let entries: Vec<(String, f64)> = conn
+            .zrange_withscores(LEGACY_QUEUE, 0, -1)
+            .await
+            .context("Failed to read legacy job queue")?;
+
+        for (raw, score) in &entries {
+            match serde_json::from_str::<JobPayload>(raw) {
+                Ok(job) => {
+                    conn.zadd::<_, _, _, ()>(ready_queue(&job.job_type), raw, *score)
+                        .await
+                        .context("Failed to move legacy job")?;
+                    conn.zrem::<_, _, ()>(LEGACY_QUEUE, raw)
+                        .await
+                        .context("Failed to move legacy job")?;
+                    Self::wake_workers(&mut **conn, &job.job_type).await?;
+                }
+                Err(e) => {
+                    self.quarantine(conn, LEGACY_QUEUE, raw, e.to_string())
+                        .await?
+                }
+            }
+        }
+        if !entries.is_empty() {
+            info!("Moved {} jobs to per-type queues", entries.len());
+        }
+        Ok(())
+    }
+
+    /// Build a queue from the `[queue]` section of the application config.
+    pub async fn from_config(config: &crate::config::Config) -> Result<Self> {
+        let queue_config = QueueConfig {
+            max_retries: config.queue_config.max_retries,
+            max_retries_by_type: parse_max_retries(&config.queue_config.max_retries_overrides)?,
+            visibility_timeout: Duration::from_secs(config.queue_config.visibility_timeout_seconds),
+            visibility_timeouts: parse_visibility_timeouts(
+                &config.queue_config.visibility_timeout_overrides,
+            )?,
+            backoff_multiplier: config.queue_config.backoff_multiplier,
+            max_backoff: Duration::from_secs(config.queue_config.max_backoff_seconds),
+            dead_letter_max_size: config.queue_config.dead_letter_max_size,
+        };
+
+        Self::new(&config.queue_config.redis_url, queue_config).await
+    }
+
+    /// Set `key` if it isn't already held, expiring after `ttl`. Returns
+    /// `true` when this call took the lock.
+    pub async fn try_lock(&self, key: &str, ttl: Duration) -> Result<bool> {
+        let mut conn = self.pool.get().await?;
+        let reply: Option<String> = bb8_redis::redis::cmd("SET")
+            .arg(key)
+            .arg(1)
+            .arg("NX")
+            .arg("PX")
+            .arg(ttl.as_millis() as u64)
+            .query_async(&mut *conn)
+            .await
+            .context("Failed to acquire Redis lock")?;
+        Ok(reply.is_some())
+    }
+
+    /// Whether `key` is currently set.
+    pub async fn key_exists(&self, key: &str) -> Result<bool> {
+        let mut conn = self.pool.get().await?;
+        let exists: bool = bb8_redis::redis::cmd("EXISTS")
+            .arg(key)
+            .query_async(&mut *conn)
+            .await
+            .context("Redis EXISTS failed")?;
+        Ok(exists)
+    }
+
+    /// Round-trip a `PING` through the pool to confirm Redis is reachable.
+    pub async fn ping(&self) -> Result<()> {
+        let mut conn = self.pool.get().await?;
+        let _: String = bb8_redis::redis::cmd("PING")
+            .query_async(&mut *conn)
+            .await
+            .context("Redis PING failed")?;
+        Ok(())
     }
 
     pub async fn enqueue(&self, job: JobPayload) -> Result<()> {
@@ -63,51 +330,188 @@ impl JobQueue {
 
         let score = job.scheduled_at.unwrap_or_else(Utc::now).timestamp();
 
-        conn.zadd::<_, _, _, ()>(DEFAULT_QUEUE, &job_json, score)
+        conn.zadd::<_, _, _, ()>(ready_queue(&job.job_type), &job_json, score)
             .await
             .context("Failed to enqueue job")?;
+        Self::wake_workers(&mut *conn, &job.job_type).await?;
 
         info!("Enqueued job {} of type {:?}", job.id, job.job_type);
         Ok(())
     }
 
+    /// Like [`dequeue`](Self::dequeue), but when no job is ready wait up to
+    /// `timeout` for one to be enqueued instead of returning immediately.
+    ///
+    /// Waiting blocks on a Redis list rather than polling, so idle workers
+    /// generate no traffic. Jobs scheduled for the future don't wake waiters;
+    /// they are picked up on the next call once `timeout` elapses.
+    pub async fn dequeue_blocking(&self, timeout: Duration) -> Result<Option<JobPayload>> {
+        self.dequeue_blocking_for(None, timeout).await
+    }
+
+    /// Like [`dequeue_blocking`](Self::dequeue_blocking), but only take jobs
+    /// of `job_type` when one is given, leaving other jobs queued. Workers
+    /// dedicated to a type are woken by enqueues of that type alone.
+    pub async fn dequeue_blocking_for(
+        &self,
+        job_type: Option<&JobType>,
+        timeout: Duration,
+    ) -> Result<Option<JobPayload>> {
+        let deadline = tokio::time::Instant::now() + timeout;
+        let wakeup_list = job_type.map_or_else(|| WAKEUP_LIST.to_string(), typed_wakeup_list);
+
+        loop {
+            if let Some(job) = self.dequeue_for(job_type).await? {
+                return Ok(Some(job));
+            }
+
+            let remaining = deadline.saturating_duration_since(tokio::time::Instant::now());
+            if remaining.is_zero() {
+                return Ok(None);
+            }
+
+            let Some(timeout) = blpop_timeout(remaining) else {
+                // Too little time left to block on; wait it out and look once more.
+                tokio::time::sleep(remaining).await;
+                return self.dequeue_for(job_type).await;
+            };
+            let mut conn = self.wait_pool.get().await?;
+            let woken: Option<(String, String)> = conn
+                .blpop(&wakeup_list, timeout)
+                .await
+                .context("Failed to wait for queued jobs")?;
+            if woken.is_none() {
+                return self.dequeue_for(job_type).await;
+            }
+            // Another worker may have taken the job; go round again.
+        }
+    }
+
+    /// Signal one waiting shared worker, and one waiting worker dedicated to
+    /// `job_type`, that a job is ready.
+    async fn wake_workers(conn: &mut impl AsyncCommands, job_type: &JobType) -> Result<()> {
+        for list in [WAKEUP_LIST.to_string(), typed_wakeup_list(job_type)] {
+            conn.lpush::<_, _, ()>(&list, 1)
+                .await
+                .context("Failed to signal workers")?;
+            conn.ltrim::<_, ()>(&list, 0, MAX_WAKEUP_TOKENS - 1)
+                .await
+                .context("Failed to trim worker wakeup list")?;
+        }
+        Ok(())
+    }
+
     pub async fn dequeue(&self) -> Result<Option<JobPayload>> {
+        self.dequeue_for(None).await
+    }
+
+    /// Take the earliest ready job, of `job_type` only if one is given.
+    ///
+    /// Only the head of each candidate type's queue is read, and the claim
+    /// is atomic: if another worker takes the job first, the next head is
+    /// tried instead.
+    pub async fn dequeue_for(&self, job_type: Option<&JobType>) -> Result<Option<JobPayload>> {
         let mut conn = self.pool.get().await?;
+        let candidates = match job_type {
+            Some(job_type) => std::slice::from_ref(job_type),
+            None => &JobType::ALL[..],
+        };
 
-        // Get the earliest ready job
-        let now = Utc::now().timestamp();
-        let jobs: Vec<String> = conn
-            .zrangebyscore(DEFAULT_QUEUE, "-inf", now)
-            .await
-            .context("Failed to fetch jobs from queue")?;
+        loop {
+            let now = Utc::now().timestamp();
+            let mut heads = Vec::with_capacity(candidates.len());
+            for candidate in candidates {
+                let queue = ready_queue(candidate);
+                let head: Vec<(String, f64)> = conn
+                    .zrangebyscore_limit_withscores(&queue, "-inf", now, 0, 1)
+                    .await
+                    .context("Failed to fetch jobs from queue")?;
+                heads.extend(
+                    head.into_iter()
+                        .map(|(raw, score)| (queue.clone(), raw, score)),
+                );
+            }
+            let Some((queue, raw, _)) = earliest(heads) else {
+                return Ok(None);
+            };
+
+            let job: JobPayload = match serde_json::from_str(&raw) {
+                Ok(job) => job,
+                Err(e) => {
+                    self.quarantine(&mut conn, &queue, &raw, e.to_string())
+                        .await?;
+                    continue;
+                }
+            };
+
+            let processing_score = self
+                .config
+                .processing_deadline(&job.job_type, Utc::now())
+                .timestamp();
+            let claimed: i64 = bb8_redis::redis::cmd("EVAL")
+                .arg(CLAIM_SCRIPT)
+                .arg(2)
+                .arg(&queue)
+                .arg(PROCESSING_QUEUE)
+                .arg(&raw)
+                .arg(processing_score)
+                .query_async(&mut *conn)
+                .await
+                .context("Failed to move job to processing queue")?;
+            if claimed == 0 {
+                // Another worker took it; look again.
+                continue;
+            }
+            self.beat(&mut conn, &job, false).await?;
 
-        if jobs.is_empty() {
-            return Ok(None);
+            debug!("Dequeued job {} for processing", job.id);
+            return Ok(Some(job));
         }
+    }
 
-        let job_json = &jobs[0];
-        let job: JobPayload =
-            serde_json::from_str(job_json).context("Failed to deserialize job")?;
-
-        // Move job to processing queue
-        conn.zrem::<_, _, ()>(DEFAULT_QUEUE, job_json)
-            .await
-            .context("Failed to remove job from main queue")?;
+    /// Extend the heartbeat of a job this worker is processing. Returns
+    /// `false` if it had already lapsed, in which case the job may have been
+    /// reclaimed and handed to another worker.
+    pub async fn heartbeat(&self, job: &JobPayload) -> Result<bool> {
+        let mut conn = self.pool.get().await?;
+        self.beat(&mut conn, job, true).await
+    }
 
-        let processing_score = (Utc::now()
-            + chrono::Duration::from_std(self.config.visibility_timeout).unwrap())
-        .timestamp();
+    /// How often workers should call [`heartbeat`](Self::heartbeat) for a
+    /// job of `job_type`.
+    pub fn heartbeat_interval(&self, job_type: &JobType) -> Duration {
+        self.config.heartbeat_interval_for(job_type)
+    }
 
-        conn.zadd::<_, _, _, ()>(PROCESSING_QUEUE, job_json, processing_score)
+    /// Set the job's heartbeat to expire after its visibility timeout; only
+    /// if it is still alive when `extend` is set.
+    async fn beat(
+        &self,
+        conn: &mut PooledConnection<'_, RedisConnectionManager>,
+        job: &JobPayload,
+        extend: bool,
+    ) -> Result<bool> {
+        let ttl = self.config.visibility_timeout_for(&job.job_type);
+        let mut cmd = bb8_redis::redis::cmd("SET");
+        cmd.arg(heartbeat_key(job.id))
+            .arg(1)
+            .arg("PX")
+            .arg(ttl.as_millis() as u64);
+        if extend {
+            cmd.arg("XX");
+        }
+        let reply: Option<String> = cmd
+            .query_async(&mut **conn)
             .await
-            .context("Failed to add job to processing queue")?;
-
-        debug!("Dequeued job {} for processing", job.id);
-        Ok(Some(job))
+            .context("Failed to set job heartbeat")?;
+        Ok(reply.is_some())
     }
 
     pub async fn complete_job(&self, job_id: Uuid, result: JobResult) -> Result<()> {
         let mut conn = self.pool.get().await?;
+        conn.del::<_, ()>(heartbeat_key(job_id))
+            .await
+            .context("Failed to clear job heartbeat")?;
 
         // Remove from processing queue
         let jobs: Vec<String> = conn
@@ -116,8 +520,10 @@ impl JobQueue {
             .context("Failed to fetch processing jobs")?;
 
         for job_json in jobs {
-            let job: JobPayload =
-                serde_json::from_str(&job_json).context("Failed to deserialize processing job")?;
+            // Unparseable entries are quarantined once they stall.
+            let Ok(job) = serde_json::from_str::<JobPayload>(&job_json) else {
+                continue;
+            };
 
             if job.id == job_id {
                 conn.zrem::<_, _, ()>(PROCESSING_QUEUE, &job_json)
@@ -137,9 +543,17 @@ impl JobQueue {
     }
 
     pub async fn retry_job(&self, job: JobPayload, error: String) -> Result<()> {
+        self.pool
+            .get()
+            .await?
+            .del::<_, ()>(heartbeat_key(job.id))
+            .await
+            .context("Failed to clear job heartbeat")?;
+
         let current_attempt = job.retries.unwrap_or(0) + 1;
+        let max_retries = self.config.max_retries_for(&job.job_type);
 
-        if current_attempt >= self.config.max_retries {
+        if self.config.exhausted(&job.job_type, current_attempt) {
             return self.send_to_dead_letter(job, error, current_attempt).await;
         }
 
@@ -171,14 +585,17 @@ impl JobQueue {
 
         warn!(
             "Retrying job {} (attempt {}/{}) in {:?}",
-            job.id, current_attempt, self.config.max_retries, backoff_delay
+            job.id, current_attempt, max_retries, backoff_delay
         );
 
         Ok(())
     }
 
+    /// Move retries that are due, and anything in the legacy shared queue,
+    /// to the ready queues.
     pub async fn process_retry_queue(&self) -> Result<()> {
         let mut conn = self.pool.get().await?;
+        self.drain_legacy_queue(&mut conn).await?;
 
         let now = Utc::now().timestamp();
         let retry_jobs: Vec<String> = conn
@@ -187,8 +604,14 @@ impl JobQueue {
             .context("Failed to fetch retry jobs")?;
 
         for job_json in retry_jobs {
-            let job: JobPayload =
-                serde_json::from_str(&job_json).context("Failed to deserialize retry job")?;
+            let job: JobPayload = match serde_json::from_str(&job_json) {
+                Ok(job) => job,
+                Err(e) => {
+                    self.quarantine(&mut conn, RETRY_QUEUE, &job_json, e.to_string())
+                        .await?;
+                    continue;
+                }
+            };
 
             // Move back to main queue
             conn.zrem::<_, _, ()>(RETRY_QUEUE, &job_json)
@@ -196,10 +619,12 @@ impl JobQueue {
                 .context("Failed to remove job from retry queue")?;
 
             let score = job.scheduled_at.unwrap().timestamp();
-            conn.zadd::<_, _, _, ()>(DEFAULT_QUEUE, &job_json, score)
+            conn.zadd::<_, _, _, ()>(ready_queue(&job.job_type), &job_json, score)
                 .await
                 .context("Failed to move job back to main queue")?;
 
+            Self::wake_workers(&mut *conn, &job.job_type).await?;
+
             debug!("Moved retry job {} back to main queue", job.id);
         }
 
@@ -222,7 +647,58 @@ impl JobQueue {
         let mut conn = self.pool.get().await?;
         let dlq_json = serde_json::to_string(&dead_letter_job)
             .context("Failed to serialize dead letter job")?;
+        self.push_dead_letter(&mut conn, &dlq_json).await?;
+
+        // Remove from processing queue
+        let original_json =
+            serde_json::to_string(&job).context("Failed to serialize original job")?;
+        conn.zrem::<_, _, ()>(PROCESSING_QUEUE, &original_json)
+            .await
+            .context("Failed to remove job from processing queue")?;
 
+        error!(
+            "Job {} sent to dead letter queue after {} attempts",
+            job.id, total_attempts
+        );
+        Ok(())
+    }
+
+    /// Move an entry that isn't a valid job from `source_queue` to the
+    /// dead-letter queue, keeping the raw string and the parse error.
+    async fn quarantine(
+        &self,
+        conn: &mut PooledConnection<'_, RedisConnectionManager>,
+        source_queue: &str,
+        raw: &str,
+        error: String,
+    ) -> Result<()> {
+        let quarantined = QuarantinedJob {
+            raw: raw.to_string(),
+            error,
+            source_queue: source_queue.to_string(),
+            quarantined_at: Utc::now(),
+        };
+        let dlq_json =
+            serde_json::to_string(&quarantined).context("Failed to serialize quarantined job")?;
+
+        conn.zrem::<_, _, ()>(source_queue, raw)
+            .await
+            .context("Failed to remove malformed job")?;
+        self.push_dead_letter(conn, &dlq_json).await?;
+
+        warn!(
+            "Quarantined malformed entry from {}: {}",
+            source_queue, quarantined.error
+        );
+        Ok(())
+    }
+
+    /// Append to the dead-letter queue, dropping the oldest entry when full.
+    async fn push_dead_letter(
+        &self,
+        conn: &mut PooledConnection<'_, RedisConnectionManager>,
+        dlq_json: &str,
+    ) -> Result<()> {
         // Check dead letter queue size
         let current_size: usize = conn
             .llen(DEAD_LETTER_QUEUE)
@@ -237,21 +713,9 @@ impl JobQueue {
                 .context("Failed to remove oldest dead letter job")?;
         }
 
-        conn.rpush::<_, _, ()>(DEAD_LETTER_QUEUE, &dlq_json)
+        conn.rpush::<_, _, ()>(DEAD_LETTER_QUEUE, dlq_json)
             .await
             .context("Failed to add job to dead letter queue")?;
-
-        // Remove from processing queue
-        let original_json =
-            serde_json::to_string(&job).context("Failed to serialize original job")?;
-        conn.zrem::<_, _, ()>(PROCESSING_QUEUE, &original_json)
-            .await
-            .context("Failed to remove job from processing queue")?;
-
-        error!(
-            "Job {} sent to dead letter queue after {} attempts",
-            job.id, total_attempts
-        );
         Ok(())
     }
 
@@ -267,10 +731,14 @@ impl JobQueue {
     pub async fn get_queue_stats(&self) -> Result<QueueStats> {
         let mut conn = self.pool.get().await?;
 
-        let main_queue_size: usize = conn
-            .zcard(DEFAULT_QUEUE)
-            .await
-            .context("Failed to get main queue size")?;
+        let mut main_queue_size = 0;
+        for job_type in &JobType::ALL {
+            let size: usize = conn
+                .zcard(ready_queue(job_type))
+                .await
+                .context("Failed to get main queue size")?;
+            main_queue_size += size;
+        }
 
         let processing_size: usize = conn
             .zcard(PROCESSING_QUEUE)
@@ -295,6 +763,66 @@ impl JobQueue {
         })
     }
 
+    /// Move dead-lettered jobs back onto the main queue.
+    ///
+    /// Only jobs of `job_type` are considered when a filter is given, and at
+    /// most `max` are replayed. Replayed jobs start over with a fresh retry
+    /// counter and are scheduled immediately.
+    pub async fn replay_dead_letters(
+        &self,
+        job_type: Option<&JobType>,
+        max: usize,
+    ) -> Result<ReplayOutcome> {
+        let mut conn = self.pool.get().await?;
+
+        let entries: Vec<String> = conn
+            .lrange(DEAD_LETTER_QUEUE, 0, -1)
+            .await
+            .context("Failed to read dead letter queue")?;
+
+        let mut replayed = 0;
+        for (raw, job) in select_for_replay(&entries, job_type, max) {
+            // Skip entries another replay already removed.
+            let removed: usize = conn
+                .lrem(DEAD_LETTER_QUEUE, 1, &raw)
+                .await
+                .context("Failed to remove job from dead letter queue")?;
+            if removed == 0 {
+                continue;
+            }
+
+            let job_json = serde_json::to_string(&job).context("Failed to serialize job")?;
+            conn.zadd::<_, _, _, ()>(
+                ready_queue(&job.job_type),
+                &job_json,
+                Utc::now().timestamp(),
+            )
+            .await
+            .context("Failed to requeue dead letter job")?;
+            Self::wake_workers(&mut *conn, &job.job_type).await?;
+            replayed += 1;
+        }
+
+        let remaining: usize = conn
+            .llen(DEAD_LETTER_QUEUE)
+            .await
+            .context("Failed to check dead letter queue size")?;
+
+        info!(
+            "Replayed {} dead letter jobs, {} remaining",
+            replayed, remaining
+        );
+        Ok(ReplayOutcome {
+            replayed,
+            remaining,
+        })
+    }
+
+    /// Return jobs whose visibility window has passed to the main queue.
+    ///
+    /// A job whose worker is still extending its heartbeat is left alone,
+    /// however long it has run; only jobs whose heartbeat has expired (or
+    /// that never had one) are reclaimed.
     pub async fn reclaim_stalled_jobs(&self) -> Result<usize> {
         let mut conn = self.pool.get().await?;
 
@@ -307,8 +835,26 @@ impl JobQueue {
         let mut reclaimed_count = 0;
 
         for job_json in stalled_jobs {
-            let job: JobPayload =
-                serde_json::from_str(&job_json).context("Failed to deserialize stalled job")?;
+            let job: JobPayload = match serde_json::from_str(&job_json) {
+                Ok(job) => job,
+                Err(e) => {
+                    self.quarantine(&mut conn, PROCESSING_QUEUE, &job_json, e.to_string())
+                        .await?;
+                    continue;
+                }
+            };
+
+            let alive: bool = conn
+                .exists(heartbeat_key(job.id))
+                .await
+                .context("Failed to check job heartbeat")?;
+            if alive {
+                debug!(
+                    "Job {} is past its window but still being processed",
+                    job.id
+                );
+                continue;
+            }
 
             // Remove from processing queue
             conn.zrem::<_, _, ()>(PROCESSING_QUEUE, &job_json)
@@ -317,9 +863,10 @@ impl JobQueue {
 
             // Add back to main queue
             let score = job.scheduled_at.unwrap_or_else(Utc::now).timestamp();
-            conn.zadd::<_, _, _, ()>(DEFAULT_QUEUE, &job_json, score)
+            conn.zadd::<_, _, _, ()>(ready_queue(&job.job_type), &job_json, score)
                 .await
                 .context("Failed to requeue stalled job")?;
+            Self::wake_workers(&mut *conn, &job.job_type).await?;
 
             reclaimed_count += 1;
             warn!("Reclaimed stalled job {}", job.id);
@@ -333,6 +880,57 @@ impl JobQueue {
     }
 }
 
+/// The head `(queue, raw, score)` due first. Ties go to the type listed
+/// first in [`JobType::ALL`].
+fn earliest(heads: Vec<(String, String, f64)>) -> Option<(String, String, f64)> {
+    heads
+        .into_iter()
+        .reduce(|best, head| if head.2 < best.2 { head } else { best })
+}
+
+/// Pick up to `max` dead-letter entries matching `job_type`, paired with the
+/// job to re-enqueue. Entries that fail to parse are left where they are.
+fn select_for_replay(
+    entries: &[String],
+    job_type: Option<&JobType>,
+    max: usize,
+) -> Vec<(String, JobPayload)> {
+    entries
+        .iter()
+        .filter_map(|raw| {
+            let dead: DeadLetterJob = serde_json::from_str(raw).ok()?;
+            Some((raw.clone(), dead.original_job))
+        })
+        .filter(|(_, job)| job_type.is_none_or(|t| &job.job_type == t))
+        .take(max)
+        .map(|(raw, mut job)| {
+            job.retries = None;
+            job.scheduled_at = None;
+            (raw, job)
+        })
+        .collect()
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ReplayOutcome {
+    pub replayed: usize,
+    pub remaining: usize,
+}
+
+/// Something jobs can be submitted to. Lets producers outside the HTTP layer
+/// enqueue work without holding a concrete [`JobQueue`].
+#[async_trait]
+pub trait JobEnqueuer: Send + Sync {
+    async fn enqueue(&self, job: JobPayload) -> Result<()>;
+}
+
+#[async_trait]
+impl JobEnqueuer for JobQueue {
+    async fn enqueue(&self, job: JobPayload) -> Result<()> {
+        JobQueue::enqueue(self, job).await
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct QueueStats {
     pub main_queue_size: usize,
@@ -340,3 +938,227 @@ pub struct QueueStats {
     pub retry_size: usize,
     pub dead_letter_size: usize,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn dead_letter(job_type: JobType) -> String {
+        let mut job = JobPayload::new(job_type, HashMap::new(), Some(3));
+        job.scheduled_at = Some(Utc::now());
+        serde_json::to_string(&DeadLetterJob {
+            original_job: job,
+            error: "provider outage".to_string(),
+            failed_at: Utc::now(),
+            total_attempts: 3,
+        })
+        .unwrap()
+    }
+
+    #[test]
+    fn replay_filters_by_job_type() {
+        let entries = vec![
+            dead_letter(JobType::Email),
+            dead_letter(JobType::Sync),
+            dead_letter(JobType::Email),
+        ];
+
+        let selected = select_for_replay(&entries, Some(&JobType::Email), 10);
+
+        assert_eq!(selected.len(), 2);
+        assert!(selected.iter().all(|(_, j)| j.job_type == JobType::Email));
+        assert_eq!(selected[0].0, entries[0]);
+        assert_eq!(selected[1].0, entries[2]);
+    }
+
+    #[test]
+    fn replay_respects_max() {
+        let entries: Vec<String> = (0..5).map(|_| dead_letter(JobType::Email)).collect();
+
+        let selected = select_for_replay(&entries, None, 3);
+
+        assert_eq!(selected.len(), 3);
+        assert_eq!(selected[2].0, entries[2]);
+    }
+
+    #[test]
+    fn replay_resets_retry_state() {
+        let entries = vec![dead_letter(JobType::Notification)];
+
+        let (_, job) = &select_for_replay(&entries, None, 1)[0];
+
+        assert_eq!(job.retries, None);
+        assert_eq!(job.scheduled_at, None);
+    }
+
+    #[test]
+    fn replay_skips_unparseable_entries() {
+        let entries = vec!["not json".to_string(), dead_letter(JobType::Sync)];
+
+        let selected = select_for_replay(&entries, None, 10);
+
+        assert_eq!(selected.len(), 1);
+        assert_eq!(selected[0].0, entries[1]);
+    }
+
+    /// Global window of one minute, with blockchain transactions allowed half an hour.
+    fn config_with_long_blockchain_jobs() -> QueueConfig {
+        QueueConfig {
+            visibility_timeout: Duration::from_secs(60),
+            visibility_timeouts: HashMap::from([(
+                JobType::BlockchainTx,
+                Duration::from_secs(1800),
+            )]),
+            ..QueueConfig::default()
+        }
+    }
+
+    #[test]
+    fn visibility_timeout_falls_back_to_global() {
+        let config = config_with_long_blockchain_jobs();
+
+        assert_eq!(
+            config.visibility_timeout_for(&JobType::BlockchainTx),
+            Duration::from_secs(1800)
+        );
+        assert_eq!(
+            config.visibility_timeout_for(&JobType::Email),
+            Duration::from_secs(60)
+        );
+    }
+
+    #[test]
+    fn long_job_type_outlives_global_window() {
+        let config = config_with_long_blockchain_jobs();
+        let dequeued_at = Utc::now();
+        // What `reclaim_stalled_jobs` would see just after the global window.
+        let reclaim_at = dequeued_at + chrono::Duration::seconds(61);
+
+        let blockchain = config.processing_deadline(&JobType::BlockchainTx, dequeued_at);
+        let email = config.processing_deadline(&JobType::Email, dequeued_at);
+
+        assert!(
+            blockchain > reclaim_at,
+            "long job must not be reclaimed yet"
+        );
+        assert!(email <= reclaim_at, "short job must be reclaimed");
+    }
+
+    #[test]
+    fn parses_snake_case_overrides() {
+        let overrides = HashMap::from([
+            ("blockchain_tx".to_string(), 1800),
+            ("email".to_string(), 10),
+        ]);
+
+        let parsed = parse_visibility_timeouts(&overrides).unwrap();
+
+        assert_eq!(parsed[&JobType::BlockchainTx], Duration::from_secs(1800));
+        assert_eq!(parsed[&JobType::Email], Duration::from_secs(10));
+    }
+
+    #[test]
+    fn rejects_unknown_job_type_override() {
+        let overrides = HashMap::from([("fax".to_string(), 10)]);
+
+        assert!(parse_visibility_timeouts(&overrides).is_err());
+    }
+
+    /// Failed attempts before `retry_job` dead-letters a job of `job_type`.
+    fn attempts_before_dead_letter(config: &QueueConfig, job_type: &JobType) -> u32 {
+        let mut attempt = 1;
+        while !config.exhausted(job_type, attempt) {
+            attempt += 1;
+        }
+        attempt
+    }
+
+    #[test]
+    fn job_type_override_gets_more_retries() {
+        let overrides = HashMap::from([("webhook".to_string(), 6)]);
+        let config = QueueConfig {
+            max_retries: 3,
+            max_retries_by_type: parse_max_retries(&overrides).unwrap(),
+            ..QueueConfig::default()
+        };
+
+        assert_eq!(attempts_before_dead_letter(&config, &JobType::Webhook), 6);
+        assert_eq!(attempts_before_dead_letter(&config, &JobType::Email), 3);
+        assert!(parse_max_retries(&HashMap::from([("fax".to_string(), 6)])).is_err());
+    }
+
+    #[test]
+    fn rejects_zero_visibility_override() {
+        let overrides = HashMap::from([("email".to_string(), 0)]);
+
+        assert!(parse_visibility_timeouts(&overrides).is_err());
+    }
+
+    #[test]
+    fn heartbeat_outpaces_visibility_window() {
+        let config = config_with_long_blockchain_jobs();
+
+        assert_eq!(
+            config.heartbeat_interval_for(&JobType::Email),
+            Duration::from_secs(20)
+        );
+        assert_eq!(
+            config.heartbeat_interval_for(&JobType::BlockchainTx),
+            Duration::from_secs(600)
+        );
+    }
+
+    #[test]
+    fn dequeue_takes_the_head_due_first() {
+        let head = |queue: &str, score| (queue.to_string(), format!("{}-job", queue), score);
+
+        let (queue, _, _) = earliest(vec![
+            head("email", 30.0),
+            head("sync", 10.0),
+            head("audit", 20.0),
+        ])
+        .unwrap();
+        assert_eq!(queue, "sync");
+
+        let (queue, _, _) = earliest(vec![head("email", 10.0), head("sync", 10.0)]).unwrap();
+        assert_eq!(queue, "email");
+
+        assert!(earliest(Vec::new()).is_none());
+    }
+
+    #[test]
+    fn parses_worker_pools() {
+        let pools = HashMap::from([("email".to_string(), 2), ("blockchain_tx".to_string(), 1)]);
+
+        let parsed = parse_worker_pools(&pools).unwrap();
+
+        assert_eq!(parsed[&JobType::Email], 2);
+        assert_eq!(parsed[&JobType::BlockchainTx], 1);
+        assert!(parse_worker_pools(&HashMap::from([("fax".to_string(), 1)])).is_err());
+        assert!(parse_worker_pools(&HashMap::from([("email".to_string(), 0)])).is_err());
+    }
+
+    #[test]
+    fn blpop_never_blocks_past_the_deadline() {
+        assert_eq!(blpop_timeout(Duration::from_millis(999)), None);
+        assert_eq!(blpop_timeout(Duration::from_millis(1000)), Some(1));
+        assert_eq!(blpop_timeout(Duration::from_millis(4999)), Some(4));
+        assert_eq!(blpop_timeout(Duration::ZERO), None);
+    }
+
+    #[test]
+    fn typed_keys_use_snake_case_names() {
+        assert_eq!(
+            typed_wakeup_list(&JobType::BlockchainTx),
+            "zaps:jobs:wakeup:blockchain_tx"
+        );
+        assert_eq!(
+            ready_queue(&JobType::DigestNotification),
+            "zaps:jobs:queue:digest_notification"
+        );
+        // Every type has a queue of its own, none of them the legacy one
+        let queues: std::collections::HashSet<_> = JobType::ALL.iter().map(ready_queue).collect();
+        assert_eq!(queues.len(), JobType::ALL.len());
+        assert!(!queues.contains(LEGACY_QUEUE));
+    }
+}
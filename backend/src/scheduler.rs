@@ -0,0 +1,415 @@
+//! Recurring jobs enqueued on a cron schedule.
+//!
+//! Jobs are declared under `[[queue.recurring_jobs]]` and fired by the
+//! [`RecurringScheduler`] that `JobWorker` runs alongside its workers. Every
+//! replica runs a scheduler; a per-tick Redis lock makes sure only one of them
+//! enqueues each firing. Built-in periodic tasks such as the registry sync are
+//! scheduled the same way, from their own config sections.
+use crate::job_processors::MAINTENANCE_TASK_FIELD;
+use crate::job_types::{JobPayload, JobType};
+use crate::queue::{JobEnqueuer, JobQueue};
+use anyhow::{bail, Context, Result};
+use async_trait::async_trait;
+use chrono::{DateTime, Datelike, DurationRound, Timelike, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::str::FromStr;
+use std::time::Duration;
+use tokio_util::sync::CancellationToken;
+use tracing::{error, info};
+
+const TICK_LOCK_PREFIX: &str = "zaps:jobs:recurring";
+/// How long a tick's lock is held. It only needs to outlast clock skew
+/// between replicas, since every tick has its own key.
+const TICK_LOCK_TTL: Duration = Duration::from_secs(600);
+
+/// A job to enqueue whenever `cron_expr` fires.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RecurringJob {
+    /// Unique name; part of the lock key that deduplicates firings.
+    pub name: String,
+    pub job_type: JobType,
+    #[serde(default)]
+    pub payload: HashMap<String, serde_json::Value>,
+    /// Standard five-field cron expression (`minute hour day month weekday`),
+    /// evaluated in UTC.
+    pub cron_expr: String,
+}
+
+impl RecurringJob {
+    /// A `Maintenance` job running the built-in `task` on `cron_expr`.
+    pub fn maintenance(task: &str, cron_expr: &str) -> Self {
+        Self {
+            name: task.to_string(),
+            job_type: JobType::Maintenance,
+            payload: HashMap::from([(
+                MAINTENANCE_TASK_FIELD.to_string(),
+                serde_json::Value::from(task),
+            )]),
+            cron_expr: cron_expr.to_string(),
+        }
+    }
+}
+
+/// A parsed five-field cron expression.
+///
+/// Each field accepts `*`, single values, ranges (`1-5`), lists (`1,15`) and
+/// steps (`*/15`, `0-30/10`). As in cron, when both day-of-month and
+/// day-of-week are restricted a time matches if either does.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CronSchedule {
+    minutes: u64,
+    hours: u64,
+    days_of_month: u64,
+    months: u64,
+    days_of_week: u64,
+    day_of_month_restricted: bool,
+    day_of_week_restricted: bool,
+}
+
+impl CronSchedule {
+    /// Whether the schedule fires during the minute containing `at`.
+    pub fn matches(&self, at: DateTime<Utc>) -> bool {
+        let bit = |set: u64, value: u32| set & (1 << value) != 0;
+
+        let day_of_month = bit(self.days_of_month, at.day());
+        let day_of_week = bit(self.days_of_week, at.weekday().num_days_from_sunday());
+        let day = match (self.day_of_month_restricted, self.day_of_week_restricted) {
+            (true, true) => day_of_month || day_of_week,
+            _ => day_of_month && day_of_week,
+        };
+
+        bit(self.minutes, at.minute())
+            && bit(self.hours, at.hour())
+            && bit(self.months, at.month())
+            && day
+    }
+}
+
+impl FromStr for CronSchedule {
+    type Err = anyhow::Error;
+
+    fn from_str(expr: &str) -> Result<Self> {
+        let fields: Vec<&str> = expr.split_whitespace().collect();
+        let [minute, hour, day_of_month, month, day_of_week] = fields[..] else {
+            bail!("cron expression must have 5 fields: {:?}", expr);
+        };
+
+        // Sunday may be written as 0 or 7.
+        let mut days_of_week = parse_field(day_of_week, 0, 7)?;
+        if days_of_week & (1 << 7) != 0 {
+            days_of_week = (days_of_week | 1) & !(1 << 7);
+        }
+
+        Ok(Self {
+            minutes: parse_field(minute, 0, 59)?,
+            hours: parse_field(hour, 0, 23)?,
+            days_of_month: parse_field(day_of_month, 1, 31)?,
+            months: parse_field(month, 1, 12)?,
+            days_of_week,
+            day_of_month_restricted: day_of_month != "*",
+            day_of_week_restricted: day_of_week != "*",
+        })
+    }
+}
+
+/// Parse one cron field into a bit set of the values it allows.
+fn parse_field(field: &str, min: u32, max: u32) -> Result<u64> {
+    let mut set = 0u64;
+
+    for part in field.split(',') {
+        let (range, step) = match part.split_once('/') {
+            Some((range, step)) => {
+                let step: u32 = step
+                    .parse()
+                    .with_context(|| format!("invalid cron step {:?}", part))?;
+                if step == 0 {
+                    bail!("cron step must be positive: {:?}", part);
+                }
+                (range, Some(step))
+            }
+            None => (part, None),
+        };
+
+        let (start, end) = if range == "*" {
+            (min, max)
+        } else if let Some((start, end)) = range.split_once('-') {
+            (parse_value(start, min, max)?, parse_value(end, min, max)?)
+        } else {
+            let start = parse_value(range, min, max)?;
+            // `5/10` means "from 5, every 10".
+            (start, if step.is_some() { max } else { start })
+        };
+        if start > end {
+            bail!("cron range is reversed: {:?}", part);
+        }
+
+        for value in (start..=end).step_by(step.unwrap_or(1) as usize) {
+            set |= 1 << value;
+        }
+    }
+
+    Ok(set)
+}
+
+fn parse_value(value: &str, min: u32, max: u32) -> Result<u32> {
+    let parsed: u32 = value
+        .parse()
+        .with_context(|| format!("invalid cron value {:?}", value))?;
+    if !(min..=max).contains(&parsed) {
+        bail!("cron value {} outside {}-{}", parsed, min, max);
+    }
+    Ok(parsed)
+}
+
+/// Claims a scheduler tick so only one replica acts on it.
+#[async_trait]
+pub trait TickLock: Send + Sync {
+    /// Returns `true` if this caller now holds `key`.
+    async fn try_acquire(&self, key: &str, ttl: Duration) -> Result<bool>;
+}
+
+#[async_trait]
+impl TickLock for JobQueue {
+    async fn try_acquire(&self, key: &str, ttl: Duration) -> Result<bool> {
+        self.try_lock(key, ttl).await
+    }
+}
+
+/// Enqueues [`RecurringJob`]s when their schedules fire.
+pub struct RecurringScheduler {
+    jobs: Vec<(RecurringJob, CronSchedule)>,
+}
+
+impl RecurringScheduler {
+    /// Parse every job's cron expression, failing on the first invalid one.
+    pub fn new(jobs: Vec<RecurringJob>) -> Result<Self> {
+        let jobs = jobs
+            .into_iter()
+            .map(|job| {
+                let schedule = job
+                    .cron_expr
+                    .parse()
+                    .with_context(|| format!("recurring job {:?}", job.name))?;
+                Ok((job, schedule))
+            })
+            .collect::<Result<_>>()?;
+        Ok(Self { jobs })
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.jobs.is_empty()
+    }
+
+    /// Enqueue every job due in the minute containing `tick`, skipping those
+    /// another replica already claimed. Returns how many were enqueued.
+    pub async fn fire_due(
+        &self,
+        tick: DateTime<Utc>,
+        lock: &dyn TickLock,
+        queue: &dyn JobEnqueuer,
+    ) -> Result<usize> {
+        let minute = tick.duration_trunc(chrono::Duration::minutes(1))?;
+        let mut enqueued = 0;
+
+        for (job, schedule) in &self.jobs {
+            if !schedule.matches(minute) {
+                continue;
+            }
+
+            let key = format!("{}:{}:{}", TICK_LOCK_PREFIX, job.name, minute.timestamp());
+            if !lock.try_acquire(&key, TICK_LOCK_TTL).await? {
+                continue;
+            }
+
+            let payload = JobPayload::new(job.job_type.clone(), job.payload.clone(), None);
+            match queue.enqueue(payload).await {
+                Ok(()) => {
+                    enqueued += 1;
+                    info!("Enqueued recurring job {}", job.name);
+                }
+                Err(e) => error!("Failed to enqueue recurring job {}: {}", job.name, e),
+            }
+        }
+
+        Ok(enqueued)
+    }
+
+    /// Fire due jobs at the start of every minute until `shutdown`.
+    pub async fn run(
+        &self,
+        lock: &dyn TickLock,
+        queue: &dyn JobEnqueuer,
+        shutdown: CancellationToken,
+    ) {
+        loop {
+            let now = Utc::now();
+            let next_minute = now
+                .duration_trunc(chrono::Duration::minutes(1))
+                .unwrap_or(now)
+                + chrono::Duration::minutes(1);
+            let wait = (next_minute - now).to_std().unwrap_or_default();
+
+            tokio::select! {
+                _ = shutdown.cancelled() => break,
+                _ = tokio::time::sleep(wait) => {}
+            }
+            if let Err(e) = self.fire_due(next_minute, lock, queue).await {
+                error!("Recurring job scheduler tick failed: {}", e);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+    use std::collections::HashSet;
+    use std::sync::Mutex;
+
+    /// Stand-in for the Redis lock shared by every replica.
+    #[derive(Default)]
+    struct MemoryLock {
+        held: Mutex<HashSet<String>>,
+    }
+
+    #[async_trait]
+    impl TickLock for MemoryLock {
+        async fn try_acquire(&self, key: &str, _ttl: Duration) -> Result<bool> {
+            Ok(self.held.lock().unwrap().insert(key.to_string()))
+        }
+    }
+
+    #[derive(Default)]
+    struct RecordingQueue {
+        jobs: Mutex<Vec<JobPayload>>,
+    }
+
+    #[async_trait]
+    impl JobEnqueuer for RecordingQueue {
+        async fn enqueue(&self, job: JobPayload) -> Result<()> {
+            self.jobs.lock().unwrap().push(job);
+            Ok(())
+        }
+    }
+
+    fn at(hour: u32, minute: u32, second: u32) -> DateTime<Utc> {
+        // 2026-10-16 is a Friday.
+        Utc.with_ymd_and_hms(2026, 10, 16, hour, minute, second)
+            .unwrap()
+    }
+
+    fn recurring(name: &str, cron_expr: &str) -> RecurringJob {
+        RecurringJob {
+            name: name.to_string(),
+            job_type: JobType::Sync,
+            payload: HashMap::new(),
+            cron_expr: cron_expr.to_string(),
+        }
+    }
+
+    #[test]
+    fn parses_steps_ranges_and_lists() {
+        let schedule: CronSchedule = "*/15 9-17 * * 1-5".parse().unwrap();
+
+        assert!(schedule.matches(at(9, 0, 0)));
+        assert!(schedule.matches(at(17, 45, 0)));
+        assert!(!schedule.matches(at(9, 10, 0)));
+        assert!(!schedule.matches(at(18, 0, 0)));
+
+        let saturday = Utc.with_ymd_and_hms(2026, 10, 17, 9, 0, 0).unwrap();
+        assert!(!schedule.matches(saturday));
+
+        let listed: CronSchedule = "0,30 0 1 * *".parse().unwrap();
+        let first = Utc.with_ymd_and_hms(2026, 11, 1, 0, 30, 0).unwrap();
+        assert!(listed.matches(first));
+    }
+
+    #[test]
+    fn sunday_can_be_written_as_seven() {
+        let schedule: CronSchedule = "0 0 * * 7".parse().unwrap();
+        let sunday = Utc.with_ymd_and_hms(2026, 10, 18, 0, 0, 0).unwrap();
+
+        assert!(schedule.matches(sunday));
+    }
+
+    #[test]
+    fn restricted_day_fields_match_either() {
+        // The 1st of the month or any Friday.
+        let schedule: CronSchedule = "0 0 1 * 5".parse().unwrap();
+        let friday = at(0, 0, 0);
+        let thursday_first = Utc.with_ymd_and_hms(2026, 10, 1, 0, 0, 0).unwrap();
+        let saturday = Utc.with_ymd_and_hms(2026, 10, 17, 0, 0, 0).unwrap();
+
+        assert!(schedule.matches(friday));
+        assert!(schedule.matches(thursday_first));
+        assert!(!schedule.matches(saturday));
+    }
+
+    #[test]
+    fn rejects_invalid_expressions() {
+        for expr in [
+            "* * * *",
+            "60 * * * *",
+            "*/0 * * * *",
+            "5-1 * * * *",
+            "a * * * *",
+        ] {
+            assert!(expr.parse::<CronSchedule>().is_err(), "{}", expr);
+        }
+    }
+
+    #[tokio::test]
+    async fn every_minute_job_enqueues_once_per_minute_across_replicas() {
+        let scheduler = RecurringScheduler::new(vec![recurring("flush", "* * * * *")]).unwrap();
+        let lock = MemoryLock::default();
+        let queue = RecordingQueue::default();
+
+        for minute in 0..5 {
+            // Three replicas tick a few seconds apart within the same minute.
+            for second in [0, 2, 7] {
+                scheduler
+                    .fire_due(at(12, minute, second), &lock, &queue)
+                    .await
+                    .unwrap();
+            }
+        }
+
+        let jobs = queue.jobs.lock().unwrap();
+        assert_eq!(jobs.len(), 5);
+        assert!(jobs.iter().all(|j| j.job_type == JobType::Sync));
+    }
+
+    #[tokio::test]
+    async fn only_due_jobs_fire() {
+        let scheduler = RecurringScheduler::new(vec![
+            recurring("often", "*/2 * * * *"),
+            recurring("hourly", "0 * * * *"),
+        ])
+        .unwrap();
+        let lock = MemoryLock::default();
+        let queue = RecordingQueue::default();
+
+        let mut fired = Vec::new();
+        for minute in 0..4 {
+            fired.push(
+                scheduler
+                    .fire_due(at(12, minute, 0), &lock, &queue)
+                    .await
+                    .unwrap(),
+            );
+        }
+
+        assert_eq!(fired, vec![2, 0, 1, 0]);
+    }
+
+    #[test]
+    fn invalid_cron_fails_construction() {
+        let err = RecurringScheduler::new(vec![recurring("broken", "every minute")])
+            .err()
+            .unwrap();
+
+        assert!(format!("{:#}", err).contains("broken"));
+    }
+}
@@ -5,14 +5,36 @@
 /// - SEP-12  : KYC data exchange — used here to check a user's `"CLEARED"` status.
 /// - SEP-24  : Interactive withdrawal — Anchor hosts a UI; we obtain a signed URL for the user.
 /// - SEP-31  : Cross-border payment — backend-to-backend POST directly to the Anchor.
-use crate::{api_error::ApiError, config::Config};
-use base64::{engine::general_purpose::STANDARD as B64, Engine as _};
-use deadpool_postgres::Pool;
-use reqwest::Client;
+use crate::{
+    api_error::ApiError,
+    assets::asset_info,
+    config::{Config, ReputationTier, WithdrawalLimit},
+    http_client,
+    job_processors::{withdrawal_notification_job, MaintenanceTask},
+    models::CreateAuditLogParams,
+    queue::JobEnqueuer,
+    service::audit_service::append_audit_log,
+    service::circuit_breaker::{BreakerState, CircuitBreaker},
+    service::memo_policy_service::MAX_TEXT_MEMO_BYTES,
+    service::{BalanceService, MetricsService},
+};
+use async_trait::async_trait;
+use base64::{
+    engine::general_purpose::{STANDARD as B64, URL_SAFE_NO_PAD},
+    Engine as _,
+};
+use deadpool_postgres::{Pool, Transaction};
+use futures::stream::{self, StreamExt};
+use reqwest::{
+    header::{HeaderMap, RETRY_AFTER},
+    Client, RequestBuilder, Response, StatusCode,
+};
 use ring::hmac;
 use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
 use std::sync::Arc;
-use tracing::{error, info, warn};
+use std::time::{Duration, Instant};
+use tracing::{debug, error, info, warn};
 use uuid::Uuid;
 
 // ──────────────────────────────────────────────────────────────────────────────
@@ -53,6 +75,68 @@ pub struct Sep24InteractiveResponse {
     pub anchor_tx_id: String,
 }
 
+/// Memo types a SEP-31 receiver may require on the payment.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum MemoType {
+    /// Up to 28 bytes of text.
+    #[default]
+    Text,
+    /// An unsigned 64-bit integer, in decimal.
+    Id,
+    /// 32 bytes, base64-encoded as in SEP-31 responses.
+    Hash,
+}
+
+impl MemoType {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            MemoType::Text => "text",
+            MemoType::Id => "id",
+            MemoType::Hash => "hash",
+        }
+    }
+
+    /// Check that `memo` is a valid value of this type.
+    pub fn validate(self, memo: &str) -> Result<(), ApiError> {
+        let valid = match self {
+            MemoType::Text => memo.len() <= MAX_TEXT_MEMO_BYTES,
+            MemoType::Id => !memo.starts_with('+') && memo.parse::<u64>().is_ok(),
+            MemoType::Hash => B64.decode(memo).is_ok_and(|hash| hash.len() == 32),
+        };
+        if valid {
+            return Ok(());
+        }
+
+        let expected = match self {
+            MemoType::Text => "at most 28 bytes of text",
+            MemoType::Id => "an unsigned 64-bit integer",
+            MemoType::Hash => "32 base64-encoded bytes",
+        };
+        Err(ApiError::Validation(format!(
+            "{} memos must be {}",
+            self.as_str(),
+            expected
+        )))
+    }
+}
+
+impl std::str::FromStr for MemoType {
+    type Err = ApiError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "text" => Ok(MemoType::Text),
+            "id" => Ok(MemoType::Id),
+            "hash" => Ok(MemoType::Hash),
+            other => Err(ApiError::Validation(format!(
+                "Unsupported memo_type {:?}; expected text, id or hash",
+                other
+            ))),
+        }
+    }
+}
+
 /// Parameters required to initiate a SEP-31 cross-border payout.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Sep31PayoutParams {
@@ -64,6 +148,9 @@ pub struct Sep31PayoutParams {
     pub receiver_id: String,
     /// Optional memo to attach to the Stellar transaction
     pub memo: Option<String>,
+    /// How `memo` is encoded; ignored without a memo.
+    #[serde(default)]
+    pub memo_type: MemoType,
 }
 
 /// Response from `POST /transactions` (SEP-31).
@@ -95,6 +182,202 @@ pub enum AnchorTxStatus {
     Error,
 }
 
+impl AnchorTxStatus {
+    /// Parse a SEP-24/SEP-31 status string; unknown values map to `Incomplete`.
+    pub fn from_anchor(status: &str) -> Self {
+        match status {
+            "pending_stellar" => AnchorTxStatus::PendingStellar,
+            "pending_anchor" => AnchorTxStatus::PendingAnchor,
+            "pending_external" => AnchorTxStatus::PendingExternal,
+            "pending_user" => AnchorTxStatus::PendingUser,
+            "pending_user_transfer_start" => AnchorTxStatus::PendingUserTransferStart,
+            "completed" => AnchorTxStatus::Completed,
+            "refunded" => AnchorTxStatus::Refunded,
+            "expired" => AnchorTxStatus::Expired,
+            "error" => AnchorTxStatus::Error,
+            _ => AnchorTxStatus::Incomplete,
+        }
+    }
+
+    /// The internal withdrawal status this anchor status maps to. Deposits
+    /// use the same status values.
+    pub fn withdrawal_status(&self) -> &'static str {
+        match self {
+            AnchorTxStatus::Completed => "completed",
+            AnchorTxStatus::Error | AnchorTxStatus::Expired => "failed",
+            AnchorTxStatus::Refunded => "refunded",
+            AnchorTxStatus::PendingStellar
+            | AnchorTxStatus::PendingAnchor
+            | AnchorTxStatus::PendingExternal
+            | AnchorTxStatus::PendingUser
+            | AnchorTxStatus::PendingUserTransferStart => "processing",
+            AnchorTxStatus::Incomplete => "pending",
+        }
+    }
+}
+
+/// Withdrawal statuses that will never change again.
+pub const TERMINAL_WITHDRAWAL_STATUSES: [&str; 3] = ["completed", "failed", "refunded"];
+
+/// Statuses a withdrawal may move to `status` from. Withdrawals only move
+/// forward — `pending`, then `processing`, then a terminal status — so an
+/// update that arrives late can't undo a newer one.
+pub fn withdrawal_status_predecessors(status: &str) -> &'static [&'static str] {
+    match status {
+        "processing" => &["pending"],
+        "completed" | "failed" | "refunded" => &["pending", "processing"],
+        _ => &[],
+    }
+}
+
+/// Counts from a single reconciliation run.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ReconcileSummary {
+    /// Withdrawals polled at the anchor.
+    pub checked: usize,
+    /// Withdrawals whose status changed.
+    pub updated: usize,
+    /// Withdrawals the anchor could not be polled for.
+    pub failed: usize,
+    /// Users notified about a withdrawal reaching a terminal status.
+    pub notified: usize,
+}
+
+/// A withdrawal status change applied by [`AnchorService::apply_withdrawal_status`].
+#[derive(Debug, Clone)]
+pub struct WithdrawalTransition {
+    pub previous_status: Option<String>,
+    pub record: WithdrawalRecord,
+}
+
+impl WithdrawalTransition {
+    /// Whether this change moved the withdrawal into a terminal status.
+    pub fn reached_terminal(&self) -> bool {
+        let was_terminal = self
+            .previous_status
+            .as_deref()
+            .is_some_and(|s| TERMINAL_WITHDRAWAL_STATUSES.contains(&s));
+        !was_terminal && TERMINAL_WITHDRAWAL_STATUSES.contains(&self.record.status.as_str())
+    }
+}
+
+/// A deposit or withdrawal that an anchor transaction ID resolves to, by internal ID.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AnchorTransaction {
+    Deposit(String),
+    Withdrawal(String),
+}
+
+/// How long a webhook may sit unprocessed in the ledger before the reconciler
+/// assumes its delivery was interrupted and replays it.
+const WEBHOOK_REPLAY_GRACE: Duration = Duration::from_secs(60);
+
+/// A verified anchor webhook event, as recorded in the `anchor_webhooks` ledger.
+#[derive(Debug, Clone)]
+pub struct AnchorWebhookEvent {
+    /// The anchor's transaction ID (matches `anchor_tx_id` in our DB).
+    pub transaction_id: String,
+    /// Raw anchor status, e.g. `"completed"`.
+    pub status: String,
+    pub message: Option<String>,
+}
+
+/// What happened to a webhook handed to [`AnchorService::handle_webhook`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WebhookDisposition {
+    /// The status was synced to the matching transaction.
+    Processed,
+    /// No transaction has this `anchor_tx_id` yet; the entry stays pending
+    /// and is replayed, as the webhook can beat the record being stored.
+    Unmatched,
+    /// The same transaction and status was already handled.
+    Duplicate,
+}
+
+/// Ledger state of an entry after an attempt, given its attempt count so
+/// far. One still unresolved after `max_attempts` is dead-lettered.
+fn webhook_ledger_status(
+    outcome: &Result<WebhookDisposition, ApiError>,
+    attempts: i32,
+    max_attempts: i32,
+) -> &'static str {
+    let retry_status = match outcome {
+        Ok(WebhookDisposition::Processed | WebhookDisposition::Duplicate) => return "processed",
+        Ok(WebhookDisposition::Unmatched) => "pending",
+        Err(_) => "failed",
+    };
+    if attempts >= max_attempts {
+        "dead_letter"
+    } else {
+        retry_status
+    }
+}
+
+/// Counts from a single webhook replay run.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct WebhookReplaySummary {
+    /// Webhooks processed on replay.
+    pub replayed: usize,
+    /// Webhooks that failed again or are still unmatched, and remain in the
+    /// ledger.
+    pub failed: usize,
+    /// Webhooks that ran out of attempts on this run.
+    pub dead_lettered: usize,
+}
+
+/// Delay before re-polling a withdrawal that has gone `attempts` polls
+/// without a status change.
+fn reconcile_backoff(attempts: i32, base_seconds: u64, max_seconds: u64) -> Duration {
+    let exponent = attempts.clamp(0, 31) as u32;
+    let seconds = base_seconds
+        .saturating_mul(1u64 << exponent)
+        .min(max_seconds);
+    Duration::from_secs(seconds)
+}
+
+/// Reject a withdrawal amount outside the asset's configured bounds, naming
+/// them in display units.
+fn check_withdrawal_limit(
+    limit: WithdrawalLimit,
+    asset: &str,
+    amount: i64,
+) -> Result<(), ApiError> {
+    let info = asset_info(asset);
+    let out_of_range =
+        limit.min.is_some_and(|min| amount < min) || limit.max.is_some_and(|max| amount > max);
+    if !out_of_range {
+        return Ok(());
+    }
+
+    let bounds = match (limit.min, limit.max) {
+        (Some(min), Some(max)) => format!("between {} and {}", info.format(min), info.format(max)),
+        (Some(min), None) => format!("at least {}", info.format(min)),
+        (None, Some(max)) => format!("at most {}", info.format(max)),
+        (None, None) => unreachable!("an unbounded amount is never out of range"),
+    };
+    Err(ApiError::Validation(format!(
+        "Withdrawal amount must be {} {}",
+        bounds, asset
+    )))
+}
+
+/// Reject replaying `record` for a request carrying the same idempotency key
+/// but asking for a different withdrawal.
+fn check_replay(
+    record: &WithdrawalRecord,
+    params: &InitiateWithdrawalParams,
+) -> Result<(), ApiError> {
+    if record.amount == params.amount
+        && record.asset == params.asset
+        && record.destination_address == params.destination_address
+    {
+        return Ok(());
+    }
+    Err(ApiError::Conflict(
+        "Idempotency key was already used for a different withdrawal".to_string(),
+    ))
+}
+
 /// Lightweight DB model returned after DB operations.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct WithdrawalRecord {
@@ -107,6 +390,7 @@ pub struct WithdrawalRecord {
     pub anchor_tx_id: Option<String>,
     pub kyc_status: String,
     pub sep24_interactive_url: Option<String>,
+    pub idempotency_key: Option<String>,
     pub created_at: chrono::DateTime<chrono::Utc>,
     pub updated_at: chrono::DateTime<chrono::Utc>,
 }
@@ -121,12 +405,269 @@ pub struct CreateWithdrawalParams {
     pub anchor_tx_id: Option<String>,
     pub kyc_status: KycStatus,
     pub sep24_interactive_url: Option<String>,
+    /// Client-supplied key, unique per user.
+    pub idempotency_key: Option<String>,
+}
+
+/// Parameters for initiating a SEP-24 withdrawal on behalf of a user.
+#[derive(Debug, Clone)]
+pub struct InitiateWithdrawalParams {
+    pub user_id: String,
+    pub stellar_address: String,
+    pub destination_address: String,
+    pub amount: i64,
+    pub asset: String,
+    /// When set, repeat requests with the same key return the original
+    /// withdrawal instead of opening a new anchor transaction.
+    pub idempotency_key: Option<String>,
+    /// The user's reputation tier, whose limits and KYC requirement take
+    /// precedence over the anchor's.
+    pub reputation_tier: Option<ReputationTier>,
+}
+
+/// Outcome of [`AnchorService::initiate_withdrawal`].
+#[derive(Debug, Clone)]
+pub struct InitiatedWithdrawal {
+    pub record: WithdrawalRecord,
+    /// `true` when the record was created by an earlier request with the
+    /// same idempotency key.
+    pub replayed: bool,
+}
+
+/// Position in a user's withdrawal history, newest first.
+///
+/// Encoded for clients as an opaque URL-safe base64 string of
+/// `{created_at}|{id}`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WithdrawalCursor {
+    pub created_at: chrono::DateTime<chrono::Utc>,
+    pub id: Uuid,
+}
+
+impl WithdrawalCursor {
+    /// Cursor pointing just past `record`.
+    pub fn after(record: &WithdrawalRecord) -> Result<Self, ApiError> {
+        let id = Uuid::parse_str(&record.id).map_err(|e| {
+            error!(error = %e, "Withdrawal id is not a UUID");
+            ApiError::InternalServerError
+        })?;
+        Ok(Self {
+            created_at: record.created_at,
+            id,
+        })
+    }
+
+    pub fn encode(&self) -> String {
+        let raw = format!(
+            "{}|{}",
+            self.created_at
+                .to_rfc3339_opts(chrono::SecondsFormat::Micros, true),
+            self.id
+        );
+        URL_SAFE_NO_PAD.encode(raw)
+    }
+
+    pub fn decode(cursor: &str) -> Result<Self, ApiError> {
+        let invalid = || ApiError::Validation("Invalid cursor".to_string());
+
+        let raw = URL_SAFE_NO_PAD.decode(cursor).map_err(|_| invalid())?;
+        let raw = String::from_utf8(raw).map_err(|_| invalid())?;
+        let (created_at, id) = raw.split_once('|').ok_or_else(invalid)?;
+
+        Ok(Self {
+            created_at: chrono::DateTime::parse_from_rfc3339(created_at)
+                .map_err(|_| invalid())?
+                .with_timezone(&chrono::Utc),
+            id: Uuid::parse_str(id).map_err(|_| invalid())?,
+        })
+    }
+}
+
+/// One page of a user's withdrawal history.
+#[derive(Debug, Clone)]
+pub struct WithdrawalPage {
+    pub withdrawals: Vec<WithdrawalRecord>,
+    /// Cursor for the next page; `None` once the history is exhausted.
+    pub next_cursor: Option<WithdrawalCursor>,
+}
+
+const WITHDRAWAL_COLUMNS: &str = "id::text AS id, user_id, destination_address, amount, asset, \
+     status, anchor_tx_id, kyc_status, sep24_interactive_url, idempotency_key, \
+     created_at, updated_at";
+
+fn withdrawal_from_row(row: &tokio_postgres::Row) -> WithdrawalRecord {
+    WithdrawalRecord {
+        id: row.get("id"),
+        user_id: row.get("user_id"),
+        destination_address: row.get("destination_address"),
+        amount: row.get("amount"),
+        asset: row.get("asset"),
+        status: row.get("status"),
+        anchor_tx_id: row.get("anchor_tx_id"),
+        kyc_status: row.get("kyc_status"),
+        sep24_interactive_url: row.get("sep24_interactive_url"),
+        idempotency_key: row.get("idempotency_key"),
+        created_at: row.get("created_at"),
+        updated_at: row.get("updated_at"),
+    }
 }
 
 // ──────────────────────────────────────────────────────────────────────────────
 // Internal Anchor API shapes (minimal — we only deserialise what we need)
 // ──────────────────────────────────────────────────────────────────────────────
 
+/// The error body SEP-10/12/24/31 anchors send with a non-2xx response.
+#[derive(Debug, Deserialize)]
+struct AnchorErrorResponse {
+    error: String,
+}
+
+/// Move a withdrawal to `status` if it may still reach it, locking the row
+/// while its previous status is read.
+async fn transition_withdrawal(
+    tx: &Transaction<'_>,
+    withdrawal_id: &str,
+    status: &str,
+) -> Result<Option<WithdrawalTransition>, ApiError> {
+    let query = format!(
+        r#"
+        WITH previous AS (
+            SELECT id AS previous_id, status AS previous_status
+            FROM withdrawals
+            WHERE id = $2::text::uuid
+            FOR UPDATE
+        )
+        UPDATE withdrawals
+        SET status = $1, updated_at = NOW()
+        FROM previous
+        WHERE id = previous_id AND status = ANY($3)
+        RETURNING previous_status, {}
+        "#,
+        WITHDRAWAL_COLUMNS
+    );
+
+    let predecessors = withdrawal_status_predecessors(status);
+    let row = tx
+        .query_opt(&query, &[&status, &withdrawal_id, &predecessors])
+        .await
+        .map_err(|e| {
+            error!(error = %e, "Failed to apply withdrawal status");
+            ApiError::InternalServerError
+        })?;
+
+    let Some(row) = row else {
+        debug!(
+            withdrawal_id,
+            status, "Withdrawal status update not applied"
+        );
+        return Ok(None);
+    };
+
+    info!(withdrawal_id, status, "Withdrawal status updated");
+    Ok(Some(WithdrawalTransition {
+        previous_status: row.get("previous_status"),
+        record: withdrawal_from_row(&row),
+    }))
+}
+
+/// Enqueue the user's notification once a committed transition reaches a
+/// terminal status. A lost notification is logged rather than failing the
+/// caller.
+async fn notify_withdrawal_settled(transition: &WithdrawalTransition, notifier: &dyn JobEnqueuer) {
+    if transition.reached_terminal() {
+        let job = withdrawal_notification_job(&transition.record);
+        if let Err(e) = notifier.enqueue(job).await {
+            error!(
+                withdrawal_id = %transition.record.id,
+                error = %e,
+                "Failed to enqueue withdrawal notification"
+            );
+        }
+    }
+}
+
+/// Map a non-2xx Anchor response onto the error our client should see, so a
+/// rejected request reads differently from an Anchor that is down. The
+/// Anchor's own message is surfaced where it sent one.
+fn anchor_error(endpoint: &str, status: StatusCode, body: &str) -> ApiError {
+    let message = serde_json::from_str::<AnchorErrorResponse>(body)
+        .map(|body| body.error)
+        .unwrap_or_else(|_| format!("{} request failed with {}", endpoint, status));
+
+    match status {
+        StatusCode::BAD_REQUEST | StatusCode::UNPROCESSABLE_ENTITY => {
+            ApiError::Validation(format!("Anchor rejected the request: {}", message))
+        }
+        // The anchor refusing our credentials is our misconfiguration, not
+        // the caller's, so it must not read as their own 401/403.
+        StatusCode::UNAUTHORIZED | StatusCode::FORBIDDEN => {
+            ApiError::ServiceUnavailable(format!("Anchor refused our credentials: {}", message))
+        }
+        StatusCode::NOT_FOUND => ApiError::NotFound(format!("Anchor: {}", message)),
+        StatusCode::TOO_MANY_REQUESTS => {
+            ApiError::ServiceUnavailable(format!("Anchor is unavailable: {}", message))
+        }
+        status if status.is_server_error() => {
+            ApiError::ServiceUnavailable(format!("Anchor is unavailable: {}", message))
+        }
+        _ => ApiError::InternalServerError,
+    }
+}
+
+/// How long a response's `Retry-After` asks us to wait, given either as
+/// delay-seconds or as an HTTP-date. A date already past means no wait.
+fn retry_after(headers: &HeaderMap, now: chrono::DateTime<chrono::Utc>) -> Option<Duration> {
+    let value = headers.get(RETRY_AFTER)?.to_str().ok()?.trim();
+    if let Ok(seconds) = value.parse::<u64>() {
+        return Some(Duration::from_secs(seconds));
+    }
+    let at = chrono::DateTime::parse_from_rfc2822(value).ok()?;
+    Some(
+        (at.with_timezone(&chrono::Utc) - now)
+            .to_std()
+            .unwrap_or(Duration::ZERO),
+    )
+}
+
+/// An Anchor endpoint we call.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum AnchorOp {
+    Kyc,
+    Sep24Interactive,
+    Sep31Payout,
+    TransactionStatus,
+}
+
+impl AnchorOp {
+    /// Name in logs and error messages.
+    fn endpoint(self) -> &'static str {
+        match self {
+            AnchorOp::Kyc => "KYC",
+            AnchorOp::Sep24Interactive => "SEP-24",
+            AnchorOp::Sep31Payout => "SEP-31",
+            AnchorOp::TransactionStatus => "transaction status",
+        }
+    }
+
+    /// `op` label on the anchor metrics.
+    fn label(self) -> &'static str {
+        match self {
+            AnchorOp::Kyc => "kyc",
+            AnchorOp::Sep24Interactive => "sep24_interactive",
+            AnchorOp::Sep31Payout => "sep31_payout",
+            AnchorOp::TransactionStatus => "transaction_status",
+        }
+    }
+}
+
+/// Log a non-2xx Anchor response and turn it into an `ApiError`.
+async fn error_from_response(endpoint: &str, response: Response) -> ApiError {
+    let status = response.status();
+    let text = response.text().await.unwrap_or_default();
+    error!(http_status = %status, body = %text, "Anchor returned non-2xx for {}", endpoint);
+    anchor_error(endpoint, status, &text)
+}
+
 #[derive(Debug, Deserialize)]
 struct AnchorKycResponse {
     /// Top-level status from a SEP-12 KYC check.
@@ -173,18 +714,124 @@ pub struct AnchorService {
     db_pool: Arc<Pool>,
     config: Config,
     http: Client,
+    breaker: CircuitBreaker,
 }
 
 impl AnchorService {
     pub fn new(db_pool: Arc<Pool>, config: Config) -> Self {
-        let http = Client::builder()
-            .timeout(std::time::Duration::from_secs(30))
-            .build()
-            .expect("Failed to build reqwest client");
+        let http = http_client::build(&config.http_client);
+        Self::with_http_client(db_pool, config, http)
+    }
+
+    /// Like `new`, but calling the Anchor through `http`.
+    pub fn with_http_client(db_pool: Arc<Pool>, config: Config, http: Client) -> Self {
+        let breaker = CircuitBreaker::new("Anchor", &config.anchor_config.circuit_breaker);
         Self {
             db_pool,
             config,
             http,
+            breaker,
+        }
+    }
+
+    /// State of the breaker guarding calls to the Anchor.
+    pub fn breaker_state(&self) -> BreakerState {
+        self.breaker.state()
+    }
+
+    /// Send a request to the Anchor, waiting out its rate limit.
+    ///
+    /// A `429` is retried up to `rate_limit_retry.max_retries` times, each
+    /// after the delay its `Retry-After` asks for, as long as the delays add
+    /// up to no more than `rate_limit_retry.max_wait_seconds`. A `429` that
+    /// can't be retried is returned like any other non-2xx response.
+    async fn send(&self, mut request: RequestBuilder, op: AnchorOp) -> Result<Response, ApiError> {
+        let settings = &self.config.anchor_config.rate_limit_retry;
+        let endpoint = op.endpoint();
+        let budget = Duration::from_secs(settings.max_wait_seconds);
+        let mut retries = 0;
+        let mut waited = Duration::ZERO;
+
+        loop {
+            // Requests carry in-memory bodies, so a clone is always available
+            let retry = request.try_clone();
+            let response = self.send_once(request, op).await?;
+            if response.status() != StatusCode::TOO_MANY_REQUESTS || retries >= settings.max_retries
+            {
+                return Ok(response);
+            }
+            let Some(retry) = retry else {
+                return Ok(response);
+            };
+
+            let delay = match retry_after(response.headers(), chrono::Utc::now()) {
+                Some(delay) => delay,
+                None => Duration::from_secs(1 << retries.min(5)),
+            };
+            if waited + delay > budget {
+                warn!(
+                    retry_after_seconds = delay.as_secs(),
+                    waited_ms = waited.as_millis() as u64,
+                    "Anchor {} rate limit outlasts the retry budget",
+                    endpoint
+                );
+                return Ok(response);
+            }
+
+            warn!(
+                retry_after_ms = delay.as_millis() as u64,
+                attempt = retries + 1,
+                "Anchor {} rate limited the request, retrying",
+                endpoint
+            );
+            tokio::time::sleep(delay).await;
+            waited += delay;
+            request = retry;
+            retries += 1;
+        }
+    }
+
+    /// Send a request to the Anchor through the circuit breaker, recording
+    /// it in the anchor metrics.
+    ///
+    /// Transport errors and `5xx` responses count as failures; while the
+    /// breaker is open the request isn't sent and `ServiceUnavailable` is
+    /// returned straight away. Other non-2xx responses are returned as-is
+    /// for the caller to map with `error_from_response`.
+    async fn send_once(&self, request: RequestBuilder, op: AnchorOp) -> Result<Response, ApiError> {
+        let endpoint = op.endpoint();
+        if let Err(e) = self.breaker.acquire() {
+            MetricsService::record_anchor_request(op.label(), "circuit_open", None);
+            return Err(e);
+        }
+
+        let started = Instant::now();
+        let result = request.send().await;
+        let elapsed = Some(started.elapsed().as_secs_f64());
+
+        match result {
+            Ok(response) => {
+                if response.status().is_server_error() {
+                    self.breaker.record_failure();
+                } else {
+                    self.breaker.record_success();
+                }
+                MetricsService::record_anchor_request(
+                    op.label(),
+                    response.status().as_str(),
+                    elapsed,
+                );
+                Ok(response)
+            }
+            Err(e) => {
+                MetricsService::record_anchor_request(op.label(), "unreachable", elapsed);
+                self.breaker.record_failure();
+                error!(error = %e, "Failed to reach anchor {} endpoint", endpoint);
+                Err(ApiError::ServiceUnavailable(format!(
+                    "Anchor {} endpoint is unreachable",
+                    endpoint
+                )))
+            }
         }
     }
 
@@ -204,6 +851,42 @@ impl AnchorService {
     ) -> Result<KycStatus, ApiError> {
         info!(user_id, stellar_address, "Checking KYC status at anchor");
 
+        let status = self.fetch_kyc_status(stellar_address).await?;
+
+        info!(user_id, kyc_status = %status, "KYC status check complete");
+        Ok(status)
+    }
+
+    /// Check many accounts' KYC status at once, keeping at most
+    /// `anchor.kyc_batch_concurrency` requests in flight.
+    ///
+    /// Results are keyed by account; repeated accounts are checked once. An
+    /// account whose check fails is logged and left out, so one bad lookup
+    /// doesn't fail the whole batch.
+    pub async fn check_kyc_status_batch(&self, accounts: &[String]) -> HashMap<String, KycStatus> {
+        let unique: HashSet<&str> = accounts.iter().map(String::as_str).collect();
+        info!(
+            accounts = unique.len(),
+            "Checking KYC status at anchor in batch"
+        );
+
+        stream::iter(unique)
+            .map(|account| async move { (account, self.fetch_kyc_status(account).await) })
+            .buffer_unordered(self.config.anchor_config.kyc_batch_concurrency.max(1))
+            .filter_map(|(account, result)| async move {
+                match result {
+                    Ok(status) => Some((account.to_string(), status)),
+                    Err(e) => {
+                        warn!(stellar_address = account, error = %e, "Batch KYC check failed");
+                        None
+                    }
+                }
+            })
+            .collect()
+            .await
+    }
+
+    async fn fetch_kyc_status(&self, stellar_address: &str) -> Result<KycStatus, ApiError> {
         let token = self.build_sep10_token(stellar_address)?;
         let url = format!(
             "{}/kyc?account={}",
@@ -211,34 +894,29 @@ impl AnchorService {
         );
 
         let response = self
-            .http
-            .get(&url)
-            .bearer_auth(&token)
-            .send()
-            .await
-            .map_err(|e| {
-                error!(error = %e, "Failed to reach anchor KYC endpoint");
+            .send(self.http.get(&url).bearer_auth(&token), AnchorOp::Kyc)
+            .await?;
+
+        let status = if response.status() == StatusCode::NOT_FOUND {
+            warn!(stellar_address, "No KYC record found at anchor");
+            KycStatus::NotFound
+        } else if !response.status().is_success() {
+            return Err(error_from_response("KYC", response).await);
+        } else {
+            let body: AnchorKycResponse = response.json().await.map_err(|e| {
+                error!(error = %e, "Failed to parse anchor KYC response");
                 ApiError::InternalServerError
             })?;
 
-        if response.status() == reqwest::StatusCode::NOT_FOUND {
-            warn!(user_id, "No KYC record found at anchor");
-            return Ok(KycStatus::NotFound);
-        }
-
-        let body: AnchorKycResponse = response.json().await.map_err(|e| {
-            error!(error = %e, "Failed to parse anchor KYC response");
-            ApiError::InternalServerError
-        })?;
-
-        let status = match body.status.as_deref() {
-            Some("CLEARED") | Some("cleared") => KycStatus::Cleared,
-            Some("PENDING") | Some("pending") => KycStatus::Pending,
-            Some("REJECTED") | Some("rejected") => KycStatus::Rejected,
-            _ => KycStatus::NotFound,
+            match body.status.as_deref() {
+                Some("CLEARED") | Some("cleared") => KycStatus::Cleared,
+                Some("PENDING") | Some("pending") => KycStatus::Pending,
+                Some("REJECTED") | Some("rejected") => KycStatus::Rejected,
+                _ => KycStatus::NotFound,
+            }
         };
 
-        info!(user_id, kyc_status = %status, "KYC status check complete");
+        MetricsService::record_kyc_status(&status.to_string());
         Ok(status)
     }
 
@@ -275,22 +953,14 @@ impl AnchorService {
         });
 
         let response = self
-            .http
-            .post(&endpoint)
-            .bearer_auth(&token)
-            .json(&body)
-            .send()
-            .await
-            .map_err(|e| {
-                error!(error = %e, "Failed to reach anchor SEP-24 endpoint");
-                ApiError::InternalServerError
-            })?;
+            .send(
+                self.http.post(&endpoint).bearer_auth(&token).json(&body),
+                AnchorOp::Sep24Interactive,
+            )
+            .await?;
 
         if !response.status().is_success() {
-            let status = response.status();
-            let text = response.text().await.unwrap_or_default();
-            error!(http_status = %status, body = %text, "Anchor returned non-2xx for SEP-24");
-            return Err(ApiError::InternalServerError);
+            return Err(error_from_response("SEP-24", response).await);
         }
 
         let parsed: AnchorSep24Response = response.json().await.map_err(|e| {
@@ -310,6 +980,119 @@ impl AnchorService {
         })
     }
 
+    /// Run the full SEP-24 withdrawal flow for a user.
+    ///
+    /// 1. Return the existing withdrawal if the idempotency key was seen before,
+    ///    or a conflict if it was for a different amount, asset or destination.
+    /// 2. Check the amount against the asset's configured withdrawal limits,
+    ///    or the user's reputation tier's limits where it sets them.
+    /// 3. Gate on KYC status at the Anchor (`CLEARED` required if `kyc_required = true`
+    ///    or the reputation tier requires it).
+    /// 4. Obtain a SEP-24 interactive URL + `anchor_tx_id` from the Anchor.
+    /// 5. Persist the withdrawal record.
+    ///
+    /// Two concurrent requests with the same key can both reach the Anchor;
+    /// the unique index on `(user_id, idempotency_key)` still guarantees a
+    /// single withdrawal row, and the loser returns the winner's record.
+    pub async fn initiate_withdrawal(
+        &self,
+        params: InitiateWithdrawalParams,
+    ) -> Result<InitiatedWithdrawal, ApiError> {
+        let user_id = params.user_id.as_str();
+
+        if let Some(key) = params.idempotency_key.as_deref() {
+            if let Some(record) = self
+                .find_withdrawal_by_idempotency_key(user_id, key)
+                .await?
+            {
+                check_replay(&record, &params)?;
+                info!(user_id, withdrawal_id = %record.id, "Replaying idempotent withdrawal");
+                return Ok(InitiatedWithdrawal {
+                    record,
+                    replayed: true,
+                });
+            }
+        }
+
+        let tier = params.reputation_tier.as_ref();
+        let limit = tier
+            .and_then(|tier| tier.withdrawal_limit(&params.asset))
+            .unwrap_or_else(|| self.config.anchor_config.withdrawal_limit(&params.asset));
+        check_withdrawal_limit(limit, &params.asset, params.amount)?;
+
+        let kyc_required =
+            self.config.anchor_config.kyc_required || tier.is_some_and(|tier| tier.kyc_required);
+        let kyc_status = if kyc_required {
+            let status = self
+                .check_kyc_status(user_id, &params.stellar_address)
+                .await?;
+
+            if status != KycStatus::Cleared {
+                return Err(ApiError::Authorization(format!(
+                    "KYC check failed: your status is {}. \
+                     Please complete identity verification at the anchor before withdrawing.",
+                    status
+                )));
+            }
+            status
+        } else {
+            KycStatus::Cleared
+        };
+
+        let sep24 = self
+            .get_sep24_interactive_url(
+                user_id,
+                &params.stellar_address,
+                &params.asset,
+                params.amount,
+            )
+            .await?;
+
+        info!(
+            user_id,
+            anchor_tx_id = %sep24.anchor_tx_id,
+            "SEP-24 URL obtained — persisting withdrawal"
+        );
+
+        let created = self
+            .create_withdrawal_record(CreateWithdrawalParams {
+                user_id: params.user_id.clone(),
+                destination_address: params.destination_address.clone(),
+                amount: params.amount,
+                asset: params.asset.clone(),
+                anchor_tx_id: Some(sep24.anchor_tx_id.clone()),
+                kyc_status,
+                sep24_interactive_url: Some(sep24.url),
+                idempotency_key: params.idempotency_key.clone(),
+            })
+            .await?;
+
+        if let Some(record) = created {
+            return Ok(InitiatedWithdrawal {
+                record,
+                replayed: false,
+            });
+        }
+
+        // Lost the race to a concurrent request carrying the same key.
+        warn!(
+            user_id,
+            anchor_tx_id = %sep24.anchor_tx_id,
+            "Idempotency key already used — discarding duplicate anchor transaction"
+        );
+        let key = params.idempotency_key.as_deref().unwrap_or_default();
+        let record = self
+            .find_withdrawal_by_idempotency_key(user_id, key)
+            .await?
+            .ok_or(ApiError::InternalServerError)?;
+        check_replay(&record, &params)?;
+
+        Ok(InitiatedWithdrawal {
+            record,
+            replayed: true,
+        })
+    }
+
     // ──────────────────────────────────────────────────────────────────────────
     // SEP-31: Backend-to-Backend Cross-Border Payout
     // ──────────────────────────────────────────────────────────────────────────
@@ -330,6 +1113,10 @@ impl AnchorService {
             "Initiating SEP-31 payout"
         );
 
+        if let Some(memo) = &params.memo {
+            params.memo_type.validate(memo)?;
+        }
+
         let endpoint = format!("{}/transactions", self.config.anchor_config.sep31_url);
         let token = self.build_sep10_token(&params.sender_id)?;
 
@@ -345,26 +1132,18 @@ impl AnchorService {
         }
         if let Some(memo) = &params.memo {
             body["memo"] = serde_json::json!(memo);
-            body["memo_type"] = serde_json::json!("text");
+            body["memo_type"] = serde_json::json!(params.memo_type.as_str());
         }
 
         let response = self
-            .http
-            .post(&endpoint)
-            .bearer_auth(&token)
-            .json(&body)
-            .send()
-            .await
-            .map_err(|e| {
-                error!(error = %e, "Failed to reach anchor SEP-31 endpoint");
-                ApiError::InternalServerError
-            })?;
+            .send(
+                self.http.post(&endpoint).bearer_auth(&token).json(&body),
+                AnchorOp::Sep31Payout,
+            )
+            .await?;
 
         if !response.status().is_success() {
-            let status = response.status();
-            let text = response.text().await.unwrap_or_default();
-            error!(http_status = %status, body = %text, "Anchor returned non-2xx for SEP-31");
-            return Err(ApiError::InternalServerError);
+            return Err(error_from_response("SEP-31", response).await);
         }
 
         let parsed: AnchorSep31Response = response.json().await.map_err(|e| {
@@ -398,97 +1177,474 @@ impl AnchorService {
             self.config.anchor_config.sep24_url, anchor_tx_id
         );
 
-        let response = self.http.get(&url).send().await.map_err(|e| {
-            error!(error = %e, "Failed to poll anchor TX status");
-            ApiError::InternalServerError
-        })?;
+        let response = self
+            .send(self.http.get(&url), AnchorOp::TransactionStatus)
+            .await?;
+        if !response.status().is_success() {
+            return Err(error_from_response("transaction status", response).await);
+        }
 
         let body: AnchorTxStatusResponse = response.json().await.map_err(|e| {
             error!(error = %e, "Failed to parse anchor TX status response");
             ApiError::InternalServerError
         })?;
 
-        let status = match body.transaction.status.as_str() {
-            "pending_stellar" => AnchorTxStatus::PendingStellar,
-            "pending_anchor" => AnchorTxStatus::PendingAnchor,
-            "pending_external" => AnchorTxStatus::PendingExternal,
-            "pending_user" => AnchorTxStatus::PendingUser,
-            "pending_user_transfer_start" => AnchorTxStatus::PendingUserTransferStart,
-            "completed" => AnchorTxStatus::Completed,
-            "refunded" => AnchorTxStatus::Refunded,
-            "expired" => AnchorTxStatus::Expired,
-            "error" => AnchorTxStatus::Error,
-            _ => AnchorTxStatus::Incomplete,
-        };
-
-        Ok(status)
+        Ok(AnchorTxStatus::from_anchor(&body.transaction.status))
     }
 
     // ──────────────────────────────────────────────────────────────────────────
-    // Webhook Signature Verification
+    // Scheduled Reconciliation
     // ──────────────────────────────────────────────────────────────────────────
 
-    /// Verify the HMAC-SHA256 signature on an incoming anchor webhook.
+    /// Poll the Anchor for a batch of non-terminal withdrawals and sync their status.
     ///
-    /// The Anchor sends the signature as a hex-encoded string in the
-    /// `X-Stellar-Signature` header.  We recompute HMAC-SHA256 over the raw
-    /// request body using `config.anchor_config.webhook_secret`.
-    pub fn verify_webhook_signature(
+    /// Picks up to `reconciler.batch_size` withdrawals that have an
+    /// `anchor_tx_id` and are due for a check. A withdrawal whose status is
+    /// unchanged (or whose poll fails) is pushed back with exponential backoff;
+    /// one that moved is re-checked after the base delay. Terminal transitions
+    /// notify the user through `notifier`.
+    pub async fn reconcile_withdrawals(
         &self,
-        payload: &[u8],
-        signature_header: &str,
-    ) -> Result<(), ApiError> {
-        let key = hmac::Key::new(
-            hmac::HMAC_SHA256,
-            self.config.anchor_config.webhook_secret.as_bytes(),
-        );
-
-        // Signature may arrive as hex or base64 — try both
-        let expected_tag = hmac::sign(&key, payload);
-        let expected_hex = hex::encode(expected_tag.as_ref());
-        let expected_b64 = B64.encode(expected_tag.as_ref());
+        notifier: &dyn JobEnqueuer,
+    ) -> Result<ReconcileSummary, ApiError> {
+        let settings = &self.config.reconciler;
+        let client = self.db_pool.get().await.map_err(|e| {
+            error!(error = %e, "DB pool error");
+            ApiError::InternalServerError
+        })?;
 
-        let header = signature_header.trim();
-        // Strip common prefixes e.g. "sha256=" sent by some anchors
-        let sig = header.strip_prefix("sha256=").unwrap_or(header);
+        let rows = client
+            .query(
+                r#"
+                SELECT id::text AS id, anchor_tx_id, status, reconcile_attempts
+                FROM withdrawals
+                WHERE anchor_tx_id IS NOT NULL
+                  AND status NOT IN ('completed', 'failed', 'refunded')
+                  AND (next_reconcile_at IS NULL OR next_reconcile_at <= NOW())
+                ORDER BY next_reconcile_at NULLS FIRST, created_at
+                LIMIT $1
+                "#,
+                &[&settings.batch_size.max(1)],
+            )
+            .await
+            .map_err(|e| {
+                error!(error = %e, "Failed to select withdrawals for reconciliation");
+                ApiError::InternalServerError
+            })?;
 
-        if sig != expected_hex && sig != expected_b64 {
-            warn!("Anchor webhook signature mismatch");
-            return Err(ApiError::Authentication(
-                "Invalid webhook signature".to_string(),
-            ));
+        let mut summary = ReconcileSummary::default();
+        for row in rows {
+            let id: String = row.get("id");
+            let anchor_tx_id: String = row.get("anchor_tx_id");
+            let current: Option<String> = row.get("status");
+            let attempts: i32 = row.get("reconcile_attempts");
+
+            let polled = self.poll_anchor_tx_status(&anchor_tx_id).await;
+            let next_status = match &polled {
+                Ok(status) => {
+                    summary.checked += 1;
+                    Some(status.withdrawal_status())
+                }
+                Err(_) => {
+                    summary.failed += 1;
+                    None
+                }
+            };
+
+            let changed = next_status.is_some_and(|s| current.as_deref() != Some(s));
+            let attempts = if changed {
+                0
+            } else {
+                attempts.saturating_add(1)
+            };
+            let next_check = chrono::Utc::now()
+                + reconcile_backoff(
+                    attempts,
+                    settings.base_backoff_seconds,
+                    settings.max_backoff_seconds,
+                );
+
+            client
+                .execute(
+                    r#"
+                    UPDATE withdrawals
+                    SET reconcile_attempts = $1,
+                        next_reconcile_at = $2
+                    WHERE id = $3::text::uuid
+                    "#,
+                    &[&attempts, &next_check, &id],
+                )
+                .await
+                .map_err(|e| {
+                    error!(error = %e, "Failed to record reconciliation result");
+                    ApiError::InternalServerError
+                })?;
+
+            if let Some(status) = next_status.filter(|_| changed) {
+                // A webhook may have landed since the SELECT; only count the
+                // change if this run actually applied it.
+                if let Some(transition) =
+                    self.apply_withdrawal_status(&id, status, notifier).await?
+                {
+                    summary.updated += 1;
+                    if transition.reached_terminal() {
+                        summary.notified += 1;
+                    }
+                    info!(withdrawal_id = %id, status, "Withdrawal reconciled with anchor");
+                }
+            }
         }
 
-        Ok(())
+        Ok(summary)
     }
 
     // ──────────────────────────────────────────────────────────────────────────
-    // Database helpers
+    // Webhook Ledger
     // ──────────────────────────────────────────────────────────────────────────
 
-    /// Persist a new withdrawal record and return the created row.
-    pub async fn create_withdrawal_record(
+    /// Record a verified webhook in the ledger, then sync its status.
+    ///
+    /// A redelivery of a transaction + status that was already processed is
+    /// acknowledged without being processed again. If processing fails the
+    /// ledger entry is marked `failed` and the error returned, so the anchor
+    /// retries and the reconciler replays it in the meantime. A webhook for
+    /// a transaction we don't have yet is kept `pending` for replay.
+    pub async fn handle_webhook(
         &self,
-        params: CreateWithdrawalParams,
-    ) -> Result<WithdrawalRecord, ApiError> {
+        event: &AnchorWebhookEvent,
+        notifier: &dyn JobEnqueuer,
+    ) -> Result<WebhookDisposition, ApiError> {
         let client = self.db_pool.get().await.map_err(|e| {
             error!(error = %e, "DB pool error");
             ApiError::InternalServerError
         })?;
 
-        let id = Uuid::new_v4().to_string();
-        let now = chrono::Utc::now();
+        let row = client
+            .query_one(
+                r#"
+                INSERT INTO anchor_webhooks (transaction_id, status, message, attempts)
+                VALUES ($1, $2, $3, 1)
+                ON CONFLICT (transaction_id, status) DO UPDATE
+                SET attempts = anchor_webhooks.attempts + 1,
+                    message = COALESCE(EXCLUDED.message, anchor_webhooks.message)
+                RETURNING id::text AS id, processing_status, attempts
+                "#,
+                &[&event.transaction_id, &event.status, &event.message],
+            )
+            .await
+            .map_err(|e| {
+                error!(error = %e, "Failed to record anchor webhook");
+                ApiError::InternalServerError
+            })?;
+
+        let ledger_id: String = row.get("id");
+        let processing_status: String = row.get("processing_status");
+        // A dead-lettered entry is matched again: the anchor redelivering it
+        // may be the only way its transaction is ever settled.
+        if processing_status == "processed" {
+            return Ok(WebhookDisposition::Duplicate);
+        }
 
+        self.process_webhook(
+            &ledger_id,
+            &event.transaction_id,
+            &event.status,
+            row.get("attempts"),
+            notifier,
+        )
+        .await
+    }
+
+    /// Replay ledger entries whose processing failed, was interrupted or
+    /// found no matching transaction.
+    ///
+    /// Picks up to `reconciler.batch_size` webhooks that are `failed`, or still
+    /// `pending` after [`WEBHOOK_REPLAY_GRACE`], oldest first. Each attempt
+    /// counts towards `reconciler.max_webhook_attempts`, after which the entry
+    /// is dead-lettered.
+    pub async fn replay_webhooks(
+        &self,
+        notifier: &dyn JobEnqueuer,
+    ) -> Result<WebhookReplaySummary, ApiError> {
+        let client = self.db_pool.get().await.map_err(|e| {
+            error!(error = %e, "DB pool error");
+            ApiError::InternalServerError
+        })?;
+
+        let stale_before = chrono::Utc::now()
+            - chrono::Duration::from_std(WEBHOOK_REPLAY_GRACE).unwrap_or_default();
+
+        let rows = client
+            .query(
+                r#"
+                SELECT id::text AS id
+                FROM anchor_webhooks
+                WHERE processing_status = 'failed'
+                   OR (processing_status = 'pending' AND received_at <= $1)
+                ORDER BY received_at
+                LIMIT $2
+                "#,
+                &[&stale_before, &self.config.reconciler.batch_size.max(1)],
+            )
+            .await
+            .map_err(|e| {
+                error!(error = %e, "Failed to select anchor webhooks for replay");
+                ApiError::InternalServerError
+            })?;
+
+        let max_attempts = self.config.reconciler.max_webhook_attempts;
+        let mut summary = WebhookReplaySummary::default();
+        for row in rows {
+            let id: String = row.get("id");
+
+            // Claim the entry; it may have been processed by a redelivery
+            // since the SELECT.
+            let claimed = client
+                .query_opt(
+                    r#"
+                    UPDATE anchor_webhooks
+                    SET attempts = attempts + 1
+                    WHERE id = $1::text::uuid
+                      AND processing_status IN ('pending', 'failed')
+                    RETURNING transaction_id, status, attempts
+                    "#,
+                    &[&id],
+                )
+                .await
+                .map_err(|e| {
+                    error!(error = %e, "Failed to claim anchor webhook for replay");
+                    ApiError::InternalServerError
+                })?;
+            let Some(claimed) = claimed else {
+                continue;
+            };
+
+            let transaction_id: String = claimed.get("transaction_id");
+            let status: String = claimed.get("status");
+            let attempts: i32 = claimed.get("attempts");
+            match self
+                .process_webhook(&id, &transaction_id, &status, attempts, notifier)
+                .await
+            {
+                Ok(WebhookDisposition::Processed | WebhookDisposition::Duplicate) => {
+                    summary.replayed += 1
+                }
+                Ok(WebhookDisposition::Unmatched) | Err(_) if attempts >= max_attempts => {
+                    summary.dead_lettered += 1
+                }
+                Ok(WebhookDisposition::Unmatched) | Err(_) => summary.failed += 1,
+            }
+        }
+
+        Ok(summary)
+    }
+
+    /// Apply a ledger entry's status and record the outcome on the entry,
+    /// which has been attempted `attempts` times including this one.
+    async fn process_webhook(
+        &self,
+        ledger_id: &str,
+        transaction_id: &str,
+        anchor_status: &str,
+        attempts: i32,
+        notifier: &dyn JobEnqueuer,
+    ) -> Result<WebhookDisposition, ApiError> {
+        let outcome = self
+            .apply_webhook_status(transaction_id, anchor_status, notifier)
+            .await;
+
+        let processing_status = webhook_ledger_status(
+            &outcome,
+            attempts,
+            self.config.reconciler.max_webhook_attempts,
+        );
+        let last_error = match &outcome {
+            Ok(WebhookDisposition::Unmatched) => Some("No matching transaction".to_string()),
+            Ok(_) => None,
+            Err(e) => Some(e.to_string()),
+        };
+        let processed_at = (processing_status == "processed").then(chrono::Utc::now);
+
+        let client = self.db_pool.get().await.map_err(|e| {
+            error!(error = %e, "DB pool error");
+            ApiError::InternalServerError
+        })?;
         client
             .execute(
                 r#"
-                INSERT INTO withdrawals
-                    (id, user_id, destination_address, amount, asset, status,
-                     anchor_tx_id, kyc_status, sep24_interactive_url, created_at, updated_at)
-                VALUES
-                    ($1, $2, $3, $4, $5, 'pending',
-                     $6, $7, $8, $9, $10)
+                UPDATE anchor_webhooks
+                SET processing_status = $1,
+                    last_error = $2,
+                    processed_at = $3
+                WHERE id = $4::text::uuid
                 "#,
+                &[&processing_status, &last_error, &processed_at, &ledger_id],
+            )
+            .await
+            .map_err(|e| {
+                error!(error = %e, "Failed to record anchor webhook outcome");
+                ApiError::InternalServerError
+            })?;
+
+        if processing_status == "dead_letter" {
+            error!(
+                anchor_tx_id = transaction_id,
+                status = anchor_status,
+                attempts,
+                "Anchor webhook still unresolved after its last attempt — dead-lettered"
+            );
+        } else if let Err(e) = &outcome {
+            warn!(
+                anchor_tx_id = transaction_id,
+                error = %e,
+                "Anchor webhook processing failed — queued for replay"
+            );
+        }
+        outcome
+    }
+
+    /// Sync the deposit or withdrawal matching `transaction_id` to an anchor status.
+    async fn apply_webhook_status(
+        &self,
+        transaction_id: &str,
+        anchor_status: &str,
+        notifier: &dyn JobEnqueuer,
+    ) -> Result<WebhookDisposition, ApiError> {
+        let Some(transaction) = self
+            .find_transaction_by_anchor_tx_id(transaction_id)
+            .await?
+        else {
+            warn!(
+                anchor_tx_id = transaction_id,
+                "Anchor webhook received for unknown transaction — kept for replay"
+            );
+            return Ok(WebhookDisposition::Unmatched);
+        };
+
+        let status = AnchorTxStatus::from_anchor(anchor_status).withdrawal_status();
+        match transaction {
+            AnchorTransaction::Withdrawal(withdrawal_id) => {
+                self.apply_withdrawal_status(&withdrawal_id, status, notifier)
+                    .await?;
+            }
+            AnchorTransaction::Deposit(deposit_id) => {
+                self.apply_deposit_status(&deposit_id, status).await?;
+            }
+        }
+        Ok(WebhookDisposition::Processed)
+    }
+
+    // ──────────────────────────────────────────────────────────────────────────
+    // Reachability Probe
+    // ──────────────────────────────────────────────────────────────────────────
+
+    /// Confirm the Anchor is reachable by calling its SEP-24 `GET /info` endpoint.
+    ///
+    /// Used by the readiness probe; any non-2xx response counts as unreachable.
+    pub async fn probe_info(&self) -> anyhow::Result<()> {
+        let url = format!("{}/info", self.config.anchor_config.sep24_url);
+        let response = self.http.get(&url).send().await?;
+
+        if !response.status().is_success() {
+            anyhow::bail!("anchor /info returned {}", response.status());
+        }
+
+        Ok(())
+    }
+
+    // ──────────────────────────────────────────────────────────────────────────
+    // Webhook Signature Verification
+    // ──────────────────────────────────────────────────────────────────────────
+
+    /// Verify the HMAC-SHA256 signature on an incoming anchor webhook.
+    ///
+    /// The Anchor sends the signature as a hex-encoded string in the
+    /// `X-Stellar-Signature` header.  We recompute HMAC-SHA256 over the raw
+    /// request body using `config.anchor_config.webhook_secret`, or any of
+    /// `previous_webhook_secrets` while a rotation is under way.
+    pub fn verify_webhook_signature(
+        &self,
+        payload: &[u8],
+        signature_header: &str,
+    ) -> Result<(), ApiError> {
+        let header = signature_header.trim();
+        // Strip common prefixes e.g. "sha256=" sent by some anchors
+        let sig = header.strip_prefix("sha256=").unwrap_or(header);
+
+        // Signature may arrive as hex or base64 — try both
+        let candidates: Vec<Vec<u8>> = [hex::decode(sig).ok(), B64.decode(sig).ok()]
+            .into_iter()
+            .flatten()
+            .collect();
+
+        let secrets = self.config.anchor_config.webhook_secret_list();
+        let matched = secrets.iter().position(|secret| {
+            let key = hmac::Key::new(hmac::HMAC_SHA256, secret.as_bytes());
+            candidates
+                .iter()
+                .any(|tag| hmac::verify(&key, payload, tag).is_ok())
+        });
+
+        match matched {
+            Some(0) => {
+                debug!("Anchor webhook verified with the current secret");
+                Ok(())
+            }
+            Some(index) => {
+                info!(
+                    previous_secret = index,
+                    "Anchor webhook verified with a previous secret"
+                );
+                Ok(())
+            }
+            None => {
+                warn!("Anchor webhook signature mismatch");
+                Err(ApiError::Authentication(
+                    "Invalid webhook signature".to_string(),
+                ))
+            }
+        }
+    }
+
+    // ──────────────────────────────────────────────────────────────────────────
+    // Database helpers
+    // ──────────────────────────────────────────────────────────────────────────
+
+    /// Persist a new withdrawal record and return the created row.
+    ///
+    /// Returns `None` when the user already has a withdrawal with the same
+    /// idempotency key.
+    pub async fn create_withdrawal_record(
+        &self,
+        params: CreateWithdrawalParams,
+    ) -> Result<Option<WithdrawalRecord>, ApiError> {
+        let client = self.db_pool.get().await.map_err(|e| {
+            error!(error = %e, "DB pool error");
+            ApiError::InternalServerError
+        })?;
+
+        let id = Uuid::new_v4().to_string();
+        let now = chrono::Utc::now();
+
+        let query = format!(
+            r#"
+            INSERT INTO withdrawals
+                (id, user_id, destination_address, amount, asset, status,
+                 anchor_tx_id, kyc_status, sep24_interactive_url, idempotency_key,
+                 created_at, updated_at)
+            VALUES
+                ($1::text::uuid, $2, $3, $4, $5, 'pending',
+                 $6, $7, $8, $9, $10, $11)
+            ON CONFLICT (user_id, idempotency_key) WHERE idempotency_key IS NOT NULL
+                DO NOTHING
+            RETURNING {}
+            "#,
+            WITHDRAWAL_COLUMNS
+        );
+
+        let row = client
+            .query_opt(
+                &query,
                 &[
                     &id,
                     &params.user_id,
@@ -498,6 +1654,7 @@ impl AnchorService {
                     &params.anchor_tx_id,
                     &params.kyc_status.to_string(),
                     &params.sep24_interactive_url,
+                    &params.idempotency_key,
                     &now,
                     &now,
                 ],
@@ -508,19 +1665,7 @@ impl AnchorService {
                 ApiError::InternalServerError
             })?;
 
-        Ok(WithdrawalRecord {
-            id,
-            user_id: params.user_id,
-            destination_address: params.destination_address,
-            amount: params.amount,
-            asset: params.asset,
-            status: "pending".to_string(),
-            anchor_tx_id: params.anchor_tx_id,
-            kyc_status: params.kyc_status.to_string(),
-            sep24_interactive_url: params.sep24_interactive_url,
-            created_at: now,
-            updated_at: now,
-        })
+        Ok(row.as_ref().map(withdrawal_from_row))
     }
 
     /// Fetch a withdrawal record by its internal ID.
@@ -533,62 +1678,310 @@ impl AnchorService {
             ApiError::InternalServerError
         })?;
 
+        let query = format!(
+            "SELECT {} FROM withdrawals WHERE id = $1::text::uuid",
+            WITHDRAWAL_COLUMNS
+        );
+
+        let row = client
+            .query_opt(&query, &[&withdrawal_id])
+            .await
+            .map_err(|e| {
+                error!(error = %e, "DB query failed");
+                ApiError::InternalServerError
+            })?
+            .ok_or_else(|| ApiError::NotFound(format!("Withdrawal {} not found", withdrawal_id)))?;
+
+        Ok(withdrawal_from_row(&row))
+    }
+
+    /// Find the deposit or withdrawal the anchor knows as `anchor_tx_id`.
+    pub async fn find_transaction_by_anchor_tx_id(
+        &self,
+        anchor_tx_id: &str,
+    ) -> Result<Option<AnchorTransaction>, ApiError> {
+        let client = self.db_pool.get().await.map_err(|e| {
+            error!(error = %e, "DB pool error");
+            ApiError::InternalServerError
+        })?;
+
         let row = client
             .query_opt(
                 r#"
-                SELECT id, user_id, destination_address, amount, asset, status,
-                       anchor_tx_id, kyc_status, sep24_interactive_url, created_at, updated_at
+                SELECT 'withdrawal' AS kind, id::text AS id
                 FROM withdrawals
-                WHERE id = $1
+                WHERE anchor_tx_id = $1
+                UNION ALL
+                SELECT 'deposit' AS kind, id::text AS id
+                FROM deposits
+                WHERE anchor_tx_id = $1
+                LIMIT 1
                 "#,
-                &[&withdrawal_id],
+                &[&anchor_tx_id],
             )
             .await
             .map_err(|e| {
                 error!(error = %e, "DB query failed");
                 ApiError::InternalServerError
-            })?
-            .ok_or_else(|| ApiError::NotFound(format!("Withdrawal {} not found", withdrawal_id)))?;
+            })?;
+
+        Ok(row.map(|r| {
+            let id: String = r.get("id");
+            match r.get::<_, &str>("kind") {
+                "deposit" => AnchorTransaction::Deposit(id),
+                _ => AnchorTransaction::Withdrawal(id),
+            }
+        }))
+    }
 
-        Ok(WithdrawalRecord {
-            id: row.get("id"),
-            user_id: row.get("user_id"),
-            destination_address: row.get("destination_address"),
-            amount: row.get("amount"),
-            asset: row.get("asset"),
-            status: row.get("status"),
-            anchor_tx_id: row.get("anchor_tx_id"),
-            kyc_status: row.get("kyc_status"),
-            sep24_interactive_url: row.get("sep24_interactive_url"),
-            created_at: row.get("created_at"),
-            updated_at: row.get("updated_at"),
+    /// Set a deposit's status from an anchor update, crediting the user's
+    /// balance in the same transaction when it completes.
+    ///
+    /// Returns `false` when the deposit is missing or already has `status`.
+    pub async fn apply_deposit_status(
+        &self,
+        deposit_id: &str,
+        status: &str,
+    ) -> Result<bool, ApiError> {
+        let mut client = self.db_pool.get().await.map_err(|e| {
+            error!(error = %e, "DB pool error");
+            ApiError::InternalServerError
+        })?;
+
+        let tx = client.transaction().await?;
+        let updated = tx
+            .query_opt(
+                r#"
+                UPDATE deposits
+                SET status = $1, updated_at = NOW()
+                WHERE id = $2::text::uuid AND status IS DISTINCT FROM $1
+                RETURNING user_id, amount, asset
+                "#,
+                &[&status, &deposit_id],
+            )
+            .await
+            .map_err(|e| {
+                error!(error = %e, "Failed to apply deposit status");
+                ApiError::InternalServerError
+            })?;
+
+        // Only the transition into `completed` credits, so a redelivered
+        // webhook can't credit twice.
+        if let (Some(row), "completed") = (&updated, status) {
+            let user_id: String = row.get("user_id");
+            let asset: String = row.get("asset");
+            BalanceService::credit_in(&tx, &user_id, &asset, row.get("amount")).await?;
+        }
+        tx.commit().await?;
+
+        if updated.is_some() {
+            info!(deposit_id, status, "Deposit status updated");
+        }
+        Ok(updated.is_some())
+    }
+
+    /// Look up a user's withdrawal by the idempotency key it was created with.
+    pub async fn find_withdrawal_by_idempotency_key(
+        &self,
+        user_id: &str,
+        idempotency_key: &str,
+    ) -> Result<Option<WithdrawalRecord>, ApiError> {
+        let client = self.db_pool.get().await.map_err(|e| {
+            error!(error = %e, "DB pool error");
+            ApiError::InternalServerError
+        })?;
+
+        let query = format!(
+            "SELECT {} FROM withdrawals WHERE user_id = $1 AND idempotency_key = $2",
+            WITHDRAWAL_COLUMNS
+        );
+
+        let row = client
+            .query_opt(&query, &[&user_id, &idempotency_key])
+            .await
+            .map_err(|e| {
+                error!(error = %e, "DB query failed");
+                ApiError::InternalServerError
+            })?;
+
+        Ok(row.as_ref().map(withdrawal_from_row))
+    }
+
+    /// List a user's withdrawals newest-first using keyset pagination.
+    ///
+    /// Pass the previous page's `next_cursor` to continue; `status` narrows the
+    /// results to a single withdrawal status.
+    pub async fn list_withdrawals(
+        &self,
+        user_id: &str,
+        status: Option<&str>,
+        limit: i64,
+        cursor: Option<&WithdrawalCursor>,
+    ) -> Result<WithdrawalPage, ApiError> {
+        let client = self.db_pool.get().await.map_err(|e| {
+            error!(error = %e, "DB pool error");
+            ApiError::InternalServerError
+        })?;
+
+        let cursor_created_at = cursor.map(|c| c.created_at);
+        let cursor_id = cursor.map(|c| c.id.to_string());
+        // Fetch one extra row to learn whether another page exists.
+        let fetch = limit + 1;
+
+        let query = format!(
+            r#"
+            SELECT {}
+            FROM withdrawals
+            WHERE user_id = $1
+              AND ($2::text IS NULL OR status = $2)
+              AND ($3::timestamptz IS NULL OR (created_at, id) < ($3, $4::text::uuid))
+            ORDER BY created_at DESC, id DESC
+            LIMIT $5
+            "#,
+            WITHDRAWAL_COLUMNS
+        );
+
+        let rows = client
+            .query(
+                &query,
+                &[&user_id, &status, &cursor_created_at, &cursor_id, &fetch],
+            )
+            .await
+            .map_err(|e| {
+                error!(error = %e, "Failed to list withdrawals");
+                ApiError::InternalServerError
+            })?;
+
+        let mut withdrawals: Vec<WithdrawalRecord> = rows.iter().map(withdrawal_from_row).collect();
+
+        let next_cursor = if withdrawals.len() as i64 > limit {
+            withdrawals.truncate(limit as usize);
+            withdrawals
+                .last()
+                .map(WithdrawalCursor::after)
+                .transpose()?
+        } else {
+            None
+        };
+
+        Ok(WithdrawalPage {
+            withdrawals,
+            next_cursor,
         })
     }
 
+    /// Set a withdrawal's status from an anchor update (webhook or poll).
+    ///
+    /// Returns `None` when the withdrawal is missing, already has `status`,
+    /// or has moved past it (see [`withdrawal_status_predecessors`]). The row
+    /// is locked while the previous status is read, so when a webhook and the
+    /// reconciler observe the same change only one of them applies it — and
+    /// only that caller enqueues the user's notification.
+    pub async fn apply_withdrawal_status(
+        &self,
+        withdrawal_id: &str,
+        status: &str,
+        notifier: &dyn JobEnqueuer,
+    ) -> Result<Option<WithdrawalTransition>, ApiError> {
+        let mut client = self.db_pool.get().await.map_err(|e| {
+            error!(error = %e, "DB pool error");
+            ApiError::InternalServerError
+        })?;
+        let tx = client.transaction().await?;
+        let transition = transition_withdrawal(&tx, withdrawal_id, status).await?;
+        tx.commit().await?;
+
+        if let Some(transition) = &transition {
+            notify_withdrawal_settled(transition, notifier).await;
+        }
+        Ok(transition)
+    }
+
+    /// Force a withdrawal to a terminal status after out-of-band
+    /// confirmation, notifying the user as an anchor update would.
+    ///
+    /// Only terminal statuses may be forced, and only onto a withdrawal that
+    /// hasn't already settled. The status change is committed together with
+    /// the audit entry recording who forced it and why.
+    pub async fn override_withdrawal_status(
+        &self,
+        withdrawal_id: &str,
+        status: &str,
+        actor_id: &str,
+        reason: &str,
+        notifier: &dyn JobEnqueuer,
+    ) -> Result<WithdrawalTransition, ApiError> {
+        if !TERMINAL_WITHDRAWAL_STATUSES.contains(&status) {
+            return Err(ApiError::Validation(format!(
+                "status must be one of: {}",
+                TERMINAL_WITHDRAWAL_STATUSES.join(", ")
+            )));
+        }
+
+        let mut client = self.db_pool.get().await.map_err(|e| {
+            error!(error = %e, "DB pool error");
+            ApiError::InternalServerError
+        })?;
+        let tx = client.transaction().await?;
+
+        let Some(transition) = transition_withdrawal(&tx, withdrawal_id, status).await? else {
+            drop(tx);
+            let current = self.get_withdrawal_by_id(withdrawal_id).await?;
+            return Err(ApiError::Conflict(format!(
+                "Withdrawal is already {}",
+                current.status
+            )));
+        };
+
+        let audit = CreateAuditLogParams {
+            actor_id: actor_id.to_string(),
+            action: "override_withdrawal_status".to_string(),
+            resource: "withdrawals".to_string(),
+            resource_id: Some(withdrawal_id.to_string()),
+            metadata: Some(serde_json::json!({
+                "from": transition.previous_status,
+                "to": status,
+                "reason": reason,
+            })),
+            ip_address: None,
+            user_agent: None,
+            occurred_at: None,
+        };
+        append_audit_log(&tx, audit).await?;
+        tx.commit().await?;
+
+        notify_withdrawal_settled(&transition, notifier).await;
+        Ok(transition)
+    }
+
     /// Update the `status` and optionally `anchor_tx_id` of a withdrawal.
+    ///
+    /// Only forward transitions apply (see
+    /// [`withdrawal_status_predecessors`]); returns whether this one did.
     pub async fn update_withdrawal_status(
         &self,
         withdrawal_id: &str,
         status: &str,
         anchor_tx_id: Option<&str>,
-    ) -> Result<(), ApiError> {
+    ) -> Result<bool, ApiError> {
         let client = self.db_pool.get().await.map_err(|e| {
             error!(error = %e, "DB pool error");
             ApiError::InternalServerError
         })?;
 
         let now = chrono::Utc::now();
+        let predecessors = withdrawal_status_predecessors(status);
 
-        client
+        let updated = client
             .execute(
                 r#"
                 UPDATE withdrawals
                 SET status = $1,
                     anchor_tx_id = COALESCE($2, anchor_tx_id),
                     updated_at = $3
-                WHERE id = $4
+                WHERE id = $4::text::uuid AND status = ANY($5)
                 "#,
-                &[&status, &anchor_tx_id, &now, &withdrawal_id],
+                &[&status, &anchor_tx_id, &now, &withdrawal_id, &predecessors],
             )
             .await
             .map_err(|e| {
@@ -596,8 +1989,16 @@ impl AnchorService {
                 ApiError::InternalServerError
             })?;
 
+        if updated == 0 {
+            debug!(
+                withdrawal_id,
+                status, "Withdrawal status update not applied"
+            );
+            return Ok(false);
+        }
+
         info!(withdrawal_id, status, "Withdrawal status updated");
-        Ok(())
+        Ok(true)
     }
 
     // ──────────────────────────────────────────────────────────────────────────
@@ -635,3 +2036,352 @@ impl AnchorService {
         })
     }
 }
+
+/// The `withdrawal_reconciliation` maintenance task:
+/// [`AnchorService::reconcile_withdrawals`] with the notifier settled
+/// withdrawals are announced through.
+#[derive(Clone)]
+pub struct WithdrawalReconciliation {
+    anchor: AnchorService,
+    notifier: Arc<dyn JobEnqueuer>,
+}
+
+impl WithdrawalReconciliation {
+    pub fn new(anchor: AnchorService, notifier: Arc<dyn JobEnqueuer>) -> Self {
+        Self { anchor, notifier }
+    }
+}
+
+#[async_trait]
+impl MaintenanceTask for WithdrawalReconciliation {
+    async fn run(&self) -> anyhow::Result<()> {
+        let summary = self
+            .anchor
+            .reconcile_withdrawals(self.notifier.as_ref())
+            .await?;
+        if summary.checked + summary.failed > 0 {
+            info!(
+                checked = summary.checked,
+                updated = summary.updated,
+                failed = summary.failed,
+                "Withdrawal reconciliation run complete"
+            );
+        }
+        Ok(())
+    }
+}
+
+/// The `anchor_webhook_replay` maintenance task:
+/// [`AnchorService::replay_webhooks`] with the notifier settled withdrawals
+/// are announced through.
+#[derive(Clone)]
+pub struct WebhookReplay {
+    anchor: AnchorService,
+    notifier: Arc<dyn JobEnqueuer>,
+}
+
+impl WebhookReplay {
+    pub fn new(anchor: AnchorService, notifier: Arc<dyn JobEnqueuer>) -> Self {
+        Self { anchor, notifier }
+    }
+}
+
+#[async_trait]
+impl MaintenanceTask for WebhookReplay {
+    async fn run(&self) -> anyhow::Result<()> {
+        let summary = self.anchor.replay_webhooks(self.notifier.as_ref()).await?;
+        if summary.replayed + summary.failed + summary.dead_lettered > 0 {
+            info!(
+                replayed = summary.replayed,
+                failed = summary.failed,
+                dead_lettered = summary.dead_lettered,
+                "Anchor webhook replay complete"
+            );
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn unresolved_webhooks_are_dead_lettered_after_the_last_attempt() {
+        let unmatched = Ok(WebhookDisposition::Unmatched);
+        let failed = Err(ApiError::InternalServerError);
+
+        assert_eq!(webhook_ledger_status(&unmatched, 1, 3), "pending");
+        assert_eq!(webhook_ledger_status(&failed, 2, 3), "failed");
+        assert_eq!(webhook_ledger_status(&unmatched, 3, 3), "dead_letter");
+        assert_eq!(webhook_ledger_status(&failed, 4, 3), "dead_letter");
+        assert_eq!(
+            webhook_ledger_status(&Ok(WebhookDisposition::Processed), 3, 3),
+            "processed"
+        );
+    }
+
+    #[test]
+    fn replay_must_match_the_original_withdrawal() {
+        let params = InitiateWithdrawalParams {
+            user_id: "alice".to_string(),
+            stellar_address: "GALICE".to_string(),
+            destination_address: "GDEST".to_string(),
+            amount: 1_000,
+            asset: "USDC".to_string(),
+            idempotency_key: Some("retry-1".to_string()),
+            reputation_tier: None,
+        };
+        let record = WithdrawalRecord {
+            id: Uuid::new_v4().to_string(),
+            user_id: "alice".to_string(),
+            destination_address: "GDEST".to_string(),
+            amount: 1_000,
+            asset: "USDC".to_string(),
+            status: "pending".to_string(),
+            anchor_tx_id: None,
+            kyc_status: "cleared".to_string(),
+            sep24_interactive_url: None,
+            idempotency_key: Some("retry-1".to_string()),
+            created_at: chrono::Utc::now(),
+            updated_at: chrono::Utc::now(),
+        };
+        check_replay(&record, &params).unwrap();
+
+        let different = [
+            InitiateWithdrawalParams {
+                amount: 2_000,
+                ..params.clone()
+            },
+            InitiateWithdrawalParams {
+                asset: "XLM".to_string(),
+                ..params.clone()
+            },
+            InitiateWithdrawalParams {
+                destination_address: "GOTHER".to_string(),
+                ..params.clone()
+            },
+        ];
+        for params in different {
+            assert!(matches!(
+                check_replay(&record, &params),
+                Err(ApiError::Conflict(_))
+            ));
+        }
+    }
+
+    #[test]
+    fn anchor_status_maps_to_withdrawal_status() {
+        let cases = [
+            ("completed", "completed"),
+            ("error", "failed"),
+            ("expired", "failed"),
+            ("refunded", "refunded"),
+            ("pending_external", "processing"),
+            ("incomplete", "pending"),
+            ("something_new", "pending"),
+        ];
+        for (anchor, internal) in cases {
+            assert_eq!(
+                AnchorTxStatus::from_anchor(anchor).withdrawal_status(),
+                internal,
+                "{}",
+                anchor
+            );
+        }
+    }
+
+    #[test]
+    fn withdrawal_statuses_only_move_forward() {
+        assert_eq!(withdrawal_status_predecessors("pending"), &[] as &[&str]);
+        assert_eq!(withdrawal_status_predecessors("processing"), ["pending"]);
+        for terminal in TERMINAL_WITHDRAWAL_STATUSES {
+            let predecessors = withdrawal_status_predecessors(terminal);
+            assert_eq!(predecessors, ["pending", "processing"]);
+            assert!(TERMINAL_WITHDRAWAL_STATUSES
+                .iter()
+                .all(|s| !predecessors.contains(s)));
+        }
+    }
+
+    #[test]
+    fn memo_types_parse_and_check_their_values() {
+        assert_eq!("id".parse::<MemoType>().unwrap(), MemoType::Id);
+        assert!(matches!(
+            "return".parse::<MemoType>(),
+            Err(ApiError::Validation(_))
+        ));
+
+        assert!(MemoType::Text.validate("a".repeat(28).as_str()).is_ok());
+        assert!(MemoType::Text.validate("a".repeat(29).as_str()).is_err());
+        assert!(MemoType::Id.validate("0").is_ok());
+        assert!(MemoType::Id.validate("-1").is_err());
+        assert!(MemoType::Id.validate("+1").is_err());
+        assert!(MemoType::Id.validate("18446744073709551616").is_err());
+        assert!(MemoType::Hash.validate(&B64.encode([7u8; 32])).is_ok());
+        assert!(MemoType::Hash.validate(&B64.encode([7u8; 31])).is_err());
+        assert!(MemoType::Hash.validate(&hex::encode([7u8; 32])).is_err());
+    }
+
+    #[test]
+    fn anchor_errors_map_by_status() {
+        let sep_error = r#"{"error": "amount exceeds the daily limit"}"#;
+
+        assert!(matches!(
+            anchor_error("SEP-24", StatusCode::UNPROCESSABLE_ENTITY, sep_error),
+            ApiError::Validation(msg) if msg.ends_with("amount exceeds the daily limit")
+        ));
+        assert!(matches!(
+            anchor_error("SEP-24", StatusCode::UNAUTHORIZED, sep_error),
+            ApiError::ServiceUnavailable(_)
+        ));
+        assert!(matches!(
+            anchor_error("SEP-24", StatusCode::FORBIDDEN, sep_error),
+            ApiError::ServiceUnavailable(_)
+        ));
+        assert!(matches!(
+            anchor_error("SEP-24", StatusCode::NOT_FOUND, sep_error),
+            ApiError::NotFound(_)
+        ));
+        assert!(matches!(
+            anchor_error("SEP-24", StatusCode::TOO_MANY_REQUESTS, sep_error),
+            ApiError::ServiceUnavailable(_)
+        ));
+        // Bodies that aren't SEP errors (an HTML error page) fall back to the status
+        assert!(matches!(
+            anchor_error("SEP-24", StatusCode::BAD_GATEWAY, "<html>Bad Gateway</html>"),
+            ApiError::ServiceUnavailable(msg) if msg.contains("SEP-24 request failed with 502")
+        ));
+        assert!(matches!(
+            anchor_error("SEP-24", StatusCode::IM_A_TEAPOT, sep_error),
+            ApiError::InternalServerError
+        ));
+    }
+
+    #[test]
+    fn retry_after_accepts_seconds_or_a_date() {
+        let now = chrono::DateTime::parse_from_rfc3339("2015-10-21T07:28:00Z")
+            .unwrap()
+            .with_timezone(&chrono::Utc);
+        let header = |value: &str| {
+            let mut headers = HeaderMap::new();
+            headers.insert(RETRY_AFTER, value.parse().unwrap());
+            headers
+        };
+
+        assert_eq!(retry_after(&header("2"), now), Some(Duration::from_secs(2)));
+        assert_eq!(
+            retry_after(&header("Wed, 21 Oct 2015 07:28:05 GMT"), now),
+            Some(Duration::from_secs(5))
+        );
+        assert_eq!(
+            retry_after(&header("Wed, 21 Oct 2015 07:27:00 GMT"), now),
+            Some(Duration::ZERO)
+        );
+        assert_eq!(retry_after(&header("soon"), now), None);
+        assert_eq!(retry_after(&HeaderMap::new(), now), None);
+    }
+
+    #[test]
+    fn reconcile_backoff_doubles_up_to_cap() {
+        assert_eq!(reconcile_backoff(0, 60, 3600), Duration::from_secs(60));
+        assert_eq!(reconcile_backoff(1, 60, 3600), Duration::from_secs(120));
+        assert_eq!(reconcile_backoff(3, 60, 3600), Duration::from_secs(480));
+        assert_eq!(reconcile_backoff(10, 60, 3600), Duration::from_secs(3600));
+        assert_eq!(
+            reconcile_backoff(i32::MAX, 60, 3600),
+            Duration::from_secs(3600)
+        );
+    }
+
+    #[test]
+    fn withdrawal_limit_bounds_the_amount() {
+        let limit = WithdrawalLimit {
+            min: Some(10_000_000),
+            max: Some(1_000_000_000),
+        };
+
+        let err = check_withdrawal_limit(limit, "USDC", 9_999_999).unwrap_err();
+        assert!(
+            matches!(&err, ApiError::Validation(msg)
+                if msg == "Withdrawal amount must be between 1.0000000 and 100.0000000 USDC"),
+            "{:?}",
+            err
+        );
+        assert!(matches!(
+            check_withdrawal_limit(limit, "USDC:GISSUER", 1_000_000_001),
+            Err(ApiError::Validation(_))
+        ));
+
+        check_withdrawal_limit(limit, "USDC", 10_000_000).unwrap();
+        check_withdrawal_limit(limit, "USDC", 500_000_000).unwrap();
+        check_withdrawal_limit(limit, "USDC", 1_000_000_000).unwrap();
+    }
+
+    #[test]
+    fn withdrawal_limit_may_be_one_sided() {
+        let min_only = WithdrawalLimit {
+            min: Some(100),
+            max: None,
+        };
+        assert!(matches!(
+            check_withdrawal_limit(min_only, "USD", 99),
+            Err(ApiError::Validation(msg)) if msg == "Withdrawal amount must be at least 1.00 USD"
+        ));
+        check_withdrawal_limit(min_only, "USD", i64::MAX).unwrap();
+
+        check_withdrawal_limit(WithdrawalLimit::default(), "USD", 1).unwrap();
+    }
+
+    #[test]
+    fn withdrawal_limit_is_looked_up_by_exact_asset() {
+        let mut config = Config::default();
+        let limit = WithdrawalLimit {
+            min: Some(5),
+            max: None,
+        };
+        config
+            .anchor_config
+            .withdrawal_limits
+            .insert("usdc".to_string(), limit);
+
+        config
+            .anchor_config
+            .withdrawal_limits
+            .insert("EURC:GISSUER".to_string(), limit);
+
+        assert_eq!(config.anchor_config.withdrawal_limit("USDC"), limit);
+        assert_eq!(config.anchor_config.withdrawal_limit("eurc:GISSUER"), limit);
+        // Same code from another (or no) issuer isn't the configured asset
+        for other in ["USDC:GISSUER", "EURC:GOTHER", "EURC"] {
+            assert_eq!(
+                config.anchor_config.withdrawal_limit(other),
+                WithdrawalLimit::default(),
+                "{}",
+                other
+            );
+        }
+    }
+
+    #[test]
+    fn withdrawal_cursor_round_trips() {
+        let cursor = WithdrawalCursor {
+            created_at: chrono::DateTime::parse_from_rfc3339("2026-10-16T09:30:00.123456Z")
+                .unwrap()
+                .with_timezone(&chrono::Utc),
+            id: Uuid::new_v4(),
+        };
+
+        let encoded = cursor.encode();
+
+        assert!(!encoded.contains('|'));
+        assert_eq!(WithdrawalCursor::decode(&encoded).unwrap(), cursor);
+    }
+
+    #[test]
+    fn withdrawal_cursor_rejects_garbage() {
+        assert!(WithdrawalCursor::decode("not a cursor").is_err());
+        assert!(WithdrawalCursor::decode(&URL_SAFE_NO_PAD.encode("2026-10-16|nope")).is_err());
+        assert!(WithdrawalCursor::decode(&URL_SAFE_NO_PAD.encode("no separator")).is_err());
+    }
+}
@@ -0,0 +1,184 @@
+use crate::{api_error::ApiError, config::Config};
+use deadpool_postgres::Pool;
+use ring::{
+    digest,
+    rand::{SecureRandom, SystemRandom},
+};
+use serde::Serialize;
+use std::sync::Arc;
+use tracing::{error, info};
+
+/// Prefix on every issued key so leaked keys are easy to recognise.
+const API_KEY_PREFIX: &str = "zaps_";
+/// Random bytes per key, hex-encoded after the prefix.
+const API_KEY_BYTES: usize = 32;
+/// Leading characters of a key kept in clear to identify it in listings.
+const KEY_PREFIX_LEN: usize = 12;
+
+/// A freshly issued key. `api_key` is the only time the plaintext is exposed.
+#[derive(Debug, Clone, Serialize)]
+pub struct IssuedApiKey {
+    pub id: String,
+    pub merchant_id: String,
+    pub key_prefix: String,
+    pub api_key: String,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+}
+
+/// SHA-256 of a key, hex-encoded, as stored in `merchant_api_keys.key_hash`.
+///
+/// Keys are 256 bits of randomness, so a fast hash is enough — there is
+/// nothing to brute-force the way there is with a password.
+pub fn hash_api_key(api_key: &str) -> String {
+    hex::encode(digest::digest(&digest::SHA256, api_key.as_bytes()))
+}
+
+fn generate_api_key() -> Result<String, ApiError> {
+    let mut bytes = [0u8; API_KEY_BYTES];
+    SystemRandom::new().fill(&mut bytes).map_err(|_| {
+        error!("Failed to generate API key");
+        ApiError::InternalServerError
+    })?;
+    Ok(format!("{}{}", API_KEY_PREFIX, hex::encode(bytes)))
+}
+
+#[derive(Clone)]
+pub struct ApiKeyService {
+    db_pool: Arc<Pool>,
+    _config: Config,
+}
+
+impl ApiKeyService {
+    pub fn new(db_pool: Arc<Pool>, config: Config) -> Self {
+        Self {
+            db_pool,
+            _config: config,
+        }
+    }
+
+    /// Issue a new key for an active merchant.
+    pub async fn issue_key(&self, merchant_id: &str) -> Result<IssuedApiKey, ApiError> {
+        let client = self.db_pool.get().await.map_err(|e| {
+            error!(error = %e, "DB pool error");
+            ApiError::InternalServerError
+        })?;
+
+        let api_key = generate_api_key()?;
+        let key_prefix = api_key[..KEY_PREFIX_LEN].to_string();
+
+        let row = client
+            .query_opt(
+                r#"
+                INSERT INTO merchant_api_keys (merchant_id, key_prefix, key_hash)
+                SELECT merchant_id, $2, $3
+                FROM merchants
+                WHERE merchant_id = $1 AND active = true
+                RETURNING id::text AS id, created_at
+                "#,
+                &[&merchant_id, &key_prefix, &hash_api_key(&api_key)],
+            )
+            .await
+            .map_err(|e| {
+                error!(error = %e, "Failed to insert API key");
+                ApiError::InternalServerError
+            })?
+            .ok_or_else(|| ApiError::NotFound("Merchant not found or inactive".to_string()))?;
+
+        info!(merchant_id, key_prefix, "Issued merchant API key");
+
+        Ok(IssuedApiKey {
+            id: row.get("id"),
+            merchant_id: merchant_id.to_string(),
+            key_prefix,
+            api_key,
+            created_at: row.get("created_at"),
+        })
+    }
+
+    /// Revoke one of a merchant's keys. Revoking an already revoked key is a
+    /// no-op; a key belonging to another merchant is reported as not found.
+    pub async fn revoke_key(&self, merchant_id: &str, key_id: &str) -> Result<(), ApiError> {
+        let client = self.db_pool.get().await.map_err(|e| {
+            error!(error = %e, "DB pool error");
+            ApiError::InternalServerError
+        })?;
+
+        let updated = client
+            .execute(
+                r#"
+                UPDATE merchant_api_keys
+                SET revoked_at = COALESCE(revoked_at, NOW())
+                WHERE id = $1::text::uuid AND merchant_id = $2
+                "#,
+                &[&key_id, &merchant_id],
+            )
+            .await
+            .map_err(|e| {
+                error!(error = %e, "Failed to revoke API key");
+                ApiError::InternalServerError
+            })?;
+
+        if updated == 0 {
+            return Err(ApiError::NotFound(format!("API key {} not found", key_id)));
+        }
+
+        info!(merchant_id, key_id, "Revoked merchant API key");
+        Ok(())
+    }
+
+    /// Resolve a presented key to its merchant.
+    ///
+    /// Returns `None` for unknown or revoked keys and for keys whose merchant
+    /// has been deactivated.
+    pub async fn authenticate(&self, api_key: &str) -> Result<Option<String>, ApiError> {
+        let client = self.db_pool.get().await.map_err(|e| {
+            error!(error = %e, "DB pool error");
+            ApiError::InternalServerError
+        })?;
+
+        let row = client
+            .query_opt(
+                r#"
+                UPDATE merchant_api_keys k
+                SET last_used_at = NOW()
+                FROM merchants m
+                WHERE k.key_hash = $1
+                  AND k.revoked_at IS NULL
+                  AND m.merchant_id = k.merchant_id
+                  AND m.active = true
+                RETURNING k.merchant_id
+                "#,
+                &[&hash_api_key(api_key)],
+            )
+            .await
+            .map_err(|e| {
+                error!(error = %e, "Failed to look up API key");
+                ApiError::InternalServerError
+            })?;
+
+        Ok(row.map(|row| row.get("merchant_id")))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn generated_keys_are_prefixed_and_unique() {
+        let a = generate_api_key().unwrap();
+        let b = generate_api_key().unwrap();
+
+        assert!(a.starts_with(API_KEY_PREFIX));
+        assert_eq!(a.len(), API_KEY_PREFIX.len() + API_KEY_BYTES * 2);
+        assert_ne!(a, b);
+    }
+
+    #[test]
+    fn hash_is_hex_sha256() {
+        let hash = hash_api_key("zaps_example");
+        assert_eq!(hash.len(), 64);
+        assert_eq!(hash, hash_api_key("zaps_example"));
+        assert_ne!(hash, hash_api_key("zaps_other"));
+    }
+}
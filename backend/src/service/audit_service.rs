@@ -1,11 +1,26 @@
 use crate::{
     api_error::ApiError,
     config::Config,
-    models::{AuditLogEntry, AuditLogQueryParams, CreateAuditLogParams},
+    job_processors::MaintenanceTask,
+    models::{
+        AuditChainVerification, AuditLogEntry, AuditLogFilter, AuditLogQueryParams,
+        AuditPurgeResult, CreateAuditLogParams,
+    },
+    service::StorageService,
+    storage::StorageAdapter,
 };
-use chrono::Utc;
-use deadpool_postgres::Pool;
+use async_trait::async_trait;
+use axum::body::Bytes;
+use chrono::{DateTime, SubsecRound, Utc};
+use deadpool_postgres::{Pool, Transaction};
+use flate2::{write::GzEncoder, Compression};
+use futures::{Stream, StreamExt};
+use ring::digest;
+use std::io::Write;
+use std::net::IpAddr;
 use std::sync::Arc;
+use tokio_postgres::Row;
+use tracing::{error, info};
 use uuid::Uuid;
 
 #[derive(Clone)]
@@ -21,78 +36,175 @@ impl AuditService {
     }
 
     /// Create a new audit log entry (immutable)
+    ///
+    /// The entry is appended to the hash chain: its `prev_hash` is the
+    /// `entry_hash` of the latest chained row. An advisory lock serialises
+    /// writers so two entries can never claim the same predecessor.
     pub async fn create_audit_log(
         &self,
         params: CreateAuditLogParams,
     ) -> Result<AuditLogEntry, ApiError> {
+        let mut client = self.db_pool.get().await?;
+        let tx = client.transaction().await?;
+        let entry = append_audit_log(&tx, params).await?;
+        tx.commit().await?;
+        Ok(entry)
+    }
+
+    /// Walk the hash chain from oldest to newest and report the first break.
+    ///
+    /// Rows written before chaining was introduced are skipped. The first
+    /// chained row's `prev_hash` is taken as the anchor, so purging old rows
+    /// does not invalidate the remainder of the chain.
+    pub async fn verify_chain(&self) -> Result<AuditChainVerification, ApiError> {
         let client = self.db_pool.get().await?;
 
-        let id = Uuid::new_v4().to_string();
-        let timestamp = Utc::now();
+        let query = format!(
+            "SELECT {} FROM audit_logs WHERE entry_hash IS NOT NULL ORDER BY chain_seq ASC",
+            AUDIT_LOG_COLUMNS
+        );
+        let rows = client.query_raw(&query, std::iter::empty::<&str>()).await?;
+        futures::pin_mut!(rows);
 
-        let row = client
-            .query_one(
-                "INSERT INTO audit_logs (id, actor_id, action, resource, resource_id, metadata, timestamp, ip_address, user_agent)
-                 VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
-                 RETURNING id, actor_id, action, resource, resource_id, metadata, timestamp, ip_address, user_agent",
-                &[
-                    &id,
-                    &params.actor_id,
-                    &params.action,
-                    &params.resource,
-                    &params.resource_id,
-                    &params.metadata,
-                    &timestamp,
-                    &params.ip_address,
-                    &params.user_agent,
-                ],
-            )
+        let mut verifier = ChainVerifier::default();
+        while let Some(row) = rows.next().await {
+            if !verifier.check(&audit_log_from_row(&row?)) {
+                break;
+            }
+        }
+
+        Ok(verifier.finish())
+    }
+
+    /// Delete every entry older than `cutoff`, oldest first, in batches of
+    /// `audit.purge_batch_size`.
+    ///
+    /// When `archive` is given each batch is first uploaded to it as a
+    /// gzipped JSON array; a batch's export and delete run in one transaction
+    /// so the archives always contain exactly what was removed.
+    pub async fn purge_older_than(
+        &self,
+        cutoff: DateTime<Utc>,
+        archive: Option<&dyn StorageAdapter>,
+    ) -> Result<AuditPurgeResult, ApiError> {
+        let batch_size = self.config.audit.purge_batch_size.max(1);
+        let mut result = AuditPurgeResult {
+            deleted: 0,
+            archive_ids: Vec::new(),
+        };
+
+        loop {
+            let (deleted, archive_id) = self.purge_batch(cutoff, batch_size, archive).await?;
+            result.deleted += deleted;
+            result.archive_ids.extend(archive_id);
+            if deleted < batch_size as u64 {
+                break;
+            }
+        }
+
+        info!(
+            deleted = result.deleted,
+            %cutoff,
+            archives = result.archive_ids.len(),
+            "Purged expired audit logs"
+        );
+        Ok(result)
+    }
+
+    /// Delete, and optionally archive, up to `limit` of the oldest entries
+    /// before `cutoff` in one transaction.
+    async fn purge_batch(
+        &self,
+        cutoff: DateTime<Utc>,
+        limit: i64,
+        archive: Option<&dyn StorageAdapter>,
+    ) -> Result<(u64, Option<String>), ApiError> {
+        let mut client = self.db_pool.get().await?;
+        let tx = client.transaction().await?;
+
+        let query = format!(
+            "SELECT {} FROM audit_logs WHERE timestamp < $1
+             ORDER BY chain_seq ASC LIMIT $2 FOR UPDATE",
+            AUDIT_LOG_COLUMNS
+        );
+        let entries: Vec<AuditLogEntry> = tx
+            .query(&query, &[&cutoff, &limit])
+            .await?
+            .iter()
+            .map(audit_log_from_row)
+            .collect();
+        if entries.is_empty() {
+            return Ok((0, None));
+        }
+
+        let archive_id = match archive {
+            Some(storage) => {
+                let name = format!(
+                    "audit-logs_before_{}_from_{}.json.gz",
+                    cutoff.format("%Y%m%dT%H%M%SZ"),
+                    entries[0].id
+                );
+                let data = gzip(&serde_json::to_vec(&entries)?).map_err(|e| {
+                    error!(error = %e, "Failed to compress audit log archive");
+                    ApiError::InternalServerError
+                })?;
+                let stored = storage
+                    .upload(Bytes::from(data), &name, "application/gzip", None)
+                    .map_err(|e| {
+                        error!(error = %e, "Failed to archive audit logs before purge");
+                        ApiError::InternalServerError
+                    })?;
+                Some(stored.id)
+            }
+            None => None,
+        };
+
+        let ids = entries
+            .iter()
+            .map(|entry| Uuid::parse_str(&entry.id))
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|_| ApiError::InternalServerError)?;
+
+        // The immutability trigger only lets deletes through with this set.
+        tx.batch_execute("SET LOCAL audit.allow_purge = 'on'")
             .await?;
+        let deleted = tx
+            .execute("DELETE FROM audit_logs WHERE id = ANY($1)", &[&ids])
+            .await?;
+        tx.commit().await?;
+
+        Ok((deleted, archive_id))
+    }
+
+    /// Purge entries outside the configured retention window, archiving to
+    /// `storage` first when `audit.archive_before_purge` is set.
+    pub async fn purge_expired(
+        &self,
+        storage: &StorageService,
+    ) -> Result<AuditPurgeResult, ApiError> {
+        let cutoff = Utc::now() - chrono::Duration::days(self.config.audit.retention_days.into());
+        let archive = self
+            .config
+            .audit
+            .archive_before_purge
+            .then(|| storage.adapter.as_ref());
 
-        Ok(AuditLogEntry {
-            id: row.get("id"),
-            actor_id: row.get("actor_id"),
-            action: row.get("action"),
-            resource: row.get("resource"),
-            resource_id: row.get("resource_id"),
-            metadata: row
-                .try_get::<_, Option<serde_json::Value>>("metadata")
-                .ok()
-                .flatten(),
-            timestamp: row.get("timestamp"),
-            ip_address: row.get("ip_address"),
-            user_agent: row.get("user_agent"),
-        })
+        self.purge_older_than(cutoff, archive).await
     }
 
     /// Get a single audit log entry by ID
     pub async fn get_audit_log(&self, id: &str) -> Result<AuditLogEntry, ApiError> {
+        let id = Uuid::parse_str(id)
+            .map_err(|_| ApiError::NotFound("Audit log not found".to_string()))?;
         let client = self.db_pool.get().await?;
 
+        let query = format!("SELECT {} FROM audit_logs WHERE id = $1", AUDIT_LOG_COLUMNS);
         let row = client
-            .query_opt(
-                "SELECT id, actor_id, action, resource, resource_id, metadata, timestamp, ip_address, user_agent
-                 FROM audit_logs
-                 WHERE id = $1",
-                &[&id],
-            )
+            .query_opt(&query, &[&id])
             .await?
             .ok_or_else(|| ApiError::NotFound("Audit log not found".to_string()))?;
 
-        Ok(AuditLogEntry {
-            id: row.get("id"),
-            actor_id: row.get("actor_id"),
-            action: row.get("action"),
-            resource: row.get("resource"),
-            resource_id: row.get("resource_id"),
-            metadata: row
-                .try_get::<_, Option<serde_json::Value>>("metadata")
-                .ok()
-                .flatten(),
-            timestamp: row.get("timestamp"),
-            ip_address: row.get("ip_address"),
-            user_agent: row.get("user_agent"),
-        })
+        Ok(audit_log_from_row(&row))
     }
 
     /// List audit logs with filtering
@@ -103,37 +215,12 @@ impl AuditService {
         let client = self.db_pool.get().await?;
 
         // Build dynamic query based on filters
-        let mut query = String::from(
-            "SELECT id, actor_id, action, resource, resource_id, metadata, timestamp, ip_address, user_agent
-             FROM audit_logs
-             WHERE 1=1",
+        let (where_clause, mut params_vec) = build_filter_clause(&params.filter());
+        let mut query = format!(
+            "SELECT {} FROM audit_logs{}",
+            AUDIT_LOG_COLUMNS, where_clause
         );
-        let mut param_index = 1;
-        let mut params_vec: Vec<Box<dyn tokio_postgres::types::ToSql + Sync + Send>> = Vec::new();
-
-        if let Some(ref actor_id) = params.actor_id {
-            query.push_str(&format!(" AND actor_id = ${}", param_index));
-            params_vec.push(Box::new(actor_id.clone()));
-            param_index += 1;
-        }
-
-        if let Some(ref action) = params.action {
-            query.push_str(&format!(" AND action = ${}", param_index));
-            params_vec.push(Box::new(action.clone()));
-            param_index += 1;
-        }
-
-        if let Some(ref from_date) = params.from_date {
-            query.push_str(&format!(" AND timestamp >= ${}", param_index));
-            params_vec.push(Box::new(*from_date));
-            param_index += 1;
-        }
-
-        if let Some(ref to_date) = params.to_date {
-            query.push_str(&format!(" AND timestamp <= ${}", param_index));
-            params_vec.push(Box::new(*to_date));
-            param_index += 1;
-        }
+        let param_index = params_vec.len() + 1;
 
         query.push_str(" ORDER BY timestamp DESC");
 
@@ -156,66 +243,427 @@ impl AuditService {
 
         let rows = client.query(&query, &param_refs[..]).await?;
 
-        let logs = rows
-            .into_iter()
-            .map(|row| AuditLogEntry {
-                id: row.get("id"),
-                actor_id: row.get("actor_id"),
-                action: row.get("action"),
-                resource: row.get("resource"),
-                resource_id: row.get("resource_id"),
-                metadata: row
-                    .try_get::<_, Option<serde_json::Value>>("metadata")
-                    .ok()
-                    .flatten(),
-                timestamp: row.get("timestamp"),
-                ip_address: row.get("ip_address"),
-                user_agent: row.get("user_agent"),
-            })
-            .collect();
-
-        Ok(logs)
+        Ok(rows.iter().map(audit_log_from_row).collect())
     }
 
     /// Count audit logs for pagination
     pub async fn count_audit_logs(&self, params: &AuditLogQueryParams) -> Result<i64, ApiError> {
         let client = self.db_pool.get().await?;
 
-        let mut query = String::from("SELECT COUNT(*) FROM audit_logs WHERE 1=1");
-        let mut param_index = 1;
-        let mut params_vec: Vec<Box<dyn tokio_postgres::types::ToSql + Sync + Send>> = Vec::new();
+        let (where_clause, params_vec) = build_filter_clause(&params.filter());
+        let query = format!("SELECT COUNT(*) FROM audit_logs{}", where_clause);
+
+        let param_refs: Vec<&(dyn tokio_postgres::types::ToSql + Sync)> = params_vec
+            .iter()
+            .map(|p| p.as_ref() as &(dyn tokio_postgres::types::ToSql + Sync))
+            .collect();
+
+        let row = client.query_one(&query, &param_refs[..]).await?;
+        let count: i64 = row.get(0);
+
+        Ok(count)
+    }
+
+    /// Stream every audit log matching `filter`, oldest first.
+    ///
+    /// Rows are read from the database as the stream is polled rather than
+    /// being collected up front, so exports of any size use constant memory.
+    pub async fn export_audit_logs(
+        &self,
+        filter: &AuditLogFilter,
+    ) -> Result<impl Stream<Item = Result<AuditLogEntry, ApiError>> + Send + 'static, ApiError>
+    {
+        let client = self.db_pool.get().await?;
+
+        let (where_clause, params_vec) = build_filter_clause(filter);
+        let query = format!(
+            "SELECT {} FROM audit_logs{} ORDER BY timestamp ASC",
+            AUDIT_LOG_COLUMNS, where_clause
+        );
+
+        let rows = client.query_raw(&query, params_vec).await?;
+
+        // Keep the pooled connection checked out until the stream is dropped.
+        Ok(rows.map(move |row| {
+            let _client = &client;
+            Ok(audit_log_from_row(&row?))
+        }))
+    }
+}
+
+// `id` is a UUID and `ip_address` an INET column; both are read back as text.
+const AUDIT_LOG_COLUMNS: &str =
+    "id::text AS id, actor_id, action, resource, resource_id, metadata, \
+     timestamp, host(ip_address) AS ip_address, user_agent, prev_hash, entry_hash";
+
+type SqlParams = Vec<Box<dyn tokio_postgres::types::ToSql + Sync + Send>>;
+
+/// Build the ` WHERE ...` clause for `filter`, numbering placeholders from `$1`.
+fn build_filter_clause(filter: &AuditLogFilter) -> (String, SqlParams) {
+    let mut clause = String::from(" WHERE 1=1");
+    let mut params_vec: SqlParams = Vec::new();
+
+    if let Some(ref actor_id) = filter.actor_id {
+        params_vec.push(Box::new(actor_id.clone()));
+        clause.push_str(&format!(" AND actor_id = ${}", params_vec.len()));
+    }
+
+    if let Some(ref action) = filter.action {
+        params_vec.push(Box::new(action.clone()));
+        clause.push_str(&format!(" AND action = ${}", params_vec.len()));
+    }
+
+    if let Some(from_date) = filter.from_date {
+        params_vec.push(Box::new(from_date));
+        clause.push_str(&format!(" AND timestamp >= ${}", params_vec.len()));
+    }
+
+    if let Some(to_date) = filter.to_date {
+        params_vec.push(Box::new(to_date));
+        clause.push_str(&format!(" AND timestamp <= ${}", params_vec.len()));
+    }
+
+    (clause, params_vec)
+}
+
+/// Append an entry to the audit chain inside the caller's transaction, so it
+/// is committed or rolled back together with the change it records.
+///
+/// The chain lock is held until that transaction ends.
+pub async fn append_audit_log(
+    tx: &Transaction<'_>,
+    params: CreateAuditLogParams,
+) -> Result<AuditLogEntry, ApiError> {
+    tx.execute("SELECT pg_advisory_xact_lock($1)", &[&AUDIT_CHAIN_LOCK_KEY])
+        .await?;
+
+    let prev_hash = tx
+        .query_opt(
+            "SELECT entry_hash FROM audit_logs
+             WHERE entry_hash IS NOT NULL
+             ORDER BY chain_seq DESC
+             LIMIT 1",
+            &[],
+        )
+        .await?
+        .map(|row| row.get::<_, String>(0))
+        .unwrap_or_else(|| GENESIS_HASH.to_string());
+
+    // Postgres keeps microseconds and normalises INET values, so do the
+    // same here to make the hash match what is read back later.
+    let mut entry = AuditLogEntry {
+        id: Uuid::new_v4().to_string(),
+        actor_id: params.actor_id,
+        action: params.action,
+        resource: params.resource,
+        resource_id: params.resource_id,
+        metadata: params.metadata,
+        timestamp: params.occurred_at.unwrap_or_else(Utc::now).trunc_subsecs(6),
+        ip_address: params
+            .ip_address
+            .and_then(|ip| ip.trim().parse::<IpAddr>().ok())
+            .map(|ip| ip.to_string()),
+        user_agent: params.user_agent,
+        prev_hash: Some(prev_hash.clone()),
+        entry_hash: None,
+    };
+    let entry_hash = compute_entry_hash(&prev_hash, &entry);
+
+    tx.execute(
+        "INSERT INTO audit_logs (id, actor_id, action, resource, resource_id, metadata, timestamp,
+                                 ip_address, user_agent, prev_hash, entry_hash)
+         VALUES ($1::text::uuid, $2, $3, $4, $5, $6, $7, $8::text::inet, $9, $10, $11)",
+        &[
+            &entry.id,
+            &entry.actor_id,
+            &entry.action,
+            &entry.resource,
+            &entry.resource_id,
+            &entry.metadata,
+            &entry.timestamp,
+            &entry.ip_address,
+            &entry.user_agent,
+            &prev_hash,
+            &entry_hash,
+        ],
+    )
+    .await?;
+
+    entry.entry_hash = Some(entry_hash);
+    Ok(entry)
+}
+
+/// Advisory lock key held while appending to the hash chain.
+const AUDIT_CHAIN_LOCK_KEY: i64 = 0x6175_6469_745f_6c6f; // "audit_lo"
+
+/// `prev_hash` of the very first chained entry.
+pub const GENESIS_HASH: &str = "0000000000000000000000000000000000000000000000000000000000000000";
+
+/// SHA-256 over an entry's fields and its predecessor's hash, hex-encoded.
+///
+/// Fields are encoded as a JSON array so values containing separators cannot
+/// be shifted between fields without changing the hash.
+pub fn compute_entry_hash(prev_hash: &str, entry: &AuditLogEntry) -> String {
+    let material = serde_json::json!([
+        prev_hash,
+        entry.id,
+        entry.actor_id,
+        entry.action,
+        entry.resource,
+        entry.resource_id,
+        entry.metadata.as_ref().map(canonical_json),
+        entry
+            .timestamp
+            .to_rfc3339_opts(chrono::SecondsFormat::Micros, true),
+        entry.ip_address,
+        entry.user_agent,
+    ]);
 
-        if let Some(ref actor_id) = params.actor_id {
-            query.push_str(&format!(" AND actor_id = ${}", param_index));
-            params_vec.push(Box::new(actor_id.clone()));
-            param_index += 1;
+    hex::encode(digest::digest(
+        &digest::SHA256,
+        material.to_string().as_bytes(),
+    ))
+}
+
+/// The `audit_retention` maintenance task: [`AuditService::purge_expired`]
+/// with the storage its archives go to.
+#[derive(Clone)]
+pub struct AuditRetention {
+    audit: AuditService,
+    storage: StorageService,
+}
+
+impl AuditRetention {
+    pub fn new(audit: AuditService, storage: StorageService) -> Self {
+        Self { audit, storage }
+    }
+}
+
+#[async_trait]
+impl MaintenanceTask for AuditRetention {
+    async fn run(&self) -> anyhow::Result<()> {
+        self.audit.purge_expired(&self.storage).await?;
+        Ok(())
+    }
+}
+
+fn gzip(data: &[u8]) -> std::io::Result<Vec<u8>> {
+    let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+    encoder.write_all(data)?;
+    encoder.finish()
+}
+
+/// Serialise JSON with object keys sorted, independent of map ordering.
+fn canonical_json(value: &serde_json::Value) -> String {
+    match value {
+        serde_json::Value::Object(map) => {
+            let mut fields: Vec<_> = map.iter().collect();
+            fields.sort_by(|a, b| a.0.cmp(b.0));
+            let body = fields
+                .into_iter()
+                .map(|(k, v)| {
+                    format!(
+                        "{}:{}",
+                        serde_json::Value::from(k.as_str()),
+                        canonical_json(v)
+                    )
+                })
+                .collect::<Vec<_>>()
+                .join(",");
+            format!("{{{}}}", body)
+        }
+        serde_json::Value::Array(items) => {
+            let body = items
+                .iter()
+                .map(canonical_json)
+                .collect::<Vec<_>>()
+                .join(",");
+            format!("[{}]", body)
         }
+        other => other.to_string(),
+    }
+}
+
+/// Incrementally checks that each entry links to the previous one and that
+/// its stored hash matches its contents.
+#[derive(Default)]
+struct ChainVerifier {
+    expected_prev: Option<String>,
+    checked: i64,
+    broken_at: Option<String>,
+    reason: Option<String>,
+}
 
-        if let Some(ref action) = params.action {
-            query.push_str(&format!(" AND action = ${}", param_index));
-            params_vec.push(Box::new(action.clone()));
-            param_index += 1;
+impl ChainVerifier {
+    /// Returns `false` once a break has been found.
+    fn check(&mut self, entry: &AuditLogEntry) -> bool {
+        let prev_hash = entry.prev_hash.as_deref().unwrap_or_default();
+        let stored_hash = entry.entry_hash.as_deref().unwrap_or_default();
+        self.checked += 1;
+
+        if let Some(expected) = &self.expected_prev {
+            if prev_hash != expected {
+                return self.fail(entry, "prev_hash does not match the preceding entry");
+            }
         }
 
-        if let Some(ref from_date) = params.from_date {
-            query.push_str(&format!(" AND timestamp >= ${}", param_index));
-            params_vec.push(Box::new(*from_date));
-            param_index += 1;
+        if compute_entry_hash(prev_hash, entry) != stored_hash {
+            return self.fail(entry, "entry_hash does not match the entry contents");
         }
 
-        if let Some(ref to_date) = params.to_date {
-            query.push_str(&format!(" AND timestamp <= ${}", param_index));
-            params_vec.push(Box::new(*to_date));
+        self.expected_prev = Some(stored_hash.to_string());
+        true
+    }
+
+    fn fail(&mut self, entry: &AuditLogEntry, reason: &str) -> bool {
+        self.broken_at = Some(entry.id.clone());
+        self.reason = Some(reason.to_string());
+        false
+    }
+
+    fn finish(self) -> AuditChainVerification {
+        AuditChainVerification {
+            valid: self.broken_at.is_none(),
+            entries_checked: self.checked,
+            broken_at: self.broken_at,
+            reason: self.reason,
         }
+    }
+}
 
-        let param_refs: Vec<&(dyn tokio_postgres::types::ToSql + Sync)> = params_vec
-            .iter()
-            .map(|p| p.as_ref() as &(dyn tokio_postgres::types::ToSql + Sync))
-            .collect();
+fn audit_log_from_row(row: &Row) -> AuditLogEntry {
+    AuditLogEntry {
+        id: row.get("id"),
+        actor_id: row.get("actor_id"),
+        action: row.get("action"),
+        resource: row.get("resource"),
+        resource_id: row.get("resource_id"),
+        metadata: row
+            .try_get::<_, Option<serde_json::Value>>("metadata")
+            .ok()
+            .flatten(),
+        timestamp: row.get("timestamp"),
+        ip_address: row.get("ip_address"),
+        user_agent: row.get("user_agent"),
+        prev_hash: row.get("prev_hash"),
+        entry_hash: row.get("entry_hash"),
+    }
+}
 
-        let row = client.query_one(&query, &param_refs[..]).await?;
-        let count: i64 = row.get(0);
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
 
-        Ok(count)
+    fn entry(n: u32) -> AuditLogEntry {
+        AuditLogEntry {
+            id: format!("00000000-0000-0000-0000-{:012}", n),
+            actor_id: "admin".to_string(),
+            action: "POST".to_string(),
+            resource: "/payments".to_string(),
+            resource_id: Some(n.to_string()),
+            metadata: Some(serde_json::json!({ "status": 201, "path": "/payments" })),
+            timestamp: Utc.with_ymd_and_hms(2026, 3, 1, 12, 0, n).unwrap(),
+            ip_address: Some("127.0.0.1".to_string()),
+            user_agent: None,
+            prev_hash: None,
+            entry_hash: None,
+        }
+    }
+
+    fn chain(len: u32) -> Vec<AuditLogEntry> {
+        let mut prev = GENESIS_HASH.to_string();
+        (0..len)
+            .map(|n| {
+                let mut e = entry(n);
+                let hash = compute_entry_hash(&prev, &e);
+                e.prev_hash = Some(std::mem::replace(&mut prev, hash.clone()));
+                e.entry_hash = Some(hash);
+                e
+            })
+            .collect()
+    }
+
+    fn verify(entries: &[AuditLogEntry]) -> AuditChainVerification {
+        let mut verifier = ChainVerifier::default();
+        for e in entries {
+            if !verifier.check(e) {
+                break;
+            }
+        }
+        verifier.finish()
+    }
+
+    #[test]
+    fn unmodified_chain_verifies() {
+        let result = verify(&chain(5));
+
+        assert!(result.valid);
+        assert_eq!(result.entries_checked, 5);
+        assert_eq!(result.broken_at, None);
+    }
+
+    #[test]
+    fn mutated_entry_is_detected() {
+        let mut entries = chain(5);
+        entries[2].actor_id = "someone-else".to_string();
+
+        let result = verify(&entries);
+
+        assert!(!result.valid);
+        assert_eq!(result.broken_at.as_deref(), Some(entries[2].id.as_str()));
+        assert_eq!(result.entries_checked, 3);
+    }
+
+    #[test]
+    fn rehashed_entry_breaks_the_next_link() {
+        let mut entries = chain(5);
+        entries[2].actor_id = "someone-else".to_string();
+        let prev = entries[2].prev_hash.clone().unwrap();
+        entries[2].entry_hash = Some(compute_entry_hash(&prev, &entries[2]));
+
+        let result = verify(&entries);
+
+        assert!(!result.valid);
+        assert_eq!(result.broken_at.as_deref(), Some(entries[3].id.as_str()));
+    }
+
+    #[test]
+    fn deleted_entry_is_detected() {
+        let mut entries = chain(5);
+        entries.remove(1);
+
+        let result = verify(&entries);
+
+        assert!(!result.valid);
+        assert_eq!(result.broken_at.as_deref(), Some(entries[1].id.as_str()));
+    }
+
+    #[test]
+    fn gzip_round_trips() {
+        use std::io::Read;
+
+        let data = br#"[{"id":"1"},{"id":"2"}]"#.repeat(50);
+        let gz = gzip(&data).unwrap();
+        assert!(gz.len() < data.len());
+
+        let mut decoded = Vec::new();
+        flate2::read::GzDecoder::new(&gz[..])
+            .read_to_end(&mut decoded)
+            .unwrap();
+        assert_eq!(decoded, data);
+    }
+
+    #[test]
+    fn hash_ignores_metadata_key_order() {
+        let mut a = entry(1);
+        let mut b = entry(1);
+        a.metadata = serde_json::from_str(r#"{"a":1,"b":{"x":true,"y":null}}"#).ok();
+        b.metadata = serde_json::from_str(r#"{"b":{"y":null,"x":true},"a":1}"#).ok();
+
+        assert_eq!(
+            compute_entry_hash(GENESIS_HASH, &a),
+            compute_entry_hash(GENESIS_HASH, &b)
+        );
     }
 }
@@ -0,0 +1,365 @@
+use crate::{
+    api_error::ApiError,
+    assets::{asset_info, STELLAR_DECIMALS},
+    config::Config,
+    job_processors::MaintenanceTask,
+    service::{AlertPayload, AlertSeverity, MetricsService, SorobanService},
+};
+use async_trait::async_trait;
+use deadpool_postgres::{Pool, Transaction};
+use serde::Serialize;
+use std::sync::Arc;
+use tracing::{error, info, warn};
+
+/// A `balances` row compared with what its owner holds on-chain.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct BalanceReconciliation {
+    pub owner_id: String,
+    pub asset: String,
+    /// Stellar account the owner's funds are held in.
+    pub address: String,
+    /// Both amounts are in the asset's smallest unit, as `balances` stores
+    /// them.
+    pub db_amount: i64,
+    /// `None` when the account, or its trustline for the asset, doesn't
+    /// exist on-chain; counted as zero. Converted from stroops, dropping any
+    /// dust below the asset's precision.
+    pub on_chain_amount: Option<i64>,
+    /// On-chain minus database amount.
+    pub drift: i64,
+}
+
+impl BalanceReconciliation {
+    pub fn is_balanced(&self) -> bool {
+        self.drift == 0
+    }
+}
+
+/// The alert raised for a balance that has drifted from the chain.
+pub fn drift_alert(report: &BalanceReconciliation) -> AlertPayload {
+    AlertPayload {
+        severity: AlertSeverity::Critical,
+        title: "Balance Drift".to_string(),
+        message: format!(
+            "{} balance of {} is {} in the database but {} on-chain ({})",
+            report.asset,
+            report.owner_id,
+            report.db_amount,
+            report.on_chain_amount.unwrap_or(0),
+            report.address
+        ),
+        metric_name: "balance_drift".to_string(),
+        current_value: report.drift as f64,
+        threshold: 0.0,
+        timestamp: chrono::Utc::now(),
+    }
+}
+
+/// `stroops` of `asset` in the asset's own smallest unit.
+fn from_stroops(asset: &str, stroops: i64) -> i64 {
+    let decimals = asset_info(asset).decimals;
+    if decimals >= STELLAR_DECIMALS {
+        stroops.saturating_mul(10i64.pow(decimals - STELLAR_DECIMALS))
+    } else {
+        stroops / 10i64.pow(STELLAR_DECIMALS - decimals)
+    }
+}
+
+fn check_amount(amount: i64) -> Result<(), ApiError> {
+    if amount <= 0 {
+        return Err(ApiError::Validation(
+            "Amount must be greater than zero".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+#[derive(Clone)]
+pub struct BalanceService {
+    db_pool: Arc<Pool>,
+    config: Config,
+    soroban: SorobanService,
+    metrics: MetricsService,
+}
+
+impl BalanceService {
+    pub fn new(db_pool: Arc<Pool>, config: Config) -> Self {
+        Self {
+            soroban: SorobanService::new(config.clone()),
+            metrics: MetricsService::new(),
+            db_pool,
+            config,
+        }
+    }
+
+    /// Like `new`, but reading on-chain balances through `http`.
+    pub fn with_http_client(db_pool: Arc<Pool>, config: Config, http: reqwest::Client) -> Self {
+        Self {
+            soroban: SorobanService::with_http_client(config.clone(), http.clone()),
+            metrics: MetricsService::with_http_client(http),
+            db_pool,
+            config,
+        }
+    }
+
+    /// Add `amount` to an owner's balance of `asset`, returning the new
+    /// balance.
+    pub async fn credit(&self, owner_id: &str, asset: &str, amount: i64) -> Result<i64, ApiError> {
+        let mut client = self.db_pool.get().await?;
+        let tx = client.transaction().await?;
+        let balance = Self::credit_in(&tx, owner_id, asset, amount).await?;
+        tx.commit().await?;
+        Ok(balance)
+    }
+
+    /// Take `amount` from an owner's balance of `asset`, returning the new
+    /// balance. Fails without changing anything if the balance is short.
+    pub async fn debit(&self, owner_id: &str, asset: &str, amount: i64) -> Result<i64, ApiError> {
+        let mut client = self.db_pool.get().await?;
+        let tx = client.transaction().await?;
+        let balance = Self::debit_in(&tx, owner_id, asset, amount).await?;
+        tx.commit().await?;
+        Ok(balance)
+    }
+
+    /// `credit` as part of the caller's transaction. The balance row stays
+    /// locked until `tx` ends.
+    pub async fn credit_in(
+        tx: &Transaction<'_>,
+        owner_id: &str,
+        asset: &str,
+        amount: i64,
+    ) -> Result<i64, ApiError> {
+        check_amount(amount)?;
+        tx.execute(
+            r#"
+            INSERT INTO balances (owner_id, asset, amount) VALUES ($1, $2, 0)
+            ON CONFLICT (owner_id, asset) DO NOTHING
+            "#,
+            &[&owner_id, &asset],
+        )
+        .await?;
+        let current = Self::lock(tx, owner_id, asset).await?.unwrap_or(0);
+        let balance = current
+            .checked_add(amount)
+            .ok_or_else(|| ApiError::Validation("Balance would overflow".to_string()))?;
+        Self::store(tx, owner_id, asset, balance).await?;
+        Ok(balance)
+    }
+
+    /// `debit` as part of the caller's transaction. The balance row stays
+    /// locked until `tx` ends.
+    pub async fn debit_in(
+        tx: &Transaction<'_>,
+        owner_id: &str,
+        asset: &str,
+        amount: i64,
+    ) -> Result<i64, ApiError> {
+        check_amount(amount)?;
+        let current = Self::lock(tx, owner_id, asset).await?.unwrap_or(0);
+        if current < amount {
+            return Err(ApiError::Validation(format!(
+                "Insufficient {} balance",
+                asset
+            )));
+        }
+        let balance = current - amount;
+        Self::store(tx, owner_id, asset, balance).await?;
+        Ok(balance)
+    }
+
+    /// Read a balance with `FOR UPDATE`, so concurrent changes to it queue
+    /// behind `tx` instead of overwriting each other.
+    async fn lock(
+        tx: &Transaction<'_>,
+        owner_id: &str,
+        asset: &str,
+    ) -> Result<Option<i64>, ApiError> {
+        let row = tx
+            .query_opt(
+                "SELECT amount FROM balances WHERE owner_id = $1 AND asset = $2 FOR UPDATE",
+                &[&owner_id, &asset],
+            )
+            .await?;
+        Ok(row.map(|row| row.get("amount")))
+    }
+
+    async fn store(
+        tx: &Transaction<'_>,
+        owner_id: &str,
+        asset: &str,
+        amount: i64,
+    ) -> Result<(), ApiError> {
+        tx.execute(
+            r#"
+            UPDATE balances SET amount = $3, last_updated = NOW()
+            WHERE owner_id = $1 AND asset = $2
+            "#,
+            &[&owner_id, &asset, &amount],
+        )
+        .await?;
+        Ok(())
+    }
+
+    /// Compare an owner's recorded balance of `asset` with the chain. The
+    /// owner is a user, whose funds sit at their Stellar address, or a
+    /// merchant, whose funds sit in its vault.
+    pub async fn reconcile(
+        &self,
+        owner_id: &str,
+        asset: &str,
+    ) -> Result<BalanceReconciliation, ApiError> {
+        let client = self.db_pool.get().await?;
+
+        let row = client
+            .query_opt(
+                r#"
+                SELECT b.amount, COALESCE(u.stellar_address, m.vault_address) AS address
+                FROM balances b
+                LEFT JOIN users u ON u.user_id = b.owner_id
+                LEFT JOIN merchants m ON m.merchant_id = b.owner_id
+                WHERE b.owner_id = $1 AND b.asset = $2
+                "#,
+                &[&owner_id, &asset],
+            )
+            .await?
+            .ok_or_else(|| ApiError::NotFound("Balance not found".to_string()))?;
+
+        let db_amount: i64 = row.get("amount");
+        let address: String = row
+            .get::<_, Option<String>>("address")
+            .ok_or_else(|| ApiError::NotFound(format!("{} has no on-chain account", owner_id)))?;
+
+        let on_chain_amount = self
+            .soroban
+            .get_account_balance(&address, asset)
+            .await?
+            .map(|stroops| from_stroops(asset, stroops));
+
+        Ok(BalanceReconciliation {
+            owner_id: owner_id.to_string(),
+            asset: asset.to_string(),
+            address,
+            db_amount,
+            on_chain_amount,
+            drift: on_chain_amount.unwrap_or(0) - db_amount,
+        })
+    }
+
+    /// Reconcile every balance, returning an alert per drifted one. Each
+    /// alert is also sent to `balance_reconciliation.alert_webhook_url`
+    /// when set. A balance that can't be checked is logged and skipped.
+    pub async fn reconcile_all(&self) -> Result<Vec<AlertPayload>, ApiError> {
+        let batch_size = self.config.balance_reconciliation.batch_size;
+        let mut alerts = Vec::new();
+        let mut after = (String::new(), String::new());
+
+        loop {
+            let client = self.db_pool.get().await?;
+            let rows = client
+                .query(
+                    r#"
+                    SELECT owner_id, asset FROM balances
+                    WHERE (owner_id, asset) > ($1, $2)
+                    ORDER BY owner_id, asset
+                    LIMIT $3
+                    "#,
+                    &[&after.0, &after.1, &batch_size],
+                )
+                .await?;
+            drop(client);
+
+            for row in &rows {
+                let (owner_id, asset): (String, String) = (row.get(0), row.get(1));
+                match self.reconcile(&owner_id, &asset).await {
+                    Ok(report) if !report.is_balanced() => {
+                        let alert = drift_alert(&report);
+                        error!(
+                            owner_id,
+                            asset,
+                            db_amount = report.db_amount,
+                            on_chain_amount = report.on_chain_amount.unwrap_or(0),
+                            drift = report.drift,
+                            "CRITICAL ALERT triggered: {}",
+                            alert.message
+                        );
+                        self.send_alert(&alert).await;
+                        alerts.push(alert);
+                    }
+                    Ok(_) => {}
+                    Err(e) => error!(owner_id, asset, error = %e, "Balance reconciliation failed"),
+                }
+                after = (owner_id, asset);
+            }
+
+            if (rows.len() as i64) < batch_size {
+                break;
+            }
+        }
+
+        Ok(alerts)
+    }
+
+    async fn send_alert(&self, alert: &AlertPayload) {
+        let Some(url) = &self.config.balance_reconciliation.alert_webhook_url else {
+            return;
+        };
+        if let Err(e) = self.metrics.send_alert_webhook(alert, url).await {
+            warn!(error = %e, "Failed to send balance drift alert");
+        }
+    }
+}
+
+#[async_trait]
+impl MaintenanceTask for BalanceService {
+    async fn run(&self) -> anyhow::Result<()> {
+        let alerts = self.reconcile_all().await?;
+        if !alerts.is_empty() {
+            info!(drifted = alerts.len(), "Balance reconciliation found drift");
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn only_positive_amounts_move_balances() {
+        assert!(check_amount(1).is_ok());
+        for amount in [0, -1, i64::MIN] {
+            assert!(matches!(check_amount(amount), Err(ApiError::Validation(_))));
+        }
+    }
+
+    #[test]
+    fn drift_alert_describes_both_sides() {
+        let report = BalanceReconciliation {
+            owner_id: "alice".to_string(),
+            asset: "USDC".to_string(),
+            address: "GALICE".to_string(),
+            db_amount: 1_000,
+            on_chain_amount: None,
+            drift: -1_000,
+        };
+
+        let alert = drift_alert(&report);
+
+        assert!(!report.is_balanced());
+        assert!(matches!(alert.severity, AlertSeverity::Critical));
+        assert_eq!(alert.metric_name, "balance_drift");
+        assert_eq!(alert.current_value, -1_000.0);
+        assert!(alert
+            .message
+            .contains("1000 in the database but 0 on-chain"));
+    }
+
+    #[test]
+    fn on_chain_stroops_are_scaled_to_the_asset_unit() {
+        assert_eq!(from_stroops("USDC", 975_000_000), 975_000_000);
+        assert_eq!(from_stroops("USDC:GISSUER", 1), 1);
+        // 12.34 USD held on-chain as 123400000 stroops, plus dust
+        assert_eq!(from_stroops("USD", 123_400_001), 1_234);
+    }
+}
@@ -0,0 +1,222 @@
+use crate::api_error::ApiError;
+use crate::config::CircuitBreakerConfig;
+use serde::Serialize;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// Where a breaker is in its closed → open → half-open cycle.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BreakerState {
+    /// Calls go through; failures are being counted.
+    Closed,
+    /// Calls are refused until the cooldown ends.
+    Open,
+    /// The cooldown has ended; the next call probes the dependency.
+    HalfOpen,
+}
+
+#[derive(Debug, Default)]
+struct Inner {
+    consecutive_failures: u32,
+    opened_at: Option<Instant>,
+    /// When the half-open probe was let through, if one is outstanding.
+    probe_started: Option<Instant>,
+}
+
+/// Counts consecutive failures of a dependency and refuses calls to it for a
+/// cooldown once `failure_threshold` is reached, so requests fail fast
+/// instead of each waiting out a timeout. Clones share state.
+#[derive(Clone)]
+pub struct CircuitBreaker {
+    name: &'static str,
+    failure_threshold: u32,
+    cooldown: Duration,
+    inner: Arc<Mutex<Inner>>,
+}
+
+impl CircuitBreaker {
+    pub fn new(name: &'static str, config: &CircuitBreakerConfig) -> Self {
+        Self {
+            name,
+            failure_threshold: config.failure_threshold.max(1),
+            cooldown: Duration::from_secs(config.cooldown_seconds),
+            inner: Arc::new(Mutex::new(Inner::default())),
+        }
+    }
+
+    pub fn state(&self) -> BreakerState {
+        self.state_at(Instant::now())
+    }
+
+    /// Ask to make a call. Refused with `ServiceUnavailable` while open, and
+    /// while half-open if another call is already probing.
+    pub fn acquire(&self) -> Result<(), ApiError> {
+        self.acquire_at(Instant::now())
+    }
+
+    pub fn record_success(&self) {
+        let mut inner = self.lock();
+        if inner.opened_at.is_some() {
+            tracing::info!(breaker = self.name, "Circuit breaker closed");
+        }
+        *inner = Inner::default();
+    }
+
+    pub fn record_failure(&self) {
+        self.record_failure_at(Instant::now())
+    }
+
+    fn state_at(&self, now: Instant) -> BreakerState {
+        match self.lock().opened_at {
+            None => BreakerState::Closed,
+            Some(opened_at) if now.duration_since(opened_at) < self.cooldown => BreakerState::Open,
+            Some(_) => BreakerState::HalfOpen,
+        }
+    }
+
+    fn acquire_at(&self, now: Instant) -> Result<(), ApiError> {
+        let mut inner = self.lock();
+        let Some(opened_at) = inner.opened_at else {
+            return Ok(());
+        };
+
+        // A probe that never reported back (e.g. its request was dropped)
+        // doesn't hold the breaker half-open forever.
+        let probing = inner
+            .probe_started
+            .is_some_and(|started| now.duration_since(started) < self.cooldown);
+        if now.duration_since(opened_at) < self.cooldown || probing {
+            return Err(ApiError::ServiceUnavailable(format!(
+                "{} is unavailable, try again later",
+                self.name
+            )));
+        }
+
+        inner.probe_started = Some(now);
+        Ok(())
+    }
+
+    fn record_failure_at(&self, now: Instant) {
+        let mut inner = self.lock();
+        inner.consecutive_failures = inner.consecutive_failures.saturating_add(1);
+
+        let probe_failed = inner.probe_started.take().is_some();
+        if probe_failed || inner.consecutive_failures == self.failure_threshold {
+            tracing::warn!(
+                breaker = self.name,
+                failures = inner.consecutive_failures,
+                cooldown_seconds = self.cooldown.as_secs(),
+                "Circuit breaker opened"
+            );
+            inner.opened_at = Some(now);
+        }
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, Inner> {
+        self.inner.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const COOLDOWN: Duration = Duration::from_secs(30);
+
+    fn breaker() -> CircuitBreaker {
+        CircuitBreaker::new(
+            "anchor",
+            &CircuitBreakerConfig {
+                failure_threshold: 3,
+                cooldown_seconds: COOLDOWN.as_secs(),
+            },
+        )
+    }
+
+    #[test]
+    fn repeated_failures_open_the_breaker() {
+        let breaker = breaker();
+        let now = Instant::now();
+
+        for _ in 0..2 {
+            breaker.acquire_at(now).unwrap();
+            breaker.record_failure_at(now);
+        }
+        assert_eq!(breaker.state_at(now), BreakerState::Closed);
+
+        breaker.acquire_at(now).unwrap();
+        breaker.record_failure_at(now);
+        assert_eq!(breaker.state_at(now), BreakerState::Open);
+        assert!(matches!(
+            breaker.acquire_at(now + COOLDOWN / 2),
+            Err(ApiError::ServiceUnavailable(_))
+        ));
+    }
+
+    #[test]
+    fn a_success_resets_the_failure_count() {
+        let breaker = breaker();
+        let now = Instant::now();
+
+        breaker.record_failure_at(now);
+        breaker.record_failure_at(now);
+        breaker.record_success();
+        breaker.record_failure_at(now);
+        breaker.record_failure_at(now);
+
+        assert_eq!(breaker.state_at(now), BreakerState::Closed);
+    }
+
+    #[test]
+    fn successful_probe_closes_the_breaker() {
+        let breaker = breaker();
+        let now = Instant::now();
+        for _ in 0..3 {
+            breaker.record_failure_at(now);
+        }
+
+        let after_cooldown = now + COOLDOWN;
+        assert_eq!(breaker.state_at(after_cooldown), BreakerState::HalfOpen);
+        breaker.acquire_at(after_cooldown).unwrap();
+        // Only one probe at a time.
+        assert!(breaker.acquire_at(after_cooldown).is_err());
+
+        breaker.record_success();
+        assert_eq!(breaker.state_at(after_cooldown), BreakerState::Closed);
+        breaker.acquire_at(after_cooldown).unwrap();
+    }
+
+    #[test]
+    fn failed_probe_reopens_the_breaker() {
+        let breaker = breaker();
+        let now = Instant::now();
+        for _ in 0..3 {
+            breaker.record_failure_at(now);
+        }
+
+        let probe_at = now + COOLDOWN;
+        breaker.acquire_at(probe_at).unwrap();
+        breaker.record_failure_at(probe_at);
+
+        assert_eq!(breaker.state_at(probe_at), BreakerState::Open);
+        assert!(breaker.acquire_at(probe_at + COOLDOWN / 2).is_err());
+        assert_eq!(
+            breaker.state_at(probe_at + COOLDOWN),
+            BreakerState::HalfOpen
+        );
+    }
+
+    #[test]
+    fn abandoned_probe_does_not_wedge_the_breaker() {
+        let breaker = breaker();
+        let now = Instant::now();
+        for _ in 0..3 {
+            breaker.record_failure_at(now);
+        }
+
+        breaker.acquire_at(now + COOLDOWN).unwrap();
+        // The probe never reports back.
+        breaker.acquire_at(now + COOLDOWN * 2).unwrap();
+    }
+}
@@ -1,36 +1,97 @@
 use crate::{
     api_error::ApiError,
+    auth,
     config::Config,
-    models::{User, Wallet},
+    custodial::CustodialKeys,
+    models::{CreateAuditLogParams, User, Wallet},
     role::Role,
+    service::audit_service::append_audit_log,
 };
 use deadpool_postgres::Pool;
+use std::collections::HashMap;
 use std::str::FromStr;
-use std::sync::Arc;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tracing::{info, warn};
 use uuid::Uuid;
 
+/// Shortest interval between two writes of a user's `last_seen_at`.
+pub const LAST_SEEN_THROTTLE: Duration = Duration::from_secs(60);
+
+/// Users tracked by [`LastSeenThrottle`] before stale entries are swept.
+const MAX_TRACKED_USERS: usize = 100_000;
+
+/// In-process record of when each user's `last_seen_at` write was last
+/// attempted, so requests inside `LAST_SEEN_THROTTLE` skip the database
+/// entirely. The conditional `UPDATE` still guards across instances.
+#[derive(Clone, Default)]
+pub struct LastSeenThrottle {
+    attempts: Arc<Mutex<HashMap<String, Instant>>>,
+}
+
+impl LastSeenThrottle {
+    /// Whether a write is due for `user_id`; if so, it is recorded as
+    /// attempted now.
+    pub fn due(&self, user_id: &str) -> bool {
+        self.due_at(user_id, Instant::now())
+    }
+
+    fn due_at(&self, user_id: &str, now: Instant) -> bool {
+        // The map holds no invariants a panicking holder could break.
+        let mut attempts = self.attempts.lock().unwrap_or_else(|e| e.into_inner());
+        if attempts
+            .get(user_id)
+            .is_some_and(|last| now.duration_since(*last) < LAST_SEEN_THROTTLE)
+        {
+            return false;
+        }
+
+        if attempts.len() >= MAX_TRACKED_USERS {
+            attempts.retain(|_, last| now.duration_since(*last) < LAST_SEEN_THROTTLE);
+            if attempts.len() >= MAX_TRACKED_USERS {
+                attempts.clear();
+            }
+        }
+        attempts.insert(user_id.to_string(), now);
+        true
+    }
+}
+
 #[derive(Clone)]
-#[allow(dead_code)]
 pub struct IdentityService {
     db_pool: Arc<Pool>,
     config: Config,
+    keys: CustodialKeys,
+    last_seen: LastSeenThrottle,
 }
 
 impl IdentityService {
     pub fn new(db_pool: Arc<Pool>, config: Config) -> Self {
-        Self { db_pool, config }
+        let keys = CustodialKeys::new(&config.custodial.master_seed);
+        Self {
+            db_pool,
+            config,
+            keys,
+            last_seen: LastSeenThrottle::default(),
+        }
+    }
+
+    /// Whether `user_id` is due a `last_seen_at` write from this instance.
+    pub fn last_seen_due(&self, user_id: &str) -> bool {
+        self.last_seen.due(user_id)
     }
 
     pub async fn create_user(&self, user_id: String, pin_hash: String) -> Result<User, ApiError> {
         let client = self.db_pool.get().await?;
 
-        // Generate a unique Stellar address (in production, this would be generated properly)
-        let stellar_address = format!("G{}", Uuid::new_v4().simple().to_string().to_uppercase());
+        // The secret is re-derived from the custodial master seed when needed,
+        // so only the public address is stored.
+        let stellar_address = self.keys.derive(&user_id).address;
 
         let role_str = Role::User.as_str();
         let row = client
             .query_one(
-                "INSERT INTO users (user_id, stellar_address, role, pin_hash) VALUES ($1, $2, $3, $4) RETURNING id, user_id, stellar_address, role, created_at, updated_at",
+                "INSERT INTO users (user_id, stellar_address, role, pin_hash) VALUES ($1, $2, $3, $4) RETURNING id, user_id, stellar_address, role, created_at, updated_at, last_seen_at",
                 &[&user_id, &stellar_address, &role_str, &pin_hash],
             )
             .await?;
@@ -42,6 +103,7 @@ impl IdentityService {
             role: Role::from_str(row.get::<_, &str>(3)).unwrap(),
             created_at: row.get::<_, chrono::DateTime<chrono::Utc>>(4),
             updated_at: row.get::<_, chrono::DateTime<chrono::Utc>>(5),
+            last_seen_at: row.get(6),
             address: row.get(2),
         })
     }
@@ -51,7 +113,7 @@ impl IdentityService {
 
         let row = client
             .query_one(
-                "SELECT id, user_id, stellar_address, role, pin_hash, created_at, updated_at FROM users WHERE user_id = $1",
+                "SELECT id, user_id, stellar_address, role, pin_hash, created_at, updated_at, last_seen_at FROM users WHERE user_id = $1",
                 &[&user_id],
             )
             .await
@@ -64,19 +126,67 @@ impl IdentityService {
             role: Role::from_str(row.get::<_, &str>(3)).unwrap(),
             created_at: row.get::<_, chrono::DateTime<chrono::Utc>>(5),
             updated_at: row.get::<_, chrono::DateTime<chrono::Utc>>(6),
+            last_seen_at: row.get(7),
             address: row.get(2),
         };
-        let pin_hash: String = row.get(4);
+        // Users mirrored from the on-chain registry have no PIN to log in with.
+        let pin_hash: String = row
+            .get::<_, Option<String>>(4)
+            .ok_or_else(|| ApiError::Authentication("Invalid credentials".to_string()))?;
 
         Ok((user, pin_hash))
     }
 
+    /// Check a user's PIN. A hash stored under a lower cost than
+    /// `pin_hash.cost` is replaced while the PIN is at hand.
+    pub async fn verify_credentials(&self, user_id: &str, pin: &str) -> Result<User, ApiError> {
+        let (user, pin_hash) = self.get_user_with_pin_hash(user_id).await?;
+
+        if !auth::verify_pin(pin, &pin_hash)? {
+            return Err(ApiError::Authentication("Invalid credentials".to_string()));
+        }
+
+        let cost = self.config.pin_hash.cost;
+        if auth::pin_needs_rehash(&pin_hash, cost) {
+            // The login stands either way; a failed upgrade is retried next time.
+            if let Err(e) = self.upgrade_pin_hash(user_id, pin, &pin_hash, cost).await {
+                warn!(user_id, error = %e, "Failed to upgrade PIN hash");
+            }
+        }
+
+        Ok(user)
+    }
+
+    async fn upgrade_pin_hash(
+        &self,
+        user_id: &str,
+        pin: &str,
+        current_hash: &str,
+        cost: u32,
+    ) -> Result<(), ApiError> {
+        let upgraded = auth::hash_pin(pin, cost)?;
+        let client = self.db_pool.get().await?;
+
+        // Only replace the hash that was verified, not a PIN changed meanwhile.
+        let updated = client
+            .execute(
+                "UPDATE users SET pin_hash = $3, updated_at = NOW() WHERE user_id = $1 AND pin_hash = $2",
+                &[&user_id, &current_hash, &upgraded],
+            )
+            .await?;
+
+        if updated > 0 {
+            info!(user_id, cost, "PIN hash upgraded");
+        }
+        Ok(())
+    }
+
     pub async fn get_user_by_id(&self, user_id: &str) -> Result<User, ApiError> {
         let client = self.db_pool.get().await?;
 
         let row = client
             .query_one(
-                "SELECT id, user_id, stellar_address, role, created_at, updated_at FROM users WHERE user_id = $1",
+                "SELECT id, user_id, stellar_address, role, created_at, updated_at, last_seen_at FROM users WHERE user_id = $1",
                 &[&user_id],
             )
             .await
@@ -89,10 +199,32 @@ impl IdentityService {
             role: Role::from_str(row.get::<_, &str>(3)).unwrap(),
             created_at: row.get::<_, chrono::DateTime<chrono::Utc>>(4),
             updated_at: row.get::<_, chrono::DateTime<chrono::Utc>>(5),
+            last_seen_at: row.get(6),
             address: row.get(2),
         })
     }
 
+    /// Record that `user_id` just authenticated. Skipped if it was already
+    /// recorded within `LAST_SEEN_THROTTLE`; returns whether it was written.
+    pub async fn touch_last_seen(&self, user_id: &str) -> Result<bool, ApiError> {
+        let client = self.db_pool.get().await?;
+        let throttle = LAST_SEEN_THROTTLE.as_secs_f64();
+
+        let updated = client
+            .execute(
+                r#"
+                UPDATE users SET last_seen_at = NOW()
+                WHERE user_id = $1
+                  AND (last_seen_at IS NULL
+                       OR last_seen_at <= NOW() - make_interval(secs => $2))
+                "#,
+                &[&user_id, &throttle],
+            )
+            .await?;
+
+        Ok(updated > 0)
+    }
+
     pub async fn get_user_wallet(&self, user_id: &str) -> Result<Wallet, ApiError> {
         let user = self.get_user_by_id(user_id).await?;
 
@@ -117,4 +249,120 @@ impl IdentityService {
 
         Ok(count > 0)
     }
+
+    /// Change `user_id`'s role on behalf of `actor_id`, returning the role it
+    /// had before.
+    ///
+    /// The user's row and every admin row are locked for the change, so two
+    /// admins demoting each other at once can't leave no admin behind. An
+    /// actual change is audited in the same transaction.
+    pub async fn set_role(
+        &self,
+        user_id: &str,
+        role: Role,
+        actor_id: &str,
+    ) -> Result<Role, ApiError> {
+        let mut client = self.db_pool.get().await?;
+        let tx = client.transaction().await?;
+
+        // Lock in a consistent order to avoid deadlocking concurrent changes.
+        let rows = tx
+            .query(
+                "SELECT user_id, role FROM users
+                 WHERE user_id = $1 OR role = $2
+                 ORDER BY user_id
+                 FOR UPDATE",
+                &[&user_id, &Role::Admin.as_str()],
+            )
+            .await?;
+
+        let previous = rows
+            .iter()
+            .find(|row| row.get::<_, &str>(0) == user_id)
+            .map(|row| Role::from_str(row.get::<_, &str>(1)).unwrap())
+            .ok_or_else(|| ApiError::NotFound("User not found".to_string()))?;
+        let admins = rows
+            .iter()
+            .filter(|row| Role::from_str(row.get::<_, &str>(1)).unwrap() == Role::Admin)
+            .count();
+        ensure_admin_remains(previous, role, admins)?;
+
+        if previous != role {
+            tx.execute(
+                "UPDATE users SET role = $1, updated_at = NOW() WHERE user_id = $2",
+                &[&role.as_str(), &user_id],
+            )
+            .await?;
+            append_audit_log(
+                &tx,
+                CreateAuditLogParams {
+                    actor_id: actor_id.to_string(),
+                    action: "update_user_role".to_string(),
+                    resource: "users".to_string(),
+                    resource_id: Some(user_id.to_string()),
+                    metadata: Some(serde_json::json!({
+                        "from": previous,
+                        "to": role,
+                    })),
+                    ip_address: None,
+                    user_agent: None,
+                    occurred_at: None,
+                },
+            )
+            .await?;
+            info!(
+                user_id,
+                from = previous.as_str(),
+                to = role.as_str(),
+                "User role changed"
+            );
+        }
+        tx.commit().await?;
+
+        Ok(previous)
+    }
+}
+
+/// Refuse a change that would demote the only remaining admin.
+fn ensure_admin_remains(previous: Role, role: Role, admins: usize) -> Result<(), ApiError> {
+    if previous == Role::Admin && role != Role::Admin && admins <= 1 {
+        return Err(ApiError::Conflict(
+            "Cannot demote the last admin".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn last_admin_cannot_be_demoted() {
+        for role in [Role::Merchant, Role::User] {
+            assert!(matches!(
+                ensure_admin_remains(Role::Admin, role, 1),
+                Err(ApiError::Conflict(_))
+            ));
+        }
+    }
+
+    #[test]
+    fn other_role_changes_are_allowed() {
+        ensure_admin_remains(Role::Admin, Role::User, 2).unwrap();
+        ensure_admin_remains(Role::Admin, Role::Admin, 1).unwrap();
+        ensure_admin_remains(Role::User, Role::Admin, 1).unwrap();
+        ensure_admin_remains(Role::Merchant, Role::User, 0).unwrap();
+    }
+
+    #[test]
+    fn last_seen_writes_are_throttled_per_user() {
+        let throttle = LastSeenThrottle::default();
+        let start = Instant::now();
+
+        assert!(throttle.due_at("alice", start));
+        assert!(!throttle.due_at("alice", start + Duration::from_secs(59)));
+        assert!(throttle.due_at("bob", start + Duration::from_secs(59)));
+        assert!(throttle.due_at("alice", start + LAST_SEEN_THROTTLE));
+    }
 }
@@ -0,0 +1,364 @@
+use crate::{api_error::ApiError, config::Config};
+use deadpool_postgres::Pool;
+use regex::Regex;
+use ring::rand::{SecureRandom, SystemRandom};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use tracing::{error, info};
+
+/// Longest text memo a Stellar transaction can carry, in bytes.
+pub const MAX_TEXT_MEMO_BYTES: usize = 28;
+/// Prefix on generated order references.
+const REFERENCE_PREFIX: &str = "ZAP-";
+/// Random bytes per generated reference, hex-encoded after the prefix.
+const REFERENCE_BYTES: usize = 6;
+/// Upper bound on compiled patterns kept; the cache starts over when full.
+const MAX_CACHED_PATTERNS: usize = 1024;
+
+/// How a merchant's payment memos must look.
+#[derive(Debug, Clone, Serialize)]
+pub struct MemoPolicy {
+    pub merchant_id: String,
+    /// Regex the whole memo must match; any memo fits when unset.
+    pub pattern: Option<String>,
+    /// Generate an order reference for payments that omit a memo.
+    pub auto_generate: bool,
+    pub updated_at: chrono::DateTime<chrono::Utc>,
+}
+
+/// The memo a payment is created with.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PaymentMemo {
+    pub memo: Option<String>,
+    /// The memo is an order reference under the merchant's memo policy, so
+    /// no other payment to the merchant may carry it.
+    pub unique: bool,
+}
+
+/// Compiled policy patterns, keyed by their source, so a pattern is
+/// compiled once rather than on every payment.
+#[derive(Clone, Default)]
+pub struct PatternCache {
+    patterns: Arc<Mutex<HashMap<String, Regex>>>,
+}
+
+impl PatternCache {
+    fn get(&self, pattern: &str) -> Result<Regex, ApiError> {
+        if let Some(regex) = self.lock().get(pattern) {
+            return Ok(regex.clone());
+        }
+
+        let regex = compile_pattern(pattern)?;
+        let mut patterns = self.lock();
+        if patterns.len() >= MAX_CACHED_PATTERNS {
+            patterns.clear();
+        }
+        patterns.insert(pattern.to_string(), regex.clone());
+        Ok(regex)
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, HashMap<String, Regex>> {
+        self.patterns.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+impl MemoPolicy {
+    /// The memo to put on a payment given the one the client sent, if any.
+    pub fn resolve(
+        &self,
+        memo: Option<&str>,
+        patterns: &PatternCache,
+    ) -> Result<Option<String>, ApiError> {
+        let memo = match memo {
+            Some(memo) => memo.to_string(),
+            None if self.auto_generate => generate_reference()?,
+            None => return Ok(None),
+        };
+
+        check_text_memo(&memo)?;
+        if let Some(pattern) = &self.pattern {
+            if !patterns.get(pattern)?.is_match(&memo) {
+                return Err(ApiError::Validation(format!(
+                    "memo must match the merchant's format {}",
+                    pattern
+                )));
+            }
+        }
+        Ok(Some(memo))
+    }
+}
+
+/// Reject memos that don't fit in a Stellar text memo.
+pub fn check_text_memo(memo: &str) -> Result<(), ApiError> {
+    if memo.len() > MAX_TEXT_MEMO_BYTES {
+        return Err(ApiError::Validation(format!(
+            "memo must be at most {} bytes",
+            MAX_TEXT_MEMO_BYTES
+        )));
+    }
+    Ok(())
+}
+
+/// `pattern` anchored so it has to match the whole memo.
+fn compile_pattern(pattern: &str) -> Result<Regex, ApiError> {
+    Regex::new(&format!("^(?:{})$", pattern))
+        .map_err(|e| ApiError::Validation(format!("pattern is not a valid regex: {}", e)))
+}
+
+/// A random order reference like `ZAP-3F9A0C12B7E4`.
+fn generate_reference() -> Result<String, ApiError> {
+    let mut bytes = [0u8; REFERENCE_BYTES];
+    SystemRandom::new().fill(&mut bytes).map_err(|_| {
+        error!("Failed to generate memo reference");
+        ApiError::InternalServerError
+    })?;
+    Ok(format!("{}{}", REFERENCE_PREFIX, hex::encode_upper(bytes)))
+}
+
+/// References covering every hex digit in every position, as
+/// `generate_reference` could produce them.
+fn reference_samples() -> Vec<String> {
+    const DIGITS: &str = "0123456789ABCDEF";
+    let width = 2 * REFERENCE_BYTES;
+    let repeated = DIGITS.chars().map(|digit| digit.to_string().repeat(width));
+    let ascending = (0..DIGITS.len()).map(|start| {
+        DIGITS
+            .chars()
+            .cycle()
+            .skip(start)
+            .take(width)
+            .collect::<String>()
+    });
+    repeated
+        .chain(ascending)
+        .map(|digits| format!("{}{}", REFERENCE_PREFIX, digits))
+        .collect()
+}
+
+/// Check a policy before it is stored, so every memo it generates passes it.
+/// The pattern has to accept a fixed set of sample references rather than
+/// one random reference, so the outcome doesn't depend on luck.
+fn check_policy(pattern: Option<&str>, auto_generate: bool) -> Result<(), ApiError> {
+    let Some(pattern) = pattern else {
+        return Ok(());
+    };
+    let regex = compile_pattern(pattern)?;
+    if auto_generate
+        && !reference_samples()
+            .iter()
+            .all(|reference| regex.is_match(reference))
+    {
+        return Err(ApiError::Validation(format!(
+            "pattern must accept generated references like {}{}",
+            REFERENCE_PREFIX,
+            "0".repeat(2 * REFERENCE_BYTES)
+        )));
+    }
+    Ok(())
+}
+
+#[derive(Clone)]
+pub struct MemoPolicyService {
+    db_pool: Arc<Pool>,
+    _config: Config,
+    patterns: PatternCache,
+}
+
+impl MemoPolicyService {
+    pub fn new(db_pool: Arc<Pool>, config: Config) -> Self {
+        Self {
+            db_pool,
+            _config: config,
+            patterns: PatternCache::default(),
+        }
+    }
+
+    /// Set an active merchant's memo policy, replacing any it already had.
+    pub async fn set_policy(
+        &self,
+        merchant_id: &str,
+        pattern: Option<&str>,
+        auto_generate: bool,
+    ) -> Result<MemoPolicy, ApiError> {
+        check_policy(pattern, auto_generate)?;
+        let client = self.db_pool.get().await?;
+
+        let row = client
+            .query_opt(
+                r#"
+                INSERT INTO merchant_memo_policies (merchant_id, pattern, auto_generate)
+                SELECT merchant_id, $2, $3
+                FROM merchants
+                WHERE merchant_id = $1 AND active = true
+                ON CONFLICT (merchant_id) DO UPDATE
+                SET pattern = EXCLUDED.pattern,
+                    auto_generate = EXCLUDED.auto_generate,
+                    updated_at = NOW()
+                RETURNING updated_at
+                "#,
+                &[&merchant_id, &pattern, &auto_generate],
+            )
+            .await?
+            .ok_or_else(|| ApiError::NotFound("Merchant not found or inactive".to_string()))?;
+
+        info!(
+            merchant_id,
+            pattern, auto_generate, "Set merchant memo policy"
+        );
+
+        Ok(MemoPolicy {
+            merchant_id: merchant_id.to_string(),
+            pattern: pattern.map(str::to_string),
+            auto_generate,
+            updated_at: row.get("updated_at"),
+        })
+    }
+
+    /// The merchant's memo policy, if it has set one.
+    pub async fn for_merchant(&self, merchant_id: &str) -> Result<Option<MemoPolicy>, ApiError> {
+        let client = self.db_pool.get().await?;
+
+        let row = client
+            .query_opt(
+                "SELECT pattern, auto_generate, updated_at FROM merchant_memo_policies WHERE merchant_id = $1",
+                &[&merchant_id],
+            )
+            .await?;
+
+        Ok(row.map(|row| MemoPolicy {
+            merchant_id: merchant_id.to_string(),
+            pattern: row.get("pattern"),
+            auto_generate: row.get("auto_generate"),
+            updated_at: row.get("updated_at"),
+        }))
+    }
+
+    /// The memo for a payment to `merchant_id`: `memo` checked against the
+    /// merchant's policy, or a generated reference if the policy asks for
+    /// one. Without a policy any memo that fits in a text memo is accepted.
+    ///
+    /// Under a policy the memo is an order reference, and one already on
+    /// another payment to the merchant is a conflict. The unique index on
+    /// `payments` backs this check up against concurrent payments.
+    pub async fn resolve_memo(
+        &self,
+        merchant_id: &str,
+        memo: Option<&str>,
+    ) -> Result<PaymentMemo, ApiError> {
+        let Some(policy) = self.for_merchant(merchant_id).await? else {
+            memo.map(check_text_memo).transpose()?;
+            return Ok(PaymentMemo {
+                memo: memo.map(str::to_string),
+                unique: false,
+            });
+        };
+
+        let memo = policy.resolve(memo, &self.patterns)?;
+        if let Some(memo) = &memo {
+            let taken = self
+                .db_pool
+                .get()
+                .await?
+                .query_opt(
+                    "SELECT 1 FROM payments WHERE merchant_id = $1 AND memo = $2 AND memo_unique",
+                    &[&merchant_id, memo],
+                )
+                .await?
+                .is_some();
+            if taken {
+                return Err(memo_taken());
+            }
+        }
+        Ok(PaymentMemo { memo, unique: true })
+    }
+}
+
+/// The error for an order reference already used on another payment.
+pub fn memo_taken() -> ApiError {
+    ApiError::Conflict("memo is already used by another payment to this merchant".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn policy(pattern: Option<&str>, auto_generate: bool) -> MemoPolicy {
+        MemoPolicy {
+            merchant_id: "merchant-1".to_string(),
+            pattern: pattern.map(str::to_string),
+            auto_generate,
+            updated_at: chrono::Utc::now(),
+        }
+    }
+
+    #[test]
+    fn memo_matching_the_pattern_is_kept() {
+        let policy = policy(Some(r"ORD-\d{6}"), false);
+        let patterns = PatternCache::default();
+
+        assert_eq!(
+            policy
+                .resolve(Some("ORD-004217"), &patterns)
+                .unwrap()
+                .as_deref(),
+            Some("ORD-004217")
+        );
+        // The pattern has to match the whole memo
+        assert!(policy.resolve(Some("x ORD-004217"), &patterns).is_err());
+        assert!(policy.resolve(Some("ORD-42"), &patterns).is_err());
+        assert_eq!(policy.resolve(None, &patterns).unwrap(), None);
+        // Compiled once, then reused
+        assert_eq!(patterns.lock().len(), 1);
+    }
+
+    #[test]
+    fn over_length_memo_is_rejected() {
+        let memo = "a".repeat(MAX_TEXT_MEMO_BYTES + 1);
+
+        assert!(matches!(
+            policy(None, false).resolve(Some(&memo), &PatternCache::default()),
+            Err(ApiError::Validation(msg)) if msg.contains("28 bytes")
+        ));
+        assert!(check_text_memo(&"a".repeat(MAX_TEXT_MEMO_BYTES)).is_ok());
+        // Bytes, not characters
+        assert!(check_text_memo(&"é".repeat(15)).is_err());
+    }
+
+    #[test]
+    fn omitted_memo_is_generated_when_asked() {
+        let policy = policy(Some(r"ZAP-[0-9A-F]{12}"), true);
+        let patterns = PatternCache::default();
+
+        let first = policy.resolve(None, &patterns).unwrap().unwrap();
+        let second = policy.resolve(None, &patterns).unwrap().unwrap();
+
+        assert!(first.starts_with(REFERENCE_PREFIX));
+        assert!(first.len() <= MAX_TEXT_MEMO_BYTES);
+        assert_ne!(first, second);
+        // A memo the client did send is still used as-is
+        assert_eq!(
+            policy
+                .resolve(Some("ZAP-000000000001"), &patterns)
+                .unwrap()
+                .as_deref(),
+            Some("ZAP-000000000001")
+        );
+    }
+
+    #[test]
+    fn policies_must_accept_their_own_references() {
+        assert!(check_policy(None, true).is_ok());
+        assert!(check_policy(Some(r"ORD-\d+"), false).is_ok());
+        assert!(check_policy(Some(r"ORD-\d+"), true).is_err());
+        assert!(check_policy(Some("ORD-("), false).is_err());
+        // Patterns accepting only some references are always caught
+        for partial in [r"ZAP-[0-9]{12}", r"ZAP-[0-9A-E]{12}", r"ZAP-[0-9A-F]{11}F"] {
+            assert!(check_policy(Some(partial), true).is_err(), "{}", partial);
+        }
+        assert!(check_policy(Some(r"ZAP-[0-9A-F]{12}"), true).is_ok());
+        assert!(reference_samples()
+            .iter()
+            .all(|reference| reference.len() == generate_reference().unwrap().len()));
+    }
+}
@@ -0,0 +1,195 @@
+use crate::{
+    api_error::ApiError,
+    config::{Config, RegistrySyncConfig},
+    models::FeeStrategy,
+    service::SorobanService,
+};
+use axum::async_trait;
+use deadpool_postgres::Pool;
+use serde::Serialize;
+use serde_json::json;
+use std::sync::Arc;
+use tracing::{error, info, warn};
+
+/// Merchant administration in the on-chain registry contract.
+#[async_trait]
+pub trait MerchantRegistry: Send + Sync {
+    /// Run `deactivate_merchant(merchant_id)`, returning the transaction hash
+    /// once it has succeeded on-chain.
+    async fn deactivate_merchant(&self, merchant_id: &str) -> Result<String, ApiError>;
+}
+
+/// The deployed registry contract, called as its admin account.
+pub struct RegistryContract {
+    soroban: SorobanService,
+    contract_id: String,
+    admin_account: String,
+    admin_secret: String,
+}
+
+impl RegistryContract {
+    pub fn new(soroban: SorobanService, config: &RegistrySyncConfig) -> Self {
+        Self {
+            soroban,
+            contract_id: config.contract_id.clone(),
+            admin_account: config.admin_account.clone(),
+            admin_secret: config.admin_secret.clone(),
+        }
+    }
+}
+
+#[async_trait]
+impl MerchantRegistry for RegistryContract {
+    async fn deactivate_merchant(&self, merchant_id: &str) -> Result<String, ApiError> {
+        if self.contract_id.is_empty()
+            || self.admin_account.is_empty()
+            || self.admin_secret.is_empty()
+        {
+            return Err(ApiError::ServiceUnavailable(
+                "Registry contract admin is not configured".to_string(),
+            ));
+        }
+
+        self.soroban
+            .invoke_contract(
+                &self.admin_account,
+                &self.admin_secret,
+                &self.contract_id,
+                "deactivate_merchant",
+                vec![json!({ "bytes": hex::encode(merchant_id) })],
+            )
+            .await
+    }
+}
+
+/// A merchant deactivated both in the database and on-chain.
+#[derive(Debug, Clone, Serialize)]
+pub struct MerchantDeactivation {
+    pub merchant_id: String,
+    /// Hash of the on-chain `deactivate_merchant` transaction.
+    pub tx_hash: String,
+}
+
+#[derive(Clone)]
+pub struct MerchantService {
+    db_pool: Arc<Pool>,
+    _config: Config,
+    registry: Arc<dyn MerchantRegistry>,
+}
+
+impl MerchantService {
+    pub fn new(db_pool: Arc<Pool>, config: Config) -> Self {
+        let registry =
+            RegistryContract::new(SorobanService::new(config.clone()), &config.registry_sync);
+        Self::with_registry(db_pool, config, Arc::new(registry))
+    }
+
+    /// Like `new`, but deactivating merchants on-chain through `registry`.
+    pub fn with_registry(
+        db_pool: Arc<Pool>,
+        config: Config,
+        registry: Arc<dyn MerchantRegistry>,
+    ) -> Self {
+        Self {
+            db_pool,
+            _config: config,
+            registry,
+        }
+    }
+
+    /// Deactivate an active merchant on-chain, then in the database.
+    ///
+    /// The row is only marked inactive once the registry transaction has
+    /// succeeded on-chain, so the database never shows a merchant inactive
+    /// that the chain still accepts. No connection or row lock is held while
+    /// the transaction is in flight.
+    pub async fn deactivate(&self, merchant_id: &str) -> Result<MerchantDeactivation, ApiError> {
+        let active = self
+            .db_pool
+            .get()
+            .await?
+            .query_opt(
+                "SELECT 1 FROM merchants WHERE merchant_id = $1 AND active = true",
+                &[&merchant_id],
+            )
+            .await?
+            .is_some();
+        if !active {
+            return Err(ApiError::NotFound(
+                "Merchant not found or inactive".to_string(),
+            ));
+        }
+
+        let tx_hash = self
+            .registry
+            .deactivate_merchant(merchant_id)
+            .await
+            .inspect_err(|e| {
+                error!(merchant_id, error = %e, "On-chain merchant deactivation failed");
+            })?;
+
+        let updated = self
+            .db_pool
+            .get()
+            .await?
+            .execute(
+                "UPDATE merchants SET active = false, updated_at = NOW()
+                 WHERE merchant_id = $1 AND active = true",
+                &[&merchant_id],
+            )
+            .await?;
+        if updated == 0 {
+            // Deactivated concurrently; the chain agrees either way.
+            warn!(merchant_id, tx_hash, "Merchant was already deactivated");
+        }
+
+        info!(merchant_id, tx_hash, "Merchant deactivated");
+        Ok(MerchantDeactivation {
+            merchant_id: merchant_id.to_string(),
+            tx_hash,
+        })
+    }
+
+    /// Set who pays the network fee on an active merchant's payments.
+    pub async fn set_fee_strategy(
+        &self,
+        merchant_id: &str,
+        strategy: FeeStrategy,
+    ) -> Result<(), ApiError> {
+        let updated = self
+            .db_pool
+            .get()
+            .await?
+            .execute(
+                "UPDATE merchants SET fee_strategy = $2, updated_at = NOW()
+                 WHERE merchant_id = $1 AND active = true",
+                &[&merchant_id, &strategy.to_string()],
+            )
+            .await?;
+        if updated == 0 {
+            return Err(ApiError::NotFound(
+                "Merchant not found or inactive".to_string(),
+            ));
+        }
+
+        info!(merchant_id, %strategy, "Set merchant fee strategy");
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn unconfigured_registry_is_unavailable() {
+        let config = Config::default();
+        let registry =
+            RegistryContract::new(SorobanService::new(config.clone()), &config.registry_sync);
+
+        assert!(matches!(
+            registry.deactivate_merchant("shop").await,
+            Err(ApiError::ServiceUnavailable(_))
+        ));
+    }
+}
@@ -0,0 +1,148 @@
+use crate::{api_error::ApiError, config::Config};
+use deadpool_postgres::Pool;
+use ring::rand::{SecureRandom, SystemRandom};
+use serde::Serialize;
+use std::sync::Arc;
+use tracing::{error, info};
+
+/// Prefix on every webhook secret so leaked secrets are easy to recognise.
+const WEBHOOK_SECRET_PREFIX: &str = "whsec_";
+/// Random bytes per secret, hex-encoded after the prefix.
+const WEBHOOK_SECRET_BYTES: usize = 32;
+
+/// Where a merchant's settlement events are delivered, and the secret they
+/// are signed with.
+#[derive(Debug, Clone, Serialize)]
+pub struct MerchantWebhook {
+    pub merchant_id: String,
+    pub url: String,
+    pub secret: String,
+    pub updated_at: chrono::DateTime<chrono::Utc>,
+}
+
+fn generate_webhook_secret() -> Result<String, ApiError> {
+    let mut bytes = [0u8; WEBHOOK_SECRET_BYTES];
+    SystemRandom::new().fill(&mut bytes).map_err(|_| {
+        error!("Failed to generate webhook secret");
+        ApiError::InternalServerError
+    })?;
+    Ok(format!("{}{}", WEBHOOK_SECRET_PREFIX, hex::encode(bytes)))
+}
+
+fn check_webhook_url(url: &str) -> Result<(), ApiError> {
+    let parsed = reqwest::Url::parse(url)
+        .map_err(|_| ApiError::Validation("url must be a valid URL".to_string()))?;
+    if !matches!(parsed.scheme(), "http" | "https") || parsed.host().is_none() {
+        return Err(ApiError::Validation(
+            "url must be an http or https URL".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+#[derive(Clone)]
+pub struct MerchantWebhookService {
+    db_pool: Arc<Pool>,
+    _config: Config,
+}
+
+impl MerchantWebhookService {
+    pub fn new(db_pool: Arc<Pool>, config: Config) -> Self {
+        Self {
+            db_pool,
+            _config: config,
+        }
+    }
+
+    /// Point an active merchant's webhook at `url` with a fresh secret,
+    /// replacing any webhook it already had.
+    pub async fn register(
+        &self,
+        merchant_id: &str,
+        url: &str,
+    ) -> Result<MerchantWebhook, ApiError> {
+        check_webhook_url(url)?;
+        let client = self.db_pool.get().await?;
+        let secret = generate_webhook_secret()?;
+
+        let row = client
+            .query_opt(
+                r#"
+                INSERT INTO merchant_webhooks (merchant_id, url, secret)
+                SELECT merchant_id, $2, $3
+                FROM merchants
+                WHERE merchant_id = $1 AND active = true
+                ON CONFLICT (merchant_id) DO UPDATE
+                SET url = EXCLUDED.url, secret = EXCLUDED.secret, updated_at = NOW()
+                RETURNING updated_at
+                "#,
+                &[&merchant_id, &url, &secret],
+            )
+            .await?
+            .ok_or_else(|| ApiError::NotFound("Merchant not found or inactive".to_string()))?;
+
+        info!(merchant_id, url, "Registered merchant webhook");
+
+        Ok(MerchantWebhook {
+            merchant_id: merchant_id.to_string(),
+            url: url.to_string(),
+            secret,
+            updated_at: row.get("updated_at"),
+        })
+    }
+
+    /// The merchant's webhook, if it has registered one.
+    pub async fn for_merchant(
+        &self,
+        merchant_id: &str,
+    ) -> Result<Option<MerchantWebhook>, ApiError> {
+        let client = self.db_pool.get().await?;
+
+        let row = client
+            .query_opt(
+                "SELECT url, secret, updated_at FROM merchant_webhooks WHERE merchant_id = $1",
+                &[&merchant_id],
+            )
+            .await?;
+
+        Ok(row.map(|row| MerchantWebhook {
+            merchant_id: merchant_id.to_string(),
+            url: row.get("url"),
+            secret: row.get("secret"),
+            updated_at: row.get("updated_at"),
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn webhook_url_must_be_http() {
+        assert!(check_webhook_url("https://merchant.example/hooks/zaps").is_ok());
+        assert!(check_webhook_url("http://localhost:8080/hook").is_ok());
+
+        for url in [
+            "",
+            "merchant.example/hook",
+            "ftp://merchant.example",
+            "file:///etc/passwd",
+        ] {
+            assert!(check_webhook_url(url).is_err(), "{}", url);
+        }
+    }
+
+    #[test]
+    fn secrets_are_random_and_prefixed() {
+        let a = generate_webhook_secret().unwrap();
+        let b = generate_webhook_secret().unwrap();
+
+        assert!(a.starts_with(WEBHOOK_SECRET_PREFIX));
+        assert_eq!(
+            a.len(),
+            WEBHOOK_SECRET_PREFIX.len() + 2 * WEBHOOK_SECRET_BYTES
+        );
+        assert_ne!(a, b);
+    }
+}
@@ -33,6 +33,32 @@ lazy_static! {
     )
     .expect("Can't create http_errors_total metric");
 
+    /// Anchor calls by operation and outcome (HTTP status, `unreachable` or
+    /// `circuit_open`)
+    pub static ref ANCHOR_REQUESTS_TOTAL: CounterVec = register_counter_vec!(
+        "anchor_requests_total",
+        "Total number of requests to the anchor",
+        &["op", "status"]
+    )
+    .expect("Can't create anchor_requests_total metric");
+
+    /// Anchor call duration histogram with an operation label
+    pub static ref ANCHOR_REQUEST_DURATION_SECONDS: HistogramVec = register_histogram_vec!(
+        "anchor_request_duration_seconds",
+        "Anchor request duration in seconds",
+        &["op"],
+        vec![0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0, 30.0]
+    )
+    .expect("Can't create anchor_request_duration_seconds metric");
+
+    /// KYC statuses resolved at the anchor
+    pub static ref KYC_STATUS_TOTAL: CounterVec = register_counter_vec!(
+        "kyc_status_total",
+        "Total number of KYC checks by resolved status",
+        &["status"]
+    )
+    .expect("Can't create kyc_status_total metric");
+
     /// Active connections gauge
     pub static ref ACTIVE_CONNECTIONS: Gauge = register_gauge!(
         "active_connections",
@@ -86,10 +112,55 @@ pub struct DetailedMetrics {
     pub active_connections: f64,
     /// Database pool connections
     pub db_pool_connections: f64,
+    /// Latency percentiles per normalized path
+    pub latency: Vec<EndpointLatency>,
     /// Timestamp of metrics collection
     pub timestamp: chrono::DateTime<chrono::Utc>,
 }
 
+/// Request latency percentiles (in seconds) for one normalized path, estimated
+/// from the `http_request_duration_seconds` histogram since startup.
+#[derive(Debug, Clone, Serialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct EndpointLatency {
+    pub path: String,
+    pub count: u64,
+    pub p50: f64,
+    pub p95: f64,
+    pub p99: f64,
+}
+
+/// Estimate quantile `q` from cumulative histogram buckets, interpolating
+/// linearly within the bucket it falls in (as PromQL's `histogram_quantile`
+/// does). Observations beyond the last finite bucket report that bound.
+fn bucket_quantile(q: f64, buckets: &[(f64, u64)], total: u64) -> f64 {
+    if total == 0 {
+        return 0.0;
+    }
+
+    let rank = q * total as f64;
+    let mut lower_bound = 0.0;
+    let mut lower_count = 0;
+
+    for &(upper_bound, cumulative) in buckets {
+        if cumulative as f64 >= rank {
+            if upper_bound.is_infinite() {
+                return lower_bound;
+            }
+            let in_bucket = (cumulative - lower_count) as f64;
+            if in_bucket == 0.0 {
+                return upper_bound;
+            }
+            let fraction = (rank - lower_count as f64) / in_bucket;
+            return lower_bound + (upper_bound - lower_bound) * fraction;
+        }
+        lower_bound = upper_bound;
+        lower_count = cumulative;
+    }
+
+    lower_bound
+}
+
 /// Alert severity levels
 #[derive(Debug, Clone, Copy, Serialize, PartialEq, Eq)]
 #[serde(rename_all = "lowercase")]
@@ -99,7 +170,7 @@ pub enum AlertSeverity {
     Critical,
 }
 
-/// Alert hook payload (placeholder for future integration)
+/// Alert as POSTed to an alert webhook
 #[derive(Debug, Clone, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct AlertPayload {
@@ -120,10 +191,47 @@ pub struct AlertThreshold {
     pub critical_threshold: f64,
 }
 
+/// Build a critical or warning alert when `value` crosses `threshold`.
+fn graded_alert(
+    threshold: &AlertThreshold,
+    value: f64,
+    (critical_title, warning_title): (&str, &str),
+    message: impl Fn(&str, f64) -> String,
+) -> Option<AlertPayload> {
+    let (severity, title, level, limit) = if value >= threshold.critical_threshold {
+        (
+            AlertSeverity::Critical,
+            critical_title,
+            "critical",
+            threshold.critical_threshold,
+        )
+    } else if value >= threshold.warning_threshold {
+        (
+            AlertSeverity::Warning,
+            warning_title,
+            "warning",
+            threshold.warning_threshold,
+        )
+    } else {
+        return None;
+    };
+
+    Some(AlertPayload {
+        severity,
+        title: title.to_string(),
+        message: message(level, limit),
+        metric_name: threshold.metric_name.clone(),
+        current_value: value,
+        threshold: limit,
+        timestamp: chrono::Utc::now(),
+    })
+}
+
 /// Metrics service for monitoring and alerting
 #[derive(Clone)]
 pub struct MetricsService {
     alert_thresholds: Vec<AlertThreshold>,
+    http: reqwest::Client,
 }
 
 impl Default for MetricsService {
@@ -148,7 +256,18 @@ impl MetricsService {
             },
         ];
 
-        Self { alert_thresholds }
+        Self {
+            alert_thresholds,
+            http: reqwest::Client::new(),
+        }
+    }
+
+    /// Like `new`, but sending alert webhooks through `http`.
+    pub fn with_http_client(http: reqwest::Client) -> Self {
+        Self {
+            http,
+            ..Self::new()
+        }
     }
 
     /// Initialize metrics (call at application startup)
@@ -157,6 +276,9 @@ impl MetricsService {
         let _ = &*HTTP_REQUESTS_TOTAL;
         let _ = &*HTTP_REQUEST_DURATION_SECONDS;
         let _ = &*HTTP_ERRORS_TOTAL;
+        let _ = &*ANCHOR_REQUESTS_TOTAL;
+        let _ = &*ANCHOR_REQUEST_DURATION_SECONDS;
+        let _ = &*KYC_STATUS_TOTAL;
         let _ = &*ACTIVE_CONNECTIONS;
         let _ = &*DB_POOL_CONNECTIONS;
         let _ = &*APP_UPTIME_SECONDS;
@@ -236,10 +358,59 @@ impl MetricsService {
             basic: Self::get_metrics_payload(),
             active_connections: ACTIVE_CONNECTIONS.get(),
             db_pool_connections: DB_POOL_CONNECTIONS.get(),
+            latency: Self::get_endpoint_latencies(),
             timestamp: chrono::Utc::now(),
         }
     }
 
+    /// Latency percentiles per normalized path, merging all methods.
+    pub fn get_endpoint_latencies() -> Vec<EndpointLatency> {
+        use std::collections::BTreeMap;
+
+        // path -> (bucket upper bounds with cumulative counts, sample count)
+        let mut by_path: BTreeMap<String, (Vec<(f64, u64)>, u64)> = BTreeMap::new();
+
+        for family in HTTP_REQUEST_DURATION_SECONDS.collect() {
+            for metric in family.get_metric() {
+                let Some(path) = metric
+                    .get_label()
+                    .iter()
+                    .find(|label| label.get_name() == "path")
+                    .map(|label| label.get_value().to_string())
+                else {
+                    continue;
+                };
+
+                let histogram = metric.get_histogram();
+                let (buckets, count) = by_path.entry(path).or_default();
+                if buckets.is_empty() {
+                    buckets.extend(
+                        histogram
+                            .get_bucket()
+                            .iter()
+                            .map(|b| (b.get_upper_bound(), 0)),
+                    );
+                }
+                for (merged, bucket) in buckets.iter_mut().zip(histogram.get_bucket()) {
+                    merged.1 += bucket.get_cumulative_count();
+                }
+                *count += histogram.get_sample_count();
+            }
+        }
+
+        by_path
+            .into_iter()
+            .filter(|(_, (_, count))| *count > 0)
+            .map(|(path, (buckets, count))| EndpointLatency {
+                p50: bucket_quantile(0.50, &buckets, count),
+                p95: bucket_quantile(0.95, &buckets, count),
+                p99: bucket_quantile(0.99, &buckets, count),
+                path,
+                count,
+            })
+            .collect()
+    }
+
     /// Export metrics in Prometheus text format
     pub fn export_prometheus() -> Result<String, prometheus::Error> {
         // Update uptime before export
@@ -280,6 +451,22 @@ impl MetricsService {
         }
     }
 
+    /// Record one call to the anchor. `duration_secs` is `None` for calls
+    /// that were never sent.
+    pub fn record_anchor_request(op: &str, status: &str, duration_secs: Option<f64>) {
+        ANCHOR_REQUESTS_TOTAL.with_label_values(&[op, status]).inc();
+        if let Some(duration_secs) = duration_secs {
+            ANCHOR_REQUEST_DURATION_SECONDS
+                .with_label_values(&[op])
+                .observe(duration_secs);
+        }
+    }
+
+    /// Record the status a KYC check resolved to
+    pub fn record_kyc_status(status: &str) {
+        KYC_STATUS_TOTAL.with_label_values(&[status]).inc();
+    }
+
     /// Normalize path for metric labels (replace IDs with placeholders)
     fn normalize_path(path: &str) -> String {
         // Replace UUIDs with :id placeholder
@@ -312,40 +499,7 @@ impl MetricsService {
     /// Check alert thresholds and generate alerts if needed
     /// Returns a list of triggered alerts (placeholder for webhook integration)
     pub fn check_alerts(&self) -> Vec<AlertPayload> {
-        let mut alerts = Vec::new();
-        let error_rate = Self::get_error_rate();
-
-        for threshold in &self.alert_thresholds {
-            if threshold.metric_name == "error_rate" {
-                if error_rate >= threshold.critical_threshold {
-                    alerts.push(AlertPayload {
-                        severity: AlertSeverity::Critical,
-                        title: "High Error Rate".to_string(),
-                        message: format!(
-                            "Error rate is {:.2}%, exceeding critical threshold of {:.2}%",
-                            error_rate, threshold.critical_threshold
-                        ),
-                        metric_name: threshold.metric_name.clone(),
-                        current_value: error_rate,
-                        threshold: threshold.critical_threshold,
-                        timestamp: chrono::Utc::now(),
-                    });
-                } else if error_rate >= threshold.warning_threshold {
-                    alerts.push(AlertPayload {
-                        severity: AlertSeverity::Warning,
-                        title: "Elevated Error Rate".to_string(),
-                        message: format!(
-                            "Error rate is {:.2}%, exceeding warning threshold of {:.2}%",
-                            error_rate, threshold.warning_threshold
-                        ),
-                        metric_name: threshold.metric_name.clone(),
-                        current_value: error_rate,
-                        threshold: threshold.warning_threshold,
-                        timestamp: chrono::Utc::now(),
-                    });
-                }
-            }
-        }
+        let alerts = self.evaluate_alerts(Self::get_error_rate(), &Self::get_endpoint_latencies());
 
         // Log alerts
         for alert in &alerts {
@@ -383,29 +537,63 @@ impl MetricsService {
         alerts
     }
 
-    /// Placeholder for sending alerts to external systems (webhooks, Slack, PagerDuty, etc.)
-    /// TODO: Implement actual webhook integration
-    #[allow(dead_code)]
+    /// Compare current values against the configured thresholds.
+    ///
+    /// `request_duration_p99` is checked against the slowest endpoint's p99.
+    fn evaluate_alerts(&self, error_rate: f64, latency: &[EndpointLatency]) -> Vec<AlertPayload> {
+        let slowest = latency.iter().max_by(|a, b| a.p99.total_cmp(&b.p99));
+
+        self.alert_thresholds
+            .iter()
+            .filter_map(|threshold| match threshold.metric_name.as_str() {
+                "error_rate" => graded_alert(
+                    threshold,
+                    error_rate,
+                    ("High Error Rate", "Elevated Error Rate"),
+                    |level, limit| {
+                        format!(
+                            "Error rate is {:.2}%, exceeding {} threshold of {:.2}%",
+                            error_rate, level, limit
+                        )
+                    },
+                ),
+                "request_duration_p99" => slowest.and_then(|endpoint| {
+                    graded_alert(
+                        threshold,
+                        endpoint.p99,
+                        ("High Request Latency", "Elevated Request Latency"),
+                        |level, limit| {
+                            format!(
+                                "p99 latency for {} is {:.3}s, exceeding {} threshold of {:.3}s",
+                                endpoint.path, endpoint.p99, level, limit
+                            )
+                        },
+                    )
+                }),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// POST `alert` as JSON to `webhook_url`. A non-2xx answer is an error.
     pub async fn send_alert_webhook(
         &self,
         alert: &AlertPayload,
         webhook_url: &str,
     ) -> Result<(), reqwest::Error> {
+        self.http
+            .post(webhook_url)
+            .json(alert)
+            .send()
+            .await?
+            .error_for_status()?;
+
         tracing::info!(
             webhook_url = %webhook_url,
             alert_title = %alert.title,
             severity = ?alert.severity,
-            "Sending alert to webhook (placeholder)"
+            "Sent alert to webhook"
         );
-
-        // Placeholder implementation - uncomment when ready to use
-        // let client = reqwest::Client::new();
-        // client
-        //     .post(webhook_url)
-        //     .json(alert)
-        //     .send()
-        //     .await?;
-
         Ok(())
     }
 }
@@ -427,6 +615,76 @@ mod tests {
         assert_eq!(MetricsService::normalize_path("/health"), "/health");
     }
 
+    #[test]
+    fn test_bucket_quantile_interpolates() {
+        // 10 samples <= 0.1s, 10 more <= 1.0s
+        let buckets = [(0.1, 10), (1.0, 20), (f64::INFINITY, 20)];
+
+        assert!((bucket_quantile(0.5, &buckets, 20) - 0.1).abs() < 1e-9);
+        assert!((bucket_quantile(0.75, &buckets, 20) - 0.55).abs() < 1e-9);
+        assert_eq!(bucket_quantile(0.99, &[], 0), 0.0);
+        // Overflow past the last finite bucket reports that bound
+        assert_eq!(
+            bucket_quantile(0.99, &[(1.0, 0), (f64::INFINITY, 5)], 5),
+            1.0
+        );
+    }
+
+    #[test]
+    fn test_observed_durations_produce_percentiles() {
+        MetricsService::init();
+        for _ in 0..50 {
+            MetricsService::record_request("GET", "/latency-test", 200, 0.02);
+        }
+        MetricsService::record_request("POST", "/latency-test", 201, 3.0);
+
+        let latency = MetricsService::get_endpoint_latencies();
+        let endpoint = latency
+            .iter()
+            .find(|l| l.path == "/latency-test")
+            .expect("latency for observed path");
+
+        assert_eq!(endpoint.count, 51);
+        assert!(endpoint.p50 > 0.0 && endpoint.p50 <= 0.025);
+        assert!(endpoint.p95 > 0.0);
+        assert!(endpoint.p99 >= endpoint.p95);
+    }
+
+    #[test]
+    fn test_high_p99_triggers_alert() {
+        let service = MetricsService::new();
+        let latency = |p99| EndpointLatency {
+            path: "/payments".to_string(),
+            count: 100,
+            p50: 0.05,
+            p95: 0.5,
+            p99,
+        };
+
+        let alerts = service.evaluate_alerts(0.0, &[latency(6.0)]);
+        assert_eq!(alerts.len(), 1);
+        assert_eq!(alerts[0].metric_name, "request_duration_p99");
+        assert_eq!(alerts[0].severity, AlertSeverity::Critical);
+        assert!(alerts[0].message.contains("/payments"));
+
+        let alerts = service.evaluate_alerts(0.0, &[latency(2.0)]);
+        assert_eq!(alerts[0].severity, AlertSeverity::Warning);
+
+        assert!(service.evaluate_alerts(0.0, &[latency(0.2)]).is_empty());
+    }
+
+    #[test]
+    fn test_error_rate_alert_unchanged() {
+        let alerts = MetricsService::new().evaluate_alerts(12.5, &[]);
+
+        assert_eq!(alerts.len(), 1);
+        assert_eq!(alerts[0].title, "High Error Rate");
+        assert_eq!(
+            alerts[0].message,
+            "Error rate is 12.50%, exceeding critical threshold of 10.00%"
+        );
+    }
+
     #[test]
     fn test_metrics_payload() {
         MetricsService::init();
@@ -434,4 +692,40 @@ mod tests {
 
         assert!(payload.error_rate >= 0.0);
     }
+
+    #[tokio::test]
+    async fn test_alert_webhook_posts_the_alert() {
+        let server = httpmock::MockServer::start_async().await;
+        let hook = server
+            .mock_async(|when, then| {
+                when.method(httpmock::Method::POST)
+                    .path("/alerts")
+                    .json_body_partial(
+                        r#"{"metricName": "balance_drift", "severity": "critical"}"#,
+                    );
+                then.status(204);
+            })
+            .await;
+        let alert = AlertPayload {
+            severity: AlertSeverity::Critical,
+            title: "Balance Drift".to_string(),
+            message: "drifted".to_string(),
+            metric_name: "balance_drift".to_string(),
+            current_value: -1.0,
+            threshold: 0.0,
+            timestamp: chrono::Utc::now(),
+        };
+        let service = MetricsService::new();
+
+        service
+            .send_alert_webhook(&alert, &server.url("/alerts"))
+            .await
+            .unwrap();
+        hook.assert_async().await;
+
+        assert!(service
+            .send_alert_webhook(&alert, &server.url("/missing"))
+            .await
+            .is_err());
+    }
 }
@@ -1,34 +1,63 @@
 pub mod anchor_service;
+pub mod api_key_service;
 pub mod audit_service;
+pub mod balance_service;
 pub mod bridge_service;
+pub mod circuit_breaker;
 pub mod compliance_service;
 pub mod identity_service;
 pub mod indexer_service;
+pub mod memo_policy_service;
+pub mod merchant_service;
+pub mod merchant_webhook_service;
 pub mod metrics_service;
 pub mod notification_service;
+pub mod payment_events;
 pub mod payment_service;
 pub mod profile_service;
+pub mod qr_cache;
 pub mod rate_limit_service;
+pub mod registry_sync_service;
+pub mod reputation_service;
+pub mod session_service;
 pub mod soroban_service;
+pub mod soroban_xdr;
 pub mod storage_service;
+pub mod transaction_service;
+pub mod transfer_service;
+pub mod tx_submission_service;
 
 pub use anchor_service::AnchorService;
+pub use api_key_service::ApiKeyService;
 pub use audit_service::AuditService;
+pub use balance_service::BalanceService;
 pub use bridge_service::BridgeService;
 pub use compliance_service::ComplianceService;
 pub use identity_service::IdentityService;
 pub use indexer_service::IndexerService;
+pub use memo_policy_service::MemoPolicyService;
+pub use merchant_service::MerchantService;
+pub use merchant_webhook_service::MerchantWebhookService;
 pub use metrics_service::{
     AlertPayload, AlertSeverity, DetailedMetrics, MetricsPayload, MetricsService,
 };
 pub use notification_service::NotificationService;
 pub use payment_service::PaymentService;
 pub use profile_service::ProfileService;
+pub use qr_cache::QrPayloadCache;
 pub use rate_limit_service::RateLimitService;
+pub use registry_sync_service::RegistrySyncService;
+pub use reputation_service::ReputationService;
+pub use session_service::SessionService;
 pub use soroban_service::SorobanService;
 pub use storage_service::StorageService;
+pub use transaction_service::TransactionService;
+pub use transfer_service::TransferService;
+pub use tx_submission_service::TxSubmissionService;
 
 use crate::config::Config;
+use crate::http_client;
+use crate::queue::JobQueue;
 use deadpool_postgres::Pool;
 use std::sync::Arc;
 
@@ -40,32 +69,72 @@ pub struct ServiceContainer {
     pub anchor: AnchorService,
     pub compliance: ComplianceService,
     pub audit: AuditService,
+    pub balances: BalanceService,
     pub indexer: IndexerService,
     pub notification: NotificationService,
     pub rate_limit: RateLimitService,
     pub profile: ProfileService,
     pub soroban: SorobanService,
     pub storage: StorageService,
+    pub transfer: TransferService,
+    pub transactions: TransactionService,
+    pub tx_submissions: TxSubmissionService,
+    pub api_keys: ApiKeyService,
+    pub merchant_webhooks: MerchantWebhookService,
+    pub memo_policies: MemoPolicyService,
+    pub merchants: MerchantService,
+    pub sessions: SessionService,
+    pub qr_cache: QrPayloadCache,
+    pub registry_sync: RegistrySyncService,
+    pub reputation: ReputationService,
     pub config: Config,
     pub db_pool: Arc<Pool>,
+    pub job_queue: Arc<JobQueue>,
+    /// Outbound HTTP client shared by the services above and the job
+    /// processors, so they reuse one connection pool.
+    pub http: reqwest::Client,
 }
 
 impl ServiceContainer {
     pub async fn new(db_pool: Pool, config: Config) -> Result<Self, Box<dyn std::error::Error>> {
         let db_pool = Arc::new(db_pool);
+        let http = http_client::build(&config.http_client);
 
         let identity = IdentityService::new(db_pool.clone(), config.clone());
-        let payment = PaymentService::new(db_pool.clone(), config.clone());
+        let payment =
+            PaymentService::with_http_client(db_pool.clone(), config.clone(), http.clone());
         let bridge = BridgeService::new(db_pool.clone(), config.clone());
-        let anchor = AnchorService::new(db_pool.clone(), config.clone());
+        let anchor = AnchorService::with_http_client(db_pool.clone(), config.clone(), http.clone());
         let compliance = ComplianceService::new(db_pool.clone(), config.clone());
         let audit = AuditService::new(db_pool.clone(), config.clone());
+        let balances =
+            BalanceService::with_http_client(db_pool.clone(), config.clone(), http.clone());
         let indexer = IndexerService::new(db_pool.clone(), config.clone());
         let notification = NotificationService::new(db_pool.clone(), config.clone());
         let rate_limit = RateLimitService::new(config.clone());
         let profile = ProfileService::new(db_pool.clone(), config.clone());
-        let soroban = SorobanService::new(config.clone());
+        let soroban = SorobanService::with_http_client(config.clone(), http.clone());
         let storage = StorageService::new(config.clone());
+        let transfer =
+            TransferService::with_http_client(db_pool.clone(), config.clone(), http.clone());
+        let transactions = TransactionService::new(db_pool.clone(), config.clone());
+        let tx_submissions = TxSubmissionService::new(db_pool.clone(), config.clone());
+        let api_keys = ApiKeyService::new(db_pool.clone(), config.clone());
+        let merchant_webhooks = MerchantWebhookService::new(db_pool.clone(), config.clone());
+        let memo_policies = MemoPolicyService::new(db_pool.clone(), config.clone());
+        let merchants = MerchantService::with_registry(
+            db_pool.clone(),
+            config.clone(),
+            Arc::new(merchant_service::RegistryContract::new(
+                soroban.clone(),
+                &config.registry_sync,
+            )),
+        );
+        let sessions = SessionService::new(db_pool.clone(), config.clone());
+        let registry_sync =
+            RegistrySyncService::with_http_client(db_pool.clone(), config.clone(), http.clone());
+        let reputation = ReputationService::with_http_client(config.clone(), http.clone());
+        let job_queue = Arc::new(JobQueue::from_config(&config).await?);
 
         Ok(Self {
             identity,
@@ -74,14 +143,28 @@ impl ServiceContainer {
             anchor,
             compliance,
             audit,
+            balances,
             indexer,
             notification,
             rate_limit,
             profile,
             soroban,
             storage,
+            transfer,
+            transactions,
+            tx_submissions,
+            api_keys,
+            merchant_webhooks,
+            memo_policies,
+            merchants,
+            sessions,
+            qr_cache: QrPayloadCache::new(),
+            registry_sync,
+            reputation,
             config,
             db_pool,
+            job_queue,
+            http,
         })
     }
 }
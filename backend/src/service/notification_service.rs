@@ -12,10 +12,18 @@ use uuid::Uuid;
 #[derive(Clone)]
 pub struct NotificationService {
     db_pool: Arc<Pool>,
-    #[allow(dead_code)]
     config: Config,
 }
 
+/// A notification held back for the user's next digest.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HeldNotification {
+    pub id: String,
+    pub notification_type: String,
+    pub message: String,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct CreateNotificationRequest {
     pub user_id: String,
@@ -130,6 +138,152 @@ impl NotificationService {
         Ok(())
     }
 
+    /// Whether the user accepts batched notifications. Opting out is done
+    /// with `preferences.notifications.digest = false` in profile metadata.
+    pub async fn wants_digest(&self, user_id: &str) -> Result<bool, ApiError> {
+        let client = self.db_pool.get().await?;
+
+        let row = client
+            .query_opt(
+                r#"
+                SELECT p.metadata #>> '{preferences,notifications,digest}'
+                FROM user_profiles p
+                JOIN users u ON u.id = p.user_id
+                WHERE u.user_id = $1 AND p.deleted_at IS NULL
+                "#,
+                &[&user_id],
+            )
+            .await?;
+
+        Ok(row.and_then(|row| row.get::<_, Option<String>>(0)) != Some("false".to_string()))
+    }
+
+    /// Hold a notification for the user's next digest. Returns whether a
+    /// digest job must be scheduled: the notification opened a new window,
+    /// or the open one is a full window overdue, so its job was evidently
+    /// lost. `job_id` makes holding a retried job a no-op.
+    pub async fn hold_for_digest(
+        &self,
+        user_id: &str,
+        job_id: Uuid,
+        notification_type: &str,
+        message: &str,
+    ) -> Result<bool, ApiError> {
+        let mut client = self.db_pool.get().await?;
+        let window = self.config.notification_digest.window_seconds as f64;
+
+        let tx = client.transaction().await?;
+        // DO UPDATE (unlike DO NOTHING) locks an existing window, so a digest
+        // closing it concurrently sees this notification or waits for it.
+        let schedule = tx
+            .query_opt(
+                r#"
+                INSERT INTO notification_digest_windows (user_id, due_at)
+                VALUES ($1, NOW() + make_interval(secs => $2))
+                ON CONFLICT (user_id) DO UPDATE
+                SET due_at = EXCLUDED.due_at
+                WHERE notification_digest_windows.due_at < NOW() - make_interval(secs => $2)
+                RETURNING due_at
+                "#,
+                &[&user_id, &window],
+            )
+            .await?
+            .is_some();
+        tx.execute(
+            r#"
+            INSERT INTO pending_notifications (job_id, user_id, type, message)
+            VALUES ($1, $2, $3, $4)
+            ON CONFLICT (job_id) DO NOTHING
+            "#,
+            &[&job_id, &user_id, &notification_type, &message],
+        )
+        .await?;
+        tx.commit().await?;
+
+        Ok(schedule)
+    }
+
+    /// Notifications currently held for the user, oldest first.
+    pub async fn held_notifications(
+        &self,
+        user_id: &str,
+    ) -> Result<Vec<HeldNotification>, ApiError> {
+        let client = self.db_pool.get().await?;
+
+        let rows = client
+            .query(
+                r#"
+                SELECT id::text AS id, type, message, created_at
+                FROM pending_notifications
+                WHERE user_id = $1
+                ORDER BY created_at, id
+                "#,
+                &[&user_id],
+            )
+            .await?;
+
+        Ok(rows
+            .iter()
+            .map(|row| HeldNotification {
+                id: row.get("id"),
+                notification_type: row.get("type"),
+                message: row.get("message"),
+                created_at: row.get("created_at"),
+            })
+            .collect())
+    }
+
+    /// Drop delivered notifications and close the user's window. Returns
+    /// whether more arrived meanwhile, in which case the window stays open
+    /// for another full period and needs a new digest job.
+    pub async fn clear_digest(
+        &self,
+        user_id: &str,
+        delivered: &[String],
+    ) -> Result<bool, ApiError> {
+        let mut client = self.db_pool.get().await?;
+        let window = self.config.notification_digest.window_seconds as f64;
+
+        let tx = client.transaction().await?;
+        tx.execute(
+            "SELECT 1 FROM notification_digest_windows WHERE user_id = $1 FOR UPDATE",
+            &[&user_id],
+        )
+        .await?;
+        tx.execute(
+            "DELETE FROM pending_notifications WHERE user_id = $1 AND id::text = ANY($2)",
+            &[&user_id, &delivered],
+        )
+        .await?;
+        let remaining: i64 = tx
+            .query_one(
+                "SELECT COUNT(*) FROM pending_notifications WHERE user_id = $1",
+                &[&user_id],
+            )
+            .await?
+            .get(0);
+        if remaining == 0 {
+            tx.execute(
+                "DELETE FROM notification_digest_windows WHERE user_id = $1",
+                &[&user_id],
+            )
+            .await?;
+        } else {
+            tx.execute(
+                r#"
+                INSERT INTO notification_digest_windows (user_id, due_at)
+                VALUES ($1, NOW() + make_interval(secs => $2))
+                ON CONFLICT (user_id) DO UPDATE SET due_at = EXCLUDED.due_at
+                "#,
+                &[&user_id, &window],
+            )
+            .await?;
+        }
+        tx.commit().await?;
+
+        Ok(remaining > 0)
+    }
+
     async fn send_email_notification(&self, notification: &Notification) -> Result<(), ApiError> {
         // MOCK EMAIL PROVIDER
         println!(
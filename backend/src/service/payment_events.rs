@@ -0,0 +1,172 @@
+use crate::models::PaymentStatus;
+use futures::Stream;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::sync::broadcast::{self, error::RecvError};
+use uuid::Uuid;
+
+/// Buffered status changes per payment before a slow subscriber lags.
+const CHANNEL_CAPACITY: usize = 16;
+
+/// A payment moving to a new status.
+#[derive(Debug, Clone)]
+pub struct PaymentStatusChange {
+    pub payment_id: Uuid,
+    pub status: PaymentStatus,
+    pub updated_at: chrono::DateTime<chrono::Utc>,
+}
+
+/// In-process fan-out of payment status changes to whoever is watching a
+/// payment, e.g. a checkout page on `GET /payments/:id/events`. Changes
+/// made by another replica aren't seen. Clones share subscribers.
+#[derive(Clone, Default)]
+pub struct PaymentEvents {
+    channels: Arc<Mutex<HashMap<Uuid, broadcast::Sender<PaymentStatusChange>>>>,
+}
+
+impl PaymentEvents {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Receive every change to `payment_id` published from now on.
+    pub fn subscribe(&self, payment_id: Uuid) -> broadcast::Receiver<PaymentStatusChange> {
+        let mut channels = self.lock();
+        channels.retain(|_, sender| sender.receiver_count() > 0);
+        channels
+            .entry(payment_id)
+            .or_insert_with(|| broadcast::channel(CHANNEL_CAPACITY).0)
+            .subscribe()
+    }
+
+    /// Deliver `change` to the payment's current subscribers, if any.
+    pub fn publish(&self, change: PaymentStatusChange) {
+        let mut channels = self.lock();
+        let Some(sender) = channels.get(&change.payment_id) else {
+            return;
+        };
+
+        let payment_id = change.payment_id;
+        let terminal = change.status.is_terminal();
+        // Nobody is listening any more, or nothing further will be sent.
+        if sender.send(change).is_err() || terminal {
+            channels.remove(&payment_id);
+        }
+    }
+
+    fn lock(
+        &self,
+    ) -> std::sync::MutexGuard<'_, HashMap<Uuid, broadcast::Sender<PaymentStatusChange>>> {
+        self.channels.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+/// The payment's status as of `current`, then each change received on
+/// `changes`. Ends after a terminal status, once `timeout` has passed, or if
+/// the publisher goes away.
+pub fn status_changes(
+    current: PaymentStatusChange,
+    changes: broadcast::Receiver<PaymentStatusChange>,
+    timeout: Duration,
+) -> impl Stream<Item = PaymentStatusChange> {
+    let deadline = tokio::time::Instant::now() + timeout;
+    futures::stream::unfold(
+        (Some(current), Some(changes)),
+        move |(pending, changes)| async move {
+            if let Some(change) = pending {
+                let changes = changes.filter(|_| !change.status.is_terminal());
+                return Some((change, (None, changes)));
+            }
+
+            let mut changes = changes?;
+            loop {
+                match tokio::time::timeout_at(deadline, changes.recv()).await {
+                    Ok(Ok(change)) => {
+                        let changes = (!change.status.is_terminal()).then_some(changes);
+                        return Some((change, (None, changes)));
+                    }
+                    // Missed some intermediate changes; the next one is
+                    // still current.
+                    Ok(Err(RecvError::Lagged(_))) => continue,
+                    Ok(Err(RecvError::Closed)) | Err(_) => return None,
+                }
+            }
+        },
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures::StreamExt;
+
+    fn change(payment_id: Uuid, status: PaymentStatus) -> PaymentStatusChange {
+        PaymentStatusChange {
+            payment_id,
+            status,
+            updated_at: chrono::Utc::now(),
+        }
+    }
+
+    fn statuses(changes: &[PaymentStatusChange]) -> Vec<String> {
+        changes.iter().map(|c| c.status.to_string()).collect()
+    }
+
+    #[tokio::test]
+    async fn streams_changes_until_a_terminal_status() {
+        let events = PaymentEvents::new();
+        let id = Uuid::new_v4();
+        let stream = status_changes(
+            change(id, PaymentStatus::Pending),
+            events.subscribe(id),
+            Duration::from_secs(5),
+        );
+
+        events.publish(change(id, PaymentStatus::Processing));
+        events.publish(change(Uuid::new_v4(), PaymentStatus::Failed));
+        events.publish(change(id, PaymentStatus::Completed));
+
+        let seen: Vec<_> = stream.collect().await;
+        assert_eq!(statuses(&seen), ["pending", "processing", "completed"]);
+    }
+
+    #[tokio::test]
+    async fn already_settled_payment_ends_immediately() {
+        let events = PaymentEvents::new();
+        let id = Uuid::new_v4();
+        let stream = status_changes(
+            change(id, PaymentStatus::Failed),
+            events.subscribe(id),
+            Duration::from_secs(5),
+        );
+
+        let seen: Vec<_> = stream.collect().await;
+        assert_eq!(statuses(&seen), ["failed"]);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn stream_ends_at_the_timeout() {
+        let events = PaymentEvents::new();
+        let id = Uuid::new_v4();
+        let stream = status_changes(
+            change(id, PaymentStatus::Pending),
+            events.subscribe(id),
+            Duration::from_secs(30),
+        );
+
+        let seen: Vec<_> = stream.collect().await;
+        assert_eq!(statuses(&seen), ["pending"]);
+    }
+
+    #[test]
+    fn terminal_publish_drops_the_channel() {
+        let events = PaymentEvents::new();
+        let id = Uuid::new_v4();
+        let _rx = events.subscribe(id);
+
+        events.publish(change(id, PaymentStatus::Completed));
+
+        assert!(events.lock().is_empty());
+    }
+}
@@ -1,12 +1,21 @@
+use super::memo_policy_service::memo_taken;
+use super::merchant_webhook_service::MerchantWebhookService;
+use super::payment_events::{PaymentEvents, PaymentStatusChange};
+use super::soroban_service::{OnChainStatus, SorobanService};
 use crate::{
     api_error::ApiError,
     config::Config,
-    models::{Merchant, Payment, PaymentStatus},
+    job_processors::{merchant_webhook_job, payment_confirmation_job},
+    models::{FeeStrategy, Merchant, Payment, PaymentStatus},
+    queue::JobEnqueuer,
 };
-use deadpool_postgres::Pool;
+use deadpool_postgres::{tokio_postgres::error::SqlState, Pool};
 use serde::{Deserialize, Serialize};
+use serde_json::json;
 use std::str::FromStr;
 use std::sync::Arc;
+use tokio::sync::broadcast;
+use tracing::{error, info, warn};
 use uuid::Uuid;
 
 #[derive(Clone)]
@@ -14,6 +23,9 @@ use uuid::Uuid;
 pub struct PaymentService {
     db_pool: Arc<Pool>,
     config: Config,
+    events: PaymentEvents,
+    webhooks: MerchantWebhookService,
+    soroban: SorobanService,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -23,6 +35,28 @@ pub struct CreatePaymentRequest {
     pub send_amount: i64,
     pub min_receive: Option<i64>,
     pub memo: Option<String>,
+    /// `memo` is an order reference no other payment to the merchant may
+    /// carry.
+    pub memo_unique: bool,
+    pub fee_strategy: FeeStrategy,
+}
+
+/// The fee strategy a payment to `merchant` is built with. The merchant's
+/// own setting applies unless the payer asks to pay the fee themselves; a
+/// payer can't opt the server or the merchant into paying it.
+pub fn fee_strategy_for(
+    merchant: &Merchant,
+    requested: Option<FeeStrategy>,
+) -> Result<FeeStrategy, ApiError> {
+    match requested {
+        None => Ok(merchant.fee_strategy),
+        Some(strategy) if strategy == merchant.fee_strategy => Ok(strategy),
+        Some(FeeStrategy::SenderPays) => Ok(FeeStrategy::SenderPays),
+        Some(strategy) => Err(ApiError::Validation(format!(
+            "Merchant {} doesn't accept fee strategy {}",
+            merchant.merchant_id, strategy
+        ))),
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -45,7 +79,29 @@ pub struct NfcPaymentPayload {
 
 impl PaymentService {
     pub fn new(db_pool: Arc<Pool>, config: Config) -> Self {
-        Self { db_pool, config }
+        Self {
+            webhooks: MerchantWebhookService::new(db_pool.clone(), config.clone()),
+            soroban: SorobanService::new(config.clone()),
+            db_pool,
+            config,
+            events: PaymentEvents::new(),
+        }
+    }
+
+    /// Like `new`, but talking to the network through `http`.
+    pub fn with_http_client(db_pool: Arc<Pool>, config: Config, http: reqwest::Client) -> Self {
+        Self {
+            webhooks: MerchantWebhookService::new(db_pool.clone(), config.clone()),
+            soroban: SorobanService::with_http_client(config.clone(), http),
+            db_pool,
+            config,
+            events: PaymentEvents::new(),
+        }
+    }
+
+    /// Status changes made through this service from now on.
+    pub fn subscribe(&self, payment_id: Uuid) -> broadcast::Receiver<PaymentStatusChange> {
+        self.events.subscribe(payment_id)
     }
 
     pub async fn create_payment(
@@ -58,8 +114,8 @@ impl PaymentService {
         // Validate merchant exists and is active
         let _merchant = self.get_merchant(&request.merchant_id).await?;
 
-        // Generate transaction hash (in production, this would be from Stellar)
-        let tx_hash = format!("tx_{}", Uuid::new_v4().simple());
+        // The transaction hash is only known once the payment is submitted
+        let tx_hash: Option<String> = None;
         let payment_id = Uuid::new_v4().to_string();
 
         let row = client
@@ -67,10 +123,10 @@ impl PaymentService {
                 r#"
                 INSERT INTO payments (
                     id, tx_hash, from_address, merchant_id, send_asset,
-                    send_amount, receive_amount, status, memo
+                    send_amount, receive_amount, status, memo, fee_strategy, memo_unique
                 )
-                VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
-                RETURNING id, tx_hash, from_address, merchant_id, send_asset,
+                VALUES ($1::text::uuid, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11)
+                RETURNING id::text, tx_hash, from_address, merchant_id, send_asset,
                          send_amount, receive_amount, status, memo, created_at, updated_at
                 "#,
                 &[
@@ -83,9 +139,20 @@ impl PaymentService {
                     &request.min_receive,
                     &"pending".to_string(),
                     &request.memo,
+                    &request.fee_strategy.to_string(),
+                    &request.memo_unique,
                 ],
             )
-            .await?;
+            .await
+            .map_err(|e| {
+                if request.memo_unique
+                    && e.as_db_error()
+                        .is_some_and(|db| db.code() == &SqlState::UNIQUE_VIOLATION)
+                {
+                    return memo_taken();
+                }
+                ApiError::from(e)
+            })?;
 
         Ok(Payment {
             id: row.get(0),
@@ -97,6 +164,7 @@ impl PaymentService {
             receive_amount: row.get(6),
             status: PaymentStatus::Pending,
             memo: row.get(8),
+            fee_strategy: request.fee_strategy,
             created_at: row.get::<_, chrono::DateTime<chrono::Utc>>(9),
             updated_at: row.get::<_, chrono::DateTime<chrono::Utc>>(10),
         })
@@ -109,7 +177,8 @@ impl PaymentService {
             .query_one(
                 r#"
                 SELECT id, tx_hash, from_address, merchant_id, send_asset,
-                       send_amount, receive_amount, status, memo, created_at, updated_at
+                       send_amount, receive_amount, status, memo, created_at, updated_at,
+                       fee_strategy
                 FROM payments WHERE id = $1
                 "#,
                 &[&payment_id],
@@ -118,7 +187,7 @@ impl PaymentService {
             .map_err(|_| ApiError::NotFound("Payment not found".to_string()))?;
 
         Ok(Payment {
-            id: row.get(0),
+            id: row.get::<_, Uuid>(0).to_string(),
             tx_hash: row.get(1),
             from_address: row.get(2),
             merchant_id: row.get(3),
@@ -127,38 +196,176 @@ impl PaymentService {
             receive_amount: row.get(6),
             status: PaymentStatus::from_str(row.get(7)).unwrap(),
             memo: row.get(8),
+            fee_strategy: FeeStrategy::from_str(row.get(11)).unwrap_or_default(),
             created_at: row.get::<_, chrono::DateTime<chrono::Utc>>(9),
             updated_at: row.get::<_, chrono::DateTime<chrono::Utc>>(10),
         })
     }
 
+    /// Move a payment to `status`. A payment that has just completed has a
+    /// `payment.settled` event queued for its merchant's webhook, if any.
     pub async fn update_payment_status(
         &self,
         payment_id: Uuid,
         status: PaymentStatus,
         tx_hash: Option<String>,
+        notifier: &dyn JobEnqueuer,
     ) -> Result<(), ApiError> {
         let client = self.db_pool.get().await?;
 
-        if let Some(hash) = tx_hash {
-            client
-                .execute(
-                    "UPDATE payments SET status = $1, tx_hash = $2, updated_at = NOW() WHERE id = $3",
-                    &[&status.to_string(), &hash, &payment_id],
-                )
-                .await?;
-        } else {
-            client
-                .execute(
-                    "UPDATE payments SET status = $1, updated_at = NOW() WHERE id = $2",
-                    &[&status.to_string(), &payment_id],
+        let row = client
+            .query_opt(
+                r#"
+                WITH previous AS (
+                    SELECT status FROM payments WHERE id = $3 FOR UPDATE
                 )
-                .await?;
+                UPDATE payments p
+                SET status = $1, tx_hash = COALESCE($2, p.tx_hash), updated_at = NOW()
+                FROM previous
+                WHERE p.id = $3
+                RETURNING p.updated_at, previous.status AS previous_status, p.merchant_id,
+                          p.send_amount, p.send_asset, p.tx_hash
+                "#,
+                &[&status.to_string(), &tx_hash, &payment_id],
+            )
+            .await?;
+        let Some(row) = row else {
+            return Ok(());
+        };
+
+        let updated_at: chrono::DateTime<chrono::Utc> = row.get("updated_at");
+        let settled = matches!(status, PaymentStatus::Completed)
+            && row.get::<_, String>("previous_status") != status.to_string();
+        self.events.publish(PaymentStatusChange {
+            payment_id,
+            status,
+            updated_at,
+        });
+
+        if settled {
+            let merchant_id: String = row.get("merchant_id");
+            let event = json!({
+                "event": "payment.settled",
+                "payment_id": payment_id,
+                "merchant_id": merchant_id,
+                "amount": row.get::<_, i64>("send_amount"),
+                "asset": row.get::<_, String>("send_asset"),
+                "tx_hash": row.get::<_, Option<String>>("tx_hash"),
+                "settled_at": updated_at,
+            });
+
+            // The payment has settled either way; a lost webhook is only logged.
+            if let Err(e) = self.notify_merchant(&merchant_id, &event, notifier).await {
+                error!(%payment_id, merchant_id, error = %e, "Failed to queue settlement webhook");
+            }
         }
 
         Ok(())
     }
 
+    /// Submit the payer's signed transaction and queue its confirmation.
+    ///
+    /// The payment moves to `processing` with its `tx_hash` and settles once
+    /// the confirmation job sees the transaction in a ledger. A submission
+    /// the network refuses leaves the payment `pending`, so it can be retried.
+    pub async fn submit_payment(
+        &self,
+        payment_id: Uuid,
+        signed_xdr: String,
+        notifier: &dyn JobEnqueuer,
+    ) -> Result<Payment, ApiError> {
+        let payment = self.get_payment(payment_id).await?;
+        if !matches!(payment.status, PaymentStatus::Pending) || payment.tx_hash.is_some() {
+            return Err(ApiError::Conflict(format!(
+                "Payment {} has already been submitted",
+                payment_id
+            )));
+        }
+
+        let submitted = self
+            .soroban
+            .submit_transaction(signed_xdr)
+            .await
+            .map_err(|e| {
+                warn!(%payment_id, error = %e, "Payment submission rejected");
+                e
+            })?;
+
+        self.update_payment_status(
+            payment_id,
+            PaymentStatus::Processing,
+            Some(submitted.tx_hash.clone()),
+            notifier,
+        )
+        .await?;
+        info!(%payment_id, tx_hash = %submitted.tx_hash, "Payment submitted");
+
+        let job = payment_confirmation_job(payment_id, &submitted.tx_hash, 1);
+        if let Err(e) = notifier.enqueue(job).await {
+            error!(%payment_id, error = %e, "Failed to enqueue payment confirmation");
+        }
+
+        self.get_payment(payment_id).await
+    }
+
+    /// Check a submitted payment's transaction on-chain and settle the
+    /// payment once it lands. Returns `None` while it isn't in a ledger yet.
+    pub async fn confirm_payment(
+        &self,
+        payment_id: Uuid,
+        tx_hash: &str,
+        notifier: &dyn JobEnqueuer,
+    ) -> Result<Option<PaymentStatus>, ApiError> {
+        let status = match self.soroban.get_transaction_status(tx_hash).await? {
+            OnChainStatus::NotFound => return Ok(None),
+            OnChainStatus::Success => PaymentStatus::Completed,
+            OnChainStatus::Failed => PaymentStatus::Failed,
+        };
+
+        self.update_payment_status(
+            payment_id,
+            status.clone(),
+            Some(tx_hash.to_string()),
+            notifier,
+        )
+        .await?;
+        info!(%payment_id, tx_hash, %status, "Payment confirmed on-chain");
+        Ok(Some(status))
+    }
+
+    /// Give up on a transaction that never appeared on-chain.
+    pub async fn expire_payment(
+        &self,
+        payment_id: Uuid,
+        tx_hash: &str,
+        notifier: &dyn JobEnqueuer,
+    ) -> Result<(), ApiError> {
+        warn!(%payment_id, tx_hash, "Payment never confirmed — marking failed");
+        self.update_payment_status(
+            payment_id,
+            PaymentStatus::Failed,
+            Some(tx_hash.to_string()),
+            notifier,
+        )
+        .await
+    }
+
+    async fn notify_merchant(
+        &self,
+        merchant_id: &str,
+        event: &serde_json::Value,
+        notifier: &dyn JobEnqueuer,
+    ) -> Result<(), ApiError> {
+        let Some(webhook) = self.webhooks.for_merchant(merchant_id).await? else {
+            return Ok(());
+        };
+
+        notifier
+            .enqueue(merchant_webhook_job(&webhook.url, &webhook.secret, event))
+            .await
+            .map_err(|e| ApiError::ServiceUnavailable(e.to_string()))
+    }
+
     pub async fn generate_qr_payment(
         &self,
         payload: crate::http::payments::QrPaymentRequest,
@@ -201,7 +408,7 @@ impl PaymentService {
 
         let row = client
             .query_one(
-                "SELECT id, merchant_id, vault_address, settlement_asset, active, created_at, updated_at FROM merchants WHERE merchant_id = $1 AND active = true",
+                "SELECT id::text, merchant_id, vault_address, settlement_asset, active, created_at, updated_at, fee_strategy FROM merchants WHERE merchant_id = $1 AND active = true",
                 &[&merchant_id],
             )
             .await
@@ -212,9 +419,50 @@ impl PaymentService {
             merchant_id: row.get(1),
             vault_address: row.get(2),
             settlement_asset: row.get(3),
+            fee_strategy: FeeStrategy::from_str(row.get(7)).unwrap_or_default(),
             active: row.get(4),
             created_at: row.get::<_, chrono::DateTime<chrono::Utc>>(5),
             updated_at: row.get::<_, chrono::DateTime<chrono::Utc>>(6),
         })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn merchant(fee_strategy: FeeStrategy) -> Merchant {
+        Merchant {
+            id: "1".to_string(),
+            merchant_id: "shop".to_string(),
+            vault_address: "GVAULT".to_string(),
+            settlement_asset: "USDC".to_string(),
+            fee_strategy,
+            active: true,
+            created_at: chrono::Utc::now(),
+            updated_at: chrono::Utc::now(),
+        }
+    }
+
+    #[test]
+    fn payer_may_only_take_the_fee_on_themselves() {
+        let sponsored = merchant(FeeStrategy::Sponsored);
+        assert_eq!(
+            fee_strategy_for(&sponsored, None).unwrap(),
+            FeeStrategy::Sponsored
+        );
+        assert_eq!(
+            fee_strategy_for(&sponsored, Some(FeeStrategy::SenderPays)).unwrap(),
+            FeeStrategy::SenderPays
+        );
+        assert!(fee_strategy_for(&sponsored, Some(FeeStrategy::MerchantPays)).is_err());
+
+        let sender_pays = merchant(FeeStrategy::SenderPays);
+        assert!(fee_strategy_for(&sender_pays, Some(FeeStrategy::Sponsored)).is_err());
+        assert!(fee_strategy_for(&sender_pays, Some(FeeStrategy::MerchantPays)).is_err());
+        assert_eq!(
+            fee_strategy_for(&merchant(FeeStrategy::MerchantPays), None).unwrap(),
+            FeeStrategy::MerchantPays
+        );
+    }
+}
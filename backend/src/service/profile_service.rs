@@ -1,20 +1,61 @@
-use crate::{api_error::ApiError, config::Config, models::UserProfile};
+use crate::{
+    api_error::ApiError, config::Config, job_processors::MaintenanceTask, models::UserProfile,
+};
+use async_trait::async_trait;
 use deadpool_postgres::Pool;
 use std::sync::Arc;
+use tracing::info;
 use uuid::Uuid;
 
+const PROFILE_COLUMNS: &str =
+    "id, user_id, display_name, avatar_url, bio, country, metadata, created_at, updated_at, version";
+
+fn profile_from_row(row: &tokio_postgres::Row) -> UserProfile {
+    UserProfile {
+        id: row.get::<_, Uuid>(0).to_string(),
+        user_id: row.get::<_, Uuid>(1).to_string(),
+        display_name: row.get(2),
+        avatar_url: row.get(3),
+        bio: row.get(4),
+        country: row.get(5),
+        metadata: row.get(6),
+        created_at: row.get(7),
+        updated_at: row.get(8),
+        version: row.get(9),
+    }
+}
+
+fn version_conflict(current_version: i32) -> ApiError {
+    ApiError::Conflict(format!(
+        "Profile has been modified (current version {})",
+        current_version
+    ))
+}
+
+/// Fields to change on a profile; `None` leaves a field as it is.
+#[derive(Debug, Clone, Default)]
+pub struct ProfileChanges {
+    pub display_name: Option<String>,
+    pub avatar_url: Option<String>,
+    pub bio: Option<String>,
+    pub country: Option<String>,
+    pub metadata: Option<serde_json::Value>,
+}
+
 #[derive(Clone)]
 pub struct ProfileService {
     db_pool: Arc<Pool>,
-    _config: Config,
+    config: Config,
 }
 
 impl ProfileService {
     pub fn new(db_pool: Arc<Pool>, config: Config) -> Self {
-        Self {
-            db_pool,
-            _config: config,
-        }
+        Self { db_pool, config }
+    }
+
+    /// Recovery window as seconds, for `make_interval`.
+    fn recovery_window_secs(&self) -> f64 {
+        (self.config.profiles.recovery_window_hours * 3600) as f64
     }
 
     pub async fn create_profile(
@@ -33,16 +74,24 @@ impl ProfileService {
         // For now, let's just run the INSERT and catch uniqueness violation if possible, or rely on calling check before.
         // But http handler does check.
 
+        // A soft-deleted profile is replaced: creating a new one gives up on
+        // restoring the old.
         let stmt = client
-            .prepare(
-                "INSERT INTO user_profiles (user_id, display_name, avatar_url, bio, country, metadata) 
-                 VALUES ($1, $2, $3, $4, $5, $6) 
-                 RETURNING id, user_id, display_name, avatar_url, bio, country, metadata, created_at, updated_at",
-            )
+            .prepare(&format!(
+                "INSERT INTO user_profiles (user_id, display_name, avatar_url, bio, country, metadata)
+                 VALUES ($1, $2, $3, $4, $5, $6)
+                 ON CONFLICT (user_id) DO UPDATE
+                 SET display_name = EXCLUDED.display_name, avatar_url = EXCLUDED.avatar_url,
+                     bio = EXCLUDED.bio, country = EXCLUDED.country, metadata = EXCLUDED.metadata,
+                     deleted_at = NULL, version = 1, created_at = NOW(), updated_at = NOW()
+                 WHERE user_profiles.deleted_at IS NOT NULL
+                 RETURNING {}",
+                PROFILE_COLUMNS
+            ))
             .await?;
 
         let row = client
-            .query_one(
+            .query_opt(
                 &stmt,
                 &[
                     &user_id,
@@ -63,54 +112,36 @@ impl ProfileService {
                     }
                 }
                 ApiError::Database(e)
-            })?;
-
-        Ok(UserProfile {
-            id: row.get::<_, Uuid>(0).to_string(),
-            user_id: row.get::<_, Uuid>(1).to_string(),
-            display_name: row.get(2),
-            avatar_url: row.get(3),
-            bio: row.get(4),
-            country: row.get(5),
-            metadata: row.get(6),
-            created_at: row.get(7),
-            updated_at: row.get(8),
-        })
+            })?
+            // The conflicting profile is live.
+            .ok_or_else(|| ApiError::Conflict("Profile already exists".into()))?;
+
+        Ok(profile_from_row(&row))
     }
 
     pub async fn get_profile(&self, user_id: Uuid) -> Result<Option<UserProfile>, ApiError> {
         let client = self.db_pool.get().await?;
 
         let stmt = client
-            .prepare("SELECT id, user_id, display_name, avatar_url, bio, country, metadata, created_at, updated_at FROM user_profiles WHERE user_id = $1")
+            .prepare(&format!(
+                "SELECT {} FROM user_profiles WHERE user_id = $1 AND deleted_at IS NULL",
+                PROFILE_COLUMNS
+            ))
             .await?;
 
         let row = client.query_opt(&stmt, &[&user_id]).await?;
 
-        match row {
-            Some(row) => Ok(Some(UserProfile {
-                id: row.get::<_, Uuid>(0).to_string(),
-                user_id: row.get::<_, Uuid>(1).to_string(),
-                display_name: row.get(2),
-                avatar_url: row.get(3),
-                bio: row.get(4),
-                country: row.get(5),
-                metadata: row.get(6),
-                created_at: row.get(7),
-                updated_at: row.get(8),
-            })),
-            None => Ok(None),
-        }
+        Ok(row.as_ref().map(profile_from_row))
     }
 
+    /// Apply `changes` if the stored profile is still at `expected_version`,
+    /// bumping the version. A profile written by someone else since it was
+    /// read is a `Conflict` rather than being overwritten.
     pub async fn update_profile(
         &self,
         user_id: Uuid,
-        display_name: Option<String>,
-        avatar_url: Option<String>,
-        bio: Option<String>,
-        country: Option<String>,
-        metadata: Option<serde_json::Value>,
+        expected_version: i32,
+        changes: ProfileChanges,
     ) -> Result<UserProfile, ApiError> {
         let client = self.db_pool.get().await?;
 
@@ -119,49 +150,56 @@ impl ProfileService {
         let mut params: Vec<Box<dyn tokio_postgres::types::ToSql + Sync + Send>> = Vec::new();
         let mut param_idx = 1;
 
-        if let Some(dn) = display_name {
+        if let Some(dn) = changes.display_name {
             query.push_str(&format!("display_name = ${}, ", param_idx));
             params.push(Box::new(dn));
             param_idx += 1;
         }
 
-        if let Some(au) = avatar_url {
+        if let Some(au) = changes.avatar_url {
             query.push_str(&format!("avatar_url = ${}, ", param_idx));
             params.push(Box::new(au));
             param_idx += 1;
         }
 
-        if let Some(b) = bio {
+        if let Some(b) = changes.bio {
             query.push_str(&format!("bio = ${}, ", param_idx));
             params.push(Box::new(b));
             param_idx += 1;
         }
 
-        if let Some(c) = country {
+        if let Some(c) = changes.country {
             query.push_str(&format!("country = ${}, ", param_idx));
             params.push(Box::new(c));
             param_idx += 1;
         }
 
-        if let Some(m) = metadata {
+        if let Some(m) = changes.metadata {
             query.push_str(&format!("metadata = ${}, ", param_idx));
             params.push(Box::new(m));
             param_idx += 1;
         }
 
-        // Remove trailing comma and space
-        if query.ends_with(", ") {
-            query.truncate(query.len() - 2);
-        } else {
+        if params.is_empty() {
             // Nothing to update, just return the profile
-            return self
+            let profile = self
                 .get_profile(user_id)
                 .await?
-                .ok_or(ApiError::NotFound("Profile not found".into()));
+                .ok_or(ApiError::NotFound("Profile not found".into()))?;
+            if profile.version != expected_version {
+                return Err(version_conflict(profile.version));
+            }
+            return Ok(profile);
         }
 
-        query.push_str(&format!(" WHERE user_id = ${} RETURNING id, user_id, display_name, avatar_url, bio, country, metadata, created_at, updated_at", param_idx));
+        query.push_str(&format!(
+            "version = version + 1 WHERE user_id = ${} AND version = ${} AND deleted_at IS NULL RETURNING {}",
+            param_idx,
+            param_idx + 1,
+            PROFILE_COLUMNS
+        ));
         params.push(Box::new(user_id));
+        params.push(Box::new(expected_version));
 
         let stmt = client.prepare(&query).await?;
 
@@ -171,30 +209,74 @@ impl ProfileService {
             .map(|p| p.as_ref() as &(dyn tokio_postgres::types::ToSql + Sync))
             .collect();
 
-        let row = client
-            .query_one(&stmt, &params_refs)
+        match client
+            .query_opt(&stmt, &params_refs)
             .await
-            .map_err(ApiError::Database)?;
-
-        Ok(UserProfile {
-            id: row.get::<_, Uuid>(0).to_string(),
-            user_id: row.get::<_, Uuid>(1).to_string(),
-            display_name: row.get(2),
-            avatar_url: row.get(3),
-            bio: row.get(4),
-            country: row.get(5),
-            metadata: row.get(6),
-            created_at: row.get(7),
-            updated_at: row.get(8),
-        })
+            .map_err(ApiError::Database)?
+        {
+            Some(row) => Ok(profile_from_row(&row)),
+            // Either there's no live profile or the version moved on.
+            None => match self.get_profile(user_id).await? {
+                Some(current) => Err(version_conflict(current.version)),
+                None => Err(ApiError::NotFound("Profile not found".into())),
+            },
+        }
     }
 
+    /// Soft-delete a profile. It disappears from reads and can be restored
+    /// until the recovery window ends.
     pub async fn delete_profile(&self, user_id: Uuid) -> Result<(), ApiError> {
         let client = self.db_pool.get().await?;
         let stmt = client
-            .prepare("DELETE FROM user_profiles WHERE user_id = $1")
+            .prepare(
+                "UPDATE user_profiles SET deleted_at = NOW() WHERE user_id = $1 AND deleted_at IS NULL",
+            )
+            .await?;
+        if client.execute(&stmt, &[&user_id]).await? == 0 {
+            return Err(ApiError::NotFound("Profile not found".into()));
+        }
+        Ok(())
+    }
+
+    /// Undo a soft delete made within the recovery window.
+    pub async fn restore_profile(&self, user_id: Uuid) -> Result<UserProfile, ApiError> {
+        let client = self.db_pool.get().await?;
+        let query = format!(
+            "UPDATE user_profiles SET deleted_at = NULL, updated_at = NOW()
+             WHERE user_id = $1 AND deleted_at > NOW() - make_interval(secs => $2)
+             RETURNING {}",
+            PROFILE_COLUMNS
+        );
+
+        let row = client
+            .query_opt(&query, &[&user_id, &self.recovery_window_secs()])
+            .await?
+            .ok_or(ApiError::NotFound("No deleted profile to restore".into()))?;
+
+        Ok(profile_from_row(&row))
+    }
+
+    /// Hard-delete profiles whose recovery window has passed.
+    pub async fn purge_deleted(&self) -> Result<u64, ApiError> {
+        let client = self.db_pool.get().await?;
+        let purged = client
+            .execute(
+                "DELETE FROM user_profiles WHERE deleted_at <= NOW() - make_interval(secs => $1)",
+                &[&self.recovery_window_secs()],
+            )
             .await?;
-        client.execute(&stmt, &[&user_id]).await?;
+
+        if purged > 0 {
+            info!(purged, "Purged deleted profiles");
+        }
+        Ok(purged)
+    }
+}
+
+#[async_trait]
+impl MaintenanceTask for ProfileService {
+    async fn run(&self) -> anyhow::Result<()> {
+        self.purge_deleted().await?;
         Ok(())
     }
 }
@@ -0,0 +1,167 @@
+use crate::api_error::ApiError;
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::{Arc, Mutex};
+
+/// Upper bound on cached payloads; the soonest-expiring entry is evicted
+/// when it is reached.
+const MAX_ENTRIES: usize = 10_000;
+
+/// Everything that goes into a QR payload. Identical requests share a key.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct QrCacheKey {
+    pub merchant_id: String,
+    pub amount: i64,
+    pub asset: String,
+    pub memo: Option<String>,
+    /// Unix timestamp after which the payload is no longer served.
+    pub expiry: i64,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CachedQr {
+    pub qr_data: String,
+    pub xdr_payload: Option<String>,
+}
+
+/// In-process cache of generated QR payloads, so a merchant re-displaying
+/// the same QR doesn't rebuild and re-sign the XDR on every poll.
+#[derive(Clone, Default)]
+pub struct QrPayloadCache {
+    entries: Arc<Mutex<HashMap<QrCacheKey, CachedQr>>>,
+}
+
+impl QrPayloadCache {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Return the cached payload for `key`, or run `build` and cache its
+    /// result until `key.expiry`.
+    ///
+    /// Payloads without a signed XDR aren't cached, so a transient signing
+    /// failure isn't served for the whole window.
+    pub async fn get_or_build<F, Fut>(
+        &self,
+        key: QrCacheKey,
+        build: F,
+    ) -> Result<CachedQr, ApiError>
+    where
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = Result<CachedQr, ApiError>>,
+    {
+        self.get_or_build_at(key, chrono::Utc::now().timestamp(), build)
+            .await
+    }
+
+    async fn get_or_build_at<F, Fut>(
+        &self,
+        key: QrCacheKey,
+        now: i64,
+        build: F,
+    ) -> Result<CachedQr, ApiError>
+    where
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = Result<CachedQr, ApiError>>,
+    {
+        if key.expiry > now {
+            if let Some(hit) = self.lock().get(&key) {
+                return Ok(hit.clone());
+            }
+        }
+
+        let built = build().await?;
+
+        if key.expiry > now && built.xdr_payload.is_some() {
+            let mut entries = self.lock();
+            entries.retain(|cached, _| cached.expiry > now);
+            if entries.len() >= MAX_ENTRIES {
+                if let Some(soonest) = entries.keys().min_by_key(|k| k.expiry).cloned() {
+                    entries.remove(&soonest);
+                }
+            }
+            entries.insert(key, built.clone());
+        }
+
+        Ok(built)
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, HashMap<QrCacheKey, CachedQr>> {
+        // The map holds no invariants a panicking holder could break.
+        self.entries.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    fn key(amount: i64, expiry: i64) -> QrCacheKey {
+        QrCacheKey {
+            merchant_id: "merchant-1".to_string(),
+            amount,
+            asset: "XLM".to_string(),
+            memo: Some("order-7".to_string()),
+            expiry,
+        }
+    }
+
+    async fn build_counted(
+        cache: &QrPayloadCache,
+        key: QrCacheKey,
+        now: i64,
+        signs: &AtomicUsize,
+        signed: bool,
+    ) -> CachedQr {
+        cache
+            .get_or_build_at(key, now, || async {
+                let n = signs.fetch_add(1, Ordering::SeqCst);
+                Ok(CachedQr {
+                    qr_data: format!("qr-{}", n),
+                    xdr_payload: signed.then(|| format!("xdr-{}_signed", n)),
+                })
+            })
+            .await
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn identical_requests_within_window_sign_once() {
+        let cache = QrPayloadCache::new();
+        let signs = AtomicUsize::new(0);
+
+        let first = build_counted(&cache, key(100, 2_000), 1_000, &signs, true).await;
+        let second = build_counted(&cache, key(100, 2_000), 1_500, &signs, true).await;
+
+        assert_eq!(first, second);
+        assert_eq!(signs.load(Ordering::SeqCst), 1);
+
+        // Any differing field is a different payload.
+        build_counted(&cache, key(101, 2_000), 1_500, &signs, true).await;
+        assert_eq!(signs.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn expired_entries_are_rebuilt() {
+        let cache = QrPayloadCache::new();
+        let signs = AtomicUsize::new(0);
+
+        build_counted(&cache, key(100, 2_000), 1_000, &signs, true).await;
+        let after = build_counted(&cache, key(100, 2_000), 2_000, &signs, true).await;
+
+        assert_eq!(signs.load(Ordering::SeqCst), 2);
+        assert_eq!(after.qr_data, "qr-1");
+    }
+
+    #[tokio::test]
+    async fn unsigned_payloads_are_not_cached() {
+        let cache = QrPayloadCache::new();
+        let signs = AtomicUsize::new(0);
+
+        build_counted(&cache, key(100, 2_000), 1_000, &signs, false).await;
+        build_counted(&cache, key(100, 2_000), 1_000, &signs, false).await;
+
+        assert_eq!(signs.load(Ordering::SeqCst), 2);
+    }
+}
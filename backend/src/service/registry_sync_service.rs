@@ -0,0 +1,550 @@
+use crate::{
+    api_error::ApiError,
+    config::Config,
+    job_processors::MaintenanceTask,
+    service::{
+        soroban_service::{ContractEvent, EventsFrom},
+        SorobanService,
+    },
+};
+use async_trait::async_trait;
+use deadpool_postgres::{tokio_postgres::error::SqlState, Pool};
+use serde::Serialize;
+use serde_json::Value;
+use std::sync::Arc;
+use tracing::{error, info, warn};
+
+/// Most `getEvents` pages fetched in one run, so a long backlog is worked
+/// through over several runs instead of one unbounded one.
+const MAX_PAGES_PER_RUN: usize = 20;
+
+/// A registration or deactivation emitted by the `BLINKSRegistry` contract.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RegistryEvent {
+    /// `user_reg`: a handle bound to a wallet.
+    User { user_id: String, wallet: String },
+    /// `merch_reg`: a merchant and its settlement metadata.
+    Merchant {
+        merchant_id: String,
+        vault: String,
+        settlement_asset: String,
+        active: bool,
+    },
+    /// `merch_dea`: a merchant switched off by the registry admin.
+    MerchantDeactivated { merchant_id: String },
+}
+
+/// Topic symbol of a JSON-encoded `ScVal`, e.g. `{"symbol": "user_reg"}`.
+fn scval_symbol(value: &Value) -> Option<&str> {
+    value["symbol"].as_str()
+}
+
+/// UTF-8 contents of a JSON-encoded `ScVal::Bytes` (hex).
+fn scval_bytes_str(value: &Value) -> Option<String> {
+    let bytes = hex::decode(value["bytes"].as_str()?).ok()?;
+    String::from_utf8(bytes).ok()
+}
+
+fn scval_address(value: &Value) -> Option<String> {
+    value["address"].as_str().map(str::to_string)
+}
+
+/// Field of a JSON-encoded `ScVal::Map` with symbol keys (a `contracttype` struct).
+fn scval_field<'a>(value: &'a Value, name: &str) -> Option<&'a Value> {
+    value["map"]
+        .as_array()?
+        .iter()
+        .find(|entry| scval_symbol(&entry["key"]) == Some(name))
+        .map(|entry| &entry["val"])
+}
+
+impl RegistryEvent {
+    /// Decode a registry event; other events from the contract yield `None`.
+    pub fn parse(event: &ContractEvent) -> Option<Self> {
+        let kind = scval_symbol(event.topics.first()?)?;
+        let id = scval_bytes_str(event.topics.get(1)?)?;
+
+        match kind {
+            "user_reg" => Some(RegistryEvent::User {
+                user_id: id,
+                wallet: scval_address(&event.value)?,
+            }),
+            "merch_reg" => Some(RegistryEvent::Merchant {
+                merchant_id: id,
+                vault: scval_address(scval_field(&event.value, "vault")?)?,
+                settlement_asset: scval_address(scval_field(&event.value, "settlement_asset")?)?,
+                active: scval_field(&event.value, "active")?["bool"].as_bool()?,
+            }),
+            "merch_dea" => Some(RegistryEvent::MerchantDeactivated { merchant_id: id }),
+            _ => None,
+        }
+    }
+}
+
+/// A write a sync makes for one registry event.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "action", rename_all = "snake_case")]
+pub enum RegistryChange {
+    UpsertMerchant {
+        merchant_id: String,
+        vault_address: String,
+        settlement_asset: String,
+    },
+    DeactivateMerchant {
+        merchant_id: String,
+    },
+    UpsertUser {
+        user_id: String,
+        stellar_address: String,
+    },
+}
+
+impl From<&RegistryEvent> for RegistryChange {
+    fn from(event: &RegistryEvent) -> Self {
+        match event.clone() {
+            RegistryEvent::User { user_id, wallet } => RegistryChange::UpsertUser {
+                user_id,
+                stellar_address: wallet,
+            },
+            RegistryEvent::Merchant {
+                merchant_id,
+                vault,
+                settlement_asset,
+                ..
+            } => RegistryChange::UpsertMerchant {
+                merchant_id,
+                vault_address: vault,
+                settlement_asset,
+            },
+            RegistryEvent::MerchantDeactivated { merchant_id } => {
+                RegistryChange::DeactivateMerchant { merchant_id }
+            }
+        }
+    }
+}
+
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize)]
+pub struct RegistrySyncSummary {
+    pub dry_run: bool,
+    pub merchants: usize,
+    pub users: usize,
+    /// Events that couldn't be applied, e.g. a wallet already bound to
+    /// another user. Always zero for a dry run, which doesn't attempt them.
+    pub skipped: usize,
+    pub last_ledger: u32,
+    /// What a dry run would write, in event order. Left empty by a real
+    /// sync.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub changes: Vec<RegistryChange>,
+}
+
+impl RegistrySyncSummary {
+    fn count(&mut self, event: &RegistryEvent) {
+        match event {
+            RegistryEvent::Merchant { .. } | RegistryEvent::MerchantDeactivated { .. } => {
+                self.merchants += 1
+            }
+            RegistryEvent::User { .. } => self.users += 1,
+        }
+    }
+
+    /// Note what applying `event` would change, for a dry run.
+    fn record(&mut self, event: &RegistryEvent) {
+        self.count(event);
+        self.changes.push(event.into());
+    }
+}
+
+/// Mirrors `BLINKSRegistry` registrations into `merchants` and `users`.
+///
+/// Progress is tracked per contract in `registry_sync_state`. Each run
+/// re-reads the last processed ledger, and applying an event is an upsert,
+/// so replaying events after a crash or a partial ledger is harmless.
+#[derive(Clone)]
+pub struct RegistrySyncService {
+    db_pool: Arc<Pool>,
+    config: Config,
+    soroban: SorobanService,
+}
+
+impl RegistrySyncService {
+    pub fn new(db_pool: Arc<Pool>, config: Config) -> Self {
+        Self {
+            db_pool: db_pool.clone(),
+            soroban: SorobanService::new(config.clone()),
+            config,
+        }
+    }
+
+    /// Like `new`, but reading contract events through `http`.
+    pub fn with_http_client(db_pool: Arc<Pool>, config: Config, http: reqwest::Client) -> Self {
+        Self {
+            db_pool,
+            soroban: SorobanService::with_http_client(config.clone(), http),
+            config,
+        }
+    }
+
+    /// Apply every registry event since the last run.
+    pub async fn sync_once(&self) -> Result<RegistrySyncSummary, ApiError> {
+        self.sync(false).await
+    }
+
+    /// Read every registry event since the last run and, unless `dry_run`,
+    /// apply it. A dry run writes nothing, not even sync progress, and
+    /// instead lists the changes it would have made.
+    pub async fn sync(&self, dry_run: bool) -> Result<RegistrySyncSummary, ApiError> {
+        let sync = &self.config.registry_sync;
+        let contract_id = sync.contract_id.as_str();
+
+        let start_ledger = match self.last_ledger(contract_id).await? {
+            Some(ledger) => ledger,
+            None if sync.start_ledger > 0 => sync.start_ledger,
+            None => self.soroban.get_latest_ledger().await?,
+        };
+
+        let mut summary = RegistrySyncSummary {
+            dry_run,
+            last_ledger: start_ledger,
+            ..Default::default()
+        };
+        let mut from = EventsFrom::Ledger(start_ledger);
+
+        for _ in 0..MAX_PAGES_PER_RUN {
+            let page = self
+                .soroban
+                .get_contract_events(contract_id, &from, sync.page_size)
+                .await?;
+
+            for event in &page.events {
+                match RegistryEvent::parse(event) {
+                    Some(registration) if dry_run => summary.record(&registration),
+                    Some(registration) => self.apply(&registration, &mut summary).await?,
+                    None => {}
+                }
+                summary.last_ledger = summary.last_ledger.max(event.ledger);
+            }
+
+            let more = page.events.len() >= sync.page_size as usize;
+            match page.cursor {
+                Some(cursor) if more => from = EventsFrom::Cursor(cursor),
+                _ => {
+                    // Every event up to the node's latest ledger has been seen.
+                    summary.last_ledger = summary.last_ledger.max(page.latest_ledger);
+                    break;
+                }
+            }
+        }
+
+        if dry_run {
+            info!(
+                merchants = summary.merchants,
+                users = summary.users,
+                last_ledger = summary.last_ledger,
+                changes = ?summary.changes,
+                "Registry sync dry run complete"
+            );
+        } else {
+            self.save_last_ledger(contract_id, summary.last_ledger)
+                .await?;
+        }
+        Ok(summary)
+    }
+
+    async fn apply(
+        &self,
+        event: &RegistryEvent,
+        summary: &mut RegistrySyncSummary,
+    ) -> Result<(), ApiError> {
+        let client = self.db_pool.get().await.map_err(|e| {
+            error!(error = %e, "DB pool error");
+            ApiError::InternalServerError
+        })?;
+
+        let result = match event {
+            RegistryEvent::Merchant {
+                merchant_id,
+                vault,
+                settlement_asset,
+                active,
+            } => {
+                client
+                    .execute(
+                        r#"
+                        INSERT INTO merchants (merchant_id, vault_address, settlement_asset, active)
+                        VALUES ($1, $2, $3, $4)
+                        ON CONFLICT (merchant_id) DO UPDATE
+                        SET vault_address = EXCLUDED.vault_address,
+                            settlement_asset = EXCLUDED.settlement_asset,
+                            active = EXCLUDED.active,
+                            updated_at = NOW()
+                        "#,
+                        &[merchant_id, vault, settlement_asset, active],
+                    )
+                    .await
+            }
+            // A merchant unknown here has nothing to switch off.
+            RegistryEvent::MerchantDeactivated { merchant_id } => {
+                client
+                    .execute(
+                        "UPDATE merchants SET active = false, updated_at = NOW() WHERE merchant_id = $1",
+                        &[merchant_id],
+                    )
+                    .await
+            }
+            RegistryEvent::User { user_id, wallet } => {
+                // `register_user` only needs the wallet's own signature, so a
+                // handle that already exists here must never be rebound.
+                let inserted = client
+                    .execute(
+                        r#"
+                        INSERT INTO users (user_id, stellar_address)
+                        VALUES ($1, $2)
+                        ON CONFLICT (user_id) DO NOTHING
+                        "#,
+                        &[user_id, wallet],
+                    )
+                    .await;
+                if let Ok(0) = inserted {
+                    let existing = client
+                        .query_opt(
+                            "SELECT stellar_address FROM users WHERE user_id = $1",
+                            &[user_id],
+                        )
+                        .await
+                        .map_err(|e| {
+                            error!(error = %e, "Failed to read conflicting user");
+                            ApiError::InternalServerError
+                        })?
+                        .map(|row| row.get::<_, String>(0));
+                    if existing.as_deref() != Some(wallet.as_str()) {
+                        warn!(
+                            user_id = %user_id,
+                            registry_wallet = %wallet,
+                            "Registry handle is already bound to another wallet; not rebinding it"
+                        );
+                        summary.skipped += 1;
+                        return Ok(());
+                    }
+                }
+                inserted
+            }
+        };
+
+        match result {
+            Ok(_) => {
+                summary.count(event);
+                Ok(())
+            }
+            Err(e)
+                if e.as_db_error()
+                    .is_some_and(|db| db.code() == &SqlState::UNIQUE_VIOLATION) =>
+            {
+                // Retrying won't help, so don't hold up the events after it.
+                warn!(error = %e, event = ?event, "Skipping conflicting registry event");
+                summary.skipped += 1;
+                Ok(())
+            }
+            Err(e) => {
+                error!(error = %e, "Failed to apply registry event");
+                Err(ApiError::InternalServerError)
+            }
+        }
+    }
+
+    async fn last_ledger(&self, contract_id: &str) -> Result<Option<u32>, ApiError> {
+        let client = self.db_pool.get().await.map_err(|e| {
+            error!(error = %e, "DB pool error");
+            ApiError::InternalServerError
+        })?;
+
+        let row = client
+            .query_opt(
+                "SELECT last_ledger FROM registry_sync_state WHERE contract_id = $1",
+                &[&contract_id],
+            )
+            .await
+            .map_err(|e| {
+                error!(error = %e, "Failed to read registry sync state");
+                ApiError::InternalServerError
+            })?;
+
+        Ok(row.map(|row| row.get::<_, i64>("last_ledger") as u32))
+    }
+
+    async fn save_last_ledger(&self, contract_id: &str, ledger: u32) -> Result<(), ApiError> {
+        let client = self.db_pool.get().await.map_err(|e| {
+            error!(error = %e, "DB pool error");
+            ApiError::InternalServerError
+        })?;
+
+        client
+            .execute(
+                r#"
+                INSERT INTO registry_sync_state (contract_id, last_ledger)
+                VALUES ($1, $2)
+                ON CONFLICT (contract_id) DO UPDATE
+                SET last_ledger = GREATEST(registry_sync_state.last_ledger, EXCLUDED.last_ledger),
+                    updated_at = NOW()
+                "#,
+                &[&contract_id, &(ledger as i64)],
+            )
+            .await
+            .map_err(|e| {
+                error!(error = %e, "Failed to save registry sync state");
+                ApiError::InternalServerError
+            })?;
+
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl MaintenanceTask for RegistrySyncService {
+    async fn run(&self) -> anyhow::Result<()> {
+        let summary = self.sync_once().await?;
+        if summary.merchants + summary.users + summary.skipped > 0 {
+            info!(
+                merchants = summary.merchants,
+                users = summary.users,
+                skipped = summary.skipped,
+                last_ledger = summary.last_ledger,
+                "Registry sync run complete"
+            );
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn event(topics: Vec<Value>, value: Value) -> ContractEvent {
+        ContractEvent {
+            id: "0000000001-0000000001".to_string(),
+            ledger: 1,
+            topics,
+            value,
+        }
+    }
+
+    #[test]
+    fn parses_user_registration() {
+        let parsed = RegistryEvent::parse(&event(
+            vec![
+                json!({ "symbol": "user_reg" }),
+                json!({ "bytes": hex::encode("alice") }),
+            ],
+            json!({ "address": "GALICE" }),
+        ));
+
+        assert_eq!(
+            parsed,
+            Some(RegistryEvent::User {
+                user_id: "alice".to_string(),
+                wallet: "GALICE".to_string(),
+            })
+        );
+    }
+
+    #[test]
+    fn parses_merchant_registration() {
+        let parsed = RegistryEvent::parse(&event(
+            vec![
+                json!({ "symbol": "merch_reg" }),
+                json!({ "bytes": hex::encode("coffee-shop") }),
+            ],
+            json!({ "map": [
+                { "key": { "symbol": "active" }, "val": { "bool": true } },
+                { "key": { "symbol": "settlement_asset" }, "val": { "address": "CUSDC" } },
+                { "key": { "symbol": "vault" }, "val": { "address": "GVAULT" } },
+            ]}),
+        ));
+
+        assert_eq!(
+            parsed,
+            Some(RegistryEvent::Merchant {
+                merchant_id: "coffee-shop".to_string(),
+                vault: "GVAULT".to_string(),
+                settlement_asset: "CUSDC".to_string(),
+                active: true,
+            })
+        );
+    }
+
+    #[test]
+    fn parses_merchant_deactivation() {
+        let parsed = RegistryEvent::parse(&event(
+            vec![
+                json!({ "symbol": "merch_dea" }),
+                json!({ "bytes": hex::encode("coffee-shop") }),
+            ],
+            json!({ "void": null }),
+        ));
+
+        assert_eq!(
+            parsed,
+            Some(RegistryEvent::MerchantDeactivated {
+                merchant_id: "coffee-shop".to_string(),
+            })
+        );
+    }
+
+    #[test]
+    fn ignores_other_and_malformed_events() {
+        let other = event(
+            vec![json!({ "symbol": "transfer" }), json!({ "bytes": "00" })],
+            json!({ "i128": "5" }),
+        );
+        let missing_vault = event(
+            vec![
+                json!({ "symbol": "merch_reg" }),
+                json!({ "bytes": hex::encode("shop") }),
+            ],
+            json!({ "map": [] }),
+        );
+
+        assert_eq!(RegistryEvent::parse(&other), None);
+        assert_eq!(RegistryEvent::parse(&missing_vault), None);
+        assert_eq!(RegistryEvent::parse(&event(vec![], Value::Null)), None);
+    }
+
+    #[test]
+    fn dry_run_records_upserts_and_deactivations() {
+        let mut summary = RegistrySyncSummary {
+            dry_run: true,
+            ..Default::default()
+        };
+        summary.record(&RegistryEvent::User {
+            user_id: "alice".to_string(),
+            wallet: "GALICE".to_string(),
+        });
+        summary.record(&RegistryEvent::Merchant {
+            merchant_id: "coffee-shop".to_string(),
+            vault: "GVAULT".to_string(),
+            settlement_asset: "CUSDC".to_string(),
+            active: true,
+        });
+        summary.record(&RegistryEvent::MerchantDeactivated {
+            merchant_id: "coffee-shop".to_string(),
+        });
+
+        assert_eq!(summary.users, 1);
+        assert_eq!(summary.merchants, 2);
+        assert_eq!(
+            serde_json::to_value(&summary.changes).unwrap(),
+            json!([
+                { "action": "upsert_user", "user_id": "alice", "stellar_address": "GALICE" },
+                {
+                    "action": "upsert_merchant",
+                    "merchant_id": "coffee-shop",
+                    "vault_address": "GVAULT",
+                    "settlement_asset": "CUSDC",
+                },
+                { "action": "deactivate_merchant", "merchant_id": "coffee-shop" },
+            ])
+        );
+    }
+}
@@ -0,0 +1,106 @@
+use crate::{
+    api_error::ApiError,
+    config::{Config, ReputationTier},
+    service::SorobanService,
+};
+use serde_json::json;
+use tracing::{debug, error};
+
+/// The tier with the highest `min_score` that `score` reaches, if any.
+pub fn select_tier(tiers: &[ReputationTier], score: u32) -> Option<&ReputationTier> {
+    tiers
+        .iter()
+        .filter(|tier| score >= tier.min_score)
+        .max_by_key(|tier| tier.min_score)
+}
+
+/// Reads users' scores from the reputation contract and maps them onto the
+/// configured withdrawal tiers.
+#[derive(Clone)]
+pub struct ReputationService {
+    config: Config,
+    soroban: SorobanService,
+}
+
+impl ReputationService {
+    pub fn new(config: Config) -> Self {
+        Self {
+            soroban: SorobanService::new(config.clone()),
+            config,
+        }
+    }
+
+    /// Like `new`, but reading the contract through `http`.
+    pub fn with_http_client(config: Config, http: reqwest::Client) -> Self {
+        Self {
+            soroban: SorobanService::with_http_client(config.clone(), http),
+            config,
+        }
+    }
+
+    /// The score the contract holds for `address`; `0` if it has none.
+    pub async fn get_score(&self, address: &str) -> Result<u32, ApiError> {
+        let value = self
+            .soroban
+            .call_contract(
+                &self.config.reputation.contract_id,
+                "get_score",
+                vec![json!({ "address": address })],
+            )
+            .await?;
+
+        value["u32"]
+            .as_u64()
+            .and_then(|score| u32::try_from(score).ok())
+            .ok_or_else(|| {
+                error!(%value, "Reputation contract returned a malformed score");
+                ApiError::Stellar(format!("get_score returned {}", value))
+            })
+    }
+
+    /// The withdrawal tier `address` qualifies for. `None` when tiers are
+    /// disabled or the score is below every tier.
+    ///
+    /// A score that can't be read fails the withdrawal rather than falling
+    /// back to limits the user may not qualify for.
+    pub async fn withdrawal_tier(&self, address: &str) -> Result<Option<ReputationTier>, ApiError> {
+        let reputation = &self.config.reputation;
+        if !reputation.enabled || reputation.tiers.is_empty() {
+            return Ok(None);
+        }
+
+        let score = self.get_score(address).await?;
+        let tier = select_tier(&reputation.tiers, score).cloned();
+        debug!(
+            address,
+            score,
+            tier = tier.as_ref().map(|t| t.name.as_str()),
+            "Selected reputation tier"
+        );
+        Ok(tier)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tier(name: &str, min_score: u32) -> ReputationTier {
+        ReputationTier {
+            name: name.to_string(),
+            min_score,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn highest_reached_tier_is_selected() {
+        let tiers = [tier("trusted", 500), tier("new", 100), tier("vip", 1000)];
+
+        assert_eq!(select_tier(&tiers, 99), None);
+        assert_eq!(select_tier(&tiers, 100).unwrap().name, "new");
+        assert_eq!(select_tier(&tiers, 999).unwrap().name, "trusted");
+        assert_eq!(select_tier(&tiers, 1000).unwrap().name, "vip");
+        assert_eq!(select_tier(&tiers, u32::MAX).unwrap().name, "vip");
+    }
+}
@@ -0,0 +1,226 @@
+use crate::{api_error::ApiError, config::Config};
+use deadpool_postgres::Pool;
+use serde::Serialize;
+use std::sync::Arc;
+use tracing::{error, info, warn};
+
+/// Longest `User-Agent` kept as a session's device label.
+const MAX_DEVICE_LEN: usize = 255;
+
+/// An active refresh-token family, as shown to its owner.
+#[derive(Debug, Clone, Serialize)]
+pub struct SessionRecord {
+    pub id: String,
+    pub user_id: String,
+    pub device: Option<String>,
+    pub issued_at: chrono::DateTime<chrono::Utc>,
+    pub last_used_at: chrono::DateTime<chrono::Utc>,
+    pub expires_at: chrono::DateTime<chrono::Utc>,
+}
+
+/// The refresh token a session currently accepts.
+#[derive(Debug, Clone)]
+pub struct SessionToken {
+    pub session_id: String,
+    pub token_id: String,
+}
+
+#[derive(Clone)]
+pub struct SessionService {
+    db_pool: Arc<Pool>,
+    config: Config,
+}
+
+impl SessionService {
+    pub fn new(db_pool: Arc<Pool>, config: Config) -> Self {
+        Self { db_pool, config }
+    }
+
+    /// Start a session for a fresh login.
+    pub async fn start_session(
+        &self,
+        user_id: &str,
+        device: Option<&str>,
+    ) -> Result<SessionToken, ApiError> {
+        let client = self.db_pool.get().await.map_err(|e| {
+            error!(error = %e, "DB pool error");
+            ApiError::InternalServerError
+        })?;
+
+        let device = device.map(|d| d.chars().take(MAX_DEVICE_LEN).collect::<String>());
+        let lifetime = self.config.jwt.refresh_ttl();
+        let expires_at = chrono::Utc::now() + lifetime;
+
+        let row = client
+            .query_one(
+                r#"
+                INSERT INTO auth_sessions (user_id, device, expires_at)
+                VALUES ($1, $2, $3)
+                RETURNING id::text AS id, current_token_id::text AS token_id
+                "#,
+                &[&user_id, &device, &expires_at],
+            )
+            .await
+            .map_err(|e| {
+                error!(error = %e, "Failed to create session");
+                ApiError::InternalServerError
+            })?;
+
+        Ok(SessionToken {
+            session_id: row.get("id"),
+            token_id: row.get("token_id"),
+        })
+    }
+
+    /// Exchange the session's current refresh token for a new one.
+    ///
+    /// Presenting a token that has already been rotated out means it leaked,
+    /// so the whole session is revoked.
+    pub async fn rotate(
+        &self,
+        user_id: &str,
+        session_id: &str,
+        token_id: &str,
+    ) -> Result<SessionToken, ApiError> {
+        let client = self.db_pool.get().await.map_err(|e| {
+            error!(error = %e, "DB pool error");
+            ApiError::InternalServerError
+        })?;
+
+        let lifetime = self.config.jwt.refresh_ttl();
+        let expires_at = chrono::Utc::now() + lifetime;
+
+        let rotated = client
+            .query_opt(
+                r#"
+                UPDATE auth_sessions
+                SET current_token_id = gen_random_uuid(),
+                    last_used_at = NOW(),
+                    expires_at = $4
+                WHERE id = $1::text::uuid
+                  AND user_id = $2
+                  AND current_token_id = $3::text::uuid
+                  AND revoked_at IS NULL
+                  AND expires_at > NOW()
+                RETURNING current_token_id::text AS token_id
+                "#,
+                &[&session_id, &user_id, &token_id, &expires_at],
+            )
+            .await
+            .map_err(|e| {
+                error!(error = %e, "Failed to rotate session token");
+                ApiError::InternalServerError
+            })?;
+
+        if let Some(row) = rotated {
+            return Ok(SessionToken {
+                session_id: session_id.to_string(),
+                token_id: row.get("token_id"),
+            });
+        }
+
+        let reused = client
+            .execute(
+                r#"
+                UPDATE auth_sessions
+                SET revoked_at = NOW()
+                WHERE id = $1::text::uuid
+                  AND user_id = $2
+                  AND current_token_id <> $3::text::uuid
+                  AND revoked_at IS NULL
+                "#,
+                &[&session_id, &user_id, &token_id],
+            )
+            .await
+            .map_err(|e| {
+                error!(error = %e, "Failed to revoke reused session");
+                ApiError::InternalServerError
+            })?;
+        if reused > 0 {
+            warn!(
+                session_id,
+                user_id, "Rotated-out refresh token replayed — session revoked"
+            );
+        }
+
+        Err(ApiError::Authentication(
+            "Session has been revoked or has expired".to_string(),
+        ))
+    }
+
+    /// Active sessions for a user, most recently used first.
+    pub async fn list_sessions(&self, user_id: &str) -> Result<Vec<SessionRecord>, ApiError> {
+        let client = self.db_pool.get().await.map_err(|e| {
+            error!(error = %e, "DB pool error");
+            ApiError::InternalServerError
+        })?;
+
+        let rows = client
+            .query(
+                r#"
+                SELECT id::text AS id, user_id, device, created_at, last_used_at, expires_at
+                FROM auth_sessions
+                WHERE user_id = $1 AND revoked_at IS NULL AND expires_at > NOW()
+                ORDER BY last_used_at DESC
+                "#,
+                &[&user_id],
+            )
+            .await
+            .map_err(|e| {
+                error!(error = %e, "Failed to list sessions");
+                ApiError::InternalServerError
+            })?;
+
+        Ok(rows
+            .iter()
+            .map(|row| SessionRecord {
+                id: row.get("id"),
+                user_id: row.get("user_id"),
+                device: row.get("device"),
+                issued_at: row.get("created_at"),
+                last_used_at: row.get("last_used_at"),
+                expires_at: row.get("expires_at"),
+            })
+            .collect())
+    }
+
+    /// Revoke a session. With `owner`, only that user's session matches;
+    /// anything else is reported as not found.
+    pub async fn revoke_session(
+        &self,
+        session_id: &str,
+        owner: Option<&str>,
+    ) -> Result<(), ApiError> {
+        let client = self.db_pool.get().await.map_err(|e| {
+            error!(error = %e, "DB pool error");
+            ApiError::InternalServerError
+        })?;
+
+        let revoked = client
+            .execute(
+                r#"
+                UPDATE auth_sessions
+                SET revoked_at = NOW()
+                WHERE id = $1::text::uuid
+                  AND ($2::text IS NULL OR user_id = $2)
+                  AND revoked_at IS NULL
+                "#,
+                &[&session_id, &owner],
+            )
+            .await
+            .map_err(|e| {
+                error!(error = %e, "Failed to revoke session");
+                ApiError::InternalServerError
+            })?;
+
+        if revoked == 0 {
+            return Err(ApiError::NotFound(format!(
+                "Session {} not found",
+                session_id
+            )));
+        }
+
+        info!(session_id, "Session revoked");
+        Ok(())
+    }
+}
@@ -2,39 +2,446 @@ use axum::async_trait;
 
 use crate::{
     api_error::ApiError,
+    assets::STELLAR_DECIMALS,
     config::Config,
-    models::{BuildTransactionDto, SignedTransactionResponse, TransactionStatus},
+    http_client,
+    models::{BuildTransactionDto, FeeStrategy, SignedTransactionResponse, TransactionStatus},
+    service::soroban_xdr,
 };
 use base64::{engine::general_purpose, Engine as _};
+use serde::Deserialize;
 use serde_json::json;
-use std::sync::Arc;
+use std::sync::{
+    atomic::{AtomicUsize, Ordering},
+    Arc,
+};
+use tracing::warn;
 
 // Mocking Stellar SDK types for now as we don't have the full crate docs loaded
 // In a real scenario, these would be imports from a Stellar SDK/crate
 pub struct StellarClient {
     pub network_passphrase: String,
     pub rpc_url: String,
+    pub horizon_url: String,
+    http: reqwest::Client,
+}
+
+/// On-chain outcome of a submitted transaction, per RPC `getTransaction`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OnChainStatus {
+    Success,
+    Failed,
+    /// Not yet in a ledger (or expired from the RPC's retention window).
+    NotFound,
+}
+
+/// Why `sendTransaction` didn't accept an envelope.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SubmitError {
+    /// The network refused the transaction (`ERROR`), with its result XDR.
+    /// Resubmitting the same envelope won't help.
+    Rejected(String),
+    /// The node was busy (`TRY_AGAIN_LATER`) or couldn't be reached; the
+    /// envelope may still be accepted later.
+    Unavailable(String),
+}
+
+/// A contract event from RPC `getEvents`, with topics and value as
+/// JSON-encoded `ScVal`s.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ContractEvent {
+    pub id: String,
+    pub ledger: u32,
+    pub topics: Vec<serde_json::Value>,
+    pub value: serde_json::Value,
+}
+
+/// Where a `getEvents` page starts: a ledger, or a previous page's cursor.
+#[derive(Debug, Clone)]
+pub enum EventsFrom {
+    Ledger(u32),
+    Cursor(String),
+}
+
+#[derive(Debug, Clone)]
+pub struct EventsPage {
+    pub events: Vec<ContractEvent>,
+    pub latest_ledger: u32,
+    pub cursor: Option<String>,
+}
+
+/// Parse a Horizon balance such as `"12.5000000"` into stroops.
+fn parse_stroops(balance: &str) -> Option<i64> {
+    let (whole, fraction) = balance.split_once('.').unwrap_or((balance, ""));
+    let decimals = STELLAR_DECIMALS as usize;
+    if fraction.len() > decimals || !fraction.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    let fraction: i64 = format!("{:0<width$}", fraction, width = decimals)
+        .parse()
+        .ok()?;
+    whole
+        .parse::<i64>()
+        .ok()?
+        .checked_mul(10i64.pow(STELLAR_DECIMALS))?
+        .checked_add(fraction)
+}
+
+/// Balance of `asset` in a Horizon account record, in stroops. `asset` is
+/// `XLM` or `CODE[:ISSUER]`; `None` means the account holds no trustline
+/// for it.
+pub fn account_balance(account: &serde_json::Value, asset: &str) -> Result<Option<i64>, String> {
+    let (code, issuer) = match asset.split_once(':') {
+        Some((code, issuer)) => (code, Some(issuer)),
+        None => (asset, None),
+    };
+    let native = code.eq_ignore_ascii_case("XLM") || code.eq_ignore_ascii_case("native");
+
+    let entry = account["balances"]
+        .as_array()
+        .ok_or_else(|| "account record has no balances".to_string())?
+        .iter()
+        .find(|entry| {
+            if native {
+                return entry["asset_type"] == "native";
+            }
+            entry["asset_code"].as_str() == Some(code)
+                && issuer.is_none_or(|issuer| entry["asset_issuer"].as_str() == Some(issuer))
+        });
+
+    entry
+        .map(|entry| {
+            entry["balance"]
+                .as_str()
+                .and_then(parse_stroops)
+                .ok_or_else(|| format!("invalid {} balance {}", asset, entry["balance"]))
+        })
+        .transpose()
 }
 
 impl StellarClient {
-    pub fn new(network_passphrase: String, rpc_url: String) -> Self {
+    pub fn new(
+        network_passphrase: String,
+        rpc_url: String,
+        horizon_url: String,
+        http: reqwest::Client,
+    ) -> Self {
         Self {
             network_passphrase,
             rpc_url,
+            horizon_url,
+            http,
+        }
+    }
+
+    /// Submit a signed envelope via RPC `sendTransaction`, returning its hash.
+    pub async fn submit_transaction(&self, tx_envelope: &str) -> Result<String, SubmitError> {
+        let result = self
+            .rpc("sendTransaction", json!({ "transaction": tx_envelope }))
+            .await
+            .map_err(SubmitError::Unavailable)?;
+
+        match result["status"].as_str() {
+            Some("PENDING") | Some("DUPLICATE") => {
+                result["hash"].as_str().map(str::to_string).ok_or_else(|| {
+                    SubmitError::Unavailable("sendTransaction returned no hash".to_string())
+                })
+            }
+            Some("ERROR") => Err(SubmitError::Rejected(
+                result["errorResultXdr"]
+                    .as_str()
+                    .unwrap_or("ERROR")
+                    .to_string(),
+            )),
+            Some(status) => Err(SubmitError::Unavailable(format!(
+                "sendTransaction returned status {}",
+                status
+            ))),
+            None => Err(SubmitError::Unavailable(
+                "sendTransaction returned no status".to_string(),
+            )),
+        }
+    }
+
+    /// Look up a submitted transaction via RPC `getTransaction`.
+    pub async fn get_transaction(&self, tx_hash: &str) -> Result<OnChainStatus, String> {
+        let result = self
+            .rpc("getTransaction", json!({ "hash": tx_hash }))
+            .await?;
+
+        match result["status"].as_str() {
+            Some("SUCCESS") => Ok(OnChainStatus::Success),
+            Some("FAILED") => Ok(OnChainStatus::Failed),
+            Some("NOT_FOUND") => Ok(OnChainStatus::NotFound),
+            other => Err(format!("unexpected getTransaction status {:?}", other)),
+        }
+    }
+
+    /// Sequence of the latest ledger known to the RPC node.
+    pub async fn get_latest_ledger(&self) -> Result<u32, String> {
+        let result = self.rpc("getLatestLedger", json!({})).await?;
+        result["sequence"]
+            .as_u64()
+            .map(|sequence| sequence as u32)
+            .ok_or_else(|| "getLatestLedger returned no sequence".to_string())
+    }
+
+    /// Fetch one page of a contract's events via RPC `getEvents`.
+    pub async fn get_events(
+        &self,
+        contract_id: &str,
+        from: &EventsFrom,
+        limit: u32,
+    ) -> Result<EventsPage, String> {
+        let mut params = json!({
+            "filters": [{ "type": "contract", "contractIds": [contract_id] }],
+            "pagination": { "limit": limit },
+            "xdrFormat": "json",
+        });
+        match from {
+            EventsFrom::Ledger(ledger) => params["startLedger"] = json!(ledger),
+            EventsFrom::Cursor(cursor) => params["pagination"]["cursor"] = json!(cursor),
+        }
+
+        let result = self.rpc("getEvents", params).await?;
+
+        let events = result["events"]
+            .as_array()
+            .map(Vec::as_slice)
+            .unwrap_or_default()
+            .iter()
+            .map(|event| ContractEvent {
+                id: event["id"].as_str().unwrap_or_default().to_string(),
+                ledger: event["ledger"].as_u64().unwrap_or_default() as u32,
+                topics: event["topicJson"].as_array().cloned().unwrap_or_default(),
+                value: event["valueJson"].clone(),
+            })
+            .collect();
+
+        Ok(EventsPage {
+            events,
+            latest_ledger: result["latestLedger"]
+                .as_u64()
+                .ok_or_else(|| "getEvents returned no latestLedger".to_string())?
+                as u32,
+            cursor: result["cursor"].as_str().map(str::to_string),
+        })
+    }
+
+    /// Simulate a transaction envelope via RPC `simulateTransaction` and
+    /// return its first result (a base64 `ScVal`). Nothing is submitted.
+    pub async fn simulate(&self, transaction: &str) -> Result<String, String> {
+        let result = self
+            .rpc("simulateTransaction", json!({ "transaction": transaction }))
+            .await?;
+
+        if let Some(error) = result["error"].as_str() {
+            return Err(format!("simulation failed: {}", error));
+        }
+        result["results"][0]["xdr"]
+            .as_str()
+            .map(str::to_string)
+            .ok_or_else(|| "simulation returned no result".to_string())
+    }
+
+    /// Simulate a contract invocation via RPC `simulateTransaction` for the
+    /// footprint, fee and authorization it needs to be submitted.
+    pub async fn simulate_invocation(
+        &self,
+        transaction: &str,
+    ) -> Result<soroban_xdr::Simulation, String> {
+        let result = self
+            .rpc("simulateTransaction", json!({ "transaction": transaction }))
+            .await?;
+
+        if let Some(error) = result["error"].as_str() {
+            return Err(format!("simulation failed: {}", error));
+        }
+        let transaction_data = result["transactionData"]
+            .as_str()
+            .ok_or("simulation returned no transaction data")?
+            .to_string();
+        // RPC reports the fee as a decimal string
+        let min_resource_fee = result["minResourceFee"]
+            .as_str()
+            .and_then(|fee| fee.parse().ok())
+            .ok_or("simulation returned no resource fee")?;
+        let auth = result["results"][0]["auth"]
+            .as_array()
+            .map(|entries| {
+                entries
+                    .iter()
+                    .filter_map(|entry| entry.as_str().map(str::to_string))
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        Ok(soroban_xdr::Simulation {
+            transaction_data,
+            min_resource_fee,
+            auth,
+        })
+    }
+
+    /// On-chain balance of `asset` held by `address`, in stroops, from
+    /// Horizon. `None` when the account doesn't exist or has no trustline
+    /// for the asset.
+    pub async fn get_account_balance(
+        &self,
+        address: &str,
+        asset: &str,
+    ) -> Result<Option<i64>, String> {
+        let url = format!(
+            "{}/accounts/{}",
+            self.horizon_url.trim_end_matches('/'),
+            address
+        );
+        let response = self
+            .http
+            .get(&url)
+            .send()
+            .await
+            .map_err(|e| format!("account request failed: {}", e))?;
+
+        if response.status() == reqwest::StatusCode::NOT_FOUND {
+            return Ok(None);
+        }
+        if !response.status().is_success() {
+            return Err(format!("account request returned {}", response.status()));
         }
+        let account: serde_json::Value = response
+            .json()
+            .await
+            .map_err(|e| format!("account request returned invalid JSON: {}", e))?;
+
+        account_balance(&account, asset)
+    }
+
+    /// Current sequence number of `address`, from Horizon.
+    pub async fn get_account_sequence(&self, address: &str) -> Result<i64, String> {
+        let url = format!(
+            "{}/accounts/{}",
+            self.horizon_url.trim_end_matches('/'),
+            address
+        );
+        let response = self
+            .http
+            .get(&url)
+            .send()
+            .await
+            .map_err(|e| format!("account request failed: {}", e))?;
+
+        if !response.status().is_success() {
+            return Err(format!("account request returned {}", response.status()));
+        }
+        let account: serde_json::Value = response
+            .json()
+            .await
+            .map_err(|e| format!("account request returned invalid JSON: {}", e))?;
+
+        account["sequence"]
+            .as_str()
+            .and_then(|sequence| sequence.parse().ok())
+            .ok_or_else(|| format!("account {} has no sequence", address))
     }
 
-    pub async fn submit_transaction(&self, _tx_envelope: &str) -> Result<String, String> {
-        // Mock submission
-        Ok("mock_tx_hash".to_string())
+    /// Make a JSON-RPC call and return its `result`.
+    async fn rpc(
+        &self,
+        method: &str,
+        params: serde_json::Value,
+    ) -> Result<serde_json::Value, String> {
+        let body = json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": method,
+            "params": params,
+        });
+
+        let response: serde_json::Value = self
+            .http
+            .post(&self.rpc_url)
+            .json(&body)
+            .send()
+            .await
+            .map_err(|e| format!("{} request failed: {}", method, e))?
+            .json()
+            .await
+            .map_err(|e| format!("{} returned invalid JSON: {}", method, e))?;
+
+        if let Some(error) = response.get("error") {
+            return Err(format!("{} failed: {}", method, error));
+        }
+        response
+            .get("result")
+            .cloned()
+            .ok_or_else(|| format!("{} returned no result", method))
     }
 }
 
+/// Body of a (mock) payment envelope.
+fn payment_payload(
+    from: &str,
+    to: &str,
+    asset: &str,
+    amount: i64,
+    memo: Option<&str>,
+) -> serde_json::Value {
+    json!({
+        "type": "payment",
+        "from": from,
+        "to": to,
+        "asset": asset,
+        "amount": amount,
+        "memo": memo.unwrap_or("")
+    })
+}
+
+/// What a payment envelope pays, to check a signed envelope against.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct PaymentTerms {
+    pub from: String,
+    pub to: String,
+    pub asset: String,
+    pub amount: i64,
+}
+
+/// The terms of a (mock) payment envelope, signed or not. Signatures follow
+/// the base64 body after a `_`, as [`CustodialSigner`] appends them.
+pub fn payment_terms(envelope: &str) -> Result<PaymentTerms, ApiError> {
+    let invalid = || ApiError::Validation("Invalid payment envelope".to_string());
+    let body = envelope.split('_').next().unwrap_or_default();
+    let decoded = general_purpose::STANDARD
+        .decode(body)
+        .map_err(|_| invalid())?;
+    serde_json::from_slice(&decoded).map_err(|_| invalid())
+}
+
+/// Base64 of a (mock) payment envelope.
+fn encode_payment(from: &str, to: &str, asset: &str, amount: i64, memo: Option<&str>) -> String {
+    general_purpose::STANDARD.encode(payment_payload(from, to, asset, amount, memo).to_string())
+}
+
+/// A payment envelope built for a fee strategy.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PaymentEnvelope {
+    /// Base64 envelope, awaiting the sender's signature.
+    pub xdr: String,
+    /// Whether `xdr` already carries a fee payer's signature.
+    pub sponsored: bool,
+}
+
+/// Delay between checks that a contract invocation has landed.
+const CONTRACT_CONFIRMATION_INTERVAL: std::time::Duration = std::time::Duration::from_secs(2);
+/// Checks before a contract invocation is reported unconfirmed. Kept well
+/// inside the request timeout, since admin calls wait for confirmation.
+pub const CONTRACT_CONFIRMATION_POLLS: u32 = 10;
+
 #[derive(Clone)]
 pub struct SorobanService {
     config: Config,
     client: Arc<StellarClient>,
-    fee_payer_signer: Option<CustodialSigner>,
+    fee_payers: FeePayerPool,
 }
 
 #[async_trait]
@@ -67,23 +474,84 @@ impl Signer for CustodialSigner {
     }
 }
 
+/// Estimated cost of a payment, or why simulating it failed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PaymentSimulation {
+    pub fee: Option<u32>,
+    pub footprint: Option<u32>,
+    pub error: Option<String>,
+}
+
+/// Fee-payer accounts used round-robin, so sponsored transactions don't all
+/// queue behind one account's sequence number.
+#[derive(Clone)]
+pub struct FeePayerPool {
+    signers: Vec<Arc<dyn Signer + Send + Sync>>,
+    next: Arc<AtomicUsize>,
+}
+
+impl FeePayerPool {
+    pub fn new(signers: Vec<Arc<dyn Signer + Send + Sync>>) -> Self {
+        Self {
+            signers,
+            next: Arc::new(AtomicUsize::new(0)),
+        }
+    }
+
+    pub fn from_secrets(secrets: Vec<String>) -> Self {
+        Self::new(
+            secrets
+                .into_iter()
+                .map(|secret| {
+                    Arc::new(CustodialSigner::new(secret)) as Arc<dyn Signer + Send + Sync>
+                })
+                .collect(),
+        )
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.signers.is_empty()
+    }
+
+    /// Sign with the next signer in rotation.
+    ///
+    /// Signing never reaches the network, so there is nothing to retry here:
+    /// a stale sequence number only shows up as a rejection on submission.
+    pub async fn sign(&self, tx_xdr: &str) -> Result<String, ApiError> {
+        if self.signers.is_empty() {
+            return Err(ApiError::Validation(
+                "Fee payer not configured on server".to_string(),
+            ));
+        }
+
+        let next = self.next.fetch_add(1, Ordering::Relaxed);
+        self.signers[next % self.signers.len()]
+            .sign_transaction(tx_xdr)
+            .await
+    }
+}
+
 impl SorobanService {
     pub fn new(config: Config) -> Self {
+        let http = http_client::build(&config.http_client);
+        Self::with_http_client(config, http)
+    }
+
+    /// Like `new`, but making RPC and Horizon calls through `http`.
+    pub fn with_http_client(config: Config, http: reqwest::Client) -> Self {
         let client = Arc::new(StellarClient::new(
             config.stellar_network.passphrase.clone(),
             config.stellar_network.rpc_url.clone(),
+            config.stellar_network.horizon_url.clone(),
+            http,
         ));
 
-        let fee_payer_signer = config
-            .stellar_network
-            .fee_payer_secret
-            .clone()
-            .map(|s| CustodialSigner::new(s));
+        let fee_payers = FeePayerPool::from_secrets(config.stellar_network.fee_payer_secret_list());
 
         Self {
             config,
             client,
-            fee_payer_signer,
+            fee_payers,
         }
     }
 
@@ -100,16 +568,163 @@ impl SorobanService {
                 tx_hash: hash,
                 status: TransactionStatus::PENDING,
             }),
-            Err(e) => Err(self.normalize_error(e)),
+            Err(SubmitError::Rejected(result)) => {
+                warn!(result, "Transaction rejected by the network");
+                Err(ApiError::TransactionRejected(
+                    "The network rejected the transaction".to_string(),
+                ))
+            }
+            Err(SubmitError::Unavailable(e)) => {
+                warn!(error = %e, "Transaction submission unavailable");
+                Err(ApiError::ServiceUnavailable(
+                    "The network did not accept the transaction yet; retry the submission"
+                        .to_string(),
+                ))
+            }
+        }
+    }
+
+    /// Fetch the on-chain status of a submitted transaction.
+    pub async fn get_transaction_status(&self, tx_hash: &str) -> Result<OnChainStatus, ApiError> {
+        self.client
+            .get_transaction(tx_hash)
+            .await
+            .map_err(|e| self.normalize_error(e))
+    }
+
+    pub async fn get_latest_ledger(&self) -> Result<u32, ApiError> {
+        self.client
+            .get_latest_ledger()
+            .await
+            .map_err(|e| self.normalize_error(e))
+    }
+
+    /// On-chain balance of `asset` held by `address`, in stroops.
+    pub async fn get_account_balance(
+        &self,
+        address: &str,
+        asset: &str,
+    ) -> Result<Option<i64>, ApiError> {
+        self.client
+            .get_account_balance(address, asset)
+            .await
+            .map_err(|e| self.normalize_error(e))
+    }
+
+    /// Call a read-only contract function by simulating it, returning its
+    /// result as a JSON-encoded `ScVal`. Nothing is signed or submitted.
+    pub async fn call_contract(
+        &self,
+        contract_id: &str,
+        method: &str,
+        args: Vec<serde_json::Value>,
+    ) -> Result<serde_json::Value, ApiError> {
+        let transaction = self
+            .build_transaction(BuildTransactionDto {
+                source_account: soroban_xdr::SIMULATION_SOURCE_ACCOUNT.to_string(),
+                sequence: 0,
+                contract_id: contract_id.to_string(),
+                method: method.to_string(),
+                args,
+            })
+            .await?;
+
+        let result = self
+            .client
+            .simulate(&transaction)
+            .await
+            .map_err(|e| self.normalize_error(format!("{} {}", method, e)))?;
+        soroban_xdr::decode_result(&result)
+    }
+
+    /// Invoke a contract function as `source_account`, whose secret seed is
+    /// `source_secret`, returning the transaction hash once it has succeeded
+    /// on-chain.
+    ///
+    /// The invocation is simulated for its footprint, fee and authorization
+    /// before being signed and submitted. A transaction that fails on-chain
+    /// is an error; one not yet in a ledger after
+    /// [`CONTRACT_CONFIRMATION_POLLS`] checks is `503`.
+    pub async fn invoke_contract(
+        &self,
+        source_account: &str,
+        source_secret: &str,
+        contract_id: &str,
+        method: &str,
+        args: Vec<serde_json::Value>,
+    ) -> Result<String, ApiError> {
+        let sequence = self
+            .client
+            .get_account_sequence(source_account)
+            .await
+            .map_err(|e| self.normalize_error(e))?;
+        let transaction = self
+            .build_transaction(BuildTransactionDto {
+                source_account: source_account.to_string(),
+                sequence: sequence + 1,
+                contract_id: contract_id.to_string(),
+                method: method.to_string(),
+                args,
+            })
+            .await?;
+
+        let simulation = self
+            .client
+            .simulate_invocation(&transaction)
+            .await
+            .map_err(|e| self.normalize_error(format!("{} {}", method, e)))?;
+        let assembled = soroban_xdr::assemble(&transaction, &simulation)?;
+        let signed = soroban_xdr::sign(
+            &assembled,
+            &self.config.stellar_network.passphrase,
+            source_secret,
+        )?;
+
+        let tx_hash = self.submit_transaction(signed).await?.tx_hash;
+        self.await_confirmation(&tx_hash).await?;
+        Ok(tx_hash)
+    }
+
+    /// Poll `getTransaction` until `tx_hash` is in a ledger.
+    async fn await_confirmation(&self, tx_hash: &str) -> Result<(), ApiError> {
+        for _ in 0..CONTRACT_CONFIRMATION_POLLS {
+            match self.get_transaction_status(tx_hash).await? {
+                OnChainStatus::Success => return Ok(()),
+                OnChainStatus::Failed => {
+                    return Err(ApiError::Stellar(format!(
+                        "Transaction {} failed on-chain",
+                        tx_hash
+                    )))
+                }
+                OnChainStatus::NotFound => tokio::time::sleep(CONTRACT_CONFIRMATION_INTERVAL).await,
+            }
         }
+
+        Err(ApiError::ServiceUnavailable(format!(
+            "Transaction {} is not confirmed yet",
+            tx_hash
+        )))
+    }
+
+    pub async fn get_contract_events(
+        &self,
+        contract_id: &str,
+        from: &EventsFrom,
+        limit: u32,
+    ) -> Result<EventsPage, ApiError> {
+        self.client
+            .get_events(contract_id, from, limit)
+            .await
+            .map_err(|e| self.normalize_error(e))
     }
 
-    fn normalize_error(&self, _: String) -> ApiError {
+    fn normalize_error(&self, error: String) -> ApiError {
         // Normalize Soroban/Stellar errors into ApiError
-        ApiError::InternalServerError
+        ApiError::Stellar(error)
     }
 
     // Validate asset strings. Accepts "XLM" for native, or "CODE:ISSUER" where ISSUER is a Stellar address
+    // and, when `stellar.accepted_assets` lists any, the pair is on that list
     pub fn validate_asset(&self, asset: &str) -> Result<(), ApiError> {
         if asset == "XLM" {
             return Ok(());
@@ -129,6 +744,12 @@ impl SorobanService {
                     .to_string(),
             ));
         }
+        if !self.config.stellar_network.accepts_asset(code, issuer) {
+            return Err(ApiError::Validation(format!(
+                "Asset {} is not accepted; its code and issuer are not on the allowlist",
+                asset
+            )));
+        }
         Ok(())
     }
 
@@ -144,18 +765,55 @@ impl SorobanService {
         // Validate asset
         self.validate_asset(asset)?;
 
-        let payload = json!({
-            "type": "payment",
-            "from": from,
-            "to": to,
-            "asset": asset,
-            "amount": amount,
-            "memo": memo.unwrap_or("")
-        });
+        Ok(encode_payment(from, to, asset, amount, memo))
+    }
+
+    /// Build a payment whose fee is paid as `strategy` says: signed by a fee
+    /// payer when `Sponsored`, otherwise unsigned with the sender or the
+    /// merchant's vault as fee source.
+    pub async fn build_payment_envelope(
+        &self,
+        from: &str,
+        to: &str,
+        asset: &str,
+        amount: i64,
+        memo: Option<&str>,
+        strategy: FeeStrategy,
+    ) -> Result<PaymentEnvelope, ApiError> {
+        self.validate_asset(asset)?;
+        let mut payload = payment_payload(from, to, asset, amount, memo);
+
+        let fee_source = match strategy {
+            FeeStrategy::Sponsored => {
+                let tx_xdr = general_purpose::STANDARD.encode(payload.to_string());
+                return Ok(PaymentEnvelope {
+                    xdr: self.sign_transaction_as_fee_payer(&tx_xdr).await?,
+                    sponsored: true,
+                });
+            }
+            FeeStrategy::SenderPays => from,
+            FeeStrategy::MerchantPays => to,
+        };
+        payload["fee_source"] = json!(fee_source);
 
-        let raw = payload.to_string();
-        let encoded = general_purpose::STANDARD.encode(raw.as_bytes());
-        Ok(encoded)
+        Ok(PaymentEnvelope {
+            xdr: general_purpose::STANDARD.encode(payload.to_string()),
+            sponsored: false,
+        })
+    }
+
+    /// Unsigned (mock) payment for a direct user-to-user transfer. Unlike
+    /// `build_payment_xdr`, the asset may be named by its code alone, as
+    /// transfers allow.
+    pub fn build_transfer_xdr(
+        &self,
+        from: &str,
+        to: &str,
+        asset: &str,
+        amount: i64,
+        memo: Option<&str>,
+    ) -> String {
+        encode_payment(from, to, asset, amount, memo)
     }
 
     // Simulate a transaction to estimate fee and footprint (mocked)
@@ -185,29 +843,316 @@ impl SorobanService {
         Ok((fee, footprint))
     }
 
+    /// Build a payment and simulate it without signing or submitting.
+    ///
+    /// Simulation failures are reported in the result rather than as an
+    /// error, so callers can show why a payment would fail.
+    pub async fn simulate_payment(
+        &self,
+        from: &str,
+        to: &str,
+        asset: &str,
+        amount: i64,
+        memo: Option<&str>,
+    ) -> Result<PaymentSimulation, ApiError> {
+        let tx_xdr = self
+            .build_payment_xdr(from, to, asset, amount, memo)
+            .await?;
+
+        Ok(match self.simulate_transaction(&tx_xdr).await {
+            Ok((fee, footprint)) => PaymentSimulation {
+                fee: Some(fee),
+                footprint: Some(footprint),
+                error: None,
+            },
+            Err(e) => PaymentSimulation {
+                fee: None,
+                footprint: None,
+                error: Some(e.to_string()),
+            },
+        })
+    }
+
     // Sign transaction as fee payer (fee sponsorship) using server-side signer
     pub async fn sign_transaction_as_fee_payer(
         &self,
         tx_xdr_base64: &str,
     ) -> Result<String, ApiError> {
-        if self.fee_payer_signer.is_none() {
-            return Err(ApiError::Validation(
-                "Fee payer not configured on server".to_string(),
-            ));
-        }
-        let signer = self.fee_payer_signer.as_ref().unwrap();
-        signer.sign_transaction(tx_xdr_base64).await
+        self.fee_payers.sign(tx_xdr_base64).await
     }
 }
 
 #[async_trait]
 impl TransactionBuilder for SorobanService {
     async fn build_transaction(&self, dto: BuildTransactionDto) -> Result<String, ApiError> {
-        // Mock transaction building for contract invocations
-        let tx_xdr = format!(
-            "mock_xdr_invoke_{}_{}_{:?}",
-            dto.contract_id, dto.method, dto.args
+        soroban_xdr::build_invocation(&dto)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Mutex;
+
+    /// Decoded body of a (mock) payment envelope.
+    fn payment_body(xdr: &str) -> serde_json::Value {
+        serde_json::from_slice(&general_purpose::STANDARD.decode(xdr).unwrap()).unwrap()
+    }
+
+    fn service_with_fee_payer() -> SorobanService {
+        let mut config = Config::default();
+        config.stellar_network.fee_payer_secret = Some("SFEEPAYER".to_string());
+        SorobanService::new(config)
+    }
+
+    #[tokio::test]
+    async fn sender_pays_envelope_is_unsigned() {
+        let envelope = service_with_fee_payer()
+            .build_payment_envelope(
+                "GSENDER",
+                "GVAULT",
+                "XLM",
+                10,
+                None,
+                FeeStrategy::SenderPays,
+            )
+            .await
+            .unwrap();
+
+        assert!(!envelope.sponsored);
+        let body = payment_body(&envelope.xdr);
+        assert_eq!(body["fee_source"], "GSENDER");
+        assert_eq!(body["amount"], 10);
+    }
+
+    #[tokio::test]
+    async fn merchant_pays_envelope_names_the_vault_as_fee_source() {
+        let envelope = service_with_fee_payer()
+            .build_payment_envelope(
+                "GSENDER",
+                "GVAULT",
+                "XLM",
+                10,
+                None,
+                FeeStrategy::MerchantPays,
+            )
+            .await
+            .unwrap();
+
+        assert!(!envelope.sponsored);
+        assert_eq!(payment_body(&envelope.xdr)["fee_source"], "GVAULT");
+    }
+
+    #[tokio::test]
+    async fn sponsored_envelope_is_signed_by_the_fee_payer() {
+        let soroban = service_with_fee_payer();
+        let unsigned = soroban
+            .build_payment_xdr("GSENDER", "GVAULT", "XLM", 10, None)
+            .await
+            .unwrap();
+
+        let envelope = soroban
+            .build_payment_envelope("GSENDER", "GVAULT", "XLM", 10, None, FeeStrategy::Sponsored)
+            .await
+            .unwrap();
+
+        assert!(envelope.sponsored);
+        assert_eq!(envelope.xdr, format!("{}_signed_by_custodial", unsigned));
+
+        // Without a fee payer there is nobody to sponsor the payment
+        let unsponsored = SorobanService::new(Config::default())
+            .build_payment_envelope("GSENDER", "GVAULT", "XLM", 10, None, FeeStrategy::Sponsored)
+            .await;
+        assert!(matches!(unsponsored, Err(ApiError::Validation(_))));
+    }
+
+    fn account(balances: serde_json::Value) -> serde_json::Value {
+        json!({ "id": "GACCOUNT", "balances": balances })
+    }
+
+    #[test]
+    fn account_balance_finds_native_and_issued_assets() {
+        let record = account(json!([
+            {
+                "balance": "25.5000000",
+                "asset_type": "credit_alphanum4",
+                "asset_code": "USDC",
+                "asset_issuer": "GISSUER"
+            },
+            { "balance": "1000.0000001", "asset_type": "native" }
+        ]));
+
+        assert_eq!(account_balance(&record, "XLM"), Ok(Some(10_000_000_001)));
+        assert_eq!(account_balance(&record, "USDC"), Ok(Some(255_000_000)));
+        assert_eq!(
+            account_balance(&record, "USDC:GISSUER"),
+            Ok(Some(255_000_000))
+        );
+        assert_eq!(account_balance(&record, "USDC:GOTHER"), Ok(None));
+        assert_eq!(account_balance(&record, "EURC"), Ok(None));
+    }
+
+    #[test]
+    fn account_balance_rejects_malformed_records() {
+        assert!(account_balance(&json!({}), "XLM").is_err());
+
+        let record = account(json!([{ "balance": "1.23456789", "asset_type": "native" }]));
+        assert!(account_balance(&record, "XLM").is_err());
+    }
+
+    /// Signer that tags output with its name and can be told to fail.
+    struct NamedSigner {
+        name: &'static str,
+        failing: bool,
+        calls: Arc<Mutex<Vec<&'static str>>>,
+    }
+
+    #[async_trait]
+    impl Signer for NamedSigner {
+        async fn sign_transaction(&self, tx_xdr: &str) -> Result<String, ApiError> {
+            self.calls.lock().unwrap().push(self.name);
+            if self.failing {
+                return Err(ApiError::InternalServerError);
+            }
+            Ok(format!("{}_signed_by_{}", tx_xdr, self.name))
+        }
+    }
+
+    fn pool_of(signers: &[(&'static str, bool)]) -> (FeePayerPool, Arc<Mutex<Vec<&'static str>>>) {
+        let calls = Arc::new(Mutex::new(Vec::new()));
+        let signers = signers
+            .iter()
+            .map(|&(name, failing)| {
+                Arc::new(NamedSigner {
+                    name,
+                    failing,
+                    calls: calls.clone(),
+                }) as Arc<dyn Signer + Send + Sync>
+            })
+            .collect();
+        (FeePayerPool::new(signers), calls)
+    }
+
+    #[tokio::test]
+    async fn consecutive_signs_rotate_across_keys() {
+        let (pool, _) = pool_of(&[("a", false), ("b", false), ("c", false)]);
+
+        let mut signed = Vec::new();
+        for _ in 0..4 {
+            signed.push(pool.sign("tx").await.unwrap());
+        }
+
+        assert_eq!(
+            signed,
+            [
+                "tx_signed_by_a",
+                "tx_signed_by_b",
+                "tx_signed_by_c",
+                "tx_signed_by_a"
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn signing_errors_are_returned_without_trying_other_keys() {
+        let (pool, calls) = pool_of(&[("a", true), ("b", false)]);
+
+        assert!(matches!(
+            pool.sign("tx").await.unwrap_err(),
+            ApiError::InternalServerError
+        ));
+        assert_eq!(*calls.lock().unwrap(), ["a"]);
+        // The failing key doesn't stall the rotation
+        assert_eq!(pool.sign("tx").await.unwrap(), "tx_signed_by_b");
+
+        let (empty, _) = pool_of(&[]);
+        assert!(matches!(
+            empty.sign("tx").await.unwrap_err(),
+            ApiError::Validation(_)
+        ));
+    }
+
+    #[tokio::test]
+    async fn simulating_native_payments_is_cheaper_than_issued_assets() {
+        let soroban = SorobanService::new(Config::default());
+        let usdc = "USDC:GA5ZSEJYB37JRC5AVCIA5MOP4RHTM335X2KGX3IHOJAPP5RE34K4KZVN";
+
+        let native = soroban
+            .simulate_payment("GFROM", "GVAULT", "XLM", 1_000_000, None)
+            .await
+            .unwrap();
+        let issued = soroban
+            .simulate_payment("GFROM", "GVAULT", usdc, 1_000_000, Some("order-1"))
+            .await
+            .unwrap();
+
+        assert_eq!(native.error, None);
+        assert_eq!(issued.error, None);
+        assert!(native.fee.unwrap() < issued.fee.unwrap());
+        assert_eq!(native.footprint, Some(1));
+        assert_eq!(issued.footprint, Some(2));
+    }
+
+    #[tokio::test]
+    async fn simulating_rejects_unknown_asset_format() {
+        let soroban = SorobanService::new(Config::default());
+
+        let err = soroban
+            .simulate_payment("GFROM", "GVAULT", "USDC", 1, None)
+            .await
+            .unwrap_err();
+        assert!(matches!(err, ApiError::Validation(_)));
+    }
+
+    #[test]
+    fn only_allowlisted_issuers_are_accepted() {
+        let trusted = format!("G{}", "A".repeat(55));
+        let untrusted = format!("G{}", "B".repeat(55));
+        let mut config = Config::default();
+        config.stellar_network.accepted_assets = vec![crate::config::AcceptedAsset {
+            code: "USDC".to_string(),
+            issuer: trusted.clone(),
+        }];
+        let soroban = SorobanService::new(config);
+
+        soroban.validate_asset("XLM").unwrap();
+        soroban
+            .validate_asset(&format!("USDC:{}", trusted))
+            .unwrap();
+
+        let err = soroban
+            .validate_asset(&format!("USDC:{}", untrusted))
+            .unwrap_err();
+        assert!(
+            matches!(&err, ApiError::Validation(msg) if msg.contains("not accepted")),
+            "{}",
+            err
+        );
+        assert!(soroban
+            .validate_asset(&format!("EURC:{}", trusted))
+            .is_err());
+
+        // Malformed assets still fail the format check first.
+        let err = soroban.validate_asset("USDC:").unwrap_err();
+        assert!(matches!(&err, ApiError::Validation(msg) if msg.contains("Invalid issued asset")));
+
+        // With nothing listed any well-formed asset passes.
+        SorobanService::new(Config::default())
+            .validate_asset(&format!("USDC:{}", untrusted))
+            .unwrap();
+    }
+
+    #[test]
+    fn secrets_are_collected_from_both_settings() {
+        let network = crate::config::StellarNetwork {
+            fee_payer_secret: Some("SPRIMARY".to_string()),
+            fee_payer_secrets: Some("SONE, STWO,,".to_string()),
+            ..Config::default().stellar_network
+        };
+
+        assert_eq!(
+            network.fee_payer_secret_list(),
+            ["SPRIMARY", "SONE", "STWO"]
         );
-        Ok(tx_xdr)
     }
 }
@@ -0,0 +1,507 @@
+//! XDR for Soroban contract invocations.
+//!
+//! Contract arguments and results cross the API as JSON-encoded `ScVal`s,
+//! the same form RPC returns with `xdrFormat: "json"`, e.g.
+//! `{"address": "G..."}`, `{"u32": 5}` or `{"bytes": "<hex>"}`.
+
+use std::str::FromStr;
+
+use ring::{
+    digest,
+    signature::{Ed25519KeyPair, KeyPair},
+};
+use serde_json::Value;
+use stellar_xdr::curr::{
+    AccountId, AlphaNum12, AlphaNum4, Asset, AssetCode12, AssetCode4, ContractIdPreimage,
+    DecoratedSignature, Hash, HashIdPreimage, HashIdPreimageContractId, HostFunction,
+    InvokeContractArgs, InvokeHostFunctionOp, Limits, Memo, MuxedAccount, Operation, OperationBody,
+    Preconditions, PublicKey, ReadXdr, ScAddress, ScSymbol, ScVal, SequenceNumber, Signature,
+    SignatureHint, SorobanAuthorizationEntry, SorobanTransactionData, Transaction,
+    TransactionEnvelope, TransactionExt, TransactionSignaturePayload,
+    TransactionSignaturePayloadTaggedTransaction, TransactionV1Envelope, Uint256, VecM, WriteXdr,
+};
+
+use crate::{api_error::ApiError, models::BuildTransactionDto};
+
+/// Inclusion fee of a built invocation, in stroops. Simulation adds the
+/// resource fee on top.
+pub const BASE_FEE: u32 = 100;
+
+/// Source of read-only simulations, which are never signed: the all-zero
+/// account key.
+pub const SIMULATION_SOURCE_ACCOUNT: &str =
+    "GAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAWHF";
+
+/// Base64 `TransactionEnvelope` invoking `dto.method` on `dto.contract_id`
+/// with `dto.args`, unsigned and ready for `simulateTransaction`.
+pub fn build_invocation(dto: &BuildTransactionDto) -> Result<String, ApiError> {
+    let source =
+        stellar_strkey::ed25519::PublicKey::from_string(&dto.source_account).map_err(|_| {
+            ApiError::Validation(format!("Invalid source account {}", dto.source_account))
+        })?;
+
+    let contract_address = match ScAddress::from_str(&dto.contract_id) {
+        Ok(address @ ScAddress::Contract(_)) => address,
+        _ => {
+            return Err(ApiError::Validation(format!(
+                "Invalid contract id {}",
+                dto.contract_id
+            )))
+        }
+    };
+
+    let function_name = dto
+        .method
+        .as_str()
+        .try_into()
+        .map(ScSymbol)
+        .map_err(|_| ApiError::Validation(format!("Invalid contract method {}", dto.method)))?;
+
+    let args = dto
+        .args
+        .iter()
+        .enumerate()
+        .map(|(i, arg)| {
+            serde_json::from_value::<ScVal>(arg.clone())
+                .map_err(|e| ApiError::Validation(format!("Invalid argument {}: {}", i, e)))
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let invoke = InvokeHostFunctionOp {
+        host_function: HostFunction::InvokeContract(InvokeContractArgs {
+            contract_address,
+            function_name,
+            args: args.try_into().map_err(xdr_error)?,
+        }),
+        auth: VecM::default(),
+    };
+    let tx = Transaction {
+        source_account: MuxedAccount::Ed25519(Uint256(source.0)),
+        fee: BASE_FEE,
+        seq_num: SequenceNumber(dto.sequence),
+        cond: Preconditions::None,
+        memo: Memo::None,
+        operations: vec![Operation {
+            source_account: None,
+            body: OperationBody::InvokeHostFunction(invoke),
+        }]
+        .try_into()
+        .map_err(xdr_error)?,
+        ext: TransactionExt::V0,
+    };
+
+    TransactionEnvelope::Tx(TransactionV1Envelope {
+        tx,
+        signatures: VecM::default(),
+    })
+    .to_xdr_base64(Limits::none())
+    .map_err(xdr_error)
+}
+
+/// Decode a simulated return value (a base64 `ScVal`, as in
+/// `simulateTransaction`'s `results[].xdr`) into its JSON form.
+pub fn decode_result(result_xdr: &str) -> Result<Value, ApiError> {
+    let value = ScVal::from_xdr_base64(result_xdr, Limits::none())
+        .map_err(|e| ApiError::Stellar(format!("Undecodable contract result: {}", e)))?;
+    serde_json::to_value(value).map_err(|e| ApiError::Stellar(e.to_string()))
+}
+
+/// What `simulateTransaction` says an invocation needs before it can be
+/// submitted.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Simulation {
+    /// Base64 `SorobanTransactionData`: the footprint and resources.
+    pub transaction_data: String,
+    /// Resource fee in stroops, on top of [`BASE_FEE`].
+    pub min_resource_fee: u32,
+    /// Base64 `SorobanAuthorizationEntry`s the contract asked for.
+    pub auth: Vec<String>,
+}
+
+/// Apply `simulation` to an invocation built by [`build_invocation`], so it
+/// carries its footprint, resource fee and authorization entries.
+pub fn assemble(envelope_xdr: &str, simulation: &Simulation) -> Result<String, ApiError> {
+    let mut envelope = decode_envelope(envelope_xdr)?;
+    let tx = &mut envelope.tx;
+
+    let data =
+        SorobanTransactionData::from_xdr_base64(&simulation.transaction_data, Limits::none())
+            .map_err(|e| ApiError::Stellar(format!("Undecodable simulation data: {}", e)))?;
+    let auth = simulation
+        .auth
+        .iter()
+        .map(|entry| SorobanAuthorizationEntry::from_xdr_base64(entry, Limits::none()))
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| ApiError::Stellar(format!("Undecodable simulation auth: {}", e)))?;
+
+    let mut operations = tx.operations.to_vec();
+    let [Operation {
+        body: OperationBody::InvokeHostFunction(invoke),
+        ..
+    }] = operations.as_mut_slice()
+    else {
+        return Err(ApiError::Stellar(
+            "Expected a single contract invocation".to_string(),
+        ));
+    };
+    invoke.auth = auth.try_into().map_err(xdr_error)?;
+
+    tx.operations = operations.try_into().map_err(xdr_error)?;
+    tx.fee = BASE_FEE
+        .checked_add(simulation.min_resource_fee)
+        .ok_or_else(|| ApiError::Stellar("Resource fee out of range".to_string()))?;
+    tx.ext = TransactionExt::V1(data);
+
+    TransactionEnvelope::Tx(envelope)
+        .to_xdr_base64(Limits::none())
+        .map_err(xdr_error)
+}
+
+/// Hash of the transaction in `envelope_xdr` on the network named by
+/// `network_passphrase`: what signers sign and RPC reports it by, in hex.
+pub fn transaction_hash(envelope_xdr: &str, network_passphrase: &str) -> Result<String, ApiError> {
+    let envelope = decode_envelope(envelope_xdr)?;
+    Ok(hex::encode(signature_payload_hash(
+        &envelope.tx,
+        network_passphrase,
+    )?))
+}
+
+/// Add a signature by the account whose secret seed (`S...`) is
+/// `secret_seed` to `envelope_xdr`, for the network named by
+/// `network_passphrase`.
+pub fn sign(
+    envelope_xdr: &str,
+    network_passphrase: &str,
+    secret_seed: &str,
+) -> Result<String, ApiError> {
+    let seed = stellar_strkey::ed25519::PrivateKey::from_string(secret_seed)
+        .map_err(|_| ApiError::Validation("Invalid signing key".to_string()))?;
+    let keypair = Ed25519KeyPair::from_seed_unchecked(&seed.0)
+        .map_err(|_| ApiError::Validation("Invalid signing key".to_string()))?;
+
+    let mut envelope = decode_envelope(envelope_xdr)?;
+    let hash = signature_payload_hash(&envelope.tx, network_passphrase)?;
+    let public = keypair.public_key().as_ref();
+
+    let mut signatures = envelope.signatures.to_vec();
+    signatures.push(DecoratedSignature {
+        hint: SignatureHint(public[public.len() - 4..].try_into().expect("4-byte hint")),
+        signature: Signature(
+            keypair
+                .sign(&hash)
+                .as_ref()
+                .to_vec()
+                .try_into()
+                .map_err(xdr_error)?,
+        ),
+    });
+    envelope.signatures = signatures.try_into().map_err(xdr_error)?;
+
+    TransactionEnvelope::Tx(envelope)
+        .to_xdr_base64(Limits::none())
+        .map_err(xdr_error)
+}
+
+/// Contract id (`C...`) of the Stellar Asset Contract for `asset` on the
+/// network named by `network_passphrase`. `asset` is `XLM`, `CODE:ISSUER`
+/// or already a contract id; a bare `CODE` names no particular asset and
+/// gives `None`.
+pub fn asset_contract_id(asset: &str, network_passphrase: &str) -> Option<String> {
+    let asset = asset.trim();
+    if stellar_strkey::Contract::from_string(asset).is_ok() {
+        return Some(asset.to_string());
+    }
+
+    let asset = if asset.eq_ignore_ascii_case("XLM") || asset.eq_ignore_ascii_case("native") {
+        Asset::Native
+    } else {
+        let (code, issuer) = asset.split_once(':')?;
+        let issuer = stellar_strkey::ed25519::PublicKey::from_string(issuer).ok()?;
+        let issuer = AccountId(PublicKey::PublicKeyTypeEd25519(Uint256(issuer.0)));
+        match code.len() {
+            1..=4 => {
+                let mut bytes = [0u8; 4];
+                bytes[..code.len()].copy_from_slice(code.as_bytes());
+                Asset::CreditAlphanum4(AlphaNum4 {
+                    asset_code: AssetCode4(bytes),
+                    issuer,
+                })
+            }
+            5..=12 => {
+                let mut bytes = [0u8; 12];
+                bytes[..code.len()].copy_from_slice(code.as_bytes());
+                Asset::CreditAlphanum12(AlphaNum12 {
+                    asset_code: AssetCode12(bytes),
+                    issuer,
+                })
+            }
+            _ => return None,
+        }
+    };
+
+    let preimage = HashIdPreimage::ContractId(HashIdPreimageContractId {
+        network_id: network_id(network_passphrase),
+        contract_id_preimage: ContractIdPreimage::Asset(asset),
+    });
+    let bytes = preimage.to_xdr(Limits::none()).ok()?;
+    let id = digest::digest(&digest::SHA256, &bytes);
+    Some(stellar_strkey::Contract(id.as_ref().try_into().expect("SHA-256 is 32 bytes")).to_string())
+}
+
+fn network_id(network_passphrase: &str) -> Hash {
+    let id = digest::digest(&digest::SHA256, network_passphrase.as_bytes());
+    Hash(id.as_ref().try_into().expect("SHA-256 is 32 bytes"))
+}
+
+fn decode_envelope(envelope_xdr: &str) -> Result<TransactionV1Envelope, ApiError> {
+    match TransactionEnvelope::from_xdr_base64(envelope_xdr, Limits::none()) {
+        Ok(TransactionEnvelope::Tx(envelope)) => Ok(envelope),
+        _ => Err(ApiError::Validation(
+            "Expected a v1 transaction envelope".to_string(),
+        )),
+    }
+}
+
+fn signature_payload_hash(
+    tx: &Transaction,
+    network_passphrase: &str,
+) -> Result<[u8; 32], ApiError> {
+    let payload = TransactionSignaturePayload {
+        network_id: network_id(network_passphrase),
+        tagged_transaction: TransactionSignaturePayloadTaggedTransaction::Tx(tx.clone()),
+    };
+    let bytes = payload.to_xdr(Limits::none()).map_err(xdr_error)?;
+
+    Ok(digest::digest(&digest::SHA256, &bytes)
+        .as_ref()
+        .try_into()
+        .expect("SHA-256 is 32 bytes"))
+}
+
+fn xdr_error(e: stellar_xdr::curr::Error) -> ApiError {
+    ApiError::Stellar(format!("Failed to encode transaction: {}", e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ring::signature::{UnparsedPublicKey, ED25519};
+    use serde_json::json;
+    use stellar_xdr::curr::{
+        BytesM, ExtensionPoint, LedgerFootprint, SorobanAuthorizedFunction,
+        SorobanAuthorizedInvocation, SorobanCredentials, SorobanResources,
+    };
+
+    const PASSPHRASE: &str = "Test SDF Network ; September 2015";
+
+    fn account() -> String {
+        stellar_strkey::ed25519::PublicKey([7; 32]).to_string()
+    }
+
+    fn registry() -> String {
+        stellar_strkey::Contract([9; 32]).to_string()
+    }
+
+    fn resolve_user(user_id: &str) -> BuildTransactionDto {
+        BuildTransactionDto {
+            source_account: account(),
+            sequence: 42,
+            contract_id: registry(),
+            method: "resolve_user".to_string(),
+            args: vec![json!({ "bytes": hex::encode(user_id) })],
+        }
+    }
+
+    #[test]
+    fn builds_a_resolve_user_invocation() {
+        let envelope = build_invocation(&resolve_user("alice")).unwrap();
+
+        let TransactionEnvelope::Tx(TransactionV1Envelope { tx, signatures }) =
+            TransactionEnvelope::from_xdr_base64(envelope, Limits::none()).unwrap()
+        else {
+            panic!("expected a v1 envelope");
+        };
+        assert!(signatures.is_empty());
+        assert_eq!(tx.source_account, MuxedAccount::Ed25519(Uint256([7; 32])));
+        assert_eq!(tx.seq_num, SequenceNumber(42));
+        assert_eq!(tx.fee, BASE_FEE);
+
+        let [Operation {
+            body: OperationBody::InvokeHostFunction(op),
+            ..
+        }] = tx.operations.as_slice()
+        else {
+            panic!("expected one invoke operation");
+        };
+        let HostFunction::InvokeContract(call) = &op.host_function else {
+            panic!("expected a contract call");
+        };
+        assert_eq!(call.contract_address, ScAddress::Contract(Hash([9; 32])));
+        assert_eq!(call.function_name.0.to_utf8_string_lossy(), "resolve_user");
+        assert_eq!(
+            call.args.as_slice(),
+            [ScVal::Bytes(
+                BytesM::try_from(b"alice".to_vec()).unwrap().into()
+            )]
+        );
+    }
+
+    #[test]
+    fn decodes_an_address_result() {
+        let wallet = account();
+        let result = ScVal::Address(ScAddress::from_str(&wallet).unwrap())
+            .to_xdr_base64(Limits::none())
+            .unwrap();
+
+        assert_eq!(
+            decode_result(&result).unwrap(),
+            json!({ "address": wallet })
+        );
+        assert!(decode_result("not xdr").is_err());
+    }
+
+    #[test]
+    fn rejects_malformed_invocations() {
+        let mut dto = resolve_user("alice");
+        dto.contract_id = account();
+        assert!(matches!(
+            build_invocation(&dto),
+            Err(ApiError::Validation(msg)) if msg.contains("contract id")
+        ));
+
+        let mut dto = resolve_user("alice");
+        dto.source_account = "GNOTANACCOUNT".to_string();
+        assert!(build_invocation(&dto).is_err());
+
+        let mut dto = resolve_user("alice");
+        dto.method = "a method name longer than thirty-two chars".to_string();
+        assert!(build_invocation(&dto).is_err());
+
+        let mut dto = resolve_user("alice");
+        dto.args = vec![json!({ "user_id": "alice" })];
+        assert!(matches!(
+            build_invocation(&dto),
+            Err(ApiError::Validation(msg)) if msg.contains("argument 0")
+        ));
+    }
+
+    #[test]
+    fn simulation_source_is_a_valid_account() {
+        let mut dto = resolve_user("alice");
+        dto.source_account = SIMULATION_SOURCE_ACCOUNT.to_string();
+        build_invocation(&dto).unwrap();
+    }
+
+    fn simulation() -> Simulation {
+        let data = SorobanTransactionData {
+            ext: ExtensionPoint::V0,
+            resources: SorobanResources {
+                footprint: LedgerFootprint {
+                    read_only: VecM::default(),
+                    read_write: VecM::default(),
+                },
+                instructions: 1_000,
+                read_bytes: 10,
+                write_bytes: 10,
+            },
+            resource_fee: 5_000,
+        };
+        let auth = SorobanAuthorizationEntry {
+            credentials: SorobanCredentials::SourceAccount,
+            root_invocation: SorobanAuthorizedInvocation {
+                function: SorobanAuthorizedFunction::ContractFn(InvokeContractArgs {
+                    contract_address: ScAddress::Contract(Hash([9; 32])),
+                    function_name: ScSymbol("resolve_user".try_into().unwrap()),
+                    args: VecM::default(),
+                }),
+                sub_invocations: VecM::default(),
+            },
+        };
+
+        Simulation {
+            transaction_data: data.to_xdr_base64(Limits::none()).unwrap(),
+            min_resource_fee: 5_000,
+            auth: vec![auth.to_xdr_base64(Limits::none()).unwrap()],
+        }
+    }
+
+    #[test]
+    fn assembles_the_simulated_resources_fee_and_auth() {
+        let envelope = build_invocation(&resolve_user("alice")).unwrap();
+        let assembled = assemble(&envelope, &simulation()).unwrap();
+
+        let tx = decode_envelope(&assembled).unwrap().tx;
+        assert_eq!(tx.fee, BASE_FEE + 5_000);
+        let TransactionExt::V1(data) = &tx.ext else {
+            panic!("expected soroban transaction data");
+        };
+        assert_eq!(data.resource_fee, 5_000);
+        let OperationBody::InvokeHostFunction(op) = &tx.operations[0].body else {
+            panic!("expected an invoke operation");
+        };
+        assert_eq!(op.auth.len(), 1);
+
+        let mut bad = simulation();
+        bad.transaction_data = "not xdr".to_string();
+        assert!(assemble(&envelope, &bad).is_err());
+    }
+
+    #[test]
+    fn signs_the_network_payload_with_the_secret() {
+        let seed = [3u8; 32];
+        let keypair = Ed25519KeyPair::from_seed_unchecked(&seed).unwrap();
+        let secret = stellar_strkey::ed25519::PrivateKey(seed).to_string();
+
+        let envelope = build_invocation(&resolve_user("alice")).unwrap();
+        let signed = sign(&envelope, PASSPHRASE, &secret).unwrap();
+
+        let signed_envelope = decode_envelope(&signed).unwrap();
+        let [signature] = signed_envelope.signatures.as_slice() else {
+            panic!("expected one signature");
+        };
+        let public = keypair.public_key().as_ref();
+        assert_eq!(signature.hint.0, public[28..]);
+
+        // Signing doesn't change what is signed, so the hash is stable
+        let hash = transaction_hash(&signed, PASSPHRASE).unwrap();
+        assert_eq!(hash, transaction_hash(&envelope, PASSPHRASE).unwrap());
+        UnparsedPublicKey::new(&ED25519, public)
+            .verify(&hex::decode(&hash).unwrap(), signature.signature.as_slice())
+            .expect("signature verifies");
+
+        // A signature for another network doesn't
+        let other = transaction_hash(&signed, "Public Global Stellar Network ; September 2015");
+        assert_ne!(hash, other.unwrap());
+
+        assert!(sign(&envelope, PASSPHRASE, "SNOTASECRET").is_err());
+    }
+
+    #[test]
+    fn derives_asset_contract_ids() {
+        // The well-known native asset contract on testnet
+        assert_eq!(
+            asset_contract_id("XLM", PASSPHRASE).as_deref(),
+            Some("CDLZFC3SYJYDZT7K67VZ75HPJVIEUVNIXF47ZG2FB2RMQQVU2HHGCYSC")
+        );
+
+        let usdc = format!("USDC:{}", account());
+        let contract = asset_contract_id(&usdc, PASSPHRASE).unwrap();
+        assert!(contract.starts_with('C'));
+        assert_eq!(
+            asset_contract_id(&contract, PASSPHRASE),
+            Some(contract.clone())
+        );
+        assert_ne!(
+            asset_contract_id(&format!("EURC:{}", account()), PASSPHRASE),
+            Some(contract.clone())
+        );
+        assert_ne!(
+            asset_contract_id(&usdc, "Public Global Stellar Network ; September 2015"),
+            Some(contract)
+        );
+
+        assert_eq!(asset_contract_id("USDC", PASSPHRASE), None);
+        assert_eq!(asset_contract_id("USDC:GNOTANISSUER", PASSPHRASE), None);
+    }
+}
@@ -2,7 +2,9 @@ use std::path::PathBuf;
 use std::sync::Arc;
 
 use crate::config::{Config, StorageBackend};
-use crate::storage::{IpfsStorageAdapter, LocalStorageAdapter, S3StorageAdapter, StorageAdapter};
+use crate::storage::{
+    FileCipher, IpfsStorageAdapter, LocalStorageAdapter, S3StorageAdapter, StorageAdapter,
+};
 
 #[derive(Clone)]
 pub struct StorageService {
@@ -19,10 +21,11 @@ impl StorageService {
                     .clone()
                     .unwrap_or_else(|| "./uploads".to_string());
                 let public_base_url = "/files".to_string();
-                Arc::new(LocalStorageAdapter::new(
-                    PathBuf::from(base_path),
-                    public_base_url,
-                ))
+                let adapter = LocalStorageAdapter::new(PathBuf::from(base_path), public_base_url);
+                match &config.storage.encryption_key {
+                    Some(key) => Arc::new(adapter.with_encryption(FileCipher::new(key))),
+                    None => Arc::new(adapter),
+                }
             }
             StorageBackend::S3 => Arc::new(S3StorageAdapter::new()),
             StorageBackend::Ipfs => Arc::new(IpfsStorageAdapter::new()),
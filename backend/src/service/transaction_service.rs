@@ -0,0 +1,186 @@
+use crate::{
+    api_error::ApiError,
+    config::Config,
+    models::{TransactionKind, TransactionQueryParams, TransactionRecord, TransactionStats},
+};
+use chrono::{DateTime, Utc};
+use deadpool_postgres::Pool;
+use std::str::FromStr;
+use std::sync::Arc;
+
+/// Largest page `list_transactions` returns.
+pub const MAX_TRANSACTION_PAGE_SIZE: i64 = 100;
+
+/// Payments, transfers and withdrawals as rows of one shape. A payment's
+/// user is whoever owns the paying address.
+const TRANSACTIONS_CTE: &str = r#"
+    WITH transactions AS (
+        SELECT 'payment' AS kind, p.id::text AS id, p.status, u.user_id,
+               NULL::text AS recipient, p.merchant_id AS counterparty,
+               p.send_amount AS amount, p.send_asset AS asset, p.tx_hash, p.created_at
+        FROM payments p
+        LEFT JOIN users u ON u.stellar_address = p.from_address
+        UNION ALL
+        SELECT 'transfer', t.id::text, t.status, t.from_user_id,
+               t.to_user_id, t.to_user_id,
+               t.amount, t.asset, t.tx_hash, t.created_at
+        FROM transfers t
+        UNION ALL
+        SELECT 'withdrawal', w.id::text, w.status, w.user_id,
+               NULL, w.destination_address,
+               w.amount, w.asset, w.tx_hash, w.created_at
+        FROM withdrawals w
+    )
+"#;
+
+/// Unset filters are passed as NULL and match everything.
+const TRANSACTIONS_FILTER: &str = r#"
+    WHERE ($1::text IS NULL OR kind = $1)
+      AND ($2::text IS NULL OR status = $2)
+      AND ($3::text IS NULL OR user_id = $3 OR recipient = $3)
+      AND ($4::timestamptz IS NULL OR created_at >= $4)
+      AND ($5::timestamptz IS NULL OR created_at <= $5)
+"#;
+
+fn check_window(from: Option<DateTime<Utc>>, to: Option<DateTime<Utc>>) -> Result<(), ApiError> {
+    match (from, to) {
+        (Some(from), Some(to)) if from > to => Err(ApiError::Validation(
+            "from must not be after to".to_string(),
+        )),
+        _ => Ok(()),
+    }
+}
+
+/// Read-only view across every kind of transaction, for operators.
+#[derive(Clone)]
+pub struct TransactionService {
+    db_pool: Arc<Pool>,
+    _config: Config,
+}
+
+impl TransactionService {
+    pub fn new(db_pool: Arc<Pool>, config: Config) -> Self {
+        Self {
+            db_pool,
+            _config: config,
+        }
+    }
+
+    /// A page of transactions matching `params`, newest first, and the
+    /// total number that match.
+    pub async fn list_transactions(
+        &self,
+        params: &TransactionQueryParams,
+    ) -> Result<(Vec<TransactionRecord>, i64), ApiError> {
+        check_window(params.from, params.to)?;
+
+        let client = self.db_pool.get().await?;
+        let kind = params.kind.map(|k| k.as_str());
+        let limit = params.limit.clamp(1, MAX_TRANSACTION_PAGE_SIZE);
+        let offset = params.offset.max(0);
+
+        let total: i64 = client
+            .query_one(
+                &format!(
+                    "{} SELECT COUNT(*) FROM transactions {}",
+                    TRANSACTIONS_CTE, TRANSACTIONS_FILTER
+                ),
+                &[
+                    &kind,
+                    &params.status,
+                    &params.user_id,
+                    &params.from,
+                    &params.to,
+                ],
+            )
+            .await?
+            .get(0);
+
+        let rows = client
+            .query(
+                &format!(
+                    "{} SELECT kind, id, status, user_id, counterparty, amount, asset, tx_hash, created_at
+                     FROM transactions {}
+                     ORDER BY created_at DESC, id DESC
+                     LIMIT $6 OFFSET $7",
+                    TRANSACTIONS_CTE, TRANSACTIONS_FILTER
+                ),
+                &[
+                    &kind,
+                    &params.status,
+                    &params.user_id,
+                    &params.from,
+                    &params.to,
+                    &limit,
+                    &offset,
+                ],
+            )
+            .await?;
+
+        let transactions = rows
+            .iter()
+            .map(|row| TransactionRecord {
+                id: row.get("id"),
+                kind: TransactionKind::from_str(row.get("kind")).unwrap(),
+                status: row.get("status"),
+                user_id: row.get("user_id"),
+                counterparty: row.get("counterparty"),
+                amount: row.get("amount"),
+                asset: row.get("asset"),
+                tx_hash: row.get("tx_hash"),
+                created_at: row.get("created_at"),
+            })
+            .collect();
+
+        Ok((transactions, total))
+    }
+
+    /// Count and volume per transaction type, asset and status, over
+    /// transactions created within `from..=to`.
+    pub async fn stats_by_asset_and_status(
+        &self,
+        from: Option<DateTime<Utc>>,
+        to: Option<DateTime<Utc>>,
+    ) -> Result<Vec<TransactionStats>, ApiError> {
+        check_window(from, to)?;
+
+        let client = self.db_pool.get().await?;
+        let rows = client
+            .query(
+                &format!(
+                    "{} SELECT kind, asset, status, COUNT(*) AS count,
+                            COALESCE(SUM(amount), 0)::bigint AS volume
+                     FROM transactions {}
+                     GROUP BY kind, asset, status
+                     ORDER BY kind, asset, status NULLS FIRST",
+                    TRANSACTIONS_CTE, TRANSACTIONS_FILTER
+                ),
+                &[&None::<&str>, &None::<&str>, &None::<&str>, &from, &to],
+            )
+            .await?;
+
+        Ok(rows
+            .iter()
+            .map(|row| TransactionStats {
+                kind: TransactionKind::from_str(row.get("kind")).unwrap(),
+                asset: row.get("asset"),
+                status: row.get("status"),
+                count: row.get("count"),
+                volume: row.get("volume"),
+            })
+            .collect())
+    }
+
+    /// Registered users and active merchants.
+    pub async fn directory_totals(&self) -> Result<(i64, i64), ApiError> {
+        let client = self.db_pool.get().await?;
+        let row = client
+            .query_one(
+                "SELECT (SELECT COUNT(*) FROM users), (SELECT COUNT(*) FROM merchants WHERE active)",
+                &[],
+            )
+            .await?;
+
+        Ok((row.get(0), row.get(1)))
+    }
+}
@@ -0,0 +1,465 @@
+use crate::{
+    api_error::ApiError,
+    config::Config,
+    job_processors::transfer_confirmation_job,
+    queue::JobEnqueuer,
+    service::{
+        soroban_service::{payment_terms, OnChainStatus, SorobanService},
+        soroban_xdr::asset_contract_id,
+        BalanceService,
+    },
+};
+use deadpool_postgres::{tokio_postgres::error::SqlState, Pool, Transaction};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use tokio_postgres::Row;
+use tracing::{error, info, warn};
+
+const TRANSFER_COLUMNS: &str = "id::text AS id, from_user_id, to_user_id, amount, asset, \
+     status, memo, tx_hash, created_at, updated_at";
+
+/// A user-to-user transfer as stored in the `transfers` table.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TransferRecord {
+    pub id: String,
+    pub from_user_id: String,
+    pub to_user_id: String,
+    pub amount: i64,
+    pub asset: String,
+    /// `pending` until the submitted transaction is confirmed `completed` or
+    /// `failed` on-chain.
+    pub status: String,
+    pub memo: Option<String>,
+    pub tx_hash: Option<String>,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+    pub updated_at: chrono::DateTime<chrono::Utc>,
+}
+
+fn transfer_from_row(row: &Row) -> TransferRecord {
+    TransferRecord {
+        id: row.get("id"),
+        from_user_id: row.get("from_user_id"),
+        to_user_id: row.get("to_user_id"),
+        amount: row.get("amount"),
+        asset: row.get("asset"),
+        status: row
+            .get::<_, Option<String>>("status")
+            .unwrap_or_else(|| "pending".to_string()),
+        memo: row.get("memo"),
+        tx_hash: row.get("tx_hash"),
+        created_at: row.get("created_at"),
+        updated_at: row.get("updated_at"),
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct CreateTransferParams {
+    pub from_user_id: String,
+    pub to_user_id: String,
+    pub amount: i64,
+    pub asset: String,
+    pub memo: Option<String>,
+}
+
+/// Result of checking a submitted transfer against the ledger.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TransferConfirmation {
+    /// The transaction landed; the transfer now has this terminal status.
+    Settled(String),
+    /// The transaction is not in a ledger yet.
+    Pending,
+}
+
+/// Reject a transfer to a merchant in anything but the merchant's
+/// registered settlement asset, which would otherwise fail at settlement.
+/// The registry records a contract id where transfers name `CODE:ISSUER`,
+/// so both sides are compared as their asset contract on `network_passphrase`.
+/// Recipients that aren't active merchants are left alone.
+async fn check_settlement_asset(
+    tx: &Transaction<'_>,
+    params: &CreateTransferParams,
+    network_passphrase: &str,
+) -> Result<(), ApiError> {
+    let row = tx
+        .query_opt(
+            "SELECT settlement_asset FROM merchants WHERE merchant_id = $1 AND active = true",
+            &[&params.to_user_id],
+        )
+        .await?;
+
+    match row.map(|row| row.get::<_, String>(0)) {
+        Some(settlement_asset)
+            if !same_asset(&settlement_asset, &params.asset, network_passphrase) =>
+        {
+            Err(ApiError::Validation(format!(
+                "Merchant {} settles in {}, not {}",
+                params.to_user_id, settlement_asset, params.asset
+            )))
+        }
+        _ => Ok(()),
+    }
+}
+
+/// Whether `a` and `b` name the same asset. A bare `CODE` has no contract
+/// to compare by and only matches itself.
+fn same_asset(a: &str, b: &str, network_passphrase: &str) -> bool {
+    match (
+        asset_contract_id(a, network_passphrase),
+        asset_contract_id(b, network_passphrase),
+    ) {
+        (Some(a), Some(b)) => a == b,
+        _ => a == b,
+    }
+}
+
+#[derive(Clone)]
+pub struct TransferService {
+    db_pool: Arc<Pool>,
+    soroban: SorobanService,
+}
+
+impl TransferService {
+    pub fn new(db_pool: Arc<Pool>, config: Config) -> Self {
+        Self {
+            db_pool,
+            soroban: SorobanService::new(config),
+        }
+    }
+
+    /// Like `new`, but talking to the network through `http`.
+    pub fn with_http_client(db_pool: Arc<Pool>, config: Config, http: reqwest::Client) -> Self {
+        Self {
+            db_pool,
+            soroban: SorobanService::with_http_client(config, http),
+        }
+    }
+
+    fn passphrase(&self) -> &str {
+        &self.soroban.get_network_config().passphrase
+    }
+
+    /// Record a new `pending` transfer, holding its amount back from the
+    /// sender's balance until it settles. A merchant recipient only accepts
+    /// its settlement asset.
+    pub async fn create_transfer(
+        &self,
+        params: CreateTransferParams,
+    ) -> Result<TransferRecord, ApiError> {
+        let mut client = self.db_pool.get().await.map_err(|e| {
+            error!(error = %e, "DB pool error");
+            ApiError::InternalServerError
+        })?;
+
+        let query = format!(
+            r#"
+            INSERT INTO transfers (from_user_id, to_user_id, amount, asset, status, memo)
+            VALUES ($1, $2, $3, $4, 'pending', $5)
+            RETURNING {}
+            "#,
+            TRANSFER_COLUMNS
+        );
+
+        let tx = client.transaction().await?;
+        check_settlement_asset(&tx, &params, self.passphrase()).await?;
+        BalanceService::debit_in(&tx, &params.from_user_id, &params.asset, params.amount).await?;
+        let row = tx
+            .query_one(
+                &query,
+                &[
+                    &params.from_user_id,
+                    &params.to_user_id,
+                    &params.amount,
+                    &params.asset,
+                    &params.memo,
+                ],
+            )
+            .await
+            .map_err(|e| {
+                error!(error = %e, "Failed to insert transfer");
+                ApiError::InternalServerError
+            })?;
+        tx.commit().await?;
+
+        Ok(transfer_from_row(&row))
+    }
+
+    /// Record several `pending` transfers in one database transaction:
+    /// either all of them are created or none are. A recipient that doesn't
+    /// exist, or a sender who can't cover every transfer, aborts the whole
+    /// batch.
+    pub async fn create_transfers(
+        &self,
+        batch: Vec<CreateTransferParams>,
+    ) -> Result<Vec<TransferRecord>, ApiError> {
+        let mut client = self.db_pool.get().await.map_err(|e| {
+            error!(error = %e, "DB pool error");
+            ApiError::InternalServerError
+        })?;
+
+        let query = format!(
+            r#"
+            INSERT INTO transfers (from_user_id, to_user_id, amount, asset, status, memo)
+            VALUES ($1, $2, $3, $4, 'pending', $5)
+            RETURNING {}
+            "#,
+            TRANSFER_COLUMNS
+        );
+
+        let tx = client.transaction().await?;
+        let stmt = tx.prepare(&query).await?;
+        let mut created = Vec::with_capacity(batch.len());
+        for params in &batch {
+            check_settlement_asset(&tx, params, self.passphrase()).await?;
+            BalanceService::debit_in(&tx, &params.from_user_id, &params.asset, params.amount)
+                .await?;
+            let row = tx
+                .query_one(
+                    &stmt,
+                    &[
+                        &params.from_user_id,
+                        &params.to_user_id,
+                        &params.amount,
+                        &params.asset,
+                        &params.memo,
+                    ],
+                )
+                .await
+                .map_err(|e| {
+                    if e.as_db_error()
+                        .is_some_and(|db| db.code() == &SqlState::FOREIGN_KEY_VIOLATION)
+                    {
+                        return ApiError::Validation(format!(
+                            "Recipient {} not found",
+                            params.to_user_id
+                        ));
+                    }
+                    error!(error = %e, "Failed to insert batch transfer");
+                    ApiError::InternalServerError
+                })?;
+            created.push(transfer_from_row(&row));
+        }
+        tx.commit().await?;
+
+        info!(count = created.len(), "Created transfer batch");
+        Ok(created)
+    }
+
+    pub async fn get_transfer(&self, transfer_id: &str) -> Result<TransferRecord, ApiError> {
+        let client = self.db_pool.get().await.map_err(|e| {
+            error!(error = %e, "DB pool error");
+            ApiError::InternalServerError
+        })?;
+
+        let query = format!(
+            "SELECT {} FROM transfers WHERE id = $1::text::uuid",
+            TRANSFER_COLUMNS
+        );
+
+        let row = client
+            .query_opt(&query, &[&transfer_id])
+            .await
+            .map_err(|e| {
+                error!(error = %e, "DB query failed");
+                ApiError::InternalServerError
+            })?
+            .ok_or_else(|| ApiError::NotFound(format!("Transfer {} not found", transfer_id)))?;
+
+        Ok(transfer_from_row(&row))
+    }
+
+    /// Submit the sender's signed transaction and queue its confirmation.
+    ///
+    /// The envelope must pay exactly what the transfer records. A
+    /// transaction the network rejects outright marks the transfer `failed`;
+    /// a busy or unreachable network leaves it `pending` for resubmission.
+    /// An accepted one stays `pending` with its `tx_hash` until the
+    /// confirmation job sees it in a ledger.
+    pub async fn submit_transfer(
+        &self,
+        transfer_id: &str,
+        user_id: &str,
+        signed_xdr: String,
+        notifier: &dyn JobEnqueuer,
+    ) -> Result<TransferRecord, ApiError> {
+        let transfer = self.get_transfer(transfer_id).await?;
+        if transfer.from_user_id != user_id {
+            return Err(ApiError::NotFound(format!(
+                "Transfer {} not found",
+                transfer_id
+            )));
+        }
+        if transfer.status != "pending" || transfer.tx_hash.is_some() {
+            return Err(ApiError::Conflict(format!(
+                "Transfer {} has already been submitted",
+                transfer_id
+            )));
+        }
+
+        self.check_envelope(&transfer, &signed_xdr).await?;
+
+        let submitted = match self.soroban.submit_transaction(signed_xdr).await {
+            Ok(submitted) => submitted,
+            Err(e @ ApiError::TransactionRejected(_)) => {
+                warn!(transfer_id, error = %e, "Transfer submission rejected");
+                self.set_status(transfer_id, "failed", None).await?;
+                return Err(e);
+            }
+            Err(e) => {
+                warn!(transfer_id, error = %e, "Transfer submission not accepted yet");
+                return Err(e);
+            }
+        };
+
+        let client = self.db_pool.get().await.map_err(|e| {
+            error!(error = %e, "DB pool error");
+            ApiError::InternalServerError
+        })?;
+
+        let query = format!(
+            r#"
+            UPDATE transfers
+            SET tx_hash = $1, updated_at = NOW()
+            WHERE id = $2::text::uuid AND status = 'pending' AND tx_hash IS NULL
+            RETURNING {}
+            "#,
+            TRANSFER_COLUMNS
+        );
+
+        let row = client
+            .query_opt(&query, &[&submitted.tx_hash, &transfer_id])
+            .await
+            .map_err(|e| {
+                error!(error = %e, "Failed to record transfer submission");
+                ApiError::InternalServerError
+            })?
+            .ok_or_else(|| {
+                ApiError::Conflict(format!(
+                    "Transfer {} has already been submitted",
+                    transfer_id
+                ))
+            })?;
+        let transfer = transfer_from_row(&row);
+
+        info!(transfer_id, tx_hash = %submitted.tx_hash, "Transfer submitted");
+
+        let job = transfer_confirmation_job(transfer_id, &submitted.tx_hash, 1);
+        if let Err(e) = notifier.enqueue(job).await {
+            error!(transfer_id, error = %e, "Failed to enqueue transfer confirmation");
+        }
+
+        Ok(transfer)
+    }
+
+    /// Reject a signed envelope that doesn't pay what `transfer` records,
+    /// between the two users' wallets.
+    async fn check_envelope(
+        &self,
+        transfer: &TransferRecord,
+        signed_xdr: &str,
+    ) -> Result<(), ApiError> {
+        let terms = payment_terms(signed_xdr)?;
+
+        let client = self.db_pool.get().await.map_err(|e| {
+            error!(error = %e, "DB pool error");
+            ApiError::InternalServerError
+        })?;
+        let row = client
+            .query_one(
+                r#"
+                SELECT
+                    (SELECT stellar_address FROM users WHERE user_id = $1),
+                    (SELECT stellar_address FROM users WHERE user_id = $2)
+                "#,
+                &[&transfer.from_user_id, &transfer.to_user_id],
+            )
+            .await?;
+        let from: Option<String> = row.get(0);
+        let to: Option<String> = row.get(1);
+
+        if from.as_deref() != Some(terms.from.as_str())
+            || to.as_deref() != Some(terms.to.as_str())
+            || terms.asset != transfer.asset
+            || terms.amount != transfer.amount
+        {
+            warn!(transfer_id = %transfer.id, "Signed transaction does not match the transfer");
+            return Err(ApiError::Validation(
+                "Signed transaction does not match the transfer".to_string(),
+            ));
+        }
+        Ok(())
+    }
+
+    /// Check a submitted transfer's transaction on-chain and settle it once
+    /// it lands.
+    pub async fn confirm_transfer(
+        &self,
+        transfer_id: &str,
+        tx_hash: &str,
+    ) -> Result<TransferConfirmation, ApiError> {
+        let status = match self.soroban.get_transaction_status(tx_hash).await? {
+            OnChainStatus::NotFound => return Ok(TransferConfirmation::Pending),
+            OnChainStatus::Success => "completed",
+            OnChainStatus::Failed => "failed",
+        };
+
+        self.set_status(transfer_id, status, Some(tx_hash)).await?;
+        info!(transfer_id, tx_hash, status, "Transfer confirmed on-chain");
+        Ok(TransferConfirmation::Settled(status.to_string()))
+    }
+
+    /// Give up on a transaction that never appeared on-chain.
+    pub async fn expire_transfer(&self, transfer_id: &str, tx_hash: &str) -> Result<(), ApiError> {
+        warn!(
+            transfer_id,
+            tx_hash, "Transfer never confirmed — marking failed"
+        );
+        self.set_status(transfer_id, "failed", Some(tx_hash)).await
+    }
+
+    /// Move a still-pending transfer to `status`, optionally only while it
+    /// carries `tx_hash`. The held amount goes to the recipient when the
+    /// transfer completes and back to the sender when it fails.
+    async fn set_status(
+        &self,
+        transfer_id: &str,
+        status: &str,
+        tx_hash: Option<&str>,
+    ) -> Result<(), ApiError> {
+        let mut client = self.db_pool.get().await.map_err(|e| {
+            error!(error = %e, "DB pool error");
+            ApiError::InternalServerError
+        })?;
+
+        let tx = client.transaction().await?;
+        let settled = tx
+            .query_opt(
+                r#"
+                UPDATE transfers
+                SET status = $1, updated_at = NOW()
+                WHERE id = $2::text::uuid
+                  AND status = 'pending'
+                  AND ($3::text IS NULL OR tx_hash = $3)
+                RETURNING from_user_id, to_user_id, amount, asset
+                "#,
+                &[&status, &transfer_id, &tx_hash],
+            )
+            .await
+            .map_err(|e| {
+                error!(error = %e, "Failed to update transfer status");
+                ApiError::InternalServerError
+            })?;
+
+        if let Some(row) = settled {
+            let owner_id: String = if status == "completed" {
+                row.get("to_user_id")
+            } else {
+                row.get("from_user_id")
+            };
+            let asset: String = row.get("asset");
+            BalanceService::credit_in(&tx, &owner_id, &asset, row.get("amount")).await?;
+        }
+        tx.commit().await?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,82 @@
+use crate::{api_error::ApiError, config::Config};
+use deadpool_postgres::Pool;
+use std::sync::Arc;
+
+/// What is known about an idempotency key when a submission reserves it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SubmissionState {
+    /// First time the key is seen; it is now reserved.
+    New,
+    /// Reserved by an earlier attempt that never recorded a hash, so its
+    /// transaction may or may not have reached the chain.
+    Unconfirmed,
+    /// Already submitted as this transaction.
+    Submitted(String),
+}
+
+/// Ledger of blockchain transaction submissions, keyed by idempotency key.
+#[derive(Clone)]
+pub struct TxSubmissionService {
+    db_pool: Arc<Pool>,
+    _config: Config,
+}
+
+impl TxSubmissionService {
+    pub fn new(db_pool: Arc<Pool>, config: Config) -> Self {
+        Self {
+            db_pool,
+            _config: config,
+        }
+    }
+
+    /// Reserve `key` ahead of submitting its transaction, reporting what an
+    /// earlier attempt left behind.
+    pub async fn reserve(&self, key: &str) -> Result<SubmissionState, ApiError> {
+        let client = self.db_pool.get().await?;
+
+        let inserted = client
+            .execute(
+                r#"
+                INSERT INTO blockchain_tx_submissions (idempotency_key)
+                VALUES ($1)
+                ON CONFLICT (idempotency_key) DO NOTHING
+                "#,
+                &[&key],
+            )
+            .await?;
+        if inserted == 1 {
+            return Ok(SubmissionState::New);
+        }
+
+        let tx_hash: Option<String> = client
+            .query_one(
+                "SELECT tx_hash FROM blockchain_tx_submissions WHERE idempotency_key = $1",
+                &[&key],
+            )
+            .await?
+            .get("tx_hash");
+
+        Ok(match tx_hash {
+            Some(tx_hash) => SubmissionState::Submitted(tx_hash),
+            None => SubmissionState::Unconfirmed,
+        })
+    }
+
+    /// Record that `key` was submitted as `tx_hash`.
+    pub async fn record_submission(&self, key: &str, tx_hash: &str) -> Result<(), ApiError> {
+        let client = self.db_pool.get().await?;
+
+        client
+            .execute(
+                r#"
+                UPDATE blockchain_tx_submissions
+                SET tx_hash = $2, submitted_at = NOW()
+                WHERE idempotency_key = $1
+                "#,
+                &[&key, &tx_hash],
+            )
+            .await?;
+
+        Ok(())
+    }
+}
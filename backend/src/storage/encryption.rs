@@ -0,0 +1,149 @@
+//! AES-256-GCM encryption of stored files.
+//!
+//! An encrypted file is [`MAGIC`], a random 96-bit nonce, then the ciphertext
+//! with its tag. The file ID is bound in as associated data, so a file copied
+//! over another ID fails to decrypt. Files without the header were written
+//! before encryption was enabled and are passed through as-is.
+
+use ring::{
+    aead::{self, Aad, LessSafeKey, Nonce, UnboundKey, AES_256_GCM, NONCE_LEN},
+    hkdf,
+    rand::{SecureRandom, SystemRandom},
+};
+use std::sync::Arc;
+
+/// Marks a file as encrypted by this module.
+const MAGIC: &[u8] = b"ZAPSENC1";
+/// Salt for deriving the file key, so the secret can't be reused to derive
+/// anything else.
+const KEY_SALT: &[u8] = b"zaps:file-encryption:v1";
+
+/// Bytes an encrypted file has on top of its plaintext.
+pub const OVERHEAD: usize = MAGIC.len() + NONCE_LEN + aead::MAX_TAG_LEN;
+
+type CipherResult<T> = Result<T, Box<dyn std::error::Error + Send + Sync>>;
+
+/// Encrypts and decrypts file contents with a key derived from a config
+/// secret.
+#[derive(Clone)]
+pub struct FileCipher {
+    key: Arc<LessSafeKey>,
+    rng: SystemRandom,
+}
+
+impl FileCipher {
+    pub fn new(secret: &str) -> Self {
+        let prk = hkdf::Salt::new(hkdf::HKDF_SHA256, KEY_SALT).extract(secret.as_bytes());
+        let okm = prk
+            .expand(&[], &AES_256_GCM)
+            .expect("AES-256 key length is a valid HKDF output length");
+        Self {
+            key: Arc::new(LessSafeKey::new(UnboundKey::from(okm))),
+            rng: SystemRandom::new(),
+        }
+    }
+
+    /// Encrypt `plaintext` for storage under `id`.
+    pub fn encrypt(&self, id: &str, plaintext: &[u8]) -> CipherResult<Vec<u8>> {
+        let mut nonce = [0u8; NONCE_LEN];
+        self.rng
+            .fill(&mut nonce)
+            .map_err(|_| "Failed to generate nonce")?;
+
+        let mut sealed = plaintext.to_vec();
+        self.key
+            .seal_in_place_append_tag(
+                Nonce::assume_unique_for_key(nonce),
+                Aad::from(id.as_bytes()),
+                &mut sealed,
+            )
+            .map_err(|_| "Failed to encrypt file")?;
+
+        let mut out = Vec::with_capacity(OVERHEAD + plaintext.len());
+        out.extend_from_slice(MAGIC);
+        out.extend_from_slice(&nonce);
+        out.extend_from_slice(&sealed);
+        Ok(out)
+    }
+
+    /// Decrypt the stored bytes of `id`. Bytes without the encryption header
+    /// are returned unchanged.
+    pub fn decrypt(&self, id: &str, stored: Vec<u8>) -> CipherResult<Vec<u8>> {
+        if !is_encrypted(&stored) {
+            return Ok(stored);
+        }
+        if stored.len() < OVERHEAD {
+            return Err(format!("Encrypted file {} is truncated", id).into());
+        }
+
+        let (nonce, sealed) = stored[MAGIC.len()..].split_at(NONCE_LEN);
+        let nonce = Nonce::try_assume_unique_for_key(nonce)
+            .map_err(|_| format!("Encrypted file {} has a bad nonce", id))?;
+        let mut sealed = sealed.to_vec();
+        let plaintext_len = self
+            .key
+            .open_in_place(nonce, Aad::from(id.as_bytes()), &mut sealed)
+            .map_err(|_| format!("Failed to decrypt file {}", id))?
+            .len();
+        sealed.truncate(plaintext_len);
+        Ok(sealed)
+    }
+}
+
+/// Whether `stored` starts with the encryption header.
+pub fn is_encrypted(stored: &[u8]) -> bool {
+    stored.starts_with(MAGIC)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_trips_and_hides_the_plaintext() {
+        let cipher = FileCipher::new("test-file-encryption-secret");
+
+        let stored = cipher.encrypt("file-1", b"passport scan").unwrap();
+
+        assert!(is_encrypted(&stored));
+        assert_eq!(stored.len(), OVERHEAD + b"passport scan".len());
+        assert!(!stored.windows(b"passport".len()).any(|w| w == b"passport"));
+        assert_eq!(cipher.decrypt("file-1", stored).unwrap(), b"passport scan");
+    }
+
+    #[test]
+    fn same_contents_encrypt_differently() {
+        let cipher = FileCipher::new("test-file-encryption-secret");
+
+        assert_ne!(
+            cipher.encrypt("file-1", b"same").unwrap(),
+            cipher.encrypt("file-1", b"same").unwrap()
+        );
+    }
+
+    #[test]
+    fn wrong_key_id_or_tampering_fails_to_decrypt() {
+        let cipher = FileCipher::new("test-file-encryption-secret");
+        let stored = cipher.encrypt("file-1", b"passport scan").unwrap();
+
+        let other_key = FileCipher::new("another-file-encryption-secret");
+        assert!(other_key.decrypt("file-1", stored.clone()).is_err());
+        assert!(cipher.decrypt("file-2", stored.clone()).is_err());
+
+        let mut tampered = stored;
+        let last = tampered.len() - 1;
+        tampered[last] ^= 1;
+        assert!(cipher.decrypt("file-1", tampered).is_err());
+        assert!(cipher.decrypt("file-1", MAGIC.to_vec()).is_err());
+    }
+
+    #[test]
+    fn plaintext_files_pass_through() {
+        let cipher = FileCipher::new("test-file-encryption-secret");
+
+        assert_eq!(
+            cipher.decrypt("legacy", b"%PDF-1.7".to_vec()).unwrap(),
+            b"%PDF-1.7"
+        );
+    }
+}
@@ -1,10 +1,15 @@
 use axum::body::Bytes;
+use serde::{Deserialize, Serialize};
 use std::{
     fs,
     path::{Path, PathBuf},
 };
 use uuid::Uuid;
 
+pub mod encryption;
+
+pub use encryption::FileCipher;
+
 #[derive(Debug, Clone)]
 pub struct StoredFile {
     pub id: String,
@@ -12,6 +17,8 @@ pub struct StoredFile {
     pub mime_type: String,
     pub size: u64,
     pub url: String,
+    /// User who uploaded the file; `None` for files the system stored itself
+    pub owner_id: Option<String>,
 }
 
 pub trait StorageAdapter: Send + Sync {
@@ -20,18 +27,32 @@ pub trait StorageAdapter: Send + Sync {
         data: Bytes,
         original_name: &str,
         mime_type: &str,
+        owner_id: Option<&str>,
     ) -> Result<StoredFile, Box<dyn std::error::Error + Send + Sync>>;
 
     fn get(&self, id: &str)
         -> Result<Option<StoredFile>, Box<dyn std::error::Error + Send + Sync>>;
 
+    /// The contents of file `id`, or `None` if there is no such file.
+    fn read(&self, id: &str) -> Result<Option<Vec<u8>>, Box<dyn std::error::Error + Send + Sync>>;
+
     fn delete(&self, id: &str) -> Result<(), Box<dyn std::error::Error + Send + Sync>>;
 }
 
-#[derive(Debug, Clone)]
+/// What `LocalStorageAdapter` keeps next to each file, since the file
+/// system only knows its id and size.
+#[derive(Serialize, Deserialize)]
+struct FileMeta {
+    original_name: String,
+    mime_type: String,
+    owner_id: Option<String>,
+}
+
+#[derive(Clone)]
 pub struct LocalStorageAdapter {
     base_path: PathBuf,
     public_base_url: String,
+    cipher: Option<FileCipher>,
 }
 
 impl LocalStorageAdapter {
@@ -39,18 +60,54 @@ impl LocalStorageAdapter {
         Self {
             base_path,
             public_base_url,
+            cipher: None,
         }
     }
 
+    /// Encrypt files before they are written, and decrypt them on read.
+    /// Files already stored in plaintext stay readable.
+    pub fn with_encryption(mut self, cipher: FileCipher) -> Self {
+        self.cipher = Some(cipher);
+        self
+    }
+
     fn ensure_base_dir(&self) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
         fs::create_dir_all(&self.base_path)?;
         Ok(())
     }
 
+    fn has_encryption_header(
+        path: &Path,
+    ) -> Result<bool, Box<dyn std::error::Error + Send + Sync>> {
+        use std::io::Read;
+
+        let mut header = Vec::new();
+        fs::File::open(path)?
+            .take(encryption::OVERHEAD as u64)
+            .read_to_end(&mut header)?;
+        Ok(encryption::is_encrypted(&header))
+    }
+
     fn path_for_id(&self, id: &str) -> PathBuf {
         self.base_path.join(id)
     }
 
+    fn meta_path_for_id(&self, id: &str) -> PathBuf {
+        self.base_path.join(format!("{}.meta.json", id))
+    }
+
+    /// The file's recorded metadata; files stored before it was kept have none.
+    fn read_meta(
+        &self,
+        id: &str,
+    ) -> Result<Option<FileMeta>, Box<dyn std::error::Error + Send + Sync>> {
+        match fs::read(self.meta_path_for_id(id)) {
+            Ok(meta) => Ok(Some(serde_json::from_slice(&meta)?)),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    }
+
     fn url_for_id(&self, id: &str) -> String {
         format!("{}/{}", self.public_base_url.trim_end_matches('/'), id)
     }
@@ -62,12 +119,22 @@ impl StorageAdapter for LocalStorageAdapter {
         data: Bytes,
         original_name: &str,
         mime_type: &str,
+        owner_id: Option<&str>,
     ) -> Result<StoredFile, Box<dyn std::error::Error + Send + Sync>> {
         self.ensure_base_dir()?;
 
         let id = Uuid::new_v4().to_string();
         let path = self.path_for_id(&id);
-        fs::write(&path, &data)?;
+        let meta = FileMeta {
+            original_name: original_name.to_string(),
+            mime_type: mime_type.to_string(),
+            owner_id: owner_id.map(str::to_string),
+        };
+        fs::write(self.meta_path_for_id(&id), serde_json::to_vec(&meta)?)?;
+        match &self.cipher {
+            Some(cipher) => fs::write(&path, cipher.encrypt(&id, &data)?)?,
+            None => fs::write(&path, &data)?,
+        }
 
         Ok(StoredFile {
             id,
@@ -80,6 +147,7 @@ impl StorageAdapter for LocalStorageAdapter {
                     .to_string_lossy()
                     .as_ref(),
             ),
+            owner_id: meta.owner_id,
         })
     }
 
@@ -92,20 +160,43 @@ impl StorageAdapter for LocalStorageAdapter {
             return Ok(None);
         }
 
-        let meta = fs::metadata(&path)?;
-        Ok(Some(StoredFile {
-            id: id.to_string(),
+        let mut size = fs::metadata(&path)?.len();
+        if self.cipher.is_some() && Self::has_encryption_header(&path)? {
+            size = size.saturating_sub(encryption::OVERHEAD as u64);
+        }
+        let meta = self.read_meta(id)?.unwrap_or_else(|| FileMeta {
             original_name: id.to_string(),
             mime_type: "application/octet-stream".to_string(),
-            size: meta.len(),
+            owner_id: None,
+        });
+        Ok(Some(StoredFile {
+            id: id.to_string(),
+            original_name: meta.original_name,
+            mime_type: meta.mime_type,
+            size,
             url: self.url_for_id(id),
+            owner_id: meta.owner_id,
         }))
     }
 
+    fn read(&self, id: &str) -> Result<Option<Vec<u8>>, Box<dyn std::error::Error + Send + Sync>> {
+        let stored = match fs::read(self.path_for_id(id)) {
+            Ok(stored) => stored,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(e.into()),
+        };
+
+        match &self.cipher {
+            Some(cipher) => Ok(Some(cipher.decrypt(id, stored)?)),
+            None => Ok(Some(stored)),
+        }
+    }
+
     fn delete(&self, id: &str) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
-        let path = self.path_for_id(id);
-        if Path::new(&path).exists() {
-            fs::remove_file(path)?;
+        for path in [self.path_for_id(id), self.meta_path_for_id(id)] {
+            if Path::new(&path).exists() {
+                fs::remove_file(path)?;
+            }
         }
         Ok(())
     }
@@ -131,6 +222,7 @@ impl StorageAdapter for S3StorageAdapter {
         _data: Bytes,
         _original_name: &str,
         _mime_type: &str,
+        _owner_id: Option<&str>,
     ) -> Result<StoredFile, Box<dyn std::error::Error + Send + Sync>> {
         Err("S3 adapter not implemented".into())
     }
@@ -142,6 +234,10 @@ impl StorageAdapter for S3StorageAdapter {
         Err("S3 adapter not implemented".into())
     }
 
+    fn read(&self, _id: &str) -> Result<Option<Vec<u8>>, Box<dyn std::error::Error + Send + Sync>> {
+        Err("S3 adapter not implemented".into())
+    }
+
     fn delete(&self, _id: &str) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
         Err("S3 adapter not implemented".into())
     }
@@ -167,6 +263,7 @@ impl StorageAdapter for IpfsStorageAdapter {
         _data: Bytes,
         _original_name: &str,
         _mime_type: &str,
+        _owner_id: Option<&str>,
     ) -> Result<StoredFile, Box<dyn std::error::Error + Send + Sync>> {
         Err("IPFS adapter not implemented".into())
     }
@@ -178,6 +275,10 @@ impl StorageAdapter for IpfsStorageAdapter {
         Err("IPFS adapter not implemented".into())
     }
 
+    fn read(&self, _id: &str) -> Result<Option<Vec<u8>>, Box<dyn std::error::Error + Send + Sync>> {
+        Err("IPFS adapter not implemented".into())
+    }
+
     fn delete(&self, _id: &str) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
         Err("IPFS adapter not implemented".into())
     }
@@ -1,4 +1,168 @@
-use tracing_subscriber::{fmt, layer::SubscriberExt, util::SubscriberInitExt, EnvFilter};
+use lazy_static::lazy_static;
+use regex::{Captures, Regex};
+use std::borrow::Cow;
+use std::io;
+use tracing_subscriber::{
+    fmt::{self, MakeWriter},
+    layer::SubscriberExt,
+    util::SubscriberInitExt,
+    EnvFilter,
+};
+
+const REDACTED: &str = "[REDACTED]";
+
+/// Names of log fields whose values are never written out. Card numbers are
+/// only caught under these names: a bare run of digits is as likely to be an
+/// amount, ledger sequence or timestamp.
+const SENSITIVE_KEYS: &str = "pin|pin_hash|password|secret|seed|token|authorization|cookie|api_key|private_key|card_number|pan";
+
+lazy_static! {
+    /// A Stellar secret seed: `S` followed by 55 base32 characters.
+    static ref SECRET_SEED: Regex = Regex::new(r"\bS[A-Z2-7]{55}\b").unwrap();
+    /// A credential or PII field name. Prefixed names such as
+    /// `webhook_secret` match too.
+    static ref SENSITIVE_NAME: Regex =
+        Regex::new(&format!(r"(?i)^(?:\w*_)?(?:{})$", SENSITIVE_KEYS)).unwrap();
+    /// The value of a sensitive field in plain text, logged as `key=value`
+    /// or as JSON `"key": value`.
+    static ref TEXT_FIELD: Regex = Regex::new(&format!(
+        r#"(?i)\b((?:\w*_)?(?:{keys}))=(?:"(?:[^"\\]|\\.)*"|[^\s,}}\]]+)|("(?:\w*_)?(?:{keys})"\s*:\s*)(?:"(?:[^"\\]|\\.)*"|[^\s,}}\]]+)"#,
+        keys = SENSITIVE_KEYS
+    ))
+    .unwrap();
+    /// The same inside a JSON string, where quotes arrive escaped and an
+    /// unescaped one ends the string.
+    static ref STRING_FIELD: Regex = Regex::new(&format!(
+        r#"(?i)\b((?:\w*_)?(?:{keys}))=(?:\\"(?:[^"\\]|\\[^"])*\\"|[^\s,}}\]"\\]+)|(\\"(?:\w*_)?(?:{keys})\\"\s*:\s*)(?:\\"(?:[^"\\]|\\[^"])*\\"|[^\s,}}\]"\\]+)"#,
+        keys = SENSITIVE_KEYS
+    ))
+    .unwrap();
+}
+
+/// Scrub Stellar secret seeds and the values of credential and PII fields
+/// from a formatted log line.
+///
+/// A line holding a JSON object is scrubbed field by field, so the result is
+/// still valid JSON; anything else is treated as plain text.
+///
+/// Complements the audit middleware's `redact_sensitive_fields`, which only
+/// covers request bodies it records.
+pub fn redact(line: &str) -> Cow<'_, str> {
+    let line = SECRET_SEED.replace_all(line, REDACTED);
+    let redacted = if line.trim_start().starts_with('{') {
+        redact_json(&line)
+    } else {
+        redact_fields(&TEXT_FIELD, &line, "\"")
+    };
+    match redacted {
+        Cow::Borrowed(_) => line,
+        Cow::Owned(redacted) => Cow::Owned(redacted),
+    }
+}
+
+/// Replace each sensitive value `fields` finds, writing `quote` around the
+/// placeholder of a JSON-style `"key": value` match.
+fn redact_fields<'a>(fields: &Regex, text: &'a str, quote: &str) -> Cow<'a, str> {
+    fields.replace_all(text, |caps: &Captures| match (caps.get(1), caps.get(2)) {
+        (Some(key), _) => format!("{}={}", key.as_str(), REDACTED),
+        (_, Some(key)) => format!("{}{q}{}{q}", key.as_str(), REDACTED, q = quote),
+        _ => caps[0].to_string(),
+    })
+}
+
+/// Walk a JSON line string by string. The value of a sensitive key is
+/// replaced whole; every other string has its contents scrubbed as text
+/// without adding unescaped quotes.
+fn redact_json(line: &str) -> Cow<'_, str> {
+    let mut out = String::with_capacity(line.len());
+    let mut changed = false;
+    let mut rest = line;
+
+    while let Some(open) = rest.find('"') {
+        out.push_str(&rest[..=open]);
+        let len = string_len(&rest[open + 1..]);
+        let contents = &rest[open + 1..open + 1 + len];
+        rest = &rest[(open + 2 + len).min(rest.len())..];
+
+        let after_key = rest.trim_start();
+        if after_key.starts_with(':') && SENSITIVE_NAME.is_match(contents) {
+            let value = after_key[1..].trim_start();
+            out.push_str(contents);
+            out.push('"');
+            out.push_str(&rest[..rest.len() - value.len()]);
+            out.push('"');
+            out.push_str(REDACTED);
+            out.push('"');
+            rest = &value[scalar_len(value)..];
+            changed = true;
+        } else {
+            let redacted = redact_fields(&STRING_FIELD, contents, "\\\"");
+            changed |= matches!(redacted, Cow::Owned(_));
+            out.push_str(&redacted);
+            out.push('"');
+        }
+    }
+
+    if !changed {
+        return Cow::Borrowed(line);
+    }
+    out.push_str(rest);
+    Cow::Owned(out)
+}
+
+/// Length of the contents of a JSON string whose opening quote has already
+/// been read, up to its closing quote.
+fn string_len(s: &str) -> usize {
+    let mut escaped = false;
+    for (i, c) in s.char_indices() {
+        match c {
+            _ if escaped => escaped = false,
+            '\\' => escaped = true,
+            '"' => return i,
+            _ => {}
+        }
+    }
+    s.len()
+}
+
+/// Length of the string, number or literal at the start of `value`. Objects
+/// and arrays count as empty so their fields are still walked.
+fn scalar_len(value: &str) -> usize {
+    match value.strip_prefix('"') {
+        Some(s) => (string_len(s) + 2).min(value.len()),
+        None if value.starts_with(['{', '[']) => 0,
+        None => value
+            .find(|c: char| c.is_whitespace() || matches!(c, ',' | '}' | ']'))
+            .unwrap_or(value.len()),
+    }
+}
+
+/// Wraps a [`MakeWriter`] so every log line is passed through [`redact`]
+/// before it is written.
+pub struct Redacting<W>(pub W);
+
+impl<'a, W: MakeWriter<'a>> MakeWriter<'a> for Redacting<W> {
+    type Writer = RedactingWriter<W::Writer>;
+
+    fn make_writer(&'a self) -> Self::Writer {
+        RedactingWriter(self.0.make_writer())
+    }
+}
+
+pub struct RedactingWriter<W>(W);
+
+impl<W: io::Write> io::Write for RedactingWriter<W> {
+    /// The fmt layer writes each event in one call, so a whole line is seen.
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let line = String::from_utf8_lossy(buf);
+        self.0.write_all(redact(&line).as_bytes())?;
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.0.flush()
+    }
+}
 
 /// Telemetry configuration options
 pub struct TelemetryConfig {
@@ -31,6 +195,7 @@ pub fn init_tracing() -> Result<(), Box<dyn std::error::Error>> {
 /// - JSON: Structured format for production and log aggregation (Datadog, Elasticsearch, etc.)
 ///
 /// Set `LOG_FORMAT=json` environment variable to enable JSON logging.
+/// Either way, output is scrubbed of secrets and PII by [`redact`].
 pub fn init_tracing_with_config(config: TelemetryConfig) -> Result<(), Box<dyn std::error::Error>> {
     let env_filter = match config.log_filter {
         Some(filter) => EnvFilter::try_new(filter)?,
@@ -47,7 +212,10 @@ pub fn init_tracing_with_config(config: TelemetryConfig) -> Result<(), Box<dyn s
             .with_file(true)
             .with_line_number(true)
             .with_current_span(true)
-            .flatten_event(true);
+            // Keep the outer `request` span's id when a nested span is current
+            .with_span_list(true)
+            .flatten_event(true)
+            .with_writer(Redacting(io::stdout));
 
         tracing_subscriber::registry()
             .with(env_filter)
@@ -66,7 +234,8 @@ pub fn init_tracing_with_config(config: TelemetryConfig) -> Result<(), Box<dyn s
             .with_target(false)
             .with_thread_ids(false)
             .with_thread_names(false)
-            .compact();
+            .compact()
+            .with_writer(Redacting(io::stdout));
 
         tracing_subscriber::registry()
             .with(env_filter)
@@ -111,3 +280,106 @@ macro_rules! log_event {
         );
     };
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::{Arc, Mutex};
+
+    /// Log output captured in memory.
+    #[derive(Clone, Default)]
+    struct Captured(Arc<Mutex<Vec<u8>>>);
+
+    impl io::Write for Captured {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0.lock().unwrap().extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    fn seed() -> String {
+        stellar_strkey::ed25519::PrivateKey([7; 32]).to_string()
+    }
+
+    #[test]
+    fn secret_shaped_strings_are_redacted_from_log_lines() {
+        let seed = seed();
+        let captured = Captured::default();
+        let writer = captured.clone();
+        let subscriber = tracing_subscriber::registry().with(
+            fmt::layer()
+                .json()
+                .with_writer(Redacting(move || writer.clone())),
+        );
+
+        tracing::subscriber::with_default(subscriber, || {
+            tracing::info!(
+                signer = %seed,
+                pin = "1234",
+                "Signing with {} token={}",
+                seed,
+                "abc123"
+            );
+        });
+
+        let output = String::from_utf8(captured.0.lock().unwrap().clone()).unwrap();
+        assert!(!output.contains(&seed), "{}", output);
+        assert!(!output.contains("1234"), "{}", output);
+        assert!(!output.contains("abc123"), "{}", output);
+        // Still valid JSON for log aggregation
+        let event: serde_json::Value = serde_json::from_str(output.trim()).unwrap();
+        assert_eq!(
+            event["fields"]["message"],
+            "Signing with [REDACTED] token=[REDACTED]"
+        );
+    }
+
+    #[test]
+    fn redacts_sensitive_fields() {
+        assert_eq!(
+            redact(r#"login user_id=alice pin=1234 webhook_secret="s3 cr3t""#),
+            "login user_id=alice pin=[REDACTED] webhook_secret=[REDACTED]"
+        );
+        assert_eq!(
+            redact(r#"body={"access_token":"eyJ.x.y","amount":5}"#),
+            r#"body={"access_token":"[REDACTED]","amount":5}"#
+        );
+        assert_eq!(
+            redact("charged card_number=4111111111111111"),
+            "charged card_number=[REDACTED]"
+        );
+    }
+
+    #[test]
+    fn json_lines_stay_valid_json() {
+        let line = r#"{"message":"login pin=1234 secret=\"s3 cr3t\" body={\"password\":\"hunter2\"}","access_token":"eyJ.x.y","pan":4111111111111111,"nested":{"api_key":"k"},"amount":5}"#;
+
+        let redacted = redact(line);
+        let value: serde_json::Value = serde_json::from_str(&redacted).unwrap();
+        assert_eq!(
+            value["message"],
+            r#"login pin=[REDACTED] secret=[REDACTED] body={"password":"[REDACTED]"}"#
+        );
+        assert_eq!(value["access_token"], REDACTED);
+        assert_eq!(value["pan"], REDACTED);
+        assert_eq!(value["nested"]["api_key"], REDACTED);
+        assert_eq!(value["amount"], 5);
+    }
+
+    #[test]
+    fn leaves_ordinary_lines_alone() {
+        let public_key = stellar_strkey::ed25519::PublicKey([7; 32]).to_string();
+        let line = format!(
+            "Payment of 4111111111111111 stroops to {} pending spinning=true japan=1",
+            public_key
+        );
+
+        assert_eq!(redact(&line), line);
+        let json = r#"{"message":"paid","amount":"4111111111111111","company":"x"}"#;
+        assert_eq!(redact(json), json);
+    }
+}
@@ -0,0 +1,237 @@
+use std::sync::Arc;
+
+use blinks_backend::api_error::ApiError;
+use blinks_backend::config::Config;
+use blinks_backend::db;
+use blinks_backend::models::{TransactionKind, TransactionQueryParams};
+use blinks_backend::service::transaction_service::MAX_TRANSACTION_PAGE_SIZE;
+use blinks_backend::service::TransactionService;
+use chrono::{DateTime, TimeZone, Utc};
+use uuid::Uuid;
+
+// Note: These tests require a running database using the config.
+// Run with: cargo test --test admin_transactions_test -- --ignored
+
+async fn setup() -> Option<(TransactionService, Arc<deadpool_postgres::Pool>)> {
+    let config = match Config::load() {
+        Ok(c) => c,
+        Err(_) => {
+            println!("Skipping test: No config found");
+            return None;
+        }
+    };
+
+    db::run_migrations(&config.database.url)
+        .await
+        .expect("Failed to run migrations");
+    let pool = Arc::new(
+        db::create_pool(&config.database.url)
+            .await
+            .expect("Failed to create pool"),
+    );
+
+    Some((TransactionService::new(pool.clone(), config), pool))
+}
+
+fn day(d: u32) -> DateTime<Utc> {
+    Utc.with_ymd_and_hms(2026, 1, d, 0, 0, 0).unwrap()
+}
+
+/// Alice pays a merchant (day 1, completed), transfers to Bob (day 2,
+/// pending), and Bob withdraws (day 3, failed).
+struct Seeded {
+    alice: String,
+    bob: String,
+    merchant_id: String,
+}
+
+async fn seed(pool: &deadpool_postgres::Pool) -> Seeded {
+    let client = pool.get().await.unwrap();
+    let suffix = Uuid::new_v4().simple().to_string()[..20].to_uppercase();
+    let alice = format!("txalice-{}", suffix);
+    let bob = format!("txbob-{}", suffix);
+    let alice_address = format!("GA{}", suffix);
+    let merchant_id = format!("txmerchant-{}", suffix);
+
+    for (user_id, address) in [
+        (&alice, alice_address.clone()),
+        (&bob, format!("GB{}", suffix)),
+    ] {
+        client
+            .execute(
+                "INSERT INTO users (user_id, stellar_address, pin_hash) VALUES ($1, $2, 'x')",
+                &[user_id, &address],
+            )
+            .await
+            .unwrap();
+    }
+    client
+        .execute(
+            "INSERT INTO merchants (merchant_id, vault_address, settlement_asset) VALUES ($1, 'GVAULT', 'USDC')",
+            &[&merchant_id],
+        )
+        .await
+        .unwrap();
+    client
+        .execute(
+            "INSERT INTO payments (from_address, merchant_id, send_asset, send_amount, status, created_at)
+             VALUES ($1, $2, 'XLM', 100, 'completed', $3)",
+            &[&alice_address, &merchant_id, &day(1)],
+        )
+        .await
+        .unwrap();
+    client
+        .execute(
+            "INSERT INTO transfers (from_user_id, to_user_id, amount, asset, status, created_at)
+             VALUES ($1, $2, 200, 'USDC', 'pending', $3)",
+            &[&alice, &bob, &day(2)],
+        )
+        .await
+        .unwrap();
+    client
+        .execute(
+            "INSERT INTO withdrawals (user_id, destination_address, amount, asset, status, created_at)
+             VALUES ($1, 'GDEST', 300, 'USDC', 'failed', $2)",
+            &[&bob, &day(3)],
+        )
+        .await
+        .unwrap();
+
+    Seeded {
+        alice,
+        bob,
+        merchant_id,
+    }
+}
+
+fn for_user(user_id: &str) -> TransactionQueryParams {
+    TransactionQueryParams {
+        user_id: Some(user_id.to_string()),
+        limit: 50,
+        ..Default::default()
+    }
+}
+
+#[tokio::test]
+#[ignore]
+async fn test_user_filter_spans_all_transaction_kinds() {
+    let Some((transactions, pool)) = setup().await else {
+        return;
+    };
+    let seeded = seed(&pool).await;
+
+    let (alice, total) = transactions
+        .list_transactions(&for_user(&seeded.alice))
+        .await
+        .unwrap();
+    assert_eq!(total, 2);
+    assert_eq!(alice[0].kind, TransactionKind::Transfer);
+    assert_eq!(alice[0].counterparty, seeded.bob);
+    assert_eq!(alice[1].kind, TransactionKind::Payment);
+    assert_eq!(alice[1].counterparty, seeded.merchant_id);
+    assert_eq!(alice[1].user_id.as_deref(), Some(seeded.alice.as_str()));
+
+    // Bob is the recipient of the transfer and owner of the withdrawal.
+    let (bob, _) = transactions
+        .list_transactions(&for_user(&seeded.bob))
+        .await
+        .unwrap();
+    let kinds: Vec<_> = bob.iter().map(|t| t.kind).collect();
+    assert_eq!(
+        kinds,
+        [TransactionKind::Withdrawal, TransactionKind::Transfer]
+    );
+}
+
+#[tokio::test]
+#[ignore]
+async fn test_type_status_and_date_filters() {
+    let Some((transactions, pool)) = setup().await else {
+        return;
+    };
+    let seeded = seed(&pool).await;
+
+    let by_type = TransactionQueryParams {
+        kind: Some(TransactionKind::Payment),
+        ..for_user(&seeded.alice)
+    };
+    let (page, total) = transactions.list_transactions(&by_type).await.unwrap();
+    assert_eq!(total, 1);
+    assert_eq!(page[0].amount, 100);
+
+    let by_status = TransactionQueryParams {
+        status: Some("failed".to_string()),
+        ..for_user(&seeded.bob)
+    };
+    let (page, _) = transactions.list_transactions(&by_status).await.unwrap();
+    assert_eq!(page.len(), 1);
+    assert_eq!(page[0].kind, TransactionKind::Withdrawal);
+
+    let from_day_two = TransactionQueryParams {
+        from: Some(day(2)),
+        ..for_user(&seeded.alice)
+    };
+    let (page, _) = transactions.list_transactions(&from_day_two).await.unwrap();
+    assert_eq!(page.len(), 1);
+    assert_eq!(page[0].kind, TransactionKind::Transfer);
+
+    let up_to_day_one = TransactionQueryParams {
+        to: Some(day(1)),
+        ..for_user(&seeded.alice)
+    };
+    let (page, _) = transactions
+        .list_transactions(&up_to_day_one)
+        .await
+        .unwrap();
+    assert_eq!(page.len(), 1);
+    assert_eq!(page[0].kind, TransactionKind::Payment);
+
+    let backwards = TransactionQueryParams {
+        from: Some(day(3)),
+        to: Some(day(1)),
+        ..Default::default()
+    };
+    let err = transactions
+        .list_transactions(&backwards)
+        .await
+        .unwrap_err();
+    assert!(matches!(err, ApiError::Validation(_)));
+}
+
+#[tokio::test]
+#[ignore]
+async fn test_pages_are_offset_and_bounded() {
+    let Some((transactions, pool)) = setup().await else {
+        return;
+    };
+    let seeded = seed(&pool).await;
+
+    let second = TransactionQueryParams {
+        limit: 1,
+        offset: 1,
+        ..for_user(&seeded.alice)
+    };
+    let (page, total) = transactions.list_transactions(&second).await.unwrap();
+    assert_eq!(total, 2);
+    assert_eq!(page.len(), 1);
+    assert_eq!(page[0].kind, TransactionKind::Payment);
+
+    pool.get()
+        .await
+        .unwrap()
+        .execute(
+            "INSERT INTO withdrawals (user_id, destination_address, amount, asset, status)
+             SELECT $1, 'GDEST', n, 'XLM', 'completed' FROM generate_series(1, 110) AS n",
+            &[&seeded.bob],
+        )
+        .await
+        .unwrap();
+
+    let huge = TransactionQueryParams {
+        limit: 10_000,
+        ..for_user(&seeded.bob)
+    };
+    let (page, total) = transactions.list_transactions(&huge).await.unwrap();
+    assert_eq!(total, 112);
+    assert_eq!(page.len() as i64, MAX_TRANSACTION_PAGE_SIZE);
+}
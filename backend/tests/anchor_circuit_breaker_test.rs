@@ -0,0 +1,102 @@
+use std::net::SocketAddr;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use axum::{extract::State, http::StatusCode, routing::get, Json, Router};
+use blinks_backend::api_error::ApiError;
+use blinks_backend::config::Config;
+use blinks_backend::db;
+use blinks_backend::service::circuit_breaker::BreakerState;
+use blinks_backend::service::AnchorService;
+use serde_json::{json, Value};
+
+/// Whether the mock anchor is up, and how many requests reached it.
+#[derive(Default)]
+struct MockAnchorState {
+    healthy: AtomicBool,
+    hits: AtomicUsize,
+}
+
+async fn spawn_mock_anchor() -> (String, Arc<MockAnchorState>) {
+    async fn transaction(
+        State(state): State<Arc<MockAnchorState>>,
+    ) -> Result<Json<Value>, StatusCode> {
+        state.hits.fetch_add(1, Ordering::SeqCst);
+        if !state.healthy.load(Ordering::SeqCst) {
+            return Err(StatusCode::BAD_GATEWAY);
+        }
+        Ok(Json(
+            json!({ "transaction": { "id": "tx-1", "status": "completed" } }),
+        ))
+    }
+
+    let state = Arc::new(MockAnchorState::default());
+    let app = Router::new()
+        .route("/transaction", get(transaction))
+        .with_state(state.clone());
+
+    let listener = tokio::net::TcpListener::bind(SocketAddr::from(([127, 0, 0, 1], 0)))
+        .await
+        .unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move {
+        axum::serve(listener, app).await.unwrap();
+    });
+
+    (format!("http://{}", addr), state)
+}
+
+/// An anchor service pointed at the mock. Status polls never touch the
+/// database, and the pool only connects on first use, so no database is
+/// needed.
+async fn setup() -> (AnchorService, Arc<MockAnchorState>) {
+    let (anchor_url, state) = spawn_mock_anchor().await;
+
+    let mut config = Config::default();
+    config.anchor_config.sep24_url = anchor_url;
+    config.anchor_config.circuit_breaker.failure_threshold = 2;
+    config.anchor_config.circuit_breaker.cooldown_seconds = 1;
+
+    let pool = Arc::new(db::create_pool(&config.database.url).await.unwrap());
+    (AnchorService::new(pool, config), state)
+}
+
+#[tokio::test]
+async fn test_repeated_failures_open_the_breaker() {
+    let (anchor, state) = setup().await;
+
+    for _ in 0..2 {
+        let err = anchor.poll_anchor_tx_status("tx-1").await.unwrap_err();
+        assert!(
+            matches!(&err, ApiError::ServiceUnavailable(msg) if msg.contains("502")),
+            "{:?}",
+            err
+        );
+    }
+    assert_eq!(anchor.breaker_state(), BreakerState::Open);
+
+    let err = anchor.poll_anchor_tx_status("tx-1").await.unwrap_err();
+    assert!(matches!(err, ApiError::ServiceUnavailable(_)), "{:?}", err);
+    // Fast-failed without reaching the anchor.
+    assert_eq!(state.hits.load(Ordering::SeqCst), 2);
+}
+
+#[tokio::test]
+async fn test_successful_probe_closes_the_breaker() {
+    let (anchor, state) = setup().await;
+
+    for _ in 0..2 {
+        anchor.poll_anchor_tx_status("tx-1").await.unwrap_err();
+    }
+    assert_eq!(anchor.breaker_state(), BreakerState::Open);
+
+    state.healthy.store(true, Ordering::SeqCst);
+    tokio::time::sleep(Duration::from_millis(1100)).await;
+    assert_eq!(anchor.breaker_state(), BreakerState::HalfOpen);
+
+    anchor.poll_anchor_tx_status("tx-1").await.unwrap();
+    assert_eq!(anchor.breaker_state(), BreakerState::Closed);
+    anchor.poll_anchor_tx_status("tx-1").await.unwrap();
+    assert_eq!(state.hits.load(Ordering::SeqCst), 4);
+}
@@ -0,0 +1,96 @@
+use std::sync::Arc;
+
+use axum::{http::StatusCode, response::IntoResponse};
+use blinks_backend::api_error::ApiError;
+use blinks_backend::config::Config;
+use blinks_backend::db;
+use blinks_backend::service::AnchorService;
+use httpmock::{Method::POST, MockServer};
+use serde_json::json;
+
+const ACCOUNT: &str = "GBRPYHIL2CI3FNQ4BXLFMNDLFJUNPU2HY3ZMFSHONUCEOASW7QC7OX2H";
+
+/// An anchor service whose SEP-24 endpoint answers with `status` and `body`.
+/// Interactive URL requests never touch the database, and the pool only
+/// connects on first use, so no database is needed.
+async fn anchor_answering(
+    server: &MockServer,
+    status: u16,
+    body: serde_json::Value,
+) -> AnchorService {
+    server
+        .mock_async(|when, then| {
+            when.method(POST).path("/transactions/withdraw/interactive");
+            then.status(status).json_body(body);
+        })
+        .await;
+
+    let mut config = Config::default();
+    config.anchor_config.sep24_url = server.base_url();
+
+    let pool = Arc::new(db::create_pool(&config.database.url).await.unwrap());
+    AnchorService::new(pool, config)
+}
+
+async fn interactive_url_error(anchor: &AnchorService) -> ApiError {
+    anchor
+        .get_sep24_interactive_url("user-1", ACCOUNT, "USDC", 10_000_000)
+        .await
+        .unwrap_err()
+}
+
+#[tokio::test]
+async fn test_anchor_rejection_becomes_a_bad_request() {
+    let server = MockServer::start_async().await;
+    let anchor = anchor_answering(
+        &server,
+        400,
+        json!({ "error": "asset USDC is not enabled for withdrawal" }),
+    )
+    .await;
+
+    let err = interactive_url_error(&anchor).await;
+
+    assert!(
+        matches!(&err, ApiError::Validation(msg) if msg.contains("asset USDC is not enabled")),
+        "{:?}",
+        err
+    );
+    assert_eq!(err.into_response().status(), StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn test_anchor_refusing_our_credentials_becomes_service_unavailable() {
+    let server = MockServer::start_async().await;
+    let anchor = anchor_answering(&server, 403, json!({ "error": "invalid jwt" })).await;
+
+    let err = interactive_url_error(&anchor).await;
+
+    assert!(
+        matches!(&err, ApiError::ServiceUnavailable(msg) if msg.contains("invalid jwt")),
+        "{:?}",
+        err
+    );
+    assert_eq!(
+        err.into_response().status(),
+        StatusCode::SERVICE_UNAVAILABLE
+    );
+}
+
+#[tokio::test]
+async fn test_anchor_outage_becomes_service_unavailable() {
+    let server = MockServer::start_async().await;
+    let anchor = anchor_answering(&server, 503, json!({ "error": "maintenance window" })).await;
+
+    let err = interactive_url_error(&anchor).await;
+
+    assert!(
+        matches!(&err, ApiError::ServiceUnavailable(msg) if msg.contains("maintenance window")),
+        "{:?}",
+        err
+    );
+    assert_eq!(
+        err.into_response().status(),
+        StatusCode::SERVICE_UNAVAILABLE
+    );
+}
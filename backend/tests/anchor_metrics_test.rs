@@ -0,0 +1,48 @@
+use std::net::SocketAddr;
+use std::sync::Arc;
+
+use axum::{routing::get, Json, Router};
+use blinks_backend::config::Config;
+use blinks_backend::db;
+use blinks_backend::service::anchor_service::KycStatus;
+use blinks_backend::service::metrics_service::{ANCHOR_REQUESTS_TOTAL, KYC_STATUS_TOTAL};
+use blinks_backend::service::AnchorService;
+use serde_json::json;
+
+/// Stand-in SEP-12 endpoint that clears every account.
+async fn spawn_mock_anchor() -> String {
+    let app = Router::new().route(
+        "/kyc",
+        get(|| async { Json(json!({ "status": "CLEARED" })) }),
+    );
+
+    let listener = tokio::net::TcpListener::bind(SocketAddr::from(([127, 0, 0, 1], 0)))
+        .await
+        .unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move {
+        axum::serve(listener, app).await.unwrap();
+    });
+
+    format!("http://{}", addr)
+}
+
+#[tokio::test]
+async fn test_kyc_check_records_resolved_status() {
+    let mut config = Config::default();
+    config.anchor_config.sep24_url = spawn_mock_anchor().await;
+    // KYC checks never touch the database, and the pool only connects on
+    // first use, so no database is needed.
+    let pool = Arc::new(db::create_pool(&config.database.url).await.unwrap());
+    let anchor = AnchorService::new(pool, config);
+
+    let cleared = KYC_STATUS_TOTAL.with_label_values(&["CLEARED"]);
+    let requests = ANCHOR_REQUESTS_TOTAL.with_label_values(&["kyc", "200"]);
+    let (cleared_before, requests_before) = (cleared.get(), requests.get());
+
+    let status = anchor.check_kyc_status("user-1", "GACCOUNT").await.unwrap();
+    assert_eq!(status, KycStatus::Cleared);
+
+    assert_eq!(cleared.get(), cleared_before + 1.0);
+    assert_eq!(requests.get(), requests_before + 1.0);
+}
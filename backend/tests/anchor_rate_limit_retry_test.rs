@@ -0,0 +1,117 @@
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use axum::{
+    extract::State,
+    http::{header::RETRY_AFTER, StatusCode},
+    response::{IntoResponse, Response},
+    routing::get,
+    Json, Router,
+};
+use blinks_backend::api_error::ApiError;
+use blinks_backend::config::Config;
+use blinks_backend::db;
+use blinks_backend::service::anchor_service::KycStatus;
+use blinks_backend::service::AnchorService;
+use serde_json::json;
+
+/// When each request reached the mock anchor.
+type Arrivals = Arc<Mutex<Vec<Instant>>>;
+
+/// Stand-in SEP-12 endpoint that rate limits the first `limited` requests
+/// with `Retry-After: {retry_after}` and clears every later one.
+async fn spawn_mock_anchor(retry_after: &'static str, limited: usize) -> (String, Arrivals) {
+    async fn kyc(
+        State((arrivals, retry_after, limited)): State<(Arrivals, &'static str, usize)>,
+    ) -> Response {
+        let rate_limited = {
+            let mut arrivals = arrivals.lock().unwrap();
+            arrivals.push(Instant::now());
+            arrivals.len() <= limited
+        };
+        if rate_limited {
+            (
+                StatusCode::TOO_MANY_REQUESTS,
+                [(RETRY_AFTER, retry_after)],
+                Json(json!({ "error": "slow down" })),
+            )
+                .into_response()
+        } else {
+            Json(json!({ "status": "CLEARED" })).into_response()
+        }
+    }
+
+    let arrivals = Arrivals::default();
+    let app =
+        Router::new()
+            .route("/kyc", get(kyc))
+            .with_state((arrivals.clone(), retry_after, limited));
+
+    let listener = tokio::net::TcpListener::bind(SocketAddr::from(([127, 0, 0, 1], 0)))
+        .await
+        .unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move {
+        axum::serve(listener, app).await.unwrap();
+    });
+
+    (format!("http://{}", addr), arrivals)
+}
+
+/// KYC checks never touch the database, and the pool only connects on
+/// first use, so no database is needed.
+async fn setup(retry_after: &'static str, limited: usize) -> (AnchorService, Arrivals) {
+    let (anchor_url, arrivals) = spawn_mock_anchor(retry_after, limited).await;
+
+    let mut config = Config::default();
+    config.anchor_config.sep24_url = anchor_url;
+    config.anchor_config.rate_limit_retry.max_wait_seconds = 5;
+
+    let pool = Arc::new(db::create_pool(&config.database.url).await.unwrap());
+    (AnchorService::new(pool, config), arrivals)
+}
+
+#[tokio::test]
+async fn test_rate_limited_call_is_retried_after_retry_after() {
+    let (anchor, arrivals) = setup("2", 1).await;
+
+    let status = anchor.check_kyc_status("user-1", "GACCOUNT").await.unwrap();
+
+    assert_eq!(status, KycStatus::Cleared);
+    let arrivals = arrivals.lock().unwrap();
+    assert_eq!(arrivals.len(), 2);
+    let waited = arrivals[1] - arrivals[0];
+    assert!(
+        waited >= Duration::from_millis(1900) && waited < Duration::from_secs(3),
+        "retried after {:?}",
+        waited
+    );
+}
+
+#[tokio::test]
+async fn test_retry_after_beyond_the_budget_is_not_retried() {
+    let (anchor, arrivals) = setup("60", 1).await;
+
+    let err = anchor
+        .check_kyc_status("user-1", "GACCOUNT")
+        .await
+        .unwrap_err();
+
+    assert!(matches!(err, ApiError::ServiceUnavailable(_)), "{:?}", err);
+    assert_eq!(arrivals.lock().unwrap().len(), 1);
+}
+
+#[tokio::test]
+async fn test_retries_stop_once_their_waits_would_exceed_the_budget() {
+    // Two 3s waits would add up to more than the 5s budget.
+    let (anchor, arrivals) = setup("3", 2).await;
+
+    let err = anchor
+        .check_kyc_status("user-1", "GACCOUNT")
+        .await
+        .unwrap_err();
+
+    assert!(matches!(err, ApiError::ServiceUnavailable(_)), "{:?}", err);
+    assert_eq!(arrivals.lock().unwrap().len(), 2);
+}
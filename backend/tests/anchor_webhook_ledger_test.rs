@@ -0,0 +1,403 @@
+use std::sync::{Arc, Mutex};
+
+use async_trait::async_trait;
+use blinks_backend::config::Config;
+use blinks_backend::db;
+use blinks_backend::job_types::JobPayload;
+use blinks_backend::queue::JobEnqueuer;
+use blinks_backend::service::anchor_service::{AnchorWebhookEvent, WebhookDisposition};
+use blinks_backend::service::AnchorService;
+use uuid::Uuid;
+
+// Note: These tests require a running database using the config.
+// Run with: cargo test --test anchor_webhook_ledger_test -- --ignored
+
+/// Withdrawals with this destination reject status updates, standing in for
+/// the database failing mid-webhook.
+const FAILING_DESTINATION: &str = "GFAIL-WEBHOOK";
+
+/// Job queue stand-in that keeps enqueued jobs for inspection.
+#[derive(Default)]
+struct RecordingQueue {
+    jobs: Mutex<Vec<JobPayload>>,
+}
+
+#[async_trait]
+impl JobEnqueuer for RecordingQueue {
+    async fn enqueue(&self, job: JobPayload) -> anyhow::Result<()> {
+        self.jobs.lock().unwrap().push(job);
+        Ok(())
+    }
+}
+
+impl RecordingQueue {
+    fn count_for(&self, withdrawal_id: &str) -> usize {
+        self.jobs
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|job| job.payload["withdrawal_id"] == withdrawal_id)
+            .count()
+    }
+}
+
+async fn setup() -> Option<(AnchorService, Arc<deadpool_postgres::Pool>)> {
+    let config = match Config::load() {
+        Ok(c) => c,
+        Err(_) => {
+            println!("Skipping test: No config found");
+            return None;
+        }
+    };
+
+    db::run_migrations(&config.database.url)
+        .await
+        .expect("Failed to run migrations");
+    let pool = Arc::new(
+        db::create_pool(&config.database.url)
+            .await
+            .expect("Failed to create pool"),
+    );
+
+    let client = pool.get().await.unwrap();
+    client
+        .batch_execute(&format!(
+            r#"
+            CREATE OR REPLACE FUNCTION test_fail_withdrawal_update() RETURNS trigger AS $$
+            BEGIN
+                RAISE EXCEPTION 'simulated database failure';
+            END;
+            $$ LANGUAGE plpgsql;
+
+            CREATE OR REPLACE TRIGGER test_fail_withdrawal_update
+                BEFORE UPDATE OF status ON withdrawals
+                FOR EACH ROW
+                WHEN (OLD.destination_address = '{}')
+                EXECUTE FUNCTION test_fail_withdrawal_update();
+            "#,
+            FAILING_DESTINATION
+        ))
+        .await
+        .unwrap();
+
+    Some((AnchorService::new(pool.clone(), config), pool))
+}
+
+/// Seed a processing withdrawal whose status updates fail until `recover`.
+async fn seed_failing_withdrawal(pool: &deadpool_postgres::Pool) -> (String, String) {
+    let suffix = Uuid::new_v4().simple().to_string();
+    let user_id = format!("ledger-{}", suffix);
+    let anchor_tx_id = format!("anchor-{}", suffix);
+
+    let client = pool.get().await.unwrap();
+    client
+        .execute(
+            "INSERT INTO users (user_id, stellar_address, pin_hash) VALUES ($1, $2, 'x')",
+            &[&user_id, &format!("G{}", suffix.to_uppercase())],
+        )
+        .await
+        .unwrap();
+    let id = client
+        .query_one(
+            "INSERT INTO withdrawals
+                (user_id, destination_address, amount, asset, status, anchor_tx_id)
+             VALUES ($1, $2, 500, 'USDC', 'processing', $3)
+             RETURNING id::text",
+            &[&user_id, &FAILING_DESTINATION, &anchor_tx_id],
+        )
+        .await
+        .unwrap()
+        .get(0);
+
+    (id, anchor_tx_id)
+}
+
+/// Let status updates for the withdrawal succeed again.
+async fn recover(pool: &deadpool_postgres::Pool, withdrawal_id: &str) {
+    pool.get()
+        .await
+        .unwrap()
+        .execute(
+            "UPDATE withdrawals SET destination_address = 'GDEST' WHERE id = $1::text::uuid",
+            &[&withdrawal_id],
+        )
+        .await
+        .unwrap();
+}
+
+/// Age the event's ledger entry past the replay grace period.
+async fn backdate(pool: &deadpool_postgres::Pool, event: &AnchorWebhookEvent) {
+    pool.get()
+        .await
+        .unwrap()
+        .execute(
+            "UPDATE anchor_webhooks SET received_at = NOW() - INTERVAL '1 hour'
+             WHERE transaction_id = $1 AND status = $2",
+            &[&event.transaction_id, &event.status],
+        )
+        .await
+        .unwrap();
+}
+
+/// `(processing_status, attempts)` of the ledger entry for an event.
+async fn ledger_entry(pool: &deadpool_postgres::Pool, event: &AnchorWebhookEvent) -> (String, i32) {
+    let row = pool
+        .get()
+        .await
+        .unwrap()
+        .query_one(
+            "SELECT processing_status, attempts FROM anchor_webhooks
+             WHERE transaction_id = $1 AND status = $2",
+            &[&event.transaction_id, &event.status],
+        )
+        .await
+        .unwrap();
+    (row.get(0), row.get(1))
+}
+
+async fn withdrawal_status(pool: &deadpool_postgres::Pool, withdrawal_id: &str) -> String {
+    pool.get()
+        .await
+        .unwrap()
+        .query_one(
+            "SELECT status FROM withdrawals WHERE id = $1::text::uuid",
+            &[&withdrawal_id],
+        )
+        .await
+        .unwrap()
+        .get(0)
+}
+
+fn completed(anchor_tx_id: &str) -> AnchorWebhookEvent {
+    AnchorWebhookEvent {
+        transaction_id: anchor_tx_id.to_string(),
+        status: "completed".to_string(),
+        message: None,
+    }
+}
+
+#[tokio::test]
+#[ignore]
+async fn test_failed_webhook_is_processed_once_on_anchor_retry() {
+    let Some((anchor, pool)) = setup().await else {
+        return;
+    };
+    let queue = RecordingQueue::default();
+    let (withdrawal_id, anchor_tx_id) = seed_failing_withdrawal(&pool).await;
+    let event = completed(&anchor_tx_id);
+
+    assert!(anchor.handle_webhook(&event, &queue).await.is_err());
+    assert_eq!(ledger_entry(&pool, &event).await, ("failed".to_string(), 1));
+    assert_eq!(withdrawal_status(&pool, &withdrawal_id).await, "processing");
+
+    recover(&pool, &withdrawal_id).await;
+    let retried = anchor.handle_webhook(&event, &queue).await.unwrap();
+    assert_eq!(retried, WebhookDisposition::Processed);
+    assert_eq!(
+        ledger_entry(&pool, &event).await,
+        ("processed".to_string(), 2)
+    );
+    assert_eq!(withdrawal_status(&pool, &withdrawal_id).await, "completed");
+
+    // A further redelivery and a replay run leave it alone.
+    let again = anchor.handle_webhook(&event, &queue).await.unwrap();
+    assert_eq!(again, WebhookDisposition::Duplicate);
+    anchor.replay_webhooks(&queue).await.unwrap();
+
+    assert_eq!(ledger_entry(&pool, &event).await.0, "processed");
+    assert_eq!(queue.count_for(&withdrawal_id), 1);
+}
+
+#[tokio::test]
+#[ignore]
+async fn test_failed_webhook_is_replayed_once() {
+    let Some((anchor, pool)) = setup().await else {
+        return;
+    };
+    let queue = RecordingQueue::default();
+    let (withdrawal_id, anchor_tx_id) = seed_failing_withdrawal(&pool).await;
+    let event = completed(&anchor_tx_id);
+
+    assert!(anchor.handle_webhook(&event, &queue).await.is_err());
+
+    recover(&pool, &withdrawal_id).await;
+    let summary = anchor.replay_webhooks(&queue).await.unwrap();
+    assert!(summary.replayed >= 1);
+    assert_eq!(
+        ledger_entry(&pool, &event).await,
+        ("processed".to_string(), 2)
+    );
+    assert_eq!(withdrawal_status(&pool, &withdrawal_id).await, "completed");
+
+    // The anchor's own retry arrives after the replay.
+    let retried = anchor.handle_webhook(&event, &queue).await.unwrap();
+    assert_eq!(retried, WebhookDisposition::Duplicate);
+    anchor.replay_webhooks(&queue).await.unwrap();
+
+    assert_eq!(
+        ledger_entry(&pool, &event).await,
+        ("processed".to_string(), 3)
+    );
+    assert_eq!(queue.count_for(&withdrawal_id), 1);
+}
+
+#[tokio::test]
+#[ignore]
+async fn test_webhook_for_unknown_transaction_is_replayed_once_it_exists() {
+    let Some((anchor, pool)) = setup().await else {
+        return;
+    };
+    let queue = RecordingQueue::default();
+    let (withdrawal_id, anchor_tx_id) = seed_failing_withdrawal(&pool).await;
+    recover(&pool, &withdrawal_id).await;
+    let event = completed(&format!("early-{}", anchor_tx_id));
+
+    // The anchor reports on the transaction before we have stored it
+    let disposition = anchor.handle_webhook(&event, &queue).await.unwrap();
+    assert_eq!(disposition, WebhookDisposition::Unmatched);
+    assert_eq!(
+        ledger_entry(&pool, &event).await,
+        ("pending".to_string(), 1)
+    );
+
+    let client = pool.get().await.unwrap();
+    client
+        .execute(
+            "UPDATE withdrawals SET anchor_tx_id = $1 WHERE id = $2::text::uuid",
+            &[&event.transaction_id, &withdrawal_id],
+        )
+        .await
+        .unwrap();
+    backdate(&pool, &event).await;
+
+    let summary = anchor.replay_webhooks(&queue).await.unwrap();
+    assert!(summary.replayed >= 1);
+    assert_eq!(
+        ledger_entry(&pool, &event).await,
+        ("processed".to_string(), 2)
+    );
+    assert_eq!(withdrawal_status(&pool, &withdrawal_id).await, "completed");
+}
+
+#[tokio::test]
+#[ignore]
+async fn test_webhook_out_of_attempts_is_dead_lettered() {
+    let Some((anchor, pool)) = setup().await else {
+        return;
+    };
+    let queue = RecordingQueue::default();
+    let event = completed(&format!("unknown-{}", Uuid::new_v4()));
+
+    anchor.handle_webhook(&event, &queue).await.unwrap();
+    pool.get()
+        .await
+        .unwrap()
+        .execute(
+            "UPDATE anchor_webhooks SET attempts = 1000 WHERE transaction_id = $1",
+            &[&event.transaction_id],
+        )
+        .await
+        .unwrap();
+    backdate(&pool, &event).await;
+
+    let summary = anchor.replay_webhooks(&queue).await.unwrap();
+    assert!(summary.dead_lettered >= 1);
+    assert_eq!(
+        ledger_entry(&pool, &event).await,
+        ("dead_letter".to_string(), 1001)
+    );
+
+    // Replays leave it alone; a redelivery is matched again but, still
+    // finding nothing, leaves it dead-lettered
+    anchor.replay_webhooks(&queue).await.unwrap();
+    assert_eq!(
+        ledger_entry(&pool, &event).await,
+        ("dead_letter".to_string(), 1001)
+    );
+    let again = anchor.handle_webhook(&event, &queue).await.unwrap();
+    assert_eq!(again, WebhookDisposition::Unmatched);
+    assert_eq!(
+        ledger_entry(&pool, &event).await,
+        ("dead_letter".to_string(), 1002)
+    );
+}
+
+#[tokio::test]
+#[ignore]
+async fn test_dead_lettered_webhook_is_processed_on_redelivery() {
+    let Some((anchor, pool)) = setup().await else {
+        return;
+    };
+    let queue = RecordingQueue::default();
+    let (withdrawal_id, anchor_tx_id) = seed_failing_withdrawal(&pool).await;
+    recover(&pool, &withdrawal_id).await;
+    let event = completed(&format!("late-{}", anchor_tx_id));
+
+    anchor.handle_webhook(&event, &queue).await.unwrap();
+    let client = pool.get().await.unwrap();
+    client
+        .execute(
+            "UPDATE anchor_webhooks SET processing_status = 'dead_letter', attempts = 1000
+             WHERE transaction_id = $1",
+            &[&event.transaction_id],
+        )
+        .await
+        .unwrap();
+    client
+        .execute(
+            "UPDATE withdrawals SET anchor_tx_id = $1 WHERE id = $2::text::uuid",
+            &[&event.transaction_id, &withdrawal_id],
+        )
+        .await
+        .unwrap();
+
+    // The anchor retrying after we gave up still settles the withdrawal
+    let disposition = anchor.handle_webhook(&event, &queue).await.unwrap();
+    assert_eq!(disposition, WebhookDisposition::Processed);
+    assert_eq!(
+        ledger_entry(&pool, &event).await,
+        ("processed".to_string(), 1001)
+    );
+    assert_eq!(withdrawal_status(&pool, &withdrawal_id).await, "completed");
+    assert_eq!(queue.count_for(&withdrawal_id), 1);
+
+    let again = anchor.handle_webhook(&event, &queue).await.unwrap();
+    assert_eq!(again, WebhookDisposition::Duplicate);
+}
+
+#[tokio::test]
+#[ignore]
+async fn test_duplicate_webhook_is_recognized_and_not_reapplied() {
+    let Some((anchor, pool)) = setup().await else {
+        return;
+    };
+    let queue = RecordingQueue::default();
+    let (withdrawal_id, anchor_tx_id) = seed_failing_withdrawal(&pool).await;
+    recover(&pool, &withdrawal_id).await;
+    let event = completed(&anchor_tx_id);
+
+    let first = anchor.handle_webhook(&event, &queue).await.unwrap();
+    assert_eq!(first, WebhookDisposition::Processed);
+    assert_eq!(withdrawal_status(&pool, &withdrawal_id).await, "completed");
+
+    // Put the withdrawal back where the event would move it from, so a
+    // re-application would show.
+    pool.get()
+        .await
+        .unwrap()
+        .execute(
+            "UPDATE withdrawals SET status = 'processing' WHERE id = $1::text::uuid",
+            &[&withdrawal_id],
+        )
+        .await
+        .unwrap();
+
+    let duplicate = anchor.handle_webhook(&event, &queue).await.unwrap();
+    assert_eq!(duplicate, WebhookDisposition::Duplicate);
+    assert_eq!(
+        ledger_entry(&pool, &event).await,
+        ("processed".to_string(), 2)
+    );
+    assert_eq!(withdrawal_status(&pool, &withdrawal_id).await, "processing");
+    assert_eq!(queue.count_for(&withdrawal_id), 1);
+}
@@ -0,0 +1,236 @@
+use std::sync::{Arc, Mutex};
+
+use async_trait::async_trait;
+use blinks_backend::config::Config;
+use blinks_backend::db;
+use blinks_backend::job_types::JobPayload;
+use blinks_backend::queue::JobEnqueuer;
+use blinks_backend::service::anchor_service::{
+    AnchorTransaction, AnchorWebhookEvent, WebhookDisposition,
+};
+use blinks_backend::service::AnchorService;
+use uuid::Uuid;
+
+// Note: These tests require a running database using the config.
+// Run with: cargo test --test anchor_webhook_routing_test -- --ignored
+
+/// Job queue stand-in that keeps enqueued jobs for inspection.
+#[derive(Default)]
+struct RecordingQueue {
+    jobs: Mutex<Vec<JobPayload>>,
+}
+
+#[async_trait]
+impl JobEnqueuer for RecordingQueue {
+    async fn enqueue(&self, job: JobPayload) -> anyhow::Result<()> {
+        self.jobs.lock().unwrap().push(job);
+        Ok(())
+    }
+}
+
+async fn setup() -> Option<(AnchorService, Arc<deadpool_postgres::Pool>)> {
+    let config = match Config::load() {
+        Ok(c) => c,
+        Err(_) => {
+            println!("Skipping test: No config found");
+            return None;
+        }
+    };
+
+    db::run_migrations(&config.database.url)
+        .await
+        .expect("Failed to run migrations");
+    let pool = Arc::new(
+        db::create_pool(&config.database.url)
+            .await
+            .expect("Failed to create pool"),
+    );
+
+    Some((AnchorService::new(pool.clone(), config), pool))
+}
+
+/// Seed a user with one processing deposit and one processing withdrawal,
+/// returning `(deposit_id, deposit_anchor_tx_id, withdrawal_id, withdrawal_anchor_tx_id)`.
+async fn seed_transactions(pool: &deadpool_postgres::Pool) -> (String, String, String, String) {
+    let suffix = Uuid::new_v4().simple().to_string();
+    let user_id = format!("routing-{}", suffix);
+    let deposit_tx = format!("anchor-dep-{}", suffix);
+    let withdrawal_tx = format!("anchor-wd-{}", suffix);
+
+    let client = pool.get().await.unwrap();
+    client
+        .execute(
+            "INSERT INTO users (user_id, stellar_address, pin_hash) VALUES ($1, $2, 'x')",
+            &[&user_id, &format!("G{}", suffix.to_uppercase())],
+        )
+        .await
+        .unwrap();
+    let deposit_id = client
+        .query_one(
+            "INSERT INTO deposits (user_id, amount, asset, status, anchor_tx_id)
+             VALUES ($1, 300, 'USDC', 'processing', $2)
+             RETURNING id::text",
+            &[&user_id, &deposit_tx],
+        )
+        .await
+        .unwrap()
+        .get(0);
+    let withdrawal_id = client
+        .query_one(
+            "INSERT INTO withdrawals
+                (user_id, destination_address, amount, asset, status, anchor_tx_id)
+             VALUES ($1, 'GDEST', 400, 'USDC', 'processing', $2)
+             RETURNING id::text",
+            &[&user_id, &withdrawal_tx],
+        )
+        .await
+        .unwrap()
+        .get(0);
+
+    (deposit_id, deposit_tx, withdrawal_id, withdrawal_tx)
+}
+
+async fn status_of(pool: &deadpool_postgres::Pool, table: &str, id: &str) -> String {
+    pool.get()
+        .await
+        .unwrap()
+        .query_one(
+            &format!("SELECT status FROM {} WHERE id = $1::text::uuid", table),
+            &[&id],
+        )
+        .await
+        .unwrap()
+        .get(0)
+}
+
+/// USDC balance of the user who made `deposit_id`.
+async fn usdc_balance(pool: &deadpool_postgres::Pool, deposit_id: &str) -> i64 {
+    pool.get()
+        .await
+        .unwrap()
+        .query_one(
+            "SELECT COALESCE(SUM(b.amount), 0)::bigint FROM balances b
+             JOIN deposits d ON d.user_id = b.owner_id AND b.asset = 'USDC'
+             WHERE d.id = $1::text::uuid",
+            &[&deposit_id],
+        )
+        .await
+        .unwrap()
+        .get(0)
+}
+
+fn event(anchor_tx_id: &str, status: &str) -> AnchorWebhookEvent {
+    AnchorWebhookEvent {
+        transaction_id: anchor_tx_id.to_string(),
+        status: status.to_string(),
+        message: None,
+    }
+}
+
+#[tokio::test]
+#[ignore]
+async fn test_deposit_webhook_updates_deposit() {
+    let Some((anchor, pool)) = setup().await else {
+        return;
+    };
+    let queue = RecordingQueue::default();
+    let (deposit_id, deposit_tx, withdrawal_id, _) = seed_transactions(&pool).await;
+
+    assert_eq!(
+        anchor
+            .find_transaction_by_anchor_tx_id(&deposit_tx)
+            .await
+            .unwrap(),
+        Some(AnchorTransaction::Deposit(deposit_id.clone()))
+    );
+
+    let disposition = anchor
+        .handle_webhook(&event(&deposit_tx, "completed"), &queue)
+        .await
+        .unwrap();
+
+    assert_eq!(disposition, WebhookDisposition::Processed);
+    assert_eq!(status_of(&pool, "deposits", &deposit_id).await, "completed");
+    assert_eq!(
+        status_of(&pool, "withdrawals", &withdrawal_id).await,
+        "processing"
+    );
+    assert!(queue.jobs.lock().unwrap().is_empty());
+
+    // The completed deposit funds the user, once.
+    assert_eq!(usdc_balance(&pool, &deposit_id).await, 300);
+    anchor
+        .apply_deposit_status(&deposit_id, "completed")
+        .await
+        .unwrap();
+    assert_eq!(usdc_balance(&pool, &deposit_id).await, 300);
+}
+
+#[tokio::test]
+#[ignore]
+async fn test_withdrawal_webhook_updates_withdrawal() {
+    let Some((anchor, pool)) = setup().await else {
+        return;
+    };
+    let queue = RecordingQueue::default();
+    let (deposit_id, _, withdrawal_id, withdrawal_tx) = seed_transactions(&pool).await;
+
+    assert_eq!(
+        anchor
+            .find_transaction_by_anchor_tx_id(&withdrawal_tx)
+            .await
+            .unwrap(),
+        Some(AnchorTransaction::Withdrawal(withdrawal_id.clone()))
+    );
+
+    let disposition = anchor
+        .handle_webhook(&event(&withdrawal_tx, "error"), &queue)
+        .await
+        .unwrap();
+
+    assert_eq!(disposition, WebhookDisposition::Processed);
+    assert_eq!(
+        status_of(&pool, "withdrawals", &withdrawal_id).await,
+        "failed"
+    );
+    assert_eq!(
+        status_of(&pool, "deposits", &deposit_id).await,
+        "processing"
+    );
+    assert_eq!(queue.jobs.lock().unwrap().len(), 1);
+}
+
+#[tokio::test]
+#[ignore]
+async fn test_unknown_transaction_is_left_untouched() {
+    let Some((anchor, pool)) = setup().await else {
+        return;
+    };
+    let queue = RecordingQueue::default();
+    let (deposit_id, _, withdrawal_id, _) = seed_transactions(&pool).await;
+    let unknown = format!("unknown-{}", Uuid::new_v4());
+
+    assert_eq!(
+        anchor
+            .find_transaction_by_anchor_tx_id(&unknown)
+            .await
+            .unwrap(),
+        None
+    );
+
+    let disposition = anchor
+        .handle_webhook(&event(&unknown, "completed"), &queue)
+        .await
+        .unwrap();
+
+    assert_eq!(disposition, WebhookDisposition::Unmatched);
+    assert_eq!(
+        status_of(&pool, "deposits", &deposit_id).await,
+        "processing"
+    );
+    assert_eq!(
+        status_of(&pool, "withdrawals", &withdrawal_id).await,
+        "processing"
+    );
+    assert!(queue.jobs.lock().unwrap().is_empty());
+}
@@ -0,0 +1,71 @@
+use std::sync::Arc;
+
+use blinks_backend::api_error::ApiError;
+use blinks_backend::config::Config;
+use blinks_backend::db;
+use blinks_backend::service::AnchorService;
+use ring::hmac;
+
+const PAYLOAD: &[u8] = br#"{"transaction":{"id":"tx-1","status":"completed"}}"#;
+const CURRENT_SECRET: &str = "current-webhook-secret";
+const PREVIOUS_SECRET: &str = "previous-webhook-secret";
+
+fn sign(secret: &str) -> String {
+    let key = hmac::Key::new(hmac::HMAC_SHA256, secret.as_bytes());
+    hex::encode(hmac::sign(&key, PAYLOAD).as_ref())
+}
+
+/// Signature checks never touch the database, and the pool only connects
+/// on first use, so no database is needed.
+async fn anchor(previous_webhook_secrets: Option<&str>) -> AnchorService {
+    let mut config = Config::default();
+    config.anchor_config.webhook_secret = CURRENT_SECRET.to_string();
+    config.anchor_config.previous_webhook_secrets = previous_webhook_secrets.map(str::to_string);
+
+    let pool = Arc::new(db::create_pool(&config.database.url).await.unwrap());
+    AnchorService::new(pool, config)
+}
+
+#[tokio::test]
+async fn test_current_secret_verifies() {
+    let anchor = anchor(Some(PREVIOUS_SECRET)).await;
+
+    anchor
+        .verify_webhook_signature(PAYLOAD, &sign(CURRENT_SECRET))
+        .unwrap();
+    anchor
+        .verify_webhook_signature(PAYLOAD, &format!("sha256={}", sign(CURRENT_SECRET)))
+        .unwrap();
+}
+
+#[tokio::test]
+async fn test_previous_secret_verifies_during_rotation() {
+    let anchor = anchor(Some(&format!("older-webhook-secret, {}", PREVIOUS_SECRET))).await;
+
+    anchor
+        .verify_webhook_signature(PAYLOAD, &sign(PREVIOUS_SECRET))
+        .unwrap();
+}
+
+#[tokio::test]
+async fn test_previous_secret_is_rejected_once_rotation_ends() {
+    let anchor = anchor(None).await;
+
+    let err = anchor
+        .verify_webhook_signature(PAYLOAD, &sign(PREVIOUS_SECRET))
+        .unwrap_err();
+    assert!(matches!(err, ApiError::Authentication(_)), "{:?}", err);
+}
+
+#[tokio::test]
+async fn test_unknown_secret_or_tampered_payload_is_rejected() {
+    let anchor = anchor(Some(PREVIOUS_SECRET)).await;
+
+    assert!(anchor
+        .verify_webhook_signature(PAYLOAD, &sign("some-other-secret"))
+        .is_err());
+    assert!(anchor
+        .verify_webhook_signature(b"{}", &sign(CURRENT_SECRET))
+        .is_err());
+    assert!(anchor.verify_webhook_signature(PAYLOAD, "").is_err());
+}
@@ -0,0 +1,236 @@
+use std::sync::{Arc, Mutex};
+
+use axum::body::Bytes;
+use blinks_backend::config::Config;
+use blinks_backend::db;
+use blinks_backend::models::CreateAuditLogParams;
+use blinks_backend::service::{AuditService, StorageService};
+use blinks_backend::storage::{StorageAdapter, StoredFile};
+
+// Note: These tests require a running database using the config.
+// Run with: cargo test --test audit_retention_test -- --ignored
+
+type StorageResult<T> = Result<T, Box<dyn std::error::Error + Send + Sync>>;
+
+/// Storage adapter that keeps uploads in memory so tests can inspect them.
+#[derive(Default)]
+struct RecordingStorage {
+    uploads: Mutex<Vec<(String, Bytes)>>,
+}
+
+impl StorageAdapter for RecordingStorage {
+    fn upload(
+        &self,
+        data: Bytes,
+        original_name: &str,
+        mime_type: &str,
+        _owner_id: Option<&str>,
+    ) -> StorageResult<StoredFile> {
+        let size = data.len() as u64;
+        self.uploads
+            .lock()
+            .unwrap()
+            .push((original_name.to_string(), data));
+        Ok(StoredFile {
+            id: format!("archive-{}", original_name),
+            original_name: original_name.to_string(),
+            mime_type: mime_type.to_string(),
+            size,
+            url: String::new(),
+            owner_id: None,
+        })
+    }
+
+    fn get(&self, _id: &str) -> StorageResult<Option<StoredFile>> {
+        Ok(None)
+    }
+
+    fn read(&self, _id: &str) -> StorageResult<Option<Vec<u8>>> {
+        Ok(None)
+    }
+
+    fn delete(&self, _id: &str) -> StorageResult<()> {
+        Ok(())
+    }
+}
+
+async fn setup() -> Option<(Config, Arc<deadpool_postgres::Pool>)> {
+    let config = match Config::load() {
+        Ok(c) => c,
+        Err(_) => {
+            println!("Skipping test: No config found");
+            return None;
+        }
+    };
+
+    db::run_migrations(&config.database.url)
+        .await
+        .expect("Failed to run migrations");
+    let pool = db::create_pool(&config.database.url)
+        .await
+        .expect("Failed to create pool");
+
+    Some((config, Arc::new(pool)))
+}
+
+fn log_params(actor_id: &str) -> CreateAuditLogParams {
+    CreateAuditLogParams {
+        actor_id: actor_id.to_string(),
+        action: "POST".to_string(),
+        resource: "/retention-test".to_string(),
+        resource_id: None,
+        metadata: None,
+        ip_address: None,
+        user_agent: None,
+        occurred_at: None,
+    }
+}
+
+fn gunzip(data: &[u8]) -> Vec<u8> {
+    use std::io::Read;
+
+    let mut out = Vec::new();
+    flate2::read::GzDecoder::new(data)
+        .read_to_end(&mut out)
+        .unwrap();
+    out
+}
+
+#[tokio::test]
+#[ignore] // Ignore by default to avoid breaking CI if no DB
+async fn test_purge_removes_old_rows_and_archives_them() {
+    let Some((config, pool)) = setup().await else {
+        return;
+    };
+    let audit = AuditService::new(pool, config);
+    let actor = format!("retention-{}", uuid::Uuid::new_v4());
+
+    let old = audit.create_audit_log(log_params(&actor)).await.unwrap();
+    tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+    let cutoff = chrono::Utc::now();
+    let recent = audit.create_audit_log(log_params(&actor)).await.unwrap();
+
+    let storage = RecordingStorage::default();
+    let result = audit
+        .purge_older_than(cutoff, Some(&storage))
+        .await
+        .unwrap();
+
+    assert!(result.deleted >= 1);
+    assert!(!result.archive_ids.is_empty());
+    assert!(matches!(
+        audit.get_audit_log(&old.id).await,
+        Err(blinks_backend::ApiError::NotFound(_))
+    ));
+    assert!(audit.get_audit_log(&recent.id).await.is_ok());
+
+    // The chain still verifies from the oldest surviving entry.
+    assert!(audit.verify_chain().await.unwrap().valid);
+
+    let uploads = storage.uploads.lock().unwrap();
+    assert_eq!(uploads.len(), 1);
+    assert!(uploads[0].0.ends_with(".json.gz"));
+    let archived: Vec<serde_json::Value> = serde_json::from_slice(&gunzip(&uploads[0].1)).unwrap();
+    assert!(archived.iter().any(|e| e["id"] == old.id.as_str()));
+    assert!(!archived.iter().any(|e| e["id"] == recent.id.as_str()));
+}
+
+#[tokio::test]
+#[ignore] // Ignore by default to avoid breaking CI if no DB
+async fn test_purge_expired_archives_only_when_configured() {
+    let Some((mut config, pool)) = setup().await else {
+        return;
+    };
+    let recording = Arc::new(RecordingStorage::default());
+    let storage = StorageService {
+        adapter: recording.clone(),
+    };
+
+    // Retention of zero days makes everything written so far eligible.
+    config.audit.retention_days = 0;
+    config.audit.archive_before_purge = false;
+    let audit = AuditService::new(pool.clone(), config.clone());
+    audit
+        .create_audit_log(log_params("retention-unarchived"))
+        .await
+        .unwrap();
+    let result = audit.purge_expired(&storage).await.unwrap();
+    assert!(result.deleted >= 1);
+    assert!(result.archive_ids.is_empty());
+    assert!(recording.uploads.lock().unwrap().is_empty());
+
+    config.audit.archive_before_purge = true;
+    let audit = AuditService::new(pool, config);
+    audit
+        .create_audit_log(log_params("retention-archived"))
+        .await
+        .unwrap();
+    let result = audit.purge_expired(&storage).await.unwrap();
+    assert!(result.deleted >= 1);
+    assert!(!result.archive_ids.is_empty());
+    assert_eq!(recording.uploads.lock().unwrap().len(), 1);
+}
+
+#[tokio::test]
+#[ignore] // Ignore by default to avoid breaking CI if no DB
+async fn test_purge_deletes_and_archives_in_batches() {
+    let Some((mut config, pool)) = setup().await else {
+        return;
+    };
+    config.audit.purge_batch_size = 2;
+    let audit = AuditService::new(pool, config);
+    let actor = format!("retention-batch-{}", uuid::Uuid::new_v4());
+
+    let mut old = Vec::new();
+    for _ in 0..5 {
+        old.push(audit.create_audit_log(log_params(&actor)).await.unwrap());
+    }
+    tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+    let cutoff = chrono::Utc::now();
+
+    let storage = RecordingStorage::default();
+    let result = audit
+        .purge_older_than(cutoff, Some(&storage))
+        .await
+        .unwrap();
+
+    assert!(result.deleted >= 5);
+    // Every batch of at most two entries got its own archive
+    let uploads = storage.uploads.lock().unwrap().clone();
+    assert_eq!(uploads.len(), result.archive_ids.len());
+    assert!(uploads.len() >= 3);
+    let mut archived = Vec::new();
+    for (_, data) in uploads.iter() {
+        let batch: Vec<serde_json::Value> = serde_json::from_slice(&gunzip(data)).unwrap();
+        assert!(batch.len() <= 2);
+        archived.extend(batch);
+    }
+    for entry in &old {
+        assert!(archived.iter().any(|e| e["id"] == entry.id.as_str()));
+        assert!(audit.get_audit_log(&entry.id).await.is_err());
+    }
+    assert!(audit.verify_chain().await.unwrap().valid);
+}
+
+#[tokio::test]
+#[ignore] // Ignore by default to avoid breaking CI if no DB
+async fn test_delete_outside_purge_is_still_rejected() {
+    let Some((config, pool)) = setup().await else {
+        return;
+    };
+    let audit = AuditService::new(pool.clone(), config);
+    let entry = audit
+        .create_audit_log(log_params("retention-immutable"))
+        .await
+        .unwrap();
+
+    let client = pool.get().await.unwrap();
+    let result = client
+        .execute(
+            "DELETE FROM audit_logs WHERE id = $1::text::uuid",
+            &[&entry.id],
+        )
+        .await;
+
+    assert!(result.is_err());
+}
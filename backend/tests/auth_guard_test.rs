@@ -109,12 +109,25 @@ mod role_tests {
 #[cfg(test)]
 mod jwt_tests {
     use super::*;
-    use blinks_backend::auth::{generate_access_token, validate_jwt};
+    use blinks_backend::auth::{generate_access_token, validate_jwt, TokenScope};
+    use chrono::Duration;
+
+    const SCOPE: TokenScope = TokenScope {
+        issuer: "zaps",
+        audience: "zaps-api",
+    };
 
     #[test]
     fn test_jwt_with_user_role() {
-        let token = generate_access_token("user123", Role::User, "test-secret", 1).unwrap();
-        let claims = validate_jwt(&token, "test-secret").unwrap();
+        let token = generate_access_token(
+            "user123",
+            Role::User,
+            "test-secret",
+            SCOPE,
+            Duration::hours(1),
+        )
+        .unwrap();
+        let claims = validate_jwt(&token, "test-secret", SCOPE).unwrap();
 
         assert_eq!(claims.sub, "user123");
         assert_eq!(claims.role, Role::User);
@@ -122,8 +135,15 @@ mod jwt_tests {
 
     #[test]
     fn test_jwt_with_admin_role() {
-        let token = generate_access_token("admin123", Role::Admin, "test-secret", 1).unwrap();
-        let claims = validate_jwt(&token, "test-secret").unwrap();
+        let token = generate_access_token(
+            "admin123",
+            Role::Admin,
+            "test-secret",
+            SCOPE,
+            Duration::hours(1),
+        )
+        .unwrap();
+        let claims = validate_jwt(&token, "test-secret", SCOPE).unwrap();
 
         assert_eq!(claims.sub, "admin123");
         assert_eq!(claims.role, Role::Admin);
@@ -131,8 +151,15 @@ mod jwt_tests {
 
     #[test]
     fn test_jwt_with_merchant_role() {
-        let token = generate_access_token("merchant123", Role::Merchant, "test-secret", 1).unwrap();
-        let claims = validate_jwt(&token, "test-secret").unwrap();
+        let token = generate_access_token(
+            "merchant123",
+            Role::Merchant,
+            "test-secret",
+            SCOPE,
+            Duration::hours(1),
+        )
+        .unwrap();
+        let claims = validate_jwt(&token, "test-secret", SCOPE).unwrap();
 
         assert_eq!(claims.sub, "merchant123");
         assert_eq!(claims.role, Role::Merchant);
@@ -140,22 +167,26 @@ mod jwt_tests {
 
     #[test]
     fn test_jwt_invalid_token() {
-        let result = validate_jwt("invalid-token", "test-secret");
+        let result = validate_jwt("invalid-token", "test-secret", SCOPE);
         assert!(result.is_err());
     }
 
     #[test]
     fn test_jwt_wrong_secret() {
-        let token = generate_access_token("user123", Role::User, "secret1", 1).unwrap();
-        let result = validate_jwt(&token, "secret2");
+        let token =
+            generate_access_token("user123", Role::User, "secret1", SCOPE, Duration::hours(1))
+                .unwrap();
+        let result = validate_jwt(&token, "secret2", SCOPE);
         assert!(result.is_err());
     }
 
     #[test]
     fn test_jwt_role_preserved_in_claims() {
         for role in [Role::User, Role::Merchant, Role::Admin] {
-            let token = generate_access_token("testuser", role, "secret", 1).unwrap();
-            let claims = validate_jwt(&token, "secret").unwrap();
+            let token =
+                generate_access_token("testuser", role, "secret", SCOPE, Duration::hours(1))
+                    .unwrap();
+            let claims = validate_jwt(&token, "secret", SCOPE).unwrap();
             assert_eq!(claims.role, role, "Role should be preserved in JWT claims");
         }
     }
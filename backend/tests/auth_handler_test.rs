@@ -6,6 +6,7 @@ use axum::{
 use lazy_static::lazy_static;
 use serde_json::{json, Value};
 use tokio::sync::Mutex;
+use tokio_util::sync::CancellationToken;
 use tower::util::ServiceExt; // for oneshot
 
 use blinks_backend::{app::create_app, config::Config, db};
@@ -39,9 +40,10 @@ async fn create_test_app() -> Router {
         .await
         .expect("Failed to create pool");
 
-    create_app(pool, config)
+    let (app, _workers) = create_app(pool, config, CancellationToken::new())
         .await
-        .expect("Failed to create app")
+        .expect("Failed to create app");
+    app
 }
 
 /// Helper to make JSON POST request
@@ -85,14 +87,20 @@ async fn parse_response(response: axum::response::Response) -> Value {
 #[cfg(test)]
 mod unit_tests {
     use blinks_backend::{
-        auth::{self, TokenType},
+        auth::{self, TokenScope, TokenType},
         role::Role,
     };
+    use chrono::Duration;
+
+    const SCOPE: TokenScope = TokenScope {
+        issuer: "zaps",
+        audience: "zaps-api",
+    };
 
     #[test]
     fn test_pin_hash_and_verify_flow() {
         let pin = "1234";
-        let hash = auth::hash_pin(pin).expect("Failed to hash");
+        let hash = auth::hash_pin(pin, 4).expect("Failed to hash");
 
         assert!(auth::verify_pin(pin, &hash).expect("Failed to verify"));
         assert!(!auth::verify_pin("wrong", &hash).expect("Failed to verify"));
@@ -102,10 +110,11 @@ mod unit_tests {
     fn test_access_token_cannot_refresh() {
         let secret = "test-secret";
         let role = Role::User;
-        let access_token = auth::generate_access_token("user1", role, secret, 1).unwrap();
+        let access_token =
+            auth::generate_access_token("user1", role, secret, SCOPE, Duration::hours(1)).unwrap();
 
         // Access token should fail refresh validation
-        let result = auth::validate_refresh_token(&access_token, secret);
+        let result = auth::validate_refresh_token(&access_token, secret, SCOPE);
         assert!(result.is_err());
     }
 
@@ -113,10 +122,12 @@ mod unit_tests {
     fn test_refresh_token_cannot_access() {
         let secret = "test-secret";
         let role = Role::User;
-        let refresh_token = auth::generate_refresh_token("user1", role, secret, 168).unwrap();
+        let refresh_token =
+            auth::generate_refresh_token("user1", role, secret, SCOPE, Duration::hours(168))
+                .unwrap();
 
         // Refresh token should fail access validation
-        let result = auth::validate_access_token(&refresh_token, secret);
+        let result = auth::validate_access_token(&refresh_token, secret, SCOPE);
         assert!(result.is_err());
     }
 
@@ -126,12 +137,15 @@ mod unit_tests {
         let user_id = "testuser";
         let role = Role::User;
 
-        let access = auth::generate_access_token(user_id, role, secret, 24).unwrap();
-        let refresh = auth::generate_refresh_token(user_id, role, secret, 168).unwrap();
+        let access =
+            auth::generate_access_token(user_id, role, secret, SCOPE, Duration::hours(24)).unwrap();
+        let refresh =
+            auth::generate_refresh_token(user_id, role, secret, SCOPE, Duration::hours(168))
+                .unwrap();
 
         // Both tokens are valid
-        let access_claims = auth::validate_access_token(&access, secret).unwrap();
-        let refresh_claims = auth::validate_refresh_token(&refresh, secret).unwrap();
+        let access_claims = auth::validate_access_token(&access, secret, SCOPE).unwrap();
+        let refresh_claims = auth::validate_refresh_token(&refresh, secret, SCOPE).unwrap();
 
         assert_eq!(access_claims.sub, user_id);
         assert_eq!(access_claims.role, role);
@@ -160,7 +174,7 @@ async fn test_register_success() {
             "/auth/register",
             json!({
                 "user_id": user_id,
-                "pin": "1234"
+                "pin": "2580"
             }),
         ))
         .await
@@ -173,6 +187,11 @@ async fn test_register_success() {
     assert_eq!(body["user_id"], user_id);
     assert!(body["token"].as_str().is_some());
     assert!(body["refresh_token"].as_str().is_some());
+
+    // Lifetimes come from the configured TTLs
+    let jwt = Config::load().unwrap().jwt;
+    assert_eq!(body["expires_in"], jwt.access_ttl_seconds);
+    assert_eq!(body["refresh_expires_in"], jwt.refresh_ttl_seconds);
 }
 
 #[tokio::test]
@@ -189,7 +208,7 @@ async fn test_register_duplicate_user() {
             "/auth/register",
             json!({
                 "user_id": user_id,
-                "pin": "1234"
+                "pin": "2580"
             }),
         ))
         .await
@@ -231,7 +250,7 @@ async fn test_login_success() {
     let app = create_test_app().await;
 
     let user_id = format!("testuser_{}", uuid::Uuid::new_v4());
-    let pin = "1234";
+    let pin = "2580";
 
     // Register first
     let _ = app
@@ -280,7 +299,7 @@ async fn test_login_wrong_pin() {
             "/auth/register",
             json!({
                 "user_id": user_id,
-                "pin": "1234"
+                "pin": "2580"
             }),
         ))
         .await
@@ -322,7 +341,7 @@ async fn test_refresh_token_success() {
             "/auth/register",
             json!({
                 "user_id": user_id,
-                "pin": "1234"
+                "pin": "2580"
             }),
         ))
         .await
@@ -348,3 +367,80 @@ async fn test_refresh_token_success() {
     assert_eq!(body["user_id"], user_id);
     assert!(body["token"].as_str().is_some());
 }
+
+/// Helper to make an authenticated GET request
+fn authed_get(uri: &str, token: &str) -> Request<Body> {
+    use axum::extract::ConnectInfo;
+    use std::net::SocketAddr;
+
+    Request::builder()
+        .method("GET")
+        .uri(uri)
+        .header("Authorization", format!("Bearer {}", token))
+        .extension(ConnectInfo(SocketAddr::from(([127, 0, 0, 1], 12345))))
+        .body(Body::empty())
+        .unwrap()
+}
+
+#[tokio::test]
+#[ignore]
+async fn test_whoami_returns_identity() {
+    let app = create_test_app().await;
+
+    let user_id = format!("testuser_{}", uuid::Uuid::new_v4());
+
+    let response = app
+        .clone()
+        .oneshot(json_post(
+            "/auth/register",
+            json!({
+                "user_id": user_id,
+                "pin": "2580"
+            }),
+        ))
+        .await
+        .unwrap();
+
+    let body = parse_response(response).await;
+    let token = body["token"].as_str().unwrap();
+
+    let response = app
+        .oneshot(authed_get("/auth/whoami", token))
+        .await
+        .unwrap();
+
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let body = parse_response(response).await;
+    assert_eq!(body["user_id"], user_id);
+    assert_eq!(body["role"], "user");
+    assert!(body["stellar_address"].as_str().unwrap().starts_with('G'));
+}
+
+#[tokio::test]
+#[ignore]
+async fn test_whoami_rejects_invalid_or_expired_token() {
+    use blinks_backend::{auth, role::Role};
+
+    let app = create_test_app().await;
+    let config = Config::load().expect("Failed to load config");
+    let jwt = &config.jwt;
+
+    let expired = auth::generate_access_token(
+        "someone",
+        Role::User,
+        &jwt.secret,
+        jwt.into(),
+        chrono::Duration::seconds(-120),
+    )
+    .unwrap();
+
+    for token in ["not-a-jwt", expired.as_str()] {
+        let response = app
+            .clone()
+            .oneshot(authed_get("/auth/whoami", token))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+    }
+}
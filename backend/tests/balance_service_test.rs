@@ -0,0 +1,156 @@
+use std::sync::Arc;
+
+use blinks_backend::api_error::ApiError;
+use blinks_backend::config::Config;
+use blinks_backend::db;
+use blinks_backend::service::transfer_service::CreateTransferParams;
+use blinks_backend::service::{BalanceService, TransferService};
+use uuid::Uuid;
+
+// Note: These tests require a running database using the config.
+// Run with: cargo test --test balance_service_test -- --ignored
+
+async fn setup() -> Option<(
+    BalanceService,
+    TransferService,
+    Arc<deadpool_postgres::Pool>,
+)> {
+    let config = match Config::load() {
+        Ok(c) => c,
+        Err(_) => {
+            println!("Skipping test: No config found");
+            return None;
+        }
+    };
+
+    db::run_migrations(&config.database.url)
+        .await
+        .expect("Failed to run migrations");
+    let pool = Arc::new(
+        db::create_pool(&config.database.url)
+            .await
+            .expect("Failed to create pool"),
+    );
+
+    Some((
+        BalanceService::new(pool.clone(), config.clone()),
+        TransferService::new(pool.clone(), config),
+        pool,
+    ))
+}
+
+async fn balance(pool: &deadpool_postgres::Pool, owner_id: &str) -> i64 {
+    let client = pool.get().await.unwrap();
+    client
+        .query_one(
+            "SELECT amount FROM balances WHERE owner_id = $1 AND asset = 'USDC'",
+            &[&owner_id],
+        )
+        .await
+        .unwrap()
+        .get(0)
+}
+
+fn new_owner() -> String {
+    format!("balance-{}", Uuid::new_v4().simple())
+}
+
+#[tokio::test]
+#[ignore]
+async fn test_credit_and_debit_move_the_balance() {
+    let Some((balances, _, pool)) = setup().await else {
+        return;
+    };
+    let owner = new_owner();
+
+    assert_eq!(balances.credit(&owner, "USDC", 500).await.unwrap(), 500);
+    assert_eq!(balances.credit(&owner, "USDC", 250).await.unwrap(), 750);
+    assert_eq!(balances.debit(&owner, "USDC", 700).await.unwrap(), 50);
+    assert_eq!(balance(&pool, &owner).await, 50);
+}
+
+#[tokio::test]
+#[ignore]
+async fn test_debit_never_goes_negative() {
+    let Some((balances, _, pool)) = setup().await else {
+        return;
+    };
+    let owner = new_owner();
+    balances.credit(&owner, "USDC", 100).await.unwrap();
+
+    for amount in [101, 0, -50] {
+        let err = balances.debit(&owner, "USDC", amount).await.unwrap_err();
+        assert!(matches!(err, ApiError::Validation(_)), "{:?}", err);
+    }
+    // An owner without a balance has nothing to debit
+    assert!(balances.debit(&new_owner(), "USDC", 1).await.is_err());
+
+    assert_eq!(balance(&pool, &owner).await, 100);
+}
+
+#[tokio::test]
+#[ignore]
+async fn test_concurrent_debits_cannot_overdraw() {
+    let Some((balances, _, pool)) = setup().await else {
+        return;
+    };
+    let owner = new_owner();
+    balances.credit(&owner, "USDC", 100).await.unwrap();
+
+    let debits = (0..10).map(|_| {
+        let balances = balances.clone();
+        let owner = owner.clone();
+        tokio::spawn(async move { balances.debit(&owner, "USDC", 60).await })
+    });
+    let results = futures::future::join_all(debits).await;
+
+    let succeeded = results
+        .iter()
+        .filter(|r| r.as_ref().unwrap().is_ok())
+        .count();
+    assert_eq!(succeeded, 1);
+    assert_eq!(balance(&pool, &owner).await, 40);
+}
+
+#[tokio::test]
+#[ignore]
+async fn test_concurrent_transfers_only_spend_the_balance_once() {
+    let Some((balances, transfers, pool)) = setup().await else {
+        return;
+    };
+    let suffix = Uuid::new_v4().simple().to_string();
+    let sender = format!("sender-{}", suffix);
+    let recipient = format!("recipient-{}", suffix);
+    let client = pool.get().await.unwrap();
+    for (user_id, prefix) in [(&sender, "GS"), (&recipient, "GR")] {
+        client
+            .execute(
+                "INSERT INTO users (user_id, stellar_address) VALUES ($1, $2)",
+                &[user_id, &format!("{}{}", prefix, suffix.to_uppercase())],
+            )
+            .await
+            .unwrap();
+    }
+    drop(client);
+    balances.credit(&sender, "USDC", 100).await.unwrap();
+
+    let attempts = (0..5).map(|_| {
+        let transfers = transfers.clone();
+        let params = CreateTransferParams {
+            from_user_id: sender.clone(),
+            to_user_id: recipient.clone(),
+            amount: 75,
+            asset: "USDC".to_string(),
+            memo: None,
+        };
+        tokio::spawn(async move { transfers.create_transfer(params).await })
+    });
+    let results = futures::future::join_all(attempts).await;
+
+    let created = results
+        .iter()
+        .filter(|r| r.as_ref().unwrap().is_ok())
+        .count();
+    assert_eq!(created, 1);
+    assert_eq!(balance(&pool, &sender).await, 25);
+}
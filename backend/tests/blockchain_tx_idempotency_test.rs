@@ -0,0 +1,226 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+use async_trait::async_trait;
+use blinks_backend::config::Config;
+use blinks_backend::db;
+use blinks_backend::job_processors::{
+    BlockchainTx, BlockchainTxProcessor, ChainSubmitter, InMemoryTxSubmissionStore,
+};
+use blinks_backend::job_types::{JobPayload, JobType};
+use blinks_backend::queue::JobProcessor;
+use blinks_backend::service::tx_submission_service::SubmissionState;
+use blinks_backend::service::TxSubmissionService;
+use serde_json::Value;
+use uuid::Uuid;
+
+/// Chain stand-in that counts submissions. With `fail_after_send`, a
+/// submission lands on-chain but the call still errors, as when the
+/// connection drops before the response arrives.
+#[derive(Default)]
+struct CountingChain {
+    on_chain: Mutex<HashMap<String, String>>,
+    submissions: Mutex<u32>,
+    fail_after_send: Mutex<bool>,
+}
+
+impl CountingChain {
+    fn submissions(&self) -> u32 {
+        *self.submissions.lock().unwrap()
+    }
+}
+
+#[async_trait]
+impl ChainSubmitter for CountingChain {
+    async fn find_submitted(&self, key: &str) -> anyhow::Result<Option<String>> {
+        Ok(self.on_chain.lock().unwrap().get(key).cloned())
+    }
+
+    async fn submit(&self, key: &str, _tx: &BlockchainTx) -> anyhow::Result<String> {
+        let mut submissions = self.submissions.lock().unwrap();
+        *submissions += 1;
+        let tx_hash = format!("hash-{}", submissions);
+        self.on_chain
+            .lock()
+            .unwrap()
+            .insert(key.to_string(), tx_hash.clone());
+
+        if std::mem::take(&mut *self.fail_after_send.lock().unwrap()) {
+            anyhow::bail!("connection reset");
+        }
+        Ok(tx_hash)
+    }
+}
+
+fn payment_job(amount: &str) -> JobPayload {
+    let payload = HashMap::from([
+        ("from_address".to_string(), Value::from("GSENDER")),
+        ("to_address".to_string(), Value::from("GRECIPIENT")),
+        ("amount".to_string(), Value::from(amount)),
+        ("memo".to_string(), Value::from("invoice-42")),
+    ]);
+    JobPayload::new(JobType::BlockchainTx, payload, None)
+}
+
+fn processor(chain: &Arc<CountingChain>) -> BlockchainTxProcessor {
+    BlockchainTxProcessor::with_store(
+        Arc::new(InMemoryTxSubmissionStore::default()),
+        chain.clone(),
+    )
+}
+
+#[tokio::test]
+async fn test_retried_job_is_not_submitted_twice() {
+    let chain = Arc::new(CountingChain::default());
+    let processor = processor(&chain);
+    let job = payment_job("100");
+
+    assert!(processor.process(&job).await.unwrap().success);
+    let mut retry = job.clone();
+    retry.retries = Some(1);
+    assert!(processor.process(&retry).await.unwrap().success);
+
+    assert_eq!(chain.submissions(), 1);
+}
+
+#[tokio::test]
+async fn test_retry_after_lost_response_finds_the_landed_transaction() {
+    let chain = Arc::new(CountingChain::default());
+    *chain.fail_after_send.lock().unwrap() = true;
+    let processor = processor(&chain);
+    let job = payment_job("100");
+
+    // The first attempt reaches the chain but never hears back
+    assert!(!processor.process(&job).await.unwrap().success);
+    assert!(processor.process(&job).await.unwrap().success);
+
+    assert_eq!(chain.submissions(), 1);
+}
+
+#[tokio::test]
+async fn test_different_transactions_are_each_submitted() {
+    let chain = Arc::new(CountingChain::default());
+    let processor = processor(&chain);
+
+    for amount in ["100", "200"] {
+        let result = processor.process(&payment_job(amount)).await.unwrap();
+        assert!(result.success);
+    }
+
+    assert_eq!(chain.submissions(), 2);
+}
+
+#[tokio::test]
+async fn test_identical_transfers_for_different_payments_are_each_submitted() {
+    let chain = Arc::new(CountingChain::default());
+    let processor = processor(&chain);
+
+    // Same addresses, amount and memo, but two separate jobs
+    for job in [payment_job("100"), payment_job("100")] {
+        assert!(processor.process(&job).await.unwrap().success);
+    }
+
+    let mut first = payment_job("100");
+    first
+        .payload
+        .insert("payment_id".to_string(), Value::from("payment-1"));
+    let mut retry = payment_job("100");
+    retry.payload = first.payload.clone();
+    // A re-enqueued job for the same payment is still recognised
+    for job in [first, retry] {
+        assert!(processor.process(&job).await.unwrap().success);
+    }
+
+    assert_eq!(chain.submissions(), 3);
+}
+
+#[test]
+fn test_idempotency_key_covers_every_field() {
+    let tx = BlockchainTx {
+        reference: "payment-1".to_string(),
+        network: "stellar".to_string(),
+        from_address: "GSENDER".to_string(),
+        to_address: "GRECIPIENT".to_string(),
+        amount: "100".to_string(),
+        memo: None,
+    };
+    let key = tx.idempotency_key();
+    assert_eq!(key, tx.clone().idempotency_key());
+    assert_eq!(key.len(), 64);
+
+    let variants = [
+        BlockchainTx {
+            reference: "payment-2".to_string(),
+            ..tx.clone()
+        },
+        BlockchainTx {
+            network: "testnet".to_string(),
+            ..tx.clone()
+        },
+        BlockchainTx {
+            to_address: "GOTHER".to_string(),
+            ..tx.clone()
+        },
+        BlockchainTx {
+            amount: "101".to_string(),
+            ..tx.clone()
+        },
+        BlockchainTx {
+            memo: Some("invoice-42".to_string()),
+            ..tx.clone()
+        },
+        // Moving characters between fields still changes the key
+        BlockchainTx {
+            from_address: "GSENDERG".to_string(),
+            to_address: "RECIPIENT".to_string(),
+            ..tx.clone()
+        },
+    ];
+    for variant in variants {
+        assert_ne!(variant.idempotency_key(), key, "{:?}", variant);
+    }
+}
+
+// Note: This test requires a running database using the config.
+// Run with: cargo test --test blockchain_tx_idempotency_test -- --ignored
+#[tokio::test]
+#[ignore]
+async fn test_submission_ledger_survives_a_new_processor() {
+    let config = match Config::load() {
+        Ok(c) => c,
+        Err(_) => {
+            println!("Skipping test: No config found");
+            return;
+        }
+    };
+    db::run_migrations(&config.database.url)
+        .await
+        .expect("Failed to run migrations");
+    let pool = Arc::new(
+        db::create_pool(&config.database.url)
+            .await
+            .expect("Failed to create pool"),
+    );
+    let ledger = Arc::new(TxSubmissionService::new(pool, config));
+
+    let key = Uuid::new_v4().simple().to_string();
+    assert_eq!(ledger.reserve(&key).await.unwrap(), SubmissionState::New);
+    assert_eq!(
+        ledger.reserve(&key).await.unwrap(),
+        SubmissionState::Unconfirmed
+    );
+    ledger.record_submission(&key, "hash-1").await.unwrap();
+    assert_eq!(
+        ledger.reserve(&key).await.unwrap(),
+        SubmissionState::Submitted("hash-1".to_string())
+    );
+
+    // A restarted worker retrying an already-submitted job
+    let chain = Arc::new(CountingChain::default());
+    let job = payment_job(&Uuid::new_v4().to_string());
+    for _ in 0..2 {
+        let processor = BlockchainTxProcessor::with_store(ledger.clone(), chain.clone());
+        assert!(processor.process(&job).await.unwrap().success);
+    }
+    assert_eq!(chain.submissions(), 1);
+}
@@ -0,0 +1,49 @@
+use base64::{engine::general_purpose, Engine as _};
+use blinks_backend::config::Config;
+use blinks_backend::custodial::{is_valid_account_id, CustodialKeys};
+use blinks_backend::service::SorobanService;
+
+fn new_user_address(user_id: &str) -> String {
+    let config = Config::default();
+    CustodialKeys::new(&config.custodial.master_seed)
+        .derive(user_id)
+        .address
+}
+
+#[test]
+fn test_new_user_address_is_a_valid_account_id() {
+    let address = new_user_address("custodial-alice");
+
+    assert_eq!(address.len(), 56);
+    assert!(address.starts_with('G'));
+    assert!(is_valid_account_id(&address));
+}
+
+#[test]
+fn test_new_user_address_passes_asset_validation_as_issuer() {
+    let soroban = SorobanService::new(Config::default());
+    let address = new_user_address("custodial-issuer");
+
+    soroban
+        .validate_asset(&format!("ZAP:{}", address))
+        .expect("derived address should be accepted as an asset issuer");
+}
+
+#[tokio::test]
+async fn test_new_user_addresses_round_trip_through_payment_xdr() {
+    let soroban = SorobanService::new(Config::default());
+    let from = new_user_address("custodial-sender");
+    let to = new_user_address("custodial-receiver");
+
+    let xdr = soroban
+        .build_payment_xdr(&from, &to, "XLM", 10_000_000, None)
+        .await
+        .unwrap();
+
+    let decoded: serde_json::Value =
+        serde_json::from_slice(&general_purpose::STANDARD.decode(xdr).unwrap()).unwrap();
+    assert_eq!(decoded["from"], from);
+    assert_eq!(decoded["to"], to);
+    assert!(is_valid_account_id(decoded["from"].as_str().unwrap()));
+    assert!(is_valid_account_id(decoded["to"].as_str().unwrap()));
+}
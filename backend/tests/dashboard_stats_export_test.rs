@@ -0,0 +1,155 @@
+use std::sync::Arc;
+
+use blinks_backend::config::Config;
+use blinks_backend::db;
+use blinks_backend::models::{TransactionKind, TransactionStats};
+use blinks_backend::service::TransactionService;
+use chrono::{DateTime, TimeZone, Utc};
+use uuid::Uuid;
+
+// Note: These tests require a running database using the config.
+// Run with: cargo test --test dashboard_stats_export_test -- --ignored
+
+async fn setup() -> Option<(TransactionService, Arc<deadpool_postgres::Pool>)> {
+    let config = match Config::load() {
+        Ok(c) => c,
+        Err(_) => {
+            println!("Skipping test: No config found");
+            return None;
+        }
+    };
+
+    db::run_migrations(&config.database.url)
+        .await
+        .expect("Failed to run migrations");
+    let pool = Arc::new(
+        db::create_pool(&config.database.url)
+            .await
+            .expect("Failed to create pool"),
+    );
+
+    Some((TransactionService::new(pool.clone(), config), pool))
+}
+
+fn day(d: u32) -> DateTime<Utc> {
+    Utc.with_ymd_and_hms(2026, 2, d, 12, 0, 0).unwrap()
+}
+
+/// Payments and transfers in an asset no other test uses, so the
+/// aggregate only sees these rows.
+async fn seed(pool: &deadpool_postgres::Pool) -> String {
+    let client = pool.get().await.unwrap();
+    let suffix = Uuid::new_v4().simple().to_string()[..16].to_uppercase();
+    let asset = format!("T{}", suffix);
+    let sender = format!("stats-sender-{}", suffix);
+    let recipient = format!("stats-recipient-{}", suffix);
+    let merchant_id = format!("stats-merchant-{}", suffix);
+
+    for user_id in [&sender, &recipient] {
+        client
+            .execute(
+                "INSERT INTO users (user_id, stellar_address, pin_hash) VALUES ($1, $2, 'x')",
+                &[user_id, &format!("G{}", user_id.to_uppercase())],
+            )
+            .await
+            .unwrap();
+    }
+    client
+        .execute(
+            "INSERT INTO merchants (merchant_id, vault_address, settlement_asset) VALUES ($1, 'GVAULT', $2)",
+            &[&merchant_id, &asset],
+        )
+        .await
+        .unwrap();
+
+    for (amount, status, created_at) in [
+        (100i64, "completed", day(1)),
+        (250, "completed", day(2)),
+        (40, "failed", day(2)),
+        (999, "completed", day(10)),
+    ] {
+        client
+            .execute(
+                "INSERT INTO payments (from_address, merchant_id, send_asset, send_amount, status, created_at)
+                 VALUES ('GPAYER', $1, $2, $3, $4, $5)",
+                &[&merchant_id, &asset, &amount, &status, &created_at],
+            )
+            .await
+            .unwrap();
+    }
+    for (amount, created_at) in [(70i64, day(3)), (30, day(4))] {
+        client
+            .execute(
+                "INSERT INTO transfers (from_user_id, to_user_id, amount, asset, status, created_at)
+                 VALUES ($1, $2, $3, $4, 'completed', $5)",
+                &[&sender, &recipient, &amount, &asset, &created_at],
+            )
+            .await
+            .unwrap();
+    }
+
+    asset
+}
+
+fn stats_for(
+    stats: Vec<TransactionStats>,
+    asset: &str,
+) -> Vec<(TransactionKind, String, i64, i64)> {
+    stats
+        .into_iter()
+        .filter(|s| s.asset == asset)
+        .map(|s| (s.kind, s.status.unwrap_or_default(), s.count, s.volume))
+        .collect()
+}
+
+#[tokio::test]
+#[ignore]
+async fn test_export_aggregates_by_asset_and_status() {
+    let Some((transactions, pool)) = setup().await else {
+        return;
+    };
+    let asset = seed(&pool).await;
+
+    let stats = transactions
+        .stats_by_asset_and_status(None, None)
+        .await
+        .unwrap();
+
+    assert_eq!(
+        stats_for(stats, &asset),
+        [
+            (TransactionKind::Payment, "completed".to_string(), 3, 1_349),
+            (TransactionKind::Payment, "failed".to_string(), 1, 40),
+            (TransactionKind::Transfer, "completed".to_string(), 2, 100),
+        ]
+    );
+}
+
+#[tokio::test]
+#[ignore]
+async fn test_export_respects_date_window() {
+    let Some((transactions, pool)) = setup().await else {
+        return;
+    };
+    let asset = seed(&pool).await;
+
+    // Day 2 through day 3 inclusive.
+    let stats = transactions
+        .stats_by_asset_and_status(Some(day(2)), Some(day(3)))
+        .await
+        .unwrap();
+
+    assert_eq!(
+        stats_for(stats, &asset),
+        [
+            (TransactionKind::Payment, "completed".to_string(), 1, 250),
+            (TransactionKind::Payment, "failed".to_string(), 1, 40),
+            (TransactionKind::Transfer, "completed".to_string(), 1, 70),
+        ]
+    );
+
+    assert!(transactions
+        .stats_by_asset_and_status(Some(day(3)), Some(day(2)))
+        .await
+        .is_err());
+}
@@ -0,0 +1,72 @@
+use std::net::SocketAddr;
+use std::time::{Duration, Instant};
+
+use axum::{routing::get, Router};
+use blinks_backend::config::HttpClientConfig;
+use blinks_backend::http_client;
+
+/// Serve `/fast`, which answers straight away, and `/slow`, which takes two
+/// seconds.
+async fn spawn_server() -> String {
+    let app = Router::new().route("/fast", get(|| async { "ok" })).route(
+        "/slow",
+        get(|| async {
+            tokio::time::sleep(Duration::from_secs(2)).await;
+            "late"
+        }),
+    );
+
+    let listener = tokio::net::TcpListener::bind(SocketAddr::from(([127, 0, 0, 1], 0)))
+        .await
+        .unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move {
+        axum::serve(listener, app).await.unwrap();
+    });
+
+    format!("http://{}", addr)
+}
+
+fn config(request_timeout_ms: u64) -> HttpClientConfig {
+    HttpClientConfig {
+        connect_timeout_ms: 100,
+        request_timeout_ms,
+        ..Default::default()
+    }
+}
+
+#[tokio::test]
+async fn test_client_times_out_after_configured_deadline() {
+    let base = spawn_server().await;
+    let client = http_client::build(&config(200));
+
+    let started = Instant::now();
+    let err = client
+        .get(format!("{}/slow", base))
+        .send()
+        .await
+        .unwrap_err();
+
+    assert!(err.is_timeout(), "{:?}", err);
+    assert!(started.elapsed() < Duration::from_secs(1));
+}
+
+#[tokio::test]
+async fn test_client_completes_requests_within_deadline() {
+    let base = spawn_server().await;
+    let client = http_client::build(&config(5000));
+
+    let body = client
+        .get(format!("{}/fast", base))
+        .send()
+        .await
+        .unwrap()
+        .text()
+        .await
+        .unwrap();
+    assert_eq!(body, "ok");
+
+    // The request deadline covers the slow route too.
+    let slow = client.get(format!("{}/slow", base)).send().await.unwrap();
+    assert_eq!(slow.text().await.unwrap(), "late");
+}
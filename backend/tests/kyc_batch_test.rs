@@ -0,0 +1,110 @@
+use std::collections::HashMap;
+use std::net::SocketAddr;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use axum::{
+    extract::{Query, State},
+    http::StatusCode,
+    routing::get,
+    Json, Router,
+};
+use blinks_backend::config::Config;
+use blinks_backend::db;
+use blinks_backend::service::anchor_service::KycStatus;
+use blinks_backend::service::AnchorService;
+use serde_json::{json, Value};
+
+/// Requests the mock anchor is serving right now, and the most it ever
+/// served at once.
+#[derive(Default)]
+struct InFlight {
+    current: AtomicUsize,
+    peak: AtomicUsize,
+    total: AtomicUsize,
+}
+
+/// Stand-in SEP-12 endpoint: `GCLEARED…` accounts are cleared, `GPENDING…`
+/// pending, and anything else unknown. Each check takes a moment so
+/// concurrent requests overlap.
+async fn spawn_mock_anchor() -> (String, Arc<InFlight>) {
+    async fn kyc(
+        State(in_flight): State<Arc<InFlight>>,
+        Query(query): Query<HashMap<String, String>>,
+    ) -> Result<Json<Value>, StatusCode> {
+        let now = in_flight.current.fetch_add(1, Ordering::SeqCst) + 1;
+        in_flight.peak.fetch_max(now, Ordering::SeqCst);
+        in_flight.total.fetch_add(1, Ordering::SeqCst);
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        in_flight.current.fetch_sub(1, Ordering::SeqCst);
+
+        let account = query.get("account").cloned().unwrap_or_default();
+        if account.starts_with("GCLEARED") {
+            Ok(Json(json!({ "status": "CLEARED" })))
+        } else if account.starts_with("GPENDING") {
+            Ok(Json(json!({ "status": "PENDING" })))
+        } else {
+            Err(StatusCode::NOT_FOUND)
+        }
+    }
+
+    let in_flight = Arc::new(InFlight::default());
+    let app = Router::new()
+        .route("/kyc", get(kyc))
+        .with_state(in_flight.clone());
+
+    let listener = tokio::net::TcpListener::bind(SocketAddr::from(([127, 0, 0, 1], 0)))
+        .await
+        .unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move {
+        axum::serve(listener, app).await.unwrap();
+    });
+
+    (format!("http://{}", addr), in_flight)
+}
+
+/// KYC checks never touch the database, and the pool only connects on
+/// first use, so no database is needed.
+async fn setup(concurrency: usize) -> (AnchorService, Arc<InFlight>) {
+    let (anchor_url, in_flight) = spawn_mock_anchor().await;
+
+    let mut config = Config::default();
+    config.anchor_config.sep24_url = anchor_url;
+    config.anchor_config.kyc_batch_concurrency = concurrency;
+
+    let pool = Arc::new(db::create_pool(&config.database.url).await.unwrap());
+    (AnchorService::new(pool, config), in_flight)
+}
+
+#[tokio::test]
+async fn test_batch_resolves_each_accounts_status() {
+    let (anchor, in_flight) = setup(8).await;
+    let accounts = ["GCLEARED1", "GPENDING1", "GUNKNOWN1", "GCLEARED1"].map(String::from);
+
+    let statuses = anchor.check_kyc_status_batch(&accounts).await;
+
+    assert_eq!(
+        statuses,
+        HashMap::from([
+            ("GCLEARED1".to_string(), KycStatus::Cleared),
+            ("GPENDING1".to_string(), KycStatus::Pending),
+            ("GUNKNOWN1".to_string(), KycStatus::NotFound),
+        ])
+    );
+    // The repeated account was only checked once
+    assert_eq!(in_flight.total.load(Ordering::SeqCst), 3);
+}
+
+#[tokio::test]
+async fn test_batch_respects_the_concurrency_cap() {
+    let (anchor, in_flight) = setup(2).await;
+    let accounts: Vec<String> = (0..6).map(|i| format!("GCLEARED{}", i)).collect();
+
+    let statuses = anchor.check_kyc_status_batch(&accounts).await;
+
+    assert_eq!(statuses.len(), 6);
+    assert!(statuses.values().all(|s| *s == KycStatus::Cleared));
+    assert_eq!(in_flight.peak.load(Ordering::SeqCst), 2);
+}
@@ -0,0 +1,157 @@
+use std::sync::Arc;
+
+use axum::http::StatusCode;
+use blinks_backend::api_error::ApiError;
+use blinks_backend::config::Config;
+use blinks_backend::db;
+use blinks_backend::middleware::auth::authenticate_api_key;
+use blinks_backend::role::Role;
+use blinks_backend::service::ApiKeyService;
+use uuid::Uuid;
+
+// Note: These tests require a running database using the config.
+// Run with: cargo test --test merchant_api_key_test -- --ignored
+
+async fn setup() -> Option<(ApiKeyService, Arc<deadpool_postgres::Pool>)> {
+    let config = match Config::load() {
+        Ok(c) => c,
+        Err(_) => {
+            println!("Skipping test: No config found");
+            return None;
+        }
+    };
+
+    db::run_migrations(&config.database.url)
+        .await
+        .expect("Failed to run migrations");
+    let pool = Arc::new(
+        db::create_pool(&config.database.url)
+            .await
+            .expect("Failed to create pool"),
+    );
+
+    Some((ApiKeyService::new(pool.clone(), config), pool))
+}
+
+async fn seed_merchant(pool: &deadpool_postgres::Pool) -> String {
+    let merchant_id = format!("merchant-{}", Uuid::new_v4().simple());
+    pool.get()
+        .await
+        .unwrap()
+        .execute(
+            "INSERT INTO merchants (merchant_id, vault_address, settlement_asset)
+             VALUES ($1, 'GVAULT', 'USDC')",
+            &[&merchant_id],
+        )
+        .await
+        .unwrap();
+    merchant_id
+}
+
+#[tokio::test]
+#[ignore]
+async fn test_valid_key_authenticates_as_merchant() {
+    let Some((api_keys, pool)) = setup().await else {
+        return;
+    };
+    let merchant_id = seed_merchant(&pool).await;
+
+    let issued = api_keys.issue_key(&merchant_id).await.unwrap();
+    assert!(issued.api_key.starts_with(&issued.key_prefix));
+
+    let (user, principal) =
+        authenticate_api_key(&api_keys, &issued.api_key, "/payments/qr/generate")
+            .await
+            .unwrap();
+    assert_eq!(user.user_id, merchant_id);
+    assert_eq!(user.role, Role::Merchant);
+    assert_eq!(principal.merchant_id, merchant_id);
+
+    // Only the hash is stored.
+    let stored: String = pool
+        .get()
+        .await
+        .unwrap()
+        .query_one(
+            "SELECT key_hash FROM merchant_api_keys WHERE id = $1::text::uuid",
+            &[&issued.id],
+        )
+        .await
+        .unwrap()
+        .get(0);
+    assert_ne!(stored, issued.api_key);
+}
+
+#[tokio::test]
+#[ignore]
+async fn test_revoked_key_is_rejected() {
+    let Some((api_keys, pool)) = setup().await else {
+        return;
+    };
+    let merchant_id = seed_merchant(&pool).await;
+    let issued = api_keys.issue_key(&merchant_id).await.unwrap();
+
+    api_keys.revoke_key(&merchant_id, &issued.id).await.unwrap();
+
+    let err = authenticate_api_key(&api_keys, &issued.api_key, "/payments/payments")
+        .await
+        .unwrap_err();
+    assert_eq!(err, StatusCode::UNAUTHORIZED);
+
+    let err = authenticate_api_key(&api_keys, "zaps_not-a-real-key", "/payments/payments")
+        .await
+        .unwrap_err();
+    assert_eq!(err, StatusCode::UNAUTHORIZED);
+}
+
+#[tokio::test]
+#[ignore]
+async fn test_key_is_limited_to_merchant_scoped_routes() {
+    let Some((api_keys, pool)) = setup().await else {
+        return;
+    };
+    let merchant_id = seed_merchant(&pool).await;
+    let issued = api_keys.issue_key(&merchant_id).await.unwrap();
+
+    for path in [
+        "/transfers/transfers",
+        "/identity/users/me",
+        "/admin/dashboard/stats",
+    ] {
+        let err = authenticate_api_key(&api_keys, &issued.api_key, path)
+            .await
+            .unwrap_err();
+        assert_eq!(
+            err,
+            StatusCode::FORBIDDEN,
+            "{} should be out of scope",
+            path
+        );
+    }
+}
+
+#[tokio::test]
+#[ignore]
+async fn test_keys_are_managed_per_merchant() {
+    let Some((api_keys, pool)) = setup().await else {
+        return;
+    };
+    let merchant_id = seed_merchant(&pool).await;
+    let other_merchant = seed_merchant(&pool).await;
+    let issued = api_keys.issue_key(&merchant_id).await.unwrap();
+
+    // Another merchant can't revoke this key.
+    let err = api_keys
+        .revoke_key(&other_merchant, &issued.id)
+        .await
+        .unwrap_err();
+    assert!(matches!(err, ApiError::NotFound(_)));
+    assert!(api_keys
+        .authenticate(&issued.api_key)
+        .await
+        .unwrap()
+        .is_some());
+
+    let err = api_keys.issue_key("no-such-merchant").await.unwrap_err();
+    assert!(matches!(err, ApiError::NotFound(_)));
+}
@@ -0,0 +1,176 @@
+use std::sync::{Arc, Mutex};
+
+use axum::async_trait;
+use blinks_backend::api_error::ApiError;
+use blinks_backend::config::Config;
+use blinks_backend::db;
+use blinks_backend::service::merchant_service::MerchantRegistry;
+use blinks_backend::service::MerchantService;
+use uuid::Uuid;
+
+// Note: These tests require a running database using the config.
+// Run with: cargo test --test merchant_deactivation_test -- --ignored
+
+/// Registry that records each deactivation, or rejects every one.
+#[derive(Default)]
+struct RecordingRegistry {
+    fail: bool,
+    deactivated: Mutex<Vec<String>>,
+}
+
+#[async_trait]
+impl MerchantRegistry for RecordingRegistry {
+    async fn deactivate_merchant(&self, merchant_id: &str) -> Result<String, ApiError> {
+        if self.fail {
+            return Err(ApiError::Stellar("transaction rejected".to_string()));
+        }
+        self.deactivated
+            .lock()
+            .unwrap()
+            .push(merchant_id.to_string());
+        Ok("deadbeef".to_string())
+    }
+}
+
+/// Registry that reads the merchant's row while its transaction is in
+/// flight, as any other request might.
+struct ObservingRegistry {
+    pool: Arc<deadpool_postgres::Pool>,
+    seen_active: Mutex<Option<bool>>,
+}
+
+#[async_trait]
+impl MerchantRegistry for ObservingRegistry {
+    async fn deactivate_merchant(&self, merchant_id: &str) -> Result<String, ApiError> {
+        let row = tokio::time::timeout(
+            std::time::Duration::from_secs(5),
+            self.pool.get().await.unwrap().query_one(
+                "SELECT active FROM merchants WHERE merchant_id = $1 FOR UPDATE NOWAIT",
+                &[&merchant_id],
+            ),
+        )
+        .await
+        .expect("merchant row is locked during the chain call")
+        .expect("merchant row is locked during the chain call");
+        *self.seen_active.lock().unwrap() = Some(row.get(0));
+        Ok("cafebabe".to_string())
+    }
+}
+
+async fn setup(
+    registry: Arc<dyn MerchantRegistry>,
+) -> Option<(MerchantService, Arc<deadpool_postgres::Pool>)> {
+    let config = match Config::load() {
+        Ok(c) => c,
+        Err(_) => {
+            println!("Skipping test: No config found");
+            return None;
+        }
+    };
+
+    db::run_migrations(&config.database.url)
+        .await
+        .expect("Failed to run migrations");
+    let pool = Arc::new(
+        db::create_pool(&config.database.url)
+            .await
+            .expect("Failed to create pool"),
+    );
+
+    Some((
+        MerchantService::with_registry(pool.clone(), config, registry),
+        pool,
+    ))
+}
+
+async fn create_merchant(pool: &deadpool_postgres::Pool) -> String {
+    let merchant_id = format!("deactivate-{}", Uuid::new_v4().simple());
+    pool.get()
+        .await
+        .unwrap()
+        .execute(
+            "INSERT INTO merchants (merchant_id, vault_address, settlement_asset) VALUES ($1, 'GVAULT', 'USDC')",
+            &[&merchant_id],
+        )
+        .await
+        .unwrap();
+    merchant_id
+}
+
+async fn is_active(pool: &deadpool_postgres::Pool, merchant_id: &str) -> bool {
+    pool.get()
+        .await
+        .unwrap()
+        .query_one(
+            "SELECT active FROM merchants WHERE merchant_id = $1",
+            &[&merchant_id],
+        )
+        .await
+        .unwrap()
+        .get(0)
+}
+
+#[tokio::test]
+#[ignore]
+async fn test_deactivation_updates_the_row_and_the_chain() {
+    let registry = Arc::new(RecordingRegistry::default());
+    let Some((merchants, pool)) = setup(registry.clone()).await else {
+        return;
+    };
+    let merchant_id = create_merchant(&pool).await;
+
+    let deactivation = merchants.deactivate(&merchant_id).await.unwrap();
+
+    assert_eq!(deactivation.tx_hash, "deadbeef");
+    assert!(!is_active(&pool, &merchant_id).await);
+    assert_eq!(
+        *registry.deactivated.lock().unwrap(),
+        vec![merchant_id.clone()]
+    );
+
+    // Already inactive, so there is nothing left to deactivate
+    assert!(matches!(
+        merchants.deactivate(&merchant_id).await,
+        Err(ApiError::NotFound(_))
+    ));
+    assert_eq!(registry.deactivated.lock().unwrap().len(), 1);
+}
+
+#[tokio::test]
+#[ignore]
+async fn test_chain_failure_rolls_back_the_deactivation() {
+    let registry = Arc::new(RecordingRegistry {
+        fail: true,
+        ..Default::default()
+    });
+    let Some((merchants, pool)) = setup(registry).await else {
+        return;
+    };
+    let merchant_id = create_merchant(&pool).await;
+
+    let err = merchants.deactivate(&merchant_id).await.unwrap_err();
+
+    assert!(matches!(err, ApiError::Stellar(_)), "{:?}", err);
+    assert!(is_active(&pool, &merchant_id).await);
+}
+
+#[tokio::test]
+#[ignore]
+async fn test_row_is_unlocked_and_active_until_the_chain_confirms() {
+    let Some((_, pool)) = setup(Arc::new(RecordingRegistry::default())).await else {
+        return;
+    };
+    let registry = Arc::new(ObservingRegistry {
+        pool: pool.clone(),
+        seen_active: Mutex::new(None),
+    });
+    let Some((merchants, _)) = setup(registry.clone()).await else {
+        return;
+    };
+    let merchant_id = create_merchant(&pool).await;
+
+    merchants.deactivate(&merchant_id).await.unwrap();
+
+    assert_eq!(*registry.seen_active.lock().unwrap(), Some(true));
+    assert!(!is_active(&pool, &merchant_id).await);
+}
@@ -0,0 +1,219 @@
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex};
+
+use async_trait::async_trait;
+use axum::{routing, Json, Router};
+use httpmock::{Method::POST, MockServer};
+use ring::hmac;
+use serde_json::{json, Value};
+use uuid::Uuid;
+
+use blinks_backend::config::Config;
+use blinks_backend::db;
+use blinks_backend::job_processors::{
+    merchant_webhook_job, sign_webhook, BlockchainTxProcessor, PaymentConfirmationProcessor,
+    WebhookProcessor, WEBHOOK_SIGNATURE_HEADER,
+};
+use blinks_backend::job_types::{JobPayload, JobType};
+use blinks_backend::models::PaymentStatus;
+use blinks_backend::queue::{JobEnqueuer, JobProcessor};
+use blinks_backend::service::ServiceContainer;
+
+/// Job queue stand-in that keeps enqueued jobs for inspection.
+#[derive(Default)]
+struct RecordingQueue {
+    jobs: Mutex<Vec<JobPayload>>,
+}
+
+#[async_trait]
+impl JobEnqueuer for RecordingQueue {
+    async fn enqueue(&self, job: JobPayload) -> anyhow::Result<()> {
+        self.jobs.lock().unwrap().push(job);
+        Ok(())
+    }
+}
+
+/// Soroban RPC stand-in: submissions are accepted with the envelope as
+/// their hash, and every transaction has landed successfully.
+async fn rpc(Json(body): Json<Value>) -> Json<Value> {
+    let result = match body["method"].as_str() {
+        Some("sendTransaction") => {
+            json!({ "status": "PENDING", "hash": body["params"]["transaction"] })
+        }
+        Some("getTransaction") => json!({ "status": "SUCCESS" }),
+        _ => return Json(json!({ "jsonrpc": "2.0", "id": 1, "error": { "code": -32601 } })),
+    };
+
+    Json(json!({ "jsonrpc": "2.0", "id": 1, "result": result }))
+}
+
+async fn spawn_rpc() -> String {
+    let app = Router::new().route("/", routing::post(rpc));
+
+    let listener = tokio::net::TcpListener::bind(SocketAddr::from(([127, 0, 0, 1], 0)))
+        .await
+        .unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move {
+        axum::serve(listener, app).await.unwrap();
+    });
+
+    format!("http://{}", addr)
+}
+
+fn field<'a>(job: &'a JobPayload, name: &str) -> &'a str {
+    job.payload[name].as_str().unwrap()
+}
+
+/// Check a delivery's signature the way a merchant would.
+fn assert_signed(secret: &str, job: &JobPayload) {
+    let key = hmac::Key::new(hmac::HMAC_SHA256, secret.as_bytes());
+    let signature = hex::decode(field(job, "signature")).unwrap();
+    hmac::verify(&key, field(job, "body").as_bytes(), &signature)
+        .expect("webhook signature does not verify");
+}
+
+#[test]
+fn test_webhook_job_is_signed_with_the_merchant_secret() {
+    let event = json!({ "event": "payment.settled", "payment_id": "p-1" });
+    let job = merchant_webhook_job("https://merchant.example/hook", "whsec_test", &event);
+
+    assert_eq!(job.job_type, JobType::Webhook);
+    assert_eq!(field(&job, "url"), "https://merchant.example/hook");
+    assert_signed("whsec_test", &job);
+    assert_eq!(
+        serde_json::from_str::<Value>(field(&job, "body")).unwrap(),
+        event
+    );
+    // The secret itself never goes on the queue
+    assert!(!serde_json::to_string(&job).unwrap().contains("whsec_test"));
+}
+
+#[tokio::test]
+async fn test_webhook_is_posted_with_its_signature() {
+    let server = MockServer::start_async().await;
+    let event = json!({ "event": "payment.settled", "payment_id": "p-1" });
+    let body = event.to_string();
+    let hook = server
+        .mock_async(|when, then| {
+            when.method(POST)
+                .path("/hook")
+                .header("content-type", "application/json")
+                .header(WEBHOOK_SIGNATURE_HEADER, sign_webhook("whsec_test", &body))
+                .body(body.clone());
+            then.status(204);
+        })
+        .await;
+
+    let job = merchant_webhook_job(&server.url("/hook"), "whsec_test", &event);
+    let result = WebhookProcessor::new().process(&job).await.unwrap();
+
+    assert!(result.success, "{:?}", result.error);
+    hook.assert_async().await;
+}
+
+#[tokio::test]
+async fn test_rejected_webhook_fails_the_job_for_retry() {
+    let server = MockServer::start_async().await;
+    server
+        .mock_async(|when, then| {
+            when.method(POST).path("/hook");
+            then.status(500);
+        })
+        .await;
+
+    let job = merchant_webhook_job(&server.url("/hook"), "whsec_test", &json!({}));
+    let result = WebhookProcessor::new().process(&job).await.unwrap();
+
+    assert!(!result.success);
+}
+
+// Note: This test requires a running database using the config.
+// Run with: cargo test --test merchant_webhook_test -- --ignored
+#[tokio::test]
+#[ignore]
+async fn test_payment_settled_on_chain_enqueues_signed_webhook() {
+    let mut config = match Config::load() {
+        Ok(c) => c,
+        Err(_) => {
+            println!("Skipping test: No config found");
+            return;
+        }
+    };
+    config.stellar_network.rpc_url = spawn_rpc().await;
+    db::run_migrations(&config.database.url)
+        .await
+        .expect("Failed to run migrations");
+    let pool = db::create_pool(&config.database.url)
+        .await
+        .expect("Failed to create pool");
+    let services = ServiceContainer::new(pool, config)
+        .await
+        .expect("Failed to create services");
+
+    let client = services.db_pool.get().await.unwrap();
+    let merchant_id = format!("webhook-{}", Uuid::new_v4().simple());
+    client
+        .execute(
+            "INSERT INTO merchants (merchant_id, vault_address, settlement_asset) VALUES ($1, 'GVAULT', 'USDC')",
+            &[&merchant_id],
+        )
+        .await
+        .unwrap();
+    let payment_id: Uuid = client
+        .query_one(
+            "INSERT INTO payments (from_address, merchant_id, send_asset, send_amount, status)
+             VALUES ('GSENDER', $1, 'USDC', 1000, 'pending') RETURNING id",
+            &[&merchant_id],
+        )
+        .await
+        .unwrap()
+        .get(0);
+
+    let webhook = services
+        .merchant_webhooks
+        .register(&merchant_id, "https://merchant.example/settled")
+        .await
+        .unwrap();
+
+    // Submitting only queues the on-chain confirmation
+    let queue = Arc::new(RecordingQueue::default());
+    let tx_hash = format!("signed-{}", Uuid::new_v4().simple());
+    services
+        .payment
+        .submit_payment(payment_id, tx_hash.clone(), queue.as_ref())
+        .await
+        .unwrap();
+    let confirmation = queue.jobs.lock().unwrap().pop().expect("confirmation job");
+    assert_eq!(confirmation.job_type, JobType::BlockchainTx);
+    assert!(queue.jobs.lock().unwrap().is_empty());
+
+    // A redelivered confirmation doesn't notify the merchant twice
+    let processor = PaymentConfirmationProcessor::new(
+        Arc::new(services.payment.clone()),
+        queue.clone(),
+        Box::new(BlockchainTxProcessor::new()),
+    );
+    for _ in 0..2 {
+        let result = processor.process(&confirmation).await.unwrap();
+        assert!(result.success, "{:?}", result.error);
+    }
+
+    let payment = services.payment.get_payment(payment_id).await.unwrap();
+    assert!(matches!(payment.status, PaymentStatus::Completed));
+
+    let jobs = queue.jobs.lock().unwrap();
+    assert_eq!(jobs.len(), 1);
+    let job = &jobs[0];
+    assert_eq!(job.job_type, JobType::Webhook);
+    assert_eq!(field(job, "url"), "https://merchant.example/settled");
+    assert_signed(&webhook.secret, job);
+
+    let event: Value = serde_json::from_str(field(job, "body")).unwrap();
+    assert_eq!(event["event"], "payment.settled");
+    assert_eq!(event["payment_id"], payment_id.to_string());
+    assert_eq!(event["merchant_id"], merchant_id);
+    assert_eq!(event["amount"], 1000);
+    assert_eq!(event["asset"], "USDC");
+    assert_eq!(event["tx_hash"], tx_hash);
+}
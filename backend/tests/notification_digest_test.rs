@@ -0,0 +1,123 @@
+use std::sync::Arc;
+
+use blinks_backend::config::Config;
+use blinks_backend::db;
+use blinks_backend::service::NotificationService;
+use uuid::Uuid;
+
+// Note: These tests require a running database using the config.
+// Run with: cargo test --test notification_digest_test -- --ignored
+
+async fn setup() -> Option<(NotificationService, Arc<deadpool_postgres::Pool>)> {
+    let config = match Config::load() {
+        Ok(c) => c,
+        Err(_) => {
+            println!("Skipping test: No config found");
+            return None;
+        }
+    };
+
+    db::run_migrations(&config.database.url)
+        .await
+        .expect("Failed to run migrations");
+    let pool = Arc::new(
+        db::create_pool(&config.database.url)
+            .await
+            .expect("Failed to create pool"),
+    );
+
+    Some((NotificationService::new(pool.clone(), config), pool))
+}
+
+async fn seed_user(pool: &deadpool_postgres::Pool) -> String {
+    let suffix = Uuid::new_v4().simple().to_string();
+    let user_id = format!("digest-{}", suffix);
+    pool.get()
+        .await
+        .unwrap()
+        .execute(
+            "INSERT INTO users (user_id, stellar_address, pin_hash) VALUES ($1, $2, 'x')",
+            &[&user_id, &format!("G{}", suffix.to_uppercase())],
+        )
+        .await
+        .unwrap();
+    user_id
+}
+
+#[tokio::test]
+#[ignore]
+async fn test_window_opens_once_and_closes_after_delivery() {
+    let Some((notifications, pool)) = setup().await else {
+        return;
+    };
+    let user_id = seed_user(&pool).await;
+
+    let retried = Uuid::new_v4();
+    assert!(notifications
+        .hold_for_digest(&user_id, retried, "payment", "Paid 5 USDC")
+        .await
+        .unwrap());
+    // A retried job isn't held twice, and later events join the open window.
+    assert!(!notifications
+        .hold_for_digest(&user_id, retried, "payment", "Paid 5 USDC")
+        .await
+        .unwrap());
+    assert!(!notifications
+        .hold_for_digest(&user_id, Uuid::new_v4(), "transfer", "Received 2 XLM")
+        .await
+        .unwrap());
+
+    let held = notifications.held_notifications(&user_id).await.unwrap();
+    assert_eq!(held.len(), 2);
+    assert_eq!(held[0].message, "Paid 5 USDC");
+
+    // Something arriving after the digest was built keeps the window open.
+    notifications
+        .hold_for_digest(&user_id, Uuid::new_v4(), "payment", "Paid 9 USDC")
+        .await
+        .unwrap();
+    let delivered: Vec<String> = held.into_iter().map(|n| n.id).collect();
+    assert!(notifications
+        .clear_digest(&user_id, &delivered)
+        .await
+        .unwrap());
+
+    let left = notifications.held_notifications(&user_id).await.unwrap();
+    assert_eq!(left.len(), 1);
+    assert_eq!(left[0].message, "Paid 9 USDC");
+
+    assert!(!notifications
+        .clear_digest(&user_id, &[left[0].id.clone()])
+        .await
+        .unwrap());
+    assert!(notifications
+        .hold_for_digest(&user_id, Uuid::new_v4(), "payment", "Paid 1 USDC")
+        .await
+        .unwrap());
+}
+
+#[tokio::test]
+#[ignore]
+async fn test_profile_preference_opts_out_of_digests() {
+    let Some((notifications, pool)) = setup().await else {
+        return;
+    };
+    let user_id = seed_user(&pool).await;
+    assert!(notifications.wants_digest(&user_id).await.unwrap());
+
+    pool.get()
+        .await
+        .unwrap()
+        .execute(
+            r#"
+            INSERT INTO user_profiles (user_id, display_name, metadata)
+            SELECT id, 'Digest Tester', '{"preferences": {"notifications": {"digest": false}}}'::jsonb
+            FROM users WHERE user_id = $1
+            "#,
+            &[&user_id],
+        )
+        .await
+        .unwrap();
+
+    assert!(!notifications.wants_digest(&user_id).await.unwrap());
+}
@@ -0,0 +1,66 @@
+use axum::{body::Body, http::Request, http::StatusCode, routing::get, Router};
+use http_body_util::BodyExt;
+use serde_json::Value;
+use tower::util::ServiceExt; // for oneshot
+
+use blinks_backend::http::openapi::{openapi_json, swagger_ui};
+
+fn app() -> Router {
+    Router::new()
+        .route("/openapi.json", get(openapi_json))
+        .route("/docs", get(swagger_ui))
+}
+
+async fn get_body(uri: &str) -> (StatusCode, Vec<u8>) {
+    let response = app()
+        .oneshot(Request::get(uri).body(Body::empty()).unwrap())
+        .await
+        .unwrap();
+    let status = response.status();
+    let body = response.into_body().collect().await.unwrap().to_bytes();
+    (status, body.to_vec())
+}
+
+#[tokio::test]
+async fn test_openapi_spec_lists_known_routes() {
+    let (status, body) = get_body("/openapi.json").await;
+    assert_eq!(status, StatusCode::OK);
+
+    let spec: Value = serde_json::from_slice(&body).expect("spec is not valid JSON");
+    assert!(spec["openapi"].as_str().unwrap().starts_with("3."));
+
+    let paths = &spec["paths"];
+    for (path, method) in [
+        ("/auth/login", "post"),
+        ("/auth/refresh", "post"),
+        ("/auth/whoami", "get"),
+        ("/withdrawals", "post"),
+        ("/withdrawals", "get"),
+        ("/withdrawals/{id}/status", "get"),
+        ("/profiles/me", "get"),
+        ("/profiles/{user_id}", "patch"),
+        ("/transfers/{id}/submit", "post"),
+        ("/health/live", "get"),
+    ] {
+        assert!(
+            paths[path][method].is_object(),
+            "missing {} {}",
+            method,
+            path
+        );
+    }
+
+    let schemas = &spec["components"]["schemas"];
+    let withdrawal = &schemas["CreateWithdrawalRequest"];
+    assert_eq!(withdrawal["properties"]["amount"]["type"], "integer");
+    assert_eq!(withdrawal["example"]["asset"], "USDC");
+    assert!(schemas["UserProfileResponseDto"].is_object());
+    assert!(spec["components"]["securitySchemes"]["bearer_auth"].is_object());
+}
+
+#[tokio::test]
+async fn test_swagger_ui_points_at_the_spec() {
+    let (status, body) = get_body("/docs").await;
+    assert_eq!(status, StatusCode::OK);
+    assert!(String::from_utf8(body).unwrap().contains("/openapi.json"));
+}
@@ -0,0 +1,275 @@
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use async_trait::async_trait;
+use axum::{
+    body::{Body, BodyDataStream},
+    http::{Request, StatusCode},
+    routing::{get, post},
+    Extension, Json, Router,
+};
+use futures::StreamExt;
+use serde_json::{json, Value};
+use tower::util::ServiceExt;
+use uuid::Uuid;
+
+use blinks_backend::config::Config;
+use blinks_backend::db;
+use blinks_backend::http::payments::stream_payment_events;
+use blinks_backend::job_processors::{BlockchainTxProcessor, PaymentConfirmationProcessor};
+use blinks_backend::job_types::{JobPayload, JobType};
+use blinks_backend::middleware::auth::AuthenticatedUser;
+use blinks_backend::models::PaymentStatus;
+use blinks_backend::queue::{JobEnqueuer, JobProcessor};
+use blinks_backend::role::Role;
+use blinks_backend::service::ServiceContainer;
+
+// Note: These tests require a running database using the config.
+// Run with: cargo test --test payment_events_test -- --ignored
+
+/// Soroban RPC stand-in: every submission is accepted with the envelope as
+/// its hash, and every transaction has landed successfully.
+async fn rpc(Json(body): Json<Value>) -> Json<Value> {
+    let result = match body["method"].as_str() {
+        Some("sendTransaction") => {
+            json!({ "status": "PENDING", "hash": body["params"]["transaction"] })
+        }
+        Some("getTransaction") => json!({ "status": "SUCCESS" }),
+        _ => return Json(json!({ "jsonrpc": "2.0", "id": 1, "error": { "code": -32601 } })),
+    };
+
+    Json(json!({ "jsonrpc": "2.0", "id": 1, "result": result }))
+}
+
+async fn spawn_rpc() -> String {
+    let app = Router::new().route("/", post(rpc));
+
+    let listener = tokio::net::TcpListener::bind(SocketAddr::from(([127, 0, 0, 1], 0)))
+        .await
+        .unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move {
+        axum::serve(listener, app).await.unwrap();
+    });
+
+    format!("http://{}", addr)
+}
+
+/// Job queue stand-in that keeps enqueued jobs for inspection.
+#[derive(Default)]
+struct RecordingQueue {
+    jobs: Mutex<Vec<JobPayload>>,
+}
+
+#[async_trait]
+impl JobEnqueuer for RecordingQueue {
+    async fn enqueue(&self, job: JobPayload) -> anyhow::Result<()> {
+        self.jobs.lock().unwrap().push(job);
+        Ok(())
+    }
+}
+
+async fn setup() -> Option<Arc<ServiceContainer>> {
+    let mut config = match Config::load() {
+        Ok(c) => c,
+        Err(_) => {
+            println!("Skipping test: No config found");
+            return None;
+        }
+    };
+    config.stellar_network.rpc_url = spawn_rpc().await;
+
+    db::run_migrations(&config.database.url)
+        .await
+        .expect("Failed to run migrations");
+    let pool = db::create_pool(&config.database.url)
+        .await
+        .expect("Failed to create pool");
+
+    Some(Arc::new(
+        ServiceContainer::new(pool, config)
+            .await
+            .expect("Failed to create services"),
+    ))
+}
+
+/// Insert a merchant and a pending payment to it; returns (merchant, payment).
+async fn create_payment(services: &ServiceContainer) -> (String, Uuid) {
+    let client = services.db_pool.get().await.unwrap();
+    let merchant_id = format!("sse-{}", Uuid::new_v4().simple());
+    client
+        .execute(
+            "INSERT INTO merchants (merchant_id, vault_address, settlement_asset) VALUES ($1, 'GVAULT', 'USDC')",
+            &[&merchant_id],
+        )
+        .await
+        .unwrap();
+    let payment_id: Uuid = client
+        .query_one(
+            "INSERT INTO payments (from_address, merchant_id, send_asset, send_amount, status)
+             VALUES ('GSENDER', $1, 'USDC', 1000, 'pending') RETURNING id",
+            &[&merchant_id],
+        )
+        .await
+        .unwrap()
+        .get(0);
+    (merchant_id, payment_id)
+}
+
+/// The events route as the given caller would see it.
+fn app(services: Arc<ServiceContainer>, user_id: &str, role: Role) -> Router {
+    Router::new()
+        .route("/payments/:id/events", get(stream_payment_events))
+        .layer(Extension(AuthenticatedUser {
+            user_id: user_id.to_string(),
+            role,
+        }))
+        .with_state(services)
+}
+
+fn events_request(payment_id: Uuid) -> Request<Body> {
+    Request::builder()
+        .uri(format!("/payments/{}/events", payment_id))
+        .body(Body::empty())
+        .unwrap()
+}
+
+/// Read body chunks until one carries an SSE event, skipping keep-alives.
+async fn next_event(body: &mut BodyDataStream) -> Option<String> {
+    loop {
+        let chunk = tokio::time::timeout(Duration::from_secs(5), body.next())
+            .await
+            .expect("timed out waiting for an event")?
+            .unwrap();
+        let text = String::from_utf8(chunk.to_vec()).unwrap();
+        if text.contains("event: status") {
+            return Some(text);
+        }
+    }
+}
+
+#[tokio::test]
+#[ignore]
+async fn test_confirmation_is_pushed_to_a_connected_client() {
+    let Some(services) = setup().await else {
+        return;
+    };
+    let (merchant_id, payment_id) = create_payment(&services).await;
+
+    let response = app(services.clone(), &merchant_id, Role::Merchant)
+        .oneshot(events_request(payment_id))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(response.headers()["content-type"], "text/event-stream");
+    let mut body = response.into_body().into_data_stream();
+
+    let initial = next_event(&mut body).await.unwrap();
+    assert!(initial.contains(r#""status":"pending""#), "{}", initial);
+
+    services
+        .payment
+        .update_payment_status(
+            payment_id,
+            PaymentStatus::Completed,
+            Some(format!("tx_{}", Uuid::new_v4().simple())),
+            services.job_queue.as_ref(),
+        )
+        .await
+        .unwrap();
+
+    let confirmed = next_event(&mut body).await.unwrap();
+    assert!(
+        confirmed.contains(r#""status":"completed""#),
+        "{}",
+        confirmed
+    );
+    assert!(confirmed.contains(&payment_id.to_string()));
+    // The stream closes once the payment has settled.
+    assert!(next_event(&mut body).await.is_none());
+}
+
+#[tokio::test]
+#[ignore]
+async fn test_on_chain_settlement_is_pushed_to_a_connected_client() {
+    let Some(services) = setup().await else {
+        return;
+    };
+    let (merchant_id, payment_id) = create_payment(&services).await;
+
+    let response = app(services.clone(), &merchant_id, Role::Merchant)
+        .oneshot(events_request(payment_id))
+        .await
+        .unwrap();
+    let mut body = response.into_body().into_data_stream();
+    let initial = next_event(&mut body).await.unwrap();
+    assert!(initial.contains(r#""status":"pending""#), "{}", initial);
+
+    let queue = Arc::new(RecordingQueue::default());
+    let xdr = format!("signed-{}", Uuid::new_v4().simple());
+    let submitted = services
+        .payment
+        .submit_payment(payment_id, xdr.clone(), queue.as_ref())
+        .await
+        .unwrap();
+    assert_eq!(submitted.tx_hash.as_deref(), Some(xdr.as_str()));
+    let processing = next_event(&mut body).await.unwrap();
+    assert!(
+        processing.contains(r#""status":"processing""#),
+        "{}",
+        processing
+    );
+
+    let job = queue.jobs.lock().unwrap().pop().expect("confirmation job");
+    assert_eq!(job.job_type, JobType::BlockchainTx);
+    assert_eq!(job.payload["payment_id"], json!(payment_id.to_string()));
+
+    let processor = PaymentConfirmationProcessor::new(
+        Arc::new(services.payment.clone()),
+        queue.clone(),
+        Box::new(BlockchainTxProcessor::new()),
+    );
+    let result = processor.process(&job).await.unwrap();
+    assert!(result.success, "{:?}", result.error);
+
+    let confirmed = next_event(&mut body).await.unwrap();
+    assert!(
+        confirmed.contains(r#""status":"completed""#),
+        "{}",
+        confirmed
+    );
+    assert!(next_event(&mut body).await.is_none());
+}
+
+#[tokio::test]
+#[ignore]
+async fn test_non_merchant_sharing_the_merchant_id_cannot_watch() {
+    let Some(services) = setup().await else {
+        return;
+    };
+    let (merchant_id, payment_id) = create_payment(&services).await;
+
+    let response = app(services.clone(), &merchant_id, Role::User)
+        .oneshot(events_request(payment_id))
+        .await
+        .unwrap();
+
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+#[ignore]
+async fn test_unrelated_user_cannot_watch_a_payment() {
+    let Some(services) = setup().await else {
+        return;
+    };
+    let (_, payment_id) = create_payment(&services).await;
+
+    let response = app(services.clone(), "someone-else", Role::Merchant)
+        .oneshot(events_request(payment_id))
+        .await
+        .unwrap();
+
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+}
@@ -0,0 +1,101 @@
+use std::sync::Arc;
+
+use blinks_backend::api_error::ApiError;
+use blinks_backend::auth;
+use blinks_backend::config::Config;
+use blinks_backend::db;
+use blinks_backend::service::IdentityService;
+use uuid::Uuid;
+
+// Note: These tests require a running database using the config.
+// Run with: cargo test --test pin_rehash_test -- --ignored
+
+/// Cost the "old" hashes were made with; the service is configured above it.
+const OLD_COST: u32 = 4;
+const CURRENT_COST: u32 = 5;
+
+async fn setup() -> Option<(IdentityService, Arc<deadpool_postgres::Pool>)> {
+    let mut config = match Config::load() {
+        Ok(c) => c,
+        Err(_) => {
+            println!("Skipping test: No config found");
+            return None;
+        }
+    };
+
+    db::run_migrations(&config.database.url)
+        .await
+        .expect("Failed to run migrations");
+    let pool = Arc::new(
+        db::create_pool(&config.database.url)
+            .await
+            .expect("Failed to create pool"),
+    );
+
+    config.pin_hash.cost = CURRENT_COST;
+    Some((IdentityService::new(pool.clone(), config), pool))
+}
+
+async fn seed_user(pool: &deadpool_postgres::Pool, pin_hash: &str) -> String {
+    let suffix = Uuid::new_v4().simple().to_string();
+    let user_id = format!("rehash-{}", suffix);
+    pool.get()
+        .await
+        .unwrap()
+        .execute(
+            "INSERT INTO users (user_id, stellar_address, pin_hash) VALUES ($1, $2, $3)",
+            &[&user_id, &format!("G{}", suffix.to_uppercase()), &pin_hash],
+        )
+        .await
+        .unwrap();
+    user_id
+}
+
+async fn stored_hash(pool: &deadpool_postgres::Pool, user_id: &str) -> String {
+    pool.get()
+        .await
+        .unwrap()
+        .query_one("SELECT pin_hash FROM users WHERE user_id = $1", &[&user_id])
+        .await
+        .unwrap()
+        .get(0)
+}
+
+#[tokio::test]
+#[ignore]
+async fn test_old_hash_verifies_and_is_upgraded_on_login() {
+    let Some((identity, pool)) = setup().await else {
+        return;
+    };
+    let old_hash = auth::hash_pin("2468", OLD_COST).unwrap();
+    let user_id = seed_user(&pool, &old_hash).await;
+
+    let user = identity.verify_credentials(&user_id, "2468").await.unwrap();
+    assert_eq!(user.user_id, user_id);
+
+    let upgraded = stored_hash(&pool, &user_id).await;
+    assert_ne!(upgraded, old_hash);
+    assert!(!auth::pin_needs_rehash(&upgraded, CURRENT_COST));
+    assert!(auth::verify_pin("2468", &upgraded).unwrap());
+
+    // Once upgraded, later logins leave the hash alone.
+    identity.verify_credentials(&user_id, "2468").await.unwrap();
+    assert_eq!(stored_hash(&pool, &user_id).await, upgraded);
+}
+
+#[tokio::test]
+#[ignore]
+async fn test_wrong_pin_does_not_upgrade() {
+    let Some((identity, pool)) = setup().await else {
+        return;
+    };
+    let old_hash = auth::hash_pin("2468", OLD_COST).unwrap();
+    let user_id = seed_user(&pool, &old_hash).await;
+
+    let err = identity
+        .verify_credentials(&user_id, "1357")
+        .await
+        .unwrap_err();
+    assert!(matches!(err, ApiError::Authentication(_)));
+    assert_eq!(stored_hash(&pool, &user_id).await, old_hash);
+}
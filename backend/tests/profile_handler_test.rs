@@ -6,6 +6,7 @@ use axum::{
 use lazy_static::lazy_static;
 use serde_json::{json, Value};
 use tokio::sync::Mutex;
+use tokio_util::sync::CancellationToken;
 use tower::util::ServiceExt;
 
 use blinks_backend::{app::create_app, config::Config, db};
@@ -39,9 +40,10 @@ async fn create_test_app() -> Router {
         .await
         .expect("Failed to create pool");
 
-    create_app(pool, config)
+    let (app, _workers) = create_app(pool, config, CancellationToken::new())
         .await
-        .expect("Failed to create app")
+        .expect("Failed to create app");
+    app
 }
 
 /// Helper to make JSON POST request
@@ -203,7 +205,7 @@ async fn test_create_profile_success() {
     let app = create_test_app().await;
 
     let user_id = format!("testuser_{}", uuid::Uuid::new_v4());
-    let token = register_and_get_token(&app, &user_id, "1234").await;
+    let token = register_and_get_token(&app, &user_id, "2580").await;
 
     let response = app
         .clone()
@@ -237,7 +239,7 @@ async fn test_create_profile_duplicate() {
     let app = create_test_app().await;
 
     let user_id = format!("testuser_{}", uuid::Uuid::new_v4());
-    let token = register_and_get_token(&app, &user_id, "1234").await;
+    let token = register_and_get_token(&app, &user_id, "2580").await;
 
     // Create first profile
     let _ = app
@@ -275,7 +277,7 @@ async fn test_create_profile_validation_empty_display_name() {
     let app = create_test_app().await;
 
     let user_id = format!("testuser_{}", uuid::Uuid::new_v4());
-    let token = register_and_get_token(&app, &user_id, "1234").await;
+    let token = register_and_get_token(&app, &user_id, "2580").await;
 
     let response = app
         .clone()
@@ -299,7 +301,7 @@ async fn test_create_profile_validation_display_name_too_long() {
     let app = create_test_app().await;
 
     let user_id = format!("testuser_{}", uuid::Uuid::new_v4());
-    let token = register_and_get_token(&app, &user_id, "1234").await;
+    let token = register_and_get_token(&app, &user_id, "2580").await;
 
     let long_name = "a".repeat(101);
     let response = app
@@ -324,7 +326,7 @@ async fn test_create_profile_validation_bio_too_long() {
     let app = create_test_app().await;
 
     let user_id = format!("testuser_{}", uuid::Uuid::new_v4());
-    let token = register_and_get_token(&app, &user_id, "1234").await;
+    let token = register_and_get_token(&app, &user_id, "2580").await;
 
     let long_bio = "a".repeat(501);
     let response = app
@@ -350,7 +352,7 @@ async fn test_create_profile_validation_invalid_avatar_url() {
     let app = create_test_app().await;
 
     let user_id = format!("testuser_{}", uuid::Uuid::new_v4());
-    let token = register_and_get_token(&app, &user_id, "1234").await;
+    let token = register_and_get_token(&app, &user_id, "2580").await;
 
     let response = app
         .clone()
@@ -375,7 +377,7 @@ async fn test_get_profile_success() {
     let app = create_test_app().await;
 
     let user_id = format!("testuser_{}", uuid::Uuid::new_v4());
-    let token = register_and_get_token(&app, &user_id, "1234").await;
+    let token = register_and_get_token(&app, &user_id, "2580").await;
 
     // Create profile first
     let create_response = app
@@ -431,7 +433,7 @@ async fn test_get_my_profile_success() {
     let app = create_test_app().await;
 
     let user_id = format!("testuser_{}", uuid::Uuid::new_v4());
-    let token = register_and_get_token(&app, &user_id, "1234").await;
+    let token = register_and_get_token(&app, &user_id, "2580").await;
 
     // Create profile first
     let _ = app
@@ -467,7 +469,7 @@ async fn test_update_profile_success() {
     let app = create_test_app().await;
 
     let user_id = format!("testuser_{}", uuid::Uuid::new_v4());
-    let token = register_and_get_token(&app, &user_id, "1234").await;
+    let token = register_and_get_token(&app, &user_id, "2580").await;
 
     // Create profile first
     let _ = app
@@ -489,7 +491,8 @@ async fn test_update_profile_success() {
             &format!("/profiles/{}", user_id),
             json!({
                 "display_name": "Updated Name",
-                "bio": "Updated bio"
+                "bio": "Updated bio",
+                "version": 1
             }),
             &token,
         ))
@@ -502,6 +505,85 @@ async fn test_update_profile_success() {
 
     assert_eq!(body["display_name"], "Updated Name");
     assert_eq!(body["bio"], "Updated bio");
+    assert_eq!(body["version"], 2);
+}
+
+#[tokio::test]
+#[ignore]
+async fn test_update_profile_stale_version_conflicts() {
+    let app = create_test_app().await;
+
+    let user_id = format!("testuser_{}", uuid::Uuid::new_v4());
+    let token = register_and_get_token(&app, &user_id, "2580").await;
+
+    let response = app
+        .clone()
+        .oneshot(json_post_auth(
+            "/profiles/",
+            json!({
+                "display_name": "Original Name"
+            }),
+            &token,
+        ))
+        .await
+        .unwrap();
+    let created = parse_response(response).await;
+    assert_eq!(created["version"], 1);
+
+    // First device updates with the version it read, via If-Match.
+    let mut request = json_patch_auth(
+        &format!("/profiles/{}", user_id),
+        json!({
+            "display_name": "From Phone"
+        }),
+        &token,
+    );
+    request
+        .headers_mut()
+        .insert("If-Match", "\"1\"".parse().unwrap());
+    let response = app.clone().oneshot(request).await.unwrap();
+    let status = response.status();
+    let body = parse_response(response).await;
+    assert_eq!(status, StatusCode::OK, "Response body: {:?}", body);
+    assert_eq!(body["version"], 2);
+
+    // Second device still holds version 1.
+    let response = app
+        .clone()
+        .oneshot(json_patch_auth(
+            &format!("/profiles/{}", user_id),
+            json!({
+                "display_name": "From Laptop",
+                "version": 1
+            }),
+            &token,
+        ))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::CONFLICT);
+
+    let response = app
+        .clone()
+        .oneshot(json_get(&format!("/profiles/{}", user_id)))
+        .await
+        .unwrap();
+    let body = parse_response(response).await;
+    assert_eq!(body["display_name"], "From Phone");
+    assert_eq!(body["version"], 2);
+
+    // A version is required.
+    let response = app
+        .clone()
+        .oneshot(json_patch_auth(
+            &format!("/profiles/{}", user_id),
+            json!({
+                "display_name": "No Version"
+            }),
+            &token,
+        ))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
 }
 
 #[tokio::test]
@@ -512,8 +594,8 @@ async fn test_update_profile_rbac_user_cannot_update_other() {
     let user1_id = format!("testuser1_{}", uuid::Uuid::new_v4());
     let user2_id = format!("testuser2_{}", uuid::Uuid::new_v4());
 
-    let token1 = register_and_get_token(&app, &user1_id, "1234").await;
-    let token2 = register_and_get_token(&app, &user2_id, "1234").await;
+    let token1 = register_and_get_token(&app, &user1_id, "2580").await;
+    let token2 = register_and_get_token(&app, &user2_id, "2580").await;
 
     // User1 creates profile
     let _ = app
@@ -552,7 +634,7 @@ async fn test_update_profile_rbac_admin_can_update_any() {
 
     // Create regular user
     let user_id = format!("testuser_{}", uuid::Uuid::new_v4());
-    let user_token = register_and_get_token(&app, &user_id, "1234").await;
+    let user_token = register_and_get_token(&app, &user_id, "2580").await;
 
     // Create admin user (we'll need to manually set role in DB or use a test helper)
     // For now, we'll test that admin can update - this requires admin token generation
@@ -581,7 +663,7 @@ async fn test_update_profile_validation() {
     let app = create_test_app().await;
 
     let user_id = format!("testuser_{}", uuid::Uuid::new_v4());
-    let token = register_and_get_token(&app, &user_id, "1234").await;
+    let token = register_and_get_token(&app, &user_id, "2580").await;
 
     // Create profile first
     let _ = app
@@ -602,7 +684,8 @@ async fn test_update_profile_validation() {
         .oneshot(json_patch_auth(
             &format!("/profiles/{}", user_id),
             json!({
-                "display_name": ""  // Empty display name
+                "display_name": "",  // Empty display name
+                "version": 1
             }),
             &token,
         ))
@@ -619,7 +702,7 @@ async fn test_delete_profile_success() {
     let app = create_test_app().await;
 
     let user_id = format!("testuser_{}", uuid::Uuid::new_v4());
-    let token = register_and_get_token(&app, &user_id, "1234").await;
+    let token = register_and_get_token(&app, &user_id, "2580").await;
 
     // Create profile first
     let _ = app
@@ -661,8 +744,8 @@ async fn test_delete_profile_rbac_user_cannot_delete_other() {
     let user1_id = format!("testuser1_{}", uuid::Uuid::new_v4());
     let user2_id = format!("testuser2_{}", uuid::Uuid::new_v4());
 
-    let token1 = register_and_get_token(&app, &user1_id, "1234").await;
-    let token2 = register_and_get_token(&app, &user2_id, "1234").await;
+    let token1 = register_and_get_token(&app, &user1_id, "2580").await;
+    let token2 = register_and_get_token(&app, &user2_id, "2580").await;
 
     // User1 creates profile
     let _ = app
@@ -0,0 +1,169 @@
+use std::sync::Arc;
+
+use blinks_backend::api_error::ApiError;
+use blinks_backend::config::Config;
+use blinks_backend::db;
+use blinks_backend::service::{profile_service::ProfileChanges, ProfileService};
+use uuid::Uuid;
+
+// Note: These tests require a running database using the config.
+// Run with: cargo test --test profile_soft_delete_test -- --ignored
+
+async fn setup() -> Option<(ProfileService, Arc<deadpool_postgres::Pool>)> {
+    let config = match Config::load() {
+        Ok(c) => c,
+        Err(_) => {
+            println!("Skipping test: No config found");
+            return None;
+        }
+    };
+
+    db::run_migrations(&config.database.url)
+        .await
+        .expect("Failed to run migrations");
+    let pool = Arc::new(
+        db::create_pool(&config.database.url)
+            .await
+            .expect("Failed to create pool"),
+    );
+
+    Some((ProfileService::new(pool.clone(), config), pool))
+}
+
+/// Create a user with a profile and return the user's internal id.
+async fn seed_profile(profiles: &ProfileService, pool: &deadpool_postgres::Pool) -> Uuid {
+    let suffix = Uuid::new_v4().simple().to_string();
+    let id: Uuid = pool
+        .get()
+        .await
+        .unwrap()
+        .query_one(
+            "INSERT INTO users (user_id, stellar_address, pin_hash) VALUES ($1, $2, 'x') RETURNING id",
+            &[&format!("profile-{}", suffix), &format!("G{}", suffix.to_uppercase())],
+        )
+        .await
+        .unwrap()
+        .get(0);
+
+    profiles
+        .create_profile(id, "Soft Delete".to_string(), None, None, None, None)
+        .await
+        .unwrap();
+    id
+}
+
+/// Pretend the profile was deleted `hours` ago.
+async fn backdate_deletion(pool: &deadpool_postgres::Pool, user_id: Uuid, hours: i32) {
+    pool.get()
+        .await
+        .unwrap()
+        .execute(
+            "UPDATE user_profiles SET deleted_at = NOW() - make_interval(hours => $2) WHERE user_id = $1",
+            &[&user_id, &hours],
+        )
+        .await
+        .unwrap();
+}
+
+#[tokio::test]
+#[ignore]
+async fn test_soft_deleted_profile_is_hidden_and_restorable() {
+    let Some((profiles, pool)) = setup().await else {
+        return;
+    };
+    let user_id = seed_profile(&profiles, &pool).await;
+
+    profiles.delete_profile(user_id).await.unwrap();
+
+    assert!(profiles.get_profile(user_id).await.unwrap().is_none());
+    // Already deleted
+    let err = profiles.delete_profile(user_id).await.unwrap_err();
+    assert!(matches!(err, ApiError::NotFound(_)));
+    let err = profiles
+        .update_profile(
+            user_id,
+            1,
+            ProfileChanges {
+                display_name: Some("Nope".to_string()),
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap_err();
+    assert!(matches!(err, ApiError::NotFound(_)));
+
+    let restored = profiles.restore_profile(user_id).await.unwrap();
+    assert_eq!(restored.display_name, "Soft Delete");
+    assert!(profiles.get_profile(user_id).await.unwrap().is_some());
+
+    // Nothing left to restore once it's live again.
+    let err = profiles.restore_profile(user_id).await.unwrap_err();
+    assert!(matches!(err, ApiError::NotFound(_)));
+}
+
+#[tokio::test]
+#[ignore]
+async fn test_profile_is_purged_after_recovery_window() {
+    let Some((profiles, pool)) = setup().await else {
+        return;
+    };
+    let recent = seed_profile(&profiles, &pool).await;
+    let expired = seed_profile(&profiles, &pool).await;
+
+    profiles.delete_profile(recent).await.unwrap();
+    profiles.delete_profile(expired).await.unwrap();
+    backdate_deletion(&pool, expired, 721).await;
+
+    let err = profiles.restore_profile(expired).await.unwrap_err();
+    assert!(matches!(err, ApiError::NotFound(_)));
+
+    assert!(profiles.purge_deleted().await.unwrap() >= 1);
+
+    let remaining: Vec<Uuid> = pool
+        .get()
+        .await
+        .unwrap()
+        .query(
+            "SELECT user_id FROM user_profiles WHERE user_id = ANY($1)",
+            &[&vec![recent, expired]],
+        )
+        .await
+        .unwrap()
+        .iter()
+        .map(|row| row.get(0))
+        .collect();
+    assert_eq!(remaining, vec![recent]);
+
+    profiles.restore_profile(recent).await.unwrap();
+}
+
+#[tokio::test]
+#[ignore]
+async fn test_creating_a_profile_replaces_a_deleted_one() {
+    let Some((profiles, pool)) = setup().await else {
+        return;
+    };
+    let user_id = seed_profile(&profiles, &pool).await;
+
+    let err = profiles
+        .create_profile(user_id, "Twice".to_string(), None, None, None, None)
+        .await
+        .unwrap_err();
+    assert!(matches!(err, ApiError::Conflict(_)));
+
+    profiles.delete_profile(user_id).await.unwrap();
+    let fresh = profiles
+        .create_profile(user_id, "Fresh".to_string(), None, None, None, None)
+        .await
+        .unwrap();
+    assert_eq!(fresh.display_name, "Fresh");
+    assert_eq!(
+        profiles
+            .get_profile(user_id)
+            .await
+            .unwrap()
+            .unwrap()
+            .display_name,
+        "Fresh"
+    );
+}
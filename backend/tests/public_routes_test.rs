@@ -0,0 +1,81 @@
+use std::sync::Arc;
+
+use axum::{
+    body::Body,
+    http::{Request, StatusCode},
+    middleware,
+    routing::get,
+    Router,
+};
+use tower::util::ServiceExt;
+
+use blinks_backend::config::Config;
+use blinks_backend::db;
+use blinks_backend::middleware::auth::authenticate;
+use blinks_backend::service::ServiceContainer;
+
+// Note: These tests require a running database using the config.
+// Run with: cargo test --test public_routes_test -- --ignored
+
+async fn setup() -> Option<Arc<ServiceContainer>> {
+    let config = match Config::load() {
+        Ok(c) => c,
+        Err(_) => {
+            println!("Skipping test: No config found");
+            return None;
+        }
+    };
+
+    let pool = db::create_pool(&config.database.url)
+        .await
+        .expect("Failed to create pool");
+
+    Some(Arc::new(
+        ServiceContainer::new(pool, config)
+            .await
+            .expect("Failed to create services"),
+    ))
+}
+
+/// Routes wired the way `create_app` wires them: authentication wraps the
+/// whole router rather than a protected group.
+fn app(services: Arc<ServiceContainer>) -> Router {
+    Router::new()
+        .route("/health", get(|| async { "ok" }))
+        .route("/reports/weekly", get(|| async { "report" }))
+        .layer(middleware::from_fn_with_state(
+            services.clone(),
+            authenticate,
+        ))
+        .with_state(services)
+}
+
+async fn status(app: Router, uri: &str) -> StatusCode {
+    app.oneshot(Request::builder().uri(uri).body(Body::empty()).unwrap())
+        .await
+        .unwrap()
+        .status()
+}
+
+#[tokio::test]
+#[ignore]
+async fn test_unlisted_route_requires_a_token() {
+    let Some(services) = setup().await else {
+        return;
+    };
+
+    assert_eq!(
+        status(app(services), "/reports/weekly").await,
+        StatusCode::UNAUTHORIZED
+    );
+}
+
+#[tokio::test]
+#[ignore]
+async fn test_listed_route_is_served_without_a_token() {
+    let Some(services) = setup().await else {
+        return;
+    };
+
+    assert_eq!(status(app(services), "/health").await, StatusCode::OK);
+}
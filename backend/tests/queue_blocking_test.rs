@@ -0,0 +1,89 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use blinks_backend::config::Config;
+use blinks_backend::job_types::{JobPayload, JobResult, JobType};
+use blinks_backend::queue::{JobQueue, QueueConfig};
+
+// Note: These tests require a running Redis using the config.
+// Run with: cargo test --test queue_blocking_test -- --ignored
+
+/// Both tests use the shared queue, so one must not take the other's job.
+static SERIAL: tokio::sync::Mutex<()> = tokio::sync::Mutex::const_new(());
+
+async fn setup() -> Option<Arc<JobQueue>> {
+    let config = match Config::load() {
+        Ok(c) => c,
+        Err(_) => {
+            println!("Skipping test: No config found");
+            return None;
+        }
+    };
+
+    let queue = JobQueue::new(&config.queue_config.redis_url, QueueConfig::default())
+        .await
+        .expect("Failed to connect to Redis");
+    Some(Arc::new(queue))
+}
+
+#[tokio::test]
+#[ignore]
+async fn test_enqueue_wakes_blocked_worker() {
+    let _serial = SERIAL.lock().await;
+    let Some(queue) = setup().await else {
+        return;
+    };
+
+    let waiter = {
+        let queue = queue.clone();
+        tokio::spawn(async move {
+            let started = Instant::now();
+            let job = queue.dequeue_blocking(Duration::from_secs(10)).await;
+            (job, started.elapsed())
+        })
+    };
+
+    // Give the worker time to block before there is anything to take.
+    tokio::time::sleep(Duration::from_millis(200)).await;
+    let job = JobPayload::new(JobType::Email, HashMap::new(), None);
+    queue.enqueue(job.clone()).await.unwrap();
+
+    let (dequeued, waited) = waiter.await.unwrap();
+    let dequeued = dequeued.unwrap().expect("worker should have been woken");
+    assert_eq!(dequeued.id, job.id);
+    assert!(waited < Duration::from_secs(2), "woke after {:?}", waited);
+
+    queue
+        .complete_job(
+            dequeued.id,
+            JobResult {
+                job_id: dequeued.id,
+                success: true,
+                error: None,
+                processed_at: chrono::Utc::now(),
+                attempt: 1,
+            },
+        )
+        .await
+        .unwrap();
+}
+
+#[tokio::test]
+#[ignore]
+async fn test_blocking_dequeue_times_out_when_idle() {
+    let _serial = SERIAL.lock().await;
+    let Some(queue) = setup().await else {
+        return;
+    };
+
+    let started = Instant::now();
+    let job = queue
+        .dequeue_blocking(Duration::from_millis(300))
+        .await
+        .unwrap();
+
+    assert!(job.is_none());
+    assert!(started.elapsed() >= Duration::from_millis(300));
+    assert!(started.elapsed() < Duration::from_secs(2));
+}
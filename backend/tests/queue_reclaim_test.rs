@@ -0,0 +1,90 @@
+use std::collections::{HashMap, HashSet};
+use std::time::Duration;
+
+use blinks_backend::config::Config;
+use blinks_backend::job_types::{JobPayload, JobResult, JobType};
+use blinks_backend::queue::{JobQueue, QueueConfig};
+use uuid::Uuid;
+
+// Note: These tests require a running Redis using the config.
+// Run with: cargo test --test queue_reclaim_test -- --ignored
+
+const VISIBILITY: Duration = Duration::from_secs(2);
+
+async fn setup() -> Option<JobQueue> {
+    let config = match Config::load() {
+        Ok(c) => c,
+        Err(_) => {
+            println!("Skipping test: No config found");
+            return None;
+        }
+    };
+
+    let queue_config = QueueConfig {
+        visibility_timeout: VISIBILITY,
+        ..QueueConfig::default()
+    };
+    let queue = JobQueue::new(&config.queue_config.redis_url, queue_config)
+        .await
+        .expect("Failed to connect to Redis");
+    Some(queue)
+}
+
+/// Take every ready job, returning their ids.
+async fn drain(queue: &JobQueue) -> HashSet<Uuid> {
+    let mut ids = HashSet::new();
+    while let Some(job) = queue.dequeue().await.unwrap() {
+        ids.insert(job.id);
+    }
+    ids
+}
+
+async fn complete(queue: &JobQueue, job: &JobPayload) {
+    let result = JobResult {
+        job_id: job.id,
+        success: true,
+        error: None,
+        processed_at: chrono::Utc::now(),
+        attempt: 1,
+    };
+    queue.complete_job(job.id, result).await.unwrap();
+}
+
+#[tokio::test]
+#[ignore]
+async fn test_only_jobs_with_lapsed_heartbeat_are_reclaimed() {
+    let Some(queue) = setup().await else {
+        return;
+    };
+
+    let running = JobPayload::new(JobType::Email, HashMap::new(), None);
+    let stalled = JobPayload::new(JobType::Email, HashMap::new(), None);
+    queue.enqueue(running.clone()).await.unwrap();
+    queue.enqueue(stalled.clone()).await.unwrap();
+    let taken = drain(&queue).await;
+    assert!(taken.contains(&running.id) && taken.contains(&stalled.id));
+
+    // Outlast the visibility window, keeping only `running` alive.
+    let lapsed_at = tokio::time::Instant::now() + VISIBILITY + Duration::from_secs(1);
+    while tokio::time::Instant::now() < lapsed_at {
+        assert!(queue.heartbeat(&running).await.unwrap());
+        tokio::time::sleep(Duration::from_millis(300)).await;
+    }
+
+    // A late heartbeat from the stalled job's worker can't revive it.
+    assert!(!queue.heartbeat(&stalled).await.unwrap());
+
+    assert!(queue.reclaim_stalled_jobs().await.unwrap() >= 1);
+    let reclaimed = drain(&queue).await;
+    assert!(
+        reclaimed.contains(&stalled.id),
+        "stalled job was not reclaimed"
+    );
+    assert!(
+        !reclaimed.contains(&running.id),
+        "job with a fresh heartbeat was reclaimed"
+    );
+
+    complete(&queue, &running).await;
+    complete(&queue, &stalled).await;
+}
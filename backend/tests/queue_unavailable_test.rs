@@ -0,0 +1,86 @@
+use axum::{
+    body::{to_bytes, Body},
+    http::{header, Request, StatusCode},
+};
+use blinks_backend::app::create_app;
+use blinks_backend::config::{Config, DependencyCriticality};
+use blinks_backend::db;
+use std::time::Duration;
+use tokio_util::sync::CancellationToken;
+use tower::ServiceExt;
+
+/// Nothing listens here, so every Redis connection is refused.
+const UNREACHABLE_REDIS: &str = "redis://127.0.0.1:1";
+
+#[tokio::test]
+async fn test_app_starts_without_redis_and_reports_queue_degraded() {
+    let mut config = Config::default();
+    config.queue_config.redis_url = UNREACHABLE_REDIS.to_string();
+    config.health.redis = DependencyCriticality::Optional;
+    config.health.probe_timeout_ms = 500;
+
+    let pool = db::create_pool(&config.database.url).await.unwrap();
+    let shutdown = CancellationToken::new();
+    let (app, workers) = create_app(pool, config, shutdown.clone())
+        .await
+        .expect("app should start while Redis is down");
+
+    let response = app
+        .oneshot(
+            Request::builder()
+                .uri("/health/ready")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+
+    assert_eq!(body["redis"]["status"], "degraded");
+    assert_eq!(body["redis"]["required"], false);
+    assert!(body["redis"]["error"].is_string());
+
+    // Workers waiting on the queue still stop on shutdown
+    shutdown.cancel();
+    tokio::time::timeout(Duration::from_secs(5), workers)
+        .await
+        .expect("workers did not stop after shutdown")
+        .unwrap();
+}
+
+#[tokio::test]
+async fn test_writes_do_not_wait_on_unreachable_redis() {
+    let mut config = Config::default();
+    config.queue_config.redis_url = UNREACHABLE_REDIS.to_string();
+    config.health.redis = DependencyCriticality::Optional;
+    config.health.probe_timeout_ms = 500;
+    config.server.request_timeout_seconds = 5;
+
+    let pool = db::create_pool(&config.database.url).await.unwrap();
+    let shutdown = CancellationToken::new();
+    let (app, _workers) = create_app(pool, config, shutdown.clone())
+        .await
+        .expect("app should start while Redis is down");
+
+    // A public write, so it passes authentication and reaches the
+    // maintenance check; what the handler then answers doesn't matter.
+    let response = tokio::time::timeout(
+        Duration::from_secs(4),
+        app.oneshot(
+            Request::builder()
+                .method("POST")
+                .uri("/auth/register")
+                .header(header::CONTENT_TYPE, "application/json")
+                .body(Body::from(r#"{"user_id":"redis-down","pin":"482915"}"#))
+                .unwrap(),
+        ),
+    )
+    .await
+    .expect("write waited on Redis")
+    .unwrap();
+
+    assert_ne!(response.status(), StatusCode::GATEWAY_TIMEOUT);
+    assert_ne!(response.status(), StatusCode::SERVICE_UNAVAILABLE);
+    shutdown.cancel();
+}
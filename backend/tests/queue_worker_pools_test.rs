@@ -0,0 +1,159 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use anyhow::Result;
+use async_trait::async_trait;
+use tokio::sync::mpsc;
+use tokio_util::sync::CancellationToken;
+use uuid::Uuid;
+
+use blinks_backend::config::Config;
+use blinks_backend::job_processors::JobProcessorRegistry;
+use blinks_backend::job_types::{JobPayload, JobResult, JobType};
+use blinks_backend::job_worker::JobWorker;
+use blinks_backend::queue::{JobProcessor, JobQueue};
+
+// Note: These tests require a running Redis using the config.
+// Run with: cargo test --test queue_worker_pools_test -- --ignored
+
+/// Stands in for a slow on-chain submission.
+struct SlowBlockchainProcessor;
+
+#[async_trait]
+impl JobProcessor for SlowBlockchainProcessor {
+    async fn process(&self, job: &JobPayload) -> Result<JobResult> {
+        tokio::time::sleep(Duration::from_secs(3)).await;
+        Ok(success(job))
+    }
+}
+
+/// Reports each email job it handles.
+struct RecordingEmailProcessor {
+    processed: mpsc::UnboundedSender<Uuid>,
+}
+
+#[async_trait]
+impl JobProcessor for RecordingEmailProcessor {
+    async fn process(&self, job: &JobPayload) -> Result<JobResult> {
+        let _ = self.processed.send(job.id);
+        Ok(success(job))
+    }
+}
+
+fn success(job: &JobPayload) -> JobResult {
+    JobResult {
+        job_id: job.id,
+        success: true,
+        error: None,
+        processed_at: chrono::Utc::now(),
+        attempt: 1,
+    }
+}
+
+#[tokio::test]
+#[ignore]
+async fn test_email_jobs_are_not_starved_by_blockchain_backlog() {
+    let mut config = match Config::load() {
+        Ok(c) => c,
+        Err(_) => {
+            println!("Skipping test: No config found");
+            return;
+        }
+    };
+    config.queue_config.worker_count = 1;
+    config.queue_config.worker_pools = HashMap::from([("email".to_string(), 1)]);
+    config.queue_config.dequeue_block_seconds = 1;
+
+    let queue = Arc::new(
+        JobQueue::from_config(&config)
+            .await
+            .expect("Failed to connect to Redis"),
+    );
+    let (processed, mut emails) = mpsc::unbounded_channel();
+    let mut processors = JobProcessorRegistry::new();
+    processors.register(JobType::BlockchainTx, Box::new(SlowBlockchainProcessor));
+    processors.register(
+        JobType::Email,
+        Box::new(RecordingEmailProcessor { processed }),
+    );
+
+    let shutdown = CancellationToken::new();
+    let worker = JobWorker::new(queue.clone(), processors, config);
+    let workers = {
+        let shutdown = shutdown.clone();
+        tokio::spawn(async move { worker.start_workers(shutdown).await })
+    };
+
+    for _ in 0..10 {
+        queue
+            .enqueue(JobPayload::new(JobType::BlockchainTx, HashMap::new(), None))
+            .await
+            .unwrap();
+    }
+    // Let the shared worker get stuck into the backlog.
+    tokio::time::sleep(Duration::from_millis(300)).await;
+
+    let email = JobPayload::new(JobType::Email, HashMap::new(), None);
+    let enqueued_at = Instant::now();
+    queue.enqueue(email.clone()).await.unwrap();
+
+    let handled = tokio::time::timeout(Duration::from_secs(2), async {
+        loop {
+            // Skip emails left over from earlier runs.
+            match emails.recv().await {
+                Some(id) if id == email.id => return,
+                Some(_) => continue,
+                None => panic!("email processor dropped"),
+            }
+        }
+    })
+    .await;
+    assert!(
+        handled.is_ok(),
+        "email job waited behind the blockchain backlog"
+    );
+    assert!(enqueued_at.elapsed() < Duration::from_secs(2));
+
+    shutdown.cancel();
+    workers.await.unwrap().unwrap();
+}
+
+#[tokio::test]
+#[ignore]
+async fn test_dedicated_dequeue_leaves_other_job_types_queued() {
+    let config = match Config::load() {
+        Ok(c) => c,
+        Err(_) => {
+            println!("Skipping test: No config found");
+            return;
+        }
+    };
+    let queue = JobQueue::from_config(&config)
+        .await
+        .expect("Failed to connect to Redis");
+
+    let blockchain = JobPayload::new(JobType::BlockchainTx, HashMap::new(), None);
+    queue.enqueue(blockchain).await.unwrap();
+    let email = JobPayload::new(JobType::Email, HashMap::new(), None);
+    queue.enqueue(email.clone()).await.unwrap();
+
+    let started = Instant::now();
+    let mut taken = None;
+    // Drain any leftover email jobs until ours comes up.
+    while let Some(job) = queue
+        .dequeue_blocking_for(Some(&JobType::Email), Duration::from_secs(1))
+        .await
+        .unwrap()
+    {
+        assert_eq!(job.job_type, JobType::Email);
+        queue.complete_job(job.id, success(&job)).await.unwrap();
+        if job.id == email.id {
+            taken = Some(job);
+            break;
+        }
+    }
+
+    assert!(taken.is_some(), "email job was not dequeued");
+    assert!(started.elapsed() < Duration::from_secs(2));
+}
@@ -0,0 +1,355 @@
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex};
+
+use axum::{extract::State, routing::post, Json, Router};
+use blinks_backend::config::Config;
+use blinks_backend::db;
+use blinks_backend::service::RegistrySyncService;
+use serde_json::{json, Value};
+use uuid::Uuid;
+
+// Note: These tests require a running database using the config.
+// Run with: cargo test --test registry_sync_test -- --ignored
+
+const LATEST_LEDGER: u64 = 500;
+
+type Events = Arc<Mutex<Vec<Value>>>;
+
+/// Soroban RPC stand-in serving a fixed list of contract events.
+/// `getEvents` honours `startLedger`, `pagination.limit` and a cursor that
+/// is the id of the last event returned.
+async fn rpc(State(events): State<Events>, Json(body): Json<Value>) -> Json<Value> {
+    let params = &body["params"];
+    let result = match body["method"].as_str() {
+        Some("getLatestLedger") => json!({ "sequence": LATEST_LEDGER }),
+        Some("getEvents") => {
+            let limit = params["pagination"]["limit"].as_u64().unwrap_or(100) as usize;
+            let events = events.lock().unwrap();
+            let matching: Vec<Value> = match params["pagination"]["cursor"].as_str() {
+                Some(cursor) => events
+                    .iter()
+                    .filter(|e| e["id"].as_str().unwrap() > cursor)
+                    .cloned()
+                    .collect(),
+                None => {
+                    let start = params["startLedger"].as_u64().unwrap_or_default();
+                    events
+                        .iter()
+                        .filter(|e| e["ledger"].as_u64().unwrap() >= start)
+                        .cloned()
+                        .collect()
+                }
+            };
+            let page: Vec<Value> = matching.into_iter().take(limit).collect();
+            let cursor = page.last().map(|e| e["id"].clone()).unwrap_or(Value::Null);
+            json!({ "events": page, "latestLedger": LATEST_LEDGER, "cursor": cursor })
+        }
+        _ => return Json(json!({ "jsonrpc": "2.0", "id": 1, "error": { "code": -32601 } })),
+    };
+
+    Json(json!({ "jsonrpc": "2.0", "id": 1, "result": result }))
+}
+
+async fn spawn_rpc(events: Events) -> String {
+    let app = Router::new().route("/", post(rpc)).with_state(events);
+
+    let listener = tokio::net::TcpListener::bind(SocketAddr::from(([127, 0, 0, 1], 0)))
+        .await
+        .unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move {
+        axum::serve(listener, app).await.unwrap();
+    });
+
+    format!("http://{}", addr)
+}
+
+fn merchant_event(ledger: u64, merchant_id: &str, vault: &str, active: bool) -> Value {
+    json!({
+        "id": format!("{:010}-0000000001", ledger),
+        "ledger": ledger,
+        "topicJson": [{ "symbol": "merch_reg" }, { "bytes": hex::encode(merchant_id) }],
+        "valueJson": { "map": [
+            { "key": { "symbol": "active" }, "val": { "bool": active } },
+            { "key": { "symbol": "settlement_asset" }, "val": { "address": "CUSDCASSET" } },
+            { "key": { "symbol": "vault" }, "val": { "address": vault } },
+        ]},
+    })
+}
+
+fn deactivation_event(ledger: u64, merchant_id: &str) -> Value {
+    json!({
+        "id": format!("{:010}-0000000003", ledger),
+        "ledger": ledger,
+        "topicJson": [{ "symbol": "merch_dea" }, { "bytes": hex::encode(merchant_id) }],
+        "valueJson": { "void": null },
+    })
+}
+
+fn user_event(ledger: u64, user_id: &str, wallet: &str) -> Value {
+    json!({
+        "id": format!("{:010}-0000000002", ledger),
+        "ledger": ledger,
+        "topicJson": [{ "symbol": "user_reg" }, { "bytes": hex::encode(user_id) }],
+        "valueJson": { "address": wallet },
+    })
+}
+
+async fn setup(
+    events: Events,
+    page_size: u32,
+) -> Option<(RegistrySyncService, Arc<deadpool_postgres::Pool>, String)> {
+    let mut config = match Config::load() {
+        Ok(c) => c,
+        Err(_) => {
+            println!("Skipping test: No config found");
+            return None;
+        }
+    };
+
+    db::run_migrations(&config.database.url)
+        .await
+        .expect("Failed to run migrations");
+    let pool = Arc::new(
+        db::create_pool(&config.database.url)
+            .await
+            .expect("Failed to create pool"),
+    );
+
+    // A fresh contract per test keeps sync state independent.
+    let contract_id = format!(
+        "C{:0<55}",
+        Uuid::new_v4().simple().to_string().to_uppercase()
+    );
+    config.stellar_network.rpc_url = spawn_rpc(events).await;
+    config.registry_sync.enabled = true;
+    config.registry_sync.contract_id = contract_id.clone();
+    config.registry_sync.start_ledger = 100;
+    config.registry_sync.page_size = page_size;
+
+    Some((
+        RegistrySyncService::new(pool.clone(), config),
+        pool,
+        contract_id,
+    ))
+}
+
+fn suffix() -> String {
+    Uuid::new_v4().simple().to_string()[..12].to_uppercase()
+}
+
+#[tokio::test]
+#[ignore]
+async fn test_new_onchain_merchant_appears_in_db() {
+    let events: Events = Default::default();
+    let Some((sync, pool, contract_id)) = setup(events.clone(), 100).await else {
+        return;
+    };
+    let merchant_id = format!("reg-merchant-{}", suffix());
+    let vault = format!("GVAULT{}", suffix());
+    events
+        .lock()
+        .unwrap()
+        .push(merchant_event(150, &merchant_id, &vault, true));
+
+    let summary = sync.sync_once().await.unwrap();
+    assert_eq!(summary.merchants, 1);
+    assert_eq!(summary.last_ledger, LATEST_LEDGER as u32);
+
+    let client = pool.get().await.unwrap();
+    let row = client
+        .query_one(
+            "SELECT vault_address, settlement_asset, active FROM merchants WHERE merchant_id = $1",
+            &[&merchant_id],
+        )
+        .await
+        .unwrap();
+    assert_eq!(row.get::<_, String>("vault_address"), vault);
+    assert_eq!(row.get::<_, String>("settlement_asset"), "CUSDCASSET");
+    assert!(row.get::<_, bool>("active"));
+
+    let stored: i64 = client
+        .query_one(
+            "SELECT last_ledger FROM registry_sync_state WHERE contract_id = $1",
+            &[&contract_id],
+        )
+        .await
+        .unwrap()
+        .get(0);
+    assert_eq!(stored, LATEST_LEDGER as i64);
+
+    // Nothing new on chain: a rerun changes nothing.
+    let rerun = sync.sync_once().await.unwrap();
+    assert_eq!(rerun.merchants, 0);
+    let count: i64 = client
+        .query_one(
+            "SELECT COUNT(*) FROM merchants WHERE merchant_id = $1",
+            &[&merchant_id],
+        )
+        .await
+        .unwrap()
+        .get(0);
+    assert_eq!(count, 1);
+
+    // A later on-chain deactivation switches the existing row off.
+    events
+        .lock()
+        .unwrap()
+        .push(deactivation_event(LATEST_LEDGER, &merchant_id));
+    let update = sync.sync_once().await.unwrap();
+    assert_eq!(update.merchants, 1);
+    let active: bool = client
+        .query_one(
+            "SELECT active FROM merchants WHERE merchant_id = $1",
+            &[&merchant_id],
+        )
+        .await
+        .unwrap()
+        .get(0);
+    assert!(!active);
+}
+
+#[tokio::test]
+#[ignore]
+async fn test_users_sync_across_pages_and_conflicts_are_skipped() {
+    let events: Events = Default::default();
+    let Some((sync, pool, _)) = setup(events.clone(), 1).await else {
+        return;
+    };
+    let alice = format!("reg-alice-{}", suffix());
+    let bob = format!("reg-bob-{}", suffix());
+    let alice_wallet = format!("GALICE{}", suffix());
+    let bob_wallet = format!("GBOB{}", suffix());
+    {
+        let mut events = events.lock().unwrap();
+        events.push(user_event(120, &alice, &alice_wallet));
+        events.push(user_event(130, &bob, &bob_wallet));
+        // A second handle claiming Alice's wallet violates the unique address.
+        events.push(user_event(
+            140,
+            &format!("reg-mallory-{}", suffix()),
+            &alice_wallet,
+        ));
+    }
+
+    let summary = sync.sync_once().await.unwrap();
+    assert_eq!(summary.users, 2);
+    assert_eq!(summary.skipped, 1);
+
+    let client = pool.get().await.unwrap();
+    for (user_id, wallet) in [(&alice, &alice_wallet), (&bob, &bob_wallet)] {
+        let row = client
+            .query_one(
+                "SELECT stellar_address, pin_hash FROM users WHERE user_id = $1",
+                &[user_id],
+            )
+            .await
+            .unwrap();
+        assert_eq!(&row.get::<_, String>("stellar_address"), wallet);
+        assert_eq!(row.get::<_, Option<String>>("pin_hash"), None);
+    }
+}
+
+#[tokio::test]
+#[ignore]
+async fn test_existing_handle_is_not_rebound_to_a_registry_wallet() {
+    let events: Events = Default::default();
+    let Some((sync, pool, _)) = setup(events.clone(), 100).await else {
+        return;
+    };
+    let victim = format!("reg-victim-{}", suffix());
+    let custodial_wallet = format!("GCUSTODY{}", suffix());
+    let client = pool.get().await.unwrap();
+    client
+        .execute(
+            "INSERT INTO users (user_id, stellar_address, pin_hash) VALUES ($1, $2, 'x')",
+            &[&victim, &custodial_wallet],
+        )
+        .await
+        .unwrap();
+
+    // Anyone can register an existing handle on chain with their own wallet.
+    events
+        .lock()
+        .unwrap()
+        .push(user_event(150, &victim, &format!("GATTACKER{}", suffix())));
+
+    let summary = sync.sync_once().await.unwrap();
+    assert_eq!(summary.users, 0);
+    assert_eq!(summary.skipped, 1);
+
+    let address: String = client
+        .query_one(
+            "SELECT stellar_address FROM users WHERE user_id = $1",
+            &[&victim],
+        )
+        .await
+        .unwrap()
+        .get(0);
+    assert_eq!(address, custodial_wallet);
+}
+
+#[tokio::test]
+#[ignore]
+async fn test_dry_run_lists_changes_without_writing() {
+    let events: Events = Default::default();
+    let Some((sync, pool, contract_id)) = setup(events.clone(), 1).await else {
+        return;
+    };
+    let merchant_id = format!("reg-merchant-{}", suffix());
+    let closed_id = format!("reg-closed-{}", suffix());
+    let user_id = format!("reg-user-{}", suffix());
+    let vault = format!("GVAULT{}", suffix());
+    let wallet = format!("GUSER{}", suffix());
+    {
+        let mut events = events.lock().unwrap();
+        events.push(merchant_event(110, &merchant_id, &vault, true));
+        events.push(merchant_event(115, &closed_id, &vault, true));
+        events.push(deactivation_event(120, &closed_id));
+        events.push(user_event(130, &user_id, &wallet));
+    }
+
+    let preview = sync.sync(true).await.unwrap();
+    assert!(preview.dry_run);
+    assert_eq!(preview.merchants, 3);
+    assert_eq!(preview.users, 1);
+    assert_eq!(preview.last_ledger, LATEST_LEDGER as u32);
+    assert_eq!(
+        serde_json::to_value(&preview.changes).unwrap(),
+        json!([
+            {
+                "action": "upsert_merchant",
+                "merchant_id": merchant_id,
+                "vault_address": vault,
+                "settlement_asset": "CUSDCASSET",
+            },
+            {
+                "action": "upsert_merchant",
+                "merchant_id": closed_id,
+                "vault_address": vault,
+                "settlement_asset": "CUSDCASSET",
+            },
+            { "action": "deactivate_merchant", "merchant_id": closed_id },
+            { "action": "upsert_user", "user_id": user_id, "stellar_address": wallet },
+        ])
+    );
+
+    let client = pool.get().await.unwrap();
+    let written: i64 = client
+        .query_one(
+            "SELECT (SELECT COUNT(*) FROM merchants WHERE merchant_id IN ($1, $2))
+                  + (SELECT COUNT(*) FROM users WHERE user_id = $3)
+                  + (SELECT COUNT(*) FROM registry_sync_state WHERE contract_id = $4)",
+            &[&merchant_id, &closed_id, &user_id, &contract_id],
+        )
+        .await
+        .unwrap()
+        .get(0);
+    assert_eq!(written, 0);
+
+    // The real sync afterwards still sees every event.
+    let applied = sync.sync_once().await.unwrap();
+    assert!(!applied.dry_run);
+    assert_eq!((applied.merchants, applied.users), (3, 1));
+    assert!(applied.changes.is_empty());
+}
@@ -0,0 +1,121 @@
+use httpmock::{Method::POST, MockServer};
+use serde_json::{json, Value};
+use stellar_xdr::curr::{Limits, ScVal, WriteXdr};
+
+use blinks_backend::config::{Config, ReputationTier, WithdrawalLimit};
+use blinks_backend::service::ReputationService;
+
+fn contract_id() -> String {
+    stellar_strkey::Contract([3; 32]).to_string()
+}
+
+fn account(seed: u8) -> String {
+    stellar_strkey::ed25519::PublicKey([seed; 32]).to_string()
+}
+
+fn tier(name: &str, min_score: u32, usdc_max: i64) -> ReputationTier {
+    ReputationTier {
+        name: name.to_string(),
+        min_score,
+        withdrawal_limits: [(
+            "USDC".to_string(),
+            WithdrawalLimit {
+                min: None,
+                max: Some(usdc_max),
+            },
+        )]
+        .into(),
+        kyc_required: min_score == 0,
+    }
+}
+
+/// Reputation tiers backed by a stand-in RPC node whose `simulateTransaction`
+/// answers with `result`.
+async fn service_with_rpc(server: &MockServer, result: Value) -> ReputationService {
+    server
+        .mock_async(|when, then| {
+            when.method(POST)
+                .path("/")
+                .body_contains("simulateTransaction");
+            then.status(200)
+                .json_body(json!({ "jsonrpc": "2.0", "id": 1, "result": result }));
+        })
+        .await;
+
+    let mut config = Config::default();
+    config.stellar_network.rpc_url = server.base_url();
+    config.reputation.enabled = true;
+    config.reputation.contract_id = contract_id();
+    config.reputation.tiers = vec![
+        tier("new", 0, 100_000_000),
+        tier("trusted", 500, 10_000_000_000),
+    ];
+    ReputationService::new(config)
+}
+
+fn score(value: u32) -> Value {
+    let xdr = ScVal::U32(value).to_xdr_base64(Limits::none()).unwrap();
+    json!({ "results": [{ "xdr": xdr }] })
+}
+
+#[tokio::test]
+async fn test_low_score_gets_the_capped_tier() {
+    let server = MockServer::start_async().await;
+    let reputation = service_with_rpc(&server, score(120)).await;
+
+    assert_eq!(reputation.get_score(&account(1)).await.unwrap(), 120);
+    let tier = reputation
+        .withdrawal_tier(&account(1))
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(tier.name, "new");
+    assert!(tier.kyc_required);
+    assert_eq!(
+        tier.withdrawal_limit("USDC").unwrap().max,
+        Some(100_000_000)
+    );
+}
+
+#[tokio::test]
+async fn test_high_score_gets_the_elevated_tier() {
+    let server = MockServer::start_async().await;
+    let reputation = service_with_rpc(&server, score(750)).await;
+
+    let tier = reputation
+        .withdrawal_tier(&account(2))
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(tier.name, "trusted");
+    assert!(!tier.kyc_required);
+    assert_eq!(
+        tier.withdrawal_limit("USDC").unwrap().max,
+        Some(10_000_000_000)
+    );
+    // Assets the tier doesn't list keep the anchor's limits
+    assert_eq!(tier.withdrawal_limit("EURC"), None);
+}
+
+#[tokio::test]
+async fn test_unreadable_score_fails_instead_of_defaulting() {
+    let server = MockServer::start_async().await;
+    let reputation = service_with_rpc(&server, json!({ "error": "HostError" })).await;
+
+    assert!(reputation.withdrawal_tier(&account(3)).await.is_err());
+}
+
+#[tokio::test]
+async fn test_disabled_tiers_skip_the_contract() {
+    let server = MockServer::start_async().await;
+    let mut config = Config::default();
+    config.stellar_network.rpc_url = server.base_url();
+    config.reputation.tiers = vec![tier("new", 0, 100_000_000)];
+
+    let tier = ReputationService::new(config)
+        .withdrawal_tier(&account(3))
+        .await
+        .unwrap();
+
+    assert_eq!(tier, None);
+}
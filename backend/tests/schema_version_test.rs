@@ -0,0 +1,58 @@
+use blinks_backend::config::Config;
+use blinks_backend::db;
+use blinks_backend::http::health::SchemaHealth;
+
+// Note: These tests require a running database using the config.
+// Run with: cargo test --test schema_version_test -- --ignored
+
+async fn migrated_pool() -> Option<deadpool_postgres::Pool> {
+    let config = match Config::load() {
+        Ok(c) => c,
+        Err(_) => {
+            println!("Skipping test: No config found");
+            return None;
+        }
+    };
+
+    db::run_migrations(&config.database.url)
+        .await
+        .expect("Failed to run migrations");
+    Some(
+        db::create_pool(&config.database.url)
+            .await
+            .expect("Failed to create pool"),
+    )
+}
+
+#[tokio::test]
+#[ignore]
+async fn test_migrated_database_is_ready() {
+    let Some(pool) = migrated_pool().await else {
+        return;
+    };
+    let client = pool.get().await.unwrap();
+
+    let applied = db::applied_schema_version(&client).await.unwrap();
+    let schema = SchemaHealth::new(db::expected_schema_version(), applied);
+
+    assert_eq!(applied, Some(db::expected_schema_version()));
+    assert_eq!(schema.status, "current");
+}
+
+#[tokio::test]
+#[ignore]
+async fn test_database_behind_the_build_is_not_ready() {
+    let Some(pool) = migrated_pool().await else {
+        return;
+    };
+    let client = pool.get().await.unwrap();
+
+    // A build shipping one more migration than the database has applied
+    let expected = db::expected_schema_version() + 1;
+    let applied = db::applied_schema_version(&client).await.unwrap();
+    let schema = SchemaHealth::new(expected, applied);
+
+    assert_eq!(schema.status, "behind");
+    assert_eq!(schema.expected_version, expected);
+    assert_eq!(schema.applied_version, Some(expected - 1));
+}
@@ -0,0 +1,105 @@
+use std::sync::Arc;
+
+use blinks_backend::api_error::ApiError;
+use blinks_backend::config::Config;
+use blinks_backend::db;
+use blinks_backend::service::anchor_service::{MemoType, Sep31PayoutParams};
+use blinks_backend::service::AnchorService;
+use httpmock::{Method::POST, MockServer};
+use serde_json::json;
+
+const SENDER: &str = "GBRPYHIL2CI3FNQ4BXLFMNDLFJUNPU2HY3ZMFSHONUCEOASW7QC7OX2H";
+
+/// An anchor service pointed at `server`'s SEP-31 endpoint. Payouts never
+/// touch the database, and the pool only connects on first use.
+async fn anchor(server: &MockServer) -> AnchorService {
+    let mut config = Config::default();
+    config.anchor_config.sep31_url = server.base_url();
+
+    let pool = Arc::new(db::create_pool(&config.database.url).await.unwrap());
+    AnchorService::new(pool, config)
+}
+
+fn payout(memo: &str, memo_type: MemoType) -> Sep31PayoutParams {
+    Sep31PayoutParams {
+        amount: "100".to_string(),
+        asset_code: "USDC".to_string(),
+        asset_issuer: None,
+        sender_id: SENDER.to_string(),
+        receiver_id: "receiver-1".to_string(),
+        memo: Some(memo.to_string()),
+        memo_type,
+    }
+}
+
+/// Pay out with `memo` and check the anchor received it as `memo_type`.
+async fn assert_memo_forwarded(memo: &str, memo_type: MemoType, wire_type: &str) {
+    let server = MockServer::start_async().await;
+    let mock = server
+        .mock_async(|when, then| {
+            when.method(POST)
+                .path("/transactions")
+                .json_body_partial(json!({ "memo": memo, "memo_type": wire_type }).to_string());
+            then.status(200)
+                .json_body(json!({ "transaction": { "id": "sep31-tx" } }));
+        })
+        .await;
+
+    let response = anchor(&server)
+        .await
+        .initiate_sep31_payout(&payout(memo, memo_type))
+        .await
+        .unwrap();
+
+    assert_eq!(response.anchor_tx_id, "sep31-tx");
+    mock.assert_async().await;
+}
+
+#[tokio::test]
+async fn test_text_memo_is_sent_as_text() {
+    assert_memo_forwarded("invoice 42", MemoType::Text, "text").await;
+}
+
+#[tokio::test]
+async fn test_id_memo_is_sent_as_id() {
+    assert_memo_forwarded("18446744073709551615", MemoType::Id, "id").await;
+}
+
+#[tokio::test]
+async fn test_hash_memo_is_sent_as_hash() {
+    let hash = "q83vEjRWeJCrze8SNFZ4kKvN7xI0VniQq83vEjRWeJA=";
+    assert_memo_forwarded(hash, MemoType::Hash, "hash").await;
+}
+
+#[tokio::test]
+async fn test_memo_not_matching_its_type_never_reaches_the_anchor() {
+    let server = MockServer::start_async().await;
+    let mock = server
+        .mock_async(|when, then| {
+            when.method(POST).path("/transactions");
+            then.status(200)
+                .json_body(json!({ "transaction": { "id": "sep31-tx" } }));
+        })
+        .await;
+    let anchor = anchor(&server).await;
+
+    for (memo, memo_type) in [
+        ("invoice-42", MemoType::Id),
+        ("not base64!", MemoType::Hash),
+        ("aGFzaA==", MemoType::Hash),
+        ("a text memo longer than twenty-eight bytes", MemoType::Text),
+    ] {
+        let err = anchor
+            .initiate_sep31_payout(&payout(memo, memo_type))
+            .await
+            .unwrap_err();
+        assert!(
+            matches!(err, ApiError::Validation(_)),
+            "{}: {:?}",
+            memo,
+            err
+        );
+    }
+
+    mock.assert_hits_async(0).await;
+}
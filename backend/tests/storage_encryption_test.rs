@@ -0,0 +1,133 @@
+use std::path::PathBuf;
+
+use axum::body::Bytes;
+use blinks_backend::storage::{FileCipher, LocalStorageAdapter, StorageAdapter};
+use uuid::Uuid;
+
+const SECRET: &str = "test-file-encryption-secret";
+const DOCUMENT: &[u8] = b"%PDF-1.7 passport number P1234567";
+
+/// A fresh upload directory, removed when dropped.
+struct TempDir(PathBuf);
+
+impl TempDir {
+    fn new() -> Self {
+        Self(std::env::temp_dir().join(format!("zaps-storage-{}", Uuid::new_v4())))
+    }
+}
+
+impl Drop for TempDir {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.0);
+    }
+}
+
+fn adapter(dir: &TempDir) -> LocalStorageAdapter {
+    LocalStorageAdapter::new(dir.0.clone(), "/files".to_string())
+}
+
+fn contains(haystack: &[u8], needle: &[u8]) -> bool {
+    haystack.windows(needle.len()).any(|w| w == needle)
+}
+
+#[test]
+fn test_encrypted_file_is_unreadable_on_disk() {
+    let dir = TempDir::new();
+    let storage = adapter(&dir).with_encryption(FileCipher::new(SECRET));
+
+    let stored = storage
+        .upload(
+            Bytes::from_static(DOCUMENT),
+            "kyc.pdf",
+            "application/pdf",
+            None,
+        )
+        .unwrap();
+
+    let on_disk = std::fs::read(dir.0.join(&stored.id)).unwrap();
+    assert_ne!(on_disk, DOCUMENT);
+    assert!(!contains(&on_disk, b"passport"));
+    assert!(!contains(&on_disk, b"P1234567"));
+}
+
+#[test]
+fn test_encrypted_file_round_trips_through_the_adapter() {
+    let dir = TempDir::new();
+    let storage = adapter(&dir).with_encryption(FileCipher::new(SECRET));
+
+    let stored = storage
+        .upload(
+            Bytes::from_static(DOCUMENT),
+            "kyc.pdf",
+            "application/pdf",
+            None,
+        )
+        .unwrap();
+
+    assert_eq!(storage.read(&stored.id).unwrap().unwrap(), DOCUMENT);
+    let meta = storage.get(&stored.id).unwrap().unwrap();
+    assert_eq!(meta.size, DOCUMENT.len() as u64);
+
+    storage.delete(&stored.id).unwrap();
+    assert!(storage.read(&stored.id).unwrap().is_none());
+}
+
+#[test]
+fn test_encrypted_file_needs_the_same_secret() {
+    let dir = TempDir::new();
+    let stored = adapter(&dir)
+        .with_encryption(FileCipher::new(SECRET))
+        .upload(
+            Bytes::from_static(DOCUMENT),
+            "kyc.pdf",
+            "application/pdf",
+            None,
+        )
+        .unwrap();
+
+    let other = adapter(&dir).with_encryption(FileCipher::new("another-file-encryption-secret"));
+    assert!(other.read(&stored.id).is_err());
+}
+
+#[test]
+fn test_plaintext_files_stay_readable_after_enabling_encryption() {
+    let dir = TempDir::new();
+    let stored = adapter(&dir)
+        .upload(
+            Bytes::from_static(DOCUMENT),
+            "old.pdf",
+            "application/pdf",
+            None,
+        )
+        .unwrap();
+
+    let storage = adapter(&dir).with_encryption(FileCipher::new(SECRET));
+    assert_eq!(storage.read(&stored.id).unwrap().unwrap(), DOCUMENT);
+    assert_eq!(
+        storage.get(&stored.id).unwrap().unwrap().size,
+        DOCUMENT.len() as u64
+    );
+}
+
+#[test]
+fn test_lookup_keeps_the_uploaded_name_type_and_owner() {
+    let dir = TempDir::new();
+    let storage = adapter(&dir).with_encryption(FileCipher::new(SECRET));
+
+    let stored = storage
+        .upload(
+            Bytes::from_static(DOCUMENT),
+            "kyc.pdf",
+            "application/pdf",
+            Some("alice"),
+        )
+        .unwrap();
+
+    let meta = storage.get(&stored.id).unwrap().unwrap();
+    assert_eq!(meta.original_name, "kyc.pdf");
+    assert_eq!(meta.mime_type, "application/pdf");
+    assert_eq!(meta.owner_id.as_deref(), Some("alice"));
+
+    storage.delete(&stored.id).unwrap();
+    assert!(storage.get(&stored.id).unwrap().is_none());
+}
@@ -0,0 +1,179 @@
+use std::sync::Arc;
+
+use blinks_backend::api_error::ApiError;
+use blinks_backend::config::Config;
+use blinks_backend::db;
+use blinks_backend::service::transfer_service::CreateTransferParams;
+use blinks_backend::service::TransferService;
+use uuid::Uuid;
+
+// Note: These tests require a running database using the config.
+// Run with: cargo test --test transfer_batch_test -- --ignored
+
+async fn setup() -> Option<(TransferService, Arc<deadpool_postgres::Pool>)> {
+    let config = match Config::load() {
+        Ok(c) => c,
+        Err(_) => {
+            println!("Skipping test: No config found");
+            return None;
+        }
+    };
+
+    db::run_migrations(&config.database.url)
+        .await
+        .expect("Failed to run migrations");
+    let pool = Arc::new(
+        db::create_pool(&config.database.url)
+            .await
+            .expect("Failed to create pool"),
+    );
+
+    Some((TransferService::new(pool.clone(), config), pool))
+}
+
+/// Insert throwaway users and return their ids.
+async fn create_users(pool: &deadpool_postgres::Pool, count: usize) -> Vec<String> {
+    let client = pool.get().await.unwrap();
+    let mut user_ids = Vec::with_capacity(count);
+    for _ in 0..count {
+        let suffix = Uuid::new_v4().simple().to_string();
+        let user_id = format!("batch-{}", suffix);
+        client
+            .execute(
+                "INSERT INTO users (user_id, stellar_address, pin_hash) VALUES ($1, $2, 'x')",
+                &[&user_id, &format!("G{}", suffix.to_uppercase())],
+            )
+            .await
+            .unwrap();
+        user_ids.push(user_id);
+    }
+    user_ids
+}
+
+/// Give `user_id` a USDC balance of `amount`.
+async fn fund(pool: &deadpool_postgres::Pool, user_id: &str, amount: i64) {
+    let client = pool.get().await.unwrap();
+    client
+        .execute(
+            "INSERT INTO balances (owner_id, asset, amount) VALUES ($1, 'USDC', $2)",
+            &[&user_id, &amount],
+        )
+        .await
+        .unwrap();
+}
+
+async fn usdc_balance(pool: &deadpool_postgres::Pool, user_id: &str) -> i64 {
+    let client = pool.get().await.unwrap();
+    client
+        .query_one(
+            "SELECT amount FROM balances WHERE owner_id = $1 AND asset = 'USDC'",
+            &[&user_id],
+        )
+        .await
+        .unwrap()
+        .get(0)
+}
+
+async fn transfers_from(pool: &deadpool_postgres::Pool, user_id: &str) -> i64 {
+    let client = pool.get().await.unwrap();
+    client
+        .query_one(
+            "SELECT COUNT(*) FROM transfers WHERE from_user_id = $1",
+            &[&user_id],
+        )
+        .await
+        .unwrap()
+        .get(0)
+}
+
+fn transfer(from: &str, to: &str, amount: i64) -> CreateTransferParams {
+    CreateTransferParams {
+        from_user_id: from.to_string(),
+        to_user_id: to.to_string(),
+        amount,
+        asset: "USDC".to_string(),
+        memo: Some("payroll".to_string()),
+    }
+}
+
+#[tokio::test]
+#[ignore]
+async fn test_batch_creates_every_transfer() {
+    let Some((service, pool)) = setup().await else {
+        return;
+    };
+    let users = create_users(&pool, 3).await;
+    let (merchant, alice, bob) = (&users[0], &users[1], &users[2]);
+    fund(&pool, merchant, 1_000).await;
+
+    let created = service
+        .create_transfers(vec![
+            transfer(merchant, alice, 100),
+            transfer(merchant, bob, 200),
+            transfer(merchant, alice, 300),
+        ])
+        .await
+        .unwrap();
+
+    assert_eq!(created.len(), 3);
+    let recipients: Vec<_> = created.iter().map(|t| t.to_user_id.as_str()).collect();
+    assert_eq!(recipients, [alice.as_str(), bob.as_str(), alice.as_str()]);
+    let amounts: Vec<_> = created.iter().map(|t| t.amount).collect();
+    assert_eq!(amounts, [100, 200, 300]);
+    assert!(created.iter().all(|t| t.status == "pending"));
+    assert_eq!(transfers_from(&pool, merchant).await, 3);
+    assert_eq!(usdc_balance(&pool, merchant).await, 400);
+}
+
+#[tokio::test]
+#[ignore]
+async fn test_unknown_recipient_aborts_the_whole_batch() {
+    let Some((service, pool)) = setup().await else {
+        return;
+    };
+    let users = create_users(&pool, 2).await;
+    let (merchant, alice) = (&users[0], &users[1]);
+    fund(&pool, merchant, 1_000).await;
+    let unknown = format!("missing-{}", Uuid::new_v4().simple());
+
+    let err = service
+        .create_transfers(vec![
+            transfer(merchant, alice, 100),
+            transfer(merchant, &unknown, 200),
+            transfer(merchant, alice, 300),
+        ])
+        .await
+        .unwrap_err();
+
+    assert!(
+        matches!(&err, ApiError::Validation(msg) if msg.contains(&unknown)),
+        "{:?}",
+        err
+    );
+    // The transfer inserted before the bad entry was rolled back.
+    assert_eq!(transfers_from(&pool, merchant).await, 0);
+    assert_eq!(usdc_balance(&pool, merchant).await, 1_000);
+}
+
+#[tokio::test]
+#[ignore]
+async fn test_underfunded_batch_creates_nothing() {
+    let Some((service, pool)) = setup().await else {
+        return;
+    };
+    let users = create_users(&pool, 2).await;
+    let (merchant, alice) = (&users[0], &users[1]);
+    fund(&pool, merchant, 250).await;
+
+    let err = service
+        .create_transfers(vec![
+            transfer(merchant, alice, 200),
+            transfer(merchant, alice, 100),
+        ])
+        .await
+        .unwrap_err();
+
+    assert!(matches!(err, ApiError::Validation(_)), "{:?}", err);
+    assert_eq!(transfers_from(&pool, merchant).await, 0);
+    assert_eq!(usdc_balance(&pool, merchant).await, 250);
+}
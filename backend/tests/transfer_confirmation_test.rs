@@ -0,0 +1,395 @@
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex};
+
+use async_trait::async_trait;
+use axum::{routing::post, Json, Router};
+use blinks_backend::api_error::ApiError;
+use blinks_backend::config::Config;
+use blinks_backend::db;
+use blinks_backend::job_processors::TransferConfirmationProcessor;
+use blinks_backend::job_types::{JobPayload, JobType};
+use blinks_backend::queue::{JobEnqueuer, JobProcessor};
+use blinks_backend::service::transfer_service::CreateTransferParams;
+use blinks_backend::service::{SorobanService, TransferService};
+use serde_json::{json, Value};
+use uuid::Uuid;
+
+// Note: These tests require a running database using the config.
+// Run with: cargo test --test transfer_confirmation_test -- --ignored
+
+/// Soroban RPC stand-in. `sendTransaction` goes by the envelope's
+/// signature: `reject…` is rejected, `busy…` is TRY_AGAIN_LATER, and
+/// anything else is accepted with the signature as its hash.
+/// `getTransaction` reports hashes by prefix: `failed…` → FAILED,
+/// `pending…` → NOT_FOUND, anything else → SUCCESS.
+async fn rpc(Json(body): Json<Value>) -> Json<Value> {
+    let params = &body["params"];
+    let result = match body["method"].as_str() {
+        Some("sendTransaction") => {
+            let envelope = params["transaction"].as_str().unwrap_or_default();
+            let signature = envelope.split_once('_').map_or(envelope, |(_, s)| s);
+            if signature.starts_with("reject") {
+                json!({ "status": "ERROR", "errorResultXdr": "AAAA" })
+            } else if signature.starts_with("busy") {
+                json!({ "status": "TRY_AGAIN_LATER" })
+            } else {
+                json!({ "status": "PENDING", "hash": signature })
+            }
+        }
+        Some("getTransaction") => {
+            let hash = params["hash"].as_str().unwrap_or_default();
+            let status = if hash.starts_with("failed") {
+                "FAILED"
+            } else if hash.starts_with("pending") {
+                "NOT_FOUND"
+            } else {
+                "SUCCESS"
+            };
+            json!({ "status": status })
+        }
+        _ => return Json(json!({ "jsonrpc": "2.0", "id": 1, "error": { "code": -32601 } })),
+    };
+
+    Json(json!({ "jsonrpc": "2.0", "id": 1, "result": result }))
+}
+
+async fn spawn_rpc() -> String {
+    let app = Router::new().route("/", post(rpc));
+
+    let listener = tokio::net::TcpListener::bind(SocketAddr::from(([127, 0, 0, 1], 0)))
+        .await
+        .unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move {
+        axum::serve(listener, app).await.unwrap();
+    });
+
+    format!("http://{}", addr)
+}
+
+/// Job queue stand-in that keeps enqueued jobs for inspection.
+#[derive(Default)]
+struct RecordingQueue {
+    jobs: Mutex<Vec<JobPayload>>,
+}
+
+#[async_trait]
+impl JobEnqueuer for RecordingQueue {
+    async fn enqueue(&self, job: JobPayload) -> anyhow::Result<()> {
+        self.jobs.lock().unwrap().push(job);
+        Ok(())
+    }
+}
+
+async fn setup() -> Option<(TransferService, Arc<deadpool_postgres::Pool>)> {
+    let mut config = match Config::load() {
+        Ok(c) => c,
+        Err(_) => {
+            println!("Skipping test: No config found");
+            return None;
+        }
+    };
+    config.stellar_network.rpc_url = spawn_rpc().await;
+
+    db::run_migrations(&config.database.url)
+        .await
+        .expect("Failed to run migrations");
+    let pool = Arc::new(
+        db::create_pool(&config.database.url)
+            .await
+            .expect("Failed to create pool"),
+    );
+
+    Some((TransferService::new(pool.clone(), config), pool))
+}
+
+/// A seeded transfer: `transfer_id` moves all of the sender's 250 XLM to
+/// the recipient.
+struct Seeded {
+    sender: String,
+    recipient: String,
+    transfer_id: String,
+    sender_address: String,
+    recipient_address: String,
+}
+
+impl Seeded {
+    /// The transfer's envelope, signed with a unique signature (and so tx
+    /// hash) starting with `prefix`.
+    fn envelope(&self, prefix: &str) -> String {
+        self.envelope_for(&self.recipient_address, 250, prefix)
+    }
+
+    fn envelope_for(&self, to: &str, amount: i64, prefix: &str) -> String {
+        let unsigned = SorobanService::new(Config::default()).build_transfer_xdr(
+            &self.sender_address,
+            to,
+            "XLM",
+            amount,
+            None,
+        );
+        format!("{}_{}-{}", unsigned, prefix, Uuid::new_v4().simple())
+    }
+}
+
+/// Seed a sender funded with 250 XLM, a recipient, and a pending transfer
+/// of all of it between them.
+async fn seed_transfer(service: &TransferService, pool: &deadpool_postgres::Pool) -> Seeded {
+    let suffix = Uuid::new_v4().simple().to_string();
+    let sender = format!("sender-{}", suffix);
+    let recipient = format!("recipient-{}", suffix);
+
+    let client = pool.get().await.unwrap();
+    for (user_id, prefix) in [(&sender, "GS"), (&recipient, "GR")] {
+        client
+            .execute(
+                "INSERT INTO users (user_id, stellar_address, pin_hash) VALUES ($1, $2, 'x')",
+                &[user_id, &format!("{}{}", prefix, suffix.to_uppercase())],
+            )
+            .await
+            .unwrap();
+    }
+    client
+        .execute(
+            "INSERT INTO balances (owner_id, asset, amount) VALUES ($1, 'XLM', 250)",
+            &[&sender],
+        )
+        .await
+        .unwrap();
+
+    let transfer = service
+        .create_transfer(CreateTransferParams {
+            from_user_id: sender.clone(),
+            to_user_id: recipient.clone(),
+            amount: 250,
+            asset: "XLM".to_string(),
+            memo: None,
+        })
+        .await
+        .unwrap();
+    assert_eq!(transfer.status, "pending");
+    assert_eq!(balance(pool, &sender).await, 0);
+
+    Seeded {
+        sender_address: format!("GS{}", suffix.to_uppercase()),
+        recipient_address: format!("GR{}", suffix.to_uppercase()),
+        sender,
+        recipient,
+        transfer_id: transfer.id,
+    }
+}
+
+async fn balance(pool: &deadpool_postgres::Pool, owner_id: &str) -> i64 {
+    let client = pool.get().await.unwrap();
+    client
+        .query_opt(
+            "SELECT amount FROM balances WHERE owner_id = $1 AND asset = 'XLM'",
+            &[&owner_id],
+        )
+        .await
+        .unwrap()
+        .map_or(0, |row| row.get(0))
+}
+
+#[tokio::test]
+#[ignore]
+async fn test_confirmed_transfer_completes() {
+    let Some((service, pool)) = setup().await else {
+        return;
+    };
+    let queue = Arc::new(RecordingQueue::default());
+    let seeded = seed_transfer(&service, &pool).await;
+
+    let xdr = seeded.envelope("ok");
+    let submitted = service
+        .submit_transfer(
+            &seeded.transfer_id,
+            &seeded.sender,
+            xdr.clone(),
+            queue.as_ref(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(submitted.status, "pending");
+    assert_eq!(
+        submitted.tx_hash.as_deref(),
+        xdr.split_once('_').map(|(_, s)| s)
+    );
+
+    let job = queue.jobs.lock().unwrap().pop().expect("confirmation job");
+    assert_eq!(job.job_type, JobType::BlockchainTx);
+    assert_eq!(job.payload["transfer_id"], json!(seeded.transfer_id));
+
+    let processor = TransferConfirmationProcessor::new(Arc::new(service.clone()), queue.clone());
+    let result = processor.process(&job).await.unwrap();
+    assert!(result.success);
+
+    let transfer = service.get_transfer(&seeded.transfer_id).await.unwrap();
+    assert_eq!(transfer.status, "completed");
+    assert!(queue.jobs.lock().unwrap().is_empty());
+    assert_eq!(balance(&pool, &seeded.sender).await, 0);
+    assert_eq!(balance(&pool, &seeded.recipient).await, 250);
+}
+
+#[tokio::test]
+#[ignore]
+async fn test_rejected_submission_fails_transfer() {
+    let Some((service, pool)) = setup().await else {
+        return;
+    };
+    let queue = RecordingQueue::default();
+    let seeded = seed_transfer(&service, &pool).await;
+
+    let err = service
+        .submit_transfer(
+            &seeded.transfer_id,
+            &seeded.sender,
+            seeded.envelope("reject"),
+            &queue,
+        )
+        .await
+        .unwrap_err();
+    assert!(matches!(err, ApiError::TransactionRejected(_)), "{:?}", err);
+
+    let transfer = service.get_transfer(&seeded.transfer_id).await.unwrap();
+    assert_eq!(transfer.status, "failed");
+    assert!(transfer.tx_hash.is_none());
+    assert!(queue.jobs.lock().unwrap().is_empty());
+    // The held amount goes back to the sender
+    assert_eq!(balance(&pool, &seeded.sender).await, 250);
+}
+
+#[tokio::test]
+#[ignore]
+async fn test_busy_network_leaves_transfer_pending_for_resubmission() {
+    let Some((service, pool)) = setup().await else {
+        return;
+    };
+    let queue = RecordingQueue::default();
+    let seeded = seed_transfer(&service, &pool).await;
+
+    let err = service
+        .submit_transfer(
+            &seeded.transfer_id,
+            &seeded.sender,
+            seeded.envelope("busy"),
+            &queue,
+        )
+        .await
+        .unwrap_err();
+    assert!(matches!(err, ApiError::ServiceUnavailable(_)), "{:?}", err);
+
+    let transfer = service.get_transfer(&seeded.transfer_id).await.unwrap();
+    assert_eq!(transfer.status, "pending");
+    assert!(transfer.tx_hash.is_none());
+    assert_eq!(balance(&pool, &seeded.sender).await, 0);
+
+    // The same transfer can be submitted again once the network is free
+    service
+        .submit_transfer(
+            &seeded.transfer_id,
+            &seeded.sender,
+            seeded.envelope("ok"),
+            &queue,
+        )
+        .await
+        .unwrap();
+}
+
+#[tokio::test]
+#[ignore]
+async fn test_envelope_must_match_the_transfer() {
+    let Some((service, pool)) = setup().await else {
+        return;
+    };
+    let queue = RecordingQueue::default();
+    let seeded = seed_transfer(&service, &pool).await;
+    let stranger = format!("GX{}", Uuid::new_v4().simple().to_string().to_uppercase());
+
+    for xdr in [
+        seeded.envelope_for(&stranger, 250, "ok"),
+        seeded.envelope_for(&seeded.recipient_address, 1, "ok"),
+        "not-an-envelope".to_string(),
+    ] {
+        let err = service
+            .submit_transfer(&seeded.transfer_id, &seeded.sender, xdr, &queue)
+            .await
+            .unwrap_err();
+        assert!(matches!(err, ApiError::Validation(_)), "{:?}", err);
+    }
+
+    let transfer = service.get_transfer(&seeded.transfer_id).await.unwrap();
+    assert_eq!(transfer.status, "pending");
+    assert!(transfer.tx_hash.is_none());
+    assert!(queue.jobs.lock().unwrap().is_empty());
+}
+
+#[tokio::test]
+#[ignore]
+async fn test_failed_on_chain_marks_transfer_failed() {
+    let Some((service, pool)) = setup().await else {
+        return;
+    };
+    let queue = Arc::new(RecordingQueue::default());
+    let seeded = seed_transfer(&service, &pool).await;
+
+    service
+        .submit_transfer(
+            &seeded.transfer_id,
+            &seeded.sender,
+            seeded.envelope("failed"),
+            queue.as_ref(),
+        )
+        .await
+        .unwrap();
+    let job = queue.jobs.lock().unwrap().pop().unwrap();
+
+    let processor = TransferConfirmationProcessor::new(Arc::new(service.clone()), queue.clone());
+    assert!(processor.process(&job).await.unwrap().success);
+
+    let transfer = service.get_transfer(&seeded.transfer_id).await.unwrap();
+    assert_eq!(transfer.status, "failed");
+    assert_eq!(balance(&pool, &seeded.sender).await, 250);
+}
+
+#[tokio::test]
+#[ignore]
+async fn test_unconfirmed_transfer_is_polled_again() {
+    let Some((service, pool)) = setup().await else {
+        return;
+    };
+    let queue = Arc::new(RecordingQueue::default());
+    let seeded = seed_transfer(&service, &pool).await;
+
+    service
+        .submit_transfer(
+            &seeded.transfer_id,
+            &seeded.sender,
+            seeded.envelope("pending"),
+            queue.as_ref(),
+        )
+        .await
+        .unwrap();
+    let job = queue.jobs.lock().unwrap().pop().unwrap();
+
+    let processor = TransferConfirmationProcessor::new(Arc::new(service.clone()), queue.clone());
+    assert!(processor.process(&job).await.unwrap().success);
+
+    let transfer = service.get_transfer(&seeded.transfer_id).await.unwrap();
+    assert_eq!(transfer.status, "pending");
+
+    let next = queue.jobs.lock().unwrap().pop().expect("follow-up check");
+    assert_eq!(next.payload["attempt"], json!(2));
+
+    // A second submission of an in-flight transfer is refused.
+    let err = service
+        .submit_transfer(
+            &seeded.transfer_id,
+            &seeded.sender,
+            seeded.envelope("ok"),
+            queue.as_ref(),
+        )
+        .await
+        .unwrap_err();
+    assert!(matches!(err, ApiError::Conflict(_)));
+}
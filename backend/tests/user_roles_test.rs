@@ -0,0 +1,234 @@
+use std::sync::Arc;
+
+use axum::{
+    body::Body,
+    http::{Request, StatusCode},
+    routing::get,
+    Extension, Router,
+};
+use http_body_util::BodyExt;
+use serde_json::{json, Value};
+use tower::util::ServiceExt;
+use uuid::Uuid;
+
+use blinks_backend::config::Config;
+use blinks_backend::db;
+use blinks_backend::http::admin::{get_user_role, update_user_role};
+use blinks_backend::middleware::auth::AuthenticatedUser;
+use blinks_backend::role::Role;
+use blinks_backend::service::ServiceContainer;
+
+// Note: These tests require a running database using the config.
+// Run with: cargo test --test user_roles_test -- --ignored
+
+/// The last-admin test demotes every other admin, so run one at a time.
+static SERIAL: tokio::sync::Mutex<()> = tokio::sync::Mutex::const_new(());
+
+async fn setup() -> Option<Arc<ServiceContainer>> {
+    let config = match Config::load() {
+        Ok(c) => c,
+        Err(_) => {
+            println!("Skipping test: No config found");
+            return None;
+        }
+    };
+
+    db::run_migrations(&config.database.url)
+        .await
+        .expect("Failed to run migrations");
+    let pool = db::create_pool(&config.database.url)
+        .await
+        .expect("Failed to create pool");
+
+    Some(Arc::new(
+        ServiceContainer::new(pool, config)
+            .await
+            .expect("Failed to create services"),
+    ))
+}
+
+async fn create_user(services: &ServiceContainer, role: Role) -> String {
+    let client = services.db_pool.get().await.unwrap();
+    let suffix = Uuid::new_v4().simple().to_string();
+    let user_id = format!("role-{}", suffix);
+    client
+        .execute(
+            "INSERT INTO users (user_id, stellar_address, pin_hash, role) VALUES ($1, $2, 'x', $3)",
+            &[
+                &user_id,
+                &format!("G{}", suffix.to_uppercase()),
+                &role.as_str(),
+            ],
+        )
+        .await
+        .unwrap();
+    user_id
+}
+
+/// The role routes as `admin_id` would call them.
+fn app(services: Arc<ServiceContainer>, admin_id: &str) -> Router {
+    Router::new()
+        .route(
+            "/admin/users/:user_id/role",
+            get(get_user_role).patch(update_user_role),
+        )
+        .layer(Extension(AuthenticatedUser {
+            user_id: admin_id.to_string(),
+            role: Role::Admin,
+        }))
+        .with_state(services)
+}
+
+async fn set_role(
+    services: &Arc<ServiceContainer>,
+    admin_id: &str,
+    user_id: &str,
+    role: &str,
+) -> (StatusCode, Value) {
+    let request = Request::builder()
+        .method("PATCH")
+        .uri(format!("/admin/users/{}/role", user_id))
+        .header("Content-Type", "application/json")
+        .body(Body::from(json!({ "role": role }).to_string()))
+        .unwrap();
+    let response = app(services.clone(), admin_id)
+        .oneshot(request)
+        .await
+        .unwrap();
+    let status = response.status();
+    let body = response.into_body().collect().await.unwrap().to_bytes();
+    (status, serde_json::from_slice(&body).unwrap_or(Value::Null))
+}
+
+async fn get_role(services: &Arc<ServiceContainer>, admin_id: &str, user_id: &str) -> Value {
+    let request = Request::builder()
+        .uri(format!("/admin/users/{}/role", user_id))
+        .body(Body::empty())
+        .unwrap();
+    let response = app(services.clone(), admin_id)
+        .oneshot(request)
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let body = response.into_body().collect().await.unwrap().to_bytes();
+    serde_json::from_slice(&body).unwrap()
+}
+
+/// `(actor_id, metadata)` of each role change audited for `user_id`.
+async fn role_audits(services: &ServiceContainer, user_id: &str) -> Vec<(String, Value)> {
+    let client = services.db_pool.get().await.unwrap();
+    client
+        .query(
+            "SELECT actor_id, metadata FROM audit_logs
+             WHERE action = 'update_user_role' AND resource_id = $1
+             ORDER BY chain_seq",
+            &[&user_id],
+        )
+        .await
+        .unwrap()
+        .iter()
+        .map(|row| (row.get(0), row.get(1)))
+        .collect()
+}
+
+#[tokio::test]
+#[ignore]
+async fn test_admin_promotes_user_to_merchant() {
+    let _serial = SERIAL.lock().await;
+    let Some(services) = setup().await else {
+        return;
+    };
+    let admin = create_user(&services, Role::Admin).await;
+    let user = create_user(&services, Role::User).await;
+
+    let (status, body) = set_role(&services, &admin, &user, "merchant").await;
+
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["role"], "merchant");
+    assert_eq!(get_role(&services, &admin, &user).await["role"], "merchant");
+    let audits = role_audits(&services, &user).await;
+    assert_eq!(audits.len(), 1);
+    assert_eq!(audits[0].0, admin);
+    assert_eq!(audits[0].1, json!({ "from": "user", "to": "merchant" }));
+}
+
+#[tokio::test]
+#[ignore]
+async fn test_admin_demotes_another_admin() {
+    let _serial = SERIAL.lock().await;
+    let Some(services) = setup().await else {
+        return;
+    };
+    let admin = create_user(&services, Role::Admin).await;
+    let other_admin = create_user(&services, Role::Admin).await;
+
+    let (status, body) = set_role(&services, &admin, &other_admin, "user").await;
+
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["role"], "user");
+    assert_eq!(
+        get_role(&services, &admin, &other_admin).await["role"],
+        "user"
+    );
+    let audits = role_audits(&services, &other_admin).await;
+    assert_eq!(audits.len(), 1);
+    assert_eq!(audits[0].1, json!({ "from": "admin", "to": "user" }));
+}
+
+#[tokio::test]
+#[ignore]
+async fn test_unchanged_role_is_not_audited() {
+    let _serial = SERIAL.lock().await;
+    let Some(services) = setup().await else {
+        return;
+    };
+    let admin = create_user(&services, Role::Admin).await;
+    let merchant = create_user(&services, Role::Merchant).await;
+
+    let (status, _) = set_role(&services, &admin, &merchant, "merchant").await;
+
+    assert_eq!(status, StatusCode::OK);
+    assert!(role_audits(&services, &merchant).await.is_empty());
+}
+
+#[tokio::test]
+#[ignore]
+async fn test_last_admin_cannot_be_demoted() {
+    let _serial = SERIAL.lock().await;
+    let Some(services) = setup().await else {
+        return;
+    };
+    let admin = create_user(&services, Role::Admin).await;
+    services
+        .db_pool
+        .get()
+        .await
+        .unwrap()
+        .execute(
+            "UPDATE users SET role = 'user' WHERE role = 'admin' AND user_id <> $1",
+            &[&admin],
+        )
+        .await
+        .unwrap();
+
+    let (status, body) = set_role(&services, &admin, &admin, "merchant").await;
+
+    assert_eq!(status, StatusCode::CONFLICT);
+    assert_eq!(body["code"], "CONFLICT");
+    assert_eq!(get_role(&services, &admin, &admin).await["role"], "admin");
+    assert!(role_audits(&services, &admin).await.is_empty());
+}
+
+#[tokio::test]
+#[ignore]
+async fn test_unknown_user_is_not_found() {
+    let _serial = SERIAL.lock().await;
+    let Some(services) = setup().await else {
+        return;
+    };
+    let admin = create_user(&services, Role::Admin).await;
+
+    let (status, _) = set_role(&services, &admin, "no-such-user", "admin").await;
+
+    assert_eq!(status, StatusCode::NOT_FOUND);
+}
@@ -0,0 +1,143 @@
+use std::collections::HashSet;
+use std::sync::Arc;
+
+use blinks_backend::config::Config;
+use blinks_backend::db;
+use blinks_backend::service::AnchorService;
+use chrono::{Duration, TimeZone, Utc};
+use uuid::Uuid;
+
+// Note: These tests require a running database using the config.
+// Run with: cargo test --test withdrawal_history_test -- --ignored
+
+async fn setup() -> Option<(AnchorService, Arc<deadpool_postgres::Pool>)> {
+    let config = match Config::load() {
+        Ok(c) => c,
+        Err(_) => {
+            println!("Skipping test: No config found");
+            return None;
+        }
+    };
+
+    db::run_migrations(&config.database.url)
+        .await
+        .expect("Failed to run migrations");
+    let pool = Arc::new(
+        db::create_pool(&config.database.url)
+            .await
+            .expect("Failed to create pool"),
+    );
+
+    Some((AnchorService::new(pool.clone(), config), pool))
+}
+
+async fn create_user(pool: &deadpool_postgres::Pool) -> String {
+    let suffix = Uuid::new_v4().simple().to_string();
+    let user_id = format!("history-{}", suffix);
+
+    let client = pool.get().await.unwrap();
+    client
+        .execute(
+            "INSERT INTO users (user_id, stellar_address, pin_hash) VALUES ($1, $2, 'x')",
+            &[&user_id, &format!("G{}", suffix.to_uppercase())],
+        )
+        .await
+        .unwrap();
+
+    user_id
+}
+
+/// Insert `statuses.len()` withdrawals one minute apart, oldest first.
+/// The last two share a timestamp so ordering falls back to `id`.
+async fn seed_withdrawals(pool: &deadpool_postgres::Pool, user_id: &str, statuses: &[&str]) {
+    let client = pool.get().await.unwrap();
+    let base = Utc.with_ymd_and_hms(2026, 1, 1, 0, 0, 0).unwrap();
+
+    for (i, status) in statuses.iter().enumerate() {
+        let minutes = (i as i64).min(statuses.len() as i64 - 2);
+        let created_at = base + Duration::minutes(minutes);
+        client
+            .execute(
+                "INSERT INTO withdrawals
+                    (user_id, destination_address, amount, asset, status, created_at, updated_at)
+                 VALUES ($1, 'GDEST', $2, 'USDC', $3, $4, $4)",
+                &[&user_id, &(i as i64 + 1), status, &created_at],
+            )
+            .await
+            .unwrap();
+    }
+}
+
+#[tokio::test]
+#[ignore]
+async fn test_list_withdrawals_pages_forward_with_cursor() {
+    let Some((anchor, pool)) = setup().await else {
+        return;
+    };
+    let user_id = create_user(&pool).await;
+    let other_user = create_user(&pool).await;
+    seed_withdrawals(&pool, &user_id, &["pending"; 5]).await;
+    seed_withdrawals(&pool, &other_user, &["pending"; 3]).await;
+
+    let mut seen = Vec::new();
+    let mut cursor = None;
+    let mut pages = 0;
+    loop {
+        let page = anchor
+            .list_withdrawals(&user_id, None, 2, cursor.as_ref())
+            .await
+            .unwrap();
+        pages += 1;
+        assert!(page.withdrawals.len() <= 2);
+        seen.extend(page.withdrawals);
+
+        match page.next_cursor {
+            Some(next) => cursor = Some(next),
+            None => break,
+        }
+    }
+
+    assert_eq!(pages, 3);
+    assert_eq!(seen.len(), 5);
+    assert!(seen.iter().all(|w| w.user_id == user_id));
+
+    let ids: HashSet<_> = seen.iter().map(|w| w.id.clone()).collect();
+    assert_eq!(ids.len(), 5, "pages must not overlap");
+
+    for pair in seen.windows(2) {
+        assert!(
+            (pair[0].created_at, &pair[0].id) > (pair[1].created_at, &pair[1].id),
+            "withdrawals must be newest first"
+        );
+    }
+}
+
+#[tokio::test]
+#[ignore]
+async fn test_list_withdrawals_filters_by_status() {
+    let Some((anchor, pool)) = setup().await else {
+        return;
+    };
+    let user_id = create_user(&pool).await;
+    seed_withdrawals(
+        &pool,
+        &user_id,
+        &["pending", "completed", "pending", "completed", "error"],
+    )
+    .await;
+
+    let first = anchor
+        .list_withdrawals(&user_id, Some("completed"), 1, None)
+        .await
+        .unwrap();
+    assert_eq!(first.withdrawals.len(), 1);
+    assert_eq!(first.withdrawals[0].amount, 4);
+
+    let second = anchor
+        .list_withdrawals(&user_id, Some("completed"), 1, first.next_cursor.as_ref())
+        .await
+        .unwrap();
+    assert_eq!(second.withdrawals.len(), 1);
+    assert_eq!(second.withdrawals[0].amount, 2);
+    assert!(second.next_cursor.is_none());
+}
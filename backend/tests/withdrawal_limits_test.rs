@@ -0,0 +1,210 @@
+use std::net::SocketAddr;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+
+use axum::{extract::State, routing::post, Json, Router};
+use blinks_backend::api_error::ApiError;
+use blinks_backend::config::{Config, ReputationTier, WithdrawalLimit};
+use blinks_backend::db;
+use blinks_backend::service::anchor_service::InitiateWithdrawalParams;
+use blinks_backend::service::AnchorService;
+use serde_json::{json, Value};
+use uuid::Uuid;
+
+// Note: These tests require a running database using the config.
+// Run with: cargo test --test withdrawal_limits_test -- --ignored
+
+/// Bounds on USDC withdrawals: 1 to 100 USDC.
+const USDC_LIMIT: WithdrawalLimit = WithdrawalLimit {
+    min: Some(10_000_000),
+    max: Some(1_000_000_000),
+};
+
+/// Stand-in SEP-24 anchor that counts interactive withdrawal requests.
+async fn spawn_mock_anchor() -> (String, Arc<AtomicUsize>) {
+    async fn interactive(State(hits): State<Arc<AtomicUsize>>) -> Json<Value> {
+        hits.fetch_add(1, Ordering::SeqCst);
+        let id = Uuid::new_v4();
+        Json(json!({
+            "id": id.to_string(),
+            "url": format!("https://anchor.test/withdraw/{}", id),
+        }))
+    }
+
+    let hits = Arc::new(AtomicUsize::new(0));
+    let app = Router::new()
+        .route("/transactions/withdraw/interactive", post(interactive))
+        .with_state(hits.clone());
+
+    let listener = tokio::net::TcpListener::bind(SocketAddr::from(([127, 0, 0, 1], 0)))
+        .await
+        .unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move {
+        axum::serve(listener, app).await.unwrap();
+    });
+
+    (format!("http://{}", addr), hits)
+}
+
+async fn setup() -> Option<(
+    AnchorService,
+    Arc<deadpool_postgres::Pool>,
+    Arc<AtomicUsize>,
+)> {
+    let mut config = match Config::load() {
+        Ok(c) => c,
+        Err(_) => {
+            println!("Skipping test: No config found");
+            return None;
+        }
+    };
+
+    db::run_migrations(&config.database.url)
+        .await
+        .expect("Failed to run migrations");
+    let pool = Arc::new(
+        db::create_pool(&config.database.url)
+            .await
+            .expect("Failed to create pool"),
+    );
+
+    let (anchor_url, hits) = spawn_mock_anchor().await;
+    config.anchor_config.sep24_url = anchor_url;
+    config.anchor_config.kyc_required = false;
+    config
+        .anchor_config
+        .withdrawal_limits
+        .insert("USDC".to_string(), USDC_LIMIT);
+
+    Some((AnchorService::new(pool.clone(), config), pool, hits))
+}
+
+/// Insert a throwaway user and return `(user_id, stellar_address)`.
+async fn create_user(pool: &deadpool_postgres::Pool) -> (String, String) {
+    let suffix = Uuid::new_v4().simple().to_string();
+    let user_id = format!("withdraw-{}", suffix);
+    let stellar_address = format!("G{}", suffix.to_uppercase());
+
+    let client = pool.get().await.unwrap();
+    client
+        .execute(
+            "INSERT INTO users (user_id, stellar_address, pin_hash) VALUES ($1, $2, 'x')",
+            &[&user_id, &stellar_address],
+        )
+        .await
+        .unwrap();
+
+    (user_id, stellar_address)
+}
+
+fn withdrawal_params(
+    user_id: &str,
+    stellar_address: &str,
+    amount: i64,
+) -> InitiateWithdrawalParams {
+    InitiateWithdrawalParams {
+        user_id: user_id.to_string(),
+        stellar_address: stellar_address.to_string(),
+        destination_address: stellar_address.to_string(),
+        amount,
+        asset: "USDC".to_string(),
+        idempotency_key: None,
+        reputation_tier: None,
+    }
+}
+
+#[tokio::test]
+#[ignore]
+async fn test_out_of_range_withdrawals_never_reach_the_anchor() {
+    let Some((anchor, pool, hits)) = setup().await else {
+        return;
+    };
+    let (user_id, address) = create_user(&pool).await;
+
+    for amount in [USDC_LIMIT.min.unwrap() - 1, USDC_LIMIT.max.unwrap() + 1] {
+        let err = anchor
+            .initiate_withdrawal(withdrawal_params(&user_id, &address, amount))
+            .await
+            .unwrap_err();
+        assert!(
+            matches!(&err, ApiError::Validation(msg) if msg.contains("1.0000000 and 100.0000000")),
+            "{:?}",
+            err
+        );
+    }
+
+    assert_eq!(hits.load(Ordering::SeqCst), 0);
+}
+
+#[tokio::test]
+#[ignore]
+async fn test_in_range_withdrawal_is_created() {
+    let Some((anchor, pool, hits)) = setup().await else {
+        return;
+    };
+    let (user_id, address) = create_user(&pool).await;
+
+    let created = anchor
+        .initiate_withdrawal(withdrawal_params(&user_id, &address, 50_000_000))
+        .await
+        .unwrap();
+
+    assert_eq!(created.record.amount, 50_000_000);
+    assert_eq!(hits.load(Ordering::SeqCst), 1);
+}
+
+/// A reputation tier bounding USDC withdrawals to at most `max`.
+fn tier(name: &str, min_score: u32, max: i64) -> ReputationTier {
+    ReputationTier {
+        name: name.to_string(),
+        min_score,
+        withdrawal_limits: [(
+            "USDC".to_string(),
+            WithdrawalLimit {
+                min: USDC_LIMIT.min,
+                max: Some(max),
+            },
+        )]
+        .into(),
+        kyc_required: false,
+    }
+}
+
+#[tokio::test]
+#[ignore]
+async fn test_low_reputation_tier_caps_withdrawals() {
+    let Some((anchor, pool, hits)) = setup().await else {
+        return;
+    };
+    let (user_id, address) = create_user(&pool).await;
+
+    // 50 USDC is within the anchor's limits but over the tier's 10 USDC cap
+    let mut params = withdrawal_params(&user_id, &address, 500_000_000);
+    params.reputation_tier = Some(tier("new", 0, 100_000_000));
+    let err = anchor.initiate_withdrawal(params).await.unwrap_err();
+
+    assert!(
+        matches!(&err, ApiError::Validation(msg) if msg.contains("1.0000000 and 10.0000000")),
+        "{:?}",
+        err
+    );
+    assert_eq!(hits.load(Ordering::SeqCst), 0);
+}
+
+#[tokio::test]
+#[ignore]
+async fn test_high_reputation_tier_raises_the_cap() {
+    let Some((anchor, pool, hits)) = setup().await else {
+        return;
+    };
+    let (user_id, address) = create_user(&pool).await;
+
+    // 500 USDC is over the anchor's 100 USDC cap but within the tier's
+    let mut params = withdrawal_params(&user_id, &address, 5_000_000_000);
+    params.reputation_tier = Some(tier("trusted", 500, 10_000_000_000));
+    let created = anchor.initiate_withdrawal(params).await.unwrap();
+
+    assert_eq!(created.record.amount, 5_000_000_000);
+    assert_eq!(hits.load(Ordering::SeqCst), 1);
+}
@@ -0,0 +1,170 @@
+use std::sync::{Arc, Mutex};
+
+use async_trait::async_trait;
+use blinks_backend::config::Config;
+use blinks_backend::db;
+use blinks_backend::job_types::{JobPayload, JobType};
+use blinks_backend::queue::JobEnqueuer;
+use blinks_backend::service::anchor_service::AnchorTxStatus;
+use blinks_backend::service::AnchorService;
+use uuid::Uuid;
+
+// Note: These tests require a running database using the config.
+// Run with: cargo test --test withdrawal_notification_test -- --ignored
+
+/// Job queue stand-in that keeps enqueued jobs for inspection.
+#[derive(Default)]
+struct RecordingQueue {
+    jobs: Mutex<Vec<JobPayload>>,
+}
+
+#[async_trait]
+impl JobEnqueuer for RecordingQueue {
+    async fn enqueue(&self, job: JobPayload) -> anyhow::Result<()> {
+        self.jobs.lock().unwrap().push(job);
+        Ok(())
+    }
+}
+
+impl RecordingQueue {
+    fn jobs_for(&self, withdrawal_id: &str) -> Vec<JobPayload> {
+        self.jobs
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|job| job.payload["withdrawal_id"] == withdrawal_id)
+            .cloned()
+            .collect()
+    }
+}
+
+async fn setup() -> Option<(AnchorService, Arc<deadpool_postgres::Pool>)> {
+    let config = match Config::load() {
+        Ok(c) => c,
+        Err(_) => {
+            println!("Skipping test: No config found");
+            return None;
+        }
+    };
+
+    db::run_migrations(&config.database.url)
+        .await
+        .expect("Failed to run migrations");
+    let pool = Arc::new(
+        db::create_pool(&config.database.url)
+            .await
+            .expect("Failed to create pool"),
+    );
+
+    Some((AnchorService::new(pool.clone(), config), pool))
+}
+
+async fn seed_pending_withdrawal(pool: &deadpool_postgres::Pool) -> String {
+    let suffix = Uuid::new_v4().simple().to_string();
+    let user_id = format!("notify-{}", suffix);
+
+    let client = pool.get().await.unwrap();
+    client
+        .execute(
+            "INSERT INTO users (user_id, stellar_address, pin_hash) VALUES ($1, $2, 'x')",
+            &[&user_id, &format!("G{}", suffix.to_uppercase())],
+        )
+        .await
+        .unwrap();
+    client
+        .query_one(
+            "INSERT INTO withdrawals
+                (user_id, destination_address, amount, asset, status, anchor_tx_id)
+             VALUES ($1, 'GDEST', 750, 'USDC', 'processing', $2)
+             RETURNING id::text",
+            &[&user_id, &format!("anchor-{}", suffix)],
+        )
+        .await
+        .unwrap()
+        .get(0)
+}
+
+/// The status the anchor webhook handler applies for a raw anchor status.
+fn webhook_status(anchor_status: &str) -> &'static str {
+    AnchorTxStatus::from_anchor(anchor_status).withdrawal_status()
+}
+
+#[tokio::test]
+#[ignore]
+async fn test_completed_webhook_enqueues_one_notification() {
+    let Some((anchor, pool)) = setup().await else {
+        return;
+    };
+    let queue = RecordingQueue::default();
+    let withdrawal_id = seed_pending_withdrawal(&pool).await;
+
+    // The anchor retries webhooks, so the same event can arrive twice.
+    for _ in 0..2 {
+        anchor
+            .apply_withdrawal_status(&withdrawal_id, webhook_status("completed"), &queue)
+            .await
+            .unwrap();
+    }
+
+    let jobs = queue.jobs_for(&withdrawal_id);
+    assert_eq!(jobs.len(), 1);
+    assert_eq!(jobs[0].job_type, JobType::Notification);
+    assert_eq!(jobs[0].payload["status"], "completed");
+    assert_eq!(jobs[0].payload["amount"], 750);
+    assert_eq!(jobs[0].payload["asset"], "USDC");
+}
+
+#[tokio::test]
+#[ignore]
+async fn test_concurrent_observers_notify_once() {
+    let Some((anchor, pool)) = setup().await else {
+        return;
+    };
+    let queue = RecordingQueue::default();
+    let withdrawal_id = seed_pending_withdrawal(&pool).await;
+
+    // Webhook and poller racing to record the same failure.
+    let (webhook, poller) = tokio::join!(
+        anchor.apply_withdrawal_status(&withdrawal_id, webhook_status("error"), &queue),
+        anchor.apply_withdrawal_status(&withdrawal_id, webhook_status("expired"), &queue),
+    );
+
+    let applied = [webhook.unwrap(), poller.unwrap()]
+        .into_iter()
+        .flatten()
+        .count();
+    assert_eq!(applied, 1);
+
+    let jobs = queue.jobs_for(&withdrawal_id);
+    assert_eq!(jobs.len(), 1);
+    assert_eq!(jobs[0].payload["status"], "failed");
+}
+
+#[tokio::test]
+#[ignore]
+async fn test_non_terminal_update_does_not_notify() {
+    let Some((anchor, pool)) = setup().await else {
+        return;
+    };
+    let queue = RecordingQueue::default();
+    let withdrawal_id = seed_pending_withdrawal(&pool).await;
+
+    let transition = anchor
+        .apply_withdrawal_status(&withdrawal_id, webhook_status("pending_user"), &queue)
+        .await
+        .unwrap();
+
+    assert!(transition.is_none(), "status was already processing");
+    assert!(queue.jobs_for(&withdrawal_id).is_empty());
+
+    let transition = anchor
+        .apply_withdrawal_status(&withdrawal_id, webhook_status("incomplete"), &queue)
+        .await
+        .unwrap();
+
+    assert!(
+        transition.is_none(),
+        "processing -> pending moves backwards"
+    );
+    assert!(queue.jobs_for(&withdrawal_id).is_empty());
+}
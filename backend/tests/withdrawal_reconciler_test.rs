@@ -0,0 +1,230 @@
+use std::collections::HashMap;
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex};
+
+use async_trait::async_trait;
+use axum::{
+    extract::{Query, State},
+    http::StatusCode,
+    routing::get,
+    Json, Router,
+};
+use blinks_backend::config::Config;
+use blinks_backend::db;
+use blinks_backend::job_types::JobPayload;
+use blinks_backend::queue::JobEnqueuer;
+use blinks_backend::service::AnchorService;
+use serde_json::{json, Value};
+use uuid::Uuid;
+
+// Note: These tests require a running database using the config.
+// Run with: cargo test --test withdrawal_reconciler_test -- --ignored
+
+/// Anchor transaction statuses served by the mock, plus a poll counter per id.
+#[derive(Default)]
+struct MockAnchorState {
+    statuses: HashMap<String, String>,
+    polls: HashMap<String, usize>,
+}
+
+type SharedAnchor = Arc<Mutex<MockAnchorState>>;
+
+async fn spawn_mock_anchor() -> (String, SharedAnchor) {
+    async fn transaction(
+        State(state): State<SharedAnchor>,
+        Query(params): Query<HashMap<String, String>>,
+    ) -> Result<Json<Value>, StatusCode> {
+        let id = params.get("id").cloned().unwrap_or_default();
+        let mut state = state.lock().unwrap();
+        *state.polls.entry(id.clone()).or_default() += 1;
+        let status = state.statuses.get(&id).ok_or(StatusCode::NOT_FOUND)?;
+        Ok(Json(
+            json!({ "transaction": { "id": id, "status": status } }),
+        ))
+    }
+
+    let state = SharedAnchor::default();
+    let app = Router::new()
+        .route("/transaction", get(transaction))
+        .with_state(state.clone());
+
+    let listener = tokio::net::TcpListener::bind(SocketAddr::from(([127, 0, 0, 1], 0)))
+        .await
+        .unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move {
+        axum::serve(listener, app).await.unwrap();
+    });
+
+    (format!("http://{}", addr), state)
+}
+
+/// Job queue stand-in that keeps enqueued jobs for inspection.
+#[derive(Default)]
+struct RecordingQueue {
+    jobs: Mutex<Vec<JobPayload>>,
+}
+
+#[async_trait]
+impl JobEnqueuer for RecordingQueue {
+    async fn enqueue(&self, job: JobPayload) -> anyhow::Result<()> {
+        self.jobs.lock().unwrap().push(job);
+        Ok(())
+    }
+}
+
+/// Each run polls every due withdrawal in the table, so tests must not overlap.
+static SERIAL: tokio::sync::Mutex<()> = tokio::sync::Mutex::const_new(());
+
+async fn setup(
+    batch_size: i64,
+) -> Option<(AnchorService, Arc<deadpool_postgres::Pool>, SharedAnchor)> {
+    let mut config = match Config::load() {
+        Ok(c) => c,
+        Err(_) => {
+            println!("Skipping test: No config found");
+            return None;
+        }
+    };
+
+    db::run_migrations(&config.database.url)
+        .await
+        .expect("Failed to run migrations");
+    let pool = Arc::new(
+        db::create_pool(&config.database.url)
+            .await
+            .expect("Failed to create pool"),
+    );
+
+    let (anchor_url, state) = spawn_mock_anchor().await;
+    config.anchor_config.sep24_url = anchor_url;
+    config.reconciler.batch_size = batch_size;
+
+    Some((AnchorService::new(pool.clone(), config), pool, state))
+}
+
+/// Insert a user with one pending withdrawal and return `(withdrawal_id, anchor_tx_id)`.
+async fn seed_pending_withdrawal(pool: &deadpool_postgres::Pool) -> (String, String) {
+    let suffix = Uuid::new_v4().simple().to_string();
+    let user_id = format!("reconcile-{}", suffix);
+    let anchor_tx_id = format!("anchor-{}", suffix);
+
+    let client = pool.get().await.unwrap();
+    client
+        .execute(
+            "INSERT INTO users (user_id, stellar_address, pin_hash) VALUES ($1, $2, 'x')",
+            &[&user_id, &format!("G{}", suffix.to_uppercase())],
+        )
+        .await
+        .unwrap();
+    let row = client
+        .query_one(
+            "INSERT INTO withdrawals
+                (user_id, destination_address, amount, asset, status, anchor_tx_id)
+             VALUES ($1, 'GDEST', 500, 'USDC', 'pending', $2)
+             RETURNING id::text",
+            &[&user_id, &anchor_tx_id],
+        )
+        .await
+        .unwrap();
+
+    (row.get(0), anchor_tx_id)
+}
+
+#[tokio::test]
+#[ignore]
+async fn test_reconciler_completes_withdrawal_without_client_request() {
+    let _serial = SERIAL.lock().await;
+    let Some((anchor, pool, state)) = setup(10_000).await else {
+        return;
+    };
+    let queue = RecordingQueue::default();
+    let (withdrawal_id, anchor_tx_id) = seed_pending_withdrawal(&pool).await;
+    state
+        .lock()
+        .unwrap()
+        .statuses
+        .insert(anchor_tx_id.clone(), "completed".to_string());
+
+    let summary = anchor.reconcile_withdrawals(&queue).await.unwrap();
+    assert!(summary.updated >= 1);
+
+    let record = anchor.get_withdrawal_by_id(&withdrawal_id).await.unwrap();
+    assert_eq!(record.status, "completed");
+
+    let notified = queue
+        .jobs
+        .lock()
+        .unwrap()
+        .iter()
+        .filter(|job| job.payload["withdrawal_id"] == withdrawal_id.as_str())
+        .count();
+    assert_eq!(notified, 1);
+
+    // Terminal withdrawals drop out of later runs.
+    anchor.reconcile_withdrawals(&queue).await.unwrap();
+    assert_eq!(state.lock().unwrap().polls[&anchor_tx_id], 1);
+}
+
+#[tokio::test]
+#[ignore]
+async fn test_reconciler_backs_off_unchanged_withdrawals() {
+    let _serial = SERIAL.lock().await;
+    let Some((anchor, pool, state)) = setup(10_000).await else {
+        return;
+    };
+    let queue = RecordingQueue::default();
+    let (withdrawal_id, anchor_tx_id) = seed_pending_withdrawal(&pool).await;
+    state
+        .lock()
+        .unwrap()
+        .statuses
+        .insert(anchor_tx_id.clone(), "incomplete".to_string());
+
+    anchor.reconcile_withdrawals(&queue).await.unwrap();
+    anchor.reconcile_withdrawals(&queue).await.unwrap();
+
+    // The second run must skip the row until its backoff has elapsed.
+    assert_eq!(state.lock().unwrap().polls[&anchor_tx_id], 1);
+
+    let client = pool.get().await.unwrap();
+    let row = client
+        .query_one(
+            "SELECT status, reconcile_attempts, next_reconcile_at > NOW()
+             FROM withdrawals WHERE id = $1::text::uuid",
+            &[&withdrawal_id],
+        )
+        .await
+        .unwrap();
+    assert_eq!(row.get::<_, String>(0), "pending");
+    assert_eq!(row.get::<_, i32>(1), 1);
+    assert!(row.get::<_, bool>(2));
+}
+
+#[tokio::test]
+#[ignore]
+async fn test_reconciler_respects_batch_size() {
+    let _serial = SERIAL.lock().await;
+    let Some((drain, _, _)) = setup(10_000).await else {
+        return;
+    };
+    let queue = RecordingQueue::default();
+    // Push everything already due into backoff so only the rows below qualify.
+    drain.reconcile_withdrawals(&queue).await.unwrap();
+
+    let (anchor, pool, state) = setup(2).await.unwrap();
+    for _ in 0..3 {
+        let (_, anchor_tx_id) = seed_pending_withdrawal(&pool).await;
+        state
+            .lock()
+            .unwrap()
+            .statuses
+            .insert(anchor_tx_id, "pending_anchor".to_string());
+    }
+
+    let first = anchor.reconcile_withdrawals(&queue).await.unwrap();
+    let second = anchor.reconcile_withdrawals(&queue).await.unwrap();
+
+    assert_eq!((first.checked, first.updated), (2, 2));
+    assert_eq!((second.checked, second.updated), (1, 1));
+}
@@ -0,0 +1,212 @@
+use std::sync::Arc;
+
+use axum::{
+    body::Body,
+    http::{Request, StatusCode},
+    middleware,
+    routing::patch,
+    Extension, Router,
+};
+use http_body_util::BodyExt;
+use serde_json::{json, Value};
+use tower::util::ServiceExt;
+use uuid::Uuid;
+
+use blinks_backend::config::Config;
+use blinks_backend::db;
+use blinks_backend::http::admin::override_withdrawal_status;
+use blinks_backend::middleware::{auth::AuthenticatedUser, role_guard};
+use blinks_backend::role::Role;
+use blinks_backend::service::ServiceContainer;
+
+// Note: These tests require a running database using the config.
+// Run with: cargo test --test withdrawal_status_override_test -- --ignored
+
+async fn setup() -> Option<Arc<ServiceContainer>> {
+    let config = match Config::load() {
+        Ok(c) => c,
+        Err(_) => {
+            println!("Skipping test: No config found");
+            return None;
+        }
+    };
+
+    db::run_migrations(&config.database.url)
+        .await
+        .expect("Failed to run migrations");
+    let pool = db::create_pool(&config.database.url)
+        .await
+        .expect("Failed to create pool");
+
+    Some(Arc::new(
+        ServiceContainer::new(pool, config)
+            .await
+            .expect("Failed to create services"),
+    ))
+}
+
+async fn seed_withdrawal(services: &ServiceContainer, status: &str) -> String {
+    let suffix = Uuid::new_v4().simple().to_string();
+    let user_id = format!("override-{}", suffix);
+
+    let client = services.db_pool.get().await.unwrap();
+    client
+        .execute(
+            "INSERT INTO users (user_id, stellar_address, pin_hash) VALUES ($1, $2, 'x')",
+            &[&user_id, &format!("G{}", suffix.to_uppercase())],
+        )
+        .await
+        .unwrap();
+    client
+        .query_one(
+            "INSERT INTO withdrawals
+                (user_id, destination_address, amount, asset, status, anchor_tx_id)
+             VALUES ($1, 'GDEST', 500, 'USDC', $2, $3)
+             RETURNING id::text",
+            &[&user_id, &status, &format!("anchor-{}", suffix)],
+        )
+        .await
+        .unwrap()
+        .get(0)
+}
+
+/// The override route, mounted behind the admin guard, as `caller` would
+/// call it.
+async fn override_status(
+    services: &Arc<ServiceContainer>,
+    caller: AuthenticatedUser,
+    withdrawal_id: &str,
+    body: Value,
+) -> (StatusCode, Value) {
+    let app = Router::new()
+        .route(
+            "/admin/withdrawals/:id/status",
+            patch(override_withdrawal_status),
+        )
+        .layer(middleware::from_fn(role_guard::require_role(Role::Admin)))
+        .layer(Extension(caller))
+        .with_state(services.clone());
+
+    let request = Request::builder()
+        .method("PATCH")
+        .uri(format!("/admin/withdrawals/{}/status", withdrawal_id))
+        .header("Content-Type", "application/json")
+        .body(Body::from(body.to_string()))
+        .unwrap();
+    let response = app.oneshot(request).await.unwrap();
+    let status = response.status();
+    let body = response.into_body().collect().await.unwrap().to_bytes();
+    (status, serde_json::from_slice(&body).unwrap_or(Value::Null))
+}
+
+fn caller(role: Role) -> AuthenticatedUser {
+    AuthenticatedUser {
+        user_id: format!("support-{}", Uuid::new_v4().simple()),
+        role,
+    }
+}
+
+#[tokio::test]
+#[ignore]
+async fn test_admin_forces_completed_with_a_reason() {
+    let Some(services) = setup().await else {
+        return;
+    };
+    let withdrawal_id = seed_withdrawal(&services, "processing").await;
+    let admin = caller(Role::Admin);
+
+    let (status, body) = override_status(
+        &services,
+        admin.clone(),
+        &withdrawal_id,
+        json!({ "status": "completed", "reason": "Anchor confirmed payout by email" }),
+    )
+    .await;
+
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["status"], "completed");
+
+    let audit = services
+        .db_pool
+        .get()
+        .await
+        .unwrap()
+        .query_one(
+            "SELECT actor_id, metadata FROM audit_logs
+             WHERE action = 'override_withdrawal_status' AND resource_id = $1",
+            &[&withdrawal_id],
+        )
+        .await
+        .unwrap();
+    assert_eq!(audit.get::<_, String>(0), admin.user_id);
+    assert_eq!(
+        audit.get::<_, Value>(1),
+        json!({
+            "from": "processing",
+            "to": "completed",
+            "reason": "Anchor confirmed payout by email",
+        })
+    );
+
+    // Settled withdrawals can't be forced again
+    let (status, _) = override_status(
+        &services,
+        admin,
+        &withdrawal_id,
+        json!({ "status": "refunded", "reason": "Changed my mind" }),
+    )
+    .await;
+    assert_eq!(status, StatusCode::CONFLICT);
+}
+
+#[tokio::test]
+#[ignore]
+async fn test_override_requires_a_reason_and_a_terminal_status() {
+    let Some(services) = setup().await else {
+        return;
+    };
+    let withdrawal_id = seed_withdrawal(&services, "pending").await;
+
+    for body in [
+        json!({ "status": "completed", "reason": "  " }),
+        json!({ "status": "processing", "reason": "Stuck at the anchor" }),
+    ] {
+        let (status, _) =
+            override_status(&services, caller(Role::Admin), &withdrawal_id, body).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+    }
+}
+
+#[tokio::test]
+#[ignore]
+async fn test_non_admin_is_forbidden() {
+    let Some(services) = setup().await else {
+        return;
+    };
+    let withdrawal_id = seed_withdrawal(&services, "processing").await;
+
+    for role in [Role::User, Role::Merchant] {
+        let (status, _) = override_status(
+            &services,
+            caller(role),
+            &withdrawal_id,
+            json!({ "status": "completed", "reason": "Confirmed" }),
+        )
+        .await;
+        assert_eq!(status, StatusCode::FORBIDDEN);
+    }
+
+    let current: String = services
+        .db_pool
+        .get()
+        .await
+        .unwrap()
+        .query_one(
+            "SELECT status FROM withdrawals WHERE id = $1::text::uuid",
+            &[&withdrawal_id],
+        )
+        .await
+        .unwrap()
+        .get(0);
+    assert_eq!(current, "processing");
+}
@@ -2,7 +2,7 @@
 
 use soroban_sdk::{
     contract, contractimpl, contracttype, panic_with_error, contracterror,
-    symbol_short, Address, Env, Symbol, BytesN,
+    symbol_short, Address, Env, IntoVal, Map, Symbol, BytesN, TryFromVal, Val, Vec,
     token::{Client as TokenClient},
 };
 
@@ -17,8 +17,78 @@ pub struct Escrow {
     pub state: EscrowState,
     pub memo: BytesN<32>,             
     pub created_at: u64,
+    pub timeout_ledger: u32,          // ledger from which anyone may trigger a refund
+    pub fee_bps: Option<u32>,         // arbitrator's cut of a resolved dispute
 }
 
+/// Layout of escrows stored before `timeout_ledger` and `fee_bps` were
+/// added; read through `load_escrow`, which upgrades them on the fly.
+#[contracttype]
+#[derive(Clone)]
+pub struct EscrowV1 {
+    pub buyer: Address,
+    pub seller: Address,
+    pub arbitrator: Option<Address>,
+    pub token: Address,
+    pub amount: i128,
+    pub state: EscrowState,
+    pub memo: BytesN<32>,
+    pub created_at: u64,
+}
+
+/// Terms of an escrow locked with an arbitrator.
+#[contracttype]
+#[derive(Clone)]
+pub struct LockTerms {
+    pub buyer: Address,
+    pub seller: Address,
+    pub token: Address,
+    pub amount: i128,
+    pub timeout_ledger: u32,
+    pub memo: BytesN<32>,
+    pub arbitrator: Option<Address>,
+    pub fee_bps: Option<u32>,
+}
+
+/// A participant's list of active escrows.
+#[contracttype]
+#[derive(Clone)]
+pub enum Index {
+    Buyer(Address),
+    Seller(Address),
+}
+
+/// Each index is stored one entry per key, so adding or removing an escrow
+/// touches a fixed number of entries however many the participant has.
+#[contracttype]
+#[derive(Clone)]
+pub enum DataKey {
+    Len(Index),                  // index => number of active escrows
+    Entry(Index, u32),           // (index, position) => escrow id
+    Position(Index, BytesN<32>), // (index, escrow id) => position
+}
+
+/// Basis points in 100%; the ceiling for an arbitrator fee.
+pub const MAX_FEE_BPS: u32 = 10_000;
+
+/// Shortest allowed escrow lifetime, in ledgers (~1 hour at 5s per ledger).
+pub const MIN_TIMEOUT_LEDGERS: u32 = 720;
+
+/// Longest allowed escrow lifetime, in ledgers (~1 year at 5s per ledger).
+pub const MAX_TIMEOUT_LEDGERS: u32 = 6_307_200;
+
+/// Refund window of escrows stored before per-escrow timeouts, in seconds.
+const LEGACY_TIMEOUT_SECS: u64 = 7 * 24 * 60 * 60;
+
+/// Number of fields in an `EscrowV1`.
+const LEGACY_ESCROW_FIELDS: u32 = 8;
+
+/// Ledger close time assumed when converting that window into ledgers.
+const SECONDS_PER_LEDGER: u64 = 5;
+
+/// Largest page the listing views will return.
+pub const MAX_PAGE_SIZE: u32 = 50;
+
 #[contracttype]
 #[derive(Copy, Clone, PartialEq, Eq, Debug)]
 pub enum EscrowState {
@@ -39,6 +109,8 @@ pub enum EscrowError {
     InvalidState = 6,
     InvalidArbitrator = 7,
     TimeoutNotReached = 8,
+    InvalidFee = 9,
+    InvalidTimeout = 10,
 }
 
 #[contract]
@@ -47,6 +119,9 @@ pub struct EscrowContract;
 #[contractimpl]
 impl EscrowContract {
 
+    // The argument list is the contract's original public interface; callers
+    // depend on it, so it is kept as is rather than folded into `LockTerms`.
+    #[allow(clippy::too_many_arguments)]
     pub fn lock_funds(
         env: Env,
         escrow_id: BytesN<32>,
@@ -54,41 +129,54 @@ impl EscrowContract {
         seller: Address,
         token: Address,
         amount: i128,
-        _timeout_ledger: u32,           
+        timeout_ledger: u32,           
         memo: BytesN<32>,
     ) {
-        buyer.require_auth();
-
-        if amount <= 0 {
-            panic_with_error!(env, EscrowError::InvalidAmount);
-        }
-
-        let key = escrow_key(&escrow_id);
-
-        if env.storage().persistent().has(&key) {
-            panic_with_error!(env, EscrowError::AlreadyLocked);
-        }
-
-        let token_client = TokenClient::new(&env, &token);
-        token_client.transfer(&buyer, &env.current_contract_address(), &amount);
-
         let escrow = Escrow {
-            buyer: buyer.clone(),
-            seller: seller.clone(),
+            buyer,
+            seller,
             arbitrator: Option::None,
             token,
             amount,
             state: EscrowState::Locked,
             memo,
             created_at: env.ledger().timestamp(),
+            timeout_ledger,
+            fee_bps: Option::None,
         };
+        lock(&env, escrow_id, escrow);
+    }
 
-        env.storage().persistent().set(&key, &escrow);
+    /// Lock funds with an arbitrator who can settle a dispute, optionally
+    /// taking `fee_bps` of the amount as commission when they do.
+    pub fn lock_funds_with_arbitrator(env: Env, escrow_id: BytesN<32>, terms: LockTerms) {
+        if let Some(bps) = terms.fee_bps {
+            if bps > MAX_FEE_BPS {
+                panic_with_error!(env, EscrowError::InvalidFee);
+            }
+            if terms.arbitrator.is_none() {
+                panic_with_error!(env, EscrowError::InvalidArbitrator);
+            }
+        }
+        if let Some(arb) = &terms.arbitrator {
+            if *arb == terms.buyer || *arb == terms.seller {
+                panic_with_error!(env, EscrowError::InvalidArbitrator);
+            }
+        }
 
-        env.events().publish(
-            (symbol_short!("escrow"), symbol_short!("locked")),
-            (escrow_id, buyer, seller, amount)
-        );
+        let escrow = Escrow {
+            buyer: terms.buyer,
+            seller: terms.seller,
+            arbitrator: terms.arbitrator,
+            token: terms.token,
+            amount: terms.amount,
+            state: EscrowState::Locked,
+            memo: terms.memo,
+            created_at: env.ledger().timestamp(),
+            timeout_ledger: terms.timeout_ledger,
+            fee_bps: terms.fee_bps,
+        };
+        lock(&env, escrow_id, escrow);
     }
 
     pub fn release_funds(
@@ -99,7 +187,7 @@ impl EscrowContract {
         caller.require_auth();
 
         let key = escrow_key(&escrow_id);
-        let mut escrow: Escrow = env.storage().persistent().get(&key)
+        let mut escrow = load_escrow(&env, &key)
             .unwrap_or_else(|| panic_with_error!(env, EscrowError::NotLocked));
 
         if escrow.state != EscrowState::Locked {
@@ -125,11 +213,9 @@ impl EscrowContract {
 
         escrow.state = EscrowState::Released;
         env.storage().persistent().set(&key, &escrow);
+        unindex(&env, &escrow, &escrow_id);
 
-        env.events().publish(
-            (symbol_short!("escrow"), symbol_short!("released")),
-            (escrow_id, caller, escrow.seller, escrow.amount)
-        );
+        publish(&env, symbol_short!("released"), &escrow, (escrow_id, caller, escrow.amount));
     }
 
     pub fn refund_funds(
@@ -140,19 +226,22 @@ impl EscrowContract {
         caller.require_auth();
 
         let key = escrow_key(&escrow_id);
-        let mut escrow: Escrow = env.storage().persistent().get(&key)
+        let mut escrow = load_escrow(&env, &key)
             .unwrap_or_else(|| panic_with_error!(env, EscrowError::NotLocked));
 
         if escrow.state != EscrowState::Locked {
             panic_with_error!(env, EscrowError::InvalidState);
         }
 
-        let is_timeout = env.ledger().timestamp() >= escrow.created_at + 7 * 24 * 60 * 60;
-        let is_authorized = 
-            caller == escrow.buyer ||
-            escrow.arbitrator.as_ref().map_or(false, |a| *a == caller);
+        // Before the timeout only the arbitrator may refund; the buyer has to
+        // wait it out, and anyone may refund the buyer once it has passed.
+        let is_timeout = env.ledger().sequence() >= escrow.timeout_ledger;
+        let is_arbitrator = escrow.arbitrator.as_ref() == Some(&caller);
 
-        if !is_authorized && !is_timeout {
+        if !is_arbitrator && !is_timeout {
+            if caller == escrow.buyer {
+                panic_with_error!(env, EscrowError::TimeoutNotReached);
+            }
             panic_with_error!(env, EscrowError::NotAuthorized);
         }
 
@@ -165,33 +254,234 @@ impl EscrowContract {
 
         escrow.state = EscrowState::Refunded;
         env.storage().persistent().set(&key, &escrow);
+        unindex(&env, &escrow, &escrow_id);
 
-        env.events().publish(
-            (symbol_short!("escrow"), symbol_short!("refunded")),
-            (escrow_id, caller, escrow.buyer, escrow.amount)
-        );
+        publish(&env, symbol_short!("refunded"), &escrow, (escrow_id, caller, escrow.amount));
+    }
+
+    /// Buyer or seller flags a locked escrow for the arbitrator to settle.
+    pub fn raise_dispute(
+        env: Env,
+        escrow_id: BytesN<32>,
+        caller: Address,
+    ) {
+        caller.require_auth();
+
+        let key = escrow_key(&escrow_id);
+        let mut escrow = load_escrow(&env, &key)
+            .unwrap_or_else(|| panic_with_error!(env, EscrowError::NotLocked));
+
+        if escrow.state != EscrowState::Locked {
+            panic_with_error!(env, EscrowError::InvalidState);
+        }
+        if caller != escrow.buyer && caller != escrow.seller {
+            panic_with_error!(env, EscrowError::NotAuthorized);
+        }
+        if escrow.arbitrator.is_none() {
+            panic_with_error!(env, EscrowError::InvalidArbitrator);
+        }
+
+        escrow.state = EscrowState::Disputed;
+        env.storage().persistent().set(&key, &escrow);
+
+        publish(&env, symbol_short!("disputed"), &escrow, (escrow_id, caller));
+    }
+
+    /// Arbitrator settles a dispute in favour of the seller (release) or the
+    /// buyer (refund). The arbitrator's fee is paid first and the winning
+    /// party receives the remainder.
+    pub fn resolve_dispute(
+        env: Env,
+        escrow_id: BytesN<32>,
+        arbitrator: Address,
+        release_to_seller: bool,
+    ) {
+        arbitrator.require_auth();
+
+        let key = escrow_key(&escrow_id);
+        let mut escrow = load_escrow(&env, &key)
+            .unwrap_or_else(|| panic_with_error!(env, EscrowError::NotLocked));
+
+        if escrow.state != EscrowState::Disputed {
+            panic_with_error!(env, EscrowError::InvalidState);
+        }
+        if escrow.arbitrator.as_ref() != Some(&arbitrator) {
+            panic_with_error!(env, EscrowError::NotAuthorized);
+        }
+
+        let fee = escrow.amount * escrow.fee_bps.unwrap_or(0) as i128 / MAX_FEE_BPS as i128;
+        let remainder = escrow.amount - fee;
+        let winner = if release_to_seller { escrow.seller.clone() } else { escrow.buyer.clone() };
+
+        let token_client = TokenClient::new(&env, &escrow.token);
+        if fee > 0 {
+            token_client.transfer(&env.current_contract_address(), &arbitrator, &fee);
+        }
+        token_client.transfer(&env.current_contract_address(), &winner, &remainder);
+
+        escrow.state = if release_to_seller { EscrowState::Released } else { EscrowState::Refunded };
+        env.storage().persistent().set(&key, &escrow);
+        unindex(&env, &escrow, &escrow_id);
+
+        publish(&env, symbol_short!("resolved"), &escrow, (escrow_id, arbitrator, winner, remainder, fee));
     }
 
     pub fn get_escrow(env: Env, escrow_id: BytesN<32>) -> Escrow {
         let key = escrow_key(&escrow_id);
-        env.storage().persistent()
-            .get(&key)
+        load_escrow(&env, &key)
             .unwrap_or_else(|| panic_with_error!(env, EscrowError::NotLocked))
     }
 
     pub fn is_locked(env: Env, escrow_id: BytesN<32>) -> bool {
         let key = escrow_key(&escrow_id);
-        match env.storage().persistent().get::<_, Escrow>(&key) {
+        match load_escrow(&env, &key) {
             Some(escrow) => escrow.state == EscrowState::Locked,
             None => false,
         }
     }
+
+    /// Ids of the buyer's active escrows, oldest first until one is
+    /// finalized: its slot is then taken by the newest.
+    /// At most `MAX_PAGE_SIZE` ids are returned per call.
+    pub fn escrows_of_buyer(env: Env, buyer: Address, start: u32, limit: u32) -> Vec<BytesN<32>> {
+        index_page(&env, Index::Buyer(buyer), start, limit)
+    }
+
+    /// Ids of the seller's active escrows, in the same order as
+    /// `escrows_of_buyer`.
+    /// At most `MAX_PAGE_SIZE` ids are returned per call.
+    pub fn escrows_of_seller(env: Env, seller: Address, start: u32, limit: u32) -> Vec<BytesN<32>> {
+        index_page(&env, Index::Seller(seller), start, limit)
+    }
 }
 
 // Helpers
 
+fn lock(env: &Env, escrow_id: BytesN<32>, escrow: Escrow) {
+    escrow.buyer.require_auth();
+
+    if escrow.amount <= 0 {
+        panic_with_error!(env, EscrowError::InvalidAmount);
+    }
+
+    let current = env.ledger().sequence();
+    if escrow.timeout_ledger < current.saturating_add(MIN_TIMEOUT_LEDGERS)
+        || escrow.timeout_ledger > current.saturating_add(MAX_TIMEOUT_LEDGERS)
+    {
+        panic_with_error!(env, EscrowError::InvalidTimeout);
+    }
+
+    let key = escrow_key(&escrow_id);
+
+    if env.storage().persistent().has(&key) {
+        panic_with_error!(env, EscrowError::AlreadyLocked);
+    }
+
+    let token_client = TokenClient::new(env, &escrow.token);
+    token_client.transfer(&escrow.buyer, &env.current_contract_address(), &escrow.amount);
+
+    env.storage().persistent().set(&key, &escrow);
+    index_add(env, Index::Buyer(escrow.buyer.clone()), &escrow_id);
+    index_add(env, Index::Seller(escrow.seller.clone()), &escrow_id);
+
+    publish(env, symbol_short!("locked"), &escrow, (escrow_id, escrow.amount));
+}
+
+/// Publish an escrow event under `("escrow", action, buyer, seller)`, so
+/// indexers can filter a participant's events on the topics alone.
+fn publish<D: IntoVal<Env, Val>>(env: &Env, action: Symbol, escrow: &Escrow, data: D) {
+    env.events().publish(
+        (symbol_short!("escrow"), action, escrow.buyer.clone(), escrow.seller.clone()),
+        data,
+    );
+}
+
+/// Read an escrow, upgrading one stored in the `EscrowV1` layout. Its refund
+/// window becomes a timeout ledger estimated from the time left of the old
+/// seven days; the upgraded record is written back on the next state change.
+fn load_escrow(env: &Env, key: &(Symbol, BytesN<32>)) -> Option<Escrow> {
+    // A struct decodes only from a map with exactly its fields (any other
+    // size is a host error, not an `Err`), so pick the layout by field count.
+    let raw: Map<Symbol, Val> = env.storage().persistent().get(key)?;
+    if raw.len() != LEGACY_ESCROW_FIELDS {
+        return Some(Escrow::try_from_val(env, &raw.to_val()).unwrap());
+    }
+    let old = EscrowV1::try_from_val(env, &raw.to_val()).unwrap();
+
+    let remaining = (old.created_at + LEGACY_TIMEOUT_SECS).saturating_sub(env.ledger().timestamp());
+    let ledgers = u32::try_from(remaining.div_ceil(SECONDS_PER_LEDGER)).unwrap_or(u32::MAX);
+    Some(Escrow {
+        buyer: old.buyer,
+        seller: old.seller,
+        arbitrator: old.arbitrator,
+        token: old.token,
+        amount: old.amount,
+        state: old.state,
+        memo: old.memo,
+        created_at: old.created_at,
+        timeout_ledger: env.ledger().sequence().saturating_add(ledgers),
+        fee_bps: None,
+    })
+}
+
 fn escrow_key(id: &BytesN<32>) -> (Symbol, BytesN<32>) {
     (symbol_short!("escrow"), id.clone())
 }
 
+fn index_len(env: &Env, index: &Index) -> u32 {
+    env.storage().persistent().get(&DataKey::Len(index.clone())).unwrap_or(0)
+}
+
+fn index_add(env: &Env, index: Index, id: &BytesN<32>) {
+    let storage = env.storage().persistent();
+    let len = index_len(env, &index);
+    storage.set(&DataKey::Entry(index.clone(), len), id);
+    storage.set(&DataKey::Position(index.clone(), id.clone()), &len);
+    storage.set(&DataKey::Len(index), &(len + 1));
+}
+
+/// Drop a finalized escrow from its buyer's and seller's indexes.
+fn unindex(env: &Env, escrow: &Escrow, id: &BytesN<32>) {
+    index_remove(env, Index::Buyer(escrow.buyer.clone()), id);
+    index_remove(env, Index::Seller(escrow.seller.clone()), id);
+}
+
+/// Remove `id` by moving the last entry into its slot.
+fn index_remove(env: &Env, index: Index, id: &BytesN<32>) {
+    let storage = env.storage().persistent();
+    let pos_key = DataKey::Position(index.clone(), id.clone());
+    let pos: u32 = match storage.get(&pos_key) {
+        Some(pos) => pos,
+        None => return,
+    };
+    storage.remove(&pos_key);
+
+    let last = index_len(env, &index) - 1;
+    let last_key = DataKey::Entry(index.clone(), last);
+    if pos != last {
+        let moved: BytesN<32> = storage.get(&last_key).unwrap();
+        storage.set(&DataKey::Entry(index.clone(), pos), &moved);
+        storage.set(&DataKey::Position(index.clone(), moved), &pos);
+    }
+    storage.remove(&last_key);
+
+    if last == 0 {
+        storage.remove(&DataKey::Len(index));
+    } else {
+        storage.set(&DataKey::Len(index), &last);
+    }
+}
+
+fn index_page(env: &Env, index: Index, start: u32, limit: u32) -> Vec<BytesN<32>> {
+    let end = start.saturating_add(limit.min(MAX_PAGE_SIZE)).min(index_len(env, &index));
+    let mut page = Vec::new(env);
+    for pos in start..end {
+        let id: BytesN<32> = env.storage().persistent()
+            .get(&DataKey::Entry(index.clone(), pos))
+            .unwrap();
+        page.push_back(id);
+    }
+    page
+}
+
 mod test;
\ No newline at end of file
@@ -2,8 +2,8 @@
 
 use super::*;
 use soroban_sdk::{
-    testutils::{Address as _, Ledger},
-    token, Address, Env, BytesN,
+    testutils::{Address as _, Events, Ledger},
+    token, vec, Address, Env, BytesN, TryFromVal,
 };
 
 #[test]
@@ -176,10 +176,9 @@ fn test_refund_funds_by_buyer_after_timeout_success() {
     let escrow_id = BytesN::from_array(&env, &[6u8; 32]);
     let amount: i128 = 1_200_000;
 
-    client.lock_funds(&escrow_id, &buyer, &seller, &token, &amount, &1_000_000, &BytesN::from_array(&env, &[0u8; 32]));
+    client.lock_funds(&escrow_id, &buyer, &seller, &token, &amount, &MIN_TIMEOUT_LEDGERS, &BytesN::from_array(&env, &[0u8; 32]));
 
-    let creation_time = env.ledger().timestamp();
-    env.ledger().set_timestamp(creation_time + 8 * 24 * 60 * 60);
+    env.ledger().set_sequence_number(MIN_TIMEOUT_LEDGERS);
 
     client.refund_funds(&escrow_id, &buyer);
 
@@ -189,7 +188,7 @@ fn test_refund_funds_by_buyer_after_timeout_success() {
 }
 
 #[test]
-fn test_refund_before_timeout_only_by_buyer_or_arbitrator() {
+fn test_buyer_cannot_refund_before_timeout() {
     let env = Env::default();
     let contract_id = env.register_contract(None, EscrowContract);
     let client = EscrowContractClient::new(&env, &contract_id);
@@ -211,10 +210,490 @@ fn test_refund_before_timeout_only_by_buyer_or_arbitrator() {
     client.lock_funds(&escrow_id, &buyer, &seller, &token, &amount, &1_000_000, &BytesN::from_array(&env, &[0u8; 32]));
 
     let result = client.try_refund_funds(&escrow_id, &random_caller);
-    assert!(result.is_err());
+    assert_eq!(result, Err(Ok(EscrowError::NotAuthorized.into())));
 
-    client.refund_funds(&escrow_id, &buyer);
+    let result = client.try_refund_funds(&escrow_id, &buyer);
+    assert_eq!(result, Err(Ok(EscrowError::TimeoutNotReached.into())));
 
     let stored = client.get_escrow(&escrow_id);
-    assert_eq!(stored.state, EscrowState::Refunded);
-}
\ No newline at end of file
+    assert_eq!(stored.state, EscrowState::Locked);
+    assert_eq!(token::Client::new(&env, &token).balance(&buyer), 0);
+}
+#[test]
+fn test_arbitrator_can_refund_before_timeout() {
+    let env = Env::default();
+    let contract_id = env.register_contract(None, EscrowContract);
+    let client = EscrowContractClient::new(&env, &contract_id);
+
+    env.mock_all_auths();
+
+    let buyer = Address::generate(&env);
+    let seller = Address::generate(&env);
+    let arbitrator = Address::generate(&env);
+    let admin = Address::generate(&env);
+    let sac_contract = env.register_stellar_asset_contract_v2(admin.clone());
+    let token = sac_contract.address();
+    let sac = token::StellarAssetClient::new(&env, &token);
+    sac.mint(&buyer, &900_000);
+
+    let escrow_id = BytesN::from_array(&env, &[15u8; 32]);
+    client.lock_funds_with_arbitrator(&escrow_id, &LockTerms {
+        buyer: buyer.clone(),
+        seller: seller.clone(),
+        token: token.clone(),
+        amount: 900_000,
+        timeout_ledger: 1_000_000,
+        memo: BytesN::from_array(&env, &[0u8; 32]),
+        arbitrator: Some(arbitrator.clone()),
+        fee_bps: None,
+    });
+
+    let result = client.try_refund_funds(&escrow_id, &buyer);
+    assert_eq!(result, Err(Ok(EscrowError::TimeoutNotReached.into())));
+
+    client.refund_funds(&escrow_id, &arbitrator);
+
+    assert_eq!(client.get_escrow(&escrow_id).state, EscrowState::Refunded);
+    assert_eq!(token::Client::new(&env, &token).balance(&buyer), 900_000);
+}
+
+#[test]
+fn test_escrow_listed_under_buyer_and_seller() {
+    let env = Env::default();
+    let contract_id = env.register_contract(None, EscrowContract);
+    let client = EscrowContractClient::new(&env, &contract_id);
+
+    env.mock_all_auths();
+
+    let buyer = Address::generate(&env);
+    let seller = Address::generate(&env);
+    let other_seller = Address::generate(&env);
+    let admin = Address::generate(&env);
+    let sac_contract = env.register_stellar_asset_contract_v2(admin.clone());
+    let token = sac_contract.address();
+    let sac = token::StellarAssetClient::new(&env, &token);
+    sac.mint(&buyer, &3_000);
+
+    let first = BytesN::from_array(&env, &[8u8; 32]);
+    let second = BytesN::from_array(&env, &[9u8; 32]);
+    let memo = BytesN::from_array(&env, &[0u8; 32]);
+
+    client.lock_funds(&first, &buyer, &seller, &token, &1_000, &1_000_000, &memo);
+    client.lock_funds(&second, &buyer, &other_seller, &token, &2_000, &MIN_TIMEOUT_LEDGERS, &memo);
+
+    assert_eq!(client.escrows_of_buyer(&buyer, &0, &10), vec![&env, first.clone(), second.clone()]);
+    assert_eq!(client.escrows_of_seller(&seller, &0, &10), vec![&env, first.clone()]);
+    assert_eq!(client.escrows_of_seller(&other_seller, &0, &10), vec![&env, second.clone()]);
+    assert_eq!(client.escrows_of_buyer(&seller, &0, &10).len(), 0);
+
+    // Finalized escrows drop out of both lists.
+    client.release_funds(&first, &seller);
+    env.ledger().set_sequence_number(MIN_TIMEOUT_LEDGERS);
+    client.refund_funds(&second, &buyer);
+
+    assert_eq!(client.escrows_of_buyer(&buyer, &0, &10).len(), 0);
+    assert_eq!(client.escrows_of_seller(&seller, &0, &10).len(), 0);
+    assert_eq!(client.escrows_of_seller(&other_seller, &0, &10).len(), 0);
+}
+
+#[test]
+fn test_escrow_listing_is_paginated() {
+    let env = Env::default();
+    let contract_id = env.register_contract(None, EscrowContract);
+    let client = EscrowContractClient::new(&env, &contract_id);
+
+    env.mock_all_auths();
+
+    let buyer = Address::generate(&env);
+    let seller = Address::generate(&env);
+    let admin = Address::generate(&env);
+    let sac_contract = env.register_stellar_asset_contract_v2(admin.clone());
+    let token = sac_contract.address();
+    let sac = token::StellarAssetClient::new(&env, &token);
+    sac.mint(&buyer, &1_000);
+
+    let memo = BytesN::from_array(&env, &[0u8; 32]);
+    let count = MAX_PAGE_SIZE + 5;
+    for i in 0..count {
+        let mut raw = [0u8; 32];
+        raw[..4].copy_from_slice(&i.to_be_bytes());
+        raw[31] = 0xee;
+        client.lock_funds(&BytesN::from_array(&env, &raw), &buyer, &seller, &token, &1, &1_000_000, &memo);
+    }
+
+    let page = client.escrows_of_seller(&seller, &2, &3);
+    assert_eq!(page.len(), 3);
+    assert_eq!(page.get(0).unwrap().to_array()[..4], 2u32.to_be_bytes());
+
+    // Oversized limits are capped, and reading past the end is empty.
+    assert_eq!(client.escrows_of_buyer(&buyer, &0, &u32::MAX).len(), MAX_PAGE_SIZE);
+    assert_eq!(client.escrows_of_buyer(&buyer, &MAX_PAGE_SIZE, &10).len(), 5);
+    assert_eq!(client.escrows_of_buyer(&buyer, &count, &10).len(), 0);
+    assert_eq!(client.escrows_of_buyer(&buyer, &u32::MAX, &u32::MAX).len(), 0);
+
+    // Finalizing one moves the newest into its slot; nothing is skipped.
+    let first = client.escrows_of_buyer(&buyer, &0, &1).get(0).unwrap();
+    let newest = client.escrows_of_buyer(&buyer, &(count - 1), &1).get(0).unwrap();
+    client.release_funds(&first, &seller);
+
+    assert_eq!(client.escrows_of_buyer(&buyer, &0, &1), vec![&env, newest.clone()]);
+    assert_eq!(client.escrows_of_seller(&seller, &0, &1), vec![&env, newest]);
+    assert_eq!(client.escrows_of_buyer(&buyer, &MAX_PAGE_SIZE, &10).len(), 4);
+}
+
+#[test]
+fn test_resolve_dispute_pays_arbitrator_fee() {
+    let env = Env::default();
+    let contract_id = env.register_contract(None, EscrowContract);
+    let client = EscrowContractClient::new(&env, &contract_id);
+
+    env.mock_all_auths();
+
+    let buyer = Address::generate(&env);
+    let seller = Address::generate(&env);
+    let arbitrator = Address::generate(&env);
+    let admin = Address::generate(&env);
+    let sac_contract = env.register_stellar_asset_contract_v2(admin.clone());
+    let token = sac_contract.address();
+    let sac = token::StellarAssetClient::new(&env, &token);
+    let balances = token::Client::new(&env, &token);
+    sac.mint(&buyer, &2_000_000);
+
+    let memo = BytesN::from_array(&env, &[0u8; 32]);
+    let to_seller = BytesN::from_array(&env, &[10u8; 32]);
+    let to_buyer = BytesN::from_array(&env, &[11u8; 32]);
+
+    // 2.5% fee
+    client.lock_funds_with_arbitrator(&to_seller, &LockTerms {
+        buyer: buyer.clone(),
+        seller: seller.clone(),
+        token: token.clone(),
+        amount: 1_000_000,
+        timeout_ledger: 1_000_000,
+        memo: memo.clone(),
+        arbitrator: Some(arbitrator.clone()),
+        fee_bps: Some(250),
+    });
+    client.raise_dispute(&to_seller, &buyer);
+    assert_eq!(client.get_escrow(&to_seller).state, EscrowState::Disputed);
+
+    client.resolve_dispute(&to_seller, &arbitrator, &true);
+    assert_eq!(balances.balance(&arbitrator), 25_000);
+    assert_eq!(balances.balance(&seller), 975_000);
+    assert_eq!(client.get_escrow(&to_seller).state, EscrowState::Released);
+
+    // Ruling for the buyer refunds the remainder to them.
+    client.lock_funds_with_arbitrator(&to_buyer, &LockTerms {
+        buyer: buyer.clone(),
+        seller: seller.clone(),
+        token: token.clone(),
+        amount: 1_000_000,
+        timeout_ledger: 1_000_000,
+        memo: memo.clone(),
+        arbitrator: Some(arbitrator.clone()),
+        fee_bps: Some(250),
+    });
+    client.raise_dispute(&to_buyer, &seller);
+    client.resolve_dispute(&to_buyer, &arbitrator, &false);
+    assert_eq!(balances.balance(&arbitrator), 50_000);
+    assert_eq!(balances.balance(&buyer), 975_000);
+    assert_eq!(balances.balance(&contract_id), 0);
+    assert_eq!(client.get_escrow(&to_buyer).state, EscrowState::Refunded);
+    assert_eq!(client.escrows_of_buyer(&buyer, &0, &10).len(), 0);
+}
+
+#[test]
+fn test_resolve_dispute_without_fee_pays_full_amount() {
+    let env = Env::default();
+    let contract_id = env.register_contract(None, EscrowContract);
+    let client = EscrowContractClient::new(&env, &contract_id);
+
+    env.mock_all_auths();
+
+    let buyer = Address::generate(&env);
+    let seller = Address::generate(&env);
+    let arbitrator = Address::generate(&env);
+    let outsider = Address::generate(&env);
+    let admin = Address::generate(&env);
+    let sac_contract = env.register_stellar_asset_contract_v2(admin.clone());
+    let token = sac_contract.address();
+    let sac = token::StellarAssetClient::new(&env, &token);
+    let balances = token::Client::new(&env, &token);
+    sac.mint(&buyer, &333);
+
+    let escrow_id = BytesN::from_array(&env, &[12u8; 32]);
+    client.lock_funds_with_arbitrator(&escrow_id, &LockTerms {
+        buyer: buyer.clone(),
+        seller: seller.clone(),
+        token: token.clone(),
+        amount: 333,
+        timeout_ledger: 1_000_000,
+        memo: BytesN::from_array(&env, &[0u8; 32]),
+        arbitrator: Some(arbitrator.clone()),
+        fee_bps: None,
+    });
+
+    // Not disputed yet, and only the arbitrator can rule.
+    assert!(client.try_resolve_dispute(&escrow_id, &arbitrator, &true).is_err());
+    assert!(client.try_raise_dispute(&escrow_id, &outsider).is_err());
+    client.raise_dispute(&escrow_id, &seller);
+    assert!(client.try_resolve_dispute(&escrow_id, &outsider, &true).is_err());
+
+    client.resolve_dispute(&escrow_id, &arbitrator, &true);
+    assert_eq!(balances.balance(&seller), 333);
+    assert_eq!(balances.balance(&arbitrator), 0);
+}
+
+#[test]
+fn test_lock_with_invalid_fee_terms_fails() {
+    let env = Env::default();
+    let contract_id = env.register_contract(None, EscrowContract);
+    let client = EscrowContractClient::new(&env, &contract_id);
+
+    env.mock_all_auths();
+
+    let buyer = Address::generate(&env);
+    let seller = Address::generate(&env);
+    let arbitrator = Address::generate(&env);
+    let admin = Address::generate(&env);
+    let sac_contract = env.register_stellar_asset_contract_v2(admin.clone());
+    let token = sac_contract.address();
+    let sac = token::StellarAssetClient::new(&env, &token);
+    sac.mint(&buyer, &1_000);
+
+    let escrow_id = BytesN::from_array(&env, &[13u8; 32]);
+    let memo = BytesN::from_array(&env, &[0u8; 32]);
+
+    let over_100_percent = client.try_lock_funds_with_arbitrator(&escrow_id, &LockTerms {
+        buyer: buyer.clone(),
+        seller: seller.clone(),
+        token: token.clone(),
+        amount: 1_000,
+        timeout_ledger: 1_000_000,
+        memo: memo.clone(),
+        arbitrator: Some(arbitrator.clone()),
+        fee_bps: Some(MAX_FEE_BPS + 1),
+    });
+    assert_eq!(over_100_percent, Err(Ok(EscrowError::InvalidFee.into())));
+
+    let fee_without_arbitrator = client.try_lock_funds_with_arbitrator(&escrow_id, &LockTerms {
+        buyer: buyer.clone(),
+        seller: seller.clone(),
+        token: token.clone(),
+        amount: 1_000,
+        timeout_ledger: 1_000_000,
+        memo: memo.clone(),
+        arbitrator: None,
+        fee_bps: Some(100),
+    });
+    assert_eq!(fee_without_arbitrator, Err(Ok(EscrowError::InvalidArbitrator.into())));
+
+    // A plain escrow has no one to take a dispute to.
+    client.lock_funds(&escrow_id, &buyer, &seller, &token, &1_000, &1_000_000, &memo);
+    assert_eq!(client.try_raise_dispute(&escrow_id, &buyer), Err(Ok(EscrowError::InvalidArbitrator.into())));
+}
+
+#[test]
+fn test_refund_gate_opens_exactly_at_timeout_ledger() {
+    let env = Env::default();
+    let contract_id = env.register_contract(None, EscrowContract);
+    let client = EscrowContractClient::new(&env, &contract_id);
+
+    env.mock_all_auths();
+
+    let buyer = Address::generate(&env);
+    let seller = Address::generate(&env);
+    let keeper = Address::generate(&env);
+    let admin = Address::generate(&env);
+    let sac_contract = env.register_stellar_asset_contract_v2(admin.clone());
+    let token = sac_contract.address();
+    let sac = token::StellarAssetClient::new(&env, &token);
+    sac.mint(&buyer, &500);
+
+    env.ledger().set_sequence_number(100);
+    let timeout_ledger = 100 + MIN_TIMEOUT_LEDGERS + 80;
+    let escrow_id = BytesN::from_array(&env, &[14u8; 32]);
+
+    client.lock_funds(&escrow_id, &buyer, &seller, &token, &500, &timeout_ledger, &BytesN::from_array(&env, &[0u8; 32]));
+    assert_eq!(client.get_escrow(&escrow_id).timeout_ledger, timeout_ledger);
+
+    // One ledger early, only the arbitrator may refund.
+    env.ledger().set_sequence_number(timeout_ledger - 1);
+    let result = client.try_refund_funds(&escrow_id, &keeper);
+    assert_eq!(result, Err(Ok(EscrowError::NotAuthorized.into())));
+    let result = client.try_refund_funds(&escrow_id, &buyer);
+    assert_eq!(result, Err(Ok(EscrowError::TimeoutNotReached.into())));
+
+    env.ledger().set_sequence_number(timeout_ledger);
+    client.refund_funds(&escrow_id, &keeper);
+
+    assert_eq!(client.get_escrow(&escrow_id).state, EscrowState::Refunded);
+    assert_eq!(token::Client::new(&env, &token).balance(&buyer), 500);
+}
+
+#[test]
+fn test_lock_funds_timeout_out_of_bounds_fails() {
+    let env = Env::default();
+    let contract_id = env.register_contract(None, EscrowContract);
+    let client = EscrowContractClient::new(&env, &contract_id);
+
+    env.mock_all_auths();
+
+    let buyer = Address::generate(&env);
+    let seller = Address::generate(&env);
+    let admin = Address::generate(&env);
+    let sac_contract = env.register_stellar_asset_contract_v2(admin.clone());
+    let token = sac_contract.address();
+    let sac = token::StellarAssetClient::new(&env, &token);
+    sac.mint(&buyer, &500);
+
+    env.ledger().set_sequence_number(1_000);
+    let escrow_id = BytesN::from_array(&env, &[15u8; 32]);
+    let memo = BytesN::from_array(&env, &[0u8; 32]);
+
+    for timeout_ledger in [
+        999,
+        1_000 + MIN_TIMEOUT_LEDGERS - 1,
+        1_000 + MAX_TIMEOUT_LEDGERS + 1,
+    ] {
+        let result = client.try_lock_funds(&escrow_id, &buyer, &seller, &token, &500, &timeout_ledger, &memo);
+        assert_eq!(result, Err(Ok(EscrowError::InvalidTimeout.into())));
+    }
+
+    client.lock_funds(&escrow_id, &buyer, &seller, &token, &500, &(1_000 + MAX_TIMEOUT_LEDGERS), &memo);
+}
+
+/// Actions of this contract's events whose topics name `participant` as
+/// buyer or seller, the way an indexer filtering on topics would see them.
+fn actions_involving(env: &Env, contract_id: &Address, participant: &Address) -> Vec<Symbol> {
+    let mut actions = Vec::new(env);
+    for (contract, topics, _) in env.events().all().iter() {
+        if contract != *contract_id {
+            continue;
+        }
+        let buyer = Address::try_from_val(env, &topics.get(2).unwrap()).unwrap();
+        let seller = Address::try_from_val(env, &topics.get(3).unwrap()).unwrap();
+        if buyer == *participant || seller == *participant {
+            actions.push_back(Symbol::try_from_val(env, &topics.get(1).unwrap()).unwrap());
+        }
+    }
+    actions
+}
+
+#[test]
+fn test_events_carry_participants_in_topics() {
+    let env = Env::default();
+    let contract_id = env.register_contract(None, EscrowContract);
+    let client = EscrowContractClient::new(&env, &contract_id);
+
+    env.mock_all_auths();
+
+    let buyer = Address::generate(&env);
+    let seller = Address::generate(&env);
+    let admin = Address::generate(&env);
+    let sac_contract = env.register_stellar_asset_contract_v2(admin.clone());
+    let token = sac_contract.address();
+    let sac = token::StellarAssetClient::new(&env, &token);
+    sac.mint(&buyer, &1_000);
+
+    let escrow_id = BytesN::from_array(&env, &[40u8; 32]);
+    client.lock_funds(&escrow_id, &buyer, &seller, &token, &1_000, &1_000_000, &BytesN::from_array(&env, &[0u8; 32]));
+
+    let (contract, topics, data) = env.events().all().last().unwrap();
+    assert_eq!(contract, contract_id);
+    assert_eq!(
+        topics,
+        (symbol_short!("escrow"), symbol_short!("locked"), buyer.clone(), seller.clone()).into_val(&env)
+    );
+    let (id, amount): (BytesN<32>, i128) = TryFromVal::try_from_val(&env, &data).unwrap();
+    assert_eq!(id, escrow_id);
+    assert_eq!(amount, 1_000);
+}
+
+#[test]
+fn test_events_can_be_filtered_by_participant() {
+    let env = Env::default();
+    let contract_id = env.register_contract(None, EscrowContract);
+    let client = EscrowContractClient::new(&env, &contract_id);
+
+    env.mock_all_auths();
+
+    let buyer = Address::generate(&env);
+    let seller = Address::generate(&env);
+    let other_buyer = Address::generate(&env);
+    let other_seller = Address::generate(&env);
+    let admin = Address::generate(&env);
+    let sac_contract = env.register_stellar_asset_contract_v2(admin.clone());
+    let token = sac_contract.address();
+    let sac = token::StellarAssetClient::new(&env, &token);
+    sac.mint(&buyer, &1_000);
+    sac.mint(&other_buyer, &1_000);
+
+    let memo = BytesN::from_array(&env, &[0u8; 32]);
+    let ours = BytesN::from_array(&env, &[41u8; 32]);
+    let theirs = BytesN::from_array(&env, &[42u8; 32]);
+    client.lock_funds(&ours, &buyer, &seller, &token, &1_000, &1_000_000, &memo);
+    client.lock_funds(&theirs, &other_buyer, &other_seller, &token, &1_000, &1_000_000, &memo);
+    client.release_funds(&ours, &seller);
+
+    let expected = vec![&env, symbol_short!("locked"), symbol_short!("released")];
+    assert_eq!(actions_involving(&env, &contract_id, &buyer), expected);
+    assert_eq!(actions_involving(&env, &contract_id, &seller), expected);
+    assert_eq!(
+        actions_involving(&env, &contract_id, &other_seller),
+        vec![&env, symbol_short!("locked")]
+    );
+}
+
+#[test]
+fn test_escrows_stored_before_timeout_ledgers_still_decode() {
+    let env = Env::default();
+    let contract_id = env.register_contract(None, EscrowContract);
+    let client = EscrowContractClient::new(&env, &contract_id);
+
+    env.mock_all_auths();
+
+    let buyer = Address::generate(&env);
+    let seller = Address::generate(&env);
+    let outsider = Address::generate(&env);
+    let admin = Address::generate(&env);
+    let sac_contract = env.register_stellar_asset_contract_v2(admin.clone());
+    let token = sac_contract.address();
+    let sac = token::StellarAssetClient::new(&env, &token);
+    let balances = token::Client::new(&env, &token);
+    sac.mint(&contract_id, &1_000);
+
+    env.ledger().set_sequence_number(100);
+    env.ledger().set_timestamp(1_000_000);
+
+    // An hour left of the old seven-day refund window.
+    let escrow_id = BytesN::from_array(&env, &[51u8; 32]);
+    let legacy = EscrowV1 {
+        buyer: buyer.clone(),
+        seller: seller.clone(),
+        arbitrator: None,
+        token: token.clone(),
+        amount: 1_000,
+        state: EscrowState::Locked,
+        memo: BytesN::from_array(&env, &[0u8; 32]),
+        created_at: 1_000_000 - LEGACY_TIMEOUT_SECS + 60 * 60,
+    };
+    env.as_contract(&contract_id, || {
+        env.storage().persistent().set(&escrow_key(&escrow_id), &legacy);
+    });
+
+    let stored = client.get_escrow(&escrow_id);
+    assert_eq!(stored.buyer, buyer);
+    assert_eq!(stored.amount, 1_000);
+    assert_eq!(stored.fee_bps, None);
+    assert_eq!(stored.timeout_ledger, 100 + 720);
+    assert!(client.is_locked(&escrow_id));
+
+    assert!(client.try_refund_funds(&escrow_id, &outsider).is_err());
+    // The clock and the ledger sequence advance together on the network.
+    env.ledger().set_sequence_number(stored.timeout_ledger);
+    env.ledger().set_timestamp(1_000_000 + 60 * 60);
+    client.refund_funds(&escrow_id, &outsider);
+    assert_eq!(balances.balance(&buyer), 1_000);
+    assert_eq!(client.get_escrow(&escrow_id).state, EscrowState::Refunded);
+}
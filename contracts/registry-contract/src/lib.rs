@@ -7,7 +7,7 @@ mod storage;
 use soroban_sdk::{contract, contractimpl, Address, Env, String, Vec};
 
 pub use error::RegistryError;
-pub use storage::{ContractEntry, DataKey};
+pub use storage::{ContractEntry, DataKey, MAX_HISTORY};
 
 #[contract]
 pub struct Registry;
@@ -58,6 +58,7 @@ impl Registry {
         env.storage()
             .instance()
             .set(&DataKey::Registry(name.clone()), &address);
+        Self::record_history(&env, &name, &address);
 
         events::emit_registered(&env, &name, &address);
         Ok(())
@@ -67,6 +68,15 @@ impl Registry {
         env.storage().instance().get(&DataKey::Registry(name))
     }
 
+    /// Every address registered under `name`, oldest first and ending with
+    /// the current one. Only the last `MAX_HISTORY` are kept.
+    pub fn get_contract_history(env: Env, name: String) -> Vec<Address> {
+        env.storage()
+            .instance()
+            .get(&DataKey::RegistryHistory(name))
+            .unwrap_or_else(|| Vec::new(&env))
+    }
+
     pub fn list_contracts(env: Env) -> Result<Vec<ContractEntry>, RegistryError> {
         let names: Vec<String> = env
             .storage()
@@ -98,4 +108,21 @@ impl Registry {
     }
 }
 
+impl Registry {
+    fn record_history(env: &Env, name: &String, address: &Address) {
+        let key = DataKey::RegistryHistory(name.clone());
+        let mut history: Vec<Address> = env
+            .storage()
+            .instance()
+            .get(&key)
+            .unwrap_or_else(|| Vec::new(env));
+
+        history.push_back(address.clone());
+        while history.len() > MAX_HISTORY {
+            history.pop_front();
+        }
+        env.storage().instance().set(&key, &history);
+    }
+}
+
 mod test;
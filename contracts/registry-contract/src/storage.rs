@@ -6,8 +6,12 @@ pub enum DataKey {
     Admin,
     Registry(String),
     ContractNames,
+    RegistryHistory(String),
 }
 
+/// Most addresses kept in a name's history; the oldest is dropped first.
+pub const MAX_HISTORY: u32 = 10;
+
 #[contracttype]
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub struct ContractEntry {
@@ -1,7 +1,7 @@
 #![cfg(test)]
 extern crate std;
 
-use crate::{ContractEntry, Registry, RegistryClient, RegistryError};
+use crate::{ContractEntry, Registry, RegistryClient, RegistryError, MAX_HISTORY};
 use soroban_sdk::{testutils::Address as _, Address, Env, String};
 
 #[test]
@@ -224,3 +224,58 @@ fn test_admin_auth_required() {
     assert_eq!(*auth_addr, admin);
     assert_ne!(*auth_addr, non_admin);
 }
+
+#[test]
+fn test_update_keeps_history() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let contract_id = env.register_contract(None, Registry);
+    let client = RegistryClient::new(&env, &contract_id);
+    let admin = Address::generate(&env);
+    let first = Address::generate(&env);
+    let second = Address::generate(&env);
+    let third = Address::generate(&env);
+
+    client.initialize(&admin);
+
+    let name = String::from_str(&env, "token");
+    client.register_contract(&name, &first);
+    client.register_contract(&name, &second);
+    client.register_contract(&name, &third);
+
+    let history = client.get_contract_history(&name);
+    assert_eq!(history.len(), 3);
+    assert_eq!(history.get(0).unwrap(), first);
+    assert_eq!(history.get(1).unwrap(), second);
+    assert_eq!(history.get(2).unwrap(), third);
+    assert_eq!(client.get_contract(&name), Some(third));
+
+    let other = String::from_str(&env, "vault");
+    assert_eq!(client.get_contract_history(&other).len(), 0);
+}
+
+#[test]
+fn test_history_is_bounded() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let contract_id = env.register_contract(None, Registry);
+    let client = RegistryClient::new(&env, &contract_id);
+    let admin = Address::generate(&env);
+
+    client.initialize(&admin);
+
+    let name = String::from_str(&env, "token");
+    let first = Address::generate(&env);
+    client.register_contract(&name, &first);
+
+    let mut latest = first.clone();
+    for _ in 0..MAX_HISTORY {
+        latest = Address::generate(&env);
+        client.register_contract(&name, &latest);
+    }
+
+    let history = client.get_contract_history(&name);
+    assert_eq!(history.len(), MAX_HISTORY);
+    assert!(!history.contains(&first));
+    assert_eq!(history.last().unwrap(), latest);
+}
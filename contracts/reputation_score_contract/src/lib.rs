@@ -1,11 +1,32 @@
 #![no_std]
-use soroban_sdk::{contract, contractimpl, contracttype, log, Address, Env};
+use soroban_sdk::{
+    contract, contractimpl, contracttype, log, symbol_short, vec, Address, Env, Vec,
+};
 
 #[contracttype]
 #[derive(Clone)]
 enum DataKey {
-    Admin,
+    Admins,
     Score(Address),
+    Tiers,
+}
+
+/// Default minimum scores for the Bronze, Silver and Gold tiers.
+const DEFAULT_TIERS: [u32; 3] = [100, 500, 1000];
+
+fn admins(env: &Env) -> Vec<Address> {
+    env.storage()
+        .instance()
+        .get(&DataKey::Admins)
+        .expect("Not initialized")
+}
+
+/// Require `caller` to have signed and to be a member of the admin set.
+fn require_admin(env: &Env, caller: &Address) {
+    caller.require_auth();
+    if !admins(env).contains(caller) {
+        panic!("Unauthorized");
+    }
 }
 
 #[contract]
@@ -15,20 +36,88 @@ pub struct ReputationScoreContract;
 impl ReputationScoreContract {
     /// Initialize the contract with an admin address.
     pub fn initialize(env: Env, admin: Address) {
-        if env.storage().instance().has(&DataKey::Admin) {
+        if env.storage().instance().has(&DataKey::Admins) {
             panic!("Already initialized");
         }
-        env.storage().instance().set(&DataKey::Admin, &admin);
+        env.storage()
+            .instance()
+            .set(&DataKey::Admins, &vec![&env, admin]);
+        env.storage().instance().set(
+            &DataKey::Tiers,
+            &vec![&env, DEFAULT_TIERS[0], DEFAULT_TIERS[1], DEFAULT_TIERS[2]],
+        );
     }
 
-    /// Increase the reputation score of a user. Only Callable by Admin.
-    pub fn increase_score(env: Env, user: Address, value: u32) {
-        let admin: Address = env
-            .storage()
+    /// Add an address to the admin set. Only Callable by Admin.
+    pub fn add_admin(env: Env, caller: Address, who: Address) {
+        require_admin(&env, &caller);
+
+        let mut admins = admins(&env);
+        if admins.contains(&who) {
+            panic!("Already an admin");
+        }
+        admins.push_back(who);
+        env.storage().instance().set(&DataKey::Admins, &admins);
+    }
+
+    /// Remove an address from the admin set. Only Callable by Admin.
+    /// The last remaining admin cannot be removed.
+    pub fn remove_admin(env: Env, caller: Address, who: Address) {
+        require_admin(&env, &caller);
+
+        let mut admins = admins(&env);
+        let index = admins.first_index_of(&who).expect("Not an admin");
+        if admins.len() == 1 {
+            panic!("Cannot remove the last admin");
+        }
+        admins.remove(index);
+        env.storage().instance().set(&DataKey::Admins, &admins);
+    }
+
+    /// Get the current admin set.
+    pub fn get_admins(env: Env) -> Vec<Address> {
+        admins(&env)
+    }
+
+    /// Replace the tier thresholds. Only Callable by Admin.
+    /// Thresholds are minimum scores and must be strictly ascending, so the
+    /// first entry is tier 1 (Bronze), the second tier 2 (Silver), and so on.
+    pub fn set_tiers(env: Env, caller: Address, thresholds: Vec<u32>) {
+        require_admin(&env, &caller);
+
+        for i in 1..thresholds.len() {
+            if thresholds.get_unchecked(i) <= thresholds.get_unchecked(i - 1) {
+                panic!("Tier thresholds must be strictly ascending");
+            }
+        }
+
+        env.storage().instance().set(&DataKey::Tiers, &thresholds);
+    }
+
+    /// Get the configured tier thresholds.
+    pub fn get_tiers(env: Env) -> Vec<u32> {
+        env.storage()
             .instance()
-            .get(&DataKey::Admin)
-            .expect("Not initialized");
-        admin.require_auth();
+            .get(&DataKey::Tiers)
+            .expect("Not initialized")
+    }
+
+    /// Get the tier index for a user's current score.
+    /// Returns 0 when the score is below the first threshold.
+    pub fn get_tier(env: Env, user: Address) -> u32 {
+        let score = Self::get_score(env.clone(), user);
+        let thresholds = Self::get_tiers(env);
+        thresholds.iter().take_while(|t| score >= *t).count() as u32
+    }
+
+    /// Check whether a user's score is at least `min_score`.
+    pub fn meets_threshold(env: Env, user: Address, min_score: u32) -> bool {
+        Self::get_score(env, user) >= min_score
+    }
+
+    /// Increase the reputation score of a user. Only Callable by Admin.
+    pub fn increase_score(env: Env, caller: Address, user: Address, value: u32) {
+        require_admin(&env, &caller);
 
         let key = DataKey::Score(user.clone());
         let current_score: u32 = env.storage().persistent().get(&key).unwrap_or(0);
@@ -36,6 +125,10 @@ impl ReputationScoreContract {
         let new_score = current_score.checked_add(value).expect("Score overflow");
 
         env.storage().persistent().set(&key, &new_score);
+        env.events().publish(
+            (symbol_short!("rep"), symbol_short!("inc"), user.clone()),
+            (current_score, new_score),
+        );
         log!(
             &env,
             "Score increased for {}: new score {}",
@@ -46,13 +139,8 @@ impl ReputationScoreContract {
 
     /// Decrease the reputation score of a user. Only Callable by Admin.
     /// Prevents underflow by capping the minimum score at 0.
-    pub fn decrease_score(env: Env, user: Address, value: u32) {
-        let admin: Address = env
-            .storage()
-            .instance()
-            .get(&DataKey::Admin)
-            .expect("Not initialized");
-        admin.require_auth();
+    pub fn decrease_score(env: Env, caller: Address, user: Address, value: u32) {
+        require_admin(&env, &caller);
 
         let key = DataKey::Score(user.clone());
         let current_score: u32 = env.storage().persistent().get(&key).unwrap_or(0);
@@ -60,6 +148,10 @@ impl ReputationScoreContract {
         let new_score = current_score.saturating_sub(value);
 
         env.storage().persistent().set(&key, &new_score);
+        env.events().publish(
+            (symbol_short!("rep"), symbol_short!("dec"), user.clone()),
+            (current_score, new_score),
+        );
         log!(
             &env,
             "Score decreased for {}: new score {}",
@@ -1,8 +1,8 @@
 #![cfg(test)]
 
 use super::*;
-use soroban_sdk::testutils::Address as _;
-use soroban_sdk::{vec, Env, IntoVal};
+use soroban_sdk::testutils::{Address as _, Events};
+use soroban_sdk::{symbol_short, vec, Env, FromVal, IntoVal, Symbol};
 
 #[test]
 fn test_initialize() {
@@ -31,10 +31,10 @@ fn test_increase_score() {
 
     client.initialize(&admin);
 
-    client.increase_score(&user, &10);
+    client.increase_score(&admin, &user, &10);
     assert_eq!(client.get_score(&user), 10);
 
-    client.increase_score(&user, &5);
+    client.increase_score(&admin, &user, &5);
     assert_eq!(client.get_score(&user), 15);
 }
 
@@ -51,14 +51,14 @@ fn test_decrease_score() {
 
     client.initialize(&admin);
 
-    client.increase_score(&user, &20);
+    client.increase_score(&admin, &user, &20);
     assert_eq!(client.get_score(&user), 20);
 
-    client.decrease_score(&user, &5);
+    client.decrease_score(&admin, &user, &5);
     assert_eq!(client.get_score(&user), 15);
 
     // Test underflow prevention
-    client.decrease_score(&user, &20);
+    client.decrease_score(&admin, &user, &20);
     assert_eq!(client.get_score(&user), 0);
 }
 
@@ -80,7 +80,7 @@ fn test_auth_enforcement_increase() {
     // This should fail because attacker is trying to call it
     // In a real test we'd need to mock the auth for the admin,
     // but here we just want to see it fail when no auth is provided or wrong one is used.
-    // client.increase_score(&user, &10);
+    // client.increase_score(&admin, &user, &10);
 
     // Setting up the specific auth for admin
     env.mock_auths(&[soroban_sdk::testutils::MockAuth {
@@ -88,12 +88,17 @@ fn test_auth_enforcement_increase() {
         invoke: &soroban_sdk::testutils::MockAuthInvoke {
             contract: &contract_id,
             fn_name: "increase_score",
-            args: vec![&env, user.into_val(&env), 10u32.into_val(&env)],
+            args: vec![
+                &env,
+                admin.into_val(&env),
+                user.into_val(&env),
+                10u32.into_val(&env),
+            ],
             sub_invokes: &[],
         },
     }]);
 
-    client.increase_score(&user, &10);
+    client.increase_score(&admin, &user, &10);
 }
 
 #[test]
@@ -120,13 +125,13 @@ fn test_multiple_users() {
 
     client.initialize(&admin);
 
-    client.increase_score(&user1, &10);
-    client.increase_score(&user2, &20);
+    client.increase_score(&admin, &user1, &10);
+    client.increase_score(&admin, &user2, &20);
 
     assert_eq!(client.get_score(&user1), 10);
     assert_eq!(client.get_score(&user2), 20);
 
-    client.decrease_score(&user1, &5);
+    client.decrease_score(&admin, &user1, &5);
     assert_eq!(client.get_score(&user1), 5);
     assert_eq!(client.get_score(&user2), 20);
 }
@@ -145,8 +150,8 @@ fn test_score_overflow() {
 
     client.initialize(&admin);
 
-    client.increase_score(&user, &u32::MAX);
-    client.increase_score(&user, &1); // Should panic
+    client.increase_score(&admin, &user, &u32::MAX);
+    client.increase_score(&admin, &user, &1); // Should panic
 }
 
 #[test]
@@ -168,10 +173,225 @@ fn test_auth_enforcement_decrease() {
         invoke: &soroban_sdk::testutils::MockAuthInvoke {
             contract: &contract_id,
             fn_name: "decrease_score",
-            args: vec![&env, user.into_val(&env), 10u32.into_val(&env)],
+            args: vec![
+                &env,
+                admin.into_val(&env),
+                user.into_val(&env),
+                10u32.into_val(&env),
+            ],
             sub_invokes: &[],
         },
     }]);
 
-    client.decrease_score(&user, &10);
+    client.decrease_score(&admin, &user, &10);
+}
+
+#[test]
+fn test_get_tier_defaults() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let contract_id = env.register_contract(None, ReputationScoreContract);
+    let client = ReputationScoreContractClient::new(&env, &contract_id);
+
+    let admin = Address::generate(&env);
+    client.initialize(&admin);
+
+    assert_eq!(client.get_tiers(), vec![&env, 100u32, 500, 1000]);
+
+    let cases: [(u32, u32); 7] = [
+        (0, 0),
+        (99, 0),
+        (100, 1),
+        (499, 1),
+        (500, 2),
+        (999, 2),
+        (1000, 3),
+    ];
+    for (score, tier) in cases {
+        let user = Address::generate(&env);
+        if score > 0 {
+            client.increase_score(&admin, &user, &score);
+        }
+        assert_eq!(client.get_tier(&user), tier);
+    }
+}
+
+#[test]
+fn test_set_tiers() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let contract_id = env.register_contract(None, ReputationScoreContract);
+    let client = ReputationScoreContractClient::new(&env, &contract_id);
+
+    let admin = Address::generate(&env);
+    let user = Address::generate(&env);
+    client.initialize(&admin);
+
+    client.set_tiers(&admin, &vec![&env, 10u32, 20]);
+    assert_eq!(client.get_tier(&user), 0);
+
+    client.increase_score(&admin, &user, &10);
+    assert_eq!(client.get_tier(&user), 1);
+
+    client.increase_score(&admin, &user, &10);
+    assert_eq!(client.get_tier(&user), 2);
+
+    client.increase_score(&admin, &user, &1000);
+    assert_eq!(client.get_tier(&user), 2);
+}
+
+#[test]
+#[should_panic(expected = "Tier thresholds must be strictly ascending")]
+fn test_set_tiers_rejects_unordered() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let contract_id = env.register_contract(None, ReputationScoreContract);
+    let client = ReputationScoreContractClient::new(&env, &contract_id);
+
+    let admin = Address::generate(&env);
+    client.initialize(&admin);
+
+    client.set_tiers(&admin, &vec![&env, 100u32, 100, 200]);
+}
+
+#[test]
+fn test_meets_threshold() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let contract_id = env.register_contract(None, ReputationScoreContract);
+    let client = ReputationScoreContractClient::new(&env, &contract_id);
+
+    let admin = Address::generate(&env);
+    let user = Address::generate(&env);
+    client.initialize(&admin);
+
+    assert!(client.meets_threshold(&user, &0));
+    assert!(!client.meets_threshold(&user, &1));
+
+    client.increase_score(&admin, &user, &50);
+    assert!(client.meets_threshold(&user, &49));
+    assert!(client.meets_threshold(&user, &50));
+    assert!(!client.meets_threshold(&user, &51));
+}
+
+fn last_score_event(env: &Env) -> (Symbol, Symbol, Address, (u32, u32)) {
+    let event = env.events().all().last().unwrap();
+    let topics = event.1;
+    (
+        Symbol::from_val(env, &topics.get(0).unwrap()),
+        Symbol::from_val(env, &topics.get(1).unwrap()),
+        Address::from_val(env, &topics.get(2).unwrap()),
+        FromVal::from_val(env, &event.2),
+    )
+}
+
+#[test]
+fn test_score_change_events() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let contract_id = env.register_contract(None, ReputationScoreContract);
+    let client = ReputationScoreContractClient::new(&env, &contract_id);
+
+    let admin = Address::generate(&env);
+    let user = Address::generate(&env);
+    client.initialize(&admin);
+
+    client.increase_score(&admin, &user, &30);
+    assert_eq!(
+        last_score_event(&env),
+        (symbol_short!("rep"), symbol_short!("inc"), user.clone(), (0, 30))
+    );
+
+    // Decreases are capped at zero and the payload reflects the clamped score
+    client.decrease_score(&admin, &user, &50);
+    assert_eq!(
+        last_score_event(&env),
+        (symbol_short!("rep"), symbol_short!("dec"), user.clone(), (30, 0))
+    );
+}
+
+#[test]
+fn test_multiple_admins_can_score() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let contract_id = env.register_contract(None, ReputationScoreContract);
+    let client = ReputationScoreContractClient::new(&env, &contract_id);
+
+    let admin = Address::generate(&env);
+    let second = Address::generate(&env);
+    let user = Address::generate(&env);
+    client.initialize(&admin);
+
+    client.add_admin(&admin, &second);
+    assert_eq!(client.get_admins(), vec![&env, admin.clone(), second.clone()]);
+
+    client.increase_score(&admin, &user, &10);
+    client.increase_score(&second, &user, &5);
+    assert_eq!(client.get_score(&user), 15);
+
+    client.decrease_score(&second, &user, &3);
+    assert_eq!(client.get_score(&user), 12);
+}
+
+#[test]
+fn test_remove_admin_revokes_access() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let contract_id = env.register_contract(None, ReputationScoreContract);
+    let client = ReputationScoreContractClient::new(&env, &contract_id);
+
+    let admin = Address::generate(&env);
+    let second = Address::generate(&env);
+    let user = Address::generate(&env);
+    client.initialize(&admin);
+    client.add_admin(&admin, &second);
+
+    client.increase_score(&second, &user, &10);
+
+    client.remove_admin(&admin, &second);
+    assert_eq!(client.get_admins(), vec![&env, admin.clone()]);
+
+    let result = client.try_increase_score(&second, &user, &10);
+    assert!(result.is_err());
+    let result = client.try_add_admin(&second, &second);
+    assert!(result.is_err());
+    assert_eq!(client.get_score(&user), 10);
+}
+
+#[test]
+#[should_panic(expected = "Cannot remove the last admin")]
+fn test_cannot_remove_last_admin() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let contract_id = env.register_contract(None, ReputationScoreContract);
+    let client = ReputationScoreContractClient::new(&env, &contract_id);
+
+    let admin = Address::generate(&env);
+    client.initialize(&admin);
+
+    client.remove_admin(&admin, &admin);
+}
+
+#[test]
+#[should_panic(expected = "Unauthorized")]
+fn test_non_admin_cannot_add_admin() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let contract_id = env.register_contract(None, ReputationScoreContract);
+    let client = ReputationScoreContractClient::new(&env, &contract_id);
+
+    let admin = Address::generate(&env);
+    let outsider = Address::generate(&env);
+    client.initialize(&admin);
+
+    client.add_admin(&outsider, &outsider);
 }
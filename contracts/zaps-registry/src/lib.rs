@@ -1,7 +1,7 @@
 #![no_std]
 
 use soroban_sdk::{
-    contract, contracterror, contractimpl, contracttype, symbol_short, Address, Bytes, Env,
+    contract, contracterror, contractimpl, contracttype, symbol_short, Address, Bytes, Env, Vec,
 };
 
 #[contracterror]
@@ -15,8 +15,17 @@ pub enum Error {
     MerchantNotFound = 5,
     InactiveMerchant = 6,
     UserNotFound = 7,
+    InvalidId = 8,
+    LengthMismatch = 9,
+    BatchTooLarge = 10,
 }
 
+/// Maximum length in bytes of a user or merchant handle
+pub const MAX_ID_LEN: u32 = 32;
+
+/// Maximum number of handles resolved in a single batch call
+pub const MAX_RESOLVE_BATCH: u32 = 50;
+
 #[contracttype]
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub struct MerchantMetadata {
@@ -33,6 +42,20 @@ pub enum DataKey {
     Merchant(Bytes),
 }
 
+/// Ensure a handle is non-empty, at most `MAX_ID_LEN` bytes and only uses
+/// lowercase alphanumerics, `_` or `-`
+fn validate_id(id: &Bytes) -> Result<(), Error> {
+    if id.is_empty() || id.len() > MAX_ID_LEN {
+        return Err(Error::InvalidId);
+    }
+    for b in id.iter() {
+        if !(b.is_ascii_lowercase() || b.is_ascii_digit() || b == b'_' || b == b'-') {
+            return Err(Error::InvalidId);
+        }
+    }
+    Ok(())
+}
+
 #[contract]
 pub struct BLINKSRegistry;
 
@@ -48,9 +71,11 @@ impl BLINKSRegistry {
     }
 
     /// Register a human-readable ID for a user address
+    /// IDs must be 1-32 bytes of lowercase alphanumerics, `_` or `-`
     /// Authentication: Required for the user address being registered
     pub fn register_user(env: Env, user_id: Bytes, wallet: Address) -> Result<(), Error> {
         wallet.require_auth();
+        validate_id(&user_id)?;
 
         let key = DataKey::User(user_id.clone());
         if env.storage().persistent().has(&key) {
@@ -82,6 +107,7 @@ impl BLINKSRegistry {
             .get(&DataKey::Admin)
             .ok_or(Error::NotInitialized)?;
         admin.require_auth();
+        validate_id(&merchant_id)?;
 
         let key = DataKey::Merchant(merchant_id.clone());
         if env.storage().persistent().has(&key) {
@@ -105,6 +131,64 @@ impl BLINKSRegistry {
         Ok(())
     }
 
+    /// Register several merchants in a single call
+    /// The whole batch is rejected if the vectors differ in length or any id
+    /// is invalid, already registered, or repeated within the batch
+    /// Access Control: Admin only
+    pub fn register_merchants(
+        env: Env,
+        ids: Vec<Bytes>,
+        vaults: Vec<Address>,
+        assets: Vec<Address>,
+    ) -> Result<(), Error> {
+        let admin: Address = env
+            .storage()
+            .instance()
+            .get(&DataKey::Admin)
+            .ok_or(Error::NotInitialized)?;
+        admin.require_auth();
+
+        if ids.len() != vaults.len() || ids.len() != assets.len() {
+            return Err(Error::LengthMismatch);
+        }
+
+        // Validate everything up front so a bad entry leaves no partial writes
+        for (i, merchant_id) in ids.iter().enumerate() {
+            validate_id(&merchant_id)?;
+            if env
+                .storage()
+                .persistent()
+                .has(&DataKey::Merchant(merchant_id.clone()))
+            {
+                return Err(Error::DuplicateId);
+            }
+            if ids.iter().take(i).any(|prev| prev == merchant_id) {
+                return Err(Error::DuplicateId);
+            }
+        }
+
+        for i in 0..ids.len() {
+            let merchant_id = ids.get_unchecked(i);
+            let key = DataKey::Merchant(merchant_id.clone());
+            let metadata = MerchantMetadata {
+                settlement_asset: assets.get_unchecked(i),
+                vault: vaults.get_unchecked(i),
+                active: true,
+            };
+            env.storage().persistent().set(&key, &metadata);
+            env.storage().persistent().extend_ttl(&key, 518400, 518400);
+
+            // Same event as register_merchant so indexers see every merchant
+            env.events()
+                .publish((symbol_short!("merch_reg"), merchant_id), metadata);
+        }
+
+        env.events()
+            .publish((symbol_short!("merch_bat"),), ids.len());
+
+        Ok(())
+    }
+
     /// Resolve a user ID to their wallet address
     pub fn resolve_user(env: Env, user_id: Bytes) -> Result<Address, Error> {
         let key = DataKey::User(user_id);
@@ -130,6 +214,42 @@ impl BLINKSRegistry {
         Ok(metadata)
     }
 
+    /// Resolve several user IDs at once
+    /// Each position holds the wallet address, or `None` if the ID is unknown
+    pub fn resolve_users(env: Env, ids: Vec<Bytes>) -> Result<Vec<Option<Address>>, Error> {
+        if ids.len() > MAX_RESOLVE_BATCH {
+            return Err(Error::BatchTooLarge);
+        }
+
+        let mut resolved = Vec::new(&env);
+        for user_id in ids.iter() {
+            resolved.push_back(env.storage().persistent().get(&DataKey::User(user_id)));
+        }
+        Ok(resolved)
+    }
+
+    /// Resolve several merchant IDs at once
+    /// Each position holds the metadata, or `None` if the merchant is unknown
+    /// or inactive
+    pub fn resolve_merchants(
+        env: Env,
+        ids: Vec<Bytes>,
+    ) -> Result<Vec<Option<MerchantMetadata>>, Error> {
+        if ids.len() > MAX_RESOLVE_BATCH {
+            return Err(Error::BatchTooLarge);
+        }
+
+        let mut resolved = Vec::new(&env);
+        for merchant_id in ids.iter() {
+            let metadata: Option<MerchantMetadata> = env
+                .storage()
+                .persistent()
+                .get(&DataKey::Merchant(merchant_id));
+            resolved.push_back(metadata.filter(|m| m.active));
+        }
+        Ok(resolved)
+    }
+
     /// Deactivate a merchant
     /// Access Control: Admin only
     pub fn deactivate_merchant(env: Env, merchant_id: Bytes) -> Result<(), Error> {
@@ -2,7 +2,7 @@
 
 use super::*;
 use soroban_sdk::testutils::{Address as _, Events};
-use soroban_sdk::{Bytes, Env, FromVal, IntoVal, Symbol};
+use soroban_sdk::{vec, Bytes, Env, FromVal, IntoVal, Symbol};
 
 #[test]
 fn test_user_registration() {
@@ -120,3 +120,276 @@ fn test_not_registered_resolutions() {
     let result_merch = client.try_resolve_merchant(&merchant_id);
     assert_eq!(result_merch, Err(Ok(Error::MerchantNotFound)));
 }
+
+#[test]
+fn test_valid_id_accepted() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let contract_id = env.register_contract(None, BLINKSRegistry);
+    let client = BLINKSRegistryClient::new(&env, &contract_id);
+
+    let admin = Address::generate(&env);
+    client.initialize(&admin);
+
+    let user = Address::generate(&env);
+    let user_id = Bytes::from_slice(&env, b"alice_01-x");
+    client.register_user(&user_id, &user);
+    assert_eq!(client.resolve_user(&user_id), user);
+
+    let max_id = Bytes::from_slice(&env, &[b'a'; MAX_ID_LEN as usize]);
+    client.register_merchant(&max_id, &Address::generate(&env), &Address::generate(&env));
+    assert!(client.resolve_merchant(&max_id).active);
+}
+
+#[test]
+fn test_empty_id_rejected() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let contract_id = env.register_contract(None, BLINKSRegistry);
+    let client = BLINKSRegistryClient::new(&env, &contract_id);
+
+    let admin = Address::generate(&env);
+    client.initialize(&admin);
+
+    let empty = Bytes::new(&env);
+    let result = client.try_register_user(&empty, &Address::generate(&env));
+    assert_eq!(result, Err(Ok(Error::InvalidId)));
+
+    let result = client.try_register_merchant(
+        &empty,
+        &Address::generate(&env),
+        &Address::generate(&env),
+    );
+    assert_eq!(result, Err(Ok(Error::InvalidId)));
+}
+
+#[test]
+fn test_over_length_id_rejected() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let contract_id = env.register_contract(None, BLINKSRegistry);
+    let client = BLINKSRegistryClient::new(&env, &contract_id);
+
+    let admin = Address::generate(&env);
+    client.initialize(&admin);
+
+    let long_id = Bytes::from_slice(&env, &[b'a'; MAX_ID_LEN as usize + 1]);
+    let result = client.try_register_user(&long_id, &Address::generate(&env));
+    assert_eq!(result, Err(Ok(Error::InvalidId)));
+
+    let result = client.try_register_merchant(
+        &long_id,
+        &Address::generate(&env),
+        &Address::generate(&env),
+    );
+    assert_eq!(result, Err(Ok(Error::InvalidId)));
+}
+
+#[test]
+fn test_illegal_bytes_rejected() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let contract_id = env.register_contract(None, BLINKSRegistry);
+    let client = BLINKSRegistryClient::new(&env, &contract_id);
+
+    let admin = Address::generate(&env);
+    client.initialize(&admin);
+
+    for bad in [&b"Alice"[..], b"bob smith", b"carol!", b"dave\x00"] {
+        let id = Bytes::from_slice(&env, bad);
+        let result = client.try_register_user(&id, &Address::generate(&env));
+        assert_eq!(result, Err(Ok(Error::InvalidId)));
+    }
+
+    let id = Bytes::from_slice(&env, b"Shop.Front");
+    let result =
+        client.try_register_merchant(&id, &Address::generate(&env), &Address::generate(&env));
+    assert_eq!(result, Err(Ok(Error::InvalidId)));
+}
+
+#[test]
+fn test_batch_merchant_registration() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let contract_id = env.register_contract(None, BLINKSRegistry);
+    let client = BLINKSRegistryClient::new(&env, &contract_id);
+
+    let admin = Address::generate(&env);
+    client.initialize(&admin);
+
+    let ids = vec![
+        &env,
+        Bytes::from_slice(&env, b"shop_a"),
+        Bytes::from_slice(&env, b"shop_b"),
+        Bytes::from_slice(&env, b"shop_c"),
+    ];
+    let vaults = vec![
+        &env,
+        Address::generate(&env),
+        Address::generate(&env),
+        Address::generate(&env),
+    ];
+    let asset = Address::generate(&env);
+    let assets = vec![&env, asset.clone(), asset.clone(), asset.clone()];
+
+    client.register_merchants(&ids, &vaults, &assets);
+
+    for i in 0..ids.len() {
+        let metadata = client.resolve_merchant(&ids.get(i).unwrap());
+        assert_eq!(metadata.vault, vaults.get(i).unwrap());
+        assert_eq!(metadata.settlement_asset, asset);
+        assert!(metadata.active);
+    }
+
+    // One merch_reg per merchant, as register_merchant emits, then the count
+    let events = env.events().all();
+    assert_eq!(events.len(), ids.len() + 1);
+    for i in 0..ids.len() {
+        let event = events.get(i).unwrap();
+        let topics = event.1.clone();
+        assert_eq!(
+            Symbol::from_val(&env, &topics.get(0).unwrap()),
+            symbol_short!("merch_reg")
+        );
+        assert_eq!(
+            Bytes::from_val(&env, &topics.get(1).unwrap()),
+            ids.get(i).unwrap()
+        );
+        let metadata: MerchantMetadata = FromVal::from_val(&env, &event.2);
+        assert_eq!(metadata.vault, vaults.get(i).unwrap());
+        assert_eq!(metadata.settlement_asset, asset);
+        assert!(metadata.active);
+    }
+
+    let last_event = events.last().unwrap();
+    let topics = last_event.1.clone();
+    assert_eq!(topics.len(), 1);
+    assert_eq!(
+        Symbol::from_val(&env, &topics.get(0).unwrap()),
+        symbol_short!("merch_bat")
+    );
+    let count: u32 = FromVal::from_val(&env, &last_event.2);
+    assert_eq!(count, 3);
+}
+
+#[test]
+fn test_batch_merchant_registration_aborts_on_duplicate() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let contract_id = env.register_contract(None, BLINKSRegistry);
+    let client = BLINKSRegistryClient::new(&env, &contract_id);
+
+    let admin = Address::generate(&env);
+    client.initialize(&admin);
+
+    let asset = Address::generate(&env);
+    let existing = Bytes::from_slice(&env, b"shop_b");
+    client.register_merchant(&existing, &Address::generate(&env), &asset);
+
+    let ids = vec![
+        &env,
+        Bytes::from_slice(&env, b"shop_a"),
+        existing,
+        Bytes::from_slice(&env, b"shop_c"),
+    ];
+    let vaults = vec![
+        &env,
+        Address::generate(&env),
+        Address::generate(&env),
+        Address::generate(&env),
+    ];
+    let assets = vec![&env, asset.clone(), asset.clone(), asset.clone()];
+
+    let result = client.try_register_merchants(&ids, &vaults, &assets);
+    assert_eq!(result, Err(Ok(Error::DuplicateId)));
+
+    // Neither the entry before nor after the duplicate was written
+    let result = client.try_resolve_merchant(&Bytes::from_slice(&env, b"shop_a"));
+    assert_eq!(result, Err(Ok(Error::MerchantNotFound)));
+    let result = client.try_resolve_merchant(&Bytes::from_slice(&env, b"shop_c"));
+    assert_eq!(result, Err(Ok(Error::MerchantNotFound)));
+
+    // Mismatched vector lengths are rejected outright
+    let short_assets = vec![&env, asset];
+    let result = client.try_register_merchants(&ids, &vaults, &short_assets);
+    assert_eq!(result, Err(Ok(Error::LengthMismatch)));
+}
+
+#[test]
+fn test_batch_resolution_is_positional() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let contract_id = env.register_contract(None, BLINKSRegistry);
+    let client = BLINKSRegistryClient::new(&env, &contract_id);
+
+    let admin = Address::generate(&env);
+    client.initialize(&admin);
+
+    let alice = Address::generate(&env);
+    let bob = Address::generate(&env);
+    client.register_user(&Bytes::from_slice(&env, b"alice"), &alice);
+    client.register_user(&Bytes::from_slice(&env, b"bob"), &bob);
+
+    let users = client.resolve_users(&vec![
+        &env,
+        Bytes::from_slice(&env, b"alice"),
+        Bytes::from_slice(&env, b"nobody"),
+        Bytes::from_slice(&env, b"bob"),
+    ]);
+    assert_eq!(users, vec![&env, Some(alice), None, Some(bob)]);
+
+    let vault = Address::generate(&env);
+    let asset = Address::generate(&env);
+    let shop = Bytes::from_slice(&env, b"shop");
+    let closed = Bytes::from_slice(&env, b"closed");
+    client.register_merchant(&shop, &vault, &asset);
+    client.register_merchant(&closed, &vault, &asset);
+    client.deactivate_merchant(&closed);
+
+    let merchants = client.resolve_merchants(&vec![
+        &env,
+        Bytes::from_slice(&env, b"missing"),
+        closed,
+        shop,
+    ]);
+    assert_eq!(merchants.len(), 3);
+    assert_eq!(merchants.get(0).unwrap(), None);
+    assert_eq!(merchants.get(1).unwrap(), None);
+    assert_eq!(
+        merchants.get(2).unwrap(),
+        Some(MerchantMetadata {
+            settlement_asset: asset,
+            vault,
+            active: true,
+        })
+    );
+}
+
+#[test]
+fn test_batch_resolution_is_bounded() {
+    let env = Env::default();
+
+    let contract_id = env.register_contract(None, BLINKSRegistry);
+    let client = BLINKSRegistryClient::new(&env, &contract_id);
+
+    let mut ids = Vec::new(&env);
+    for _ in 0..=MAX_RESOLVE_BATCH {
+        ids.push_back(Bytes::from_slice(&env, b"someone"));
+    }
+
+    assert_eq!(
+        client.try_resolve_users(&ids),
+        Err(Ok(Error::BatchTooLarge))
+    );
+    assert_eq!(
+        client.try_resolve_merchants(&ids),
+        Err(Ok(Error::BatchTooLarge))
+    );
+}